@@ -0,0 +1,55 @@
+//! Batch verification benchmark for the SP1 backend
+//!
+//! Compares verifying 100 proofs one at a time (one `setup` per proof) against
+//! `batch_verify`, which sets each distinct program up once and shares the key.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use frostgate_circuits::{Sp1Backend, ZkBackend, ZkBackendExt};
+use sha2::{Sha256, Digest};
+
+const BATCH_SIZE: usize = 100;
+
+fn message_program(message: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(message);
+    let mut program = vec![0x01];
+    program.extend_from_slice(&hasher.finalize());
+    program
+}
+
+fn bench_batch_verify(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let backend = Sp1Backend::new();
+
+    // Generate a single proof and verify it BATCH_SIZE times
+    let message = b"Hello, World!";
+    let program = message_program(message);
+    let (proof, _) = runtime
+        .block_on(backend.prove(&program, message, None))
+        .expect("Proof generation failed");
+    let verifications: Vec<(&[u8], &[u8])> = (0..BATCH_SIZE)
+        .map(|_| (program.as_slice(), proof.as_slice()))
+        .collect();
+
+    let mut group = c.benchmark_group("sp1_verify_100");
+    group.sample_size(10);
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                for (program, proof) in &verifications {
+                    backend.verify(program, proof, None).await.unwrap();
+                }
+            })
+        })
+    });
+
+    group.bench_function("batch", |b| {
+        b.iter(|| runtime.block_on(backend.batch_verify(&verifications, None)).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_batch_verify);
+criterion_main!(benches);
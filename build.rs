@@ -1,6 +1,3 @@
-#![allow(unused_imports)]
-#![allow(unused_variables)]
-
 use std::env;
 use std::path::PathBuf;
 use std::fs;
@@ -10,72 +7,198 @@ fn main() {
     println!("cargo:rerun-if-changed=circuits/message_verify.rs");
     println!("cargo:rerun-if-changed=circuits/tx_verify.rs");
     println!("cargo:rerun-if-changed=circuits/block_verify.rs");
+    println!("cargo:rerun-if-changed=circuits/message_aggregate.rs");
+    println!("cargo:rerun-if-changed=circuits/message_compose.rs");
+    println!("cargo:rerun-if-changed=circuits/eth_header_verify.rs");
+    println!("cargo:rerun-if-changed=circuits/ecdsa_verify.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/proof_aggregate.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/risc0_bridge.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/eth_verifier.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/eth_log_verifier.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/eth_header_chain.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/beacon_verifier.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/dot_verifier.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/sol_verifier.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/tm_verifier.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/state_proof.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/eip712_verifier.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/ecdsa_verifier.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/message_verifier.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/poseidon_commit.rs");
+    println!("cargo:rerun-if-changed=circuits/sp1/message_event.rs");
+    println!("cargo:rerun-if-changed=src/hasher.rs");
+    println!("cargo:rerun-if-changed=src/message");
+    println!("cargo:rerun-if-changed=circuits/sp1/merkle_inclusion.rs");
+    println!("cargo:rerun-if-changed=src/inclusion");
+    println!("cargo:rerun-if-changed=src/chains");
+    println!("cargo:rerun-if-changed=src/merkle.rs");
 
-    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
-    let circuits_dir = PathBuf::from("circuits");
-    
-    // Create programs directory if it doesn't exist
-    let programs_dir = PathBuf::from("programs");
-    fs::create_dir_all(&programs_dir).expect("Failed to create programs directory");
+    pin_sdk_versions();
+    compile_protos();
+    compile_schema();
+
+    // Guest programs are only needed by the provers
+    if env::var_os("CARGO_FEATURE_PROVE").is_none() {
+        return;
+    }
+
+    println!("cargo:rerun-if-env-changed=SKIP_GUEST_BUILD");
+    if env::var_os("SKIP_GUEST_BUILD").is_some() {
+        println!("cargo:warning=SKIP_GUEST_BUILD is set, using placeholder RISC0 ELF files and no SP1 guests");
+        warn_integration_tests_skipped();
+        create_placeholder_elf_files(&PathBuf::from("target/riscv"));
+        return;
+    }
+
+    build_risc0_guests();
+    build_sp1_guests();
+}
 
-    // Create target/riscv directory for RISC0 ELF files
+/// Guest programs of the RISC0 circuits
+const RISC0_GUESTS: [&str; 7] = [
+    "message_verify",
+    "tx_verify",
+    "block_verify",
+    "message_aggregate",
+    "message_compose",
+    "eth_header_verify",
+    "ecdsa_verify",
+];
+
+/// Build the RISC0 guests with `cargo risczero` and copy them to `target/riscv`
+fn build_risc0_guests() {
+    let circuits_dir = PathBuf::from("circuits");
     let target_riscv_dir = PathBuf::from("target/riscv");
     fs::create_dir_all(&target_riscv_dir).expect("Failed to create target/riscv directory");
 
-    // Try to build RISC0 circuits using cargo-risczero
-    if let Ok(status) = Command::new("cargo")
+    let status = Command::new("cargo")
         .args(&["risczero", "build", "--package", "frostgate-risc0-circuits"])
         .current_dir(&circuits_dir)
-        .status() {
-        if status.success() {
-            println!("cargo:warning=RISC0 circuits built successfully");
-            
-            // Copy ELF files to expected locations
-            let elf_files = ["message_verify", "tx_verify"];
-            for elf_name in &elf_files {
-                let source_path = circuits_dir.join("target/riscv32im-risc0-zkvm-elf/release").join(format!("{}.elf", elf_name));
-                let dest_path = target_riscv_dir.join(format!("{}.elf", elf_name));
-                
-                if source_path.exists() {
-                    fs::copy(&source_path, &dest_path)
-                        .unwrap_or_else(|_| {
-                            println!("cargo:warning=Failed to copy {} ELF file", elf_name);
-                            0
-                        });
-                } else {
-                    // Create placeholder ELF file for development
-                    fs::write(&dest_path, vec![0u8; 64])
-                        .unwrap_or_else(|_| println!("cargo:warning=Failed to create placeholder {} ELF file", elf_name));
-                }
-            }
-        } else {
-            println!("cargo:warning=RISC0 build failed, using placeholder ELF files");
-            create_placeholder_elf_files(&target_riscv_dir);
-        }
-    } else {
-        println!("cargo:warning=cargo-risczero not available, using placeholder ELF files");
-        create_placeholder_elf_files(&target_riscv_dir);
+        .status()
+        .unwrap_or_else(|e| guest_build_failed("RISC0", &format!("cargo risczero is not available: {}", e)));
+    if !status.success() {
+        guest_build_failed("RISC0", &format!("cargo risczero build exited with {}", status));
     }
 
-    // Compile circuits for each chain (SP1 format)
-    let chains = ["eth", "dot", "sol"];
-    for chain in chains.iter() {
-        let verifier_path = programs_dir.join(format!("{}_verifier.sp1", chain));
-        
-        // For development, create empty verifier files if they don't exist
-        if !verifier_path.exists() {
-            fs::write(&verifier_path, vec![0u8; 64]).expect("Failed to create verifier file");
+    for elf_name in &RISC0_GUESTS {
+        let source_path = circuits_dir.join("target/riscv32im-risc0-zkvm-elf/release").join(format!("{}.elf", elf_name));
+        let dest_path = target_riscv_dir.join(format!("{}.elf", elf_name));
+        if let Err(e) = fs::copy(&source_path, &dest_path) {
+            guest_build_failed("RISC0", &format!("failed to copy {}: {}", source_path.display(), e));
         }
     }
+}
+
+/// Build the SP1 guests with `sp1-build` into `target/sp1`
+#[cfg(feature = "prove")]
+fn build_sp1_guests() {
+    use sp1_build::{build_program_with_args, BuildArgs};
 
-    println!("cargo:warning=ELF files generated successfully");
+    let target_sp1_dir = PathBuf::from("target/sp1");
+    fs::create_dir_all(&target_sp1_dir).expect("Failed to create target/sp1 directory");
+
+    // sp1-build panics with the toolchain's output if the build fails
+    for elf_name in &["proof_aggregate", "risc0_bridge", "eth_verifier", "eth_log_verifier", "eth_header_chain", "beacon_verifier", "dot_verifier", "sol_verifier", "tm_verifier", "state_proof", "eip712_verifier", "ecdsa_verifier", "message_verifier", "poseidon_commit", "message_event", "merkle_inclusion"] {
+        build_program_with_args("circuits/sp1", BuildArgs {
+            binaries: vec![elf_name.to_string()],
+            elf_name: Some(format!("{}.elf", elf_name)),
+            output_directory: Some(target_sp1_dir.display().to_string()),
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(not(feature = "prove"))]
+fn build_sp1_guests() {}
+
+/// Abort the build of a crate that cannot prove without its guests
+fn guest_build_failed(toolchain: &str, reason: &str) -> ! {
+    panic!(
+        "Failed to build the {} guest programs: {}. Install the toolchain or set SKIP_GUEST_BUILD=1 to build without them.",
+        toolchain, reason
+    );
 }
 
 fn create_placeholder_elf_files(target_dir: &PathBuf) {
-    let elf_files = ["message_verify", "tx_verify"];
-    for elf_name in &elf_files {
+    fs::create_dir_all(target_dir).expect("Failed to create target/riscv directory");
+    for elf_name in &RISC0_GUESTS {
         let elf_path = target_dir.join(format!("{}.elf", elf_name));
         fs::write(&elf_path, vec![0u8; 64])
             .unwrap_or_else(|_| println!("cargo:warning=Failed to create placeholder {} ELF file", elf_name));
     }
-} 
\ No newline at end of file
+}
+
+/// Point out that integration tests cannot run against placeholder guests
+fn warn_integration_tests_skipped() {
+    if env::var_os("CARGO_FEATURE_INTEGRATION_TESTS").is_some() {
+        println!("cargo:warning=RISC0 integration tests will be skipped until the guests build");
+    }
+}
+
+/// Generate the gRPC service and client of the proving server
+#[cfg(feature = "grpc")]
+fn compile_protos() {
+    println!("cargo:rerun-if-changed=proto/prover.proto");
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("Failed to locate vendored protoc");
+    env::set_var("PROTOC", protoc);
+    tonic_build::configure()
+        .build_client(true)
+        .compile(&["proto/prover.proto"], &["proto"])
+        .expect("Failed to compile proto/prover.proto");
+}
+
+#[cfg(not(feature = "grpc"))]
+fn compile_protos() {}
+
+/// Generate the types of the proof wire format
+#[cfg(feature = "proto")]
+fn compile_schema() {
+    println!("cargo:rerun-if-changed=proto/frostgate.proto");
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("Failed to locate vendored protoc");
+    env::set_var("PROTOC", protoc);
+    prost_build::compile_protos(&["proto/frostgate.proto"], &["proto"])
+        .expect("Failed to compile proto/frostgate.proto");
+}
+
+#[cfg(not(feature = "proto"))]
+fn compile_schema() {}
+
+/// Expose the resolved prover SDK and guest toolchain versions to the crate
+fn pin_sdk_versions() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-env-changed=SP1_TOOLCHAIN_VERSION");
+    println!("cargo:rerun-if-env-changed=RISC0_TOOLCHAIN_VERSION");
+
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    let sdks = [
+        ("FROSTGATE_SP1_SDK_VERSION", "sp1-sdk", "5.0.0"),
+        ("FROSTGATE_RISC0_SDK_VERSION", "risc0-zkvm", "0.19.0"),
+    ];
+    for (var, package, fallback) in &sdks {
+        let version = locked_version(&lock, package).unwrap_or_else(|| fallback.to_string());
+        println!("cargo:rustc-env={}={}", var, version);
+    }
+
+    let toolchains = [
+        ("FROSTGATE_SP1_TOOLCHAIN", "SP1_TOOLCHAIN_VERSION"),
+        ("FROSTGATE_RISC0_TOOLCHAIN", "RISC0_TOOLCHAIN_VERSION"),
+    ];
+    for (var, source) in &toolchains {
+        let version = env::var(source).unwrap_or_else(|_| "unknown".to_string());
+        println!("cargo:rustc-env={}={}", var, version);
+    }
+}
+
+/// Find the version of a package in a Cargo.lock file
+fn locked_version(lock: &str, package: &str) -> Option<String> {
+    let name = format!("name = \"{}\"", package);
+    let mut lines = lock.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() == name {
+            return lines.next()
+                .and_then(|line| line.trim().strip_prefix("version = "))
+                .map(|version| version.trim_matches('"').to_string());
+        }
+    }
+    None
+}
@@ -8,6 +8,13 @@ use risc0_zkvm::guest::env;
 use sha2::{Sha256, Digest};
 use serde_json_core::from_slice;
 
+extern crate alloc;
+
+#[path = "../src/codec/mod.rs"]
+mod codec;
+#[path = "../src/domain.rs"]
+mod domain;
+
 risc0_zkvm::guest::entry!(main);
 
 #[derive(serde::Deserialize)]
@@ -25,7 +32,7 @@ struct BlockHeader<'a> {
 
 fn main() {
     // Read block header from private input
-    let header_bytes: Vec<u8> = env::read();the
+    let header_bytes: Vec<u8> = env::read();
     
     // Read expected hash and block number from public input
     let mut expected_hash = [0u8; 32];
@@ -36,6 +43,11 @@ fn main() {
     
     let expected_number = env::read::<u64>();
     
+    // Read the origin and destination domain the proof is bound to
+    let domain_bytes: [u8; domain::DomainSeparator::LEN] = env::read();
+    let domain = domain::DomainSeparator::from_bytes(&domain_bytes)
+        .expect("Invalid domain separator");
+    
     // Parse and validate block header
     let header: BlockHeader = from_slice(&header_bytes)
         .expect("Failed to parse block header JSON").0;
@@ -68,9 +80,11 @@ fn main() {
         .expect("Invalid gas limit");
     assert!(gas_used <= gas_limit, "Gas used exceeds limit");
     
-    // Compute block header hash
+    // Compute hash over the canonical encoding
+    let canonical = codec::json::canonicalize(&header_bytes)
+        .expect("Failed to canonicalize block header JSON");
     let mut hasher = Sha256::new();
-    hasher.update(&header_bytes);
+    hasher.update(&canonical);
     let computed_hash = hasher.finalize();
     
     // Verify hash matches expected
@@ -84,4 +98,7 @@ fn main() {
         gas_used.to_le_bytes(),
         gas_limit.to_le_bytes(),
     ].concat());
-} 
\ No newline at end of file
+    
+    // Bind the proof to its domain
+    env::commit_slice(&domain.to_bytes());
+}
@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+use risc0_zkvm::guest::env;
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+#[path = "../src/domain.rs"]
+mod domain;
+#[path = "../src/signatures/mod.rs"]
+mod signatures;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the signed message hash from public input
+    let mut message_hash = [0u8; 32];
+    for chunk in message_hash.chunks_mut(4) {
+        chunk.copy_from_slice(&env::read::<u32>().to_le_bytes());
+    }
+
+    // Read the SEC1 public key and the signature from private input
+    let public_key: Vec<u8> = env::read();
+    let signature: Vec<u8> = env::read();
+
+    // Read the origin and destination domain the proof is bound to
+    let domain_bytes: [u8; domain::DomainSeparator::LEN] = env::read();
+    let domain = domain::DomainSeparator::from_bytes(&domain_bytes)
+        .expect("Invalid domain separator");
+
+    let witness = signatures::ecdsa::EcdsaWitness { message_hash, public_key, signature };
+    let statement = signatures::ecdsa::verify(&witness)
+        .unwrap_or_else(|e| panic!("Signature does not verify: {}", e));
+
+    // Commit the message hash and the compressed public key
+    env::commit_slice(&statement.to_bytes());
+
+    // Bind the proof to its domain
+    env::commit_slice(&domain.to_bytes());
+}
@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+use risc0_zkvm::guest::env;
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+#[path = "../src/codec/mod.rs"]
+mod codec;
+#[path = "../src/domain.rs"]
+mod domain;
+#[path = "../src/chains/mod.rs"]
+mod chains;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the expected block hash and number from public input
+    let mut expected_hash = [0u8; 32];
+    for chunk in expected_hash.chunks_mut(4) {
+        chunk.copy_from_slice(&env::read::<u32>().to_le_bytes());
+    }
+    let expected_number = env::read::<u64>();
+
+    // Read the RLP-encoded header from private input
+    let header_bytes: Vec<u8> = env::read();
+
+    // Read the origin and destination domain the proof is bound to
+    let domain_bytes: [u8; domain::DomainSeparator::LEN] = env::read();
+    let domain = domain::DomainSeparator::from_bytes(&domain_bytes)
+        .expect("Invalid domain separator");
+
+    // Decode the header; its Keccak-256 hash is the block hash
+    let header = chains::eth::EthHeader::decode(&header_bytes)
+        .unwrap_or_else(|e| panic!("Invalid block header: {}", e));
+    assert_eq!(header.hash, expected_hash, "Block hash mismatch");
+    assert_eq!(header.number, expected_number, "Block number mismatch");
+
+    // Commit the header fields in the block verification journal layout
+    env::commit_slice(&header.hash);
+    env::commit_slice(&header.number.to_le_bytes());
+    env::commit_slice(&header.timestamp.to_le_bytes());
+    env::commit_slice(&header.gas_used.to_le_bytes());
+    env::commit_slice(&header.gas_limit.to_le_bytes());
+
+    // Bind the proof to its domain
+    env::commit_slice(&domain.to_bytes());
+}
@@ -0,0 +1,79 @@
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+
+#![no_std]
+#![no_main]
+
+use risc0_zkvm::guest::env;
+use sha2::{Sha256, Digest};
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+#[path = "../src/domain.rs"]
+mod domain;
+#[path = "../src/merkle.rs"]
+mod merkle;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the number of aggregated messages from public input
+    let count = env::read::<u32>() as usize;
+    assert!(count > 0, "Nothing to aggregate");
+
+    // Read the expected hash of every message from public input
+    let mut hashes = Vec::with_capacity(count);
+    for _ in 0..count {
+        let mut hash = [0u8; 32];
+        for i in 0..8 {
+            let word = env::read::<u32>();
+            hash[i*4..(i+1)*4].copy_from_slice(&word.to_le_bytes());
+        }
+        hashes.push(hash);
+    }
+
+    // Read the source chain and nonce of the first message from public input, if
+    // committed; the others follow consecutively
+    let nonce = read_nonce();
+
+    // Read every message from private input and verify its hash
+    let mut leaves = Vec::with_capacity(count);
+    for hash in &hashes {
+        let message: Vec<u8> = env::read();
+        let computed_hash = Sha256::digest(&message);
+        assert_eq!(computed_hash.as_slice(), hash);
+        leaves.push(merkle::leaf(hash));
+    }
+
+    // Read the origin and destination domain the proof is bound to
+    let domain_bytes: [u8; domain::DomainSeparator::LEN] = env::read();
+    let domain = domain::DomainSeparator::from_bytes(&domain_bytes)
+        .expect("Invalid domain separator");
+    if let Some(nonce) = &nonce {
+        assert!(nonce.matches(&domain), "Message nonce is from another chain than the domain's origin");
+    }
+
+    // Commit to the Merkle root of all messages, their number and first nonce
+    let root = merkle::root_in_place(&mut leaves);
+    env::commit_slice(&root);
+    env::commit_slice(&(count as u64).to_le_bytes());
+    if let Some(nonce) = nonce {
+        env::commit_slice(&nonce.to_bytes());
+    }
+
+    // Bind the proof to its domain
+    env::commit_slice(&domain.to_bytes());
+}
+
+/// Read a flag from public input, then the message nonce if the flag is set
+fn read_nonce() -> Option<domain::MessageNonce> {
+    if env::read::<u32>() == 0 {
+        return None;
+    }
+    let mut bytes = [0u8; domain::MessageNonce::LEN];
+    for chunk in bytes.chunks_mut(4) {
+        chunk.copy_from_slice(&env::read::<u32>().to_le_bytes());
+    }
+    Some(domain::MessageNonce::from_bytes(&bytes).expect("nonce length"))
+}
@@ -0,0 +1,79 @@
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+
+#![no_std]
+#![no_main]
+
+use risc0_zkvm::guest::env;
+use risc0_zkvm::sha::Digest;
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+#[path = "../src/domain.rs"]
+mod domain;
+#[path = "../src/merkle.rs"]
+mod merkle;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the image ID of the message verification guest from public input
+    let mut image_id = [0u32; 8];
+    for word in image_id.iter_mut() {
+        *word = env::read::<u32>();
+    }
+    let image_id = Digest::from(image_id);
+
+    // Read the number of composed receipts from public input
+    let count = env::read::<u32>() as usize;
+    assert!(count > 0, "Nothing to compose");
+
+    // Read the journal of every receipt from private input
+    let mut journals = Vec::with_capacity(count);
+    for _ in 0..count {
+        let journal: Vec<u8> = env::read();
+        journals.push(journal);
+    }
+
+    // Read the origin and destination domain the proof is bound to
+    let domain_bytes: [u8; domain::DomainSeparator::LEN] = env::read();
+    let domain = domain::DomainSeparator::from_bytes(&domain_bytes)
+        .expect("Invalid domain separator");
+
+    // Verify every receipt; the host supplies them as assumptions
+    let mut leaves = Vec::with_capacity(count);
+    let mut nonces = Vec::with_capacity(count);
+    for journal in &journals {
+        env::verify(image_id, journal).expect("Receipt does not verify");
+
+        // Every message must be bound to the composed proof's domain
+        assert!(journal.len() == 32 + domain::DomainSeparator::LEN
+            || journal.len() == 32 + domain::MessageNonce::LEN + domain::DomainSeparator::LEN);
+        let (message, bound) = journal.split_at(journal.len() - domain::DomainSeparator::LEN);
+        assert_eq!(bound, &domain.to_bytes());
+
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&message[..32]);
+        leaves.push(merkle::leaf(&hash));
+        nonces.push(domain::MessageNonce::from_bytes(&message[32..]));
+    }
+
+    // Messages carry consecutive nonces from one chain, or none at all
+    let first = nonces[0];
+    for (offset, nonce) in nonces.iter().enumerate() {
+        let expected = first.map(|first| domain::MessageNonce::new(first.source_chain, first.nonce.wrapping_add(offset as u64)));
+        assert_eq!(*nonce, expected, "Messages do not carry consecutive nonces");
+    }
+
+    // Commit to the Merkle root of all messages, their number and first nonce
+    let root = merkle::root_in_place(&mut leaves);
+    env::commit_slice(&root);
+    env::commit_slice(&(count as u64).to_le_bytes());
+    if let Some(nonce) = first {
+        env::commit_slice(&nonce.to_bytes());
+    }
+
+    // Bind the proof to its domain
+    env::commit_slice(&domain.to_bytes());
+}
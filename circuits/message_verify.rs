@@ -6,28 +6,84 @@
 
 use risc0_zkvm::guest::env;
 use sha2::{Sha256, Digest};
+use sha3::Keccak256;
+use blake2::{Blake2b, digest::consts::U32};
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+#[path = "../src/domain.rs"]
+mod domain;
 
 risc0_zkvm::guest::entry!(main);
 
+/// `HashFunction::message_hash_id` of SHA-256
+const SHA256: u32 = 0;
+
+/// `HashFunction::message_hash_id` of Keccak-256
+const KECCAK256: u32 = 1;
+
+/// `HashFunction::message_hash_id` of Blake2b-256
+const BLAKE2B: u32 = 2;
+
+/// `HashFunction::message_hash_id` of BLAKE3
+const BLAKE3: u32 = 3;
+
 fn main() {
-    // Read message from private input
-    let message: Vec<u8> = env::read();
-    
-    // Read expected hash from public input
+    // Read expected hash and the hash function from public input
     let mut expected_hash = [0u8; 32];
     for i in 0..8 {
         let word = env::read::<u32>();
         expected_hash[i*4..(i+1)*4].copy_from_slice(&word.to_le_bytes());
     }
-    
-    // Compute message hash
-    let mut hasher = Sha256::new();
-    hasher.update(&message);
-    let computed_hash = hasher.finalize();
-    
+    let hash_function = env::read::<u32>();
+
+    // Read the source chain and nonce of the message from public input, if committed
+    let nonce = read_nonce();
+
+    // Read message from private input
+    let message: Vec<u8> = env::read();
+
+    // Read the origin and destination domain the proof is bound to
+    let domain_bytes: [u8; domain::DomainSeparator::LEN] = env::read();
+    let domain = domain::DomainSeparator::from_bytes(&domain_bytes)
+        .expect("Invalid domain separator");
+    if let Some(nonce) = &nonce {
+        assert!(nonce.matches(&domain), "Message nonce is from another chain than the domain's origin");
+    }
+
+    // Compute message hash. SHA-256 runs on the accelerator, the other functions in
+    // software; Keccak-256 until the guests move to a risc0-zkvm with the Keccak
+    // accelerator.
+    let computed_hash: [u8; 32] = match hash_function {
+        SHA256 => Sha256::digest(&message).into(),
+        KECCAK256 => Keccak256::digest(&message).into(),
+        BLAKE2B => Blake2b::<U32>::digest(&message).into(),
+        BLAKE3 => *blake3::hash(&message).as_bytes(),
+        _ => panic!("Unknown message hash function {}", hash_function),
+    };
+
     // Verify hash matches expected
-    assert_eq!(computed_hash.as_slice(), &expected_hash);
-    
-    // Write hash to journal for verification
-    env::commit(&computed_hash);
-} 
\ No newline at end of file
+    assert_eq!(computed_hash, expected_hash);
+
+    // Write hash and nonce to journal for verification
+    env::commit_slice(&computed_hash);
+    if let Some(nonce) = nonce {
+        env::commit_slice(&nonce.to_bytes());
+    }
+
+    // Bind the proof to its domain
+    env::commit_slice(&domain.to_bytes());
+}
+
+/// Read a flag from public input, then the message nonce if the flag is set
+fn read_nonce() -> Option<domain::MessageNonce> {
+    if env::read::<u32>() == 0 {
+        return None;
+    }
+    let mut bytes = [0u8; domain::MessageNonce::LEN];
+    for chunk in bytes.chunks_mut(4) {
+        chunk.copy_from_slice(&env::read::<u32>().to_le_bytes());
+    }
+    Some(domain::MessageNonce::from_bytes(&bytes).expect("nonce length"))
+}
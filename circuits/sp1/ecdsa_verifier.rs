@@ -0,0 +1,26 @@
+#![no_main]
+
+extern crate alloc;
+
+#[path = "../../src/domain.rs"]
+mod domain;
+#[path = "../../src/signatures/mod.rs"]
+mod signatures;
+
+sp1_zkvm::entrypoint!(main);
+
+fn main() {
+    // Read the witness and the domain the proof is bound to
+    let input = sp1_zkvm::io::read_vec();
+    let domain = domain::DomainSeparator::from_bytes(&sp1_zkvm::io::read_vec())
+        .expect("Invalid domain separator");
+
+    // k256 is patched to run its curve arithmetic on the secp256k1 precompiles
+    let witness: signatures::ecdsa::EcdsaWitness = bincode::deserialize(&input).expect("Malformed witness");
+    let statement = signatures::ecdsa::verify(&witness)
+        .unwrap_or_else(|e| panic!("Signature does not verify: {}", e));
+
+    // Commit the statement and bind the proof to its domain
+    sp1_zkvm::io::commit_slice(&statement.to_bytes());
+    sp1_zkvm::io::commit_slice(&domain.to_bytes());
+}
@@ -0,0 +1,26 @@
+#![no_main]
+
+extern crate alloc;
+
+#[path = "../../src/domain.rs"]
+mod domain;
+#[path = "../../src/signatures/mod.rs"]
+mod signatures;
+
+sp1_zkvm::entrypoint!(main);
+
+fn main() {
+    // Read the witness and the domain the proof is bound to
+    let input = sp1_zkvm::io::read_vec();
+    let domain = domain::DomainSeparator::from_bytes(&sp1_zkvm::io::read_vec())
+        .expect("Invalid domain separator");
+
+    // Hash the typed data and recover its signer; the message stays private
+    let witness: signatures::eip712::Eip712Witness = bincode::deserialize(&input).expect("Malformed witness");
+    let statement = signatures::eip712::verify(&witness)
+        .unwrap_or_else(|e| panic!("Typed data signature does not verify: {}", e));
+
+    // Commit the statement and bind the proof to its domain
+    sp1_zkvm::io::commit_slice(&statement.to_bytes());
+    sp1_zkvm::io::commit_slice(&domain.to_bytes());
+}
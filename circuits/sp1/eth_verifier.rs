@@ -0,0 +1,27 @@
+#![no_main]
+
+extern crate alloc;
+
+#[path = "../../src/codec/mod.rs"]
+mod codec;
+#[path = "../../src/domain.rs"]
+mod domain;
+#[path = "../../src/chains/mod.rs"]
+mod chains;
+
+sp1_zkvm::entrypoint!(main);
+
+fn main() {
+    // Read the witness and the domain the proof is bound to
+    let input = sp1_zkvm::io::read_vec();
+    let domain = domain::DomainSeparator::from_bytes(&sp1_zkvm::io::read_vec())
+        .expect("Invalid domain separator");
+
+    let witness: chains::eth::EthWitness = bincode::deserialize(&input).expect("Malformed witness");
+    let statement = chains::eth::verify(&witness)
+        .unwrap_or_else(|e| panic!("Receipt inclusion does not verify: {}", e));
+
+    // Commit the statement and bind the proof to its domain
+    sp1_zkvm::io::commit_slice(&statement.to_bytes());
+    sp1_zkvm::io::commit_slice(&domain.to_bytes());
+}
@@ -0,0 +1,24 @@
+#![no_main]
+
+#[path = "../../src/domain.rs"]
+mod domain;
+#[path = "../../src/merkle.rs"]
+mod merkle;
+#[path = "../../src/message/mod.rs"]
+mod message;
+
+sp1_zkvm::entrypoint!(main);
+
+fn main() {
+    // Read the encoded event and the domain the proof is bound to
+    let input = sp1_zkvm::io::read_vec();
+    let domain = domain::DomainSeparator::from_bytes(&sp1_zkvm::io::read_vec())
+        .expect("Invalid domain separator");
+
+    let statement = message::verify(&input, &domain)
+        .unwrap_or_else(|e| panic!("Message event does not verify: {}", e));
+
+    // Commit the statement and bind the proof to its domain
+    sp1_zkvm::io::commit_slice(&statement.to_bytes());
+    sp1_zkvm::io::commit_slice(&domain.to_bytes());
+}
@@ -0,0 +1,64 @@
+#![no_main]
+
+use blake2::{Blake2b, digest::consts::U32};
+use sha2::{Digest, Sha256};
+use tiny_keccak::{Hasher, Keccak};
+
+#[path = "../../src/domain.rs"]
+mod domain;
+
+sp1_zkvm::entrypoint!(main);
+
+/// `HashFunction::message_hash_id` of SHA-256
+const SHA256: u8 = 0;
+
+/// `HashFunction::message_hash_id` of Keccak-256
+const KECCAK256: u8 = 1;
+
+/// `HashFunction::message_hash_id` of Blake2b-256
+const BLAKE2B: u8 = 2;
+
+/// `HashFunction::message_hash_id` of BLAKE3
+const BLAKE3: u8 = 3;
+
+/// Flag on the hash function of input carrying a message nonce
+const NONCE_FLAG: u8 = 0x80;
+
+fn main() {
+    // Read the hash function, the message nonce if flagged and the message, and the
+    // domain the proof is bound to
+    let input = sp1_zkvm::io::read_vec();
+    let domain = domain::DomainSeparator::from_bytes(&sp1_zkvm::io::read_vec())
+        .expect("Invalid domain separator");
+    let (&hash_function, message) = input.split_first().expect("Missing message hash function");
+    let (hash_function, nonce, message) = if hash_function & NONCE_FLAG != 0 {
+        let (nonce, message) = message.split_at(domain::MessageNonce::LEN);
+        let nonce = domain::MessageNonce::from_bytes(nonce).expect("Invalid message nonce");
+        assert!(nonce.matches(&domain), "Message nonce is from another chain than the domain's origin");
+        (hash_function & !NONCE_FLAG, Some(nonce), message)
+    } else {
+        (hash_function, None, message)
+    };
+
+    // Keccak-256 runs on the keccak-f precompile through SP1's patched tiny-keccak
+    let hash: [u8; 32] = match hash_function {
+        SHA256 => Sha256::digest(message).into(),
+        KECCAK256 => {
+            let mut hash = [0u8; 32];
+            let mut keccak = Keccak::v256();
+            keccak.update(message);
+            keccak.finalize(&mut hash);
+            hash
+        }
+        BLAKE2B => Blake2b::<U32>::digest(message).into(),
+        BLAKE3 => *blake3::hash(message).as_bytes(),
+        _ => panic!("Unknown message hash function {}", hash_function),
+    };
+
+    // Commit the message hash and nonce, and bind the proof to its domain
+    sp1_zkvm::io::commit_slice(&hash);
+    if let Some(nonce) = nonce {
+        sp1_zkvm::io::commit_slice(&nonce.to_bytes());
+    }
+    sp1_zkvm::io::commit_slice(&domain.to_bytes());
+}
@@ -0,0 +1,22 @@
+#![no_main]
+
+#[path = "../../src/domain.rs"]
+mod domain;
+#[path = "../../src/hasher.rs"]
+mod hasher;
+
+sp1_zkvm::entrypoint!(main);
+
+fn main() {
+    // Read the message and the domain the proof is bound to
+    let message = sp1_zkvm::io::read_vec();
+    let domain = domain::DomainSeparator::from_bytes(&sp1_zkvm::io::read_vec())
+        .expect("Invalid domain separator");
+
+    // The host computes commitments with the very same sponge
+    let commitment = hasher::HashFunction::Poseidon.hash(&message);
+
+    // Commit the commitment and bind the proof to its domain
+    sp1_zkvm::io::commit_slice(&commitment);
+    sp1_zkvm::io::commit_slice(&domain.to_bytes());
+}
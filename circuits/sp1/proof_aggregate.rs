@@ -0,0 +1,32 @@
+#![no_main]
+
+use sp1_zkvm::lib::verify::verify_sp1_proof;
+use sha2::{Sha256, Digest};
+
+#[path = "../../src/domain.rs"]
+mod domain;
+#[path = "../../src/merkle.rs"]
+mod merkle;
+
+sp1_zkvm::entrypoint!(main);
+
+fn main() {
+    // Read the verifying key digest and public values of every proof
+    let vkeys: Vec<[u32; 8]> = sp1_zkvm::io::read();
+    let public_values: Vec<Vec<u8>> = sp1_zkvm::io::read();
+    assert_eq!(vkeys.len(), public_values.len());
+    assert!(!vkeys.is_empty(), "Nothing to aggregate");
+
+    // Verify every proof; the proofs themselves are supplied by the host
+    let mut leaves = Vec::with_capacity(vkeys.len());
+    for (vkey, values) in vkeys.iter().zip(&public_values) {
+        let digest: [u8; 32] = Sha256::digest(values).into();
+        verify_sp1_proof(vkey, &digest);
+        leaves.push(merkle::leaf(&merkle::proof_hash(vkey, values)));
+    }
+
+    // Commit to the Merkle root of all proofs and their number
+    let root = merkle::root_in_place(&mut leaves);
+    sp1_zkvm::io::commit_slice(&root);
+    sp1_zkvm::io::commit_slice(&(vkeys.len() as u64).to_le_bytes());
+}
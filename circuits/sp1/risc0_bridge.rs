@@ -0,0 +1,21 @@
+#![no_main]
+
+use risc0_zkvm::{InnerReceipt, Receipt};
+use risc0_zkvm::sha::Digest;
+
+sp1_zkvm::entrypoint!(main);
+
+fn main() {
+    // Read the image ID the receipt must prove and the receipt itself
+    let image_id: [u32; 8] = sp1_zkvm::io::read();
+    let receipt: Receipt = sp1_zkvm::io::read();
+
+    // Only succinct receipts are small enough to verify here
+    assert!(matches!(receipt.inner, InnerReceipt::Succinct(_)), "Receipt is not succinct");
+    let image_id = Digest::from(image_id);
+    receipt.verify(image_id).expect("Receipt does not verify");
+
+    // Commit the statement the receipt proved
+    sp1_zkvm::io::commit_slice(image_id.as_bytes());
+    sp1_zkvm::io::commit_slice(&receipt.journal.bytes);
+}
@@ -8,6 +8,13 @@ use risc0_zkvm::guest::env;
 use sha2::{Sha256, Digest};
 use serde_json_core::from_slice;
 
+extern crate alloc;
+
+#[path = "../src/codec/mod.rs"]
+mod codec;
+#[path = "../src/domain.rs"]
+mod domain;
+
 risc0_zkvm::guest::entry!(main);
 
 #[derive(serde::Deserialize)]
@@ -28,6 +35,11 @@ fn main() {
         expected_hash[i*4..(i+1)*4].copy_from_slice(&word.to_le_bytes());
     }
     
+    // Read the origin and destination domain the proof is bound to
+    let domain_bytes: [u8; domain::DomainSeparator::LEN] = env::read();
+    let domain = domain::DomainSeparator::from_bytes(&domain_bytes)
+        .expect("Invalid domain separator");
+    
     // Parse and validate transaction
     let tx: Transaction = from_slice(&tx_bytes)
         .expect("Failed to parse transaction JSON").0;
@@ -37,9 +49,11 @@ fn main() {
     assert!(tx.to.starts_with("0x"), "Invalid to address");
     assert!(tx.value.parse::<u64>().is_ok(), "Invalid value");
     
-    // Compute transaction hash
+    // Compute hash over the canonical encoding
+    let canonical = codec::json::canonicalize(&tx_bytes)
+        .expect("Failed to canonicalize transaction JSON");
     let mut hasher = Sha256::new();
-    hasher.update(&tx_bytes);
+    hasher.update(&canonical);
     let computed_hash = hasher.finalize();
     
     // Verify hash matches expected
@@ -52,4 +66,7 @@ fn main() {
         tx.to.len() as u8,
         tx.value.len() as u8,
     ]);
-} 
\ No newline at end of file
+    
+    // Bind the proof to its domain
+    env::commit_slice(&domain.to_bytes());
+}
@@ -0,0 +1,202 @@
+//! Aggregation of many messages into a single proof
+//!
+//! Bridges that post proofs on-chain pay verification gas per proof. The aggregation
+//! circuit (`circuits/message_aggregate.rs`) takes k messages together with their
+//! expected hashes, verifies every hash and commits to the [`merkle`] root over them,
+//! so one on-chain verification covers all k messages. Each message is then shown to
+//! be part of the aggregate with an [`InclusionProof`] against the committed root.
+//!
+//! [`merkle`]: crate::merkle
+
+use std::sync::Arc;
+use sha2::{Digest, Sha256};
+use frostgate_zkip::{ProofMetadata, ZkBackend};
+
+use crate::cluster::encode_shard;
+use crate::descriptor::ProgramDescriptor;
+use crate::error::ZkError;
+use crate::merkle;
+use crate::profiles::MESSAGE_AGGREGATE;
+
+/// Default maximum number of messages per aggregate proof
+pub const DEFAULT_MAX_MESSAGES: usize = 256;
+
+/// Messages proven together by the aggregation circuit
+#[derive(Debug, Clone)]
+pub struct AggregateStatement {
+    /// Aggregated messages, in leaf order
+    pub messages: Vec<Vec<u8>>,
+}
+
+impl AggregateStatement {
+    /// Create a statement over `messages`
+    pub fn new(messages: Vec<Vec<u8>>) -> Result<Self, ZkError> {
+        if messages.is_empty() {
+            return Err(ZkError::InvalidInput("No messages to aggregate".into()));
+        }
+        Ok(Self { messages })
+    }
+
+    /// SHA-256 of every message
+    pub fn hashes(&self) -> Vec<[u8; 32]> {
+        self.messages.iter().map(|message| Sha256::digest(message).into()).collect()
+    }
+
+    /// Merkle root the aggregate proof commits to
+    pub fn root(&self) -> [u8; 32] {
+        let mut leaves: Vec<_> = self.hashes().iter().map(merkle::leaf).collect();
+        merkle::root_in_place(&mut leaves)
+    }
+
+    /// Program of the aggregation circuit, committing to the root
+    pub fn program(&self) -> Vec<u8> {
+        ProgramDescriptor::new(MESSAGE_AGGREGATE, self.root()).encode()
+    }
+
+    /// Guest input, encoded like a cluster shard
+    pub fn input(&self) -> Vec<u8> {
+        encode_shard(&self.messages)
+    }
+
+    /// Proof that message `index` is part of the aggregate
+    pub fn inclusion_proof(&self, index: usize) -> Option<InclusionProof> {
+        let hashes = self.hashes();
+        let hash = *hashes.get(index)?;
+        let mut level: Vec<_> = hashes.iter().map(merkle::leaf).collect();
+        let mut path = Vec::new();
+        let mut position = index;
+        while level.len() > 1 {
+            if let Some(sibling) = level.get(position ^ 1) {
+                path.push(*sibling);
+            }
+            level = level.chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => merkle::node(left, right),
+                    [single] => *single,
+                    _ => unreachable!("chunks of two"),
+                })
+                .collect();
+            position /= 2;
+        }
+        Some(InclusionProof {
+            index,
+            count: self.messages.len(),
+            hash,
+            path,
+        })
+    }
+}
+
+/// Merkle path from one message to the aggregate root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    /// Position of the message in the aggregate
+    pub index: usize,
+    /// Number of messages in the aggregate
+    pub count: usize,
+    /// SHA-256 of the message
+    pub hash: [u8; 32],
+    /// Siblings on the path from the leaf up to the root
+    pub path: Vec<[u8; 32]>,
+}
+
+impl InclusionProof {
+    /// Check the path leads to `root`
+    pub fn verify(&self, root: &[u8; 32]) -> bool {
+        merkle::verify_path(&merkle::leaf(&self.hash), self.index, self.count, &self.path, root)
+    }
+}
+
+/// Proof covering every message of an aggregate
+#[derive(Debug, Clone)]
+pub struct AggregateProof {
+    /// Merkle root committed by the proof
+    pub root: [u8; 32],
+    /// Number of aggregated messages
+    pub count: usize,
+    /// Serialized proof
+    pub proof: Vec<u8>,
+    /// Proof metadata
+    pub metadata: ProofMetadata,
+}
+
+/// Proves batches of messages with the aggregation circuit
+#[derive(Debug)]
+pub struct Aggregator<B> {
+    backend: Arc<B>,
+    max_messages: usize,
+}
+
+impl<B: ZkBackend + 'static> Aggregator<B> {
+    /// Create an aggregator proving on `backend`
+    pub fn new(backend: Arc<B>) -> Self {
+        Self {
+            backend,
+            max_messages: DEFAULT_MAX_MESSAGES,
+        }
+    }
+
+    /// Set the maximum number of messages per aggregate proof
+    pub fn with_max_messages(mut self, max_messages: usize) -> Self {
+        self.max_messages = max_messages.max(1);
+        self
+    }
+
+    /// Prove that every message of `statement` hashes as committed
+    pub async fn prove(&self, statement: &AggregateStatement) -> Result<AggregateProof, ZkError> {
+        if statement.messages.len() > self.max_messages {
+            return Err(ZkError::InvalidInput(format!(
+                "Aggregate of {} messages exceeds the limit of {}",
+                statement.messages.len(),
+                self.max_messages
+            )));
+        }
+
+        let root = statement.root();
+        let program = ProgramDescriptor::new(MESSAGE_AGGREGATE, root).encode();
+        let (proof, metadata) = self.backend.prove(&program, &statement.input(), None)
+            .await
+            .map_err(ZkError::from)?;
+        Ok(AggregateProof {
+            root,
+            count: statement.messages.len(),
+            proof,
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inclusion_proofs() {
+        for count in [1, 2, 5, 8] {
+            let messages = (0..count).map(|i| vec![i as u8; 3]).collect();
+            let statement = AggregateStatement::new(messages).unwrap();
+            let root = statement.root();
+            for index in 0..count {
+                let proof = statement.inclusion_proof(index).unwrap();
+                assert!(proof.verify(&root), "message {} of {}", index, count);
+
+                let wrong = InclusionProof { index: (index + 1) % count, ..proof.clone() };
+                assert_eq!(wrong.verify(&root), count == 1);
+            }
+            assert!(statement.inclusion_proof(count).is_none());
+        }
+    }
+
+    #[test]
+    fn test_program_commits_to_root() {
+        let statement = AggregateStatement::new(vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]).unwrap();
+        let program = ProgramDescriptor::decode(&statement.program()).unwrap();
+        assert_eq!(program.circuit_type, MESSAGE_AGGREGATE);
+        assert_eq!(program.public_params, statement.root());
+
+        // The root depends on message order
+        let swapped = AggregateStatement::new(vec![b"b".to_vec(), b"a".to_vec(), b"c".to_vec()]).unwrap();
+        assert_ne!(swapped.root(), statement.root());
+        assert!(AggregateStatement::new(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,475 @@
+//! HTTP/JSON proving API
+//!
+//! Components that cannot link the prover SDKs request proofs over HTTP. The
+//! [`router`] serves any [`ZkBackend`] with these endpoints:
+//!
+//! - `POST /prove`: queue a proof on the [`JobManager`] and return its job ID,
+//!   optionally posting the result to a `callback_url` with the `webhooks` feature
+//! - `GET /jobs/:id`: status, timing and, once complete, the proof
+//! - `POST /verify`: verify a proof
+//! - `GET /health`: backend health, `503` unless healthy
+//! - `GET /metrics`: request counters and resource usage in the Prometheus text format
+//!
+//! Programs, inputs and proofs are hex-encoded in request and response bodies.
+//!
+//! With a signing key set, every endpoint except `/health` and `/metrics` requires
+//! the signature headers of [`crate::signing`] made with the key the gRPC service
+//! uses, signing the request method and path (e.g. `POST /prove`) over the raw
+//! body. Bodies are limited to [`DEFAULT_MAX_BODY_SIZE`] unless configured
+//! otherwise, and callbacks are only posted to public `http(s)` hosts or to the
+//! configured allowlist.
+
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use axum::body::{to_bytes, Body};
+use axum::extract::{DefaultBodyLimit, Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Serialize, Deserialize};
+use frostgate_zkip::{HealthStatus, ZkBackend};
+
+use crate::error::ZkError;
+use crate::jobs::{JobId, JobManager, JobReport, JobStatus};
+
+/// Default limit on request body size in bytes
+pub const DEFAULT_MAX_BODY_SIZE: usize = 32 * 1024 * 1024;
+
+/// Body of `POST /prove`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProveRequest {
+    /// Hex-encoded program
+    pub program: String,
+    /// Hex-encoded program input
+    pub input: String,
+    /// URL notified with a [`crate::jobs::JobNotification`] when the job finishes
+    #[serde(default)]
+    pub callback_url: Option<String>,
+}
+
+/// Response to `POST /prove`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProveResponse {
+    /// Job to poll for the proof
+    pub job_id: JobId,
+}
+
+/// Body of `POST /verify`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyRequest {
+    /// Hex-encoded program
+    pub program: String,
+    /// Hex-encoded proof
+    pub proof: String,
+}
+
+/// Response to `POST /verify`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyResponse {
+    /// Whether the proof is valid
+    pub valid: bool,
+}
+
+/// Response to `GET /jobs/:id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobResponse {
+    /// Job identifier
+    pub job_id: JobId,
+    /// One of `queued`, `running`, `completed` or `failed`
+    pub status: String,
+    /// Submission time in seconds since the Unix epoch
+    pub submitted: u64,
+    /// Time spent waiting for a proving slot in milliseconds
+    pub queue_time_ms: u64,
+    /// Time spent proving in milliseconds, once started
+    pub proving_time_ms: Option<u64>,
+    /// Hex-encoded proof, once completed
+    pub proof: Option<String>,
+    /// Hex-encoded program hash, once completed
+    pub program_hash: Option<String>,
+    /// Error message, if failed
+    pub error: Option<String>,
+}
+
+impl JobResponse {
+    fn new(job_id: JobId, report: &JobReport) -> Self {
+        let (status, proof, program_hash, error) = match &report.status {
+            JobStatus::Queued => ("queued", None, None, None),
+            JobStatus::Running => ("running", None, None, None),
+            JobStatus::Completed { proof, metadata } => {
+                ("completed", Some(hex::encode(proof)), Some(metadata.program_hash.clone()), None)
+            }
+            JobStatus::Failed(error) => ("failed", None, None, Some(error.clone())),
+        };
+        Self {
+            job_id,
+            status: status.to_string(),
+            submitted: report.submitted.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            queue_time_ms: report.queue_time().as_millis() as u64,
+            proving_time_ms: report.proving_time().map(|time| time.as_millis() as u64),
+            proof,
+            program_hash,
+            error,
+        }
+    }
+}
+
+/// Response to `GET /health`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthResponse {
+    /// Whether the backend reports itself healthy
+    pub healthy: bool,
+    /// Reported health status
+    pub status: String,
+}
+
+/// Shared state of the API
+#[derive(Clone)]
+pub struct ApiState {
+    backend: Arc<dyn ZkBackend>,
+    jobs: Arc<JobManager>,
+    metrics: Arc<Metrics>,
+    signing_key: Option<Arc<[u8]>>,
+    max_body_size: usize,
+    callback_hosts: Arc<[String]>,
+}
+
+impl ApiState {
+    /// Serve `backend`, proving at most `max_concurrent` jobs at once
+    pub fn new(backend: Arc<dyn ZkBackend>, max_concurrent: usize) -> Self {
+        Self {
+            jobs: Arc::new(JobManager::new(backend.clone(), max_concurrent)),
+            backend,
+            metrics: Arc::new(Metrics::default()),
+            signing_key: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            callback_hosts: Vec::new().into(),
+        }
+    }
+
+    /// Require requests to be signed with `key`
+    pub fn with_signing_key(mut self, key: impl Into<Vec<u8>>) -> Self {
+        let key: Vec<u8> = key.into();
+        self.signing_key = Some(key.into());
+        self
+    }
+
+    /// Set the largest accepted request body in bytes
+    pub fn with_max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Only post callbacks to these hosts
+    ///
+    /// Callbacks never go to loopback, private or link-local addresses, whether or
+    /// not the host is allowlisted.
+    pub fn with_callback_hosts(mut self, hosts: impl IntoIterator<Item = String>) -> Self {
+        self.callback_hosts = hosts.into_iter().collect();
+        self
+    }
+}
+
+/// Request counters exported on `/metrics`
+#[derive(Debug, Default)]
+struct Metrics {
+    prove_requests: AtomicU64,
+    verify_requests: AtomicU64,
+    rejected_requests: AtomicU64,
+}
+
+/// Router serving the API
+pub fn router(state: ApiState) -> Router {
+    let signed = Router::new()
+        .route("/prove", post(prove))
+        .route("/verify", post(verify))
+        .route("/jobs/:id", get(job))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authenticate));
+    Router::new()
+        .merge(signed)
+        .route("/health", get(health))
+        .route("/metrics", get(metrics))
+        .layer(DefaultBodyLimit::max(state.max_body_size))
+        .with_state(state)
+}
+
+/// Serve the API on `addr` until the server fails
+pub async fn serve(addr: SocketAddr, state: ApiState) -> Result<(), ZkError> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+/// Error response carrying a JSON `{"error": ...}` body
+#[derive(Debug)]
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<ZkError> for ApiError {
+    fn from(error: ZkError) -> Self {
+        let status = match error {
+            ZkError::InvalidInput(_)
+            | ZkError::InvalidProgram(_)
+            | ZkError::UnsupportedProgramVersion { .. }
+            | ZkError::UnsupportedEnvelopeVersion { .. } => StatusCode::BAD_REQUEST,
+            ZkError::UnknownProgram(_) => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError(status, error.to_string())
+    }
+}
+
+/// Check the request signature if signing is required
+async fn authenticate(State(state): State<ApiState>, request: Request, next: Next) -> Result<Response, ApiError> {
+    let Some(key) = state.signing_key.clone() else {
+        return Ok(next.run(request).await);
+    };
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, state.max_body_size)
+        .await
+        .map_err(|_| ApiError(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large".to_string()))?;
+    let method = format!("{} {}", parts.method, parts.uri.path());
+    crate::signing::check(&key, &method, &body, |name| {
+        parts.headers.get(name).and_then(|value| value.to_str().ok())
+    })
+    .map_err(|reason| {
+        state.metrics.rejected_requests.fetch_add(1, Ordering::Relaxed);
+        ApiError(StatusCode::UNAUTHORIZED, reason)
+    })?;
+    Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+}
+
+async fn prove(State(state): State<ApiState>, Json(request): Json<ProveRequest>) -> Result<impl IntoResponse, ApiError> {
+    state.metrics.prove_requests.fetch_add(1, Ordering::Relaxed);
+    let program = decode(&state, "program", &request.program)?;
+    let input = decode(&state, "input", &request.input)?;
+    let job_id = match request.callback_url {
+        #[cfg(feature = "webhooks")]
+        Some(url) => {
+            let url = callback_url(&state, &url)?;
+            state.jobs.submit_prove_with_callback(program, input, crate::jobs::JobCallback::Webhook(url))
+        }
+        #[cfg(not(feature = "webhooks"))]
+        Some(_) => {
+            state.metrics.rejected_requests.fetch_add(1, Ordering::Relaxed);
+            return Err(ApiError(StatusCode::BAD_REQUEST, "Callbacks require the webhooks feature".to_string()));
+        }
+        None => state.jobs.submit_prove(program, input),
+    };
+    Ok((StatusCode::ACCEPTED, Json(ProveResponse { job_id })))
+}
+
+async fn verify(State(state): State<ApiState>, Json(request): Json<VerifyRequest>) -> Result<Json<VerifyResponse>, ApiError> {
+    state.metrics.verify_requests.fetch_add(1, Ordering::Relaxed);
+    let program = decode(&state, "program", &request.program)?;
+    let proof = decode(&state, "proof", &request.proof)?;
+    let valid = state.backend.verify(&program, &proof, None)
+        .await
+        .map_err(ZkError::from)?;
+    Ok(Json(VerifyResponse { valid }))
+}
+
+async fn job(State(state): State<ApiState>, Path(id): Path<JobId>) -> Result<Json<JobResponse>, ApiError> {
+    let report = state.jobs.job_status(id)
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("Unknown job: {}", id)))?;
+    Ok(Json(JobResponse::new(id, &report)))
+}
+
+async fn health(State(state): State<ApiState>) -> impl IntoResponse {
+    let health = state.backend.health_check().await;
+    let healthy = matches!(health, HealthStatus::Healthy);
+    let status = if healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(HealthResponse { healthy, status: format!("{:?}", health) }))
+}
+
+async fn metrics(State(state): State<ApiState>) -> impl IntoResponse {
+    let usage = state.backend.resource_usage();
+    let counters = [
+        ("frostgate_prove_requests_total", "counter", state.metrics.prove_requests.load(Ordering::Relaxed) as f64),
+        ("frostgate_verify_requests_total", "counter", state.metrics.verify_requests.load(Ordering::Relaxed) as f64),
+        ("frostgate_rejected_requests_total", "counter", state.metrics.rejected_requests.load(Ordering::Relaxed) as f64),
+        ("frostgate_pending_jobs", "gauge", state.jobs.pending() as f64),
+        ("frostgate_active_tasks", "gauge", usage.active_tasks as f64),
+        ("frostgate_queue_depth", "gauge", usage.queue_depth as f64),
+        ("frostgate_cpu_usage", "gauge", usage.cpu_usage as f64),
+        ("frostgate_memory_usage_bytes", "gauge", usage.memory_usage as f64),
+    ];
+
+    let mut body = String::new();
+    for (name, kind, value) in counters {
+        let _ = writeln!(body, "# TYPE {} {}", name, kind);
+        let _ = writeln!(body, "{} {}", name, value);
+    }
+    let _ = writeln!(body, "# TYPE frostgate_scrape_timestamp_seconds gauge");
+    let _ = writeln!(
+        body,
+        "frostgate_scrape_timestamp_seconds {}",
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+    );
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+/// Check that `url` may receive job notifications
+#[cfg(feature = "webhooks")]
+fn callback_url(state: &ApiState, url: &str) -> Result<String, ApiError> {
+    let reject = |reason: &str| {
+        state.metrics.rejected_requests.fetch_add(1, Ordering::Relaxed);
+        ApiError(StatusCode::BAD_REQUEST, format!("Invalid callback URL: {}", reason))
+    };
+    let parsed = reqwest::Url::parse(url).map_err(|e| reject(&e.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(reject("scheme must be http or https"));
+    }
+    let host = parsed.host_str().ok_or_else(|| reject("missing host"))?;
+    let allowed = public_host(host)
+        && (state.callback_hosts.is_empty()
+            || state.callback_hosts.iter().any(|allowed| allowed.eq_ignore_ascii_case(host)));
+    if !allowed {
+        return Err(reject("host is not allowed"));
+    }
+    Ok(parsed.into())
+}
+
+/// Whether `host` names neither the local machine nor a private network
+///
+/// This only rejects obviously internal callbacks early; the addresses a name
+/// resolves to are checked again when the webhook is sent.
+#[cfg(feature = "webhooks")]
+fn public_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match host.parse() {
+        Ok(ip) => crate::jobs::public_address(ip),
+        Err(_) => {
+            let host = host.trim_end_matches('.').to_ascii_lowercase();
+            host != "localhost" && !host.ends_with(".localhost") && !host.ends_with(".internal")
+        }
+    }
+}
+
+fn decode(state: &ApiState, field: &str, value: &str) -> Result<Vec<u8>, ApiError> {
+    hex::decode(value.trim_start_matches("0x")).map_err(|e| {
+        state.metrics.rejected_requests.fetch_add(1, Ordering::Relaxed);
+        ApiError(StatusCode::BAD_REQUEST, format!("Invalid hex in {}: {}", field, e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use axum::http::Request;
+    use tower::ServiceExt;
+    use crate::backends::mock::MockBackend;
+
+    async fn call(router: &Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    fn post(uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::post(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_prove_and_poll() {
+        let router = router(ApiState::new(Arc::new(MockBackend::new()), 2));
+
+        let (status, body) = call(&router, post("/prove", serde_json::json!({ "program": "01", "input": "02" }))).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let job_id = body["job_id"].as_str().unwrap().to_string();
+
+        let proof = loop {
+            let request = Request::get(format!("/jobs/{}", job_id)).body(Body::empty()).unwrap();
+            let (status, body) = call(&router, request).await;
+            assert_eq!(status, StatusCode::OK);
+            if body["status"] == "completed" {
+                break body["proof"].as_str().unwrap().to_string();
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        };
+
+        let (status, body) = call(&router, post("/verify", serde_json::json!({ "program": "01", "proof": proof }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["valid"], true);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bad_requests() {
+        let router = router(ApiState::new(Arc::new(MockBackend::new().with_failure("down")), 1));
+
+        let (status, _) = call(&router, post("/prove", serde_json::json!({ "program": "zz", "input": "" }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let request = Request::get(format!("/jobs/{}", uuid::Uuid::new_v4())).body(Body::empty()).unwrap();
+        assert_eq!(call(&router, request).await.0, StatusCode::NOT_FOUND);
+
+        let request = Request::get("/health").body(Body::empty()).unwrap();
+        let (status, body) = call(&router, request).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["healthy"], false);
+    }
+
+    #[tokio::test]
+    async fn test_signed_requests() {
+        let router = router(ApiState::new(Arc::new(MockBackend::new()), 1).with_signing_key(b"secret".to_vec()));
+        let body = serde_json::json!({ "program": "01", "proof": "02" });
+
+        assert_eq!(call(&router, post("/verify", body.clone())).await.0, StatusCode::UNAUTHORIZED);
+
+        let (signature, timestamp) = crate::signing::sign(b"secret", "POST /verify", body.to_string().as_bytes());
+        let mut request = post("/verify", body.clone());
+        request.headers_mut().insert(crate::signing::SIGNATURE_HEADER, signature.parse().unwrap());
+        request.headers_mut().insert(crate::signing::TIMESTAMP_HEADER, timestamp.parse().unwrap());
+        assert_eq!(call(&router, request).await.0, StatusCode::OK);
+
+        // Signed for another endpoint
+        let (signature, timestamp) = crate::signing::sign(b"secret", "POST /prove", body.to_string().as_bytes());
+        let mut request = post("/verify", body);
+        request.headers_mut().insert(crate::signing::SIGNATURE_HEADER, signature.parse().unwrap());
+        request.headers_mut().insert(crate::signing::TIMESTAMP_HEADER, timestamp.parse().unwrap());
+        assert_eq!(call(&router, request).await.0, StatusCode::UNAUTHORIZED);
+
+        // Health probes stay unsigned
+        let request = Request::get("/health").body(Body::empty()).unwrap();
+        assert_eq!(call(&router, request).await.0, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_body_limit() {
+        let router = router(ApiState::new(Arc::new(MockBackend::new()), 1).with_max_body_size(64));
+        let body = serde_json::json!({ "program": "01", "proof": "00".repeat(64) });
+        assert_eq!(call(&router, post("/verify", body)).await.0, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_callback_hosts() {
+        let state = ApiState::new(Arc::new(MockBackend::new()), 1);
+        for url in ["http://127.0.0.1/hook", "http://[::1]/hook", "http://10.0.0.8/hook", "http://169.254.169.254/latest", "http://localhost/hook", "file:///etc/passwd"] {
+            assert!(callback_url(&state, url).is_err(), "{}", url);
+        }
+        assert!(callback_url(&state, "https://hooks.example.com/done").is_ok());
+
+        let state = state.with_callback_hosts(["hooks.example.com".to_string()]);
+        assert!(callback_url(&state, "https://hooks.example.com/done").is_ok());
+        assert!(callback_url(&state, "https://other.example.com/done").is_err());
+
+        let state = state.with_callback_hosts(["10.0.0.8".to_string()]);
+        assert!(callback_url(&state, "http://10.0.0.8/hook").is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Deterministic mock backend for integration testing
+//!
+//! [`MockBackend`] produces fake proofs instantly and without any prover toolchain,
+//! so downstream crates can test relayer logic in CI. A mock proof commits to the
+//! program and input hashes; the same program and input always yield the same proof
+//! and a proof only verifies against the program it was produced for.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use frostgate_zkip::{
+    HealthStatus, ProofMetadata, ResourceUsage, ZkBackend, ZkBackendExt, ZkConfig, ZkError, ZkResult,
+};
+
+/// Leading bytes of every mock proof
+const MAGIC: &[u8; 6] = b"FGMOCK";
+
+/// Length of a mock proof: magic, program hash, input hash and tag
+const PROOF_LEN: usize = MAGIC.len() + 3 * 32;
+
+/// Backend producing deterministic fake proofs
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    /// Simulated proving time
+    latency: Duration,
+    /// Error returned by every prove call, if set
+    failure: Option<String>,
+    /// Number of proofs generated
+    proofs: AtomicU64,
+}
+
+impl MockBackend {
+    /// Create a mock backend that proves instantly
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Simulate proving taking `latency`
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Fail every prove call with `message`
+    pub fn with_failure(mut self, message: impl Into<String>) -> Self {
+        self.failure = Some(message.into());
+        self
+    }
+
+    /// Number of proofs generated so far
+    pub fn proofs_generated(&self) -> u64 {
+        self.proofs.load(Ordering::Relaxed)
+    }
+
+    /// Mock proof of `input` against `program`
+    pub fn proof(program: &[u8], input: &[u8]) -> Vec<u8> {
+        let program_hash = Sha256::digest(program);
+        let input_hash = Sha256::digest(input);
+        let mut proof = Vec::with_capacity(PROOF_LEN);
+        proof.extend_from_slice(MAGIC);
+        proof.extend_from_slice(&program_hash);
+        proof.extend_from_slice(&input_hash);
+        proof.extend_from_slice(&tag(&program_hash, &input_hash));
+        proof
+    }
+
+    /// Check a mock proof against `program`
+    pub fn check(program: &[u8], proof: &[u8]) -> bool {
+        if proof.len() != PROOF_LEN || !proof.starts_with(MAGIC) {
+            return false;
+        }
+        let program_hash = &proof[MAGIC.len()..MAGIC.len() + 32];
+        let input_hash = &proof[MAGIC.len() + 32..MAGIC.len() + 64];
+        program_hash == Sha256::digest(program).as_slice()
+            && proof[MAGIC.len() + 64..] == tag(program_hash, input_hash)
+    }
+}
+
+/// Tag binding the program and input hashes together
+fn tag(program_hash: &[u8], input_hash: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(MAGIC);
+    hasher.update(program_hash);
+    hasher.update(input_hash);
+    hasher.finalize().into()
+}
+
+#[async_trait]
+impl ZkBackend for MockBackend {
+    async fn prove(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<(Vec<u8>, ProofMetadata)> {
+        let start = SystemTime::now();
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+        if let Some(message) = &self.failure {
+            return Err(ZkError::ProofGeneration(message.clone()));
+        }
+
+        let proof = Self::proof(program, input);
+        self.proofs.fetch_add(1, Ordering::Relaxed);
+        Ok((proof.clone(), ProofMetadata {
+            generation_time: start.elapsed().unwrap_or_default(),
+            proof_size: proof.len(),
+            program_hash: hex::encode(Sha256::digest(program)),
+            timestamp: start,
+        }))
+    }
+
+    async fn verify(
+        &self,
+        program: &[u8],
+        proof: &[u8],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<bool> {
+        Ok(Self::check(program, proof))
+    }
+
+    fn resource_usage(&self) -> ResourceUsage {
+        ResourceUsage {
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            active_tasks: 0,
+            max_concurrent: usize::MAX,
+            queue_depth: 0,
+        }
+    }
+
+    async fn health_check(&self) -> HealthStatus {
+        match &self.failure {
+            Some(message) => HealthStatus::Degraded(message.clone()),
+            None => HealthStatus::Healthy,
+        }
+    }
+}
+
+#[async_trait]
+impl ZkBackendExt for MockBackend {
+    async fn batch_prove(
+        &self,
+        programs: &[(&[u8], &[u8])],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<Vec<(Vec<u8>, ProofMetadata)>> {
+        let results = futures::future::join_all(programs.iter().map(|(program, input)| {
+            self.prove(program, input, config)
+        })).await;
+        results.into_iter().collect()
+    }
+
+    async fn batch_verify(
+        &self,
+        verifications: &[(&[u8], &[u8])],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<Vec<bool>> {
+        Ok(verifications.iter().map(|(program, proof)| Self::check(program, proof)).collect())
+    }
+
+    async fn clear_cache(&mut self) -> ZkResult<()> {
+        Ok(())
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        vec![
+            "mock".to_string(),
+            "message_verify".to_string(),
+            "tx_verify".to_string(),
+            "block_verify".to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_deterministic_proofs() {
+        let backend = MockBackend::new();
+        let (proof, metadata) = backend.prove(b"program", b"input", None).await.unwrap();
+        let (again, _) = backend.prove(b"program", b"input", None).await.unwrap();
+        assert_eq!(proof, again);
+        assert_eq!(metadata.proof_size, proof.len());
+        assert_eq!(backend.proofs_generated(), 2);
+
+        assert!(backend.verify(b"program", &proof, None).await.unwrap());
+        assert!(!backend.verify(b"other", &proof, None).await.unwrap());
+
+        // Tampering with the committed input hash breaks the tag
+        let mut tampered = proof.clone();
+        tampered[MAGIC.len() + 32] ^= 1;
+        let program: &[u8] = b"program";
+        let batch = [(program, proof.as_slice()), (program, tampered.as_slice())];
+        assert_eq!(backend.batch_verify(&batch, None).await.unwrap(), vec![true, false]);
+    }
+
+    #[tokio::test]
+    async fn test_failure() {
+        let backend = MockBackend::new().with_failure("prover offline");
+        assert!(backend.prove(b"program", b"input", None).await.is_err());
+        assert!(matches!(backend.health_check().await, HealthStatus::Degraded(_)));
+    }
+}
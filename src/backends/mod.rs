@@ -0,0 +1,17 @@
+//! Backend selection by name
+//!
+//! Downstream services pick their proving system from configuration through the
+//! [`registry`] instead of depending on concrete backend types, and can combine
+//! several backends into a [`multi::MultiBackend`] that fails over between them.
+//! [`mock::MockBackend`] stands in for a real prover in tests, and
+//! `remote::RemoteBackend` proves on a `frostgate-prover-server` over the network.
+//! A [`universal::UniversalVerifier`] verifies enveloped proofs with whichever
+//! backend produced them.
+
+pub mod mock;
+pub mod multi;
+#[cfg(feature = "prove")]
+pub mod registry;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod universal;
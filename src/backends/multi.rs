@@ -0,0 +1,259 @@
+//! Failover across an ordered list of backends
+//!
+//! A [`MultiBackend`] tries its backends in order, e.g. SP1 network, SP1 local and
+//! then RISC0, and moves on to the next one when a prove call fails. Backends that
+//! report a degraded health status are tried only after all healthy ones.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+use async_trait::async_trait;
+use frostgate_zkip::{
+    HealthStatus, ProofMetadata, ResourceUsage, ZkBackend, ZkConfig, ZkError, ZkResult,
+};
+
+use crate::stats::{ProgramStats, ProgramStatsTracker};
+
+/// Metadata of a proof produced through a [`MultiBackend`]
+#[derive(Debug, Clone)]
+pub struct MultiProofMetadata {
+    /// Metadata reported by the backend that produced the proof
+    pub base: ProofMetadata,
+    /// Identifier of the backend that produced the proof
+    pub backend_id: String,
+    /// Backends that failed before, with their errors
+    pub failed: Vec<(String, String)>,
+}
+
+impl MultiProofMetadata {
+    /// Whether the proof was produced after failing over
+    pub fn failed_over(&self) -> bool {
+        !self.failed.is_empty()
+    }
+}
+
+/// Backend failing over across an ordered list of backends
+pub struct MultiBackend {
+    backends: Vec<(String, Arc<dyn ZkBackend>)>,
+    stats: ProgramStatsTracker,
+}
+
+impl MultiBackend {
+    /// Create a backend without any backends to fail over to
+    pub fn new() -> Self {
+        Self {
+            backends: Vec::new(),
+            stats: ProgramStatsTracker::new(),
+        }
+    }
+
+    /// Append a backend, tried after all previously added ones
+    pub fn with_backend(mut self, id: impl Into<String>, backend: Arc<dyn ZkBackend>) -> Self {
+        self.backends.push((id.into(), backend));
+        self
+    }
+
+    /// Identifiers of the backends in failover order
+    pub fn backend_ids(&self) -> Vec<&str> {
+        self.backends.iter().map(|(id, _)| id.as_str()).collect()
+    }
+
+    /// Proving statistics per backend identifier
+    pub fn stats(&self) -> HashMap<String, ProgramStats> {
+        self.stats.snapshot()
+    }
+
+    /// Backends in the order they are tried: healthy ones first
+    async fn ordered(&self) -> Vec<&(String, Arc<dyn ZkBackend>)> {
+        let mut healthy = Vec::new();
+        let mut degraded = Vec::new();
+        for entry in &self.backends {
+            match entry.1.health_check().await {
+                HealthStatus::Healthy => healthy.push(entry),
+                _ => degraded.push(entry),
+            }
+        }
+        healthy.extend(degraded);
+        healthy
+    }
+
+    /// Generate a proof, reporting which backend produced it
+    pub async fn prove_with_backend(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<(Vec<u8>, MultiProofMetadata)> {
+        let mut failed = Vec::new();
+        for (id, backend) in self.ordered().await {
+            let start = SystemTime::now();
+            let result = backend.prove(program, input, config).await;
+            let duration = start.elapsed().unwrap_or_default();
+            self.stats.record(id, duration, result.is_ok());
+
+            match result {
+                Ok((proof, base)) => {
+                    return Ok((proof, MultiProofMetadata {
+                        base,
+                        backend_id: id.clone(),
+                        failed,
+                    }));
+                }
+                Err(e) => {
+                    tracing::warn!(backend = %id, "proving failed, failing over: {:?}", e);
+                    failed.push((id.clone(), format!("{:?}", e)));
+                }
+            }
+        }
+
+        if failed.is_empty() {
+            return Err(ZkError::Backend("No backends configured".into()));
+        }
+        let errors: Vec<String> = failed.iter().map(|(id, e)| format!("{}: {}", id, e)).collect();
+        Err(ZkError::Backend(format!("All backends failed: {}", errors.join("; "))))
+    }
+}
+
+impl Default for MultiBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ZkBackend for MultiBackend {
+    async fn prove(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<(Vec<u8>, ProofMetadata)> {
+        let (proof, metadata) = self.prove_with_backend(program, input, config).await?;
+        Ok((proof, metadata.base))
+    }
+
+    async fn verify(
+        &self,
+        program: &[u8],
+        proof: &[u8],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<bool> {
+        // The proof may come from any backend, so accept it if one of them does
+        let mut last_error = None;
+        let mut rejected = false;
+        for (_, backend) in self.ordered().await {
+            match backend.verify(program, proof, config).await {
+                Ok(true) => return Ok(true),
+                Ok(false) => rejected = true,
+                Err(e) => last_error = Some(e),
+            }
+        }
+        match last_error {
+            Some(e) if !rejected => Err(e),
+            _ => Ok(false),
+        }
+    }
+
+    fn resource_usage(&self) -> ResourceUsage {
+        let mut usage = ResourceUsage {
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            active_tasks: 0,
+            max_concurrent: 0,
+            queue_depth: 0,
+        };
+        for (_, backend) in &self.backends {
+            let backend_usage = backend.resource_usage();
+            usage.cpu_usage = usage.cpu_usage.max(backend_usage.cpu_usage);
+            usage.memory_usage += backend_usage.memory_usage;
+            usage.active_tasks += backend_usage.active_tasks;
+            usage.max_concurrent += backend_usage.max_concurrent;
+            usage.queue_depth += backend_usage.queue_depth;
+        }
+        usage
+    }
+
+    async fn health_check(&self) -> HealthStatus {
+        if self.backends.is_empty() {
+            return HealthStatus::Degraded("No backends configured".into());
+        }
+        for (_, backend) in &self.backends {
+            if matches!(backend.health_check().await, HealthStatus::Healthy) {
+                return HealthStatus::Healthy;
+            }
+        }
+        HealthStatus::Degraded("All backends degraded".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Backend that either fails or returns its own name as the proof
+    struct StubBackend {
+        name: &'static str,
+        fails: bool,
+    }
+
+    #[async_trait]
+    impl ZkBackend for StubBackend {
+        async fn prove(&self, program: &[u8], _: &[u8], _: Option<&ZkConfig>) -> ZkResult<(Vec<u8>, ProofMetadata)> {
+            if self.fails {
+                return Err(ZkError::Backend(format!("{} unavailable", self.name)));
+            }
+            Ok((self.name.as_bytes().to_vec(), ProofMetadata {
+                generation_time: Duration::ZERO,
+                proof_size: self.name.len(),
+                program_hash: hex::encode(program),
+                timestamp: SystemTime::now(),
+            }))
+        }
+
+        async fn verify(&self, _: &[u8], proof: &[u8], _: Option<&ZkConfig>) -> ZkResult<bool> {
+            Ok(proof == self.name.as_bytes())
+        }
+
+        fn resource_usage(&self) -> ResourceUsage {
+            ResourceUsage {
+                cpu_usage: 0.0,
+                memory_usage: 0,
+                active_tasks: 0,
+                max_concurrent: 1,
+                queue_depth: 0,
+            }
+        }
+
+        async fn health_check(&self) -> HealthStatus {
+            HealthStatus::Healthy
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failover() {
+        let multi = MultiBackend::new()
+            .with_backend("sp1-network", Arc::new(StubBackend { name: "sp1-network", fails: true }))
+            .with_backend("sp1-local", Arc::new(StubBackend { name: "sp1-local", fails: false }))
+            .with_backend("risc0", Arc::new(StubBackend { name: "risc0", fails: false }));
+
+        let (proof, metadata) = multi.prove_with_backend(&[0x01], b"input", None).await.unwrap();
+        assert_eq!(metadata.backend_id, "sp1-local");
+        assert!(metadata.failed_over());
+        assert!(multi.verify(&[0x01], &proof, None).await.unwrap());
+
+        let stats = multi.stats();
+        assert_eq!(stats["sp1-network"].failures, 1);
+        assert_eq!(stats["sp1-local"].proofs, 1);
+        assert!(!stats.contains_key("risc0"));
+    }
+
+    #[tokio::test]
+    async fn test_all_backends_failing() {
+        let multi = MultiBackend::new()
+            .with_backend("a", Arc::new(StubBackend { name: "a", fails: true }))
+            .with_backend("b", Arc::new(StubBackend { name: "b", fails: true }));
+        assert!(multi.prove(&[0x01], b"input", None).await.is_err());
+        assert!(MultiBackend::new().prove(&[0x01], b"input", None).await.is_err());
+    }
+}
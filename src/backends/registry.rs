@@ -0,0 +1,135 @@
+//! Registry of proving backends by name
+//!
+//! The built-in `"sp1"`, `"risc0"` and `"mock"` backends are always available;
+//! services can register their own under any other name. Backends are created from
+//! a JSON configuration, which for the SP1 and RISC0 backends holds their
+//! [`Sp1Options`] or [`Risc0Options`]. A `null` configuration selects the defaults.
+//!
+//! There is no built-in Plonky3 backend. The message, transaction and block
+//! circuits prove statements about SHA-256 digests and Plonky3 ships no SHA-256
+//! AIR, so it cannot prove the same statements without one. A Plonky3 prover that
+//! does can be registered under its own name.
+//!
+//! There is no built-in zkWASM backend either. zkWASM provers are standalone
+//! applications on their own halo2 forks, not libraries this crate can call. A
+//! zkWASM prover run as a service can be registered here, or served behind the
+//! gRPC proving server and reached with `RemoteBackend`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use parking_lot::RwLock;
+use serde_json::Value;
+use frostgate_zkip::ZkBackend;
+
+use crate::error::ZkError;
+use crate::risc0::{Risc0Backend, Risc0Options};
+use crate::sp1::{CacheConfig, Sp1Backend, Sp1Options};
+use super::mock::MockBackend;
+
+/// Creates a backend from its configuration
+pub type BackendFactory = Arc<dyn Fn(&Value) -> Result<Arc<dyn ZkBackend>, ZkError> + Send + Sync>;
+
+/// Backend factories by name
+pub struct BackendRegistry {
+    factories: RwLock<HashMap<String, BackendFactory>>,
+}
+
+impl BackendRegistry {
+    /// Create a registry holding the built-in backends
+    pub fn new() -> Self {
+        let registry = Self {
+            factories: RwLock::new(HashMap::new()),
+        };
+        registry.register("sp1", Arc::new(create_sp1));
+        registry.register("risc0", Arc::new(create_risc0));
+        registry.register("mock", Arc::new(|_: &Value| Ok(Arc::new(MockBackend::new()) as Arc<dyn ZkBackend>)));
+        registry
+    }
+
+    /// Register a backend, replacing any backend registered under the same name
+    pub fn register(&self, name: impl Into<String>, factory: BackendFactory) {
+        self.factories.write().insert(name.into(), factory);
+    }
+
+    /// Create the backend registered under `name`
+    pub fn create(&self, name: &str, config: &Value) -> Result<Arc<dyn ZkBackend>, ZkError> {
+        let factory = self.factories.read()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ZkError::UnknownBackend(name.to_string()))?;
+        factory(config)
+    }
+
+    /// Names of all registered backends, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.factories.read().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Process-wide registry used by [`create_backend`] and [`register_backend`]
+    pub fn global() -> &'static BackendRegistry {
+        static GLOBAL: OnceLock<BackendRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(BackendRegistry::new)
+    }
+}
+
+impl Default for BackendRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Create a backend by name from the global registry
+pub fn create_backend(name: &str, config: &Value) -> Result<Arc<dyn ZkBackend>, ZkError> {
+    BackendRegistry::global().create(name, config)
+}
+
+/// Register a custom backend in the global registry
+pub fn register_backend(name: impl Into<String>, factory: BackendFactory) {
+    BackendRegistry::global().register(name, factory)
+}
+
+fn create_sp1(config: &Value) -> Result<Arc<dyn ZkBackend>, ZkError> {
+    if config.is_null() {
+        return Ok(Arc::new(Sp1Backend::new()));
+    }
+    let options: Sp1Options = serde_json::from_value(config.clone())?;
+    Ok(Arc::new(Sp1Backend::with_config(options, CacheConfig::default())))
+}
+
+fn create_risc0(config: &Value) -> Result<Arc<dyn ZkBackend>, ZkError> {
+    if config.is_null() {
+        return Ok(Arc::new(Risc0Backend::default()));
+    }
+    let options: Risc0Options = serde_json::from_value(config.clone())?;
+    Ok(Arc::new(Risc0Backend::with_config(options, Default::default())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_and_custom_backends() {
+        let registry = BackendRegistry::new();
+        assert_eq!(registry.names(), vec!["mock".to_string(), "risc0".to_string(), "sp1".to_string()]);
+        assert!(registry.create("risc0", &Value::Null).is_ok());
+        assert!(matches!(
+            registry.create("halo2", &Value::Null),
+            Err(ZkError::UnknownBackend(name)) if name == "halo2"
+        ));
+
+        registry.register("halo2", Arc::new(|_: &Value| {
+            Ok(Arc::new(Risc0Backend::default()) as Arc<dyn ZkBackend>)
+        }));
+        assert!(registry.create("halo2", &Value::Null).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let registry = BackendRegistry::new();
+        let config = serde_json::json!({ "num_threads": "many" });
+        assert!(matches!(registry.create("risc0", &config), Err(ZkError::Serialization(_))));
+    }
+}
@@ -0,0 +1,546 @@
+//! Proving through a remote proving server
+//!
+//! [`RemoteBackend`] implements [`ZkBackend`] and [`ZkBackendExt`] by calling the
+//! `frostgate-prover-server` gRPC service, so applications move proving off the
+//! machine by constructing a different backend. Requests are signed when a signing
+//! key is set and failed requests are retried with a linear backoff while the
+//! server is unavailable or overloaded. Proofs are requested zstd-compressed
+//! unless disabled, and every returned proof is decompressed and checked against
+//! its metadata and the request before it reaches the caller.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, UNIX_EPOCH};
+use async_trait::async_trait;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Request, Response, Status};
+use frostgate_zkip::{
+    HealthStatus, ProofMetadata, ResourceUsage, ZkBackend, ZkBackendExt, ZkConfig, ZkResult,
+};
+
+use crate::compression;
+use crate::descriptor;
+use crate::domain::DomainSeparator;
+use crate::error::ZkError as CustomZkError;
+use crate::execution::ExecutionReport;
+use crate::grpc::{mode_name, parse_mode, pb, sign, wire_domain};
+use crate::hasher::HashFunction;
+use crate::metadata::ExtendedProofMetadata;
+use crate::options::ProveOptions;
+
+use pb::prover_client::ProverClient;
+
+/// Deadline of the health probe
+const HEALTH_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Backend proving on a remote proving server
+pub struct RemoteBackend {
+    /// Address of the proving server
+    endpoint: String,
+    client: ProverClient<Channel>,
+    /// Key requests are signed with, if the server requires signatures
+    signing_key: Option<Vec<u8>>,
+    /// Retries after a failed request
+    max_retries: u32,
+    /// Delay before the first retry; later retries wait proportionally longer
+    retry_delay: Duration,
+    /// Deadline of each request, unlimited if unset
+    request_timeout: Option<Duration>,
+    /// Whether proofs are requested compressed
+    compress_proofs: bool,
+    /// Requests in flight
+    active: AtomicUsize,
+}
+
+impl RemoteBackend {
+    /// Prove on the server at `endpoint`, e.g. `http://prover:50051`.
+    ///
+    /// The connection is established on the first request, which must be made from
+    /// within a tokio runtime.
+    pub fn new(endpoint: impl Into<String>) -> Result<Self, CustomZkError> {
+        let endpoint = endpoint.into();
+        let channel = Endpoint::from_shared(endpoint.clone())
+            .map_err(|e| CustomZkError::InvalidInput(format!("Invalid endpoint {}: {}", endpoint, e)))?
+            .connect_lazy();
+        Ok(Self {
+            endpoint,
+            client: ProverClient::new(channel),
+            signing_key: None,
+            max_retries: 3,
+            retry_delay: Duration::from_secs(1),
+            request_timeout: None,
+            compress_proofs: true,
+            active: AtomicUsize::new(0),
+        })
+    }
+
+    /// Sign every request with `key`
+    pub fn with_signing_key(mut self, key: impl Into<Vec<u8>>) -> Self {
+        self.signing_key = Some(key.into());
+        self
+    }
+
+    /// Set the number of retries after a failed request
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the delay before the first retry
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Set the deadline of each request
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    /// Set whether proofs are requested zstd-compressed
+    pub fn with_proof_compression(mut self, compress_proofs: bool) -> Self {
+        self.compress_proofs = compress_proofs;
+        self
+    }
+
+    /// Address of the proving server
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Generate a proof with per-call options
+    pub async fn prove_with_options(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        options: &ProveOptions,
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        let request = prove_request(program, input, options, self.compress_proofs);
+        let response = self.call("prove", request, |mut client, request| async move {
+            client.prove(request).await
+        }).await?;
+        check_envelope(program, options, response)
+    }
+
+    /// Verify a proof and require it to be bound to `domain`
+    pub async fn verify_with_domain(
+        &self,
+        program: &[u8],
+        proof: &[u8],
+        domain: DomainSeparator,
+    ) -> Result<bool, CustomZkError> {
+        self.verify_remote(program, proof, Some(domain)).await
+    }
+
+    /// Execute a program on the server without proving it
+    pub async fn execute(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        domain: DomainSeparator,
+    ) -> Result<ExecutionReport, CustomZkError> {
+        let request = pb::ExecuteRequest {
+            program: program.to_vec(),
+            input: input.to_vec(),
+            domain: Some(wire_domain(domain)),
+        };
+        let response = self.call("execute", request, |mut client, request| async move {
+            client.execute(request).await
+        }).await?;
+        Ok(ExecutionReport {
+            public_values: response.public_values,
+            cycles: response.cycles,
+            segments: response.segments.map(|segments| segments as usize),
+            memory: None,
+            projected_proving_time: None,
+        })
+    }
+
+    async fn verify_remote(
+        &self,
+        program: &[u8],
+        proof: &[u8],
+        domain: Option<DomainSeparator>,
+    ) -> Result<bool, CustomZkError> {
+        let request = pb::VerifyRequest {
+            program: program.to_vec(),
+            proof: proof.to_vec(),
+            domain: domain.map(wire_domain),
+        };
+        let response = self.call("verify", request, |mut client, request| async move {
+            client.verify(request).await
+        }).await?;
+        Ok(response.valid)
+    }
+
+    /// Send a signed request, retrying while the server is unavailable
+    async fn call<M, R, F, Fut>(&self, method: &str, message: M, send: F) -> Result<R, CustomZkError>
+    where
+        M: prost::Message + Clone,
+        F: Fn(ProverClient<Channel>, Request<M>) -> Fut,
+        Fut: Future<Output = Result<Response<R>, Status>>,
+    {
+        self.active.fetch_add(1, Ordering::Relaxed);
+        let mut attempt = 0;
+        let result = loop {
+            // Sign every attempt so retries carry a fresh timestamp
+            let mut request = Request::new(message.clone());
+            if let Some(key) = &self.signing_key {
+                sign(key, method, &message, request.metadata_mut());
+            }
+            if let Some(timeout) = self.request_timeout {
+                request.set_timeout(timeout);
+            }
+
+            match send(self.client.clone(), request).await {
+                Ok(response) => break Ok(response.into_inner()),
+                Err(status) if attempt < self.max_retries && retryable(&status) => {
+                    attempt += 1;
+                    tracing::warn!(endpoint = %self.endpoint, attempt, "remote {} failed, retrying: {}", method, status.message());
+                    tokio::time::sleep(self.retry_delay * attempt).await;
+                }
+                Err(status) => break Err(remote_error(status)),
+            }
+        };
+        self.active.fetch_sub(1, Ordering::Relaxed);
+        result
+    }
+}
+
+impl std::fmt::Debug for RemoteBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteBackend")
+            .field("endpoint", &self.endpoint)
+            .field("signing_key", &self.signing_key.as_ref().map(|_| "<redacted>"))
+            .field("max_retries", &self.max_retries)
+            .field("retry_delay", &self.retry_delay)
+            .field("request_timeout", &self.request_timeout)
+            .field("compress_proofs", &self.compress_proofs)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl ZkBackend for RemoteBackend {
+    async fn prove(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<(Vec<u8>, ProofMetadata)> {
+        let (proof, metadata) = self.prove_with_options(program, input, &ProveOptions::default())
+            .await
+            .map_err(|e| frostgate_zkip::ZkError::Backend(e.to_string()))?;
+        Ok((proof, metadata.base))
+    }
+
+    async fn verify(
+        &self,
+        program: &[u8],
+        proof: &[u8],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<bool> {
+        self.verify_remote(program, proof, None)
+            .await
+            .map_err(|e| frostgate_zkip::ZkError::Backend(e.to_string()))
+    }
+
+    fn resource_usage(&self) -> ResourceUsage {
+        // Load on the server is not visible to the client
+        ResourceUsage {
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            active_tasks: self.active.load(Ordering::Relaxed),
+            max_concurrent: usize::MAX,
+            queue_depth: 0,
+        }
+    }
+
+    async fn health_check(&self) -> HealthStatus {
+        // A single unsigned empty batch, so an unreachable server is reported
+        // promptly rather than after the retry schedule. A server rejecting the
+        // missing signature is reachable.
+        let mut request = Request::new(pb::BatchVerifyRequest { requests: Vec::new() });
+        request.set_timeout(HEALTH_PROBE_TIMEOUT);
+        match self.client.clone().batch_verify(request).await {
+            Ok(_) => HealthStatus::Healthy,
+            Err(status) if status.code() == Code::Unauthenticated => HealthStatus::Healthy,
+            Err(status) => HealthStatus::Degraded(format!(
+                "Remote prover {} unreachable: {}",
+                self.endpoint,
+                remote_error(status)
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl ZkBackendExt for RemoteBackend {
+    async fn batch_prove(
+        &self,
+        programs: &[(&[u8], &[u8])],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<Vec<(Vec<u8>, ProofMetadata)>> {
+        let options = ProveOptions::default();
+        let request = pb::BatchProveRequest {
+            requests: programs.iter().map(|(program, input)| prove_request(program, input, &options, self.compress_proofs)).collect(),
+        };
+        let response = self.call("batch_prove", request, |mut client, request| async move {
+            client.batch_prove(request).await
+        })
+        .await
+        .map_err(|e| frostgate_zkip::ZkError::Backend(e.to_string()))?;
+        if response.items.len() != programs.len() {
+            return Err(frostgate_zkip::ZkError::Backend(format!(
+                "Remote prover returned {} proofs for {} programs",
+                response.items.len(),
+                programs.len()
+            )));
+        }
+
+        programs.iter().zip(response.items).map(|((program, _), item)| {
+            match item.outcome {
+                Some(pb::batch_prove_item::Outcome::Proof(response)) => check_envelope(program, &options, response)
+                    .map(|(proof, metadata)| (proof, metadata.base))
+                    .map_err(|e| frostgate_zkip::ZkError::Backend(e.to_string())),
+                Some(pb::batch_prove_item::Outcome::Error(message)) => Err(frostgate_zkip::ZkError::ProofGeneration(message)),
+                None => Err(frostgate_zkip::ZkError::Backend("Remote prover returned an empty batch item".into())),
+            }
+        }).collect()
+    }
+
+    async fn batch_verify(
+        &self,
+        verifications: &[(&[u8], &[u8])],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<Vec<bool>> {
+        let request = pb::BatchVerifyRequest {
+            requests: verifications.iter().map(|(program, proof)| pb::VerifyRequest {
+                program: program.to_vec(),
+                proof: proof.to_vec(),
+                domain: None,
+            }).collect(),
+        };
+        let response = self.call("batch_verify", request, |mut client, request| async move {
+            client.batch_verify(request).await
+        })
+        .await
+        .map_err(|e| frostgate_zkip::ZkError::Backend(e.to_string()))?;
+        if response.items.len() != verifications.len() {
+            return Err(frostgate_zkip::ZkError::Backend(format!(
+                "Remote prover returned {} results for {} proofs",
+                response.items.len(),
+                verifications.len()
+            )));
+        }
+
+        // Proofs the server could not check are reported invalid, as local backends do
+        Ok(response.items.into_iter()
+            .map(|item| matches!(item.outcome, Some(pb::batch_verify_item::Outcome::Valid(true))))
+            .collect())
+    }
+
+    async fn clear_cache(&mut self) -> ZkResult<()> {
+        // The server manages its own caches
+        Ok(())
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        vec![
+            "remote".to_string(),
+            "message_verify".to_string(),
+            "tx_verify".to_string(),
+            "block_verify".to_string(),
+        ]
+    }
+}
+
+fn prove_request(program: &[u8], input: &[u8], options: &ProveOptions, compress_proof: bool) -> pb::ProveRequest {
+    pb::ProveRequest {
+        program: program.to_vec(),
+        input: input.to_vec(),
+        proof_mode: options.proof_mode.map(mode_name),
+        max_proof_size: options.max_proof_size.map(|size| size as u64),
+        timeout_ms: options.timeout.map(|timeout| timeout.as_millis() as u64),
+        domain: options.domain.map(wire_domain),
+        compress_proof,
+    }
+}
+
+/// Check a returned proof against its metadata and the request that produced it
+fn check_envelope(
+    program: &[u8],
+    options: &ProveOptions,
+    response: pb::ProveResponse,
+) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+    let invalid = |reason: String| CustomZkError::ProofVerification(format!("Invalid remote proof: {}", reason));
+    let wire = response.metadata.ok_or_else(|| invalid("missing metadata".into()))?;
+    let proof = if response.compressed {
+        compression::decompress(&response.proof).map_err(|e| invalid(format!("undecodable proof: {}", e)))?
+    } else {
+        response.proof
+    };
+
+    if proof.is_empty() {
+        return Err(invalid("empty proof".into()));
+    }
+    if wire.proof_size != proof.len() as u64 {
+        return Err(invalid(format!("proof is {} bytes, metadata reports {}", proof.len(), wire.proof_size)));
+    }
+    if let Some(circuit_type) = wire.circuit_type {
+        if descriptor::circuit_type(program).map(u32::from) != Some(circuit_type) {
+            return Err(invalid(format!("proof is for circuit 0x{:02x}", circuit_type)));
+        }
+    }
+    // Servers hash programs with the hash function of their circuit profile
+    let program_hash = hex::decode(&wire.program_hash).ok();
+    if !HashFunction::ALL.iter().any(|hash_function| program_hash.as_deref() == Some(&hash_function.hash(program)[..])) {
+        return Err(invalid(format!("proof is for program {}", wire.program_hash)));
+    }
+    let vkey_hash = match wire.vkey_hash.len() {
+        0 => None,
+        32 => wire.vkey_hash.as_slice().try_into().ok(),
+        len => return Err(invalid(format!("verifying key hash is {} bytes", len))),
+    };
+
+    let requested_mode = options.proof_mode.unwrap_or_default();
+    let proof_mode = match &wire.proof_mode {
+        Some(mode) => parse_mode(mode).map_err(|status| invalid(status.message().to_string()))?,
+        None => requested_mode,
+    };
+    let metadata = ExtendedProofMetadata {
+        base: ProofMetadata {
+            generation_time: Duration::from_millis(wire.generation_time_ms),
+            proof_size: proof.len(),
+            program_hash: wire.program_hash,
+            timestamp: UNIX_EPOCH + Duration::from_secs(wire.timestamp),
+        },
+        proof_mode,
+        requested_mode,
+        max_proof_size: options.max_proof_size,
+        backend: wire.backend.unwrap_or_else(|| "remote".to_string()),
+        sdk_version: wire.sdk_version.unwrap_or_default(),
+        circuit_type: wire.circuit_type.map(|circuit_type| circuit_type as u8),
+        circuit_version: wire.circuit_version,
+        vkey_hash,
+        public_values: wire.public_values,
+        cycles: wire.cycles,
+        segments: wire.segments.map(|segments| segments as usize),
+        peak_memory: wire.peak_memory.map(|memory| memory as usize),
+    };
+
+    if let Some(limit) = options.max_proof_size {
+        if proof.len() > limit {
+            return Err(CustomZkError::ProofTooLarge { size: proof.len(), limit, mode: proof_mode });
+        }
+    }
+    // Servers only report public values for circuits they can decode
+    if let (Some(expected), Some(output)) = (options.domain, metadata.output()) {
+        match output.domain() {
+            Some(found) if found != expected => return Err(CustomZkError::DomainMismatch { expected, found }),
+            Some(_) => {}
+            None => return Err(invalid("proof is not bound to the requested domain".into())),
+        }
+    }
+
+    Ok((proof, metadata))
+}
+
+fn retryable(status: &Status) -> bool {
+    matches!(status.code(), Code::Unavailable | Code::ResourceExhausted | Code::Aborted)
+}
+
+fn remote_error(status: Status) -> CustomZkError {
+    let message = status.message().to_string();
+    match status.code() {
+        Code::InvalidArgument => CustomZkError::InvalidInput(message),
+        Code::Cancelled => CustomZkError::Cancelled,
+        Code::Unauthenticated => CustomZkError::Backend(format!("Remote prover rejected the request: {}", message)),
+        _ => CustomZkError::Backend(format!("Remote prover failed: {}", message)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ChainId;
+
+    fn response(proof: Vec<u8>, metadata: pb::ProofMetadata) -> pb::ProveResponse {
+        pb::ProveResponse { proof, metadata: Some(metadata), compressed: false }
+    }
+
+    #[test]
+    fn test_envelope_checks() {
+        let options = ProveOptions::default();
+        let metadata = pb::ProofMetadata {
+            proof_size: 3,
+            program_hash: hex::encode(HashFunction::Keccak256.hash(&[0x01])),
+            circuit_type: Some(0x01),
+            proof_mode: Some("compressed".into()),
+            cycles: Some(1_000),
+            ..Default::default()
+        };
+
+        let (proof, checked) = check_envelope(&[0x01], &options, response(vec![1, 2, 3], metadata.clone())).unwrap();
+        assert_eq!(proof, vec![1, 2, 3]);
+        assert_eq!(checked.base.proof_size, 3);
+        assert_eq!(checked.circuit_type, Some(0x01));
+        assert_eq!(checked.cycles, Some(1_000));
+
+        // Size disagreeing with the metadata, wrong circuit, missing metadata
+        assert!(check_envelope(&[0x01], &options, response(vec![1, 2], metadata.clone())).is_err());
+        assert!(check_envelope(&[0x02], &options, response(vec![1, 2, 3], metadata.clone())).is_err());
+        let missing = pb::ProveResponse { proof: vec![1, 2, 3], metadata: None, compressed: false };
+        assert!(check_envelope(&[0x01], &options, missing).is_err());
+
+        // Proof of another program
+        let other = pb::ProofMetadata { program_hash: hex::encode(HashFunction::Sha256.hash(&[0x01, 0x00])), ..metadata.clone() };
+        assert!(check_envelope(&[0x01], &options, response(vec![1, 2, 3], other)).is_err());
+
+        // Compressed proofs are checked once decompressed
+        let compressed = pb::ProveResponse {
+            proof: compression::compress(&[1, 2, 3]).unwrap(),
+            metadata: Some(metadata.clone()),
+            compressed: true,
+        };
+        assert_eq!(check_envelope(&[0x01], &options, compressed).unwrap().0, vec![1, 2, 3]);
+
+        // Over budget
+        let options = ProveOptions::default().with_max_proof_size(2);
+        assert!(matches!(
+            check_envelope(&[0x01], &options, response(vec![1, 2, 3], metadata)),
+            Err(CustomZkError::ProofTooLarge { size: 3, limit: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_envelope_domain() {
+        let expected = DomainSeparator::new(ChainId(1), ChainId(2));
+        let other = DomainSeparator::new(ChainId(1), ChainId(3));
+        let mut public_values = vec![7u8; 32];
+        public_values.extend_from_slice(&other.to_bytes());
+        let metadata = pb::ProofMetadata {
+            proof_size: 1,
+            program_hash: hex::encode(HashFunction::Sha256.hash(&[0x01])),
+            circuit_type: Some(0x01),
+            public_values,
+            ..Default::default()
+        };
+
+        let options = ProveOptions::default().with_domain(expected);
+        assert!(matches!(
+            check_envelope(&[0x01], &options, response(vec![1], metadata.clone())),
+            Err(CustomZkError::DomainMismatch { found, .. }) if found == other
+        ));
+        let options = ProveOptions::default().with_domain(other);
+        assert!(check_envelope(&[0x01], &options, response(vec![1], metadata)).is_ok());
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(retryable(&Status::unavailable("down")));
+        assert!(!retryable(&Status::invalid_argument("bad")));
+        assert!(matches!(remote_error(Status::invalid_argument("bad")), CustomZkError::InvalidInput(_)));
+    }
+}
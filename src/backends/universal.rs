@@ -0,0 +1,153 @@
+//! Verification of proofs from any backend
+//!
+//! Message consumers receive proofs from relayers that may prove with either system.
+//! A [`UniversalVerifier`] reads the [`ProofEnvelope`] header of a proof, picks the
+//! backend that produced it and hands the envelope to that backend, which checks
+//! that it was produced for the program before verifying the proof inside. Raw
+//! proofs carry no header and are rejected.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use frostgate_zkip::ZkBackend;
+
+use crate::envelope::{self, ProofEnvelope};
+use crate::error::ZkError;
+use crate::metadata::ProofMode;
+
+/// Proof modes each built-in backend produces
+///
+/// SP1 proves in every mode; RISC0 composite, succinct and Groth16 receipts are
+/// reported as core, compressed and Groth16 proofs.
+fn supported_modes(backend_id: &str) -> Option<&'static [ProofMode]> {
+    match backend_id {
+        "sp1" => Some(&[ProofMode::Core, ProofMode::Compressed, ProofMode::Plonk, ProofMode::Groth16]),
+        "risc0" => Some(&[ProofMode::Core, ProofMode::Compressed, ProofMode::Groth16]),
+        _ => None,
+    }
+}
+
+/// Verifier dispatching enveloped proofs to the backend that produced them
+#[derive(Default)]
+pub struct UniversalVerifier {
+    backends: HashMap<String, Arc<dyn ZkBackend>>,
+}
+
+impl UniversalVerifier {
+    /// Create a verifier without any backends
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a verifier of SP1 and RISC0 proofs with default backends
+    #[cfg(feature = "prove")]
+    pub fn with_builtin() -> Self {
+        Self::new()
+            .with_backend("sp1", Arc::new(crate::sp1::Sp1Backend::new()))
+            .with_backend("risc0", Arc::new(crate::risc0::Risc0Backend::default()))
+    }
+
+    /// Verify proofs whose envelope names `id` with `backend`
+    ///
+    /// The backend receives the whole envelope, so it must accept enveloped proofs
+    /// as the SP1 and RISC0 backends do.
+    pub fn with_backend(mut self, id: impl Into<String>, backend: Arc<dyn ZkBackend>) -> Self {
+        self.backends.insert(id.into(), backend);
+        self
+    }
+
+    /// Identifiers of the registered backends, sorted
+    pub fn backend_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.backends.keys().map(String::as_str).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Decode the envelope of `proof` and pick the backend to verify it with
+    pub fn route(&self, proof: &[u8]) -> Result<(ProofEnvelope, Arc<dyn ZkBackend>), ZkError> {
+        if !envelope::is_envelope(proof) {
+            return Err(ZkError::InvalidInput("Proof is not wrapped in an envelope".into()));
+        }
+        let envelope = ProofEnvelope::from_bytes(proof)?;
+        let backend = self.backends.get(&envelope.backend_id)
+            .cloned()
+            .ok_or_else(|| ZkError::UnknownBackend(envelope.backend_id.clone()))?;
+        if let Some(modes) = supported_modes(&envelope.backend_id) {
+            if !modes.contains(&envelope.proof_mode) {
+                return Err(ZkError::ProofVerification(format!(
+                    "{} does not produce {:?} proofs",
+                    envelope.backend_id, envelope.proof_mode
+                )));
+            }
+        }
+        Ok((envelope, backend))
+    }
+
+    /// Verify an enveloped proof of `program` with the backend that produced it
+    pub async fn verify(&self, program: &[u8], proof: &[u8]) -> Result<bool, ZkError> {
+        let (envelope, backend) = self.route(proof)?;
+        tracing::debug!(backend = %envelope.backend_id, mode = ?envelope.proof_mode, "verifying enveloped proof");
+        backend.verify(program, proof, None)
+            .await
+            .map_err(ZkError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use frostgate_zkip::{HealthStatus, ProofMetadata, ResourceUsage, ZkConfig, ZkResult};
+
+    /// Backend accepting envelopes it produced
+    struct StubBackend {
+        name: &'static str,
+    }
+
+    #[async_trait]
+    impl ZkBackend for StubBackend {
+        async fn prove(&self, _: &[u8], _: &[u8], _: Option<&ZkConfig>) -> ZkResult<(Vec<u8>, ProofMetadata)> {
+            Err(frostgate_zkip::ZkError::Backend("verification only".into()))
+        }
+
+        async fn verify(&self, _: &[u8], proof: &[u8], _: Option<&ZkConfig>) -> ZkResult<bool> {
+            Ok(ProofEnvelope::from_bytes(proof).is_ok_and(|envelope| envelope.backend_id == self.name))
+        }
+
+        fn resource_usage(&self) -> ResourceUsage {
+            ResourceUsage {
+                cpu_usage: 0.0,
+                memory_usage: 0,
+                active_tasks: 0,
+                max_concurrent: 1,
+                queue_depth: 0,
+            }
+        }
+
+        async fn health_check(&self) -> HealthStatus {
+            HealthStatus::Healthy
+        }
+    }
+
+    fn enveloped(backend_id: &str, mode: ProofMode) -> Vec<u8> {
+        ProofEnvelope::new(backend_id, "1.0.0", mode, hex::encode([1u8; 32]), Vec::new(), vec![1, 2, 3]).to_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_dispatch() {
+        let verifier = UniversalVerifier::new()
+            .with_backend("sp1", Arc::new(StubBackend { name: "sp1" }))
+            .with_backend("risc0", Arc::new(StubBackend { name: "risc0" }));
+        assert_eq!(verifier.backend_ids(), vec!["risc0", "sp1"]);
+
+        assert!(verifier.verify(&[0x01], &enveloped("sp1", ProofMode::Groth16)).await.unwrap());
+        assert!(verifier.verify(&[0x01], &enveloped("risc0", ProofMode::Compressed)).await.unwrap());
+
+        // RISC0 has no PLONK receipts, and unknown backends and raw proofs cannot be routed
+        assert!(verifier.verify(&[0x01], &enveloped("risc0", ProofMode::Plonk)).await.is_err());
+        assert!(matches!(
+            verifier.route(&enveloped("unknown", ProofMode::Groth16)),
+            Err(ZkError::UnknownBackend(_))
+        ));
+        assert!(verifier.verify(&[0x01], &[1, 2, 3]).await.is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! gRPC proving server for Frostgate relayers
+//!
+//! Configuration is read from the environment:
+//! - `FROSTGATE_SERVER_ADDR`: address to listen on, defaults to `0.0.0.0:50051`
+//! - `FROSTGATE_SERVER_BACKEND`: backend to prove with, one of `sp1` (default),
+//!   `risc0` or any name registered with the backend registry
+//! - `FROSTGATE_SERVER_BACKEND_CONFIG`: JSON configuration for registry backends
+//! - `FROSTGATE_SERVER_SIGNING_KEY`: if set, only requests signed with this key are
+//!   accepted
+//!
+//! Ctrl-C stops accepting requests and lets requests in flight finish.
+
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use frostgate_circuits::backends::registry::create_backend;
+use frostgate_circuits::risc0::{Risc0Backend, Risc0Config};
+use frostgate_circuits::server::{ProverService, ServedBackend};
+use frostgate_circuits::sp1::Sp1Backend;
+
+#[tokio::main]
+async fn main() {
+    let addr: SocketAddr = match env::var("FROSTGATE_SERVER_ADDR")
+        .unwrap_or_else(|_| "0.0.0.0:50051".to_string())
+        .parse()
+    {
+        Ok(addr) => addr,
+        Err(e) => {
+            eprintln!("invalid FROSTGATE_SERVER_ADDR: {}", e);
+            std::process::exit(2);
+        }
+    };
+    let name = env::var("FROSTGATE_SERVER_BACKEND").unwrap_or_else(|_| "sp1".to_string());
+
+    let backend = match name.as_str() {
+        "sp1" => ServedBackend::Sp1(Arc::new(Sp1Backend::new())),
+        "risc0" => ServedBackend::Risc0(Arc::new(Risc0Backend::new(Risc0Config::default()))),
+        name => {
+            let config = env::var("FROSTGATE_SERVER_BACKEND_CONFIG")
+                .ok()
+                .and_then(|config| serde_json::from_str(&config).ok())
+                .unwrap_or(serde_json::Value::Null);
+            match create_backend(name, &config) {
+                Ok(backend) => ServedBackend::Other(backend),
+                Err(e) => {
+                    eprintln!("cannot create backend {}: {}", name, e);
+                    std::process::exit(2);
+                }
+            }
+        }
+    };
+
+    let mut service = ProverService::new(backend);
+    if let Ok(key) = env::var("FROSTGATE_SERVER_SIGNING_KEY") {
+        service = service.with_signing_key(key);
+    }
+
+    eprintln!("serving {} backend on {}", name, addr);
+    let result = tonic::transport::Server::builder()
+        .add_service(service.into_server())
+        .serve_with_shutdown(addr, async {
+            let _ = tokio::signal::ctrl_c().await;
+            eprintln!("shutting down");
+        })
+        .await;
+    if let Err(e) = result {
+        eprintln!("server stopped: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,56 @@
+//! Prover worker for Frostgate cluster mode
+//!
+//! Configuration is read from the environment:
+//! - `FROSTGATE_COORDINATOR_URL`: coordinator base URL (required)
+//! - `FROSTGATE_WORKER_NAME`: worker name, defaults to the host name
+//! - `FROSTGATE_WORKER_BACKENDS`: comma-separated backends, defaults to `sp1,risc0`
+//! - `FROSTGATE_WORKER_MEMORY`: memory available for proving in bytes
+//!
+//! Ctrl-C drains the worker: jobs in progress are handed off to the coordinator
+//! before the process exits.
+
+use std::env;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use frostgate_circuits::risc0::{Risc0Backend, Risc0Config};
+use frostgate_circuits::sp1::Sp1Backend;
+use frostgate_circuits::worker::{HttpCoordinator, Worker};
+
+#[tokio::main]
+async fn main() {
+    let coordinator_url = match env::var("FROSTGATE_COORDINATOR_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("FROSTGATE_COORDINATOR_URL must be set");
+            std::process::exit(2);
+        }
+    };
+    let name = env::var("FROSTGATE_WORKER_NAME")
+        .or_else(|_| env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "frostgate-worker".to_string());
+    let backends = env::var("FROSTGATE_WORKER_BACKENDS").unwrap_or_else(|_| "sp1,risc0".to_string());
+    let backends: Vec<&str> = backends.split(',').map(str::trim).collect();
+
+    let sp1 = backends.contains(&"sp1").then(|| Arc::new(Sp1Backend::new()));
+    let risc0 = backends.contains(&"risc0").then(|| Arc::new(Risc0Backend::new(Risc0Config::default())));
+
+    let mut worker = Worker::new(HttpCoordinator::new(&coordinator_url), &name, sp1, risc0);
+    if let Some(memory) = env::var("FROSTGATE_WORKER_MEMORY").ok().and_then(|m| m.parse().ok()) {
+        worker = worker.with_memory(memory);
+    }
+
+    // Drain on Ctrl-C
+    let draining = worker.drain_handle();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("draining worker");
+            draining.store(true, Ordering::Release);
+        }
+    });
+
+    if let Err(e) = worker.run().await {
+        eprintln!("worker stopped: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,65 @@
+//! Proofs carried from one proving system into the other
+//!
+//! Deployments migrating between RISC0 and SP1 keep a single on-chain verifier by
+//! wrapping the proofs of one system in proofs of the other. The SP1 guest
+//! `circuits/sp1/risc0_bridge.rs` verifies a RISC0 succinct receipt and commits the
+//! receipt's image ID followed by its journal, so the SP1 verifier sees the same
+//! statement the receipt proved. [`BridgedReceipt`] decodes those public values.
+
+use crate::error::ZkError;
+use crate::journal::CircuitOutput;
+
+/// Statement of a RISC0 receipt proven inside an SP1 proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BridgedReceipt {
+    /// Image ID of the guest that produced the receipt
+    pub image_id: [u8; 32],
+    /// Journal of the receipt
+    pub journal: Vec<u8>,
+}
+
+impl BridgedReceipt {
+    /// Decode from the public values of a bridge proof
+    pub fn decode(public_values: &[u8]) -> Result<Self, ZkError> {
+        if public_values.len() < 32 {
+            return Err(ZkError::ProofVerification(format!(
+                "Malformed bridge public values: expected at least 32 bytes, got {}",
+                public_values.len()
+            )));
+        }
+        let mut image_id = [0u8; 32];
+        image_id.copy_from_slice(&public_values[..32]);
+        Ok(Self {
+            image_id,
+            journal: public_values[32..].to_vec(),
+        })
+    }
+
+    /// Encode as the bridge guest commits it
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [&self.image_id[..], &self.journal].concat()
+    }
+
+    /// Decode the journal as the outputs of a circuit of `circuit_type`
+    pub fn output(&self, circuit_type: u8) -> Result<CircuitOutput, ZkError> {
+        CircuitOutput::decode(circuit_type, &self.journal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::MESSAGE_VERIFY;
+
+    #[test]
+    fn test_bridged_receipt_round_trip() {
+        let bridged = BridgedReceipt {
+            image_id: [0x42; 32],
+            journal: vec![0x11; 32],
+        };
+        let decoded = BridgedReceipt::decode(&bridged.to_bytes()).unwrap();
+        assert_eq!(decoded, bridged);
+        assert_eq!(decoded.output(MESSAGE_VERIFY).unwrap().hash(), [0x11; 32]);
+        assert!(BridgedReceipt::decode(&[0u8; 16]).is_err());
+    }
+}
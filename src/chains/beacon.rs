@@ -0,0 +1,396 @@
+//! Ethereum consensus finality through sync committee light-client updates
+//!
+//! The witness is an SSZ-encoded Altair `LightClientUpdate` and the sync committee
+//! of its period. The anchor binds the committee to its chain:
+//! `sha256(hash_tree_root(sync_committee) ++ genesis_validators_root)`, so a proof
+//! shows that more than two thirds of the committee the verifier trusts signed a
+//! header whose state finalizes the committed header. The statement carries the
+//! finalized slot, the root of the finalized header, and the root of the next sync
+//! committee when the update proves it, which is what the following period's
+//! anchor is built from.
+//!
+//! Capella and later updates wrap their headers with an execution payload header;
+//! only the plain beacon block headers of the Altair layout are decoded here.
+
+use alloc::vec::Vec;
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{pairing, G1Affine, G1Projective, G2Affine, G2Projective};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{supermajority, ChainError, ChainStatement};
+
+/// Number of validators in a sync committee
+pub const SYNC_COMMITTEE_SIZE: usize = 512;
+
+/// Length of an SSZ-encoded sync committee: the pubkeys and their aggregate
+pub const SYNC_COMMITTEE_LEN: usize = (SYNC_COMMITTEE_SIZE + 1) * PUBKEY_LEN;
+
+/// Length of an SSZ-encoded Altair light-client update
+pub const UPDATE_LEN: usize = HEADER_LEN
+    + SYNC_COMMITTEE_LEN
+    + NEXT_SYNC_COMMITTEE_DEPTH * 32
+    + HEADER_LEN
+    + FINALIZED_ROOT_DEPTH * 32
+    + SYNC_COMMITTEE_SIZE / 8
+    + SIGNATURE_LEN
+    + 8;
+
+/// Length of a compressed BLS public key
+const PUBKEY_LEN: usize = 48;
+
+/// Length of a compressed BLS signature
+const SIGNATURE_LEN: usize = 96;
+
+/// Length of an SSZ-encoded `BeaconBlockHeader`
+const HEADER_LEN: usize = 112;
+
+/// Depth and index of the finalized checkpoint root in the beacon state (generalized index 105)
+const FINALIZED_ROOT_DEPTH: usize = 6;
+const FINALIZED_ROOT_INDEX: u64 = 41;
+
+/// Depth and index of the next sync committee in the beacon state (generalized index 55)
+const NEXT_SYNC_COMMITTEE_DEPTH: usize = 5;
+const NEXT_SYNC_COMMITTEE_INDEX: u64 = 23;
+
+/// `DOMAIN_SYNC_COMMITTEE`
+const DOMAIN_SYNC_COMMITTEE: [u8; 4] = [7, 0, 0, 0];
+
+/// Ciphersuite of consensus-layer BLS signatures
+const BLS_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Beacon chain light-client update witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeaconWitness {
+    /// Genesis validators root of the chain
+    pub genesis_validators_root: [u8; 32],
+    /// Fork version at the signature slot
+    pub fork_version: [u8; 4],
+    /// SSZ-encoded sync committee of the signature slot's period
+    pub sync_committee: Vec<u8>,
+    /// SSZ-encoded Altair `LightClientUpdate`
+    pub update: Vec<u8>,
+}
+
+/// SSZ `BeaconBlockHeader`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeaconBlockHeader {
+    /// Slot of the block
+    pub slot: u64,
+    /// Index of the block's proposer
+    pub proposer_index: u64,
+    /// Root of the parent block
+    pub parent_root: [u8; 32],
+    /// Root of the post-state
+    pub state_root: [u8; 32],
+    /// Root of the block body
+    pub body_root: [u8; 32],
+}
+
+impl BeaconBlockHeader {
+    /// Decode a header from its SSZ encoding
+    pub fn decode(bytes: &[u8]) -> Result<Self, ChainError> {
+        let mut reader = Reader::new(bytes, HEADER_LEN, "beacon block header")?;
+        Ok(Self {
+            slot: reader.u64(),
+            proposer_index: reader.u64(),
+            parent_root: reader.array(),
+            state_root: reader.array(),
+            body_root: reader.array(),
+        })
+    }
+
+    /// SSZ encoding of the header
+    pub fn encode(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[..8].copy_from_slice(&self.slot.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.proposer_index.to_le_bytes());
+        bytes[16..48].copy_from_slice(&self.parent_root);
+        bytes[48..80].copy_from_slice(&self.state_root);
+        bytes[80..].copy_from_slice(&self.body_root);
+        bytes
+    }
+
+    /// SSZ `hash_tree_root` of the header, the block root
+    pub fn hash_tree_root(&self) -> [u8; 32] {
+        merkleize(&[
+            uint64(self.slot),
+            uint64(self.proposer_index),
+            self.parent_root,
+            self.state_root,
+            self.body_root,
+        ])
+    }
+}
+
+/// Verify that the sync committee signed a header finalizing the update's finalized header
+pub fn verify(witness: &BeaconWitness) -> Result<ChainStatement, ChainError> {
+    let committee = Reader::new(&witness.sync_committee, SYNC_COMMITTEE_LEN, "sync committee")?.bytes(SYNC_COMMITTEE_LEN);
+    let mut update = Reader::new(&witness.update, UPDATE_LEN, "light-client update")?;
+    let attested = BeaconBlockHeader::decode(update.bytes(HEADER_LEN))?;
+    let next_committee = update.bytes(SYNC_COMMITTEE_LEN);
+    let next_committee_branch = update.bytes(NEXT_SYNC_COMMITTEE_DEPTH * 32);
+    let finalized = BeaconBlockHeader::decode(update.bytes(HEADER_LEN))?;
+    let finality_branch = update.bytes(FINALIZED_ROOT_DEPTH * 32);
+    let participation = update.bytes(SYNC_COMMITTEE_SIZE / 8);
+    let signature = update.bytes(SIGNATURE_LEN);
+    let signature_slot = update.u64();
+
+    if !(signature_slot > attested.slot && attested.slot >= finalized.slot) {
+        return Err(ChainError::Malformed("update slots"));
+    }
+
+    // The finalized header is committed to by the attested state
+    let finalized_root = finalized.hash_tree_root();
+    if !is_valid_merkle_branch(finalized_root, finality_branch, FINALIZED_ROOT_INDEX, attested.state_root) {
+        return Err(ChainError::InvalidProof("finality branch does not lead to the attested state"));
+    }
+
+    // Updates rotating the committee also prove the next one
+    let next_committee_root = if next_committee_branch.iter().all(|byte| *byte == 0) {
+        [0; 32]
+    } else {
+        let root = sync_committee_root(next_committee);
+        if !is_valid_merkle_branch(root, next_committee_branch, NEXT_SYNC_COMMITTEE_INDEX, attested.state_root) {
+            return Err(ChainError::InvalidProof("next sync committee branch does not lead to the attested state"));
+        }
+        root
+    };
+
+    // More than two thirds of the committee must have signed the attested header
+    let signers: Vec<&[u8]> = committee[..SYNC_COMMITTEE_SIZE * PUBKEY_LEN]
+        .chunks(PUBKEY_LEN)
+        .enumerate()
+        .filter(|(i, _)| participation[i / 8] >> (i % 8) & 1 == 1)
+        .map(|(_, pubkey)| pubkey)
+        .collect();
+    supermajority(signers.len() as u64, SYNC_COMMITTEE_SIZE as u64)?;
+    let domain = sync_committee_domain(witness.fork_version, witness.genesis_validators_root);
+    let signing_root = hash_pair(&attested.hash_tree_root(), &domain);
+    verify_aggregate(&signers, &signing_root, signature)?;
+
+    Ok(ChainStatement {
+        anchor: committee_anchor(&sync_committee_root(committee), &witness.genesis_validators_root),
+        number: finalized.slot,
+        block_hash: finalized_root,
+        commitment: next_committee_root,
+    })
+}
+
+/// Anchor of beacon chain proofs: a sync committee root bound to its chain
+pub fn committee_anchor(sync_committee_root: &[u8; 32], genesis_validators_root: &[u8; 32]) -> [u8; 32] {
+    hash_pair(sync_committee_root, genesis_validators_root)
+}
+
+/// SSZ `hash_tree_root` of an encoded sync committee
+///
+/// # Panics
+///
+/// If `committee` is not [`SYNC_COMMITTEE_LEN`] long.
+pub fn sync_committee_root(committee: &[u8]) -> [u8; 32] {
+    assert_eq!(committee.len(), SYNC_COMMITTEE_LEN, "sync committee length");
+    let (pubkeys, aggregate) = committee.split_at(SYNC_COMMITTEE_SIZE * PUBKEY_LEN);
+    let pubkey_roots: Vec<[u8; 32]> = pubkeys.chunks(PUBKEY_LEN).map(pubkey_root).collect();
+    hash_pair(&merkleize(&pubkey_roots), &pubkey_root(aggregate))
+}
+
+/// Domain of sync committee signatures for a fork of the chain
+pub fn sync_committee_domain(fork_version: [u8; 4], genesis_validators_root: [u8; 32]) -> [u8; 32] {
+    let mut version = [0u8; 32];
+    version[..4].copy_from_slice(&fork_version);
+    let fork_data_root = hash_pair(&version, &genesis_validators_root);
+    let mut domain = [0u8; 32];
+    domain[..4].copy_from_slice(&DOMAIN_SYNC_COMMITTEE);
+    domain[4..].copy_from_slice(&fork_data_root[..28]);
+    domain
+}
+
+/// Verify a BLS signature aggregated over `message` by every key of `pubkeys`
+fn verify_aggregate(pubkeys: &[&[u8]], message: &[u8; 32], signature: &[u8]) -> Result<(), ChainError> {
+    let mut aggregate = G1Projective::identity();
+    for pubkey in pubkeys {
+        let pubkey: [u8; PUBKEY_LEN] = (*pubkey).try_into().expect("pubkey length");
+        let point = Option::<G1Affine>::from(G1Affine::from_compressed(&pubkey))
+            .ok_or(ChainError::Malformed("sync committee pubkey"))?;
+        aggregate += point;
+    }
+    let signature: [u8; SIGNATURE_LEN] = signature.try_into().expect("signature length");
+    let signature = Option::<G2Affine>::from(G2Affine::from_compressed(&signature))
+        .ok_or(ChainError::InvalidSignature)?;
+    let hashed = <G2Projective as HashToCurve<ExpandMsgXmd<sha2_v09::Sha256>>>::hash_to_curve(message, BLS_DST);
+
+    if pairing(&G1Affine::from(aggregate), &G2Affine::from(hashed)) != pairing(&G1Affine::generator(), &signature) {
+        return Err(ChainError::InvalidSignature);
+    }
+    Ok(())
+}
+
+/// Whether `branch` proves `leaf` at `index` of the tree with `root`
+fn is_valid_merkle_branch(leaf: [u8; 32], branch: &[u8], index: u64, root: [u8; 32]) -> bool {
+    let node = branch.chunks(32).enumerate().fold(leaf, |node, (depth, sibling)| {
+        let sibling: [u8; 32] = sibling.try_into().expect("branch of 32-byte nodes");
+        if index >> depth & 1 == 1 {
+            hash_pair(&sibling, &node)
+        } else {
+            hash_pair(&node, &sibling)
+        }
+    });
+    node == root
+}
+
+/// SSZ root of a compressed public key, packed into two chunks
+fn pubkey_root(pubkey: &[u8]) -> [u8; 32] {
+    let mut chunks = [0u8; 64];
+    chunks[..PUBKEY_LEN].copy_from_slice(pubkey);
+    Sha256::digest(chunks).into()
+}
+
+/// SSZ chunk of a `uint64`
+fn uint64(value: u64) -> [u8; 32] {
+    let mut chunk = [0u8; 32];
+    chunk[..8].copy_from_slice(&value.to_le_bytes());
+    chunk
+}
+
+/// SSZ merkleization of `chunks`, padded with zero chunks to a power of two
+fn merkleize(chunks: &[[u8; 32]]) -> [u8; 32] {
+    let mut layer = chunks.to_vec();
+    layer.resize(chunks.len().next_power_of_two(), [0; 32]);
+    while layer.len() > 1 {
+        layer = layer.chunks(2).map(|pair| hash_pair(&pair[0], &pair[1])).collect();
+    }
+    layer[0]
+}
+
+/// SHA-256 of two concatenated nodes
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Reader over a fixed-size SSZ container
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Reader over `bytes`, which must be exactly `len` long
+    fn new(bytes: &'a [u8], len: usize, what: &'static str) -> Result<Self, ChainError> {
+        if bytes.len() != len {
+            return Err(ChainError::Malformed(what));
+        }
+        Ok(Self { bytes })
+    }
+
+    fn bytes(&mut self, len: usize) -> &'a [u8] {
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        bytes
+    }
+
+    fn array<const N: usize>(&mut self) -> [u8; N] {
+        self.bytes(N).try_into().expect("length checked")
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.array())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls12_381::Scalar;
+
+    /// Sign `message` with the aggregate of the secret keys
+    fn sign(secret_keys: impl Iterator<Item = u64>, message: &[u8; 32]) -> [u8; SIGNATURE_LEN] {
+        let secret = secret_keys.map(Scalar::from).fold(Scalar::zero(), |sum, key| sum + key);
+        let hashed = <G2Projective as HashToCurve<ExpandMsgXmd<sha2_v09::Sha256>>>::hash_to_curve(message, BLS_DST);
+        G2Affine::from(hashed * secret).to_compressed()
+    }
+
+    /// Committee whose validator `i` has secret key `i + 1`
+    fn committee() -> Vec<u8> {
+        let mut committee = Vec::with_capacity(SYNC_COMMITTEE_LEN);
+        let mut pubkey = G1Projective::identity();
+        for _ in 0..SYNC_COMMITTEE_SIZE {
+            pubkey += G1Affine::generator();
+            committee.extend_from_slice(&G1Affine::from(pubkey).to_compressed());
+        }
+        committee.extend_from_slice(&G1Affine::from(pubkey).to_compressed());
+        committee
+    }
+
+    /// Merkle branch of `leaf` at `index` in a tree of the given depth whose
+    /// other leaves are zero, and the tree's root
+    fn branch(leaf: [u8; 32], index: u64, depth: usize) -> (Vec<u8>, [u8; 32]) {
+        let mut branch = Vec::with_capacity(depth * 32);
+        let mut node = leaf;
+        let mut zero = [0u8; 32];
+        for level in 0..depth {
+            branch.extend_from_slice(&zero);
+            node = if index >> level & 1 == 1 { hash_pair(&zero, &node) } else { hash_pair(&node, &zero) };
+            zero = hash_pair(&zero, &zero);
+        }
+        (branch, node)
+    }
+
+    #[test]
+    fn test_light_client_update() {
+        let committee = committee();
+        let genesis_validators_root = [0x4b; 32];
+        let fork_version = [4, 0, 0, 0];
+        let finalized = BeaconBlockHeader {
+            slot: 9_000_000,
+            proposer_index: 7,
+            parent_root: [1; 32],
+            state_root: [2; 32],
+            body_root: [3; 32],
+        };
+        let (finality_branch, state_root) = branch(finalized.hash_tree_root(), FINALIZED_ROOT_INDEX, FINALIZED_ROOT_DEPTH);
+        let attested = BeaconBlockHeader { slot: 9_000_064, state_root, ..finalized };
+        let signing_root = hash_pair(&attested.hash_tree_root(), &sync_committee_domain(fork_version, genesis_validators_root));
+
+        let update = |signers: usize| {
+            let mut update = attested.encode().to_vec();
+            update.extend_from_slice(&[0; SYNC_COMMITTEE_LEN + NEXT_SYNC_COMMITTEE_DEPTH * 32]);
+            update.extend_from_slice(&finalized.encode());
+            update.extend_from_slice(&finality_branch);
+            let mut participation = [0u8; SYNC_COMMITTEE_SIZE / 8];
+            for i in 0..signers {
+                participation[i / 8] |= 1 << (i % 8);
+            }
+            update.extend_from_slice(&participation);
+            update.extend_from_slice(&sign(1..=signers as u64, &signing_root));
+            update.extend_from_slice(&9_000_065u64.to_le_bytes());
+            update
+        };
+        let mut witness = BeaconWitness {
+            genesis_validators_root,
+            fork_version,
+            sync_committee: committee.clone(),
+            update: update(400),
+        };
+
+        let statement = verify(&witness).unwrap();
+        assert_eq!(statement.anchor, committee_anchor(&sync_committee_root(&committee), &genesis_validators_root));
+        assert_eq!((statement.number, statement.block_hash), (9_000_000, finalized.hash_tree_root()));
+        assert_eq!(statement.commitment, [0; 32]);
+
+        // Two thirds of the committee is not enough
+        witness.update = update(341);
+        assert!(matches!(verify(&witness), Err(ChainError::InsufficientWeight { signed: 341, total: 512 })));
+
+        // A signature for another fork does not verify
+        witness.update = update(400);
+        witness.fork_version = [5, 0, 0, 0];
+        assert_eq!(verify(&witness), Err(ChainError::InvalidSignature));
+
+        // Neither does a finalized header the attested state does not commit to
+        witness.fork_version = fork_version;
+        witness.update[HEADER_LEN + SYNC_COMMITTEE_LEN + NEXT_SYNC_COMMITTEE_DEPTH * 32] ^= 1;
+        assert!(matches!(verify(&witness), Err(ChainError::InvalidProof(_))));
+    }
+}
@@ -0,0 +1,654 @@
+//! Programs and inputs driving the chain verifier guests
+//!
+//! A [`ChainVerifierCircuit`] is a chain verifier bound to the anchor its verifier
+//! trusts. Its program is a [`ProgramDescriptor`] of the chain's circuit type with
+//! the anchor as parameters; the SP1 backend runs the guest listed for that type in
+//! the program manifest (`eth_verifier`, `beacon_verifier`, `dot_verifier`,
+//! `sol_verifier` or `tm_verifier`, built into `target/sp1`) unless the descriptor
+//! embeds one. The proving input is the encoded [`ChainWitness`].
+//!
+//! A [`BeaconUpdateCircuit`] follows the beacon chain from one sync committee
+//! period to the next, a [`GrandpaCircuit`] verifies Substrate headers finalized by
+//! a GRANDPA authority set and a [`TmHeaderCircuit`] verifies Tendermint headers
+//! committed by a validator set of a chain ID. A [`LogInclusionCircuit`] proves an
+//! event log of a block with the `eth_log_verifier` guest, a [`HeaderChainCircuit`]
+//! proves a block is an ancestor of a trusted block with the `eth_header_chain`
+//! guest, and a [`StateProofCircuit`] proves an Ethereum storage slot under a
+//! trusted state root with the `state_proof` guest.
+
+use serde::{Deserialize, Serialize};
+
+use crate::descriptor::ProgramDescriptor;
+use crate::error::ZkError;
+use crate::journal::{ChainVerifyOutput, StateProofOutput};
+use crate::profiles::{BEACON_VERIFY, DOT_VERIFY, ETH_HEADER_CHAIN, ETH_LOG_VERIFY, ETH_VERIFY, SOL_VERIFY, STATE_PROOF, TM_VERIFY};
+use super::beacon::{self, BeaconWitness};
+use super::dot::{self, DotWitness};
+use super::eth::{self, EthLog, EthLogWitness, EthWitness, HeaderChainWitness};
+use super::sol::SolWitness;
+use super::state::{self, StateProof, StateWitness, StorageKey, StorageStatement};
+use super::tm::{self, TmWitness};
+use super::ChainStatement;
+
+/// Chain with a verifier guest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Chain {
+    /// Ethereum and other EVM chains with Ethereum headers
+    Ethereum,
+    /// Ethereum consensus layer, followed through sync committee updates
+    Beacon,
+    /// Polkadot and other GRANDPA-finalized Substrate chains
+    Polkadot,
+    /// Solana
+    Solana,
+    /// Cosmos and other Tendermint / CometBFT chains
+    Tendermint,
+}
+
+impl Chain {
+    /// Circuit type of the chain's verifier
+    pub fn circuit_type(self) -> u8 {
+        match self {
+            Chain::Ethereum => ETH_VERIFY,
+            Chain::Beacon => BEACON_VERIFY,
+            Chain::Polkadot => DOT_VERIFY,
+            Chain::Solana => SOL_VERIFY,
+            Chain::Tendermint => TM_VERIFY,
+        }
+    }
+
+    /// Chain verified by a circuit type
+    pub fn from_circuit_type(circuit_type: u8) -> Option<Self> {
+        match circuit_type {
+            ETH_VERIFY => Some(Chain::Ethereum),
+            BEACON_VERIFY => Some(Chain::Beacon),
+            DOT_VERIFY => Some(Chain::Polkadot),
+            SOL_VERIFY => Some(Chain::Solana),
+            TM_VERIFY => Some(Chain::Tendermint),
+            _ => None,
+        }
+    }
+
+    /// Name of the chain's guest program
+    pub fn guest_name(self) -> &'static str {
+        match self {
+            Chain::Ethereum => "eth_verifier",
+            Chain::Beacon => "beacon_verifier",
+            Chain::Polkadot => "dot_verifier",
+            Chain::Solana => "sol_verifier",
+            Chain::Tendermint => "tm_verifier",
+        }
+    }
+}
+
+/// Witness read by a chain verifier guest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChainWitness {
+    /// Ethereum receipt inclusion
+    Ethereum(EthWitness),
+    /// Sync committee light-client update
+    Beacon(BeaconWitness),
+    /// GRANDPA finality
+    Polkadot(DotWitness),
+    /// Solana slot confirmation
+    Solana(SolWitness),
+    /// Tendermint header commit
+    Tendermint(TmWitness),
+}
+
+impl ChainWitness {
+    /// Chain the witness is for
+    pub fn chain(&self) -> Chain {
+        match self {
+            ChainWitness::Ethereum(_) => Chain::Ethereum,
+            ChainWitness::Beacon(_) => Chain::Beacon,
+            ChainWitness::Polkadot(_) => Chain::Polkadot,
+            ChainWitness::Solana(_) => Chain::Solana,
+            ChainWitness::Tendermint(_) => Chain::Tendermint,
+        }
+    }
+
+    /// Encode as the proving input of the chain's guest
+    pub fn to_input(&self) -> Vec<u8> {
+        let encoded = match self {
+            ChainWitness::Ethereum(witness) => bincode::serialize(witness),
+            ChainWitness::Beacon(witness) => bincode::serialize(witness),
+            ChainWitness::Polkadot(witness) => bincode::serialize(witness),
+            ChainWitness::Solana(witness) => bincode::serialize(witness),
+            ChainWitness::Tendermint(witness) => bincode::serialize(witness),
+        };
+        encoded.expect("witness serialization cannot fail")
+    }
+
+    /// Check the witness on the host, as the guest will
+    ///
+    /// Useful to reject a witness before spending a proof on it.
+    pub fn verify(&self) -> Result<ChainStatement, ZkError> {
+        let statement = match self {
+            ChainWitness::Ethereum(witness) => eth::verify(witness),
+            ChainWitness::Beacon(witness) => beacon::verify(witness),
+            ChainWitness::Polkadot(witness) => dot::verify(witness),
+            ChainWitness::Solana(witness) => super::sol::verify(witness),
+            ChainWitness::Tendermint(witness) => tm::verify(witness),
+        };
+        statement.map_err(|e| ZkError::InvalidInput(format!("Invalid {:?} witness: {}", self.chain(), e)))
+    }
+}
+
+/// Chain verifier bound to a trusted anchor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainVerifierCircuit {
+    /// Verified chain
+    pub chain: Chain,
+    /// Trusted block hash, sync committee anchor, authority set hash or stake set hash
+    pub anchor: [u8; 32],
+}
+
+impl ChainVerifierCircuit {
+    /// Create a verifier of `chain` trusting `anchor`
+    pub fn new(chain: Chain, anchor: [u8; 32]) -> Self {
+        Self { chain, anchor }
+    }
+
+    /// Descriptor of the verifier's program
+    pub fn descriptor(&self) -> ProgramDescriptor {
+        ProgramDescriptor::new(self.chain.circuit_type(), self.anchor)
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.descriptor().encode()
+    }
+
+    /// Proving input for `witness`, checked to be for this verifier's chain and anchor
+    pub fn input(&self, witness: &ChainWitness) -> Result<Vec<u8>, ZkError> {
+        if witness.chain() != self.chain {
+            return Err(ZkError::InvalidInput(format!(
+                "{:?} witness given to the {:?} verifier",
+                witness.chain(),
+                self.chain
+            )));
+        }
+        if witness.verify()?.anchor != self.anchor {
+            return Err(ZkError::InvalidInput("Witness does not start from the trusted anchor".into()));
+        }
+        Ok(witness.to_input())
+    }
+
+    /// Decode the public values of a proof, rejecting statements from another anchor
+    pub fn output(&self, public_values: &[u8]) -> Result<ChainVerifyOutput, ZkError> {
+        let output = ChainVerifyOutput::decode(public_values)?;
+        if output.statement.anchor != self.anchor {
+            return Err(ZkError::ProofVerification(format!(
+                "Proof starts from anchor {}, expected {}",
+                hex::encode(output.statement.anchor),
+                hex::encode(self.anchor)
+            )));
+        }
+        Ok(output)
+    }
+}
+
+/// Beacon chain verifier trusting a sync committee
+///
+/// Each proof finalizes a beacon block header. When the update also proves the next
+/// sync committee, [`BeaconUpdateCircuit::next`] gives the circuit verifying the
+/// updates of the following period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeaconUpdateCircuit {
+    /// Genesis validators root of the chain
+    pub genesis_validators_root: [u8; 32],
+    /// SSZ root of the trusted sync committee
+    pub sync_committee_root: [u8; 32],
+}
+
+impl BeaconUpdateCircuit {
+    /// Create a verifier of updates signed by the sync committee with `sync_committee_root`
+    pub fn new(genesis_validators_root: [u8; 32], sync_committee_root: [u8; 32]) -> Self {
+        Self { genesis_validators_root, sync_committee_root }
+    }
+
+    /// Chain verifier with the committee's anchor
+    pub fn verifier(&self) -> ChainVerifierCircuit {
+        ChainVerifierCircuit::new(
+            Chain::Beacon,
+            beacon::committee_anchor(&self.sync_committee_root, &self.genesis_validators_root),
+        )
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.verifier().program()
+    }
+
+    /// Proving input for a light-client update
+    pub fn input(&self, witness: BeaconWitness) -> Result<Vec<u8>, ZkError> {
+        self.verifier().input(&ChainWitness::Beacon(witness))
+    }
+
+    /// Decode the public values of a proof, rejecting updates of another committee
+    pub fn output(&self, public_values: &[u8]) -> Result<ChainVerifyOutput, ZkError> {
+        self.verifier().output(public_values)
+    }
+
+    /// Verifier of the next period, if the proven update rotates the committee
+    pub fn next(&self, output: &ChainVerifyOutput) -> Option<Self> {
+        let next = output.statement.commitment;
+        (next != [0; 32]).then(|| Self::new(self.genesis_validators_root, next))
+    }
+}
+
+/// Substrate finality verifier trusting a GRANDPA authority set
+///
+/// Each proof shows a header was finalized by a justification of the authority set,
+/// and commits its state root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrandpaCircuit {
+    /// Hash of the trusted authority set
+    pub authority_set_hash: [u8; 32],
+}
+
+impl GrandpaCircuit {
+    /// Create a verifier of headers finalized by the authority set `set_id` with
+    /// Ed25519 keys and voting weights `authorities`
+    pub fn new(set_id: u64, authorities: &[([u8; 32], u64)]) -> Self {
+        Self { authority_set_hash: dot::authority_set_hash(set_id, authorities) }
+    }
+
+    /// Chain verifier with the authority set's anchor
+    pub fn verifier(&self) -> ChainVerifierCircuit {
+        ChainVerifierCircuit::new(Chain::Polkadot, self.authority_set_hash)
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.verifier().program()
+    }
+
+    /// Proving input for a header, the authority set and the header's justification
+    pub fn input(&self, witness: DotWitness) -> Result<Vec<u8>, ZkError> {
+        self.verifier().input(&ChainWitness::Polkadot(witness))
+    }
+
+    /// Decode the public values of a proof, rejecting headers finalized by another
+    /// authority set
+    pub fn output(&self, public_values: &[u8]) -> Result<ChainVerifyOutput, ZkError> {
+        self.verifier().output(public_values)
+    }
+}
+
+/// Tendermint header verifier trusting a validator set of a chain
+///
+/// Each proof shows a header of the chain was committed by the validator set, and
+/// commits its app hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmHeaderCircuit {
+    /// Chain ID
+    pub chain_id: String,
+    /// Hash of the trusted validator set
+    pub validators_hash: [u8; 32],
+}
+
+impl TmHeaderCircuit {
+    /// Create a verifier of headers of `chain_id` committed by the validator set with
+    /// `validators_hash`
+    pub fn new(chain_id: impl Into<String>, validators_hash: [u8; 32]) -> Self {
+        Self { chain_id: chain_id.into(), validators_hash }
+    }
+
+    /// Chain verifier with the validator set's anchor
+    pub fn verifier(&self) -> ChainVerifierCircuit {
+        ChainVerifierCircuit::new(Chain::Tendermint, tm::validator_set_anchor(&self.validators_hash, &self.chain_id))
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.verifier().program()
+    }
+
+    /// Proving input for a header, its validator set and its commit
+    pub fn input(&self, witness: TmWitness) -> Result<Vec<u8>, ZkError> {
+        self.verifier().input(&ChainWitness::Tendermint(witness))
+    }
+
+    /// Decode the public values of a proof, rejecting headers of another chain or
+    /// validator set
+    pub fn output(&self, public_values: &[u8]) -> Result<ChainVerifyOutput, ZkError> {
+        self.verifier().output(public_values)
+    }
+}
+
+/// Ethereum event log inclusion proven against a trusted block hash
+///
+/// The statement commits to the [`EthLog::hash`] of the log, so a verifier
+/// expecting a given event checks it with [`LogInclusionCircuit::output_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogInclusionCircuit {
+    /// Trusted block hash
+    pub block_hash: [u8; 32],
+}
+
+impl LogInclusionCircuit {
+    /// Create a verifier of logs of the block with `block_hash`
+    pub fn new(block_hash: [u8; 32]) -> Self {
+        Self { block_hash }
+    }
+
+    /// Descriptor of the log inclusion program for the block
+    pub fn descriptor(&self) -> ProgramDescriptor {
+        ProgramDescriptor::new(ETH_LOG_VERIFY, self.block_hash)
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.descriptor().encode()
+    }
+
+    /// Proving input for `witness`, checked to prove a log of the trusted block
+    pub fn input(&self, witness: &EthLogWitness) -> Result<Vec<u8>, ZkError> {
+        let statement = eth::verify_log(witness)
+            .map_err(|e| ZkError::InvalidInput(format!("Invalid log inclusion witness: {}", e)))?;
+        if statement.anchor != self.block_hash {
+            return Err(ZkError::InvalidInput("Witness is not for the trusted block".into()));
+        }
+        Ok(bincode::serialize(witness).expect("witness serialization cannot fail"))
+    }
+
+    /// Decode the public values of a proof, rejecting logs of another block
+    pub fn output(&self, public_values: &[u8]) -> Result<ChainVerifyOutput, ZkError> {
+        ChainVerifierCircuit::new(Chain::Ethereum, self.block_hash).output(public_values)
+    }
+
+    /// Decode the public values of a proof, rejecting proofs of any log but `log`
+    pub fn output_for(&self, public_values: &[u8], log: &EthLog) -> Result<ChainVerifyOutput, ZkError> {
+        let output = self.output(public_values)?;
+        if output.statement.commitment != log.hash() {
+            return Err(ZkError::ProofVerification("Proof is for another log".into()));
+        }
+        Ok(output)
+    }
+}
+
+/// Ethereum block proven to be an ancestor of a trusted block
+///
+/// The witness is the headers from the ancestor up to the trusted block. The
+/// statement gives the ancestor's number and hash, and commits to its state root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderChainCircuit {
+    /// Hash of the trusted, most recent block
+    pub head_hash: [u8; 32],
+}
+
+impl HeaderChainCircuit {
+    /// Create a verifier of ancestors of the block with `head_hash`
+    pub fn new(head_hash: [u8; 32]) -> Self {
+        Self { head_hash }
+    }
+
+    /// Descriptor of the header chain program for the head block
+    pub fn descriptor(&self) -> ProgramDescriptor {
+        ProgramDescriptor::new(ETH_HEADER_CHAIN, self.head_hash)
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.descriptor().encode()
+    }
+
+    /// Proving input for `witness`, checked to link up to the trusted block
+    pub fn input(&self, witness: &HeaderChainWitness) -> Result<Vec<u8>, ZkError> {
+        let statement = eth::verify_header_chain(witness)
+            .map_err(|e| ZkError::InvalidInput(format!("Invalid header chain witness: {}", e)))?;
+        if statement.anchor != self.head_hash {
+            return Err(ZkError::InvalidInput("Header chain does not end at the trusted block".into()));
+        }
+        Ok(bincode::serialize(witness).expect("witness serialization cannot fail"))
+    }
+
+    /// Decode the public values of a proof, rejecting ancestors of another block
+    pub fn output(&self, public_values: &[u8]) -> Result<ChainVerifyOutput, ZkError> {
+        ChainVerifierCircuit::new(Chain::Ethereum, self.head_hash).output(public_values)
+    }
+
+    /// Decode the public values of a proof, rejecting proofs of any ancestor but the
+    /// block with `block_hash`
+    pub fn output_for(&self, public_values: &[u8], block_hash: &[u8; 32]) -> Result<ChainVerifyOutput, ZkError> {
+        let output = self.output(public_values)?;
+        if output.statement.block_hash != *block_hash {
+            return Err(ZkError::ProofVerification(format!(
+                "Proof is for block {}, expected {}",
+                hex::encode(output.statement.block_hash),
+                hex::encode(block_hash)
+            )));
+        }
+        Ok(output)
+    }
+}
+
+/// Ethereum storage slot proven against a trusted state root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateProofCircuit {
+    /// Trusted state root
+    pub root: [u8; 32],
+    /// Account and slot to prove
+    pub key: StorageKey,
+    /// `eth_getProof` nodes from the root to the slot
+    pub proof_nodes: StateProof,
+}
+
+impl StateProofCircuit {
+    /// Create a proof of `key` under `root`
+    pub fn new(root: [u8; 32], key: StorageKey, proof_nodes: StateProof) -> Self {
+        Self { root, key, proof_nodes }
+    }
+
+    /// Descriptor of the state proof program for the root
+    pub fn descriptor(&self) -> ProgramDescriptor {
+        ProgramDescriptor::new(STATE_PROOF, self.root)
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.descriptor().encode()
+    }
+
+    /// Check the proof on the host, as the guest will
+    pub fn verify(&self) -> Result<StorageStatement, ZkError> {
+        state::verify(&self.witness())
+            .map_err(|e| ZkError::InvalidInput(format!("Invalid state proof: {}", e)))
+    }
+
+    /// Proving input, checked to prove the slot
+    pub fn input(&self) -> Result<Vec<u8>, ZkError> {
+        self.verify()?;
+        Ok(bincode::serialize(&self.witness()).expect("witness serialization cannot fail"))
+    }
+
+    /// Decode the public values of a proof, rejecting proofs of another root or slot
+    pub fn output(&self, public_values: &[u8]) -> Result<StateProofOutput, ZkError> {
+        let output = StateProofOutput::decode(public_values)?;
+        let statement = &output.statement;
+        if statement.state_root != self.root {
+            return Err(ZkError::ProofVerification(format!(
+                "Proof is under state root {}, expected {}",
+                hex::encode(statement.state_root),
+                hex::encode(self.root)
+            )));
+        }
+        if statement.address != self.key.address || statement.slot != self.key.slot {
+            return Err(ZkError::ProofVerification("Proof is for another storage slot".into()));
+        }
+        Ok(output)
+    }
+
+    fn witness(&self) -> StateWitness {
+        StateWitness {
+            state_root: self.root,
+            key: self.key,
+            proof: self.proof_nodes.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::eth::keccak256;
+    use crate::codec::rlp::{self, RlpItem};
+
+    #[test]
+    fn test_eth_verifier_circuit() {
+        let leaf = rlp::encode(&RlpItem::List(vec![
+            RlpItem::Bytes(vec![0x20, 0x80]),
+            RlpItem::Bytes(vec![0x02; 120]),
+        ]));
+        let mut fields = vec![RlpItem::Bytes(vec![0; 32]); 15];
+        fields[5] = RlpItem::Bytes(keccak256(&leaf).to_vec());
+        fields[8] = RlpItem::uint(19_000_000);
+        fields[9] = RlpItem::uint(30_000_000);
+        let header = rlp::encode(&RlpItem::List(fields));
+        let witness = ChainWitness::Ethereum(EthWitness {
+            header: header.clone(),
+            tx_index: 0,
+            receipt_proof: vec![leaf],
+        });
+
+        let circuit = ChainVerifierCircuit::new(Chain::Ethereum, keccak256(&header));
+        assert_eq!(ProgramDescriptor::decode(&circuit.program()).unwrap().circuit_type, ETH_VERIFY);
+        assert!(circuit.input(&witness).is_ok());
+        assert!(ChainVerifierCircuit::new(Chain::Ethereum, [0; 32]).input(&witness).is_err());
+        assert!(ChainVerifierCircuit::new(Chain::Solana, keccak256(&header)).input(&witness).is_err());
+
+        // Public values committing another anchor are rejected
+        let statement = witness.verify().unwrap();
+        assert_eq!(circuit.output(&statement.to_bytes()).unwrap().statement, statement);
+        let other = ChainStatement { anchor: [0; 32], ..statement };
+        assert!(circuit.output(&other.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_log_inclusion_circuit() {
+        let log = EthLog { address: [0xAA; 20], topics: vec![[0x01; 32]], data: Vec::new() };
+        let circuit = LogInclusionCircuit::new([0x42; 32]);
+        assert_eq!(ProgramDescriptor::decode(&circuit.program()).unwrap().circuit_type, ETH_LOG_VERIFY);
+
+        let statement = ChainStatement {
+            anchor: [0x42; 32],
+            number: 19_000_000,
+            block_hash: [0x42; 32],
+            commitment: log.hash(),
+        };
+        assert!(circuit.output_for(&statement.to_bytes(), &log).is_ok());
+        let other = EthLog { data: vec![1], ..log.clone() };
+        assert!(circuit.output_for(&statement.to_bytes(), &other).is_err());
+        assert!(LogInclusionCircuit::new([0; 32]).output(&statement.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_state_proof_circuit() {
+        // A state trie whose root branch is empty holds no account, so every slot is zero
+        let branch = rlp::encode(&RlpItem::List(vec![RlpItem::Bytes(Vec::new()); 17]));
+        let key = StorageKey { address: [0xAB; 20], slot: [1; 32] };
+        let circuit = StateProofCircuit::new(keccak256(&branch), key, StateProof {
+            account_proof: vec![branch],
+            storage_proof: Vec::new(),
+        });
+        let statement = circuit.verify().unwrap();
+        assert_eq!(statement.value, [0; 32]);
+        assert!(circuit.input().is_ok());
+        assert!(circuit.output(&statement.to_bytes()).is_ok());
+        assert!(StateProofCircuit { root: [0; 32], ..circuit.clone() }.input().is_err());
+
+        // Public values for another slot are rejected
+        let other = StorageStatement { slot: [2; 32], ..statement };
+        assert!(circuit.output(&other.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_beacon_update_circuit() {
+        let circuit = BeaconUpdateCircuit::new([0x4b; 32], [0x5c; 32]);
+        assert_eq!(ProgramDescriptor::decode(&circuit.program()).unwrap().circuit_type, BEACON_VERIFY);
+
+        // An update rotating the committee leads to the next period's verifier
+        let statement = ChainStatement {
+            anchor: beacon::committee_anchor(&[0x5c; 32], &[0x4b; 32]),
+            number: 9_000_000,
+            block_hash: [1; 32],
+            commitment: [0x6d; 32],
+        };
+        let output = circuit.output(&statement.to_bytes()).unwrap();
+        let next = circuit.next(&output).unwrap();
+        assert_eq!(next, BeaconUpdateCircuit::new([0x4b; 32], [0x6d; 32]));
+        assert!(next.output(&statement.to_bytes()).is_err());
+
+        let final_update = ChainStatement { commitment: [0; 32], ..statement };
+        assert_eq!(circuit.next(&circuit.output(&final_update.to_bytes()).unwrap()), None);
+    }
+    #[test]
+    fn test_header_chain_circuit() {
+        let header = |parent_hash: [u8; 32], number: u64| {
+            let mut fields = vec![RlpItem::Bytes(vec![0; 32]); 15];
+            fields[0] = RlpItem::Bytes(parent_hash.to_vec());
+            fields[8] = RlpItem::uint(number);
+            fields[9] = RlpItem::uint(30_000_000);
+            fields[11] = RlpItem::uint(1_700_000_000 + 12 * number);
+            rlp::encode(&RlpItem::List(fields))
+        };
+        let ancestor = header([0; 32], 1);
+        let head = header(keccak256(&ancestor), 2);
+        let witness = HeaderChainWitness { headers: vec![ancestor.clone(), head.clone()] };
+
+        let circuit = HeaderChainCircuit::new(keccak256(&head));
+        assert_eq!(ProgramDescriptor::decode(&circuit.program()).unwrap().circuit_type, ETH_HEADER_CHAIN);
+        assert!(circuit.input(&witness).is_ok());
+        assert!(HeaderChainCircuit::new(keccak256(&ancestor)).input(&witness).is_err());
+
+        // Public values of another head or ancestor are rejected
+        let statement = eth::verify_header_chain(&witness).unwrap();
+        assert!(circuit.output_for(&statement.to_bytes(), &keccak256(&ancestor)).is_ok());
+        assert!(circuit.output_for(&statement.to_bytes(), &keccak256(&head)).is_err());
+        assert!(HeaderChainCircuit::new([0; 32]).output(&statement.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_grandpa_circuit() {
+        let authorities = vec![([0x11; 32], 1), ([0x22; 32], 1)];
+        let circuit = GrandpaCircuit::new(3, &authorities);
+        assert_eq!(ProgramDescriptor::decode(&circuit.program()).unwrap().circuit_type, DOT_VERIFY);
+
+        // A justification without precommits does not finalize the header
+        let header = [[0xAA; 32].as_slice(), &[0x04], &[0xBB; 32], &[0xCC; 32], &[0]].concat();
+        let mut justification = 7u64.to_le_bytes().to_vec();
+        justification.extend_from_slice(&dot::blake2_256(&header));
+        justification.extend_from_slice(&1u32.to_le_bytes());
+        justification.extend_from_slice(&[0, 0]); // No precommits or vote ancestries
+        let witness = DotWitness { header, set_id: 3, authorities: authorities.clone(), justification };
+        assert!(circuit.input(witness).is_err());
+
+        let statement = ChainStatement {
+            anchor: dot::authority_set_hash(3, &authorities),
+            number: 1,
+            block_hash: [1; 32],
+            commitment: [0xBB; 32],
+        };
+        assert!(circuit.output(&statement.to_bytes()).is_ok());
+        assert!(GrandpaCircuit::new(4, &authorities).output(&statement.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_tm_header_circuit() {
+        let circuit = TmHeaderCircuit::new("cosmoshub-4", [0x7e; 32]);
+        assert_eq!(ProgramDescriptor::decode(&circuit.program()).unwrap().circuit_type, TM_VERIFY);
+
+        let statement = ChainStatement {
+            anchor: tm::validator_set_anchor(&[0x7e; 32], "cosmoshub-4"),
+            number: 20_000_000,
+            block_hash: [1; 32],
+            commitment: [2; 32],
+        };
+        assert!(circuit.output(&statement.to_bytes()).is_ok());
+
+        // The same validator set on another chain is another anchor
+        assert!(TmHeaderCircuit::new("theta-testnet-001", [0x7e; 32]).output(&statement.to_bytes()).is_err());
+    }
+}
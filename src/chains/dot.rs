@@ -0,0 +1,313 @@
+//! Substrate finality through GRANDPA justifications
+//!
+//! The witness is a SCALE-encoded header, the GRANDPA authority set and the
+//! SCALE-encoded justification finalizing the header, as returned by the
+//! `grandpa_proveFinality` RPC or stored with the block. The guest decodes the
+//! justification itself, so hosts pass it through untouched. The anchor is the hash
+//! of the authority set, so a proof shows the header was finalized by the set the
+//! verifier trusts. Authority sets are hashed as
+//! `blake2b_256(set_id (u64 LE) ++ for each authority: key (32) ++ weight (u64 LE))`.
+//!
+//! Only precommits for the header itself are counted. Justifications reaching the
+//! threshold through precommits for descendants of the header are rejected, so
+//! their vote ancestries are decoded but not used.
+
+use alloc::vec::Vec;
+use blake2::{Blake2b, Digest, digest::consts::U32};
+use serde::{Deserialize, Serialize};
+
+use crate::codec::scale;
+use super::{supermajority, verify_ed25519, ChainError, ChainStatement};
+
+/// Precommit signed by a GRANDPA authority
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Precommit {
+    /// Hash of the block voted for
+    pub target_hash: [u8; 32],
+    /// Number of the block voted for
+    pub target_number: u32,
+    /// Ed25519 key of the authority
+    pub authority: [u8; 32],
+    /// Ed25519 signature over the precommit message
+    pub signature: Vec<u8>,
+}
+
+/// GRANDPA finality witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DotWitness {
+    /// SCALE-encoded block header
+    pub header: Vec<u8>,
+    /// Identifier of the authority set
+    pub set_id: u64,
+    /// Ed25519 keys and voting weights of the authorities
+    pub authorities: Vec<([u8; 32], u64)>,
+    /// SCALE-encoded GRANDPA justification of the header
+    pub justification: Vec<u8>,
+}
+
+/// Decoded GRANDPA justification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Justification {
+    /// Round the commit was made in
+    pub round: u64,
+    /// Hash of the committed block
+    pub target_hash: [u8; 32],
+    /// Number of the committed block
+    pub target_number: u32,
+    /// Signed precommits of the commit
+    pub precommits: Vec<Precommit>,
+}
+
+/// Verify that the header is finalized by the authority set
+pub fn verify(witness: &DotWitness) -> Result<ChainStatement, ChainError> {
+    let (number, state_root) = parse_header(&witness.header)?;
+    let block_hash = blake2_256(&witness.header);
+    let justification = decode_justification(&witness.justification)?;
+    if justification.target_hash != block_hash || justification.target_number != number {
+        return Err(ChainError::Malformed("justification target"));
+    }
+
+    let total = witness.authorities.iter()
+        .try_fold(0u64, |total, (_, weight)| total.checked_add(*weight))
+        .ok_or(ChainError::Malformed("authority weights"))?;
+    let mut voted = Vec::with_capacity(justification.precommits.len());
+    let mut signed = 0u64;
+    for precommit in &justification.precommits {
+        if precommit.target_hash != block_hash || precommit.target_number != number {
+            continue;
+        }
+        let weight = match witness.authorities.iter().find(|(key, _)| *key == precommit.authority) {
+            Some((_, weight)) => *weight,
+            None => continue,
+        };
+        // An authority's weight counts once, however many precommits it signed
+        if voted.contains(&precommit.authority) {
+            continue;
+        }
+        let message = precommit_message(precommit, justification.round, witness.set_id);
+        verify_ed25519(&precommit.authority, &message, &precommit.signature)?;
+        voted.push(precommit.authority);
+        signed += weight;
+    }
+    supermajority(signed, total)?;
+
+    Ok(ChainStatement {
+        anchor: authority_set_hash(witness.set_id, &witness.authorities),
+        number: u64::from(number),
+        block_hash,
+        commitment: state_root,
+    })
+}
+
+/// Decode a justification: `round (u64) ++ commit ++ votes_ancestries (Vec<Header>)`,
+/// where the commit is `target_hash ++ target_number (u32) ++ Vec<SignedPrecommit>` and
+/// a signed precommit is `target_hash ++ target_number ++ signature (64) ++ authority (32)`
+pub fn decode_justification(bytes: &[u8]) -> Result<Justification, ChainError> {
+    let mut cursor = Cursor { bytes, offset: 0 };
+    let round = cursor.u64()?;
+    let target_hash = cursor.array()?;
+    let target_number = cursor.u32()?;
+    let count = cursor.len()?;
+    let mut precommits = Vec::with_capacity(count.min(bytes.len() / 132));
+    for _ in 0..count {
+        precommits.push(Precommit {
+            target_hash: cursor.array()?,
+            target_number: cursor.u32()?,
+            signature: cursor.take(64)?.to_vec(),
+            authority: cursor.array()?,
+        });
+    }
+    for _ in 0..cursor.len()? {
+        cursor.skip_header()?;
+    }
+    if cursor.offset != bytes.len() {
+        return Err(ChainError::Malformed("justification"));
+    }
+    Ok(Justification { round, target_hash, target_number, precommits })
+}
+
+/// Hash of an authority set, the anchor of Substrate proofs
+pub fn authority_set_hash(set_id: u64, authorities: &[([u8; 32], u64)]) -> [u8; 32] {
+    let mut hasher = Blake2b::<U32>::new();
+    hasher.update(set_id.to_le_bytes());
+    for (key, weight) in authorities {
+        hasher.update(key);
+        hasher.update(weight.to_le_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Message an authority signs for a precommit: the SCALE encoding of
+/// `(Message::Precommit(precommit), round, set_id)`
+pub fn precommit_message(precommit: &Precommit, round: u64, set_id: u64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(53);
+    message.push(1); // Message::Precommit
+    message.extend_from_slice(&precommit.target_hash);
+    message.extend_from_slice(&precommit.target_number.to_le_bytes());
+    message.extend_from_slice(&round.to_le_bytes());
+    message.extend_from_slice(&set_id.to_le_bytes());
+    message
+}
+
+/// Blake2b-256 of `bytes`, the Substrate block hash
+pub fn blake2_256(bytes: &[u8]) -> [u8; 32] {
+    Blake2b::<U32>::digest(bytes).into()
+}
+
+/// Number and state root of a header: parent hash (32), compact number, state root (32), ...
+fn parse_header(header: &[u8]) -> Result<(u32, [u8; 32]), ChainError> {
+    let rest = header.get(32..).ok_or(ChainError::Malformed("header"))?;
+    let (number, len) = scale::decode_compact(rest).map_err(|_| ChainError::Malformed("header"))?;
+    let number = u32::try_from(number).map_err(|_| ChainError::Malformed("block number"))?;
+    let state_root = rest.get(len..len + 32)
+        .and_then(|root| root.try_into().ok())
+        .ok_or(ChainError::Malformed("header"))?;
+    Ok((number, state_root))
+}
+
+/// Cursor over a SCALE-encoded justification
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ChainError> {
+        let bytes = self.offset.checked_add(len)
+            .and_then(|end| self.bytes.get(self.offset..end))
+            .ok_or(ChainError::Malformed("justification"))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ChainError> {
+        Ok(self.take(N)?.try_into().expect("length checked"))
+    }
+
+    fn u32(&mut self) -> Result<u32, ChainError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, ChainError> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    /// Compact length prefix of a vector
+    fn len(&mut self) -> Result<usize, ChainError> {
+        let rest = self.bytes.get(self.offset..).unwrap_or_default();
+        let (len, read) = scale::decode_compact(rest).map_err(|_| ChainError::Malformed("justification"))?;
+        self.offset += read;
+        usize::try_from(len).map_err(|_| ChainError::Malformed("justification"))
+    }
+
+    /// Header of a vote ancestry: parent hash, compact number, state root, extrinsics
+    /// root and digest items
+    fn skip_header(&mut self) -> Result<(), ChainError> {
+        self.take(32)?;
+        self.len()?;
+        self.take(64)?;
+        for _ in 0..self.len()? {
+            match self.take(1)?[0] {
+                // Consensus, Seal and PreRuntime: engine ID and payload
+                4..=6 => {
+                    self.take(4)?;
+                    let len = self.len()?;
+                    self.take(len)?;
+                }
+                // Other: payload
+                0 => {
+                    let len = self.len()?;
+                    self.take(len)?;
+                }
+                // RuntimeEnvironmentUpdated
+                8 => {}
+                _ => return Err(ChainError::Malformed("justification")),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use parity_scale_codec::Encode;
+
+    fn header(digest: &[u8]) -> Vec<u8> {
+        let mut header = vec![0xAA; 32];
+        header.extend_from_slice(&scale::encode_compact(20_000_000));
+        header.extend_from_slice(&[0xBB; 32]);
+        header.extend_from_slice(&[0xCC; 32]);
+        header.extend_from_slice(digest);
+        header
+    }
+
+    /// Justification as Substrate encodes it, with one vote ancestry
+    fn justification(round: u64, target_hash: [u8; 32], precommits: &[Precommit]) -> Vec<u8> {
+        let signed: Vec<_> = precommits.iter()
+            .map(|p| ((p.target_hash, p.target_number), <[u8; 64]>::try_from(&p.signature[..]).unwrap(), p.authority))
+            .collect();
+        let mut bytes = (round, (target_hash, 20_000_000u32, signed)).encode();
+        bytes.extend_from_slice(&scale::encode_compact(1));
+        let mut digest = scale::encode_compact(2);
+        digest.push(6); // PreRuntime
+        digest.extend_from_slice(b"BABE");
+        digest.extend_from_slice(&scale::encode_bytes(&[1, 2, 3]));
+        digest.push(8); // RuntimeEnvironmentUpdated
+        bytes.extend_from_slice(&header(&digest));
+        bytes
+    }
+
+    #[test]
+    fn test_grandpa_finality() {
+        let keys: Vec<SigningKey> = (1..=4u8).map(|seed| SigningKey::from_bytes(&[seed; 32])).collect();
+        let authorities: Vec<_> = keys.iter().map(|key| (key.verifying_key().to_bytes(), 1)).collect();
+
+        let header = header(&[0]); // Empty digest
+        let block_hash = blake2_256(&header);
+
+        let precommit = |key: &SigningKey| {
+            let mut precommit = Precommit {
+                target_hash: block_hash,
+                target_number: 20_000_000,
+                authority: key.verifying_key().to_bytes(),
+                signature: Vec::new(),
+            };
+            precommit.signature = key.sign(&precommit_message(&precommit, 7, 3)).to_bytes().to_vec();
+            precommit
+        };
+        let mut precommits: Vec<_> = keys[..3].iter().map(precommit).collect();
+        let mut witness = DotWitness {
+            header,
+            set_id: 3,
+            authorities: authorities.clone(),
+            justification: justification(7, block_hash, &precommits),
+        };
+
+        let decoded = decode_justification(&witness.justification).unwrap();
+        assert_eq!((decoded.round, decoded.target_number), (7, 20_000_000));
+        assert_eq!(decoded.precommits, precommits);
+
+        let statement = verify(&witness).unwrap();
+        assert_eq!(statement.anchor, authority_set_hash(3, &authorities));
+        assert_eq!(statement.number, 20_000_000);
+        assert_eq!(statement.block_hash, block_hash);
+        assert_eq!(statement.commitment, [0xBB; 32]);
+
+        // Truncated justifications and commits to another block are malformed
+        let truncated = &witness.justification[..witness.justification.len() - 1];
+        assert_eq!(decode_justification(truncated), Err(ChainError::Malformed("justification")));
+        witness.justification = justification(7, [0xDD; 32], &precommits);
+        assert_eq!(verify(&witness), Err(ChainError::Malformed("justification target")));
+
+        // Duplicate precommits do not add weight, and two of four is not enough
+        precommits[2] = precommits[0].clone();
+        witness.justification = justification(7, block_hash, &precommits);
+        assert!(matches!(verify(&witness), Err(ChainError::InsufficientWeight { signed: 2, total: 4 })));
+
+        // A signature for another round is invalid
+        witness.justification = justification(8, block_hash, &precommits);
+        assert_eq!(verify(&witness), Err(ChainError::InvalidSignature));
+    }
+}
@@ -0,0 +1,478 @@
+//! Ethereum receipt and event log inclusion, and header chains
+//!
+//! The witness is an RLP-encoded block header and the receipts trie nodes on the
+//! path to one receipt. The anchor is the Keccak-256 hash of the header, i.e. the
+//! block hash, so a proof shows the receipt (and the logs it carries) is part of
+//! the block the verifier trusts. [`verify_log`] goes on to pick one event log of
+//! the receipt, committing to its [`EthLog::hash`] instead of the receipt's hash.
+//!
+//! [`verify_header_chain`] proves a run of consecutive headers links up to the last
+//! one, so a single proof shows a block is an ancestor of a trusted (e.g. finalized)
+//! block. Its anchor is the hash of the last header.
+//!
+//! [`EthHeader`] decodes the fields of a header the circuits check, and is also used
+//! by the RISC0 header verification guest.
+
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::codec::rlp::{self, RlpItem};
+use super::{ChainError, ChainStatement};
+
+/// Index of the parent hash in a header
+const PARENT_HASH: usize = 0;
+
+/// Index of the state root in a header
+const STATE_ROOT: usize = 3;
+
+/// Index of the transactions root in a header
+const TRANSACTIONS_ROOT: usize = 4;
+
+/// Index of the receipts root in a header
+const RECEIPTS_ROOT: usize = 5;
+
+/// Index of the block number in a header
+const NUMBER: usize = 8;
+
+/// Index of the gas limit in a header
+const GAS_LIMIT: usize = 9;
+
+/// Index of the gas used in a header
+const GAS_USED: usize = 10;
+
+/// Index of the timestamp in a header
+const TIMESTAMP: usize = 11;
+
+/// Number of fields of a pre-London header; later forks append fields
+const MIN_FIELDS: usize = 15;
+
+/// Fields of an Ethereum block header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthHeader {
+    /// Keccak-256 of the RLP-encoded header, the block hash
+    pub hash: [u8; 32],
+    /// Hash of the parent block
+    pub parent_hash: [u8; 32],
+    /// State trie root
+    pub state_root: [u8; 32],
+    /// Transactions trie root
+    pub transactions_root: [u8; 32],
+    /// Receipts trie root
+    pub receipts_root: [u8; 32],
+    /// Block number
+    pub number: u64,
+    /// Gas limit of the block
+    pub gas_limit: u64,
+    /// Gas used by the block
+    pub gas_used: u64,
+    /// Block timestamp in seconds
+    pub timestamp: u64,
+}
+
+impl EthHeader {
+    /// Decode an RLP-encoded header and check its gas used is within its gas limit
+    pub fn decode(header: &[u8]) -> Result<Self, ChainError> {
+        let item = rlp::decode(header).map_err(|_| ChainError::Malformed("header"))?;
+        let fields = item.as_list()
+            .filter(|fields| fields.len() >= MIN_FIELDS)
+            .ok_or(ChainError::Malformed("header"))?;
+        let hash = |index: usize, what: &'static str| -> Result<[u8; 32], ChainError> {
+            fields[index].as_bytes()
+                .and_then(|hash| hash.try_into().ok())
+                .ok_or(ChainError::Malformed(what))
+        };
+        let uint = |index: usize, what: &'static str| fields[index].as_u64().ok_or(ChainError::Malformed(what));
+
+        let decoded = Self {
+            hash: keccak256(header),
+            parent_hash: hash(PARENT_HASH, "parent hash")?,
+            state_root: hash(STATE_ROOT, "state root")?,
+            transactions_root: hash(TRANSACTIONS_ROOT, "transactions root")?,
+            receipts_root: hash(RECEIPTS_ROOT, "receipts root")?,
+            number: uint(NUMBER, "block number")?,
+            gas_limit: uint(GAS_LIMIT, "gas limit")?,
+            gas_used: uint(GAS_USED, "gas used")?,
+            timestamp: uint(TIMESTAMP, "timestamp")?,
+        };
+        if decoded.gas_used > decoded.gas_limit {
+            return Err(ChainError::Malformed("gas used exceeds gas limit"));
+        }
+        Ok(decoded)
+    }
+}
+
+/// Ethereum receipt inclusion witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthWitness {
+    /// RLP-encoded block header
+    pub header: Vec<u8>,
+    /// Index of the transaction whose receipt is proven
+    pub tx_index: u64,
+    /// Receipts trie nodes on the path from the root to the receipt
+    pub receipt_proof: Vec<Vec<u8>>,
+}
+
+/// Event log emitted by a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthLog {
+    /// Address of the emitting contract
+    pub address: [u8; 20],
+    /// Indexed topics, the event signature hash first
+    pub topics: Vec<[u8; 32]>,
+    /// Non-indexed event data
+    pub data: Vec<u8>,
+}
+
+impl EthLog {
+    /// Keccak-256 of the RLP-encoded log, committed by log inclusion proofs
+    pub fn hash(&self) -> [u8; 32] {
+        let topics = self.topics.iter().map(|topic| RlpItem::Bytes(topic.to_vec())).collect();
+        keccak256(&rlp::encode(&RlpItem::List(vec![
+            RlpItem::Bytes(self.address.to_vec()),
+            RlpItem::List(topics),
+            RlpItem::Bytes(self.data.clone()),
+        ])))
+    }
+
+    /// Decode a log from its RLP item
+    fn from_item(item: &RlpItem) -> Result<Self, ChainError> {
+        let fields = item.as_list()
+            .filter(|fields| fields.len() == 3)
+            .ok_or(ChainError::Malformed("log"))?;
+        let address = fields[0].as_bytes()
+            .and_then(|address| address.try_into().ok())
+            .ok_or(ChainError::Malformed("log address"))?;
+        let topics = fields[1].as_list()
+            .ok_or(ChainError::Malformed("log topics"))?
+            .iter()
+            .map(|topic| topic.as_bytes().and_then(|topic| topic.try_into().ok()))
+            .collect::<Option<_>>()
+            .ok_or(ChainError::Malformed("log topics"))?;
+        let data = fields[2].as_bytes().ok_or(ChainError::Malformed("log data"))?.to_vec();
+        Ok(Self { address, topics, data })
+    }
+}
+
+/// Ethereum event log inclusion witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthLogWitness {
+    /// Inclusion of the receipt carrying the log
+    pub receipt: EthWitness,
+    /// Index of the log in the receipt
+    pub log_index: u64,
+}
+
+/// Consecutive Ethereum headers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderChainWitness {
+    /// RLP-encoded headers, oldest first
+    pub headers: Vec<Vec<u8>>,
+}
+
+/// Verify that the receipt at `tx_index` is included in the header's block
+pub fn verify(witness: &EthWitness) -> Result<ChainStatement, ChainError> {
+    let (header, receipt) = included_receipt(witness)?;
+    Ok(ChainStatement {
+        anchor: header.hash,
+        number: header.number,
+        block_hash: header.hash,
+        commitment: keccak256(&receipt),
+    })
+}
+
+/// Verify that the log at `log_index` of an included receipt is part of the header's block
+pub fn verify_log(witness: &EthLogWitness) -> Result<ChainStatement, ChainError> {
+    let (header, receipt) = included_receipt(&witness.receipt)?;
+    let logs = receipt_logs(&receipt)?;
+    let log = usize::try_from(witness.log_index).ok()
+        .and_then(|index| logs.get(index))
+        .ok_or(ChainError::InvalidProof("receipt has no log at the index"))?;
+    Ok(ChainStatement {
+        anchor: header.hash,
+        number: header.number,
+        block_hash: header.hash,
+        commitment: log.hash(),
+    })
+}
+
+/// Verify that each header is the parent of the next: the child names the parent's
+/// hash, has the next block number and a later timestamp
+///
+/// The statement is anchored at the last block and proves the first one, committing
+/// to its state root.
+pub fn verify_header_chain(witness: &HeaderChainWitness) -> Result<ChainStatement, ChainError> {
+    let mut headers = witness.headers.iter().map(|header| EthHeader::decode(header));
+    let first = headers.next().ok_or(ChainError::Malformed("empty header chain"))??;
+    let mut parent = first;
+    for header in headers {
+        let header = header?;
+        if header.parent_hash != parent.hash {
+            return Err(ChainError::BrokenChain("parent hash does not match"));
+        }
+        if parent.number.checked_add(1) != Some(header.number) {
+            return Err(ChainError::BrokenChain("block numbers are not consecutive"));
+        }
+        if header.timestamp <= parent.timestamp {
+            return Err(ChainError::BrokenChain("timestamp does not increase"));
+        }
+        parent = header;
+    }
+    Ok(ChainStatement {
+        anchor: parent.hash,
+        number: first.number,
+        block_hash: first.hash,
+        commitment: first.state_root,
+    })
+}
+
+/// Logs of an encoded receipt, legacy or typed
+pub fn receipt_logs(receipt: &[u8]) -> Result<Vec<EthLog>, ChainError> {
+    // Typed receipts (EIP-2718) prefix the RLP payload with their type
+    let payload = match receipt.split_first() {
+        Some((receipt_type, payload)) if *receipt_type < 0x80 => payload,
+        _ => receipt,
+    };
+    let item = rlp::decode(payload).map_err(|_| ChainError::Malformed("receipt"))?;
+    let fields = item.as_list()
+        .filter(|fields| fields.len() == 4)
+        .ok_or(ChainError::Malformed("receipt"))?;
+    fields[3].as_list()
+        .ok_or(ChainError::Malformed("receipt logs"))?
+        .iter()
+        .map(EthLog::from_item)
+        .collect()
+}
+
+/// Header and receipt proven by a receipt inclusion witness
+fn included_receipt(witness: &EthWitness) -> Result<(EthHeader, Vec<u8>), ChainError> {
+    let header = EthHeader::decode(&witness.header)?;
+    let key = rlp::encode(&RlpItem::uint(witness.tx_index));
+    let receipt = trie_value(&header.receipts_root, &key, &witness.receipt_proof)?;
+    Ok((header, receipt))
+}
+
+/// Keccak-256 of `bytes`
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    Keccak256::digest(bytes).into()
+}
+
+/// Follow a Merkle-Patricia proof from `root` along `key`, returning the value at `key`
+pub fn trie_value(root: &[u8; 32], key: &[u8], proof: &[Vec<u8>]) -> Result<Vec<u8>, ChainError> {
+    trie_get(root, key, proof)?.ok_or(ChainError::InvalidProof("key is not in the trie"))
+}
+
+/// Follow a Merkle-Patricia proof from `root` along `key`, returning the value at
+/// `key`, or `None` if the proof shows the trie does not hold `key`
+///
+/// Nodes are referenced by hash, except nodes shorter than 32 bytes which are
+/// embedded in their parent.
+pub fn trie_get(root: &[u8; 32], key: &[u8], proof: &[Vec<u8>]) -> Result<Option<Vec<u8>>, ChainError> {
+    let nibbles: Vec<u8> = key.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect();
+    let mut path = &nibbles[..];
+    let mut expected = *root;
+    for node in proof {
+        if keccak256(node) != expected {
+            return Err(ChainError::InvalidProof("node does not match its reference"));
+        }
+        let mut node = rlp::decode(node).map_err(|_| ChainError::Malformed("trie node"))?;
+        // Walk down the nodes embedded in this one until a child is referenced by hash
+        loop {
+            let items = node.as_list().ok_or(ChainError::Malformed("trie node"))?;
+            let child = match items.len() {
+                // Branch: sixteen children and a value
+                17 => match path.split_first() {
+                    Some((nibble, rest)) => {
+                        path = rest;
+                        items[*nibble as usize].clone()
+                    }
+                    None => return Ok(value(&items[16])),
+                },
+                // Extension or leaf: hex-prefix encoded path and child or value
+                2 => {
+                    let encoded = items[0].as_bytes().ok_or(ChainError::Malformed("trie node path"))?;
+                    let (is_leaf, node_path) = hex_prefix(encoded)?;
+                    path = match path.strip_prefix(&node_path[..]) {
+                        Some(rest) => rest,
+                        None => return Ok(None),
+                    };
+                    if is_leaf {
+                        return Ok(if path.is_empty() { value(&items[1]) } else { None });
+                    }
+                    items[1].clone()
+                }
+                _ => return Err(ChainError::Malformed("trie node")),
+            };
+            match child {
+                RlpItem::List(_) => node = child,
+                RlpItem::Bytes(hash) if hash.is_empty() => return Ok(None),
+                RlpItem::Bytes(hash) => {
+                    expected = hash.try_into().map_err(|_| ChainError::Malformed("trie node reference"))?;
+                    break;
+                }
+            }
+        }
+    }
+    Err(ChainError::InvalidProof("proof ends before the key"))
+}
+
+/// Value stored in a trie node, `None` if empty
+fn value(item: &RlpItem) -> Option<Vec<u8>> {
+    item.as_bytes().filter(|value| !value.is_empty()).map(<[u8]>::to_vec)
+}
+
+/// Decode a hex-prefix encoded path into its leaf flag and nibbles
+fn hex_prefix(encoded: &[u8]) -> Result<(bool, Vec<u8>), ChainError> {
+    let (first, rest) = encoded.split_first().ok_or(ChainError::Malformed("trie node path"))?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(ChainError::Malformed("trie node path"));
+    }
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    // Odd-length paths keep their first nibble in the flag byte
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]));
+    Ok((flag >= 2, nibbles))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Header with the given receipts root and number
+    fn header(receipts_root: [u8; 32], number: u64) -> Vec<u8> {
+        child([0; 32], receipts_root, number, 1_700_000_000)
+    }
+
+    /// Header with the given parent hash, receipts root, number and timestamp
+    fn child(parent_hash: [u8; 32], receipts_root: [u8; 32], number: u64, timestamp: u64) -> Vec<u8> {
+        let mut fields = vec![RlpItem::Bytes(vec![0; 32]); MIN_FIELDS];
+        fields[PARENT_HASH] = RlpItem::Bytes(parent_hash.to_vec());
+        fields[STATE_ROOT] = RlpItem::Bytes(vec![number as u8; 32]);
+        fields[RECEIPTS_ROOT] = RlpItem::Bytes(receipts_root.to_vec());
+        fields[NUMBER] = RlpItem::uint(number);
+        fields[GAS_LIMIT] = RlpItem::uint(30_000_000);
+        fields[GAS_USED] = RlpItem::uint(12_000_000);
+        fields[TIMESTAMP] = RlpItem::uint(timestamp);
+        rlp::encode(&RlpItem::List(fields))
+    }
+
+    #[test]
+    fn test_header_chain() {
+        let mut headers = vec![header([0; 32], 100)];
+        for number in 101..104 {
+            let parent = keccak256(headers.last().unwrap());
+            headers.push(child(parent, [0; 32], number, 1_700_000_000 + 12 * (number - 100)));
+        }
+        let mut witness = HeaderChainWitness { headers };
+
+        let statement = verify_header_chain(&witness).unwrap();
+        assert_eq!(statement.anchor, keccak256(&witness.headers[3]));
+        assert_eq!(statement.number, 100);
+        assert_eq!(statement.block_hash, keccak256(&witness.headers[0]));
+        assert_eq!(statement.commitment, [100; 32]);
+
+        // A single header is its own ancestor
+        let single = HeaderChainWitness { headers: witness.headers[..1].to_vec() };
+        assert_eq!(verify_header_chain(&single).unwrap().anchor, statement.block_hash);
+
+        // Skipped blocks, reordered headers and stalled timestamps break the chain
+        witness.headers.remove(1);
+        assert_eq!(verify_header_chain(&witness), Err(ChainError::BrokenChain("parent hash does not match")));
+        witness.headers.swap(0, 1);
+        assert!(verify_header_chain(&witness).is_err());
+        let stalled = child(keccak256(&witness.headers[1]), [0; 32], 101, 1_700_000_000);
+        let witness = HeaderChainWitness { headers: vec![witness.headers[1].clone(), stalled] };
+        assert_eq!(verify_header_chain(&witness), Err(ChainError::BrokenChain("timestamp does not increase")));
+        assert!(verify_header_chain(&HeaderChainWitness { headers: Vec::new() }).is_err());
+    }
+
+    #[test]
+    fn test_header_decode() {
+        let encoded = header([0x11; 32], 19_000_000);
+        let decoded = EthHeader::decode(&encoded).unwrap();
+        assert_eq!(decoded.hash, keccak256(&encoded));
+        assert_eq!(decoded.receipts_root, [0x11; 32]);
+        assert_eq!((decoded.number, decoded.timestamp), (19_000_000, 1_700_000_000));
+        assert_eq!((decoded.gas_used, decoded.gas_limit), (12_000_000, 30_000_000));
+
+        // Truncated headers and integers with leading zeros are malformed
+        let item = rlp::decode(&encoded).unwrap();
+        let mut fields = item.as_list().unwrap().to_vec();
+        fields[GAS_USED] = RlpItem::Bytes(vec![0, 1]);
+        assert!(EthHeader::decode(&rlp::encode(&RlpItem::List(fields.clone()))).is_err());
+        fields.truncate(10);
+        assert!(EthHeader::decode(&rlp::encode(&RlpItem::List(fields))).is_err());
+    }
+
+    #[test]
+    fn test_log_inclusion() {
+        let log = EthLog {
+            address: [0xAA; 20],
+            topics: vec![keccak256(b"MessageSent(bytes32,bytes)"), [0x01; 32]],
+            data: vec![0xDA; 40],
+        };
+        let encoded_log = RlpItem::List(vec![
+            RlpItem::Bytes(log.address.to_vec()),
+            RlpItem::List(log.topics.iter().map(|topic| RlpItem::Bytes(topic.to_vec())).collect()),
+            RlpItem::Bytes(log.data.clone()),
+        ]);
+        // EIP-1559 receipt: status, cumulative gas, bloom, logs
+        let mut receipt = vec![0x02];
+        receipt.extend(rlp::encode(&RlpItem::List(vec![
+            RlpItem::uint(1),
+            RlpItem::uint(21_000),
+            RlpItem::Bytes(vec![0; 256]),
+            RlpItem::List(vec![encoded_log]),
+        ])));
+        assert_eq!(receipt_logs(&receipt).unwrap(), vec![log.clone()]);
+
+        let leaf = rlp::encode(&RlpItem::List(vec![
+            RlpItem::Bytes(vec![0x20, 0x80]),
+            RlpItem::Bytes(receipt),
+        ]));
+        let mut witness = EthLogWitness {
+            receipt: EthWitness {
+                header: header(keccak256(&leaf), 19_000_000),
+                tx_index: 0,
+                receipt_proof: vec![leaf],
+            },
+            log_index: 0,
+        };
+        let statement = verify_log(&witness).unwrap();
+        assert_eq!(statement.commitment, log.hash());
+        assert_eq!(statement.anchor, keccak256(&witness.receipt.header));
+
+        witness.log_index = 1;
+        assert!(matches!(verify_log(&witness), Err(ChainError::InvalidProof(_))));
+    }
+
+    #[test]
+    fn test_receipt_inclusion() {
+        let receipt = vec![0x02; 120];
+        // Single-leaf trie holding the receipt of transaction 0, keyed by rlp(0) = 0x80
+        let leaf = rlp::encode(&RlpItem::List(vec![
+            RlpItem::Bytes(vec![0x20, 0x80]),
+            RlpItem::Bytes(receipt.clone()),
+        ]));
+        let header = header(keccak256(&leaf), 19_000_000);
+        let witness = EthWitness {
+            header: header.clone(),
+            tx_index: 0,
+            receipt_proof: vec![leaf],
+        };
+
+        let statement = verify(&witness).unwrap();
+        assert_eq!(statement.anchor, keccak256(&header));
+        assert_eq!(statement.number, 19_000_000);
+        assert_eq!(statement.commitment, keccak256(&receipt));
+
+        // Another index is not in the trie, and a tampered proof does not hash to the root
+        assert!(verify(&EthWitness { tx_index: 1, ..witness.clone() }).is_err());
+        let mut tampered = witness;
+        tampered.receipt_proof[0][5] ^= 1;
+        assert!(matches!(verify(&tampered), Err(ChainError::InvalidProof(_))));
+    }
+}
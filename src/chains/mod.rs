@@ -0,0 +1,157 @@
+//! Light-client verification of Ethereum, Polkadot, Solana and Tendermint chains
+//!
+//! The chain verifier guests in `circuits/sp1/` each prove one statement about a
+//! chain, starting from a trusted anchor the verifier of the proof already holds:
+//!
+//! - [`eth`]: a receipt, or one event log of it, is included in the block with a
+//!   trusted hash, or a block is an ancestor of the block with a trusted hash
+//! - [`beacon`]: a beacon block header is finalized according to a light-client
+//!   update signed by a trusted sync committee
+//! - [`dot`]: a header is finalized by a GRANDPA justification of a trusted
+//!   authority set
+//! - [`sol`]: a bank hash is voted on by a supermajority of a trusted epoch stake set
+//! - [`tm`]: a Tendermint / CometBFT header is committed by a trusted validator set
+//! - [`state`]: an Ethereum storage slot holds a value under a trusted state root
+//!
+//! Every guest but the state proof one commits a [`ChainStatement`] followed by the
+//! [`DomainSeparator`](crate::domain::DomainSeparator) the proof is bound to. The
+//! verification logic only depends on `alloc` and the hash and signature crates, so
+//! the guests include the very same source files; the host-side programs and inputs
+//! driving the guests are in [`circuit`].
+
+pub mod beacon;
+pub mod dot;
+pub mod eth;
+pub mod sol;
+pub mod state;
+pub mod tm;
+#[cfg(feature = "std")]
+pub mod circuit;
+
+use core::fmt;
+
+/// Statement proven by a chain verifier guest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainStatement {
+    /// Trusted anchor the proof starts from: block hash (Ethereum), sync committee
+    /// anchor (beacon chain), authority set hash (Polkadot), epoch stake set hash
+    /// (Solana) or validator set anchor (Tendermint)
+    pub anchor: [u8; 32],
+    /// Number of the verified block, slot (beacon chain, Solana) or height (Tendermint)
+    pub number: u64,
+    /// Hash of the verified block, block root (beacon chain) or bank hash (Solana)
+    pub block_hash: [u8; 32],
+    /// Keccak-256 of the receipt or log (Ethereum), state root of the first block
+    /// (Ethereum header chains), next sync committee root or zero (beacon chain),
+    /// state root (Polkadot), zero (Solana) or app hash (Tendermint)
+    pub commitment: [u8; 32],
+}
+
+impl ChainStatement {
+    /// Length of an encoded statement
+    pub const LEN: usize = 104;
+
+    /// Encode as the guests commit it: anchor, number (little endian), block hash,
+    /// commitment
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..32].copy_from_slice(&self.anchor);
+        bytes[32..40].copy_from_slice(&self.number.to_le_bytes());
+        bytes[40..72].copy_from_slice(&self.block_hash);
+        bytes[72..].copy_from_slice(&self.commitment);
+        bytes
+    }
+
+    /// Decode a statement, `None` if `bytes` is not [`Self::LEN`] long
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        let mut statement = Self {
+            anchor: [0; 32],
+            number: 0,
+            block_hash: [0; 32],
+            commitment: [0; 32],
+        };
+        statement.anchor.copy_from_slice(&bytes[..32]);
+        statement.number = u64::from_le_bytes(bytes[32..40].try_into().ok()?);
+        statement.block_hash.copy_from_slice(&bytes[40..72]);
+        statement.commitment.copy_from_slice(&bytes[72..]);
+        Some(statement)
+    }
+}
+
+/// Reason a chain witness does not prove its statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainError {
+    /// A structure of the witness could not be decoded
+    Malformed(&'static str),
+    /// A Merkle-Patricia proof does not lead to the claimed value
+    InvalidProof(&'static str),
+    /// Headers do not link up into a chain
+    BrokenChain(&'static str),
+    /// A signature does not verify
+    InvalidSignature,
+    /// Signatures cover less than two thirds of the voting weight
+    InsufficientWeight {
+        /// Weight of the valid signatures
+        signed: u64,
+        /// Total weight of the set
+        total: u64,
+    },
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::Malformed(what) => write!(f, "malformed {}", what),
+            ChainError::InvalidProof(reason) => write!(f, "invalid inclusion proof: {}", reason),
+            ChainError::BrokenChain(reason) => write!(f, "headers do not form a chain: {}", reason),
+            ChainError::InvalidSignature => write!(f, "invalid signature"),
+            ChainError::InsufficientWeight { signed, total } => {
+                write!(f, "signatures cover {} of {} voting weight, more than two thirds are needed", signed, total)
+            }
+        }
+    }
+}
+
+/// Whether `signed` is a supermajority of more than two thirds of `total`
+pub(crate) fn supermajority(signed: u64, total: u64) -> Result<(), ChainError> {
+    if total == 0 || u128::from(signed) * 3 <= u128::from(total) * 2 {
+        return Err(ChainError::InsufficientWeight { signed, total });
+    }
+    Ok(())
+}
+
+/// Verify an Ed25519 signature by `public_key` over `message`
+pub(crate) fn verify_ed25519(public_key: &[u8; 32], message: &[u8], signature: &[u8]) -> Result<(), ChainError> {
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    let key = VerifyingKey::from_bytes(public_key).map_err(|_| ChainError::InvalidSignature)?;
+    let signature = Signature::from_slice(signature).map_err(|_| ChainError::InvalidSignature)?;
+    key.verify(message, &signature).map_err(|_| ChainError::InvalidSignature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statement_round_trip() {
+        let statement = ChainStatement {
+            anchor: [1; 32],
+            number: 19_000_000,
+            block_hash: [2; 32],
+            commitment: [3; 32],
+        };
+        assert_eq!(ChainStatement::from_bytes(&statement.to_bytes()), Some(statement));
+        assert_eq!(ChainStatement::from_bytes(&[0; 40]), None);
+    }
+
+    #[test]
+    fn test_supermajority() {
+        assert!(supermajority(67, 100).is_ok());
+        assert!(supermajority(2, 3).is_err());
+        assert!(supermajority(0, 0).is_err());
+    }
+}
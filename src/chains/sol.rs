@@ -0,0 +1,283 @@
+//! Solana slot confirmation through validator votes
+//!
+//! The witness is the stake set of an epoch and the vote transactions of its
+//! validators for one slot. The anchor is the hash of the stake set, so a proof
+//! shows that validators holding more than two thirds of the stake the verifier
+//! trusts voted for the slot's bank hash. Stake sets are hashed as
+//! `sha256(epoch (u64 LE) ++ for each validator: vote account (32) ++ authorized voter (32) ++ stake (u64 LE))`.
+//!
+//! Votes are read from `Vote` and `VoteSwitch` instructions of the vote program;
+//! the compact tower sync instructions are not decoded yet.
+
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{supermajority, verify_ed25519, ChainError, ChainStatement};
+
+/// Address of the vote program, `Vote111111111111111111111111111111111111111`
+pub const VOTE_PROGRAM_ID: [u8; 32] = [
+    7, 97, 72, 29, 53, 116, 116, 187, 124, 77, 118, 36, 235, 211, 189, 179,
+    216, 53, 94, 115, 209, 16, 67, 252, 13, 163, 83, 128, 0, 0, 0, 0,
+];
+
+/// `VoteInstruction::Vote`
+const VOTE: u32 = 2;
+
+/// `VoteInstruction::VoteSwitch`
+const VOTE_SWITCH: u32 = 6;
+
+/// Validator of the epoch stake set
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakedValidator {
+    /// Vote account the stake is delegated to
+    pub vote_account: [u8; 32],
+    /// Key authorized to vote for the vote account in the epoch
+    pub authorized_voter: [u8; 32],
+    /// Delegated stake in lamports
+    pub stake: u64,
+}
+
+/// Signed vote transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteTransaction {
+    /// Signatures, in the order of the message's signer keys
+    pub signatures: Vec<Vec<u8>>,
+    /// Serialized transaction message
+    pub message: Vec<u8>,
+}
+
+/// Solana slot confirmation witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolWitness {
+    /// Epoch of the stake set
+    pub epoch: u64,
+    /// Stake set of the epoch
+    pub validators: Vec<StakedValidator>,
+    /// Slot voted for
+    pub slot: u64,
+    /// Bank hash of the slot
+    pub bank_hash: [u8; 32],
+    /// Vote transactions for the slot
+    pub votes: Vec<VoteTransaction>,
+}
+
+/// Verify that a supermajority of the stake voted for the slot's bank hash
+pub fn verify(witness: &SolWitness) -> Result<ChainStatement, ChainError> {
+    let total = witness.validators.iter()
+        .try_fold(0u64, |total, validator| total.checked_add(validator.stake))
+        .ok_or(ChainError::Malformed("stake set"))?;
+    let mut voted = Vec::with_capacity(witness.votes.len());
+    let mut signed = 0u64;
+    for transaction in &witness.votes {
+        let vote = match parse_vote(&transaction.message)? {
+            Some(vote) if vote.slot == witness.slot && vote.hash == witness.bank_hash => vote,
+            _ => continue,
+        };
+        let validator = match witness.validators.iter().find(|validator| validator.vote_account == vote.vote_account) {
+            Some(validator) if validator.authorized_voter == vote.authority => validator,
+            _ => continue,
+        };
+        // A validator's stake counts once, however many votes it sent
+        if voted.contains(&validator.vote_account) {
+            continue;
+        }
+        let signature = transaction.signatures.get(vote.authority_index)
+            .ok_or(ChainError::Malformed("vote transaction signatures"))?;
+        verify_ed25519(&vote.authority, &transaction.message, signature)?;
+        voted.push(validator.vote_account);
+        signed += validator.stake;
+    }
+    supermajority(signed, total)?;
+
+    Ok(ChainStatement {
+        anchor: stake_set_hash(witness.epoch, &witness.validators),
+        number: witness.slot,
+        block_hash: witness.bank_hash,
+        commitment: [0; 32],
+    })
+}
+
+/// Hash of an epoch stake set, the anchor of Solana proofs
+pub fn stake_set_hash(epoch: u64, validators: &[StakedValidator]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(epoch.to_le_bytes());
+    for validator in validators {
+        hasher.update(validator.vote_account);
+        hasher.update(validator.authorized_voter);
+        hasher.update(validator.stake.to_le_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Vote found in a transaction message
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Vote {
+    vote_account: [u8; 32],
+    authority: [u8; 32],
+    /// Index of the authority among the signers
+    authority_index: usize,
+    slot: u64,
+    hash: [u8; 32],
+}
+
+/// Find the first vote instruction of a legacy or v0 transaction message
+fn parse_vote(message: &[u8]) -> Result<Option<Vote>, ChainError> {
+    let mut cursor = Cursor { bytes: message, offset: 0 };
+    // Versioned messages set the top bit of their first byte
+    if cursor.peek()? & 0x80 != 0 {
+        cursor.take(1)?;
+    }
+    let signers = cursor.take(3)?[0] as usize;
+    let keys: Vec<[u8; 32]> = (0..cursor.short_vec()?).map(|_| cursor.array()).collect::<Result<_, _>>()?;
+    cursor.take(32)?; // Recent blockhash
+
+    for _ in 0..cursor.short_vec()? {
+        let program = keys.get(cursor.take(1)?[0] as usize).ok_or(ChainError::Malformed("vote transaction"))?;
+        let accounts_len = cursor.short_vec()?;
+        let accounts = cursor.take(accounts_len)?;
+        let data_len = cursor.short_vec()?;
+        let data = cursor.take(data_len)?;
+        if *program != VOTE_PROGRAM_ID {
+            continue;
+        }
+        // Vote instruction accounts: vote account, slot hashes, clock, authority
+        let key = |index: usize| accounts.get(index)
+            .and_then(|key| keys.get(*key as usize))
+            .copied()
+            .ok_or(ChainError::Malformed("vote instruction"));
+        let authority_index = *accounts.get(3).ok_or(ChainError::Malformed("vote instruction"))? as usize;
+        if authority_index >= signers {
+            return Err(ChainError::Malformed("vote authority is not a signer"));
+        }
+        let (slot, hash) = match parse_vote_data(data)? {
+            Some(vote) => vote,
+            None => continue,
+        };
+        return Ok(Some(Vote {
+            vote_account: key(0)?,
+            authority: key(3)?,
+            authority_index,
+            slot,
+            hash,
+        }));
+    }
+    Ok(None)
+}
+
+/// Last voted slot and bank hash of `Vote` and `VoteSwitch` instruction data
+fn parse_vote_data(data: &[u8]) -> Result<Option<(u64, [u8; 32])>, ChainError> {
+    let mut cursor = Cursor { bytes: data, offset: 0 };
+    let tag = u32::from_le_bytes(cursor.array()?);
+    if tag != VOTE && tag != VOTE_SWITCH {
+        return Ok(None);
+    }
+    let slots = u64::from_le_bytes(cursor.array()?) as usize;
+    let mut last = None;
+    for _ in 0..slots {
+        last = Some(u64::from_le_bytes(cursor.array()?));
+    }
+    let last = last.ok_or(ChainError::Malformed("vote without slots"))?;
+    Ok(Some((last, cursor.array()?)))
+}
+
+/// Cursor over a serialized message
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&self) -> Result<u8, ChainError> {
+        self.bytes.get(self.offset).copied().ok_or(ChainError::Malformed("vote transaction"))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ChainError> {
+        let bytes = self.offset.checked_add(len)
+            .and_then(|end| self.bytes.get(self.offset..end))
+            .ok_or(ChainError::Malformed("vote transaction"))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ChainError> {
+        Ok(self.take(N)?.try_into().expect("length checked"))
+    }
+
+    /// Compact-u16 length prefix
+    fn short_vec(&mut self) -> Result<usize, ChainError> {
+        let mut len = 0usize;
+        for shift in [0, 7, 14] {
+            let byte = self.take(1)?[0];
+            len |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(len);
+            }
+        }
+        Err(ChainError::Malformed("vote transaction"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    /// Legacy message of a `Vote` instruction for `slot` and `hash`, signed by `voter`
+    fn vote_message(vote_account: [u8; 32], voter: [u8; 32], slot: u64, hash: [u8; 32]) -> Vec<u8> {
+        let mut data = VOTE.to_le_bytes().to_vec();
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&slot.to_le_bytes());
+        data.extend_from_slice(&hash);
+        data.push(0); // No timestamp
+
+        let mut message = vec![1, 0, 3];
+        message.push(5);
+        for key in [voter, vote_account, [0x11; 32], [0x22; 32], VOTE_PROGRAM_ID] {
+            message.extend_from_slice(&key);
+        }
+        message.extend_from_slice(&[0x33; 32]);
+        message.push(1);
+        message.extend_from_slice(&[4, 4, 1, 2, 3, 0, data.len() as u8]);
+        message.extend_from_slice(&data);
+        message
+    }
+
+    #[test]
+    fn test_slot_confirmation() {
+        let voters: Vec<SigningKey> = (1..=3u8).map(|seed| SigningKey::from_bytes(&[seed; 32])).collect();
+        let validators: Vec<_> = voters.iter().enumerate()
+            .map(|(i, voter)| StakedValidator {
+                vote_account: [0x40 + i as u8; 32],
+                authorized_voter: voter.verifying_key().to_bytes(),
+                stake: [50, 30, 20][i],
+            })
+            .collect();
+        let vote = |i: usize, slot: u64| {
+            let message = vote_message(validators[i].vote_account, validators[i].authorized_voter, slot, [0xBA; 32]);
+            VoteTransaction {
+                signatures: vec![voters[i].sign(&message).to_bytes().to_vec()],
+                message,
+            }
+        };
+        let mut witness = SolWitness {
+            epoch: 600,
+            validators: validators.clone(),
+            slot: 260_000_000,
+            bank_hash: [0xBA; 32],
+            votes: vec![vote(0, 260_000_000), vote(1, 260_000_000)],
+        };
+
+        let statement = verify(&witness).unwrap();
+        assert_eq!(statement.anchor, stake_set_hash(600, &validators));
+        assert_eq!((statement.number, statement.block_hash), (260_000_000, [0xBA; 32]));
+
+        // Votes for another slot do not count
+        witness.votes[1] = vote(1, 259_999_999);
+        assert!(matches!(verify(&witness), Err(ChainError::InsufficientWeight { signed: 50, total: 100 })));
+
+        // Forged signatures are rejected
+        witness.votes[0].signatures[0][0] ^= 1;
+        assert_eq!(verify(&witness), Err(ChainError::InvalidSignature));
+    }
+}
@@ -0,0 +1,223 @@
+//! Ethereum account and storage proofs
+//!
+//! The witness is the `eth_getProof` output for one storage slot: the state trie
+//! nodes from a state root to the account, and the storage trie nodes from the
+//! account's storage root to the slot. The state trie is keyed by the Keccak-256
+//! hash of the address and storage tries by the hash of the slot. Slots missing from
+//! the storage trie, and every slot of a missing account, hold zero.
+//!
+//! The state root is trusted by the verifier of the proof, typically the
+//! [`EthHeader::state_root`](super::eth::EthHeader) of a verified header.
+
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::codec::rlp;
+use super::eth::{keccak256, trie_get};
+use super::ChainError;
+
+/// Storage slot of an account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageKey {
+    /// Address of the account
+    pub address: [u8; 20],
+    /// Storage slot
+    pub slot: [u8; 32],
+}
+
+/// Trie nodes proving a storage slot, as returned by `eth_getProof`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateProof {
+    /// State trie nodes from the state root to the account
+    pub account_proof: Vec<Vec<u8>>,
+    /// Storage trie nodes from the account's storage root to the slot
+    pub storage_proof: Vec<Vec<u8>>,
+}
+
+/// Storage proof witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateWitness {
+    /// State root the proof starts from
+    pub state_root: [u8; 32],
+    /// Proven slot
+    pub key: StorageKey,
+    /// Trie nodes proving the slot
+    pub proof: StateProof,
+}
+
+/// Account stored in the state trie
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Account {
+    /// Number of transactions sent, or contracts created
+    pub nonce: u64,
+    /// Balance in wei, big endian
+    pub balance: [u8; 32],
+    /// Root of the account's storage trie
+    pub storage_root: [u8; 32],
+    /// Keccak-256 of the account's code
+    pub code_hash: [u8; 32],
+}
+
+/// Statement proven by the state proof guest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageStatement {
+    /// Trusted state root the proof starts from
+    pub state_root: [u8; 32],
+    /// Address of the account
+    pub address: [u8; 20],
+    /// Storage slot
+    pub slot: [u8; 32],
+    /// Value of the slot, big endian
+    pub value: [u8; 32],
+}
+
+impl StorageStatement {
+    /// Length of an encoded statement
+    pub const LEN: usize = 116;
+
+    /// Encode as the guest commits it: state root, address, slot, value
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..32].copy_from_slice(&self.state_root);
+        bytes[32..52].copy_from_slice(&self.address);
+        bytes[52..84].copy_from_slice(&self.slot);
+        bytes[84..].copy_from_slice(&self.value);
+        bytes
+    }
+
+    /// Decode a statement, `None` if `bytes` is not [`Self::LEN`] long
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        Some(Self {
+            state_root: bytes[..32].try_into().ok()?,
+            address: bytes[32..52].try_into().ok()?,
+            slot: bytes[52..84].try_into().ok()?,
+            value: bytes[84..].try_into().ok()?,
+        })
+    }
+}
+
+/// Verify the value of the witness's storage slot under its state root
+pub fn verify(witness: &StateWitness) -> Result<StorageStatement, ChainError> {
+    let value = match account(&witness.state_root, &witness.key.address, &witness.proof.account_proof)? {
+        Some(account) => storage(&account.storage_root, &witness.key.slot, &witness.proof.storage_proof)?,
+        None => [0; 32],
+    };
+    Ok(StorageStatement {
+        state_root: witness.state_root,
+        address: witness.key.address,
+        slot: witness.key.slot,
+        value,
+    })
+}
+
+/// Account at `address` under `state_root`, `None` if the proof shows there is none
+pub fn account(state_root: &[u8; 32], address: &[u8; 20], proof: &[Vec<u8>]) -> Result<Option<Account>, ChainError> {
+    let encoded = match trie_get(state_root, &keccak256(address), proof)? {
+        Some(encoded) => encoded,
+        None => return Ok(None),
+    };
+    let item = rlp::decode(&encoded).map_err(|_| ChainError::Malformed("account"))?;
+    let fields = item.as_list()
+        .filter(|fields| fields.len() == 4)
+        .ok_or(ChainError::Malformed("account"))?;
+    let hash = |index: usize| -> Result<[u8; 32], ChainError> {
+        fields[index].as_bytes()
+            .and_then(|hash| hash.try_into().ok())
+            .ok_or(ChainError::Malformed("account"))
+    };
+    Ok(Some(Account {
+        nonce: fields[0].as_u64().ok_or(ChainError::Malformed("account nonce"))?,
+        balance: uint256(fields[1].as_bytes().ok_or(ChainError::Malformed("account balance"))?)
+            .ok_or(ChainError::Malformed("account balance"))?,
+        storage_root: hash(2)?,
+        code_hash: hash(3)?,
+    }))
+}
+
+/// Value of `slot` under `storage_root`, zero if the proof shows the slot is empty
+pub fn storage(storage_root: &[u8; 32], slot: &[u8; 32], proof: &[Vec<u8>]) -> Result<[u8; 32], ChainError> {
+    let encoded = match trie_get(storage_root, &keccak256(slot), proof)? {
+        Some(encoded) => encoded,
+        None => return Ok([0; 32]),
+    };
+    let item = rlp::decode(&encoded).map_err(|_| ChainError::Malformed("storage value"))?;
+    item.as_bytes()
+        .and_then(uint256)
+        .ok_or(ChainError::Malformed("storage value"))
+}
+
+/// Canonical big-endian integer of at most 32 bytes, left-padded to 32
+fn uint256(bytes: &[u8]) -> Option<[u8; 32]> {
+    if bytes.len() > 32 || bytes.first() == Some(&0) {
+        return None;
+    }
+    let mut value = [0u8; 32];
+    value[32 - bytes.len()..].copy_from_slice(bytes);
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::rlp::RlpItem;
+
+    /// Leaf node holding `value` at the whole of `key`'s hash
+    fn leaf(key: &[u8], value: Vec<u8>) -> Vec<u8> {
+        let mut path = vec![0x20];
+        path.extend_from_slice(&keccak256(key));
+        rlp::encode(&RlpItem::List(vec![RlpItem::Bytes(path), RlpItem::Bytes(value)]))
+    }
+
+    #[test]
+    fn test_storage_slot() {
+        let address = [0xAB; 20];
+        let slot = [0; 32];
+        let storage_leaf = leaf(&slot, rlp::encode(&RlpItem::uint(0x1234)));
+        let account = rlp::encode(&RlpItem::List(vec![
+            RlpItem::uint(1),
+            RlpItem::uint(10u64.pow(18)),
+            RlpItem::Bytes(keccak256(&storage_leaf).to_vec()),
+            RlpItem::Bytes(keccak256(b"code").to_vec()),
+        ]));
+        let account_leaf = leaf(&address, account);
+        let mut witness = StateWitness {
+            state_root: keccak256(&account_leaf),
+            key: StorageKey { address, slot },
+            proof: StateProof {
+                account_proof: vec![account_leaf],
+                storage_proof: vec![storage_leaf],
+            },
+        };
+
+        let statement = verify(&witness).unwrap();
+        assert_eq!(&statement.value[30..], &[0x12, 0x34]);
+        assert_eq!(StorageStatement::from_bytes(&statement.to_bytes()), Some(statement));
+
+        // The leaf shows another slot is empty
+        witness.key.slot = [1; 32];
+        assert_eq!(verify(&witness).unwrap().value, [0; 32]);
+
+        // Proofs from another root are rejected
+        witness.state_root = [0; 32];
+        assert!(matches!(verify(&witness), Err(ChainError::InvalidProof(_))));
+    }
+
+    #[test]
+    fn test_embedded_nodes() {
+        // A branch embedding a leaf shorter than 32 bytes, at the first nibble of the key
+        let key = keccak256(&[0; 32]);
+        let embedded = RlpItem::List(vec![RlpItem::Bytes(vec![0x30 | (key[0] & 0x0f)]), RlpItem::Bytes(vec![0x05])]);
+        let mut children = vec![RlpItem::Bytes(Vec::new()); 17];
+        children[(key[0] >> 4) as usize] = embedded;
+        let branch = rlp::encode(&RlpItem::List(children));
+
+        // The embedded leaf only covers one nibble, so the key's remaining path is absent
+        let root = keccak256(&branch);
+        let proof = [branch];
+        assert_eq!(trie_get(&root, &key, &proof), Ok(None));
+        assert_eq!(trie_get(&root, &key[..1], &proof), Ok(Some(vec![0x05])));
+    }
+}
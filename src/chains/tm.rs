@@ -0,0 +1,369 @@
+//! Tendermint / CometBFT headers through validator commits
+//!
+//! The witness is a header, the validator set it names and the commit for it. The
+//! anchor binds the hash of the validator set to the chain ID as
+//! `sha256(validators hash ++ chain ID)`, so a proof shows the header was committed by
+//! more than two thirds of the voting power of the set the verifier trusts on the
+//! chain it expects. The commitment is the header's app hash, the root Cosmos SDK
+//! state proofs start from.
+//!
+//! Headers and validator sets are hashed, and votes signed, over their Protocol
+//! Buffers encoding exactly as CometBFT does. Only Ed25519 validator keys are
+//! supported, and the trusting period is left to the verifier since the guest has
+//! no clock.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::codec::protobuf;
+use super::{supermajority, verify_ed25519, ChainError, ChainStatement};
+
+/// `SignedMsgType::Precommit`
+const PRECOMMIT: u64 = 2;
+
+/// Protocol versions of a header
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TmVersion {
+    /// Block protocol version
+    pub block: u64,
+    /// Application protocol version
+    pub app: u64,
+}
+
+/// Time as a Protocol Buffers `Timestamp`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TmTimestamp {
+    /// Seconds since the Unix epoch
+    pub seconds: i64,
+    /// Nanoseconds within the second
+    pub nanos: i32,
+}
+
+/// Block identifier: header hash and part set header
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TmBlockId {
+    /// Header hash
+    pub hash: [u8; 32],
+    /// Number of parts of the block
+    pub part_set_total: u32,
+    /// Merkle root of the parts of the block
+    pub part_set_hash: [u8; 32],
+}
+
+/// Block header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TmHeader {
+    /// Protocol versions
+    pub version: TmVersion,
+    /// Chain ID
+    pub chain_id: String,
+    /// Block height
+    pub height: u64,
+    /// Block time
+    pub time: TmTimestamp,
+    /// Previous block
+    pub last_block_id: TmBlockId,
+    /// Merkle root of the previous block's commit
+    pub last_commit_hash: [u8; 32],
+    /// Merkle root of the block's transactions
+    pub data_hash: [u8; 32],
+    /// Hash of the validator set committing the block
+    pub validators_hash: [u8; 32],
+    /// Hash of the validator set committing the next block
+    pub next_validators_hash: [u8; 32],
+    /// Hash of the consensus parameters
+    pub consensus_hash: [u8; 32],
+    /// Application state root after the previous block
+    pub app_hash: Vec<u8>,
+    /// Merkle root of the previous block's transaction results
+    pub last_results_hash: [u8; 32],
+    /// Merkle root of the block's evidence
+    pub evidence_hash: [u8; 32],
+    /// Address of the block proposer
+    pub proposer_address: [u8; 20],
+}
+
+impl TmHeader {
+    /// Header hash: the Merkle root of the encoded fields
+    pub fn hash(&self) -> [u8; 32] {
+        let mut version = Vec::new();
+        protobuf::encode_uint(1, self.version.block, &mut version);
+        protobuf::encode_uint(2, self.version.app, &mut version);
+        let mut height = Vec::new();
+        protobuf::encode_int(1, self.height as i64, &mut height);
+        // Scalar fields are encoded wrapped in their `google.protobuf` value message
+        let wrapped = |bytes: &[u8]| {
+            let mut out = Vec::new();
+            protobuf::encode_bytes(1, bytes, &mut out);
+            out
+        };
+        merkle_root(&[
+            version,
+            wrapped(self.chain_id.as_bytes()),
+            height,
+            encode_timestamp(&self.time),
+            encode_block_id(&self.last_block_id),
+            wrapped(&self.last_commit_hash),
+            wrapped(&self.data_hash),
+            wrapped(&self.validators_hash),
+            wrapped(&self.next_validators_hash),
+            wrapped(&self.consensus_hash),
+            wrapped(&self.app_hash),
+            wrapped(&self.last_results_hash),
+            wrapped(&self.evidence_hash),
+            wrapped(&self.proposer_address),
+        ])
+    }
+}
+
+/// Validator of a validator set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TmValidator {
+    /// Ed25519 public key
+    pub public_key: [u8; 32],
+    /// Voting power
+    pub voting_power: u64,
+}
+
+impl TmValidator {
+    /// Address of the validator: the first 20 bytes of the SHA-256 of its key
+    pub fn address(&self) -> [u8; 20] {
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&Sha256::digest(self.public_key)[..20]);
+        address
+    }
+}
+
+/// Precommit signature of a commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TmCommitSig {
+    /// Address of the signing validator
+    pub validator_address: [u8; 20],
+    /// Time of the vote
+    pub timestamp: TmTimestamp,
+    /// Ed25519 signature over the vote
+    pub signature: Vec<u8>,
+}
+
+/// Commit for a block
+///
+/// Only the signatures of validators that voted for the block are needed; absent
+/// and nil votes are left out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TmCommit {
+    /// Height of the committed block
+    pub height: u64,
+    /// Round the block was committed in
+    pub round: u32,
+    /// Committed block
+    pub block_id: TmBlockId,
+    /// Precommits for the block
+    pub signatures: Vec<TmCommitSig>,
+}
+
+/// Tendermint header witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TmWitness {
+    /// Verified header
+    pub header: TmHeader,
+    /// Validator set of the header, in the order it is hashed in
+    pub validators: Vec<TmValidator>,
+    /// Commit for the header
+    pub commit: TmCommit,
+}
+
+/// Verify that the header is committed by the validator set
+pub fn verify(witness: &TmWitness) -> Result<ChainStatement, ChainError> {
+    let header = &witness.header;
+    let validators_hash = validator_set_hash(&witness.validators);
+    if header.validators_hash != validators_hash {
+        return Err(ChainError::InvalidProof("header names another validator set"));
+    }
+    let block_hash = header.hash();
+    let commit = &witness.commit;
+    if commit.height != header.height || commit.block_id.hash != block_hash {
+        return Err(ChainError::InvalidProof("commit is for another block"));
+    }
+    let commitment = match header.app_hash.len() {
+        0 => [0; 32],
+        _ => header.app_hash.as_slice().try_into().map_err(|_| ChainError::Malformed("app hash"))?,
+    };
+
+    let total = witness.validators.iter()
+        .try_fold(0u64, |total, validator| total.checked_add(validator.voting_power))
+        .ok_or(ChainError::Malformed("validator voting powers"))?;
+    let addresses: Vec<[u8; 20]> = witness.validators.iter().map(TmValidator::address).collect();
+    let mut voted = Vec::with_capacity(commit.signatures.len());
+    let mut signed = 0u64;
+    for vote in &commit.signatures {
+        let validator = match addresses.iter().position(|address| *address == vote.validator_address) {
+            Some(index) => &witness.validators[index],
+            None => continue,
+        };
+        // A validator's power counts once, however many precommits it signed
+        if voted.contains(&vote.validator_address) {
+            continue;
+        }
+        let message = vote_sign_bytes(&header.chain_id, commit, &vote.timestamp);
+        verify_ed25519(&validator.public_key, &message, &vote.signature)?;
+        voted.push(vote.validator_address);
+        signed += validator.voting_power;
+    }
+    supermajority(signed, total)?;
+
+    Ok(ChainStatement {
+        anchor: validator_set_anchor(&validators_hash, &header.chain_id),
+        number: header.height,
+        block_hash,
+        commitment,
+    })
+}
+
+/// Anchor of Tendermint proofs: a validator set hash bound to a chain ID
+pub fn validator_set_anchor(validators_hash: &[u8; 32], chain_id: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(validators_hash);
+    hasher.update(chain_id.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Hash of a validator set: the Merkle root of the encoded validators
+pub fn validator_set_hash(validators: &[TmValidator]) -> [u8; 32] {
+    let leaves: Vec<Vec<u8>> = validators.iter()
+        .map(|validator| {
+            let mut public_key = Vec::with_capacity(34);
+            protobuf::encode_bytes(1, &validator.public_key, &mut public_key);
+            let mut leaf = Vec::with_capacity(48);
+            protobuf::encode_message(1, &public_key, &mut leaf);
+            protobuf::encode_int(2, validator.voting_power as i64, &mut leaf);
+            leaf
+        })
+        .collect();
+    merkle_root(&leaves)
+}
+
+/// Bytes a validator signs for a precommit: the length-delimited `CanonicalVote`
+pub fn vote_sign_bytes(chain_id: &str, commit: &TmCommit, timestamp: &TmTimestamp) -> Vec<u8> {
+    let mut vote = Vec::with_capacity(160);
+    protobuf::encode_uint(1, PRECOMMIT, &mut vote);
+    protobuf::encode_sfixed64(2, commit.height as i64, &mut vote);
+    protobuf::encode_sfixed64(3, i64::from(commit.round), &mut vote);
+    protobuf::encode_message(4, &encode_block_id(&commit.block_id), &mut vote);
+    protobuf::encode_message(5, &encode_timestamp(timestamp), &mut vote);
+    protobuf::encode_bytes(6, chain_id.as_bytes(), &mut vote);
+    protobuf::length_delimited(&vote)
+}
+
+fn encode_timestamp(timestamp: &TmTimestamp) -> Vec<u8> {
+    let mut out = Vec::new();
+    protobuf::encode_int(1, timestamp.seconds, &mut out);
+    protobuf::encode_int(2, i64::from(timestamp.nanos), &mut out);
+    out
+}
+
+fn encode_block_id(block_id: &TmBlockId) -> Vec<u8> {
+    let mut part_set_header = Vec::with_capacity(40);
+    protobuf::encode_uint(1, u64::from(block_id.part_set_total), &mut part_set_header);
+    protobuf::encode_bytes(2, &block_id.part_set_hash, &mut part_set_header);
+    let mut out = Vec::with_capacity(76);
+    protobuf::encode_bytes(1, &block_id.hash, &mut out);
+    protobuf::encode_message(2, &part_set_header, &mut out);
+    out
+}
+
+/// Merkle root of `leaves` as RFC 6962 defines it, splitting at the largest power
+/// of two below the number of leaves
+fn merkle_root(leaves: &[Vec<u8>]) -> [u8; 32] {
+    match leaves.len() {
+        0 => Sha256::digest([]).into(),
+        1 => Sha256::new().chain_update([0]).chain_update(&leaves[0]).finalize().into(),
+        len => {
+            let split = 1 << (len - 1).ilog2();
+            Sha256::new()
+                .chain_update([1])
+                .chain_update(merkle_root(&leaves[..split]))
+                .chain_update(merkle_root(&leaves[split..]))
+                .finalize()
+                .into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn header(validators_hash: [u8; 32]) -> TmHeader {
+        TmHeader {
+            version: TmVersion { block: 11, app: 0 },
+            chain_id: "cosmoshub-4".into(),
+            height: 20_000_000,
+            time: TmTimestamp { seconds: 1_700_000_000, nanos: 500 },
+            last_block_id: TmBlockId { hash: [1; 32], part_set_total: 1, part_set_hash: [2; 32] },
+            last_commit_hash: [3; 32],
+            data_hash: [4; 32],
+            validators_hash,
+            next_validators_hash: validators_hash,
+            consensus_hash: [5; 32],
+            app_hash: vec![6; 32],
+            last_results_hash: [7; 32],
+            evidence_hash: [8; 32],
+            proposer_address: [9; 20],
+        }
+    }
+
+    #[test]
+    fn test_merkle_split() {
+        // Five leaves split four and one
+        let leaves: Vec<Vec<u8>> = (0..5u8).map(|leaf| vec![leaf]).collect();
+        let inner = |left: [u8; 32], right: [u8; 32]| -> [u8; 32] {
+            Sha256::new().chain_update([1]).chain_update(left).chain_update(right).finalize().into()
+        };
+        assert_eq!(merkle_root(&leaves), inner(merkle_root(&leaves[..4]), merkle_root(&leaves[4..])));
+        assert_eq!(merkle_root(&leaves[..4]), inner(merkle_root(&leaves[..2]), merkle_root(&leaves[2..4])));
+        assert_eq!(merkle_root(&leaves[..3]), inner(merkle_root(&leaves[..2]), merkle_root(&leaves[2..3])));
+    }
+
+    #[test]
+    fn test_commit() {
+        let keys: Vec<SigningKey> = (1..=4u8).map(|seed| SigningKey::from_bytes(&[seed; 32])).collect();
+        let validators: Vec<_> = keys.iter()
+            .map(|key| TmValidator { public_key: key.verifying_key().to_bytes(), voting_power: 10 })
+            .collect();
+        let header = header(validator_set_hash(&validators));
+        let mut commit = TmCommit {
+            height: header.height,
+            round: 1,
+            block_id: TmBlockId { hash: header.hash(), part_set_total: 1, part_set_hash: [0xAB; 32] },
+            signatures: Vec::new(),
+        };
+        for (key, validator) in keys.iter().zip(&validators).take(3) {
+            let timestamp = TmTimestamp { seconds: 1_700_000_001, nanos: 0 };
+            let signature = key.sign(&vote_sign_bytes(&header.chain_id, &commit, &timestamp));
+            commit.signatures.push(TmCommitSig {
+                validator_address: validator.address(),
+                timestamp,
+                signature: signature.to_bytes().to_vec(),
+            });
+        }
+        let mut witness = TmWitness { header, validators: validators.clone(), commit };
+
+        let statement = verify(&witness).unwrap();
+        assert_eq!(statement.anchor, validator_set_anchor(&validator_set_hash(&validators), "cosmoshub-4"));
+        assert_eq!(statement.number, 20_000_000);
+        assert_eq!(statement.commitment, [6; 32]);
+
+        // Duplicate precommits do not add power, and two of four is not enough
+        witness.commit.signatures[2] = witness.commit.signatures[0].clone();
+        assert!(matches!(verify(&witness), Err(ChainError::InsufficientWeight { signed: 20, total: 40 })));
+
+        // The commit is for the header's hash, and the header names its validator set
+        witness.header.chain_id = "cosmoshub-5".into();
+        assert!(matches!(verify(&witness), Err(ChainError::InvalidProof(_))));
+        witness.validators.pop();
+        assert!(matches!(verify(&witness), Err(ChainError::InvalidProof(_))));
+    }
+}
@@ -0,0 +1,209 @@
+//! Sharded proving across a cluster of workers
+//!
+//! A large batch-verification statement proven as a single circuit serializes on
+//! one worker. The [`Cluster`] splits such a batch into fixed-size shards, proves
+//! every shard independently on its pool of workers and returns the [`ShardProofs`].
+//!
+//! The shards are not combined into a single proof: a verifier checks every shard
+//! proof and recomputes the batch commitment from the shard inputs. The commitment
+//! is a plain hash computed by the host, not a proven statement.
+
+use std::ops::Range;
+use std::sync::Arc;
+use futures::future;
+use sha2::{Digest, Sha256};
+use frostgate_zkip::{ProofMetadata, ZkBackend};
+
+use crate::error::ZkError;
+
+/// Default number of messages per shard
+pub const DEFAULT_SHARD_SIZE: usize = 500;
+
+/// Batch of messages verified by a single program
+#[derive(Debug, Clone)]
+pub struct BatchStatement {
+    /// Batch verification program
+    pub program: Vec<u8>,
+    /// Messages in the batch
+    pub messages: Vec<Vec<u8>>,
+}
+
+impl BatchStatement {
+    /// Split the batch into ranges of at most `shard_size` messages
+    pub fn shard_ranges(&self, shard_size: usize) -> Vec<Range<usize>> {
+        let shard_size = shard_size.max(1);
+        (0..self.messages.len())
+            .step_by(shard_size)
+            .map(|start| start..(start + shard_size).min(self.messages.len()))
+            .collect()
+    }
+}
+
+/// Encode the messages of a shard as guest input
+///
+/// The layout is a little-endian `u32` message count followed by each message
+/// prefixed with its little-endian `u32` length.
+pub fn encode_shard(messages: &[Vec<u8>]) -> Vec<u8> {
+    let mut input = Vec::with_capacity(4 + messages.iter().map(|m| 4 + m.len()).sum::<usize>());
+    input.extend_from_slice(&(messages.len() as u32).to_le_bytes());
+    for message in messages {
+        input.extend_from_slice(&(message.len() as u32).to_le_bytes());
+        input.extend_from_slice(message);
+    }
+    input
+}
+
+/// Decode guest input produced by [`encode_shard`]
+pub fn decode_shard(input: &[u8]) -> Result<Vec<Vec<u8>>, ZkError> {
+    fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], ZkError> {
+        if input.len() < len {
+            return Err(ZkError::InvalidInput("Truncated shard input".into()));
+        }
+        let (head, rest) = input.split_at(len);
+        *input = rest;
+        Ok(head)
+    }
+    fn length(input: &mut &[u8]) -> Result<usize, ZkError> {
+        let bytes = take(input, 4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+
+    let mut input = input;
+    let count = length(&mut input)?;
+    let mut messages = Vec::with_capacity(count.min(input.len() / 4));
+    for _ in 0..count {
+        let len = length(&mut input)?;
+        messages.push(take(&mut input, len)?.to_vec());
+    }
+    if !input.is_empty() {
+        return Err(ZkError::InvalidInput("Trailing bytes after shard input".into()));
+    }
+    Ok(messages)
+}
+
+/// Proof of a single shard
+#[derive(Debug, Clone)]
+pub struct ShardProof {
+    /// Shard index within the batch
+    pub index: usize,
+    /// Messages of the batch covered by the shard
+    pub range: Range<usize>,
+    /// SHA-256 of the shard input, computed by the host
+    pub commitment: [u8; 32],
+    /// Serialized proof
+    pub proof: Vec<u8>,
+    /// Proof metadata
+    pub metadata: ProofMetadata,
+}
+
+/// Independent shard proofs of a complete batch
+///
+/// Each shard proof must be verified on its own; nothing proves that the shards
+/// together cover the batch.
+#[derive(Debug, Clone)]
+pub struct ShardProofs {
+    /// Shard proofs in batch order
+    pub shards: Vec<ShardProof>,
+    /// SHA-256 over the ordered shard commitments, computed by the host
+    pub commitment: [u8; 32],
+}
+
+impl ShardProofs {
+    /// Compute the batch commitment over ordered shard commitments
+    pub fn compute_commitment(shards: &[ShardProof]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for shard in shards {
+            hasher.update(shard.commitment);
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// Pool of proving workers
+#[derive(Debug)]
+pub struct Cluster<B> {
+    workers: Vec<Arc<B>>,
+    shard_size: usize,
+}
+
+impl<B: ZkBackend + 'static> Cluster<B> {
+    /// Create a cluster from its workers
+    pub fn new(workers: Vec<Arc<B>>) -> Self {
+        Self {
+            workers,
+            shard_size: DEFAULT_SHARD_SIZE,
+        }
+    }
+
+    /// Set the number of messages per shard
+    pub fn with_shard_size(mut self, shard_size: usize) -> Self {
+        self.shard_size = shard_size.max(1);
+        self
+    }
+
+    /// Number of workers in the cluster
+    pub fn worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Prove a batch by proving its shards concurrently across the workers
+    pub async fn prove_sharded(&self, batch: &BatchStatement) -> Result<ShardProofs, ZkError> {
+        if self.workers.is_empty() {
+            return Err(ZkError::Backend("Cluster has no workers".into()));
+        }
+
+        let tasks = batch.shard_ranges(self.shard_size)
+            .into_iter()
+            .enumerate()
+            .map(|(index, range)| {
+                let worker = self.workers[index % self.workers.len()].clone();
+                let input = encode_shard(&batch.messages[range.clone()]);
+                let program = &batch.program;
+                async move {
+                    let commitment: [u8; 32] = Sha256::digest(&input).into();
+                    let (proof, metadata) = worker.prove(program, &input, None)
+                        .await
+                        .map_err(ZkError::from)?;
+                    Ok::<_, ZkError>(ShardProof {
+                        index,
+                        range,
+                        commitment,
+                        proof,
+                        metadata,
+                    })
+                }
+            });
+
+        let shards = future::try_join_all(tasks).await?;
+        let commitment = ShardProofs::compute_commitment(&shards);
+        Ok(ShardProofs { shards, commitment })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_ranges() {
+        let batch = BatchStatement {
+            program: vec![0x01],
+            messages: vec![vec![0u8; 4]; 10_000],
+        };
+        let ranges = batch.shard_ranges(DEFAULT_SHARD_SIZE);
+        assert_eq!(ranges.len(), 20);
+        assert_eq!(ranges.last().unwrap(), &(9_500..10_000));
+
+        let ranges = batch.shard_ranges(3_000);
+        assert_eq!(ranges.len(), 4);
+        assert_eq!(ranges.last().unwrap().len(), 1_000);
+    }
+
+    #[test]
+    fn test_encode_shard() {
+        let input = encode_shard(&[vec![0xAA], vec![0xBB, 0xCC]]);
+        assert_eq!(input, vec![2, 0, 0, 0, 1, 0, 0, 0, 0xAA, 2, 0, 0, 0, 0xBB, 0xCC]);
+        assert_eq!(decode_shard(&input).unwrap(), vec![vec![0xAA], vec![0xBB, 0xCC]]);
+        assert!(decode_shard(&input[..10]).is_err());
+    }
+}
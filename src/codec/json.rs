@@ -0,0 +1,281 @@
+//! Canonical JSON
+//!
+//! The canonical form has no insignificant whitespace and object members sorted by
+//! the bytes of their (still escaped) keys. Strings and numbers are copied verbatim,
+//! so two encoders that agree on the data but not on key order or formatting produce
+//! identical canonical bytes. Numbers must follow the RFC 8259 grammar, and keys
+//! are compared after unescaping when looking for duplicates.
+
+use alloc::vec::Vec;
+use super::CodecError;
+
+/// Maximum nesting depth accepted
+pub const MAX_DEPTH: usize = 64;
+
+/// Canonicalize a JSON document
+pub fn canonicalize(input: &[u8]) -> Result<Vec<u8>, CodecError> {
+    let mut parser = Parser { input, pos: 0 };
+    let mut out = Vec::with_capacity(input.len());
+
+    parser.skip_whitespace();
+    parser.value(&mut out, 0)?;
+    parser.skip_whitespace();
+    if parser.pos != input.len() {
+        return Err(CodecError::TrailingBytes(parser.pos));
+    }
+
+    Ok(out)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Result<u8, CodecError> {
+        self.input.get(self.pos).copied().ok_or(CodecError::UnexpectedEnd)
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), CodecError> {
+        if self.peek()? != byte {
+            return Err(CodecError::UnexpectedByte(self.pos));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.input.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self, out: &mut Vec<u8>, depth: usize) -> Result<(), CodecError> {
+        if depth > MAX_DEPTH {
+            return Err(CodecError::TooDeep);
+        }
+        match self.peek()? {
+            b'{' => self.object(out, depth),
+            b'[' => self.array(out, depth),
+            b'"' => {
+                let string = self.string()?;
+                out.extend_from_slice(string);
+                Ok(())
+            }
+            b't' => self.literal(b"true", out),
+            b'f' => self.literal(b"false", out),
+            b'n' => self.literal(b"null", out),
+            b'-' | b'0'..=b'9' => self.number(out),
+            _ => Err(CodecError::UnexpectedByte(self.pos)),
+        }
+    }
+
+    fn literal(&mut self, literal: &[u8], out: &mut Vec<u8>) -> Result<(), CodecError> {
+        let end = self.pos + literal.len();
+        if self.input.get(self.pos..end) != Some(literal) {
+            return Err(CodecError::UnexpectedByte(self.pos));
+        }
+        self.pos = end;
+        out.extend_from_slice(literal);
+        Ok(())
+    }
+
+    /// Read a number: `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`
+    fn number(&mut self, out: &mut Vec<u8>) -> Result<(), CodecError> {
+        let start = self.pos;
+        if self.input.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        match self.peek()? {
+            b'0' => self.pos += 1,
+            b'1'..=b'9' => self.digits()?,
+            _ => return Err(CodecError::UnexpectedByte(self.pos)),
+        }
+        if self.input.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            self.digits()?;
+        }
+        if let Some(b'e' | b'E') = self.input.get(self.pos) {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.input.get(self.pos) {
+                self.pos += 1;
+            }
+            self.digits()?;
+        }
+        out.extend_from_slice(&self.input[start..self.pos]);
+        Ok(())
+    }
+
+    /// Read one or more decimal digits
+    fn digits(&mut self) -> Result<(), CodecError> {
+        if !self.peek()?.is_ascii_digit() {
+            return Err(CodecError::UnexpectedByte(self.pos));
+        }
+        while self.input.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    /// Read a string token including its quotes
+    fn string(&mut self) -> Result<&'a [u8], CodecError> {
+        let start = self.pos;
+        self.expect(b'"')?;
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    break;
+                }
+                0x00..=0x1f => return Err(CodecError::UnexpectedByte(self.pos)),
+                _ => self.pos += 1,
+            }
+        }
+        self.input.get(start..self.pos).ok_or(CodecError::UnexpectedEnd)
+    }
+
+    fn object(&mut self, out: &mut Vec<u8>, depth: usize) -> Result<(), CodecError> {
+        self.expect(b'{')?;
+        self.skip_whitespace();
+
+        let mut members: Vec<(&'a [u8], Vec<u8>)> = Vec::new();
+        let mut keys = Vec::new();
+        if self.peek()? == b'}' {
+            self.pos += 1;
+        } else {
+            loop {
+                self.skip_whitespace();
+                let offset = self.pos;
+                let key = self.string()?;
+                keys.push(unescape(key, offset)?);
+                self.skip_whitespace();
+                self.expect(b':')?;
+                self.skip_whitespace();
+                let mut value = Vec::new();
+                self.value(&mut value, depth + 1)?;
+                members.push((key, value));
+                self.skip_whitespace();
+                match self.peek()? {
+                    b',' => self.pos += 1,
+                    b'}' => {
+                        self.pos += 1;
+                        break;
+                    }
+                    _ => return Err(CodecError::UnexpectedByte(self.pos)),
+                }
+            }
+        }
+
+        // Reject keys that are the same once unescaped, then sort members by key
+        keys.sort_unstable();
+        if keys.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(CodecError::DuplicateKey);
+        }
+        members.sort_by(|a, b| a.0.cmp(b.0));
+
+        out.push(b'{');
+        for (i, (key, value)) in members.iter().enumerate() {
+            if i > 0 {
+                out.push(b',');
+            }
+            out.extend_from_slice(key);
+            out.push(b':');
+            out.extend_from_slice(value);
+        }
+        out.push(b'}');
+        Ok(())
+    }
+
+    fn array(&mut self, out: &mut Vec<u8>, depth: usize) -> Result<(), CodecError> {
+        self.expect(b'[')?;
+        self.skip_whitespace();
+        out.push(b'[');
+
+        if self.peek()? == b']' {
+            self.pos += 1;
+        } else {
+            let mut first = true;
+            loop {
+                self.skip_whitespace();
+                if !first {
+                    out.push(b',');
+                }
+                first = false;
+                self.value(out, depth + 1)?;
+                self.skip_whitespace();
+                match self.peek()? {
+                    b',' => self.pos += 1,
+                    b']' => {
+                        self.pos += 1;
+                        break;
+                    }
+                    _ => return Err(CodecError::UnexpectedByte(self.pos)),
+                }
+            }
+        }
+
+        out.push(b']');
+        Ok(())
+    }
+}
+
+/// UTF-8 content of the string token at `offset`, with escapes resolved
+fn unescape(token: &[u8], offset: usize) -> Result<Vec<u8>, CodecError> {
+    let end = token.len() - 1;
+    let mut out = Vec::with_capacity(end);
+    let mut pos = 1;
+    while pos < end {
+        if token[pos] != b'\\' {
+            out.push(token[pos]);
+            pos += 1;
+            continue;
+        }
+        let escape = pos + 1;
+        pos += 2;
+        let unit = match token[escape] {
+            byte @ (b'"' | b'\\' | b'/') => {
+                out.push(byte);
+                continue;
+            }
+            b'b' => 0x08,
+            b'f' => 0x0c,
+            b'n' => 0x0a,
+            b'r' => 0x0d,
+            b't' => 0x09,
+            b'u' => {
+                let unit = hex_unit(token, pos, offset)?;
+                pos += 4;
+                if (0xd800..0xdc00).contains(&unit) {
+                    // A high surrogate must be followed by an escaped low surrogate
+                    let low = match token.get(pos..pos + 2) {
+                        Some(b"\\u") => hex_unit(token, pos + 2, offset)?,
+                        _ => return Err(CodecError::UnexpectedByte(offset + pos)),
+                    };
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(CodecError::UnexpectedByte(offset + pos));
+                    }
+                    pos += 6;
+                    0x10000 + ((unit - 0xd800) << 10) + (low - 0xdc00)
+                } else {
+                    unit
+                }
+            }
+            _ => return Err(CodecError::UnexpectedByte(offset + escape)),
+        };
+        // Lone low surrogates are not characters
+        let c = char::from_u32(unit).ok_or(CodecError::UnexpectedByte(offset + escape))?;
+        out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+    Ok(out)
+}
+
+/// Value of the four hex digits at `pos` of the token at `offset`
+fn hex_unit(token: &[u8], pos: usize, offset: usize) -> Result<u32, CodecError> {
+    let digits = token.get(pos..pos + 4).ok_or(CodecError::UnexpectedEnd)?;
+    digits.iter().zip(pos..).try_fold(0, |unit, (digit, at)| {
+        let value = char::from(*digit).to_digit(16).ok_or(CodecError::UnexpectedByte(offset + at))?;
+        Ok(unit << 4 | value)
+    })
+}
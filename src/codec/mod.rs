@@ -0,0 +1,61 @@
+//! Canonical input encoders
+//!
+//! Hashing circuits must hash exactly the same bytes on the host and in the guest.
+//! This module provides canonical encoders for the formats Frostgate messages arrive
+//! in (RLP, SCALE, SSZ, Protocol Buffers and JSON). It only depends on `alloc` so the guest programs in
+//! `circuits/` include the very same source file instead of reimplementing it.
+
+pub mod json;
+pub mod protobuf;
+pub mod rlp;
+pub mod scale;
+pub mod ssz;
+
+use core::fmt;
+
+/// Error produced while canonicalizing input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    /// Input ended before a complete value was read
+    UnexpectedEnd,
+    /// Unexpected byte at the given offset
+    UnexpectedByte(usize),
+    /// Trailing bytes after a complete value
+    TrailingBytes(usize),
+    /// Object contains the same key twice
+    DuplicateKey,
+    /// Nesting exceeds the supported depth
+    TooDeep,
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            CodecError::UnexpectedByte(offset) => write!(f, "unexpected byte at offset {}", offset),
+            CodecError::TrailingBytes(offset) => write!(f, "trailing bytes at offset {}", offset),
+            CodecError::DuplicateKey => write!(f, "duplicate object key"),
+            CodecError::TooDeep => write!(f, "nesting too deep"),
+        }
+    }
+}
+
+/// Input encoding used before hashing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Bytes are hashed as-is
+    Raw,
+    /// JSON is canonicalized (sorted keys, no insignificant whitespace)
+    CanonicalJson,
+}
+
+/// Canonicalize `input` according to `encoding`
+pub fn canonicalize(encoding: Encoding, input: &[u8]) -> Result<alloc::vec::Vec<u8>, CodecError> {
+    match encoding {
+        Encoding::Raw => Ok(input.to_vec()),
+        Encoding::CanonicalJson => json::canonicalize(input),
+    }
+}
+
+#[cfg(test)]
+mod tests;
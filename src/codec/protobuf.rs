@@ -0,0 +1,70 @@
+//! Protocol Buffers encoding (Tendermint / CometBFT)
+//!
+//! Only what hashing and signing need: fields are written in field number order and
+//! scalar fields holding their default value are omitted, as proto3 encoders do.
+
+use alloc::vec::Vec;
+
+/// Wire type of varint fields
+pub const WIRE_VARINT: u8 = 0;
+/// Wire type of 64-bit fixed-width fields
+pub const WIRE_FIXED64: u8 = 1;
+/// Wire type of length-delimited fields (bytes, strings and messages)
+pub const WIRE_LEN: u8 = 2;
+
+/// Encode a varint
+pub fn encode_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Encode a field key
+pub fn encode_key(field: u32, wire_type: u8, out: &mut Vec<u8>) {
+    encode_varint((u64::from(field) << 3) | u64::from(wire_type), out);
+}
+
+/// Encode a `uint64`, `uint32` or enum field, omitted if zero
+pub fn encode_uint(field: u32, value: u64, out: &mut Vec<u8>) {
+    if value != 0 {
+        encode_key(field, WIRE_VARINT, out);
+        encode_varint(value, out);
+    }
+}
+
+/// Encode an `int64` or `int32` field, omitted if zero
+pub fn encode_int(field: u32, value: i64, out: &mut Vec<u8>) {
+    encode_uint(field, value as u64, out);
+}
+
+/// Encode an `sfixed64` field, omitted if zero
+pub fn encode_sfixed64(field: u32, value: i64, out: &mut Vec<u8>) {
+    if value != 0 {
+        encode_key(field, WIRE_FIXED64, out);
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Encode a `bytes` or `string` field, omitted if empty
+pub fn encode_bytes(field: u32, bytes: &[u8], out: &mut Vec<u8>) {
+    if !bytes.is_empty() {
+        encode_message(field, bytes, out);
+    }
+}
+
+/// Encode an embedded message field from its encoding, written even if empty
+pub fn encode_message(field: u32, message: &[u8], out: &mut Vec<u8>) {
+    encode_key(field, WIRE_LEN, out);
+    encode_varint(message.len() as u64, out);
+    out.extend_from_slice(message);
+}
+
+/// Prefix a message with its varint length
+pub fn length_delimited(message: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(message.len() + 2);
+    encode_varint(message.len() as u64, &mut out);
+    out.extend_from_slice(message);
+    out
+}
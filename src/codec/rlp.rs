@@ -0,0 +1,170 @@
+//! Recursive Length Prefix encoding (Ethereum)
+
+use alloc::vec::Vec;
+
+use super::CodecError;
+
+/// Maximum nesting of lists accepted by [`decode`]
+const MAX_DEPTH: usize = 16;
+
+/// RLP item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RlpItem {
+    /// Byte string
+    Bytes(Vec<u8>),
+    /// List of items
+    List(Vec<RlpItem>),
+}
+
+impl RlpItem {
+    /// Encode an unsigned integer as a minimal big-endian byte string
+    pub fn uint(value: u64) -> Self {
+        let bytes = value.to_be_bytes();
+        let first = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+        RlpItem::Bytes(bytes[first..].to_vec())
+    }
+
+    /// Byte string contents, `None` for lists
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            RlpItem::Bytes(bytes) => Some(bytes),
+            RlpItem::List(_) => None,
+        }
+    }
+
+    /// List items, `None` for byte strings
+    pub fn as_list(&self) -> Option<&[RlpItem]> {
+        match self {
+            RlpItem::List(items) => Some(items),
+            RlpItem::Bytes(_) => None,
+        }
+    }
+
+    /// Unsigned integer encoded as a minimal big-endian byte string
+    pub fn as_u64(&self) -> Option<u64> {
+        let bytes = self.as_bytes()?;
+        if bytes.len() > 8 || bytes.first() == Some(&0) {
+            return None;
+        }
+        Some(bytes.iter().fold(0u64, |value, byte| (value << 8) | u64::from(*byte)))
+    }
+}
+
+/// Encode an item
+pub fn encode(item: &RlpItem) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_into(item, &mut out);
+    out
+}
+
+/// Encode a byte string
+pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() + 9);
+    encode_bytes_into(bytes, &mut out);
+    out
+}
+
+fn encode_into(item: &RlpItem, out: &mut Vec<u8>) {
+    match item {
+        RlpItem::Bytes(bytes) => encode_bytes_into(bytes, out),
+        RlpItem::List(items) => {
+            let mut payload = Vec::new();
+            for item in items {
+                encode_into(item, &mut payload);
+            }
+            encode_length(payload.len(), 0xc0, out);
+            out.extend_from_slice(&payload);
+        }
+    }
+}
+
+fn encode_bytes_into(bytes: &[u8], out: &mut Vec<u8>) {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        out.push(bytes[0]);
+    } else {
+        encode_length(bytes.len(), 0x80, out);
+        out.extend_from_slice(bytes);
+    }
+}
+
+fn encode_length(len: usize, offset: u8, out: &mut Vec<u8>) {
+    if len < 56 {
+        out.push(offset + len as u8);
+    } else {
+        let bytes = (len as u64).to_be_bytes();
+        let first = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+        out.push(offset + 55 + (bytes.len() - first) as u8);
+        out.extend_from_slice(&bytes[first..]);
+    }
+}
+
+/// Decode a single item spanning all of `bytes`
+///
+/// Only canonical encodings are accepted, so every item has exactly one encoding
+/// and hashes of decoded input are unambiguous.
+pub fn decode(bytes: &[u8]) -> Result<RlpItem, CodecError> {
+    let (item, end) = decode_item(bytes, 0, 0)?;
+    if end != bytes.len() {
+        return Err(CodecError::TrailingBytes(end));
+    }
+    Ok(item)
+}
+
+/// Decode the item at `offset`, returning it and the offset following it
+fn decode_item(bytes: &[u8], offset: usize, depth: usize) -> Result<(RlpItem, usize), CodecError> {
+    if depth > MAX_DEPTH {
+        return Err(CodecError::TooDeep);
+    }
+    let prefix = *bytes.get(offset).ok_or(CodecError::UnexpectedEnd)?;
+    let (is_list, start, len) = match prefix {
+        0x00..=0x7f => return Ok((RlpItem::Bytes(Vec::from([prefix])), offset + 1)),
+        0x80..=0xb7 => (false, offset + 1, (prefix - 0x80) as usize),
+        0xb8..=0xbf => {
+            let (start, len) = long_length(bytes, offset, prefix - 0xb7)?;
+            (false, start, len)
+        }
+        0xc0..=0xf7 => (true, offset + 1, (prefix - 0xc0) as usize),
+        0xf8..=0xff => {
+            let (start, len) = long_length(bytes, offset, prefix - 0xf7)?;
+            (true, start, len)
+        }
+    };
+    let end = start.checked_add(len)
+        .filter(|end| *end <= bytes.len())
+        .ok_or(CodecError::UnexpectedEnd)?;
+
+    if !is_list {
+        // A single byte below 0x80 is its own encoding
+        if len == 1 && bytes[start] < 0x80 {
+            return Err(CodecError::UnexpectedByte(offset));
+        }
+        return Ok((RlpItem::Bytes(bytes[start..end].to_vec()), end));
+    }
+    let mut items = Vec::new();
+    let mut cursor = start;
+    while cursor < end {
+        let (item, next) = decode_item(&bytes[..end], cursor, depth + 1)?;
+        items.push(item);
+        cursor = next;
+    }
+    Ok((RlpItem::List(items), end))
+}
+
+/// Read the length of a long string or list, returning the payload offset and length
+fn long_length(bytes: &[u8], offset: usize, len_of_len: u8) -> Result<(usize, usize), CodecError> {
+    let len_of_len = len_of_len as usize;
+    if len_of_len > core::mem::size_of::<usize>() {
+        return Err(CodecError::UnexpectedByte(offset));
+    }
+    let start = offset + 1 + len_of_len;
+    let len_bytes = bytes.get(offset + 1..start).ok_or(CodecError::UnexpectedEnd)?;
+    if len_bytes[0] == 0 {
+        return Err(CodecError::UnexpectedByte(offset + 1));
+    }
+    let len = len_bytes.iter().fold(0usize, |len, byte| (len << 8) | *byte as usize);
+    // Short payloads must use the single-byte prefix
+    if len < 56 {
+        return Err(CodecError::UnexpectedByte(offset));
+    }
+    Ok((start, len))
+}
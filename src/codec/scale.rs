@@ -0,0 +1,62 @@
+//! SCALE encoding (Substrate)
+
+use alloc::vec::Vec;
+
+use super::CodecError;
+
+/// Encode an integer in SCALE compact form
+pub fn encode_compact(value: u128) -> Vec<u8> {
+    match value {
+        0..=0x3f => Vec::from([(value as u8) << 2]),
+        0x40..=0x3fff => (((value as u16) << 2) | 0b01).to_le_bytes().to_vec(),
+        0x4000..=0x3fff_ffff => (((value as u32) << 2) | 0b10).to_le_bytes().to_vec(),
+        _ => {
+            let bytes = value.to_le_bytes();
+            let len = bytes.iter().rposition(|b| *b != 0).map_or(1, |i| i + 1).max(4);
+            let mut out = Vec::with_capacity(len + 1);
+            out.push((((len - 4) as u8) << 2) | 0b11);
+            out.extend_from_slice(&bytes[..len]);
+            out
+        }
+    }
+}
+
+/// Decode an integer in SCALE compact form, returning it and the number of bytes read
+pub fn decode_compact(bytes: &[u8]) -> Result<(u128, usize), CodecError> {
+    let first = *bytes.first().ok_or(CodecError::UnexpectedEnd)?;
+    let len = match first & 0b11 {
+        0b00 => return Ok((u128::from(first >> 2), 1)),
+        0b01 => 2,
+        0b10 => 4,
+        _ => (first >> 2) as usize + 5,
+    };
+    if len > 17 {
+        return Err(CodecError::UnexpectedByte(0));
+    }
+    let encoded = bytes.get(..len).ok_or(CodecError::UnexpectedEnd)?;
+    let mut le = [0u8; 16];
+    if first & 0b11 == 0b11 {
+        // Big integer mode: the value follows the length byte
+        le[..len - 1].copy_from_slice(&encoded[1..]);
+        return Ok((u128::from_le_bytes(le), len));
+    }
+    le[..len].copy_from_slice(encoded);
+    Ok((u128::from_le_bytes(le) >> 2, len))
+}
+
+/// Encode a byte vector (compact length prefix followed by the bytes)
+pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut out = encode_compact(bytes.len() as u128);
+    out.extend_from_slice(bytes);
+    out
+}
+
+/// Encode a `u32` as fixed-width little endian
+pub fn encode_u32(value: u32) -> Vec<u8> {
+    value.to_le_bytes().to_vec()
+}
+
+/// Encode a `u64` as fixed-width little endian
+pub fn encode_u64(value: u64) -> Vec<u8> {
+    value.to_le_bytes().to_vec()
+}
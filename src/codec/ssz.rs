@@ -0,0 +1,51 @@
+//! Simple Serialize encoding (Ethereum consensus layer)
+
+use alloc::vec::Vec;
+
+/// Size of a variable-length field offset
+pub const OFFSET_SIZE: usize = 4;
+
+/// Field of an SSZ container
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SszField {
+    /// Fixed-size field, serialized inline
+    Fixed(Vec<u8>),
+    /// Variable-size field, serialized after the fixed part and referenced by offset
+    Variable(Vec<u8>),
+}
+
+/// Encode a `u64`
+pub fn encode_u64(value: u64) -> Vec<u8> {
+    value.to_le_bytes().to_vec()
+}
+
+/// Encode a boolean
+pub fn encode_bool(value: bool) -> Vec<u8> {
+    Vec::from([value as u8])
+}
+
+/// Encode a container from its fields in declaration order
+pub fn encode_container(fields: &[SszField]) -> Vec<u8> {
+    let fixed_len: usize = fields.iter()
+        .map(|field| match field {
+            SszField::Fixed(bytes) => bytes.len(),
+            SszField::Variable(_) => OFFSET_SIZE,
+        })
+        .sum();
+
+    let mut fixed = Vec::with_capacity(fixed_len);
+    let mut variable = Vec::new();
+    for field in fields {
+        match field {
+            SszField::Fixed(bytes) => fixed.extend_from_slice(bytes),
+            SszField::Variable(bytes) => {
+                let offset = (fixed_len + variable.len()) as u32;
+                fixed.extend_from_slice(&offset.to_le_bytes());
+                variable.extend_from_slice(bytes);
+            }
+        }
+    }
+
+    fixed.extend_from_slice(&variable);
+    fixed
+}
@@ -0,0 +1,154 @@
+//! Property tests for the canonical encoders
+//!
+//! The guest programs compile this same module, so checking it against reference
+//! implementations here is what keeps host and guest hashing in agreement.
+
+use super::*;
+use proptest::prelude::*;
+use serde_json::{json, Map, Value};
+
+fn arb_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(|n| json!(n)),
+        "[a-zA-Z0-9 ]{0,16}".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(4, 32, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(Value::Array),
+            prop::collection::btree_map("[a-z]{1,8}", inner, 0..8)
+                .prop_map(|map| Value::Object(map.into_iter().collect::<Map<_, _>>())),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn json_formatting_does_not_change_canonical_form(value in arb_json()) {
+        let compact = serde_json::to_vec(&value).unwrap();
+        let pretty = serde_json::to_vec_pretty(&value).unwrap();
+        prop_assert_eq!(json::canonicalize(&compact).unwrap(), json::canonicalize(&pretty).unwrap());
+    }
+
+    #[test]
+    fn json_canonical_form_is_valid_and_idempotent(value in arb_json()) {
+        let canonical = json::canonicalize(&serde_json::to_vec(&value).unwrap()).unwrap();
+        let reparsed: Value = serde_json::from_slice(&canonical).unwrap();
+        prop_assert_eq!(&reparsed, &value);
+        prop_assert_eq!(json::canonicalize(&canonical).unwrap(), canonical);
+    }
+
+    #[test]
+    fn rlp_bytes_match_reference(bytes in prop::collection::vec(any::<u8>(), 0..128)) {
+        prop_assert_eq!(rlp::encode_bytes(&bytes), ::rlp::encode(&bytes).to_vec());
+    }
+
+    #[test]
+    fn rlp_uint_matches_reference(value in any::<u64>()) {
+        prop_assert_eq!(rlp::encode(&rlp::RlpItem::uint(value)), ::rlp::encode(&value).to_vec());
+    }
+
+    #[test]
+    fn rlp_decode_round_trips_reference(items in prop::collection::vec(prop::collection::vec(any::<u8>(), 0..80), 0..8)) {
+        let mut stream = ::rlp::RlpStream::new_list(items.len());
+        for item in &items {
+            stream.append(item);
+        }
+        let decoded = rlp::decode(&stream.out()).unwrap();
+        let expected: Vec<_> = items.into_iter().map(rlp::RlpItem::Bytes).collect();
+        prop_assert_eq!(decoded, rlp::RlpItem::List(expected));
+    }
+
+    #[test]
+    fn protobuf_varint_matches_reference(value in any::<u64>()) {
+        let mut encoded = Vec::new();
+        protobuf::encode_varint(value, &mut encoded);
+        let mut expected = Vec::new();
+        ::prost::encoding::encode_varint(value, &mut expected);
+        prop_assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn protobuf_fields_match_reference(number in any::<i64>(), fixed in any::<i64>(), bytes in prop::collection::vec(any::<u8>(), 0..200)) {
+        use ::prost::encoding::{bytes as pb_bytes, int64, sfixed64};
+        let mut encoded = Vec::new();
+        protobuf::encode_int(1, number, &mut encoded);
+        protobuf::encode_sfixed64(2, fixed, &mut encoded);
+        protobuf::encode_bytes(3, &bytes, &mut encoded);
+        let mut expected = Vec::new();
+        if number != 0 {
+            int64::encode(1, &number, &mut expected);
+        }
+        if fixed != 0 {
+            sfixed64::encode(2, &fixed, &mut expected);
+        }
+        if !bytes.is_empty() {
+            pb_bytes::encode(3, &bytes, &mut expected);
+        }
+        prop_assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn scale_compact_decodes_own_encoding(value in any::<u128>()) {
+        let encoded = scale::encode_compact(value);
+        prop_assert_eq!(scale::decode_compact(&encoded).unwrap(), (value, encoded.len()));
+    }
+
+    #[test]
+    fn scale_compact_matches_reference(value in any::<u128>()) {
+        use parity_scale_codec::{Compact, Encode};
+        prop_assert_eq!(scale::encode_compact(value), Compact(value).encode());
+    }
+
+    #[test]
+    fn scale_bytes_match_reference(bytes in prop::collection::vec(any::<u8>(), 0..128)) {
+        use parity_scale_codec::Encode;
+        prop_assert_eq!(scale::encode_bytes(&bytes), bytes.encode());
+    }
+}
+
+#[test]
+fn test_json_key_order() {
+    let a = br#"{"b": 1, "a": [true, null]}"#;
+    let b = br#"{"a":[true,null],"b":1}"#;
+    assert_eq!(json::canonicalize(a).unwrap(), b.to_vec());
+}
+
+#[test]
+fn test_json_rejects_duplicate_keys() {
+    assert_eq!(json::canonicalize(br#"{"a":1,"a":2}"#), Err(CodecError::DuplicateKey));
+    // Keys are compared once unescaped
+    assert_eq!(json::canonicalize(br#"{"a":1,"\u0061":2}"#), Err(CodecError::DuplicateKey));
+    assert_eq!(json::canonicalize(r#"{"\ud83d\ude00":1,"\u00e9":2,"é":3}"#.as_bytes()), Err(CodecError::DuplicateKey));
+    assert!(json::canonicalize(br#"{"\ud83d":1}"#).is_err());
+}
+
+#[test]
+fn test_json_number_grammar() {
+    for number in ["0", "-0", "12", "-1.5", "1e9", "2.5E-3", "1e+2"] {
+        assert_eq!(json::canonicalize(number.as_bytes()).unwrap(), number.as_bytes(), "{}", number);
+    }
+    for number in ["-", "1e", "--1", "1.2.3", "01", "1.", ".5", "+1", "1e+"] {
+        assert!(json::canonicalize(number.as_bytes()).is_err(), "{}", number);
+    }
+}
+
+#[test]
+fn test_rlp_rejects_non_canonical() {
+    // 0x05 encoded as a one-byte string, and a short string with a long prefix
+    assert_eq!(rlp::decode(&[0x81, 0x05]), Err(CodecError::UnexpectedByte(0)));
+    assert_eq!(rlp::decode(&[0xb8, 0x01, 0xff]), Err(CodecError::UnexpectedByte(0)));
+    assert_eq!(rlp::decode(&[0x82, 0x01]), Err(CodecError::UnexpectedEnd));
+    assert_eq!(rlp::decode(&[0x01, 0x02]), Err(CodecError::TrailingBytes(1)));
+    assert_eq!(rlp::decode(&[0x82, 0x00, 0x01]).unwrap().as_u64(), None);
+}
+
+#[test]
+fn test_ssz_container_offsets() {
+    let encoded = ssz::encode_container(&[
+        ssz::SszField::Fixed(ssz::encode_u64(7)),
+        ssz::SszField::Variable(vec![0xaa, 0xbb]),
+    ]);
+    assert_eq!(encoded, [7, 0, 0, 0, 0, 0, 0, 0, 12, 0, 0, 0, 0xaa, 0xbb]);
+}
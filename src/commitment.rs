@@ -0,0 +1,98 @@
+//! Poseidon commitments to messages
+//!
+//! A [`PoseidonCommitCircuit`] proves knowledge of a message with a given Poseidon
+//! commitment, computed by [`HashFunction::Poseidon`]. Unlike the SHA-256 or Keccak
+//! digests of message verification, the commitment is cheap to recompute inside
+//! another SNARK, so a proof's output can be re-verified by the aggregation circuit
+//! or an on-chain Poseidon verifier. Those verifiers match the instance and sponge
+//! described by [`POSEIDON_PARAMS`].
+//!
+//! Its program is a [`ProgramDescriptor`] of [`POSEIDON_COMMIT`] with the commitment
+//! as parameters; the SP1 backend runs the `poseidon_commit` guest listed for that
+//! type in the program manifest. The proving input is the message itself.
+
+use crate::descriptor::ProgramDescriptor;
+use crate::error::ZkError;
+use crate::hasher::{HashFunction, PoseidonParams, POSEIDON_PARAMS};
+use crate::journal::MessageVerifyOutput;
+use crate::profiles::POSEIDON_COMMIT;
+
+/// Knowledge of a message with a trusted Poseidon commitment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoseidonCommitCircuit {
+    /// Poseidon commitment to the message
+    pub commitment: [u8; 32],
+}
+
+impl PoseidonCommitCircuit {
+    /// Create a verifier of messages committed to by `commitment`
+    pub fn new(commitment: [u8; 32]) -> Self {
+        Self { commitment }
+    }
+
+    /// Create a verifier of `message`
+    pub fn for_message(message: &[u8]) -> Self {
+        Self::new(HashFunction::Poseidon.hash(message))
+    }
+
+    /// Parameters of the Poseidon instance the commitment is computed with
+    pub fn params(&self) -> PoseidonParams {
+        POSEIDON_PARAMS
+    }
+
+    /// Descriptor of the commitment program
+    pub fn descriptor(&self) -> ProgramDescriptor {
+        ProgramDescriptor::new(POSEIDON_COMMIT, self.commitment)
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.descriptor().encode()
+    }
+
+    /// Proving input for `message`, checked to match the commitment
+    pub fn input(&self, message: &[u8]) -> Result<Vec<u8>, ZkError> {
+        if HashFunction::Poseidon.hash(message) != self.commitment {
+            return Err(ZkError::InvalidInput("Message does not match the Poseidon commitment".into()));
+        }
+        Ok(message.to_vec())
+    }
+
+    /// Decode the public values of a proof, rejecting other commitments
+    pub fn output(&self, public_values: &[u8]) -> Result<MessageVerifyOutput, ZkError> {
+        let output = MessageVerifyOutput::decode(public_values)?;
+        if output.hash != self.commitment {
+            return Err(ZkError::ProofVerification(format!(
+                "Proof commits to {}, expected {}",
+                hex::encode(output.hash),
+                hex::encode(self.commitment)
+            )));
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ChainId, DomainSeparator};
+
+    #[test]
+    fn test_poseidon_commit_circuit() {
+        let message = b"Hello, World!";
+        let circuit = PoseidonCommitCircuit::for_message(message);
+        assert_eq!(ProgramDescriptor::decode(&circuit.program()).unwrap().circuit_type, POSEIDON_COMMIT);
+        assert_eq!(circuit.params().width, 3);
+
+        assert_eq!(circuit.input(message).unwrap(), message.to_vec());
+        assert!(circuit.input(b"Goodbye, World!").is_err());
+
+        let domain = DomainSeparator::new(ChainId(1), ChainId(137));
+        let mut public_values = circuit.commitment.to_vec();
+        public_values.extend_from_slice(&domain.to_bytes());
+        assert_eq!(circuit.output(&public_values).unwrap().domain, Some(domain));
+
+        let other = PoseidonCommitCircuit::for_message(b"Goodbye, World!");
+        assert!(other.output(&public_values).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+//! zstd compression of proofs
+//!
+//! SP1 core proofs run to several megabytes and compress well. Proofs are
+//! compressed in the proof caches, inside compressed envelopes and on the wire
+//! when a remote prover is asked to. Compressed bytes are recognized by the zstd
+//! frame magic, which neither raw proofs of either backend nor envelopes start
+//! with, so readers accept compressed and uncompressed proofs alike.
+
+use std::borrow::Cow;
+use std::io::{self, Read};
+
+/// Magic prefix of zstd frames
+pub const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Compression level proofs are compressed with
+pub const DEFAULT_LEVEL: i32 = 3;
+
+/// Largest decompressed proof accepted, bounding what a crafted frame can expand to
+pub const MAX_DECOMPRESSED_SIZE: usize = 256 << 20;
+
+/// Whether `bytes` start with a zstd frame
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&ZSTD_MAGIC)
+}
+
+/// Compress `bytes` into a single zstd frame recording its decompressed size
+pub fn compress(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    zstd::bulk::compress(bytes, DEFAULT_LEVEL)
+}
+
+/// Decompress a zstd frame of at most [`MAX_DECOMPRESSED_SIZE`] bytes
+pub fn decompress(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    zstd::stream::read::Decoder::new(bytes)?
+        .take(MAX_DECOMPRESSED_SIZE as u64 + 1)
+        .read_to_end(&mut decompressed)?;
+    if decompressed.len() > MAX_DECOMPRESSED_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("decompressed proof exceeds {} bytes", MAX_DECOMPRESSED_SIZE),
+        ));
+    }
+    Ok(decompressed)
+}
+
+/// Decompress `bytes` if they are compressed, passing other bytes through
+pub fn decompress_if_compressed(bytes: &[u8]) -> io::Result<Cow<'_, [u8]>> {
+    if is_compressed(bytes) {
+        decompress(bytes).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(bytes))
+    }
+}
+
+/// Decompressed size recorded in the frame header of compressed `bytes`, or the
+/// length of other bytes
+pub fn decompressed_size(bytes: &[u8]) -> Option<usize> {
+    if !is_compressed(bytes) {
+        return Some(bytes.len());
+    }
+    zstd::zstd_safe::get_frame_content_size(bytes)
+        .ok()
+        .flatten()
+        .and_then(|size| usize::try_from(size).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_roundtrip() {
+        let proof: Vec<u8> = (0..64 * 1024).map(|i| (i % 7) as u8).collect();
+        let compressed = compress(&proof).unwrap();
+        assert!(is_compressed(&compressed));
+        assert!(compressed.len() < proof.len());
+        assert_eq!(decompressed_size(&compressed), Some(proof.len()));
+        assert_eq!(decompress(&compressed).unwrap(), proof);
+
+        // Uncompressed bytes pass through, truncated frames are rejected
+        assert!(matches!(decompress_if_compressed(&proof).unwrap(), Cow::Borrowed(_)));
+        assert_eq!(decompressed_size(&proof), Some(proof.len()));
+        assert!(decompress(&compressed[..compressed.len() / 2]).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+//! Dual-proving cross-check mode
+//!
+//! For high-value messages the same statement can be proven on both SP1 and RISC0.
+//! The public values committed by each guest are compared before the bundle is
+//! released, so a bug in one circuit family cannot silently produce a bad proof.
+//!
+//! The same comparison runs as a QA mode over a set of [`GoldenVector`]s with
+//! [`DualProver::differential`], keeping the two circuit families in sync.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use sha2::{Digest, Sha256};
+use frostgate_zkip::ProofMetadata;
+
+use crate::descriptor;
+use crate::error::ZkError;
+use crate::options::ProveOptions;
+use crate::risc0::Risc0Backend;
+use crate::sp1::Sp1Backend;
+
+/// Policy deciding which requests are proven on both backends
+#[derive(Debug, Clone, Default)]
+pub struct CrossCheckPolicy {
+    /// Circuit type identifiers that are always cross-checked
+    pub circuit_types: HashSet<u8>,
+    /// Message value at or above which requests are cross-checked
+    pub value_threshold: Option<u128>,
+}
+
+impl CrossCheckPolicy {
+    /// Cross-check every request
+    pub fn always() -> Self {
+        Self {
+            circuit_types: HashSet::new(),
+            value_threshold: Some(0),
+        }
+    }
+
+    /// Whether a request for `circuit_type` carrying `value` must be cross-checked
+    pub fn requires_cross_check(&self, circuit_type: u8, value: Option<u128>) -> bool {
+        if self.circuit_types.contains(&circuit_type) {
+            return true;
+        }
+        match (self.value_threshold, value) {
+            (Some(threshold), Some(value)) => value >= threshold,
+            (Some(0), None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Proofs released for a single request
+#[derive(Debug, Clone)]
+pub struct DualProofBundle {
+    /// SP1 proof bytes
+    pub sp1_proof: Vec<u8>,
+    /// SP1 proof metadata
+    pub sp1_metadata: ProofMetadata,
+    /// RISC0 receipt bytes, present when the request was cross-checked
+    pub risc0_proof: Option<Vec<u8>>,
+    /// RISC0 proof metadata, present when the request was cross-checked
+    pub risc0_metadata: Option<ProofMetadata>,
+    /// Public values committed by the guest
+    pub public_values: Vec<u8>,
+}
+
+impl DualProofBundle {
+    /// Whether both backends proved this statement
+    pub fn cross_checked(&self) -> bool {
+        self.risc0_proof.is_some()
+    }
+}
+
+/// Statement with known-good input, proven on both backends in differential mode
+#[derive(Debug, Clone)]
+pub struct GoldenVector {
+    /// Vector name
+    pub name: String,
+    /// Program bytes
+    pub program: Vec<u8>,
+    /// Program input
+    pub input: Vec<u8>,
+}
+
+impl GoldenVector {
+    /// Message verification vector for `message`
+    pub fn message(name: &str, message: &[u8]) -> Self {
+        let mut program = Vec::with_capacity(33);
+        program.push(crate::profiles::MESSAGE_VERIFY);
+        program.extend_from_slice(&Sha256::digest(message));
+        Self {
+            name: name.to_string(),
+            program,
+            input: message.to_vec(),
+        }
+    }
+}
+
+/// Built-in golden vectors covering the message verification circuit
+pub fn golden_vectors() -> Vec<GoldenVector> {
+    vec![
+        GoldenVector::message("empty", b""),
+        GoldenVector::message("ascii", b"Hello, World!"),
+        GoldenVector::message("binary", &[0x00, 0xFF, 0x80, 0x7F]),
+        GoldenVector::message("large", &[0xAB; 4096]),
+    ]
+}
+
+/// Outcome of running a golden vector on both backends
+#[derive(Debug, Clone)]
+pub struct DifferentialResult {
+    /// Vector name
+    pub name: String,
+    /// SP1 public values, or the error SP1 failed with
+    pub sp1: Result<Vec<u8>, String>,
+    /// RISC0 journal, or the error RISC0 failed with
+    pub risc0: Result<Vec<u8>, String>,
+}
+
+impl DifferentialResult {
+    /// Whether both backends committed identical public values
+    pub fn matches(&self) -> bool {
+        matches!((&self.sp1, &self.risc0), (Ok(sp1), Ok(risc0)) if sp1 == risc0)
+    }
+}
+
+/// Report of a differential run
+#[derive(Debug, Clone, Default)]
+pub struct DifferentialReport {
+    /// Per-vector results in input order
+    pub results: Vec<DifferentialResult>,
+}
+
+impl DifferentialReport {
+    /// Vectors on which the backends diverged or failed
+    pub fn divergences(&self) -> Vec<&DifferentialResult> {
+        self.results.iter().filter(|result| !result.matches()).collect()
+    }
+
+    /// Whether every vector matched
+    pub fn is_consistent(&self) -> bool {
+        self.results.iter().all(DifferentialResult::matches)
+    }
+}
+
+/// Prover that cross-checks SP1 and RISC0 according to a [`CrossCheckPolicy`]
+#[derive(Debug, Clone)]
+pub struct DualProver {
+    sp1: Arc<Sp1Backend>,
+    risc0: Arc<Risc0Backend>,
+    policy: CrossCheckPolicy,
+}
+
+impl DualProver {
+    /// Create a new dual prover
+    pub fn new(sp1: Arc<Sp1Backend>, risc0: Arc<Risc0Backend>, policy: CrossCheckPolicy) -> Self {
+        Self { sp1, risc0, policy }
+    }
+
+    /// Get the cross-check policy
+    pub fn policy(&self) -> &CrossCheckPolicy {
+        &self.policy
+    }
+
+    /// Prove `input` against `program`, cross-checking if the policy requires it.
+    ///
+    /// `value` is the value carried by the message, if known. The bundle is only
+    /// returned once both backends agree on the committed public values.
+    pub async fn prove(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        value: Option<u128>,
+    ) -> Result<DualProofBundle, ZkError> {
+        let circuit_type = descriptor::circuit_type(program)
+            .ok_or_else(|| ZkError::InvalidInput("program cannot be empty".to_string()))?;
+        let options = ProveOptions::default();
+
+        if !self.policy.requires_cross_check(circuit_type, value) {
+            let (sp1_proof, sp1_metadata) = self.sp1.prove_with_options(program, input, &options).await?;
+            let public_values = self.sp1.public_values(&sp1_proof)?;
+            return Ok(DualProofBundle {
+                sp1_proof,
+                sp1_metadata: sp1_metadata.base,
+                risc0_proof: None,
+                risc0_metadata: None,
+                public_values,
+            });
+        }
+
+        // Prove on both backends concurrently
+        let (sp1_result, risc0_result) = futures::join!(
+            self.sp1.prove_with_options(program, input, &options),
+            self.risc0.prove_with_options(program, input, &options),
+        );
+        let (sp1_proof, sp1_metadata) = sp1_result?;
+        let (risc0_proof, risc0_metadata) = risc0_result?;
+
+        // Cross-check the committed public values
+        let sp1_values = self.sp1.public_values(&sp1_proof)?;
+        let risc0_values = self.risc0.public_values(&risc0_proof)?;
+        if sp1_values != risc0_values {
+            return Err(ZkError::CrossCheckMismatch {
+                sp1: hex::encode(&sp1_values),
+                risc0: hex::encode(&risc0_values),
+            });
+        }
+
+        Ok(DualProofBundle {
+            sp1_proof,
+            sp1_metadata: sp1_metadata.base,
+            risc0_proof: Some(risc0_proof),
+            risc0_metadata: Some(risc0_metadata.base),
+            public_values: sp1_values,
+        })
+    }
+
+    /// Run every vector through both backends regardless of the policy.
+    ///
+    /// Divergences and failures are collected in the report rather than
+    /// aborting the run.
+    pub async fn differential(&self, vectors: &[GoldenVector]) -> DifferentialReport {
+        let options = ProveOptions::default();
+        let mut report = DifferentialReport::default();
+        for vector in vectors {
+            let (sp1_result, risc0_result) = futures::join!(
+                self.sp1.prove_with_options(&vector.program, &vector.input, &options),
+                self.risc0.prove_with_options(&vector.program, &vector.input, &options),
+            );
+            let sp1 = sp1_result
+                .and_then(|(proof, _)| self.sp1.public_values(&proof))
+                .map_err(|e| e.to_string());
+            let risc0 = risc0_result
+                .and_then(|(proof, _)| self.risc0.public_values(&proof))
+                .map_err(|e| e.to_string());
+            report.results.push(DifferentialResult {
+                name: vector.name.clone(),
+                sp1,
+                risc0,
+            });
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_by_circuit_type() {
+        let mut policy = CrossCheckPolicy::default();
+        policy.circuit_types.insert(0x03);
+        assert!(policy.requires_cross_check(0x03, None));
+        assert!(!policy.requires_cross_check(0x01, None));
+    }
+
+    #[test]
+    fn test_policy_by_value_threshold() {
+        let policy = CrossCheckPolicy {
+            circuit_types: HashSet::new(),
+            value_threshold: Some(1_000),
+        };
+        assert!(policy.requires_cross_check(0x01, Some(1_000)));
+        assert!(!policy.requires_cross_check(0x01, Some(999)));
+        assert!(!policy.requires_cross_check(0x01, None));
+        assert!(CrossCheckPolicy::always().requires_cross_check(0x01, None));
+    }
+
+    #[test]
+    fn test_differential_report() {
+        let result = |name: &str, sp1: Result<Vec<u8>, String>, risc0: Result<Vec<u8>, String>| DifferentialResult {
+            name: name.to_string(),
+            sp1,
+            risc0,
+        };
+        let mut report = DifferentialReport {
+            results: vec![
+                result("same", Ok(vec![1, 2]), Ok(vec![1, 2])),
+                result("diverged", Ok(vec![1, 2]), Ok(vec![1, 3])),
+                result("failed", Ok(vec![1, 2]), Err("guest panicked".to_string())),
+                result("both failed", Err("oom".to_string()), Err("oom".to_string())),
+            ],
+        };
+        assert!(report.results[0].matches());
+        assert!(!report.is_consistent());
+        let divergences: Vec<_> = report.divergences().iter().map(|result| result.name.as_str()).collect();
+        assert_eq!(divergences, ["diverged", "failed", "both failed"]);
+
+        report.results.truncate(1);
+        assert!(report.is_consistent());
+        assert!(DifferentialReport::default().is_consistent());
+    }
+}
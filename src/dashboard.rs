@@ -0,0 +1,204 @@
+//! Backend dashboard data model
+//!
+//! [`BackendDashboard`] gathers everything an operator needs to see about a backend
+//! into one serializable snapshot. It is the data source for the ops UI and the
+//! HTTP admin endpoint and is rebuilt on demand rather than maintained incrementally.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use serde::{Serialize, Deserialize};
+use frostgate_zkip::{HealthStatus, ResourceUsage, ZkStats};
+
+use crate::stats::{FailureCategory, FailureRecord, LatencyStats, ProgramStats};
+
+/// Aggregate proving and verification statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSummary {
+    /// Total proofs generated
+    pub total_proofs: u64,
+    /// Total proofs verified
+    pub total_verifications: u64,
+    /// Total failed operations
+    pub total_failures: u64,
+    /// Average proving time
+    pub avg_proving_time: Duration,
+    /// Average verification time
+    pub avg_verification_time: Duration,
+}
+
+impl From<&ZkStats> for StatsSummary {
+    fn from(stats: &ZkStats) -> Self {
+        Self {
+            total_proofs: stats.total_proofs,
+            total_verifications: stats.total_verifications,
+            total_failures: stats.total_failures,
+            avg_proving_time: stats.avg_proving_time,
+            avg_verification_time: stats.avg_verification_time,
+        }
+    }
+}
+
+/// Latency percentiles over the retention window, `None` where nothing was recorded
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencySummary {
+    /// Retention window the percentiles cover
+    pub window: Duration,
+    /// Median proving time
+    pub proving_p50: Option<Duration>,
+    /// 95th percentile proving time
+    pub proving_p95: Option<Duration>,
+    /// 99th percentile proving time
+    pub proving_p99: Option<Duration>,
+    /// Median verification time
+    pub verification_p50: Option<Duration>,
+    /// 95th percentile verification time
+    pub verification_p95: Option<Duration>,
+    /// 99th percentile verification time
+    pub verification_p99: Option<Duration>,
+}
+
+impl From<&LatencyStats> for LatencySummary {
+    fn from(latency: &LatencyStats) -> Self {
+        let proving = latency.proving();
+        let verification = latency.verification();
+        Self {
+            window: latency.window(),
+            proving_p50: proving.p50(),
+            proving_p95: proving.p95(),
+            proving_p99: proving.p99(),
+            verification_p50: verification.p50(),
+            verification_p95: verification.p95(),
+            verification_p99: verification.p99(),
+        }
+    }
+}
+
+/// Cache occupancy and hit counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheSummary {
+    /// Number of cached circuits
+    pub circuit_entries: usize,
+    /// Number of cached proofs
+    pub proof_entries: usize,
+    /// Total circuit cache hits
+    pub circuit_hits: u64,
+    /// Total proof cache hits
+    pub proof_hits: u64,
+    /// Size of the cached proofs before compression
+    #[serde(default)]
+    pub proof_bytes: usize,
+    /// Size of the cached proofs as stored
+    #[serde(default)]
+    pub stored_proof_bytes: usize,
+}
+
+impl From<crate::sp1::CacheStats> for CacheSummary {
+    fn from(stats: crate::sp1::CacheStats) -> Self {
+        Self {
+            circuit_entries: stats.circuit_entries,
+            proof_entries: stats.proof_entries,
+            circuit_hits: stats.circuit_hits,
+            proof_hits: stats.proof_hits,
+            proof_bytes: stats.proof_bytes,
+            stored_proof_bytes: stats.stored_proof_bytes,
+        }
+    }
+}
+
+impl From<crate::risc0::CacheStats> for CacheSummary {
+    fn from(stats: crate::risc0::CacheStats) -> Self {
+        Self {
+            circuit_entries: stats.circuit_entries,
+            proof_entries: stats.proof_entries,
+            circuit_hits: stats.circuit_hits,
+            proof_hits: stats.proof_hits,
+            proof_bytes: stats.proof_bytes,
+            stored_proof_bytes: stats.stored_proof_bytes,
+        }
+    }
+}
+
+/// Queue and task occupancy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueSummary {
+    /// Tasks currently being proven
+    pub active_jobs: usize,
+    /// Tasks waiting to be proven
+    pub queue_depth: usize,
+    /// Maximum concurrent tasks
+    pub max_concurrent: usize,
+    /// CPU usage percentage
+    pub cpu_usage: f64,
+    /// Memory usage in bytes
+    pub memory_usage: usize,
+}
+
+impl From<&ResourceUsage> for QueueSummary {
+    fn from(resources: &ResourceUsage) -> Self {
+        Self {
+            active_jobs: resources.active_tasks,
+            queue_depth: resources.queue_depth,
+            max_concurrent: resources.max_concurrent,
+            cpu_usage: resources.cpu_usage,
+            memory_usage: resources.memory_usage,
+        }
+    }
+}
+
+/// Snapshot of a backend's operational state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendDashboard {
+    /// Backend identifier
+    pub backend: String,
+    /// Time the snapshot was taken
+    pub generated_at: SystemTime,
+    /// Whether the backend reports itself healthy
+    pub healthy: bool,
+    /// Health status description
+    pub health: String,
+    /// Aggregate statistics
+    pub stats: StatsSummary,
+    /// Latency percentiles
+    #[serde(default)]
+    pub latency: LatencySummary,
+    /// Per-program statistics keyed by program hash
+    pub programs: HashMap<String, ProgramStats>,
+    /// Cache statistics
+    pub cache: CacheSummary,
+    /// Queue and active jobs
+    pub queue: QueueSummary,
+    /// Failure counts per category
+    pub failure_counts: HashMap<FailureCategory, u64>,
+    /// Most recent failures, oldest first
+    pub recent_failures: Vec<FailureRecord>,
+}
+
+impl BackendDashboard {
+    /// Assemble a dashboard from its parts
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        backend: &str,
+        health: &HealthStatus,
+        stats: &ZkStats,
+        latency: &LatencyStats,
+        programs: HashMap<String, ProgramStats>,
+        cache: CacheSummary,
+        resources: &ResourceUsage,
+        failure_counts: HashMap<FailureCategory, u64>,
+        recent_failures: Vec<FailureRecord>,
+    ) -> Self {
+        Self {
+            backend: backend.to_string(),
+            generated_at: SystemTime::now(),
+            healthy: matches!(health, HealthStatus::Healthy),
+            health: format!("{:?}", health),
+            stats: StatsSummary::from(stats),
+            latency: LatencySummary::from(latency),
+            programs,
+            cache,
+            queue: QueueSummary::from(resources),
+            failure_counts,
+            recent_failures,
+        }
+    }
+}
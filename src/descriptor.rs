@@ -0,0 +1,389 @@
+//! Structured encoding of proving programs
+//!
+//! A program tells a backend which circuit to build: its circuit type, the public
+//! parameters the circuit commits to and, optionally, the guest ELF replacing the
+//! built-in one and the image ID (RISC0) or verifying key hash (SP1) that guest
+//! must have. Programs used to be the raw concatenation `[type | params | elf]`,
+//! which leaves the boundary between parameters and ELF to each circuit. A
+//! [`ProgramDescriptor`] carries the three fields explicitly and is encoded as
+//!
+//! - `FGPD`: magic
+//! - `u16` little-endian format version
+//! - the bincode-encoded descriptor
+//!
+//! [`ProgramDescriptor::decode`] still accepts programs in the old format and in
+//! format version 1, which had no image ID, so existing callers and stored programs
+//! keep working.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::MessageNonce;
+use crate::error::ZkError;
+use crate::hasher::HashFunction;
+use crate::profiles::{
+    BEACON_VERIFY, BLOCK_VERIFY, DOT_VERIFY, ECDSA_VERIFY, EIP712_VERIFY, ETH_HEADER_CHAIN, ETH_HEADER_VERIFY, ETH_LOG_VERIFY, ETH_VERIFY, MERKLE_INCLUSION, MESSAGE_AGGREGATE, MESSAGE_COMPOSE, MESSAGE_EVENT, MESSAGE_VERIFY, POSEIDON_COMMIT, SOL_VERIFY, STATE_PROOF, TM_VERIFY, TX_VERIFY, USER_PROGRAM,
+};
+
+/// Magic prefix of encoded descriptors
+pub const MAGIC: [u8; 4] = *b"FGPD";
+
+/// Format version written by [`ProgramDescriptor::encode`]
+pub const FORMAT_VERSION: u16 = 2;
+
+/// Length of the magic and version prefix
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+/// Magic number every ELF file starts with
+pub(crate) const ELF_MAGIC: &[u8] = b"\x7fELF";
+
+/// Circuit type, public parameters and guest program of a proving program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramDescriptor {
+    /// Circuit type identifier, see [`crate::profiles`]
+    pub circuit_type: u8,
+    /// Public parameters of the circuit, such as the expected hash
+    pub public_params: Vec<u8>,
+    /// Guest ELF replacing the circuit's built-in guest
+    pub elf: Option<Vec<u8>>,
+    /// Image ID (RISC0) or verifying key hash (SP1) the guest must have
+    pub image_id: Option<[u8; 32]>,
+}
+
+/// Descriptor in format version 1
+#[derive(Deserialize)]
+struct DescriptorV1 {
+    circuit_type: u8,
+    public_params: Vec<u8>,
+    elf: Option<Vec<u8>>,
+}
+
+impl From<DescriptorV1> for ProgramDescriptor {
+    fn from(v1: DescriptorV1) -> Self {
+        Self {
+            circuit_type: v1.circuit_type,
+            public_params: v1.public_params,
+            elf: v1.elf,
+            image_id: None,
+        }
+    }
+}
+
+impl ProgramDescriptor {
+    /// Create a descriptor using the circuit's built-in guest
+    pub fn new(circuit_type: u8, public_params: impl Into<Vec<u8>>) -> Self {
+        Self {
+            circuit_type,
+            public_params: public_params.into(),
+            elf: None,
+            image_id: None,
+        }
+    }
+
+    /// Descriptor of a message verification program for a message hashing to
+    /// `expected_hash` under `hash_function`
+    ///
+    /// SHA-256 programs keep the 32-byte parameters of programs predating the choice
+    /// of hash function; others append the function's identifier.
+    pub fn message_verify(expected_hash: [u8; 32], hash_function: HashFunction) -> Result<Self, ZkError> {
+        let id = hash_function.message_hash_id().ok_or_else(|| {
+            ZkError::InvalidProgram(format!("Message verification does not support {}", hash_function))
+        })?;
+        let mut params = expected_hash.to_vec();
+        if hash_function != HashFunction::Sha256 {
+            params.push(id);
+        }
+        Ok(Self::new(MESSAGE_VERIFY, params))
+    }
+
+    /// Descriptor of a message verification program for `message`, hashed on the host
+    /// with `hash_function` as the guests hash it
+    pub fn message_verify_for(message: &[u8], hash_function: HashFunction) -> Result<Self, ZkError> {
+        Self::message_verify(hash_function.hash(message), hash_function)
+    }
+
+    /// Hash function of a message verification program, SHA-256 unless its parameters
+    /// select another
+    pub fn message_hash_function(&self) -> HashFunction {
+        self.public_params.get(32)
+            .and_then(|id| HashFunction::from_message_hash_id(*id))
+            .unwrap_or(HashFunction::Sha256)
+    }
+
+    /// Commit the proof to the source chain and nonce of its message
+    ///
+    /// Message verification programs append the nonce to the hash function's
+    /// identifier, which SHA-256 programs then carry as well. Aggregation and
+    /// composition programs append the nonce of their first message, the others
+    /// following consecutively. Other circuits take no nonce.
+    pub fn with_nonce(mut self, nonce: MessageNonce) -> Result<Self, ZkError> {
+        match (self.circuit_type, self.public_params.len()) {
+            (MESSAGE_VERIFY, 32) => self.public_params.push(HashFunction::Sha256.message_hash_id().expect("SHA-256 hashes messages")),
+            (MESSAGE_VERIFY, 33) | (MESSAGE_AGGREGATE | MESSAGE_COMPOSE, 32) => {}
+            _ => return Err(ZkError::InvalidProgram(format!(
+                "Circuit 0x{:02x} cannot commit to a message nonce",
+                self.circuit_type
+            ))),
+        }
+        self.public_params.extend_from_slice(&nonce.to_bytes());
+        Ok(self)
+    }
+
+    /// Source chain and nonce the program commits to, the first message's for
+    /// aggregation and composition programs
+    pub fn message_nonce(&self) -> Option<MessageNonce> {
+        let offset = match (self.circuit_type, self.public_params.len()) {
+            (MESSAGE_VERIFY, 49) => 33,
+            (MESSAGE_AGGREGATE | MESSAGE_COMPOSE, 48) => 32,
+            _ => return None,
+        };
+        MessageNonce::from_bytes(&self.public_params[offset..])
+    }
+
+    /// Use `elf` as the guest program
+    pub fn with_elf(mut self, elf: impl Into<Vec<u8>>) -> Self {
+        self.elf = Some(elf.into());
+        self
+    }
+
+    /// Require the guest to have `image_id`, its RISC0 image ID or SP1 verifying key hash
+    pub fn with_image_id(mut self, image_id: [u8; 32]) -> Self {
+        self.image_id = Some(image_id);
+        self
+    }
+
+    /// Encode the descriptor in the current format
+    pub fn encode(&self) -> Vec<u8> {
+        let body = bincode::serialize(self).expect("descriptor serialization cannot fail");
+        let mut bytes = Vec::with_capacity(HEADER_LEN + body.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&body);
+        bytes
+    }
+
+    /// Decode and validate a program in either the current or the legacy format
+    pub fn decode(program: &[u8]) -> Result<Self, ZkError> {
+        let descriptor = if is_encoded(program) {
+            let body = &program[HEADER_LEN..];
+            let malformed = |e: bincode::Error| ZkError::InvalidProgram(format!("Malformed descriptor: {}", e));
+            match u16::from_le_bytes([program[4], program[5]]) {
+                1 => bincode::deserialize::<DescriptorV1>(body).map_err(malformed)?.into(),
+                FORMAT_VERSION => bincode::deserialize(body).map_err(malformed)?,
+                version => return Err(ZkError::UnsupportedProgramVersion {
+                    version,
+                    supported: FORMAT_VERSION,
+                }),
+            }
+        } else {
+            Self::from_legacy(program)?
+        };
+        descriptor.validate()?;
+        Ok(descriptor)
+    }
+
+    /// Parse a program in the legacy `[type | params | elf]` format
+    ///
+    /// Only transaction and block verification programs could embed an ELF, after
+    /// their fixed-size parameters; other circuits take everything after the type as
+    /// parameters.
+    pub fn from_legacy(program: &[u8]) -> Result<Self, ZkError> {
+        let (&circuit_type, rest) = program.split_first()
+            .ok_or_else(|| ZkError::InvalidProgram("Empty program".into()))?;
+        let params_len = match circuit_type {
+            TX_VERIFY => 32,
+            BLOCK_VERIFY => 40,
+            _ => rest.len(),
+        };
+        let (public_params, elf) = rest.split_at(params_len.min(rest.len()));
+        Ok(Self {
+            circuit_type,
+            public_params: public_params.to_vec(),
+            elf: (!elf.is_empty()).then(|| elf.to_vec()),
+            image_id: None,
+        })
+    }
+
+    /// Encode the descriptor in the legacy format, for peers not yet reading descriptors
+    ///
+    /// The legacy format cannot carry the image ID.
+    pub fn to_legacy(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(1 + self.public_params.len() + self.elf.as_ref().map_or(0, Vec::len));
+        bytes.push(self.circuit_type);
+        bytes.extend_from_slice(&self.public_params);
+        if let Some(elf) = &self.elf {
+            bytes.extend_from_slice(elf);
+        }
+        bytes
+    }
+
+    /// Check the parameters of the built-in circuit types and the embedded ELF
+    pub fn validate(&self) -> Result<(), ZkError> {
+        let expected_len = match self.circuit_type {
+            // Message verification optionally selects its hash function, then the
+            // message nonce
+            MESSAGE_VERIFY if matches!(self.public_params.len(), 33 | 49) => Some(self.public_params.len()),
+            // Aggregates optionally commit the nonce of their first message
+            MESSAGE_AGGREGATE | MESSAGE_COMPOSE if self.public_params.len() == 48 => Some(48),
+            MESSAGE_VERIFY | TX_VERIFY | MESSAGE_AGGREGATE | MESSAGE_COMPOSE | POSEIDON_COMMIT => Some(32),
+            ETH_VERIFY | ETH_LOG_VERIFY | ETH_HEADER_CHAIN | BEACON_VERIFY | DOT_VERIFY | SOL_VERIFY | TM_VERIFY | STATE_PROOF => Some(32),
+            EIP712_VERIFY | ECDSA_VERIFY | MESSAGE_EVENT => Some(32),
+            BLOCK_VERIFY | ETH_HEADER_VERIFY => Some(40),
+            // Root, hash function and depth of the tree
+            MERKLE_INCLUSION => Some(34),
+            _ => None,
+        };
+        if let Some(expected_len) = expected_len {
+            if self.public_params.len() != expected_len {
+                return Err(ZkError::InvalidProgram(format!(
+                    "Circuit 0x{:02x} takes {} bytes of parameters, got {}",
+                    self.circuit_type,
+                    expected_len,
+                    self.public_params.len()
+                )));
+            }
+        }
+        if let (MESSAGE_VERIFY, Some(id)) = (self.circuit_type, self.public_params.get(32)) {
+            if HashFunction::from_message_hash_id(*id).is_none() {
+                return Err(ZkError::InvalidProgram(format!("Unknown message hash function 0x{:02x}", id)));
+            }
+        }
+        if let (MERKLE_INCLUSION, [.., id, depth]) = (self.circuit_type, self.public_params.as_slice()) {
+            if HashFunction::from_message_hash_id(*id).is_none() {
+                return Err(ZkError::InvalidProgram(format!("Unknown Merkle hash function 0x{:02x}", id)));
+            }
+            if usize::from(*depth) > crate::inclusion::MAX_DEPTH {
+                return Err(ZkError::InvalidProgram(format!("Merkle trees are at most {} deep, got {}", crate::inclusion::MAX_DEPTH, depth)));
+            }
+        }
+        if self.circuit_type == USER_PROGRAM && std::str::from_utf8(&self.public_params).map_or(true, str::is_empty) {
+            return Err(ZkError::InvalidProgram("Guest programs are referenced by a non-empty UTF-8 name".into()));
+        }
+        if let Some(elf) = &self.elf {
+            if !elf.starts_with(ELF_MAGIC) {
+                return Err(ZkError::InvalidProgram("Embedded guest is not an ELF".into()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Circuit type of a program in either format, without decoding the rest of it
+pub fn circuit_type(program: &[u8]) -> Option<u8> {
+    if is_encoded(program) {
+        // The circuit type is the first field of the bincode body
+        program.get(HEADER_LEN).copied()
+    } else {
+        program.first().copied()
+    }
+}
+
+/// Whether a program is an encoded descriptor rather than in the legacy format
+pub fn is_encoded(program: &[u8]) -> bool {
+    program.len() >= HEADER_LEN && program.starts_with(&MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptor_round_trip() {
+        let descriptor = ProgramDescriptor::new(TX_VERIFY, [0xAA; 32])
+            .with_elf(b"\x7fELF guest".to_vec())
+            .with_image_id([0xCC; 32]);
+        let encoded = descriptor.encode();
+        assert_eq!(circuit_type(&encoded), Some(TX_VERIFY));
+        assert_eq!(ProgramDescriptor::decode(&encoded).unwrap(), descriptor);
+
+        let mut future = encoded.clone();
+        future[4] = 3;
+        assert!(matches!(
+            ProgramDescriptor::decode(&future),
+            Err(ZkError::UnsupportedProgramVersion { version: 3, .. })
+        ));
+
+        // Version 1 descriptors have no image ID
+        let mut v1 = MAGIC.to_vec();
+        v1.extend_from_slice(&1u16.to_le_bytes());
+        v1.extend_from_slice(&bincode::serialize(&(TX_VERIFY, vec![0xAAu8; 32], None::<Vec<u8>>)).unwrap());
+        assert_eq!(ProgramDescriptor::decode(&v1).unwrap(), ProgramDescriptor::new(TX_VERIFY, [0xAA; 32]));
+        assert!(ProgramDescriptor::decode(&encoded[..HEADER_LEN + 3]).is_err());
+    }
+
+    #[test]
+    fn test_legacy_programs() {
+        let mut block = vec![BLOCK_VERIFY];
+        block.extend_from_slice(&[0xBB; 40]);
+        let descriptor = ProgramDescriptor::decode(&block).unwrap();
+        assert_eq!(descriptor.public_params, vec![0xBB; 40]);
+        assert_eq!(descriptor.elf, None);
+        assert_eq!(descriptor.to_legacy(), block);
+
+        block.extend_from_slice(b"\x7fELF guest");
+        let descriptor = ProgramDescriptor::decode(&block).unwrap();
+        assert_eq!(descriptor.elf.as_deref(), Some(&b"\x7fELF guest"[..]));
+        assert_eq!(circuit_type(&block), Some(BLOCK_VERIFY));
+
+        // Malformed parameters and embedded guests are rejected
+        assert!(ProgramDescriptor::decode(&block[..20]).is_err());
+        assert!(ProgramDescriptor::decode(&[MESSAGE_VERIFY; 40]).is_err());
+        assert!(ProgramDescriptor::decode(&[]).is_err());
+    }
+
+    #[test]
+    fn test_message_hash_function() {
+        // SHA-256 programs are unchanged
+        let sha256 = ProgramDescriptor::message_verify([0xAA; 32], HashFunction::Sha256).unwrap();
+        assert_eq!(sha256, ProgramDescriptor::new(MESSAGE_VERIFY, [0xAA; 32]));
+        assert_eq!(sha256.message_hash_function(), HashFunction::Sha256);
+
+        let keccak = ProgramDescriptor::message_verify([0xAA; 32], HashFunction::Keccak256).unwrap();
+        assert_eq!(ProgramDescriptor::decode(&keccak.encode()).unwrap().message_hash_function(), HashFunction::Keccak256);
+        let mut legacy = vec![MESSAGE_VERIFY];
+        legacy.extend_from_slice(&keccak.public_params);
+        assert_eq!(ProgramDescriptor::decode(&legacy).unwrap(), keccak);
+
+        // Substrate data is hashed with Blake2b
+        let blake2b = ProgramDescriptor::message_verify_for(b"extrinsic", HashFunction::Blake2b).unwrap();
+        assert_eq!(blake2b.public_params[..32], HashFunction::Blake2b.hash(b"extrinsic"));
+        assert_eq!(blake2b.public_params[32], 2);
+        assert_eq!(ProgramDescriptor::decode(&blake2b.encode()).unwrap().message_hash_function(), HashFunction::Blake2b);
+
+        // Functions the guests do not implement are rejected
+        assert!(ProgramDescriptor::message_verify([0xAA; 32], HashFunction::Poseidon).is_err());
+        *legacy.last_mut().unwrap() = 0x7F;
+        assert!(ProgramDescriptor::decode(&legacy).is_err());
+    }
+
+    #[test]
+    fn test_message_nonce() {
+        let nonce = MessageNonce::new(crate::domain::ChainId(1), 7);
+        let sha256 = ProgramDescriptor::message_verify([0xAA; 32], HashFunction::Sha256).unwrap()
+            .with_nonce(nonce)
+            .unwrap();
+        assert_eq!(sha256.public_params.len(), 49);
+        let decoded = ProgramDescriptor::decode(&sha256.encode()).unwrap();
+        assert_eq!(decoded.message_nonce(), Some(nonce));
+        assert_eq!(decoded.message_hash_function(), HashFunction::Sha256);
+
+        let aggregate = ProgramDescriptor::new(MESSAGE_AGGREGATE, [0xBB; 32]).with_nonce(nonce).unwrap();
+        assert_eq!(ProgramDescriptor::decode(&aggregate.encode()).unwrap().message_nonce(), Some(nonce));
+        assert_eq!(ProgramDescriptor::new(MESSAGE_AGGREGATE, [0xBB; 32]).message_nonce(), None);
+
+        // Only message circuits commit nonces, once
+        assert!(ProgramDescriptor::new(TX_VERIFY, [0xAA; 32]).with_nonce(nonce).is_err());
+        assert!(aggregate.with_nonce(nonce).is_err());
+    }
+
+    #[test]
+    fn test_merkle_inclusion_params() {
+        let mut params = vec![0xAA; 32];
+        params.extend_from_slice(&[1, 64]);
+        assert!(ProgramDescriptor::new(MERKLE_INCLUSION, params.clone()).validate().is_ok());
+        params[33] = 65;
+        assert!(ProgramDescriptor::new(MERKLE_INCLUSION, params.clone()).validate().is_err());
+        params[33] = 3;
+        params[32] = 9;
+        assert!(ProgramDescriptor::new(MERKLE_INCLUSION, params).validate().is_err());
+    }
+}
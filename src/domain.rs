@@ -0,0 +1,286 @@
+//! Chain identifiers and message domain separation
+//!
+//! Every proof is bound to the domain it was generated for: the host passes the
+//! [`DomainSeparator`] to the guest, the guest appends it to its public output, and
+//! verifiers reject proofs whose committed domain differs from the one they expect.
+//! A proof generated for a message from chain A to chain B therefore never verifies
+//! as a message for any other pair of chains.
+//!
+//! Hashes a guest computes over its own structures, rather than over data whose hash
+//! a chain defines, are prefixed with the tag of a [`HashDomain`]: the circuit type,
+//! the chain and the protocol version. A digest computed by one circuit for one chain
+//! therefore never equals a digest of another circuit, chain or protocol version,
+//! even over the same bytes.
+//!
+//! Like [`crate::codec`], this module only depends on `core` so the guest programs in
+//! `circuits/` include the very same source file.
+
+use core::fmt;
+
+/// Numeric identifier of a chain
+///
+/// Identifier 0 is reserved for proofs that are not bound to any chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChainId(pub u64);
+
+impl ChainId {
+    /// Reserved identifier of proofs not bound to a chain
+    pub const UNBOUND: ChainId = ChainId(0);
+}
+
+impl fmt::Display for ChainId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "chain {}", self.0)
+    }
+}
+
+/// Origin and destination domain a proof is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct DomainSeparator {
+    /// Chain the message originates from
+    pub origin: ChainId,
+    /// Chain the message is delivered to
+    pub destination: ChainId,
+}
+
+impl DomainSeparator {
+    /// Encoded length in bytes
+    pub const LEN: usize = 16;
+
+    /// Domain of proofs that are not bound to any chain
+    pub const UNBOUND: DomainSeparator = DomainSeparator {
+        origin: ChainId::UNBOUND,
+        destination: ChainId::UNBOUND,
+    };
+
+    /// Create a domain for messages from `origin` to `destination`
+    pub fn new(origin: ChainId, destination: ChainId) -> Self {
+        Self { origin, destination }
+    }
+
+    /// Whether the domain binds the proof to a chain
+    pub fn is_bound(&self) -> bool {
+        *self != Self::UNBOUND
+    }
+
+    /// Encode as origin and destination, both little-endian
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..8].copy_from_slice(&self.origin.0.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.destination.0.to_le_bytes());
+        bytes
+    }
+
+    /// Decode from exactly [`Self::LEN`] bytes
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        let origin = u64::from_le_bytes(bytes[..8].try_into().ok()?);
+        let destination = u64::from_le_bytes(bytes[8..].try_into().ok()?);
+        Some(Self::new(ChainId(origin), ChainId(destination)))
+    }
+
+    /// Decode the domain a guest committed at the end of its public output
+    pub fn from_public_values(public_values: &[u8]) -> Option<Self> {
+        let start = public_values.len().checked_sub(Self::LEN)?;
+        Self::from_bytes(&public_values[start..])
+    }
+
+    /// Input a proof of `input` is cached under; bound proofs are cached per domain
+    #[cfg(feature = "std")]
+    pub fn cache_input(&self, input: &[u8]) -> std::vec::Vec<u8> {
+        if self.is_bound() {
+            [&self.to_bytes()[..], input].concat()
+        } else {
+            input.to_vec()
+        }
+    }
+}
+
+/// Source chain and nonce of a message, committed by the message guests
+///
+/// A verifier with a replay guard accepts every pair once, so a proof of the
+/// message cannot be delivered twice. Guests check that the source chain is the
+/// origin of a bound domain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageNonce {
+    /// Chain the message originates from
+    pub source_chain: ChainId,
+    /// Nonce of the message on its source chain
+    pub nonce: u64,
+}
+
+impl MessageNonce {
+    /// Encoded length in bytes
+    pub const LEN: usize = 16;
+
+    /// Create the nonce of message `nonce` from `source_chain`
+    pub fn new(source_chain: ChainId, nonce: u64) -> Self {
+        Self { source_chain, nonce }
+    }
+
+    /// Whether the message may come from the origin of `domain`
+    pub fn matches(&self, domain: &DomainSeparator) -> bool {
+        !domain.is_bound() || self.source_chain == domain.origin
+    }
+
+    /// Encode as source chain and nonce, both little-endian
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..8].copy_from_slice(&self.source_chain.0.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.nonce.to_le_bytes());
+        bytes
+    }
+
+    /// Decode from exactly [`Self::LEN`] bytes
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        let source_chain = u64::from_le_bytes(bytes[..8].try_into().ok()?);
+        let nonce = u64::from_le_bytes(bytes[8..].try_into().ok()?);
+        Some(Self::new(ChainId(source_chain), nonce))
+    }
+}
+
+/// Version of the Frostgate protocol hashed into every [`HashDomain`]
+///
+/// Bumped whenever the layout of a tagged structure changes, so digests of the old
+/// and new layout never collide.
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// Circuit, chain and protocol version a guest hash is computed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct HashDomain {
+    /// Circuit type identifier of the guest computing the hash
+    pub circuit_type: u8,
+    /// Chain the hashed structure belongs to
+    pub chain_id: ChainId,
+    /// Protocol version of the hashed structure
+    pub version: u16,
+}
+
+impl HashDomain {
+    /// Prefix of every tag
+    pub const PREFIX: &'static [u8] = b"frostgate";
+
+    /// Length of a tag
+    pub const LEN: usize = 9 + 2 + 1 + 8;
+
+    /// Create a domain of the current [`PROTOCOL_VERSION`]
+    pub fn new(circuit_type: u8, chain_id: ChainId) -> Self {
+        Self {
+            circuit_type,
+            chain_id,
+            version: PROTOCOL_VERSION,
+        }
+    }
+
+    /// Tag prefixed to hashed data: [`Self::PREFIX`], version, circuit type and
+    /// chain ID, integers little-endian
+    pub fn tag(&self) -> [u8; Self::LEN] {
+        let mut tag = [0u8; Self::LEN];
+        tag[..9].copy_from_slice(Self::PREFIX);
+        tag[9..11].copy_from_slice(&self.version.to_le_bytes());
+        tag[11] = self.circuit_type;
+        tag[12..].copy_from_slice(&self.chain_id.0.to_le_bytes());
+        tag
+    }
+
+    /// Decode a tag, `None` if `bytes` is not a tag
+    pub fn from_tag(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN || !bytes.starts_with(Self::PREFIX) {
+            return None;
+        }
+        Some(Self {
+            circuit_type: bytes[11],
+            chain_id: ChainId(u64::from_le_bytes(bytes[12..].try_into().ok()?)),
+            version: u16::from_le_bytes(bytes[9..11].try_into().ok()?),
+        })
+    }
+}
+
+impl DomainSeparator {
+    /// Hash domain of a circuit's structures originating from this domain's chain
+    pub fn hash_domain(&self, circuit_type: u8) -> HashDomain {
+        HashDomain::new(circuit_type, self.origin)
+    }
+}
+
+impl fmt::Display for HashDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "circuit 0x{:02x} on {} (v{})", self.circuit_type, self.chain_id, self.version)
+    }
+}
+
+impl Default for DomainSeparator {
+    fn default() -> Self {
+        Self::UNBOUND
+    }
+}
+
+impl fmt::Display for DomainSeparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.origin, self.destination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let domain = DomainSeparator::new(ChainId(1), ChainId(137));
+        assert_eq!(DomainSeparator::from_bytes(&domain.to_bytes()), Some(domain));
+        assert!(DomainSeparator::from_bytes(&[0u8; 15]).is_none());
+    }
+
+    #[test]
+    fn test_from_public_values() {
+        let domain = DomainSeparator::new(ChainId(1), ChainId(137));
+        let mut public_values = vec![0xAA; 32];
+        public_values.extend_from_slice(&domain.to_bytes());
+        assert_eq!(DomainSeparator::from_public_values(&public_values), Some(domain));
+
+        // Swapping origin and destination yields a different domain
+        let reversed = DomainSeparator::new(ChainId(137), ChainId(1));
+        assert_ne!(DomainSeparator::from_public_values(&public_values), Some(reversed));
+        assert!(DomainSeparator::from_public_values(&[0u8; 8]).is_none());
+    }
+
+    #[test]
+    fn test_message_nonce() {
+        let nonce = MessageNonce::new(ChainId(1), 42);
+        assert_eq!(MessageNonce::from_bytes(&nonce.to_bytes()), Some(nonce));
+        assert!(MessageNonce::from_bytes(&[0u8; 8]).is_none());
+
+        // Bound domains fix the source chain
+        assert!(nonce.matches(&DomainSeparator::UNBOUND));
+        assert!(nonce.matches(&DomainSeparator::new(ChainId(1), ChainId(137))));
+        assert!(!nonce.matches(&DomainSeparator::new(ChainId(137), ChainId(1))));
+    }
+
+    #[test]
+    fn test_hash_domain_tags() {
+        let domain = DomainSeparator::new(ChainId(1), ChainId(137)).hash_domain(0x12);
+        assert_eq!(domain, HashDomain::new(0x12, ChainId(1)));
+        assert_eq!(HashDomain::from_tag(&domain.tag()), Some(domain));
+        assert!(HashDomain::from_tag(&[0u8; HashDomain::LEN]).is_none());
+
+        // Every field changes the tag
+        let others = [
+            HashDomain::new(0x11, ChainId(1)),
+            HashDomain::new(0x12, ChainId(137)),
+            HashDomain { version: PROTOCOL_VERSION + 1, ..domain },
+        ];
+        for other in others {
+            assert_ne!(other.tag(), domain.tag());
+        }
+    }
+}
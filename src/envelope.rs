@@ -0,0 +1,253 @@
+//! Self-describing proof envelopes
+//!
+//! Backends produce proofs as opaque bytes whose format depends on the backend, the
+//! prover SDK and the proof mode, so a raw proof cannot be routed to the backend
+//! able to verify it. A [`ProofEnvelope`] carries the proof together with what is
+//! needed to route and check it, including the SDK version checked by
+//! [`crate::BackendInfo::compatibility_check`]. Its binary encoding is
+//!
+//! - `FGPE`: magic
+//! - `u16` little-endian envelope version
+//! - the bincode-encoded envelope, optionally compressed with zstd
+//!
+//! A compressed body is recognized by the zstd frame magic and decompressed
+//! transparently, so readers need not know how an envelope was written. Envelopes
+//! can also be encoded as CBOR for consumers outside of Rust. Both
+//! backends emit envelopes through `prove_envelope` and accept them in place of raw
+//! proofs when verifying.
+
+use serde::{Deserialize, Serialize};
+
+use bincode::Options;
+
+use crate::compression;
+use crate::error::ZkError;
+use crate::metadata::{ExtendedProofMetadata, ProofMode};
+
+/// Magic prefix of binary envelopes
+pub const MAGIC: [u8; 4] = *b"FGPE";
+
+/// Envelope version written by [`ProofEnvelope::new`]
+///
+/// Version 2 added the SDK version of the producing backend.
+pub const ENVELOPE_VERSION: u16 = 2;
+
+/// Length of the magic and version prefix
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+/// Proof with the backend, mode, program and public values it was produced for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofEnvelope {
+    /// Envelope version
+    pub version: u16,
+    /// Backend that produced the proof, as in [`crate::BackendInfo::backend`]
+    pub backend_id: String,
+    /// Prover SDK version of that backend, as in [`crate::BackendInfo::sdk_version`]
+    pub sdk_version: String,
+    /// Mode the proof was produced in
+    pub proof_mode: ProofMode,
+    /// Hex-encoded hash of the program, as reported in the proof metadata
+    pub program_hash: String,
+    /// Public values (SP1) or journal (RISC0) committed by the guest
+    #[serde(with = "serde_bytes")]
+    pub public_values: Vec<u8>,
+    /// Proof as produced by the backend
+    #[serde(with = "serde_bytes")]
+    pub proof_bytes: Vec<u8>,
+}
+
+impl ProofEnvelope {
+    /// Wrap a proof in an envelope of the current version
+    pub fn new(
+        backend_id: impl Into<String>,
+        sdk_version: impl Into<String>,
+        proof_mode: ProofMode,
+        program_hash: impl Into<String>,
+        public_values: Vec<u8>,
+        proof_bytes: Vec<u8>,
+    ) -> Self {
+        Self {
+            version: ENVELOPE_VERSION,
+            backend_id: backend_id.into(),
+            sdk_version: sdk_version.into(),
+            proof_mode,
+            program_hash: program_hash.into(),
+            public_values,
+            proof_bytes,
+        }
+    }
+
+    /// Wrap a proof with the metadata its backend reported
+    pub fn from_metadata(metadata: &ExtendedProofMetadata, proof_bytes: Vec<u8>) -> Self {
+        Self::new(
+            metadata.backend.clone(),
+            metadata.sdk_version.clone(),
+            metadata.proof_mode,
+            metadata.base.program_hash.clone(),
+            metadata.public_values.clone(),
+            proof_bytes,
+        )
+    }
+
+    /// Encode the envelope in the binary format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let body = options().serialize(self).expect("envelope serialization cannot fail");
+        self.with_header(&body)
+    }
+
+    /// Encode the envelope in the binary format with a zstd-compressed body
+    pub fn to_compressed_bytes(&self) -> Result<Vec<u8>, ZkError> {
+        let body = options().serialize(self).expect("envelope serialization cannot fail");
+        let body = compression::compress(&body)
+            .map_err(|e| ZkError::Backend(format!("Failed to compress envelope: {}", e)))?;
+        Ok(self.with_header(&body))
+    }
+
+    /// Decode and check an envelope in the binary format
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        if !is_envelope(bytes) {
+            return Err(malformed("missing magic"));
+        }
+        check_version(u16::from_le_bytes([bytes[4], bytes[5]]))?;
+        let body = compression::decompress_if_compressed(&bytes[HEADER_LEN..]).map_err(malformed)?;
+        let envelope: Self = options()
+            .with_limit(body.len() as u64)
+            .deserialize(&body)
+            .map_err(malformed)?;
+        if envelope.version != ENVELOPE_VERSION {
+            return Err(malformed("version does not match its header"));
+        }
+        envelope.check()?;
+        Ok(envelope)
+    }
+
+    /// Encode the envelope as CBOR
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(self, &mut bytes).expect("envelope serialization cannot fail");
+        bytes
+    }
+
+    /// Decode and check a CBOR-encoded envelope
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, ZkError> {
+        let envelope: Self = ciborium::de::from_reader(bytes).map_err(malformed)?;
+        check_version(envelope.version)?;
+        envelope.check()?;
+        Ok(envelope)
+    }
+
+    /// Proof bytes, checked to be produced by `backend_id` for the program hashing to
+    /// `program_hash`
+    pub fn open(&self, backend_id: &str, program_hash: &str) -> Result<&[u8], ZkError> {
+        if self.backend_id != backend_id {
+            return Err(ZkError::ProofVerification(format!(
+                "Proof was produced by {}, expected {}",
+                self.backend_id, backend_id
+            )));
+        }
+        if !self.program_hash.eq_ignore_ascii_case(program_hash) {
+            return Err(ZkError::ProofVerification(format!(
+                "Proof is of program {}, expected {}",
+                self.program_hash, program_hash
+            )));
+        }
+        Ok(&self.proof_bytes)
+    }
+
+    fn with_header(&self, body: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + body.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    pub(crate) fn check(&self) -> Result<(), ZkError> {
+        if self.backend_id.is_empty() {
+            return Err(malformed("missing backend"));
+        }
+        if self.sdk_version.is_empty() {
+            return Err(malformed("missing SDK version"));
+        }
+        if self.proof_bytes.is_empty() {
+            return Err(malformed("empty proof"));
+        }
+        match hex::decode(&self.program_hash) {
+            Ok(hash) if hash.len() == 32 => Ok(()),
+            _ => Err(malformed("program hash is not a hex-encoded 32-byte hash")),
+        }
+    }
+}
+
+/// Whether `bytes` start like a binary envelope rather than a raw proof
+pub fn is_envelope(bytes: &[u8]) -> bool {
+    bytes.len() >= HEADER_LEN && bytes[..MAGIC.len()] == MAGIC
+}
+
+/// Bincode options of envelope bodies, rejecting trailing bytes
+fn options() -> impl Options {
+    bincode::DefaultOptions::new().with_fixint_encoding()
+}
+
+pub(crate) fn check_version(version: u16) -> Result<(), ZkError> {
+    if version != ENVELOPE_VERSION {
+        return Err(ZkError::UnsupportedEnvelopeVersion { version, supported: ENVELOPE_VERSION });
+    }
+    Ok(())
+}
+
+fn malformed(reason: impl std::fmt::Display) -> ZkError {
+    ZkError::ProofVerification(format!("Malformed proof envelope: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope() -> ProofEnvelope {
+        ProofEnvelope::new("sp1", "5.0.0", ProofMode::Compressed, hex::encode([7u8; 32]), vec![1, 2, 3], vec![4, 5, 6, 7])
+    }
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let envelope = envelope();
+        let bytes = envelope.to_bytes();
+        assert!(is_envelope(&bytes));
+        assert!(!is_envelope(&envelope.proof_bytes));
+        assert_eq!(ProofEnvelope::from_bytes(&bytes).unwrap(), envelope);
+        assert_eq!(ProofEnvelope::from_cbor(&envelope.to_cbor()).unwrap(), envelope);
+        let compressed = envelope.to_compressed_bytes().unwrap();
+        assert!(is_envelope(&compressed));
+        assert_eq!(ProofEnvelope::from_bytes(&compressed).unwrap(), envelope);
+
+        assert_eq!(envelope.open("sp1", &hex::encode([7u8; 32])).unwrap(), &[4, 5, 6, 7]);
+        assert!(envelope.open("risc0", &hex::encode([7u8; 32])).is_err());
+        assert!(envelope.open("sp1", &hex::encode([8u8; 32])).is_err());
+    }
+
+    #[test]
+    fn test_envelope_checks() {
+        let bytes = envelope().to_bytes();
+        assert!(ProofEnvelope::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(ProofEnvelope::from_bytes(&trailing).is_err());
+
+        let mut future = bytes.clone();
+        future[4] = 3;
+        assert!(matches!(
+            ProofEnvelope::from_bytes(&future),
+            Err(ZkError::UnsupportedEnvelopeVersion { version: 3, supported: ENVELOPE_VERSION })
+        ));
+
+        let mut empty = envelope();
+        empty.proof_bytes.clear();
+        assert!(ProofEnvelope::from_bytes(&empty.to_bytes()).is_err());
+        let mut unversioned = envelope();
+        unversioned.sdk_version.clear();
+        assert!(ProofEnvelope::from_bytes(&unversioned.to_bytes()).is_err());
+        let mut unhashed = envelope();
+        unhashed.program_hash = "01".into();
+        assert!(ProofEnvelope::from_cbor(&unhashed.to_cbor()).is_err());
+    }
+}
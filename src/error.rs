@@ -25,6 +25,160 @@ pub enum ZkError {
     #[error("Backend error: {0}")]
     Backend(String),
 
+    /// No program is registered under the name
+    #[error("Unknown program: {0}")]
+    UnknownProgram(String),
+
+    /// No backend is registered under the name
+    #[error("Unknown backend: {0}")]
+    UnknownBackend(String),
+
+    /// Guest program failed an assertion or panicked during execution
+    #[error("Guest assertion failed in circuit 0x{circuit_type:02x}: {message}")]
+    GuestAssertion {
+        /// Panic or assertion message reported by the guest
+        message: String,
+        /// Circuit type identifier of the failing program
+        circuit_type: u8,
+    },
+
+    /// Proving did not finish within the allowed time
+    #[error("Proving timed out after {0:?}")]
+    Timeout(std::time::Duration),
+
+    /// Proving was cancelled by the caller
+    #[error("Proving was cancelled")]
+    Cancelled,
+
+    /// Job cannot finish before its deadline according to the prediction model
+    #[error("Deadline {deadline:?} unreachable: predicted proving time {predicted:?}")]
+    DeadlineUnreachable {
+        /// Requested deadline
+        deadline: std::time::SystemTime,
+        /// Predicted proving time
+        predicted: std::time::Duration,
+    },
+
+    /// Proving queue cannot take more jobs
+    #[error("Proving queue is full ({capacity} jobs)")]
+    QueueFull {
+        /// Maximum number of queued jobs
+        capacity: usize,
+    },
+
+    /// Proof exceeds the caller's size budget in every available mode
+    #[error("Proof size {size} exceeds budget of {limit} bytes (mode {mode:?})")]
+    ProofTooLarge {
+        /// Size of the most succinct proof produced
+        size: usize,
+        /// Requested size budget
+        limit: usize,
+        /// Mode of the most succinct proof produced
+        mode: crate::metadata::ProofMode,
+    },
+
+    /// SP1 and RISC0 committed different public values for the same statement
+    #[error("Cross-check mismatch: sp1 committed {sp1}, risc0 committed {risc0}")]
+    CrossCheckMismatch {
+        /// Hex-encoded SP1 public values
+        sp1: String,
+        /// Hex-encoded RISC0 journal
+        risc0: String,
+    },
+
+    /// Proof was produced by an incompatible prover SDK
+    #[error("Incompatible proof: produced by {backend} {proof_version}, backend is {backend_version}")]
+    IncompatibleSdk {
+        /// Backend that produced the proof
+        backend: String,
+        /// SDK version that produced the proof
+        proof_version: String,
+        /// Backend and SDK version of the verifier
+        backend_version: String,
+    },
+
+    /// Proof was produced by a different version of the circuit
+    #[error("Incompatible circuit 0x{circuit_type:02x}: proof is version {proof_version}, backend has version {backend_version}")]
+    IncompatibleCircuit {
+        /// Circuit type identifier
+        circuit_type: u8,
+        /// Circuit version that produced the proof
+        proof_version: u32,
+        /// Circuit version known to the backend
+        backend_version: u32,
+    },
+
+    /// No verifier exists for the proof mode on the target chain
+    #[error("No {mode:?} verifier available on {chain}")]
+    UnsupportedVerifier {
+        /// Target chain
+        chain: String,
+        /// Proof mode without a verifier
+        mode: crate::metadata::ProofMode,
+    },
+
+    /// Proof is bound to a different domain than the verifier expects
+    #[error("Domain mismatch: expected {expected}, proof is bound to {found}")]
+    DomainMismatch {
+        /// Domain the verifier expects
+        expected: crate::domain::DomainSeparator,
+        /// Domain committed by the guest
+        found: crate::domain::DomainSeparator,
+    },
+
+    /// Proof is of a message whose nonce was already accepted
+    #[error("Replayed message: nonce {nonce} from {source_chain} was already accepted")]
+    Replayed {
+        /// Chain the message originates from
+        source_chain: crate::domain::ChainId,
+        /// Nonce of the message on its source chain
+        nonce: u64,
+    },
+
+    /// Program is malformed or its parameters do not fit its circuit
+    #[error("Invalid program: {0}")]
+    InvalidProgram(String),
+
+    /// Program manifest is malformed or lists programs that cannot be loaded
+    #[error("Invalid program manifest: {0}")]
+    InvalidManifest(String),
+
+    /// Program descriptor was encoded in a format version this build cannot read
+    #[error("Unsupported program format version {version} (supported: {supported})")]
+    UnsupportedProgramVersion {
+        /// Format version of the program
+        version: u16,
+        /// Format version this build reads
+        supported: u16,
+    },
+
+    /// Proof envelope was encoded in a version this build cannot read
+    #[error("Unsupported proof envelope version {version} (supported: {supported})")]
+    UnsupportedEnvelopeVersion {
+        /// Version of the envelope
+        version: u16,
+        /// Envelope version this build reads
+        supported: u16,
+    },
+
+    /// Guest ELF does not have the image ID or verifying key hash its program expects
+    #[error("Image mismatch: program expects {expected}, guest has {found}")]
+    ImageMismatch {
+        /// Hex-encoded image ID or verifying key hash the program expects
+        expected: String,
+        /// Hex-encoded image ID or verifying key hash of the guest
+        found: String,
+    },
+
+    /// Guest ELF loaded from the filesystem does not match its pinned identity
+    #[error("Guest {guest} at {path} does not match its pin")]
+    GuestPinMismatch {
+        /// Name of the guest
+        guest: String,
+        /// Path the guest was loaded from
+        path: String,
+    },
+
     /// IO error
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -34,6 +188,39 @@ pub enum ZkError {
     Serialization(#[from] serde_json::Error),
 }
 
+/// Prefixes prover SDKs use when reporting a guest panic
+const GUEST_PANIC_MARKERS: &[&str] = &["Guest panicked: ", "panicked at "];
+
+impl ZkError {
+    /// Classify a prover error, surfacing the guest's panic message when present
+    pub fn from_prover_error(circuit_type: u8, error: impl fmt::Display) -> Self {
+        let error = error.to_string();
+
+        for marker in GUEST_PANIC_MARKERS {
+            if let Some(index) = error.find(marker) {
+                let message = &error[index + marker.len()..];
+                // `panicked at file:line:col:\nmessage` carries the location first
+                let message = message.split_once(":\n")
+                    .map(|(_, message)| message)
+                    .unwrap_or(message);
+                return ZkError::GuestAssertion {
+                    message: message.trim().to_string(),
+                    circuit_type,
+                };
+            }
+        }
+
+        if error.contains("HaltWithNonZeroExitCode") {
+            return ZkError::GuestAssertion {
+                message: error,
+                circuit_type,
+            };
+        }
+
+        ZkError::ProofGeneration(error)
+    }
+}
+
 impl From<ZkError> for String {
     fn from(err: ZkError) -> String {
         err.to_string()
@@ -0,0 +1,200 @@
+//! Backend event stream
+//!
+//! Backends publish structured [`BackendEvent`]s on an [`EventBus`] so embedding
+//! applications can react to proving activity, cache evictions and health changes
+//! without polling statistics or registering individual callbacks.
+//!
+//! Every proof a backend generates publishes [`BackendEvent::ProofStarted`] followed
+//! by either [`BackendEvent::ProofCompleted`] or [`BackendEvent::ProofFailed`], the
+//! latter carrying the same [`FailureCategory`] the failure statistics count it under.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use futures::stream::{self, Stream};
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+use tokio::sync::broadcast;
+
+use crate::error::ZkError;
+use crate::metadata::ProofMode;
+use crate::stats::FailureCategory;
+
+/// Default number of events buffered for slow subscribers
+pub const DEFAULT_EVENT_CAPACITY: usize = 1024;
+
+/// Number of past events retained for diagnostics
+pub const DEFAULT_EVENT_HISTORY: usize = 256;
+
+/// Cache that produced an eviction event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CacheKind {
+    /// Compiled circuit cache
+    Circuit,
+    /// Generated proof cache
+    Proof,
+    /// Proving and verifying key cache
+    Keys,
+}
+
+/// Structured backend event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BackendEvent {
+    /// Proof generation started
+    ///
+    /// Proofs served from the cache or shared with an identical request in flight
+    /// publish no events.
+    ProofStarted {
+        /// Hex-encoded program hash
+        program_hash: String,
+        /// Time proving started
+        timestamp: SystemTime,
+    },
+    /// A proof was generated
+    ProofCompleted {
+        /// Hex-encoded program hash
+        program_hash: String,
+        /// Wall-clock proving time
+        duration: Duration,
+        /// Size of the proof in bytes
+        proof_size: usize,
+        /// Mode the proof was generated in
+        proof_mode: ProofMode,
+    },
+    /// Proof generation failed
+    ProofFailed {
+        /// Hex-encoded program hash
+        program_hash: String,
+        /// Wall-clock time until the failure
+        duration: Duration,
+        /// Category of the failure
+        category: FailureCategory,
+        /// Error message
+        error: String,
+    },
+    /// An entry was evicted from one of the backend caches
+    CacheEvicted {
+        /// Cache the entry was evicted from
+        cache: CacheKind,
+        /// Hex-encoded cache key
+        key: String,
+    },
+    /// The reported health status changed
+    HealthChanged {
+        /// Previous health status
+        previous: String,
+        /// Current health status
+        current: String,
+    },
+    /// Condition that requires operator attention
+    Alert {
+        /// Human-readable alert message
+        message: String,
+    },
+}
+
+impl BackendEvent {
+    /// Event reporting that proving `program_hash` failed with `error`
+    pub fn proof_failed(program_hash: String, duration: Duration, error: &ZkError) -> Self {
+        BackendEvent::ProofFailed {
+            program_hash,
+            duration,
+            category: FailureCategory::of(error),
+            error: error.to_string(),
+        }
+    }
+}
+
+/// Broadcast channel of backend events
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<BackendEvent>,
+    history: Arc<Mutex<VecDeque<BackendEvent>>>,
+}
+
+impl EventBus {
+    /// Create a new event bus buffering up to `capacity` events per subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            history: Arc::new(Mutex::new(VecDeque::with_capacity(DEFAULT_EVENT_HISTORY))),
+        }
+    }
+
+    /// Publish an event to all current subscribers
+    pub fn publish(&self, event: BackendEvent) {
+        {
+            let mut history = self.history.lock();
+            if history.len() == DEFAULT_EVENT_HISTORY {
+                history.pop_front();
+            }
+            history.push_back(event.clone());
+        }
+        // No subscribers is not an error
+        let _ = self.sender.send(event);
+    }
+
+    /// Most recently published events, oldest first
+    pub fn recent(&self) -> Vec<BackendEvent> {
+        self.history.lock().iter().cloned().collect()
+    }
+
+    /// Subscribe to events published from now on.
+    ///
+    /// Subscribers that fall behind by more than the bus capacity skip the
+    /// missed events rather than ending the stream.
+    pub fn subscribe(&self) -> impl Stream<Item = BackendEvent> + Send + 'static {
+        stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_subscriber_receives_events() {
+        let bus = EventBus::default();
+        let mut events = Box::pin(bus.subscribe());
+
+        bus.publish(BackendEvent::Alert { message: "test".into() });
+
+        match events.next().await {
+            Some(BackendEvent::Alert { message }) => assert_eq!(message, "test"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failure_is_categorized() {
+        let bus = EventBus::default();
+        let mut events = Box::pin(bus.subscribe());
+
+        bus.publish(BackendEvent::proof_failed("00".into(), Duration::from_secs(1), &ZkError::Cancelled));
+
+        match events.next().await {
+            Some(BackendEvent::ProofFailed { category, error, .. }) => {
+                assert_eq!(category, FailureCategory::Cancelled);
+                assert_eq!(error, ZkError::Cancelled.to_string());
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert_eq!(bus.recent().len(), 1);
+    }
+}
@@ -0,0 +1,62 @@
+//! EVM calldata encoding
+//!
+//! Wrapped SP1 proofs are verified on EVM chains by the `SP1Verifier` gateway
+//! contracts. [`encode_for_evm`] turns a proof produced by this crate into calldata
+//! for their `verifyProof` entry point, so consumers don't have to re-implement
+//! the ABI encoding.
+
+use alloy_sol_types::{sol, SolCall};
+
+use crate::error::ZkError;
+use crate::metadata::ExtendedProofMetadata;
+use crate::sp1::verifier;
+
+sol! {
+    /// Entry point of the `SP1Verifier` gateway contracts
+    function verifyProof(bytes32 programVKey, bytes publicValues, bytes proofBytes) external view;
+}
+
+/// Encode a serialized Groth16 or PLONK proof as `verifyProof` calldata
+///
+/// The verifying key hash is taken from the proof's metadata and the public values
+/// from the proof itself.
+pub fn encode_for_evm(proof: &[u8], metadata: &ExtendedProofMetadata) -> Result<Vec<u8>, ZkError> {
+    if !metadata.proof_mode.is_onchain_verifiable() {
+        return Err(ZkError::UnsupportedVerifier {
+            chain: "evm".to_string(),
+            mode: metadata.proof_mode,
+        });
+    }
+    let vkey_hash = metadata.vkey_hash
+        .ok_or_else(|| ZkError::InvalidInput("Proof metadata has no verifying key hash".into()))?;
+    let proof = verifier::decode_proof(proof)?;
+    Ok(encode_calldata(vkey_hash, proof.public_values.as_slice(), &proof.bytes()))
+}
+
+/// ABI-encode a `verifyProof` call
+pub fn encode_calldata(vkey_hash: [u8; 32], public_values: &[u8], proof: &[u8]) -> Vec<u8> {
+    verifyProofCall {
+        programVKey: vkey_hash.into(),
+        publicValues: public_values.to_vec().into(),
+        proofBytes: proof.to_vec().into(),
+    }
+    .abi_encode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calldata_layout() {
+        let calldata = encode_calldata([0x11; 32], &[0xAA; 48], &[0xBB; 260]);
+
+        // Selector of verifyProof(bytes32,bytes,bytes)
+        assert_eq!(calldata[..4], verifyProofCall::SELECTOR);
+        assert_eq!(calldata[4..36], [0x11; 32]);
+
+        let call = verifyProofCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(call.publicValues.as_ref(), &[0xAA; 48][..]);
+        assert_eq!(call.proofBytes.len(), 260);
+    }
+}
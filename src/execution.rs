@@ -0,0 +1,69 @@
+//! Guest execution without proving
+//!
+//! Executing a guest is orders of magnitude cheaper than proving it, so backends
+//! offer an `estimate` preflight: the guest runs in the executor and the report
+//! carries its cycle count, memory and a projection of the proving time. The
+//! projection is the observed average for programs the backend already proved and
+//! otherwise the rough model in this module.
+
+use std::time::Duration;
+
+use serde::{Serialize, Deserialize};
+
+use crate::metadata::ProofMode;
+
+/// Cycles per second an SP1 core proof proceeds at on a single machine
+pub const SP1_CYCLES_PER_SECOND: u64 = 1_000_000;
+
+/// Time to prove a single RISC0 segment of the default size
+pub const RISC0_SEGMENT_TIME: Duration = Duration::from_secs(10);
+
+/// Outcome of executing a guest program without proving it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    /// Public values (SP1) or journal (RISC0) committed by the guest
+    pub public_values: Vec<u8>,
+    /// Number of cycles executed, if reported by the executor
+    pub cycles: Option<u64>,
+    /// Number of segments a proof would consist of, if known
+    pub segments: Option<usize>,
+    /// Bytes of guest memory touched, if reported by the executor
+    #[serde(default)]
+    pub memory: Option<u64>,
+    /// Projected time to prove the execution, set by `estimate`
+    #[serde(default)]
+    pub projected_proving_time: Option<Duration>,
+}
+
+/// Additional time to compress and wrap a proof into `mode`
+pub fn wrap_time(mode: ProofMode) -> Duration {
+    match mode {
+        ProofMode::Core => Duration::ZERO,
+        ProofMode::Compressed => Duration::from_secs(30),
+        ProofMode::Plonk => Duration::from_secs(150),
+        ProofMode::Groth16 => Duration::from_secs(90),
+    }
+}
+
+/// Projected time to prove `cycles` SP1 cycles in `mode`
+pub fn project_sp1(cycles: u64, mode: ProofMode) -> Duration {
+    Duration::from_secs_f64(cycles as f64 / SP1_CYCLES_PER_SECOND as f64) + wrap_time(mode)
+}
+
+/// Projected time to prove `segments` RISC0 segments in `mode`
+pub fn project_risc0(segments: usize, mode: ProofMode) -> Duration {
+    RISC0_SEGMENT_TIME * segments as u32 + wrap_time(mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_projection() {
+        assert_eq!(project_sp1(2 * SP1_CYCLES_PER_SECOND, ProofMode::Core), Duration::from_secs(2));
+        assert!(project_sp1(1_000, ProofMode::Groth16) > project_sp1(1_000, ProofMode::Compressed));
+        assert_eq!(project_risc0(3, ProofMode::Core), RISC0_SEGMENT_TIME * 3);
+        assert_eq!(project_risc0(0, ProofMode::Core), Duration::ZERO);
+    }
+}
@@ -0,0 +1,191 @@
+//! On-chain verification cost estimation
+//!
+//! The relayer has to pay for verifying every proof on the target chain, and the
+//! cost depends heavily on the proof mode. [`GasEstimator`] predicts that cost from
+//! known verifier cost models so the cheapest acceptable mode can be chosen before
+//! a proof is generated or submitted.
+
+use std::collections::HashMap;
+use std::fmt;
+use serde::{Serialize, Deserialize};
+
+use crate::error::ZkError;
+use crate::metadata::{ExtendedProofMetadata, ProofMode};
+
+/// Chain family a proof is submitted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TargetChain {
+    /// EVM chains, cost in gas
+    Evm,
+    /// Substrate chains, cost in reference-time weight
+    Substrate,
+    /// Solana, cost in compute units
+    Solana,
+}
+
+impl TargetChain {
+    /// Unit the chain charges verification in
+    pub fn unit(self) -> &'static str {
+        match self {
+            TargetChain::Evm => "gas",
+            TargetChain::Substrate => "weight",
+            TargetChain::Solana => "compute units",
+        }
+    }
+}
+
+impl fmt::Display for TargetChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetChain::Evm => write!(f, "evm"),
+            TargetChain::Substrate => write!(f, "substrate"),
+            TargetChain::Solana => write!(f, "solana"),
+        }
+    }
+}
+
+/// Linear cost model of a verifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostModel {
+    /// Fixed cost of a verification call
+    pub base: u64,
+    /// Cost per byte of proof submitted
+    pub per_byte: u64,
+    /// Typical proof size in this mode, used before a proof exists
+    pub typical_proof_size: usize,
+}
+
+impl CostModel {
+    /// Cost of verifying a proof of the given size
+    pub fn cost(&self, proof_size: usize) -> u64 {
+        self.base.saturating_add(self.per_byte.saturating_mul(proof_size as u64))
+    }
+}
+
+/// Predicted verification cost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasEstimate {
+    /// Target chain
+    pub chain: TargetChain,
+    /// Proof mode the estimate is for
+    pub proof_mode: ProofMode,
+    /// Predicted cost in the chain's unit
+    pub cost: u64,
+}
+
+/// Verification cost estimator backed by per-chain, per-mode cost models
+#[derive(Debug, Clone)]
+pub struct GasEstimator {
+    models: HashMap<(TargetChain, ProofMode), CostModel>,
+}
+
+impl GasEstimator {
+    /// Create an estimator without any cost models
+    pub fn new() -> Self {
+        Self {
+            models: HashMap::new(),
+        }
+    }
+
+    /// Create an estimator with the cost models of the known verifiers
+    ///
+    /// STARK proofs have no EVM or Solana verifier, so only the Substrate
+    /// verifier pallet is modelled for them. Groth16 and PLONK proofs are verified
+    /// by the BN254 pairing precompiles on EVM, and Groth16 proofs by the alt_bn128
+    /// syscalls on Solana.
+    pub fn with_known_models() -> Self {
+        Self::new()
+            .with_model(TargetChain::Substrate, ProofMode::Core, CostModel {
+                base: 5_000_000_000,
+                per_byte: 2_000,
+                typical_proof_size: 2 * 1024 * 1024,
+            })
+            .with_model(TargetChain::Substrate, ProofMode::Compressed, CostModel {
+                base: 2_000_000_000,
+                per_byte: 1_000,
+                typical_proof_size: 300 * 1024,
+            })
+            .with_model(TargetChain::Evm, ProofMode::Groth16, CostModel {
+                base: 270_000,
+                per_byte: 16,
+                typical_proof_size: 260,
+            })
+            .with_model(TargetChain::Evm, ProofMode::Plonk, CostModel {
+                base: 300_000,
+                per_byte: 16,
+                typical_proof_size: 868,
+            })
+            .with_model(TargetChain::Solana, ProofMode::Groth16, CostModel {
+                base: 200_000,
+                per_byte: 0,
+                typical_proof_size: 260,
+            })
+    }
+
+    /// Register or replace the cost model for a chain and proof mode
+    pub fn with_model(mut self, chain: TargetChain, mode: ProofMode, model: CostModel) -> Self {
+        self.models.insert((chain, mode), model);
+        self
+    }
+
+    /// Get the cost model for a chain and proof mode
+    pub fn model(&self, chain: TargetChain, mode: ProofMode) -> Option<&CostModel> {
+        self.models.get(&(chain, mode))
+    }
+
+    /// Estimate the cost of verifying a proof of the given size
+    pub fn estimate_for(&self, chain: TargetChain, mode: ProofMode, proof_size: usize) -> Result<GasEstimate, ZkError> {
+        let model = self.model(chain, mode)
+            .ok_or_else(|| ZkError::UnsupportedVerifier { chain: chain.to_string(), mode })?;
+        Ok(GasEstimate {
+            chain,
+            proof_mode: mode,
+            cost: model.cost(proof_size),
+        })
+    }
+
+    /// Estimate the cost of verifying an existing proof
+    pub fn estimate(&self, metadata: &ExtendedProofMetadata, chain: TargetChain) -> Result<GasEstimate, ZkError> {
+        self.estimate_for(chain, metadata.proof_mode, metadata.base.proof_size)
+    }
+
+    /// Pick the cheapest of the acceptable modes before proving
+    ///
+    /// Returns `None` if no acceptable mode can be verified on the chain.
+    pub fn cheapest_mode(&self, chain: TargetChain, acceptable: &[ProofMode]) -> Option<GasEstimate> {
+        acceptable.iter()
+            .filter_map(|mode| {
+                let model = self.model(chain, *mode)?;
+                self.estimate_for(chain, *mode, model.typical_proof_size).ok()
+            })
+            .min_by_key(|estimate| estimate.cost)
+    }
+}
+
+impl Default for GasEstimator {
+    fn default() -> Self {
+        Self::with_known_models()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cheapest_mode() {
+        let estimator = GasEstimator::default();
+        let modes = [ProofMode::Core, ProofMode::Compressed];
+
+        let estimate = estimator.cheapest_mode(TargetChain::Substrate, &modes).unwrap();
+        assert_eq!(estimate.proof_mode, ProofMode::Compressed);
+
+        assert!(estimator.cheapest_mode(TargetChain::Evm, &modes).is_none());
+        assert!(estimator.estimate_for(TargetChain::Evm, ProofMode::Core, 1024).is_err());
+
+        // Only Groth16 proofs can be verified on EVM
+        let modes = [ProofMode::Compressed, ProofMode::Groth16];
+        let estimate = estimator.cheapest_mode(TargetChain::Evm, &modes).unwrap();
+        assert_eq!(estimate.proof_mode, ProofMode::Groth16);
+    }
+}
@@ -0,0 +1,261 @@
+//! Groth16 backend for the message verification circuit
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+use async_trait::async_trait;
+use ark_bn254::Bn254;
+use ark_groth16::{Groth16, Proof};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use frostgate_zkip::{
+    HealthStatus, ProofMetadata, ResourceUsage, ZkBackend, ZkBackendExt, ZkConfig, ZkError, ZkResult,
+};
+
+use crate::domain::DomainSeparator;
+use crate::error::ZkError as CustomZkError;
+use crate::profiles::MESSAGE_VERIFY;
+use super::circuit::HashEqualityCircuit;
+use super::keys::KeyStore;
+
+/// Length of a compressed BN254 Groth16 proof
+pub const PROOF_LEN: usize = 128;
+
+/// Length of a serialized proof: message length, domain and Groth16 proof
+pub const ENVELOPE_LEN: usize = 4 + DomainSeparator::LEN + PROOF_LEN;
+
+/// Backend proving message verification with a hand-written Groth16 circuit
+///
+/// Programs use the message verification format, the circuit type `0x01` followed
+/// by the expected SHA-256 hash. A proof is the little-endian `u32` message length
+/// selecting the verifying key, the domain and the compressed Groth16 proof.
+#[derive(Debug)]
+pub struct Groth16Backend {
+    keys: Arc<KeyStore>,
+    active_tasks: Arc<AtomicUsize>,
+}
+
+impl Groth16Backend {
+    /// Create a backend keeping setup artifacts in memory
+    pub fn new() -> Self {
+        Self::with_keys(KeyStore::in_memory())
+    }
+
+    /// Create a backend using `keys` for setup artifacts
+    pub fn with_keys(keys: KeyStore) -> Self {
+        Self {
+            keys: Arc::new(keys),
+            active_tasks: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Setup artifacts of the backend
+    pub fn keys(&self) -> &KeyStore {
+        &self.keys
+    }
+
+    /// Generate a proof of `message` against `program`, bound to `domain`
+    pub async fn prove_with_domain(
+        &self,
+        program: &[u8],
+        message: &[u8],
+        domain: DomainSeparator,
+    ) -> Result<(Vec<u8>, ProofMetadata), CustomZkError> {
+        let start = SystemTime::now();
+        let expected_hash = expected_hash(program)?;
+        if Sha256::digest(message).as_slice() != expected_hash {
+            return Err(CustomZkError::InvalidInput("Message does not match the expected hash".into()));
+        }
+        let message_len = u32::try_from(message.len())
+            .map_err(|_| CustomZkError::InvalidInput("Message too long".into()))?;
+
+        // Setup and proving are CPU-bound, keep them off the async executor
+        let keys = self.keys.clone();
+        let circuit = HashEqualityCircuit::new(message.to_vec(), domain);
+        self.active_tasks.fetch_add(1, Ordering::Relaxed);
+        let result = tokio::task::spawn_blocking(move || {
+            let proving_key = keys.get_or_setup(circuit.message_len())?;
+            let proof = Groth16::<Bn254>::prove(&proving_key, circuit, &mut OsRng)
+                .map_err(|e| CustomZkError::ProofGeneration(e.to_string()))?;
+            let mut bytes = Vec::with_capacity(ENVELOPE_LEN);
+            bytes.extend_from_slice(&message_len.to_le_bytes());
+            bytes.extend_from_slice(&domain.to_bytes());
+            proof.serialize_compressed(&mut bytes)
+                .map_err(|e| CustomZkError::Backend(format!("Failed to serialize proof: {}", e)))?;
+            Ok::<_, CustomZkError>(bytes)
+        }).await;
+        self.active_tasks.fetch_sub(1, Ordering::Relaxed);
+        let proof = result.map_err(|e| CustomZkError::Backend(format!("Proving task failed: {}", e)))??;
+
+        Ok((proof.clone(), ProofMetadata {
+            generation_time: start.elapsed().unwrap_or_default(),
+            proof_size: proof.len(),
+            program_hash: hex::encode(Sha256::digest(program)),
+            timestamp: start,
+        }))
+    }
+
+    /// Verify a proof against `program` and check that it is bound to `domain`
+    pub fn verify_with_domain(
+        &self,
+        program: &[u8],
+        proof: &[u8],
+        domain: DomainSeparator,
+    ) -> Result<bool, CustomZkError> {
+        let found = proof_domain(proof)?;
+        if found != domain {
+            return Err(CustomZkError::DomainMismatch { expected: domain, found });
+        }
+        self.check(program, proof)
+    }
+
+    fn check(&self, program: &[u8], proof: &[u8]) -> Result<bool, CustomZkError> {
+        let expected_hash = expected_hash(program)?;
+        let domain = proof_domain(proof)?;
+        let message_len = u32::from_le_bytes([proof[0], proof[1], proof[2], proof[3]]) as usize;
+        let verifying_key = self.keys.verifying_key(message_len)?
+            .ok_or_else(|| CustomZkError::ProofVerification(format!("No Groth16 key for {}-byte messages", message_len)))?;
+        let groth16_proof = match Proof::<Bn254>::deserialize_compressed(&proof[4 + DomainSeparator::LEN..]) {
+            Ok(proof) => proof,
+            Err(_) => return Ok(false),
+        };
+        let inputs = HashEqualityCircuit::public_inputs(&expected_hash, domain);
+        Groth16::<Bn254>::verify(&verifying_key, &inputs, &groth16_proof)
+            .map_err(|e| CustomZkError::ProofVerification(e.to_string()))
+    }
+}
+
+impl Default for Groth16Backend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Expected hash of a message verification program
+fn expected_hash(program: &[u8]) -> Result<[u8; 32], CustomZkError> {
+    match program {
+        [MESSAGE_VERIFY, hash @ ..] if hash.len() == 32 => Ok(hash.try_into().expect("length checked")),
+        [MESSAGE_VERIFY, ..] => Err(CustomZkError::InvalidInput("Program too short for message verification".into())),
+        _ => Err(CustomZkError::InvalidInput("Groth16 backend only supports message verification".into())),
+    }
+}
+
+/// Domain a serialized proof is bound to
+fn proof_domain(proof: &[u8]) -> Result<DomainSeparator, CustomZkError> {
+    if proof.len() != ENVELOPE_LEN {
+        return Err(CustomZkError::ProofVerification(format!(
+            "Malformed Groth16 proof: expected {} bytes, got {}",
+            ENVELOPE_LEN,
+            proof.len()
+        )));
+    }
+    DomainSeparator::from_bytes(&proof[4..4 + DomainSeparator::LEN])
+        .ok_or_else(|| CustomZkError::ProofVerification("Malformed domain separator".into()))
+}
+
+#[async_trait]
+impl ZkBackend for Groth16Backend {
+    async fn prove(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<(Vec<u8>, ProofMetadata)> {
+        self.prove_with_domain(program, input, DomainSeparator::UNBOUND)
+            .await
+            .map_err(|e| ZkError::Backend(e.to_string()))
+    }
+
+    async fn verify(
+        &self,
+        program: &[u8],
+        proof: &[u8],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<bool> {
+        self.check(program, proof).map_err(|e| ZkError::Backend(e.to_string()))
+    }
+
+    fn resource_usage(&self) -> ResourceUsage {
+        ResourceUsage {
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            active_tasks: self.active_tasks.load(Ordering::Relaxed),
+            max_concurrent: usize::MAX,
+            queue_depth: 0,
+        }
+    }
+
+    async fn health_check(&self) -> HealthStatus {
+        HealthStatus::Healthy
+    }
+}
+
+#[async_trait]
+impl ZkBackendExt for Groth16Backend {
+    async fn batch_prove(
+        &self,
+        programs: &[(&[u8], &[u8])],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<Vec<(Vec<u8>, ProofMetadata)>> {
+        let results = futures::future::join_all(programs.iter().map(|(program, input)| {
+            self.prove(program, input, config)
+        })).await;
+        results.into_iter().collect()
+    }
+
+    async fn batch_verify(
+        &self,
+        verifications: &[(&[u8], &[u8])],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<Vec<bool>> {
+        verifications.iter()
+            .map(|(program, proof)| self.check(program, proof).map_err(|e| ZkError::Backend(e.to_string())))
+            .collect()
+    }
+
+    async fn clear_cache(&mut self) -> ZkResult<()> {
+        Ok(())
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        vec![
+            "groth16".to_string(),
+            "groth16_bn254".to_string(),
+            "message_verify".to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ChainId;
+
+    #[tokio::test]
+    async fn test_prove_and_verify() {
+        let backend = Groth16Backend::new();
+        let message = b"frostgate".to_vec();
+        let mut program = vec![MESSAGE_VERIFY];
+        program.extend_from_slice(&Sha256::digest(&message));
+
+        let domain = DomainSeparator::new(ChainId(1), ChainId(10));
+        let (proof, metadata) = backend.prove_with_domain(&program, &message, domain).await.unwrap();
+        assert_eq!(proof.len(), ENVELOPE_LEN);
+        assert_eq!(metadata.proof_size, ENVELOPE_LEN);
+        assert!(backend.verify_with_domain(&program, &proof, domain).unwrap());
+        assert!(backend.verify_with_domain(&program, &proof, DomainSeparator::UNBOUND).is_err());
+
+        // Another hash or a forged domain does not verify
+        let mut other = program.clone();
+        other[1] ^= 1;
+        assert!(!backend.verify(&other, &proof, None).await.unwrap());
+        let mut forged = proof.clone();
+        forged[4..4 + DomainSeparator::LEN].copy_from_slice(&DomainSeparator::UNBOUND.to_bytes());
+        assert!(!backend.verify(&program, &forged, None).await.unwrap());
+
+        // Messages not matching the program are rejected before proving
+        assert!(backend.prove(&program, b"other", None).await.is_err());
+    }
+}
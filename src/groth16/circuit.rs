@@ -0,0 +1,90 @@
+//! R1CS circuit proving knowledge of a message with a given SHA-256 hash
+
+use ark_bn254::Fr;
+use ark_crypto_primitives::crh::sha256::constraints::Sha256Gadget;
+use ark_ff::ToConstraintField;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use sha2::{Digest, Sha256};
+
+use crate::domain::DomainSeparator;
+
+/// Hash-equality circuit over messages of a fixed length
+///
+/// The message is a private witness; the expected hash and the domain are public
+/// inputs, packed into as few field elements as they fit.
+#[derive(Debug, Clone)]
+pub struct HashEqualityCircuit {
+    /// Message bytes
+    message: Vec<u8>,
+    /// SHA-256 of the message
+    hash: [u8; 32],
+    /// Domain the proof is bound to
+    domain: DomainSeparator,
+}
+
+impl HashEqualityCircuit {
+    /// Circuit proving `message`, bound to `domain`
+    pub fn new(message: Vec<u8>, domain: DomainSeparator) -> Self {
+        let hash = Sha256::digest(&message).into();
+        Self { message, hash, domain }
+    }
+
+    /// Circuit with the shape of messages of `message_len` bytes, used for setup
+    pub fn blank(message_len: usize) -> Self {
+        Self::new(vec![0u8; message_len], DomainSeparator::UNBOUND)
+    }
+
+    /// Length of the message
+    pub fn message_len(&self) -> usize {
+        self.message.len()
+    }
+
+    /// SHA-256 of the message
+    pub fn hash(&self) -> [u8; 32] {
+        self.hash
+    }
+
+    /// Public inputs of a proof of `hash` bound to `domain`
+    pub fn public_inputs(hash: &[u8; 32], domain: DomainSeparator) -> Vec<Fr> {
+        public_bytes(hash, domain)
+            .to_field_elements()
+            .expect("bytes always pack into field elements")
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for HashEqualityCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let public = UInt8::new_input_vec(cs.clone(), &public_bytes(&self.hash, self.domain))?;
+        let message = UInt8::new_witness_vec(cs, &self.message)?;
+        let digest = Sha256Gadget::digest(&message)?;
+        digest.0.as_slice().enforce_equal(&public[..32])
+    }
+}
+
+/// Hash followed by the domain, as allocated for the public inputs
+fn public_bytes(hash: &[u8; 32], domain: DomainSeparator) -> Vec<u8> {
+    [&hash[..], &domain.to_bytes()].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::r1cs::ConstraintSystem;
+    use crate::domain::ChainId;
+
+    #[test]
+    fn test_constraints_satisfied_only_for_matching_hash() {
+        let domain = DomainSeparator::new(ChainId(1), ChainId(2));
+        let circuit = HashEqualityCircuit::new(b"hello".to_vec(), domain);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let mut wrong = circuit;
+        wrong.hash[0] ^= 1;
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        wrong.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,145 @@
+//! Setup artifacts of the Groth16 circuits
+//!
+//! The constraints of a hash-equality circuit depend on the length of the message it
+//! hashes, so every message length has its own proving and verifying key. A
+//! [`KeyStore`] runs the circuit-specific setup the first time a length is needed,
+//! keeps the keys in memory and, when opened on a directory, persists them so
+//! restarts and other hosts reuse them. Keys from a setup ceremony are installed with
+//! [`KeyStore::import`]; a locally generated setup is only as trustworthy as the
+//! host that ran it.
+//!
+//! Layout under the key directory:
+//! - `message-<len>.pk`: compressed proving key, which embeds the verifying key
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use ark_bn254::Bn254;
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::CircuitSpecificSetupSNARK;
+use parking_lot::RwLock;
+use rand::rngs::OsRng;
+
+use crate::error::ZkError;
+use super::circuit::HashEqualityCircuit;
+
+/// Proving and verifying keys by message length
+#[derive(Debug, Default)]
+pub struct KeyStore {
+    dir: Option<PathBuf>,
+    keys: RwLock<HashMap<usize, Arc<ProvingKey<Bn254>>>>,
+}
+
+impl KeyStore {
+    /// Create a store keeping keys in memory only
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Open a store persisting keys under `dir`, creating it if needed
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, ZkError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir: Some(dir),
+            keys: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Proving key for messages of `message_len` bytes, if set up
+    pub fn get(&self, message_len: usize) -> Result<Option<Arc<ProvingKey<Bn254>>>, ZkError> {
+        if let Some(key) = self.keys.read().get(&message_len) {
+            return Ok(Some(key.clone()));
+        }
+        let path = match self.key_path(message_len) {
+            Some(path) if path.exists() => path,
+            _ => return Ok(None),
+        };
+        let key = Arc::new(decode_key(&fs::read(path)?)?);
+        self.keys.write().insert(message_len, key.clone());
+        Ok(Some(key))
+    }
+
+    /// Proving key for messages of `message_len` bytes, running the setup if needed
+    pub fn get_or_setup(&self, message_len: usize) -> Result<Arc<ProvingKey<Bn254>>, ZkError> {
+        if let Some(key) = self.get(message_len)? {
+            return Ok(key);
+        }
+        let (key, _) = Groth16::<Bn254>::circuit_specific_setup(HashEqualityCircuit::blank(message_len), &mut OsRng)
+            .map_err(|e| ZkError::CircuitCompilation(format!("Groth16 setup failed: {}", e)))?;
+        self.insert(message_len, key)
+    }
+
+    /// Install a compressed proving key for messages of `message_len` bytes, such as
+    /// the output of a setup ceremony
+    pub fn import(&self, message_len: usize, proving_key: &[u8]) -> Result<(), ZkError> {
+        self.insert(message_len, decode_key(proving_key)?).map(|_| ())
+    }
+
+    /// Verifying key for messages of `message_len` bytes, if set up
+    pub fn verifying_key(&self, message_len: usize) -> Result<Option<VerifyingKey<Bn254>>, ZkError> {
+        Ok(self.get(message_len)?.map(|key| key.vk.clone()))
+    }
+
+    /// Compressed verifying key for messages of `message_len` bytes
+    pub fn export_verifying_key(&self, message_len: usize) -> Result<Vec<u8>, ZkError> {
+        let key = self.verifying_key(message_len)?
+            .ok_or_else(|| ZkError::InvalidInput(format!("No Groth16 key for {}-byte messages", message_len)))?;
+        let mut bytes = Vec::new();
+        key.serialize_compressed(&mut bytes)
+            .map_err(|e| ZkError::Backend(format!("Failed to serialize verifying key: {}", e)))?;
+        Ok(bytes)
+    }
+
+    /// Message lengths with keys in memory, sorted
+    pub fn lengths(&self) -> Vec<usize> {
+        let mut lengths: Vec<usize> = self.keys.read().keys().copied().collect();
+        lengths.sort_unstable();
+        lengths
+    }
+
+    fn insert(&self, message_len: usize, key: ProvingKey<Bn254>) -> Result<Arc<ProvingKey<Bn254>>, ZkError> {
+        if let Some(path) = self.key_path(message_len) {
+            let mut bytes = Vec::new();
+            key.serialize_compressed(&mut bytes)
+                .map_err(|e| ZkError::Backend(format!("Failed to serialize proving key: {}", e)))?;
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, bytes)?;
+            fs::rename(&tmp, &path)?;
+        }
+        let key = Arc::new(key);
+        self.keys.write().insert(message_len, key.clone());
+        Ok(key)
+    }
+
+    fn key_path(&self, message_len: usize) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(format!("message-{}.pk", message_len)))
+    }
+}
+
+fn decode_key(bytes: &[u8]) -> Result<ProvingKey<Bn254>, ZkError> {
+    ProvingKey::deserialize_compressed(bytes)
+        .map_err(|e| ZkError::InvalidInput(format!("Invalid Groth16 proving key: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = KeyStore::open(dir.path()).unwrap();
+        let key = store.get_or_setup(4).unwrap();
+        assert_eq!(store.lengths(), vec![4]);
+
+        // A fresh store on the same directory loads the key instead of a new setup
+        let reopened = KeyStore::open(dir.path()).unwrap();
+        assert!(reopened.lengths().is_empty());
+        assert_eq!(reopened.get(4).unwrap().unwrap().vk, key.vk);
+        assert!(reopened.get(5).unwrap().is_none());
+        assert!(!reopened.export_verifying_key(4).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+//! Groth16 proofs of the message verification circuit without a zkVM
+//!
+//! The message verification statement is a single SHA-256 hash equality. Proving it
+//! in a zkVM pays for executing and proving a whole RISC-V guest; this module builds
+//! the R1CS constraints directly with arkworks instead. Proofs are 148 bytes over
+//! BN254, cheap to verify on EVM chains, and take far less time to generate.
+//!
+//! Each message length needs its own circuit-specific setup; see [`keys`] for how
+//! the setup artifacts are generated, persisted and imported.
+
+pub mod backend;
+pub mod circuit;
+pub mod keys;
+
+pub use backend::Groth16Backend;
+pub use circuit::HashEqualityCircuit;
+pub use keys::KeyStore;
@@ -0,0 +1,101 @@
+//! Wire types and request signing shared by the gRPC server and client
+//!
+//! Requests may be signed with a key shared between client and server as
+//! described in [`crate::signing`], with the protobuf encoding of the request as
+//! the signed body and the signature carried in request metadata.
+
+use prost::Message;
+use tonic::metadata::MetadataMap;
+use tonic::Status;
+
+use crate::domain::{ChainId, DomainSeparator};
+use crate::metadata::ProofMode;
+
+pub use crate::signing::{MAX_CLOCK_SKEW, SIGNATURE_HEADER, SIGNATURE_VALIDITY, TIMESTAMP_HEADER};
+
+/// Types generated from `proto/prover.proto`
+pub mod pb {
+    tonic::include_proto!("frostgate.prover.v1");
+}
+
+/// Sign `request` for `method`, adding the signature to `metadata`
+pub(crate) fn sign<M: Message>(key: &[u8], method: &str, request: &M, metadata: &mut MetadataMap) {
+    let (signature, timestamp) = crate::signing::sign(key, method, &request.encode_to_vec());
+    // Hex digits and decimal numbers are valid metadata values
+    metadata.insert(SIGNATURE_HEADER, signature.parse().expect("hex is ASCII"));
+    metadata.insert(TIMESTAMP_HEADER, timestamp.parse().expect("digits are ASCII"));
+}
+
+/// Check the signature of `request` for `method`
+pub(crate) fn check_signature<M: Message>(
+    key: &[u8],
+    method: &str,
+    request: &M,
+    metadata: &MetadataMap,
+) -> Result<(), Status> {
+    crate::signing::check(key, method, &request.encode_to_vec(), |name| {
+        metadata.get(name).and_then(|value| value.to_str().ok())
+    })
+    .map_err(Status::unauthenticated)
+}
+
+/// Domain separator of a wire domain
+pub(crate) fn domain(domain: pb::Domain) -> DomainSeparator {
+    DomainSeparator::new(ChainId(domain.origin), ChainId(domain.destination))
+}
+
+/// Wire domain of a domain separator
+pub(crate) fn wire_domain(domain: DomainSeparator) -> pb::Domain {
+    pb::Domain {
+        origin: domain.origin.0,
+        destination: domain.destination.0,
+    }
+}
+
+/// Parse a wire proof mode name
+pub(crate) fn parse_mode(mode: &str) -> Result<ProofMode, Status> {
+    match mode {
+        "core" => Ok(ProofMode::Core),
+        "compressed" => Ok(ProofMode::Compressed),
+        "plonk" => Ok(ProofMode::Plonk),
+        "groth16" => Ok(ProofMode::Groth16),
+        mode => Err(Status::invalid_argument(format!("Unknown proof mode: {}", mode))),
+    }
+}
+
+/// Wire name of a proof mode
+pub(crate) fn mode_name(mode: ProofMode) -> String {
+    match mode {
+        ProofMode::Core => "core",
+        ProofMode::Compressed => "compressed",
+        ProofMode::Plonk => "plonk",
+        ProofMode::Groth16 => "groth16",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_round_trip() {
+        let request = pb::VerifyRequest {
+            program: vec![1],
+            proof: vec![2, 3],
+            domain: None,
+        };
+        let mut metadata = MetadataMap::new();
+        sign(b"secret", "verify", &request, &mut metadata);
+        assert!(check_signature(b"secret", "verify", &request, &metadata).is_ok());
+
+        // Wrong key, method or body
+        assert!(check_signature(b"other", "verify", &request, &metadata).is_err());
+        assert!(check_signature(b"secret", "prove", &request, &metadata).is_err());
+        let tampered = pb::VerifyRequest { proof: vec![2, 4], ..request };
+        assert!(check_signature(b"secret", "verify", &tampered, &metadata).is_err());
+
+        // Unsigned
+        assert!(check_signature(b"secret", "verify", &tampered, &MetadataMap::new()).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Guest programs brought by applications
+//!
+//! Besides the built-in circuits, applications can register their own compiled
+//! guest programs with a backend (`register_program`) and prove and verify them
+//! through the standard API. A registered program is referenced by its name: the
+//! returned program bytes are a [`ProgramDescriptor`] of type [`USER_PROGRAM`]
+//! carrying the name. The guest reads the proving input as written, followed by the
+//! domain separator, like the built-in circuits.
+//!
+//! Each program comes with a journal validator. Verification only accepts a proof
+//! whose seal verifies against the registered ELF and whose journal (RISC0) or
+//! public values (SP1) the validator accepts.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use parking_lot::RwLock;
+
+use crate::descriptor::{self, ProgramDescriptor, ELF_MAGIC};
+use crate::error::ZkError;
+use crate::profiles::USER_PROGRAM;
+
+/// Checks the journal committed by a guest program
+pub type JournalValidator = Arc<dyn Fn(&[u8]) -> bool + Send + Sync>;
+
+/// Guest program registered by an application
+#[derive(Clone)]
+pub struct GuestProgram {
+    /// Name the program is registered under
+    pub name: String,
+    /// Guest ELF
+    pub elf: Arc<[u8]>,
+    validator: JournalValidator,
+}
+
+impl GuestProgram {
+    /// Create a program named `name` running `elf`
+    pub fn new(name: impl Into<String>, elf: impl Into<Arc<[u8]>>, validator: JournalValidator) -> Self {
+        Self {
+            name: name.into(),
+            elf: elf.into(),
+            validator,
+        }
+    }
+
+    /// Whether the validator accepts a journal committed by the program
+    pub fn validate(&self, journal: &[u8]) -> bool {
+        (self.validator)(journal)
+    }
+
+    /// Program bytes referencing this program
+    pub fn program(&self) -> Vec<u8> {
+        ProgramDescriptor::new(USER_PROGRAM, self.name.as_bytes()).encode()
+    }
+}
+
+impl fmt::Debug for GuestProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GuestProgram")
+            .field("name", &self.name)
+            .field("elf_len", &self.elf.len())
+            .finish()
+    }
+}
+
+/// Guest programs by name
+#[derive(Debug, Default)]
+pub struct GuestPrograms {
+    programs: RwLock<HashMap<String, Arc<GuestProgram>>>,
+}
+
+impl GuestPrograms {
+    /// Create an empty set of programs
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a program, replacing any program registered under the same name
+    pub fn register(
+        &self,
+        name: &str,
+        elf: Vec<u8>,
+        journal_validator: JournalValidator,
+    ) -> Result<Arc<GuestProgram>, ZkError> {
+        if name.is_empty() {
+            return Err(ZkError::InvalidInput("Program name cannot be empty".into()));
+        }
+        if !elf.starts_with(ELF_MAGIC) {
+            return Err(ZkError::InvalidInput(format!("Program {} is not an ELF", name)));
+        }
+        let program = Arc::new(GuestProgram::new(name, elf, journal_validator));
+        self.programs.write().insert(name.to_string(), program.clone());
+        Ok(program)
+    }
+
+    /// Remove a program, returning whether it was registered
+    pub fn unregister(&self, name: &str) -> bool {
+        self.programs.write().remove(name).is_some()
+    }
+
+    /// Get a program by name
+    pub fn get(&self, name: &str) -> Option<Arc<GuestProgram>> {
+        self.programs.read().get(name).cloned()
+    }
+
+    /// Get the registered program referenced by a descriptor
+    pub fn for_descriptor(&self, program: &ProgramDescriptor) -> Result<Arc<GuestProgram>, ZkError> {
+        let name = String::from_utf8_lossy(&program.public_params);
+        self.get(&name).ok_or_else(|| ZkError::UnknownProgram(name.into_owned()))
+    }
+
+    /// Get the registered program referenced by program bytes
+    ///
+    /// Returns `None` for programs of other circuit types.
+    pub fn resolve(&self, program: &[u8]) -> Result<Option<Arc<GuestProgram>>, ZkError> {
+        if descriptor::circuit_type(program) != Some(USER_PROGRAM) {
+            return Ok(None);
+        }
+        self.for_descriptor(&ProgramDescriptor::decode(program)?).map(Some)
+    }
+
+    /// Names of the registered programs, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.programs.read().keys().cloned().collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::MESSAGE_VERIFY;
+
+    #[test]
+    fn test_register_and_resolve() {
+        let programs = GuestPrograms::new();
+        let validator: JournalValidator = Arc::new(|journal: &[u8]| journal.len() == 4);
+        assert!(programs.register("counter", b"not an elf".to_vec(), validator.clone()).is_err());
+
+        let program = programs.register("counter", b"\x7fELF counter".to_vec(), validator).unwrap();
+        let resolved = programs.resolve(&program.program()).unwrap().unwrap();
+        assert_eq!(resolved.name, "counter");
+        assert!(resolved.validate(&[0; 4]));
+        assert!(!resolved.validate(&[0; 5]));
+
+        // Built-in programs are left alone, unknown names are errors
+        let mut builtin = vec![MESSAGE_VERIFY];
+        builtin.extend_from_slice(&[0; 32]);
+        assert!(programs.resolve(&builtin).unwrap().is_none());
+        assert!(programs.unregister("counter"));
+        assert!(matches!(programs.resolve(&program.program()), Err(ZkError::UnknownProgram(_))));
+    }
+}
@@ -0,0 +1,279 @@
+//! Configurable hash functions for host utilities
+//!
+//! Target chains identify programs and messages with different hash functions:
+//! EVM chains use Keccak-256, Substrate chains Blake2b and SNARK-friendly verifiers
+//! Poseidon; BLAKE3 is common for off-chain data. A [`HashFunction`] is selected per circuit through its
+//! [`crate::profiles::CircuitProfile`] and is used wherever this crate computes a
+//! program hash, so "program hash" always means the hash the target chain uses.
+//!
+//! The message verification guests also hash messages with SHA-256, Keccak-256,
+//! Blake2b or BLAKE3, selected by the [`HashFunction::message_hash_id`] byte of their
+//! program. [`HashFunction::hash`] computes the digests they expect.
+//!
+//! Poseidon digests are cheap to recompute inside other SNARKs. [`POSEIDON_PARAMS`]
+//! describes the instance and sponge so that verifiers outside this crate can match
+//! [`HashFunction::Poseidon`] exactly.
+
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use blake2::{Blake2b, digest::consts::U32};
+use ark_bn254::Fr;
+use light_poseidon::{Poseidon, PoseidonBytesHasher};
+
+/// Parameters of the Poseidon instance and sponge behind [`HashFunction::Poseidon`]
+///
+/// The state starts as the input length as a big-endian integer. Each chunk of at
+/// most [`chunk_size`](Self::chunk_size) input bytes is then absorbed as
+/// `state = Poseidon(state, chunk)`, both read as big-endian field elements, and the
+/// final state is the digest. Empty inputs hash to the length seed, zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PoseidonParams {
+    /// Scalar field the permutation works over
+    pub field: &'static str,
+    /// Modulus of the field, big-endian hex
+    pub modulus: &'static str,
+    /// State width `t`, the number of inputs plus the capacity element
+    pub width: usize,
+    /// Number of full rounds
+    pub full_rounds: usize,
+    /// Number of partial rounds
+    pub partial_rounds: usize,
+    /// Exponent of the S-box
+    pub alpha: u64,
+    /// Bytes of input packed into each field element
+    pub chunk_size: usize,
+}
+
+/// Circom-compatible BN254 Poseidon with two inputs, as used by [`HashFunction::Poseidon`]
+pub const POSEIDON_PARAMS: PoseidonParams = PoseidonParams {
+    field: "bn254-fr",
+    modulus: "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+    width: 3,
+    full_rounds: 8,
+    partial_rounds: 57,
+    alpha: 5,
+    chunk_size: 31,
+};
+
+/// Hash function producing 32-byte digests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashFunction {
+    /// SHA-256
+    Sha256,
+    /// Keccak-256, as used by EVM chains
+    Keccak256,
+    /// Blake2b with a 256-bit digest, as used by Substrate chains
+    Blake2b,
+    /// Poseidon over the BN254 scalar field with circom parameters
+    Poseidon,
+    /// BLAKE3 with its default 256-bit output
+    Blake3,
+}
+
+impl HashFunction {
+    /// Every supported hash function
+    pub const ALL: [HashFunction; 5] = [
+        HashFunction::Sha256,
+        HashFunction::Keccak256,
+        HashFunction::Blake2b,
+        HashFunction::Poseidon,
+        HashFunction::Blake3,
+    ];
+
+    /// Create an incremental hasher
+    pub fn hasher(self) -> Box<dyn Hasher> {
+        match self {
+            HashFunction::Sha256 => Box::new(DigestHasher(Sha256::new())),
+            HashFunction::Keccak256 => Box::new(DigestHasher(Keccak256::new())),
+            HashFunction::Blake2b => Box::new(DigestHasher(Blake2b::<U32>::new())),
+            HashFunction::Poseidon => Box::new(PoseidonHasher(Vec::new())),
+            HashFunction::Blake3 => Box::new(Blake3Hasher(blake3::Hasher::new())),
+        }
+    }
+
+    /// Hash `data` in one call
+    pub fn hash(self, data: &[u8]) -> [u8; 32] {
+        let mut hasher = self.hasher();
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    /// Hash the concatenation of two digests
+    pub fn combine(self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = self.hasher();
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize()
+    }
+
+    /// Identifier of the function in message verification programs and guest input,
+    /// `None` if the guests do not implement it
+    pub fn message_hash_id(self) -> Option<u8> {
+        match self {
+            HashFunction::Sha256 => Some(0),
+            HashFunction::Keccak256 => Some(1),
+            HashFunction::Blake2b => Some(2),
+            HashFunction::Blake3 => Some(3),
+            HashFunction::Poseidon => None,
+        }
+    }
+
+    /// Function with a message verification identifier
+    pub fn from_message_hash_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(HashFunction::Sha256),
+            1 => Some(HashFunction::Keccak256),
+            2 => Some(HashFunction::Blake2b),
+            3 => Some(HashFunction::Blake3),
+            _ => None,
+        }
+    }
+}
+
+impl Default for HashFunction {
+    fn default() -> Self {
+        HashFunction::Sha256
+    }
+}
+
+impl fmt::Display for HashFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashFunction::Sha256 => write!(f, "sha256"),
+            HashFunction::Keccak256 => write!(f, "keccak256"),
+            HashFunction::Blake2b => write!(f, "blake2b-256"),
+            HashFunction::Poseidon => write!(f, "poseidon-bn254"),
+            HashFunction::Blake3 => write!(f, "blake3"),
+        }
+    }
+}
+
+/// Incremental hasher producing 32-byte digests
+pub trait Hasher: Send {
+    /// Feed more data
+    fn update(&mut self, data: &[u8]);
+
+    /// Finish and return the digest
+    fn finalize(self: Box<Self>) -> [u8; 32];
+}
+
+struct DigestHasher<D>(D);
+
+impl<D: Digest + Send> Hasher for DigestHasher<D> {
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(&mut self.0, data);
+    }
+
+    fn finalize(self: Box<Self>) -> [u8; 32] {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&self.0.finalize());
+        hash
+    }
+}
+
+struct Blake3Hasher(blake3::Hasher);
+
+impl Hasher for Blake3Hasher {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self: Box<Self>) -> [u8; 32] {
+        *self.0.finalize().as_bytes()
+    }
+}
+
+/// Poseidon sponge over 31-byte chunks, seeded with the input length
+///
+/// Poseidon works on field elements, so the input is buffered and absorbed one
+/// chunk at a time into a two-element permutation when finalized.
+struct PoseidonHasher(Vec<u8>);
+
+impl Hasher for PoseidonHasher {
+    fn update(&mut self, data: &[u8]) {
+        self.0.extend_from_slice(data);
+    }
+
+    fn finalize(self: Box<Self>) -> [u8; 32] {
+        let mut poseidon = Poseidon::<Fr>::new_circom(POSEIDON_PARAMS.width - 1).expect("two inputs are supported");
+        let mut state = [0u8; 32];
+        state[24..].copy_from_slice(&(self.0.len() as u64).to_be_bytes());
+        for chunk in self.0.chunks(POSEIDON_PARAMS.chunk_size) {
+            state = poseidon.hash_bytes_be(&[&state, chunk])
+                .expect("chunks are smaller than the field modulus");
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_digests() {
+        assert_eq!(
+            hex::encode(HashFunction::Sha256.hash(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex::encode(HashFunction::Keccak256.hash(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::encode(HashFunction::Blake2b.hash(b"")),
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+        assert_eq!(
+            hex::encode(HashFunction::Blake3.hash(b"")),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+    }
+
+    #[test]
+    fn test_functions_differ_and_are_deterministic() {
+        let functions = [
+            HashFunction::Sha256,
+            HashFunction::Keccak256,
+            HashFunction::Blake2b,
+            HashFunction::Poseidon,
+            HashFunction::Blake3,
+        ];
+        for (i, a) in functions.iter().enumerate() {
+            assert_eq!(a.hash(b"program"), a.hash(b"program"));
+            for b in &functions[i + 1..] {
+                assert_ne!(a.hash(b"program"), b.hash(b"program"));
+            }
+        }
+
+        // Incremental and one-shot hashing agree
+        let mut hasher = HashFunction::Poseidon.hasher();
+        hasher.update(b"pro");
+        hasher.update(b"gram");
+        assert_eq!(hasher.finalize(), HashFunction::Poseidon.hash(b"program"));
+    }
+
+    #[test]
+    fn test_message_hash_ids() {
+        for function in [HashFunction::Sha256, HashFunction::Keccak256, HashFunction::Blake2b, HashFunction::Blake3] {
+            let id = function.message_hash_id().unwrap();
+            assert_eq!(HashFunction::from_message_hash_id(id), Some(function));
+        }
+        assert_eq!(HashFunction::Poseidon.message_hash_id(), None);
+        assert_eq!(HashFunction::from_message_hash_id(4), None);
+    }
+
+    #[test]
+    fn test_poseidon_params() {
+        assert_eq!(HashFunction::Poseidon.hash(b""), [0u8; 32]);
+
+        // A single chunk is one permutation of the length seed and the chunk
+        let mut poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+        let mut seed = [0u8; 32];
+        seed[31] = 31;
+        let chunk = [0x42u8; 31];
+        assert_eq!(HashFunction::Poseidon.hash(&chunk), poseidon.hash_bytes_be(&[&seed, &chunk]).unwrap());
+    }
+}
@@ -0,0 +1,166 @@
+//! Program and input driving the Merkle inclusion guest
+//!
+//! A [`MerkleInclusionCircuit`] proves a leaf is included in the tree with a trusted
+//! root. Its program is a [`ProgramDescriptor`] of [`MERKLE_INCLUSION`] with the
+//! root, hash function identifier and depth as parameters, so proofs for another
+//! tree, hash function or depth do not verify against it; the SP1 backend runs the
+//! `merkle_inclusion` guest listed for that type in the program manifest. The
+//! proving input is the encoded [`MerkleWitness`].
+
+use crate::descriptor::ProgramDescriptor;
+use crate::error::ZkError;
+use crate::hasher::HashFunction;
+use crate::journal::MerkleInclusionOutput;
+use crate::profiles::MERKLE_INCLUSION;
+use super::{MerkleInclusion, MerkleWitness};
+
+/// Inclusion of a leaf in a binary Merkle tree with a trusted root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleInclusionCircuit {
+    /// Included leaf
+    pub leaf: [u8; 32],
+    /// Siblings on the path from the leaf up to the root
+    pub path: Vec<[u8; 32]>,
+    /// Trusted root
+    pub root: [u8; 32],
+    /// Position of the leaf
+    pub index: u64,
+    /// Hash function of inner nodes
+    pub hash_function: HashFunction,
+}
+
+impl MerkleInclusionCircuit {
+    /// Create a proof of `leaf` at index 0 of a SHA-256 tree with `root`
+    pub fn new(leaf: [u8; 32], path: Vec<[u8; 32]>, root: [u8; 32]) -> Self {
+        Self {
+            leaf,
+            path,
+            root,
+            index: 0,
+            hash_function: HashFunction::Sha256,
+        }
+    }
+
+    /// Prove the leaf at `index`
+    pub fn with_index(mut self, index: u64) -> Self {
+        self.index = index;
+        self
+    }
+
+    /// Hash inner nodes with `hash_function`; Poseidon trees are not supported
+    pub fn with_hash_function(mut self, hash_function: HashFunction) -> Result<Self, ZkError> {
+        if hash_function.message_hash_id().is_none() {
+            return Err(ZkError::InvalidInput(format!("{} Merkle trees are not supported", hash_function)));
+        }
+        self.hash_function = hash_function;
+        Ok(self)
+    }
+
+    /// Depth of the tree, the length of the path
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Descriptor of the inclusion program for the tree
+    pub fn descriptor(&self) -> ProgramDescriptor {
+        let mut params = self.root.to_vec();
+        params.push(self.hash_id());
+        params.push(u8::try_from(self.depth()).unwrap_or(u8::MAX));
+        ProgramDescriptor::new(MERKLE_INCLUSION, params)
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.descriptor().encode()
+    }
+
+    /// Witness the guest verifies
+    pub fn witness(&self) -> MerkleWitness {
+        MerkleWitness {
+            hash_function: self.hash_id(),
+            leaf: self.leaf,
+            index: self.index,
+            path: self.path.clone(),
+        }
+    }
+
+    /// Check the path on the host, as the guest will
+    pub fn verify(&self) -> Result<MerkleInclusion, ZkError> {
+        let statement = super::verify(&self.witness())
+            .map_err(|e| ZkError::InvalidInput(format!("Invalid Merkle witness: {}", e)))?;
+        if statement.root != self.root {
+            return Err(ZkError::InvalidInput("Path does not lead to the trusted root".into()));
+        }
+        Ok(statement)
+    }
+
+    /// Proving input, checked to lead to the trusted root
+    pub fn input(&self) -> Result<Vec<u8>, ZkError> {
+        self.verify()?;
+        Ok(bincode::serialize(&self.witness()).expect("witness serialization cannot fail"))
+    }
+
+    /// Decode the public values of a proof, rejecting proofs of another leaf, index
+    /// or tree
+    pub fn output(&self, public_values: &[u8]) -> Result<MerkleInclusionOutput, ZkError> {
+        let output = MerkleInclusionOutput::decode(public_values)?;
+        let expected = MerkleInclusion {
+            root: self.root,
+            leaf: self.leaf,
+            index: self.index,
+            depth: self.depth() as u8,
+            hash_function: self.hash_id(),
+        };
+        if output.statement != expected {
+            return Err(ZkError::ProofVerification(format!(
+                "Proof is of leaf {} at index {} under root {}, expected leaf {} at index {} under root {}",
+                hex::encode(output.statement.leaf),
+                output.statement.index,
+                hex::encode(output.statement.root),
+                hex::encode(self.leaf),
+                self.index,
+                hex::encode(self.root)
+            )));
+        }
+        Ok(output)
+    }
+
+    fn hash_id(&self) -> u8 {
+        self.hash_function.message_hash_id().expect("checked by with_hash_function")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inclusion::{node, KECCAK256};
+
+    #[test]
+    fn test_merkle_inclusion_circuit() {
+        // Leaf 1 of a Keccak-256 tree of four leaves
+        let leaves = [[0u8; 32], [1; 32], [2; 32], [3; 32]];
+        let left = node(KECCAK256, &leaves[0], &leaves[1]).unwrap();
+        let right = node(KECCAK256, &leaves[2], &leaves[3]).unwrap();
+        let root = node(KECCAK256, &left, &right).unwrap();
+        let circuit = MerkleInclusionCircuit::new(leaves[1], vec![leaves[0], right], root)
+            .with_index(1)
+            .with_hash_function(HashFunction::Keccak256)
+            .unwrap();
+
+        let descriptor = ProgramDescriptor::decode(&circuit.program()).unwrap();
+        assert_eq!(descriptor.circuit_type, MERKLE_INCLUSION);
+        assert_eq!(&descriptor.public_params[32..], &[KECCAK256, 2]);
+
+        let statement = circuit.verify().unwrap();
+        assert_eq!(circuit.output(&statement.to_bytes()).unwrap().statement, statement);
+        assert!(circuit.clone().with_index(2).input().is_err());
+        assert!(circuit.clone().with_hash_function(HashFunction::Sha256).unwrap().input().is_err());
+        assert!(circuit.clone().with_hash_function(HashFunction::Poseidon).is_err());
+
+        // Proofs of another leaf of the same tree are rejected
+        let other = MerkleInclusionCircuit::new(leaves[0], vec![leaves[1], right], root)
+            .with_hash_function(HashFunction::Keccak256)
+            .unwrap();
+        assert!(circuit.output(&other.verify().unwrap().to_bytes()).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! Inclusion of a leaf in a binary Merkle tree
+//!
+//! Block bodies, message queues and validator sets are committed to by the roots of
+//! binary Merkle trees built by other systems, so unlike [`crate::merkle`] the tree
+//! is the plain one: an inner node is the hash of its left and right child, with the
+//! hash function selected by its [`HashFunction::message_hash_id`](crate::hasher::HashFunction::message_hash_id).
+//! Bit `i` of the leaf index selects whether the node at height `i` is a right
+//! (1) or a left (0) child, and the depth of the tree is the length of the path.
+//!
+//! The `merkle_inclusion` guest checks a [`MerkleWitness`] and commits a
+//! [`MerkleInclusion`]. The logic only depends on `alloc` and the hash crates so the
+//! guest includes the very same source file; the host-side program and input
+//! driving the guest are in [`circuit`].
+
+#[cfg(feature = "std")]
+pub mod circuit;
+
+use alloc::vec::Vec;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// Identifier of SHA-256 node hashing
+pub const SHA256: u8 = 0;
+/// Identifier of Keccak-256 node hashing
+pub const KECCAK256: u8 = 1;
+/// Identifier of Blake2b-256 node hashing
+pub const BLAKE2B: u8 = 2;
+/// Identifier of BLAKE3 node hashing
+pub const BLAKE3: u8 = 3;
+
+/// Maximum depth of a tree, the number of bits of a leaf index
+pub const MAX_DEPTH: usize = 64;
+
+/// Inclusion witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleWitness {
+    /// Identifier of the hash function of inner nodes
+    pub hash_function: u8,
+    /// Leaf, usually the hash of the included item
+    pub leaf: [u8; 32],
+    /// Position of the leaf
+    pub index: u64,
+    /// Siblings on the path from the leaf up to the root
+    pub path: Vec<[u8; 32]>,
+}
+
+/// Statement proven by the Merkle inclusion guest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MerkleInclusion {
+    /// Root of the tree
+    pub root: [u8; 32],
+    /// Included leaf
+    pub leaf: [u8; 32],
+    /// Position of the leaf
+    pub index: u64,
+    /// Depth of the tree
+    pub depth: u8,
+    /// Identifier of the hash function of inner nodes
+    pub hash_function: u8,
+}
+
+impl MerkleInclusion {
+    /// Length of an encoded statement
+    pub const LEN: usize = 74;
+
+    /// Encode as the guest commits it: root, leaf, index (little endian), depth,
+    /// hash function
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..32].copy_from_slice(&self.root);
+        bytes[32..64].copy_from_slice(&self.leaf);
+        bytes[64..72].copy_from_slice(&self.index.to_le_bytes());
+        bytes[72] = self.depth;
+        bytes[73] = self.hash_function;
+        bytes
+    }
+
+    /// Decode a statement, `None` if `bytes` is not [`Self::LEN`] long
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        Some(Self {
+            root: bytes[..32].try_into().ok()?,
+            leaf: bytes[32..64].try_into().ok()?,
+            index: u64::from_le_bytes(bytes[64..72].try_into().ok()?),
+            depth: bytes[72],
+            hash_function: bytes[73],
+        })
+    }
+}
+
+/// Reason a witness does not prove inclusion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InclusionError {
+    /// The hash function identifier is unknown
+    UnknownHashFunction(u8),
+    /// The path is longer than [`MAX_DEPTH`] or the index does not fit its length
+    InvalidPath,
+}
+
+impl fmt::Display for InclusionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InclusionError::UnknownHashFunction(id) => write!(f, "unknown hash function {}", id),
+            InclusionError::InvalidPath => write!(f, "leaf index does not fit a path of its length"),
+        }
+    }
+}
+
+/// Inner node over two children
+pub fn node(hash_function: u8, left: &[u8; 32], right: &[u8; 32]) -> Result<[u8; 32], InclusionError> {
+    use sha2::Digest;
+
+    Ok(match hash_function {
+        SHA256 => sha2::Sha256::new().chain_update(left).chain_update(right).finalize().into(),
+        KECCAK256 => sha3::Keccak256::new().chain_update(left).chain_update(right).finalize().into(),
+        BLAKE2B => blake2::Blake2b::<blake2::digest::consts::U32>::new()
+            .chain_update(left)
+            .chain_update(right)
+            .finalize()
+            .into(),
+        BLAKE3 => *blake3::Hasher::new().update(left).update(right).finalize().as_bytes(),
+        id => return Err(InclusionError::UnknownHashFunction(id)),
+    })
+}
+
+/// Root of the tree the witness's leaf is included in
+pub fn verify(witness: &MerkleWitness) -> Result<MerkleInclusion, InclusionError> {
+    if !matches!(witness.hash_function, SHA256 | KECCAK256 | BLAKE2B | BLAKE3) {
+        return Err(InclusionError::UnknownHashFunction(witness.hash_function));
+    }
+    let depth = witness.path.len();
+    if depth > MAX_DEPTH || (depth < MAX_DEPTH && witness.index >> depth != 0) {
+        return Err(InclusionError::InvalidPath);
+    }
+    let mut hash = witness.leaf;
+    for (height, sibling) in witness.path.iter().enumerate() {
+        hash = if witness.index >> height & 1 == 1 {
+            node(witness.hash_function, sibling, &hash)?
+        } else {
+            node(witness.hash_function, &hash, sibling)?
+        };
+    }
+    Ok(MerkleInclusion {
+        root: hash,
+        leaf: witness.leaf,
+        index: witness.index,
+        depth: depth as u8,
+        hash_function: witness.hash_function,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    /// Levels of the tree over `leaves`, from the leaves up to the root
+    fn levels(hash_function: u8, leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let level = levels.last().unwrap()
+                .chunks(2)
+                .map(|pair| node(hash_function, &pair[0], &pair[1]).unwrap())
+                .collect();
+            levels.push(level);
+        }
+        levels
+    }
+
+    #[test]
+    fn test_inclusion() {
+        for hash_function in [SHA256, KECCAK256, BLAKE2B, BLAKE3] {
+            let leaves: Vec<[u8; 32]> = (0..8u8).map(|i| [i; 32]).collect();
+            let levels = levels(hash_function, leaves.clone());
+            let root = levels[3][0];
+            for (index, leaf) in leaves.iter().enumerate() {
+                let path = (0..3).map(|height| levels[height][(index >> height) ^ 1]).collect();
+                let witness = MerkleWitness { hash_function, leaf: *leaf, index: index as u64, path };
+                let statement = verify(&witness).unwrap();
+                assert_eq!(statement.root, root);
+                assert_eq!(statement.depth, 3);
+                assert_eq!(MerkleInclusion::from_bytes(&statement.to_bytes()), Some(statement));
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_witness() {
+        let mut witness = MerkleWitness { hash_function: SHA256, leaf: [1; 32], index: 4, path: vec![[2; 32]; 2] };
+        assert_eq!(verify(&witness), Err(InclusionError::InvalidPath));
+        witness.index = 3;
+        witness.path = vec![[2; 32]; MAX_DEPTH + 1];
+        assert_eq!(verify(&witness), Err(InclusionError::InvalidPath));
+        witness.path.clear();
+        witness.hash_function = 9;
+        assert_eq!(verify(&witness), Err(InclusionError::UnknownHashFunction(9)));
+    }
+}
@@ -0,0 +1,167 @@
+//! Backend version information
+//!
+//! Proofs are only readable by a prover SDK with the same major version as the one
+//! that produced them. [`BackendInfo`] records the exact SDK, guest toolchain and
+//! circuit versions a backend was built with, and [`BackendInfo::compatibility_check`]
+//! rejects enveloped proofs from an incompatible SDK before they reach the
+//! deserializer. [`BackendInfo::check_metadata`] does the same for proofs whose
+//! metadata is at hand.
+
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+
+use crate::envelope::ProofEnvelope;
+use crate::error::ZkError;
+use crate::metadata::ExtendedProofMetadata;
+use crate::profiles::{
+    BEACON_VERIFY, BLOCK_VERIFY, DOT_VERIFY, ECDSA_VERIFY, EIP712_VERIFY, ETH_HEADER_CHAIN, ETH_HEADER_VERIFY, ETH_LOG_VERIFY, ETH_VERIFY, MERKLE_INCLUSION, MESSAGE_AGGREGATE, MESSAGE_COMPOSE, MESSAGE_EVENT, MESSAGE_VERIFY, POSEIDON_COMMIT, SOL_VERIFY, STATE_PROOF, TM_VERIFY, TX_VERIFY,
+};
+
+/// Resolved `sp1-sdk` version
+pub const SP1_SDK_VERSION: &str = env!("FROSTGATE_SP1_SDK_VERSION");
+/// Resolved `risc0-zkvm` version
+pub const RISC0_SDK_VERSION: &str = env!("FROSTGATE_RISC0_SDK_VERSION");
+/// SP1 guest toolchain version used to build the circuits
+pub const SP1_TOOLCHAIN_VERSION: &str = env!("FROSTGATE_SP1_TOOLCHAIN");
+/// RISC0 guest toolchain version used to build the circuits
+pub const RISC0_TOOLCHAIN_VERSION: &str = env!("FROSTGATE_RISC0_TOOLCHAIN");
+
+/// Version of the built-in circuits
+pub const CIRCUIT_VERSION: u32 = 1;
+
+/// Version information of a backend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackendInfo {
+    /// Backend identifier
+    pub backend: String,
+    /// Exact prover SDK version
+    pub sdk_version: String,
+    /// Guest toolchain version
+    pub guest_toolchain: String,
+    /// Circuit versions keyed by circuit type identifier
+    pub circuit_versions: BTreeMap<u8, u32>,
+}
+
+impl BackendInfo {
+    /// Version information of the SP1 backend
+    pub fn sp1() -> Self {
+        let mut info = Self::new("sp1", SP1_SDK_VERSION, SP1_TOOLCHAIN_VERSION);
+        // The chain and signature verifiers, Poseidon commitments, message events and
+        // Merkle inclusion only have SP1 guests
+        for circuit_type in [ETH_VERIFY, ETH_LOG_VERIFY, ETH_HEADER_CHAIN, BEACON_VERIFY, DOT_VERIFY, SOL_VERIFY, TM_VERIFY, STATE_PROOF, EIP712_VERIFY, POSEIDON_COMMIT, MESSAGE_EVENT, MERKLE_INCLUSION] {
+            info.circuit_versions.insert(circuit_type, CIRCUIT_VERSION);
+        }
+        info
+    }
+
+    /// Version information of the RISC0 backend
+    pub fn risc0() -> Self {
+        let mut info = Self::new("risc0", RISC0_SDK_VERSION, RISC0_TOOLCHAIN_VERSION);
+        // The Ethereum header verifier only has a RISC0 guest
+        info.circuit_versions.insert(ETH_HEADER_VERIFY, CIRCUIT_VERSION);
+        info
+    }
+
+    fn new(backend: &str, sdk_version: &str, guest_toolchain: &str) -> Self {
+        let circuit_versions = [MESSAGE_VERIFY, TX_VERIFY, BLOCK_VERIFY, MESSAGE_AGGREGATE, MESSAGE_COMPOSE, ECDSA_VERIFY]
+            .iter()
+            .map(|circuit_type| (*circuit_type, CIRCUIT_VERSION))
+            .collect();
+        Self {
+            backend: backend.to_string(),
+            sdk_version: sdk_version.to_string(),
+            guest_toolchain: guest_toolchain.to_string(),
+            circuit_versions,
+        }
+    }
+
+    /// Compatibility-relevant part of the SDK version
+    pub fn sdk_major(&self) -> &str {
+        sdk_major(&self.sdk_version)
+    }
+
+    /// Version of a circuit type, if the backend knows it
+    pub fn circuit_version(&self, circuit_type: u8) -> Option<u32> {
+        self.circuit_versions.get(&circuit_type).copied()
+    }
+
+    /// Check that an enveloped proof was produced by a compatible backend
+    pub fn compatibility_check(&self, envelope: &ProofEnvelope) -> Result<(), ZkError> {
+        self.check_sdk(&envelope.backend_id, &envelope.sdk_version)
+    }
+
+    /// Check that the proof described by `metadata` was produced by a compatible
+    /// backend and circuit version
+    pub fn check_metadata(&self, metadata: &ExtendedProofMetadata) -> Result<(), ZkError> {
+        self.check_sdk(&metadata.backend, &metadata.sdk_version)?;
+
+        if let (Some(circuit_type), Some(proof_version)) = (metadata.circuit_type, metadata.circuit_version) {
+            if let Some(backend_version) = self.circuit_version(circuit_type) {
+                if backend_version != proof_version {
+                    return Err(ZkError::IncompatibleCircuit {
+                        circuit_type,
+                        proof_version,
+                        backend_version,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_sdk(&self, backend: &str, sdk_version: &str) -> Result<(), ZkError> {
+        if backend != self.backend || sdk_major(sdk_version) != self.sdk_major() {
+            return Err(ZkError::IncompatibleSdk {
+                backend: backend.to_string(),
+                proof_version: sdk_version.to_string(),
+                backend_version: format!("{} {}", self.backend, self.sdk_version),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Compatibility-relevant part of a semver version
+///
+/// For `0.x` releases the minor version is the breaking component.
+fn sdk_major(version: &str) -> &str {
+    let mut parts = version.splitn(3, '.');
+    match (parts.next(), parts.next()) {
+        (Some("0"), Some(minor)) => &version[..2 + minor.len()],
+        (Some(major), _) => major,
+        _ => version,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sdk_major() {
+        assert_eq!(sdk_major("5.0.2"), "5");
+        assert_eq!(sdk_major("0.19.1"), "0.19");
+        assert_eq!(sdk_major("1"), "1");
+    }
+
+    #[test]
+    fn test_envelope_compatibility() {
+        let info = BackendInfo {
+            backend: "risc0".to_string(),
+            sdk_version: "0.19.1".to_string(),
+            guest_toolchain: "1.0".to_string(),
+            circuit_versions: BTreeMap::new(),
+        };
+        let envelope = |backend: &str, sdk_version: &str| {
+            ProofEnvelope::new(backend, sdk_version, crate::metadata::ProofMode::Core, hex::encode([1u8; 32]), Vec::new(), vec![1])
+        };
+
+        assert!(info.compatibility_check(&envelope("risc0", "0.19.0")).is_ok());
+        assert!(matches!(
+            info.compatibility_check(&envelope("risc0", "0.20.0")),
+            Err(ZkError::IncompatibleSdk { .. })
+        ));
+        assert!(info.compatibility_check(&envelope("sp1", "0.19.1")).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! ink! verifier contract generation for Substrate chains
+//!
+//! Polkadot-side deployments verify wrapped SP1 proofs in an ink! contract. The
+//! [`InkVerifierExport`] generates that contract for a set of programs: the Groth16
+//! or PLONK verifying key and the program key hashes are embedded as constants, and
+//! verification itself uses the `no_std` build of `sp1-verifier`. The same check is
+//! also emitted as a plain function for pallets via [`InkVerifierExport::pallet_source`].
+
+use std::fmt::Write as _;
+use std::path::Path;
+use sp1_verifier::{GROTH16_VK_BYTES, PLONK_VK_BYTES};
+
+use crate::domain::ChainId;
+use crate::error::ZkError;
+use crate::metadata::ProofMode;
+use crate::vk::{ExportedKey, KeyKind};
+
+/// Version of `ink` the generated contract depends on
+const INK_VERSION: &str = "5.0";
+
+/// Version of `sp1-verifier` the generated contract depends on
+const SP1_VERIFIER_VERSION: &str = "5.0.0";
+
+/// Generator of an ink! verifier contract
+#[derive(Debug, Clone)]
+pub struct InkVerifierExport {
+    /// Contract crate name
+    name: String,
+    /// Wrapped proof mode the contract verifies
+    mode: ProofMode,
+    /// Program names and verifying key hashes accepted by the contract
+    programs: Vec<(String, [u8; 32])>,
+    /// Chain proofs must be bound to, if any
+    destination: Option<ChainId>,
+}
+
+impl InkVerifierExport {
+    /// Create an export verifying proofs in `mode`, which must be Groth16 or PLONK
+    pub fn new(name: impl Into<String>, mode: ProofMode) -> Result<Self, ZkError> {
+        if !mode.is_onchain_verifiable() {
+            return Err(ZkError::UnsupportedVerifier {
+                chain: "substrate".to_string(),
+                mode,
+            });
+        }
+        Ok(Self {
+            name: name.into(),
+            mode,
+            programs: Vec::new(),
+            destination: None,
+        })
+    }
+
+    /// Accept proofs of the program the SP1 verifying key was exported for
+    pub fn with_program(mut self, name: impl Into<String>, key: &ExportedKey) -> Result<Self, ZkError> {
+        if key.kind != KeyKind::Sp1VerifyingKey {
+            return Err(ZkError::InvalidInput("ink! verifiers require an SP1 verifying key".into()));
+        }
+        self.programs.push((name.into(), key.key_hash));
+        Ok(self)
+    }
+
+    /// Only accept proofs bound to `destination`
+    pub fn with_destination(mut self, destination: ChainId) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    /// Source of the contract's `lib.rs`
+    pub fn contract_source(&self) -> String {
+        let mut source = String::new();
+        source.push_str("//! Frostgate proof verifier generated by frostgate-circuits\n\n");
+        source.push_str("#![cfg_attr(not(feature = \"std\"), no_std, no_main)]\n\n");
+        source.push_str("#[ink::contract]\n");
+        let _ = writeln!(source, "mod {} {{", self.module_name());
+        source.push_str("    use ink::prelude::vec::Vec;\n\n");
+        for line in self.verifier_items().lines() {
+            if line.is_empty() {
+                source.push('\n');
+            } else {
+                let _ = writeln!(source, "    {}", line);
+            }
+        }
+        source.push_str(
+            "\n    #[ink(storage)]\n\
+             \x20   pub struct Verifier {}\n\n\
+             \x20   impl Verifier {\n\
+             \x20       #[ink(constructor)]\n\
+             \x20       pub fn new() -> Self {\n\
+             \x20           Self {}\n\
+             \x20       }\n\n\
+             \x20       /// Verify a wrapped proof of a registered program\n\
+             \x20       #[ink(message)]\n\
+             \x20       pub fn verify(&self, vkey_hash: [u8; 32], proof: Vec<u8>, public_values: Vec<u8>) -> bool {\n\
+             \x20           verify_proof(&vkey_hash, &proof, &public_values)\n\
+             \x20       }\n\
+             \x20   }\n",
+        );
+        source.push_str("}\n");
+        source
+    }
+
+    /// Source of a `no_std` module exposing the check to pallets
+    pub fn pallet_source(&self) -> String {
+        let mut source = String::new();
+        source.push_str("//! Frostgate proof verification generated by frostgate-circuits\n\n");
+        source.push_str(&self.verifier_items());
+        source
+    }
+
+    /// Contract `Cargo.toml`
+    pub fn cargo_manifest(&self) -> String {
+        format!(
+            "[package]\n\
+             name = \"{name}\"\n\
+             version = \"0.1.0\"\n\
+             edition = \"2021\"\n\n\
+             [dependencies]\n\
+             ink = {{ version = \"{ink}\", default-features = false }}\n\
+             sp1-verifier = {{ version = \"{sp1}\", default-features = false }}\n\n\
+             [lib]\n\
+             path = \"lib.rs\"\n\n\
+             [features]\n\
+             default = [\"std\"]\n\
+             std = [\"ink/std\"]\n\
+             ink-as-dependency = []\n",
+            name = self.name,
+            ink = INK_VERSION,
+            sp1 = SP1_VERIFIER_VERSION,
+        )
+    }
+
+    /// Write the contract crate into `dir`
+    pub fn write(&self, dir: impl AsRef<Path>) -> Result<(), ZkError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join("Cargo.toml"), self.cargo_manifest())?;
+        std::fs::write(dir.join("lib.rs"), self.contract_source())?;
+        Ok(())
+    }
+
+    fn module_name(&self) -> String {
+        self.name.replace('-', "_")
+    }
+
+    /// Constants and the verification function shared by contract and pallet
+    fn verifier_items(&self) -> String {
+        let (verifier, vk_bytes) = match self.mode {
+            ProofMode::Plonk => ("PlonkVerifier", &PLONK_VK_BYTES[..]),
+            _ => ("Groth16Verifier", &GROTH16_VK_BYTES[..]),
+        };
+
+        let mut items = String::new();
+        let _ = writeln!(items, "use sp1_verifier::{};\n", verifier);
+        let _ = writeln!(items, "/// {:?} verifying key over BN254", self.mode);
+        let _ = writeln!(items, "pub const VERIFYING_KEY: [u8; {}] = {};\n", vk_bytes.len(), byte_array(vk_bytes));
+        items.push_str("/// Verifying key hashes of the accepted programs\n");
+        let _ = writeln!(items, "pub const PROGRAMS: [[u8; 32]; {}] = [", self.programs.len());
+        for (name, hash) in &self.programs {
+            let _ = writeln!(items, "    // {}", name);
+            let _ = writeln!(items, "    {},", byte_array(hash));
+        }
+        items.push_str("];\n\n");
+
+        items.push_str("/// Verify a wrapped proof of a registered program\n");
+        items.push_str("pub fn verify_proof(vkey_hash: &[u8; 32], proof: &[u8], public_values: &[u8]) -> bool {\n");
+        items.push_str("    if !PROGRAMS.contains(vkey_hash) {\n        return false;\n    }\n");
+        if let Some(destination) = self.destination {
+            items.push_str("    // The destination chain is the last 8 bytes of the committed domain\n");
+            items.push_str("    let Some(start) = public_values.len().checked_sub(8) else {\n        return false;\n    };\n");
+            let _ = writeln!(
+                items,
+                "    if public_values[start..] != {}u64.to_le_bytes() {{\n        return false;\n    }}",
+                destination.0
+            );
+        }
+        items.push_str("    let mut hash = [0u8; 66];\n");
+        items.push_str("    hash[..2].copy_from_slice(b\"0x\");\n");
+        items.push_str("    for (i, byte) in vkey_hash.iter().enumerate() {\n");
+        items.push_str("        hash[2 + 2 * i] = HEX[(byte >> 4) as usize];\n");
+        items.push_str("        hash[3 + 2 * i] = HEX[(byte & 0xf) as usize];\n");
+        items.push_str("    }\n");
+        items.push_str("    let hash = core::str::from_utf8(&hash).expect(\"hex is ASCII\");\n");
+        let _ = writeln!(items, "    {}::verify(proof, public_values, hash, &VERIFYING_KEY).is_ok()", verifier);
+        items.push_str("}\n\n");
+        items.push_str("const HEX: &[u8; 16] = b\"0123456789abcdef\";\n");
+        items
+    }
+}
+
+/// Format bytes as a Rust array literal
+pub(crate) fn byte_array(bytes: &[u8]) -> String {
+    let bytes: Vec<String> = bytes.iter().map(|byte| format!("0x{:02x}", byte)).collect();
+    format!("[{}]", bytes.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contract_embeds_keys() {
+        let key = ExportedKey::new(KeyKind::Sp1VerifyingKey, b"program", crate::hasher::HashFunction::Sha256, [0xAB; 32], vec![1, 2, 3]);
+        let export = InkVerifierExport::new("frostgate-verifier", ProofMode::Groth16)
+            .unwrap()
+            .with_program("message_verify", &key)
+            .unwrap()
+            .with_destination(ChainId(1000));
+
+        let source = export.contract_source();
+        assert!(source.contains("mod frostgate_verifier {"));
+        assert!(source.contains("// message_verify"));
+        assert!(source.contains(&byte_array(&[0xAB; 32])));
+        assert!(source.contains(&format!("pub const VERIFYING_KEY: [u8; {}]", GROTH16_VK_BYTES.len())));
+        assert!(source.contains("1000u64.to_le_bytes()"));
+        assert!(export.pallet_source().contains("Groth16Verifier::verify"));
+        assert!(export.cargo_manifest().contains("name = \"frostgate-verifier\""));
+    }
+
+    #[test]
+    fn test_rejects_unwrapped_modes_and_risc0_keys() {
+        assert!(InkVerifierExport::new("verifier", ProofMode::Compressed).is_err());
+
+        let key = ExportedKey::new(KeyKind::Risc0ImageId, b"program", crate::hasher::HashFunction::Sha256, [0; 32], vec![0; 32]);
+        let export = InkVerifierExport::new("verifier", ProofMode::Plonk).unwrap();
+        assert!(export.with_program("message_verify", &key).is_err());
+    }
+}
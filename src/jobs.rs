@@ -0,0 +1,461 @@
+//! Asynchronous proving jobs
+//!
+//! Relayers submit many proofs that each take minutes. The [`JobManager`] runs them
+//! in the background: [`JobManager::submit_prove`] returns a [`JobId`] right away and
+//! [`JobManager::job_status`] reports how far the job got, so callers poll instead of
+//! holding a future open for the whole proving time. Finished jobs are kept until
+//! they are removed with [`JobManager::remove`].
+//!
+//! Orchestrators that track many jobs can instead be notified when a job finishes
+//! by submitting it with a [`JobCallback`], either on a channel or, with the
+//! `webhooks` feature, by an HTTP `POST` of a [`JobNotification`] to a URL.
+//!
+//! Each job runs in a `job` span carrying its ID, so the proving spans the backend
+//! opens for it can be told apart from those of other jobs.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use parking_lot::RwLock;
+use serde::Serialize;
+use tokio::sync::{mpsc, Semaphore};
+use tracing::Instrument;
+use uuid::Uuid;
+use frostgate_zkip::{ProofMetadata, ZkBackend};
+
+use crate::error::ZkError;
+
+/// Identifier of a submitted job
+pub type JobId = Uuid;
+
+/// Stage a job is in
+#[derive(Debug, Clone)]
+pub enum JobStatus {
+    /// Waiting for a proving slot
+    Queued,
+    /// Being proven
+    Running,
+    /// Proven successfully
+    Completed {
+        /// Serialized proof
+        proof: Vec<u8>,
+        /// Proof metadata
+        metadata: ProofMetadata,
+    },
+    /// Proving failed
+    Failed(String),
+}
+
+impl JobStatus {
+    /// Whether the job completed or failed
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Completed { .. } | JobStatus::Failed(_))
+    }
+}
+
+/// Status of a job along with its timing
+#[derive(Debug, Clone)]
+pub struct JobReport {
+    /// Current stage
+    pub status: JobStatus,
+    /// Time the job was submitted
+    pub submitted: SystemTime,
+    /// Time proving started
+    pub started: Option<SystemTime>,
+    /// Time the job completed or failed
+    pub finished: Option<SystemTime>,
+}
+
+impl JobReport {
+    /// Time spent waiting for a proving slot so far
+    pub fn queue_time(&self) -> Duration {
+        let until = self.started.unwrap_or_else(SystemTime::now);
+        until.duration_since(self.submitted).unwrap_or_default()
+    }
+
+    /// Time spent proving so far
+    pub fn proving_time(&self) -> Option<Duration> {
+        let started = self.started?;
+        let until = self.finished.unwrap_or_else(SystemTime::now);
+        Some(until.duration_since(started).unwrap_or_default())
+    }
+}
+
+/// Where to deliver the notification that a job finished
+#[derive(Debug, Clone)]
+pub enum JobCallback {
+    /// Send the job's final report on a channel
+    Channel(mpsc::UnboundedSender<(JobId, JobReport)>),
+    /// `POST` a [`JobNotification`] as JSON to a URL
+    #[cfg(feature = "webhooks")]
+    Webhook(String),
+}
+
+/// Payload of a completion webhook
+#[derive(Debug, Clone, Serialize)]
+pub struct JobNotification {
+    /// Job that finished
+    pub job_id: JobId,
+    /// `completed`, `failed`, or `pending` for a job that has not finished
+    pub status: String,
+    /// Time spent waiting for a proving slot in milliseconds
+    pub queue_time_ms: u64,
+    /// Time spent proving in milliseconds
+    pub proving_time_ms: Option<u64>,
+    /// Hex-encoded proof, if completed
+    pub proof: Option<String>,
+    /// Size of the proof in bytes, if completed
+    pub proof_size: Option<usize>,
+    /// Hex-encoded program hash, if completed
+    pub program_hash: Option<String>,
+    /// Time the backend spent generating the proof in milliseconds, if completed
+    pub generation_time_ms: Option<u64>,
+    /// Error message, if failed
+    pub error: Option<String>,
+}
+
+impl JobNotification {
+    /// Notification for a finished job
+    pub fn new(job_id: JobId, report: &JobReport) -> Self {
+        let mut notification = Self {
+            job_id,
+            status: "failed".to_string(),
+            queue_time_ms: report.queue_time().as_millis() as u64,
+            proving_time_ms: report.proving_time().map(|time| time.as_millis() as u64),
+            proof: None,
+            proof_size: None,
+            program_hash: None,
+            generation_time_ms: None,
+            error: None,
+        };
+        match &report.status {
+            JobStatus::Completed { proof, metadata } => {
+                notification.status = "completed".to_string();
+                notification.proof = Some(hex::encode(proof));
+                notification.proof_size = Some(metadata.proof_size);
+                notification.program_hash = Some(metadata.program_hash.clone());
+                notification.generation_time_ms = Some(metadata.generation_time.as_millis() as u64);
+            }
+            JobStatus::Failed(error) => notification.error = Some(error.clone()),
+            JobStatus::Queued | JobStatus::Running => {
+                notification.status = "pending".to_string();
+            }
+        }
+        notification
+    }
+}
+
+/// Runs proving jobs in the background with bounded concurrency
+///
+/// Jobs are spawned on the current tokio runtime.
+pub struct JobManager {
+    backend: Arc<dyn ZkBackend>,
+    permits: Arc<Semaphore>,
+    jobs: Arc<RwLock<HashMap<JobId, JobReport>>>,
+}
+
+impl JobManager {
+    /// Create a manager proving at most `max_concurrent` jobs at once
+    pub fn new(backend: Arc<dyn ZkBackend>, max_concurrent: usize) -> Self {
+        Self {
+            backend,
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Queue a proof of `input` against `program`, returning without waiting for it
+    pub fn submit_prove(&self, program: Vec<u8>, input: Vec<u8>) -> JobId {
+        self.spawn(program, input, None)
+    }
+
+    /// Queue a proof like [`Self::submit_prove`] and notify `callback` once it finishes
+    pub fn submit_prove_with_callback(&self, program: Vec<u8>, input: Vec<u8>, callback: JobCallback) -> JobId {
+        self.spawn(program, input, Some(callback))
+    }
+
+    fn spawn(&self, program: Vec<u8>, input: Vec<u8>, callback: Option<JobCallback>) -> JobId {
+        let id = Uuid::new_v4();
+        self.jobs.write().insert(id, JobReport {
+            status: JobStatus::Queued,
+            submitted: SystemTime::now(),
+            started: None,
+            finished: None,
+        });
+
+        let backend = self.backend.clone();
+        let permits = self.permits.clone();
+        let jobs = self.jobs.clone();
+        tokio::spawn(async move {
+            let permit = permits.acquire_owned().await;
+            update(&jobs, id, |report| {
+                report.status = JobStatus::Running;
+                report.started = Some(SystemTime::now());
+            });
+
+            let status = match permit {
+                Ok(_permit) => match backend.prove(&program, &input, None).await {
+                    Ok((proof, metadata)) => JobStatus::Completed { proof, metadata },
+                    Err(e) => JobStatus::Failed(ZkError::from(e).to_string()),
+                },
+                Err(e) => JobStatus::Failed(format!("Job manager closed: {}", e)),
+            };
+            if let JobStatus::Failed(message) = &status {
+                tracing::warn!(job_id = %id, "proving job failed: {}", message);
+            }
+            update(&jobs, id, |report| {
+                report.status = status;
+                report.finished = Some(SystemTime::now());
+            });
+
+            let report = jobs.read().get(&id).cloned();
+            if let (Some(callback), Some(report)) = (callback, report) {
+                notify(callback, id, report).await;
+            }
+        }.instrument(tracing::info_span!("job", job_id = %id)));
+        id
+    }
+
+    /// Status of a job, if it is known
+    pub fn job_status(&self, id: JobId) -> Option<JobReport> {
+        self.jobs.read().get(&id).cloned()
+    }
+
+    /// Remove a finished job, returning its final report
+    ///
+    /// Queued and running jobs are left in place.
+    pub fn remove(&self, id: JobId) -> Option<JobReport> {
+        let mut jobs = self.jobs.write();
+        if !jobs.get(&id)?.status.is_finished() {
+            return None;
+        }
+        jobs.remove(&id)
+    }
+
+    /// Notifications of the `limit` most recently failed jobs, newest first
+    pub fn failed_jobs(&self, limit: usize) -> Vec<JobNotification> {
+        let jobs = self.jobs.read();
+        let mut failed: Vec<_> = jobs.iter()
+            .filter(|(_, report)| matches!(report.status, JobStatus::Failed(_)))
+            .collect();
+        failed.sort_by_key(|(_, report)| std::cmp::Reverse(report.finished));
+        failed.into_iter()
+            .take(limit)
+            .map(|(id, report)| JobNotification::new(*id, report))
+            .collect()
+    }
+
+    /// Number of jobs that have not finished yet
+    pub fn pending(&self) -> usize {
+        self.jobs.read().values().filter(|report| !report.status.is_finished()).count()
+    }
+}
+
+impl std::fmt::Debug for JobManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobManager")
+            .field("available_permits", &self.permits.available_permits())
+            .field("jobs", &self.jobs.read().len())
+            .finish()
+    }
+}
+
+fn update(jobs: &RwLock<HashMap<JobId, JobReport>>, id: JobId, f: impl FnOnce(&mut JobReport)) {
+    if let Some(report) = jobs.write().get_mut(&id) {
+        f(report);
+    }
+}
+
+/// Deliver a job's completion notification, logging failures
+async fn notify(callback: JobCallback, id: JobId, report: JobReport) {
+    match callback {
+        JobCallback::Channel(sender) => {
+            // The receiver going away only means nobody is listening any more
+            let _ = sender.send((id, report));
+        }
+        #[cfg(feature = "webhooks")]
+        JobCallback::Webhook(url) => {
+            const ATTEMPTS: u32 = 3;
+            if let Err(e) = check_webhook_url(&url) {
+                tracing::warn!(job_id = %id, "job webhook to {} refused: {}", url, e);
+                return;
+            }
+            let notification = JobNotification::new(id, &report);
+            for attempt in 1..=ATTEMPTS {
+                let result = webhook_client().post(&url)
+                    .json(&notification)
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|response| match response.status() {
+                        // Redirects are not followed, so only a 2xx counts as delivered
+                        status if status.is_success() => Ok(()),
+                        status => Err(format!("HTTP status {}", status)),
+                    });
+                match result {
+                    Ok(()) => return,
+                    Err(e) if attempt < ATTEMPTS => {
+                        tracing::warn!(job_id = %id, attempt, "job webhook failed, retrying: {}", e);
+                        tokio::time::sleep(Duration::from_secs(1) * attempt).await;
+                    }
+                    Err(e) => tracing::warn!(job_id = %id, "job webhook to {} failed: {}", url, e),
+                }
+            }
+        }
+    }
+}
+
+/// How long a single webhook delivery may take
+#[cfg(feature = "webhooks")]
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Client shared by all webhook deliveries
+///
+/// Redirects are not followed, so a callback URL that passed the host checks
+/// cannot send the notification on to another host, and host names are only
+/// connected to when every address they resolve to is public.
+#[cfg(feature = "webhooks")]
+fn webhook_client() -> &'static reqwest::Client {
+    static CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(WEBHOOK_TIMEOUT)
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .expect("webhook client configuration is valid")
+    })
+}
+
+/// Refuse webhook URLs naming a non-public IP address directly
+///
+/// Host names are checked by [`PublicResolver`] when the request is sent.
+#[cfg(feature = "webhooks")]
+fn check_webhook_url(url: &str) -> Result<(), String> {
+    let url = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+    let host = url.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']');
+    match host.parse() {
+        Ok(ip) if !public_address(ip) => Err(format!("{} is not a public address", ip)),
+        _ => Ok(()),
+    }
+}
+
+/// Resolver refusing names that resolve to any non-public address
+///
+/// Checking the addresses that are actually connected to, rather than the host
+/// name when the callback is submitted, keeps a name from being rebound to an
+/// internal address between the check and the delivery.
+#[cfg(feature = "webhooks")]
+struct PublicResolver;
+
+#[cfg(feature = "webhooks")]
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: hyper::client::connect::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if let Some(addr) = addrs.iter().find(|addr| !public_address(addr.ip())) {
+                return Err(format!("{} resolves to non-public address {}", name.as_str(), addr.ip()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Whether `ip` is neither loopback, private, link-local nor unspecified
+#[cfg(feature = "webhooks")]
+pub(crate) fn public_address(ip: std::net::IpAddr) -> bool {
+    use std::net::IpAddr;
+
+    match ip {
+        IpAddr::V4(ip) => !(ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified() || ip.is_broadcast()),
+        IpAddr::V6(ip) => {
+            let unique_local = ip.segments()[0] & 0xfe00 == 0xfc00;
+            let link_local = ip.segments()[0] & 0xffc0 == 0xfe80;
+            let mapped_private = ip.to_ipv4_mapped().is_some_and(|ip| !public_address(IpAddr::V4(ip)));
+            !(ip.is_loopback() || ip.is_unspecified() || unique_local || link_local || mapped_private)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::mock::MockBackend;
+
+    async fn wait_finished(manager: &JobManager, id: JobId) -> JobReport {
+        loop {
+            let report = manager.job_status(id).unwrap();
+            if report.status.is_finished() {
+                return report;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_poll() {
+        let backend = Arc::new(MockBackend::new().with_latency(Duration::from_millis(50)));
+        let manager = JobManager::new(backend, 1);
+
+        let first = manager.submit_prove(vec![1], vec![2]);
+        let second = manager.submit_prove(vec![3], vec![4]);
+        assert_eq!(manager.pending(), 2);
+        assert!(!manager.job_status(second).unwrap().status.is_finished());
+
+        let report = wait_finished(&manager, first).await;
+        match report.status {
+            JobStatus::Completed { proof, .. } => assert!(MockBackend::check(&[1], &proof)),
+            status => panic!("unexpected status {:?}", status),
+        }
+        assert!(report.proving_time().unwrap() >= Duration::from_millis(50));
+
+        wait_finished(&manager, second).await;
+        assert!(manager.remove(first).is_some());
+        assert!(manager.job_status(first).is_none());
+        assert_eq!(manager.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_job() {
+        let manager = JobManager::new(Arc::new(MockBackend::new().with_failure("prover down")), 2);
+        let id = manager.submit_prove(vec![1], vec![2]);
+        match wait_finished(&manager, id).await.status {
+            JobStatus::Failed(message) => assert!(message.contains("prover down")),
+            status => panic!("unexpected status {:?}", status),
+        }
+
+        let failed = manager.failed_jobs(10);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].job_id, id);
+        assert!(failed[0].error.as_ref().unwrap().contains("prover down"));
+    }
+
+    #[tokio::test]
+    async fn test_channel_callback() {
+        let manager = JobManager::new(Arc::new(MockBackend::new()), 1);
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let id = manager.submit_prove_with_callback(vec![1], vec![2], JobCallback::Channel(sender));
+
+        let (notified, report) = receiver.recv().await.unwrap();
+        assert_eq!(notified, id);
+        assert!(report.status.is_finished());
+
+        let notification = JobNotification::new(id, &report);
+        assert_eq!(notification.status, "completed");
+        assert_eq!(notification.proof, Some(hex::encode(MockBackend::proof(&[1], &[2]))));
+        assert!(notification.error.is_none());
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_webhook_refuses_internal_addresses() {
+        for url in ["http://127.0.0.1/hook", "http://[::ffff:10.0.0.1]/hook", "http://169.254.169.254/latest"] {
+            assert!(check_webhook_url(url).is_err(), "{}", url);
+        }
+        assert!(check_webhook_url("https://203.0.113.7/hook").is_ok());
+
+        // Names are checked against the addresses they resolve to
+        let error = webhook_client().post("http://localhost:9/hook").send().await.unwrap_err();
+        assert!(format!("{:?}", error).contains("non-public address"));
+    }
+}
@@ -0,0 +1,577 @@
+//! Typed decoding of the outputs committed by the guest programs
+//!
+//! Every guest commits its outputs in a fixed little-endian layout, followed by the
+//! [`DomainSeparator`] the proof is bound to:
+//!
+//! - message verification: hash (32), optionally a [`MessageNonce`] (16)
+//! - Poseidon commitments: hash (32)
+//! - transaction verification: hash (32), lengths of `from`, `to` and `value` (1 each)
+//! - block verification: hash (32), number (8), timestamp (8), gas used (8), gas limit (8)
+//! - message aggregation and composition: Merkle root (32), message count (8),
+//!   optionally the [`MessageNonce`] of the first message (16)
+//! - chain verification: [`ChainStatement`] (104)
+//! - state proofs: [`StorageStatement`] (116)
+//! - EIP-712 signatures: [`SignedMessage`] (84)
+//! - ECDSA signatures: [`VerifiedSignature`] (65)
+//! - message events: [`VerifiedEvent`] (88)
+//! - Merkle inclusion: [`MerkleInclusion`] (74)
+//!
+//! The decoders accept the same bytes whether they come from an SP1 proof's public
+//! values or a RISC0 journal. Outputs of guests that predate domain binding carry no
+//! domain; message nonces are only committed next to a domain. [`commitment`] hashes public values under the [`HashDomain`] of their
+//! circuit and origin chain, for relayers that sign or forward journals.
+
+use crate::chains::state::StorageStatement;
+use crate::chains::ChainStatement;
+use crate::domain::{DomainSeparator, HashDomain, MessageNonce};
+use crate::error::ZkError;
+use crate::inclusion::MerkleInclusion;
+use crate::message::VerifiedEvent;
+use crate::profiles::{
+    BEACON_VERIFY, BLOCK_VERIFY, DOT_VERIFY, ECDSA_VERIFY, EIP712_VERIFY, ETH_HEADER_CHAIN, ETH_HEADER_VERIFY, ETH_LOG_VERIFY, ETH_VERIFY, MERKLE_INCLUSION, MESSAGE_AGGREGATE, MESSAGE_COMPOSE,
+    MESSAGE_EVENT, MESSAGE_VERIFY, POSEIDON_COMMIT, SOL_VERIFY, STATE_PROOF, TM_VERIFY, TX_VERIFY,
+};
+use crate::signatures::ecdsa::VerifiedSignature;
+use crate::signatures::eip712::SignedMessage;
+
+/// Outputs of the message verification circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageVerifyOutput {
+    /// Hash of the message, its Poseidon commitment for Poseidon commitment proofs
+    pub hash: [u8; 32],
+    /// Source chain and nonce of the message
+    pub nonce: Option<MessageNonce>,
+    /// Domain the proof is bound to
+    pub domain: Option<DomainSeparator>,
+}
+
+impl MessageVerifyOutput {
+    /// Decode from public values
+    pub fn decode(bytes: &[u8]) -> Result<Self, ZkError> {
+        let mut reader = Reader::with_nonce(bytes, 32)?;
+        Ok(Self {
+            hash: reader.array(),
+            nonce: reader.nonce(),
+            domain: reader.domain()?,
+        })
+    }
+}
+
+/// Outputs of the transaction verification circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxVerifyOutput {
+    /// SHA-256 of the canonical transaction encoding
+    pub hash: [u8; 32],
+    /// Length of the `from` address
+    pub from_len: u8,
+    /// Length of the `to` address
+    pub to_len: u8,
+    /// Length of the value
+    pub value_len: u8,
+    /// Domain the proof is bound to
+    pub domain: Option<DomainSeparator>,
+}
+
+impl TxVerifyOutput {
+    /// Decode from public values
+    pub fn decode(bytes: &[u8]) -> Result<Self, ZkError> {
+        let mut reader = Reader::new(bytes, 35)?;
+        let hash = reader.array();
+        let [from_len, to_len, value_len] = reader.array();
+        Ok(Self {
+            hash,
+            from_len,
+            to_len,
+            value_len,
+            domain: reader.domain()?,
+        })
+    }
+}
+
+/// Outputs of the block verification and Ethereum header verification circuits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockVerifyOutput {
+    /// SHA-256 of the canonical header encoding, or the block hash for Ethereum
+    /// header verification
+    pub hash: [u8; 32],
+    /// Block number
+    pub number: u64,
+    /// Block timestamp in seconds
+    pub timestamp: u64,
+    /// Gas used by the block
+    pub gas_used: u64,
+    /// Gas limit of the block
+    pub gas_limit: u64,
+    /// Domain the proof is bound to
+    pub domain: Option<DomainSeparator>,
+}
+
+impl BlockVerifyOutput {
+    /// Decode from public values
+    pub fn decode(bytes: &[u8]) -> Result<Self, ZkError> {
+        let mut reader = Reader::new(bytes, 64)?;
+        Ok(Self {
+            hash: reader.array(),
+            number: reader.u64(),
+            timestamp: reader.u64(),
+            gas_used: reader.u64(),
+            gas_limit: reader.u64(),
+            domain: reader.domain()?,
+        })
+    }
+}
+
+/// Outputs of the message aggregation and composition circuits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageAggregateOutput {
+    /// Merkle root over the hashes of the aggregated messages
+    pub root: [u8; 32],
+    /// Number of aggregated messages
+    pub count: u64,
+    /// Source chain and nonce of the first message, the others following
+    /// consecutively
+    pub nonce: Option<MessageNonce>,
+    /// Domain the proof is bound to
+    pub domain: Option<DomainSeparator>,
+}
+
+impl MessageAggregateOutput {
+    /// Decode from public values
+    pub fn decode(bytes: &[u8]) -> Result<Self, ZkError> {
+        let mut reader = Reader::with_nonce(bytes, 40)?;
+        Ok(Self {
+            root: reader.array(),
+            count: reader.u64(),
+            nonce: reader.nonce(),
+            domain: reader.domain()?,
+        })
+    }
+}
+
+/// Outputs of the chain verification circuits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainVerifyOutput {
+    /// Statement proven about the chain
+    pub statement: ChainStatement,
+    /// Domain the proof is bound to
+    pub domain: Option<DomainSeparator>,
+}
+
+impl ChainVerifyOutput {
+    /// Decode from public values
+    pub fn decode(bytes: &[u8]) -> Result<Self, ZkError> {
+        let mut reader = Reader::new(bytes, ChainStatement::LEN)?;
+        let statement = ChainStatement::from_bytes(&reader.array::<{ ChainStatement::LEN }>())
+            .expect("statement length checked");
+        Ok(Self {
+            statement,
+            domain: reader.domain()?,
+        })
+    }
+}
+
+/// Outputs of the state proof circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateProofOutput {
+    /// Storage slot value proven under the state root
+    pub statement: StorageStatement,
+    /// Domain the proof is bound to
+    pub domain: Option<DomainSeparator>,
+}
+
+impl StateProofOutput {
+    /// Decode from public values
+    pub fn decode(bytes: &[u8]) -> Result<Self, ZkError> {
+        let mut reader = Reader::new(bytes, StorageStatement::LEN)?;
+        let statement = StorageStatement::from_bytes(&reader.array::<{ StorageStatement::LEN }>())
+            .expect("statement length checked");
+        Ok(Self {
+            statement,
+            domain: reader.domain()?,
+        })
+    }
+}
+
+/// Outputs of the EIP-712 signature circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedMessageOutput {
+    /// Message hash and the address that signed it
+    pub statement: SignedMessage,
+    /// Domain the proof is bound to
+    pub domain: Option<DomainSeparator>,
+}
+
+impl SignedMessageOutput {
+    /// Decode from public values
+    pub fn decode(bytes: &[u8]) -> Result<Self, ZkError> {
+        let mut reader = Reader::new(bytes, SignedMessage::LEN)?;
+        let statement = SignedMessage::from_bytes(&reader.array::<{ SignedMessage::LEN }>())
+            .expect("statement length checked");
+        Ok(Self {
+            statement,
+            domain: reader.domain()?,
+        })
+    }
+}
+
+/// Outputs of the ECDSA signature circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EcdsaVerifyOutput {
+    /// Message hash and the public key that signed it
+    pub statement: VerifiedSignature,
+    /// Domain the proof is bound to
+    pub domain: Option<DomainSeparator>,
+}
+
+impl EcdsaVerifyOutput {
+    /// Decode from public values
+    pub fn decode(bytes: &[u8]) -> Result<Self, ZkError> {
+        let mut reader = Reader::new(bytes, VerifiedSignature::LEN)?;
+        let statement = VerifiedSignature::from_bytes(&reader.array::<{ VerifiedSignature::LEN }>())
+            .expect("statement length checked");
+        Ok(Self {
+            statement,
+            domain: reader.domain()?,
+        })
+    }
+}
+
+/// Outputs of the message event circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageEventOutput {
+    /// Digest of the validated event and the event itself
+    pub statement: VerifiedEvent,
+    /// Domain the proof is bound to
+    pub domain: Option<DomainSeparator>,
+}
+
+impl MessageEventOutput {
+    /// Decode from public values
+    pub fn decode(bytes: &[u8]) -> Result<Self, ZkError> {
+        let mut reader = Reader::new(bytes, VerifiedEvent::LEN)?;
+        let statement = VerifiedEvent::from_bytes(&reader.array::<{ VerifiedEvent::LEN }>())
+            .expect("statement length checked");
+        Ok(Self {
+            statement,
+            domain: reader.domain()?,
+        })
+    }
+}
+
+/// Outputs of the Merkle inclusion circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MerkleInclusionOutput {
+    /// Included leaf, its index and the tree
+    pub statement: MerkleInclusion,
+    /// Domain the proof is bound to
+    pub domain: Option<DomainSeparator>,
+}
+
+impl MerkleInclusionOutput {
+    /// Decode from public values
+    pub fn decode(bytes: &[u8]) -> Result<Self, ZkError> {
+        let mut reader = Reader::new(bytes, MerkleInclusion::LEN)?;
+        let statement = MerkleInclusion::from_bytes(&reader.array::<{ MerkleInclusion::LEN }>())
+            .expect("statement length checked");
+        Ok(Self {
+            statement,
+            domain: reader.domain()?,
+        })
+    }
+}
+
+/// Decoded outputs of any circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitOutput {
+    /// Message verification outputs
+    Message(MessageVerifyOutput),
+    /// Transaction verification outputs
+    Tx(TxVerifyOutput),
+    /// Block verification outputs
+    Block(BlockVerifyOutput),
+    /// Message aggregation outputs
+    Aggregate(MessageAggregateOutput),
+    /// Chain verification outputs
+    Chain(ChainVerifyOutput),
+    /// State proof outputs
+    State(StateProofOutput),
+    /// EIP-712 signature outputs
+    Signed(SignedMessageOutput),
+    /// ECDSA signature outputs
+    Ecdsa(EcdsaVerifyOutput),
+    /// Message event outputs
+    Event(MessageEventOutput),
+    /// Merkle inclusion outputs
+    Inclusion(MerkleInclusionOutput),
+}
+
+impl CircuitOutput {
+    /// Decode the public values of a proof of `circuit_type`
+    pub fn decode(circuit_type: u8, bytes: &[u8]) -> Result<Self, ZkError> {
+        match circuit_type {
+            MESSAGE_VERIFY | POSEIDON_COMMIT => MessageVerifyOutput::decode(bytes).map(CircuitOutput::Message),
+            TX_VERIFY => TxVerifyOutput::decode(bytes).map(CircuitOutput::Tx),
+            BLOCK_VERIFY | ETH_HEADER_VERIFY => BlockVerifyOutput::decode(bytes).map(CircuitOutput::Block),
+            MESSAGE_AGGREGATE | MESSAGE_COMPOSE => MessageAggregateOutput::decode(bytes).map(CircuitOutput::Aggregate),
+            ETH_VERIFY | ETH_LOG_VERIFY | ETH_HEADER_CHAIN | BEACON_VERIFY | DOT_VERIFY | SOL_VERIFY | TM_VERIFY => {
+                ChainVerifyOutput::decode(bytes).map(CircuitOutput::Chain)
+            }
+            STATE_PROOF => StateProofOutput::decode(bytes).map(CircuitOutput::State),
+            EIP712_VERIFY => SignedMessageOutput::decode(bytes).map(CircuitOutput::Signed),
+            ECDSA_VERIFY => EcdsaVerifyOutput::decode(bytes).map(CircuitOutput::Ecdsa),
+            MESSAGE_EVENT => MessageEventOutput::decode(bytes).map(CircuitOutput::Event),
+            MERKLE_INCLUSION => MerkleInclusionOutput::decode(bytes).map(CircuitOutput::Inclusion),
+            _ => Err(ZkError::InvalidInput(format!("Unknown circuit type 0x{:02x}", circuit_type))),
+        }
+    }
+
+    /// Hash of the statement the guest verified, the Merkle root for aggregates, the
+    /// block hash for chain verification, the state root for state proofs, the
+    /// message hash for signatures, the event digest for message events and the
+    /// tree root for Merkle inclusion
+    pub fn hash(&self) -> [u8; 32] {
+        match self {
+            CircuitOutput::Message(output) => output.hash,
+            CircuitOutput::Tx(output) => output.hash,
+            CircuitOutput::Block(output) => output.hash,
+            CircuitOutput::Aggregate(output) => output.root,
+            CircuitOutput::Chain(output) => output.statement.block_hash,
+            CircuitOutput::State(output) => output.statement.state_root,
+            CircuitOutput::Signed(output) => output.statement.struct_hash,
+            CircuitOutput::Ecdsa(output) => output.statement.message_hash,
+            CircuitOutput::Event(output) => output.statement.digest,
+            CircuitOutput::Inclusion(output) => output.statement.root,
+        }
+    }
+
+    /// Domain the proof is bound to
+    pub fn domain(&self) -> Option<DomainSeparator> {
+        match self {
+            CircuitOutput::Message(output) => output.domain,
+            CircuitOutput::Tx(output) => output.domain,
+            CircuitOutput::Block(output) => output.domain,
+            CircuitOutput::Aggregate(output) => output.domain,
+            CircuitOutput::Chain(output) => output.domain,
+            CircuitOutput::State(output) => output.domain,
+            CircuitOutput::Signed(output) => output.domain,
+            CircuitOutput::Ecdsa(output) => output.domain,
+            CircuitOutput::Event(output) => output.domain,
+            CircuitOutput::Inclusion(output) => output.domain,
+        }
+    }
+}
+
+/// Hash domain of the public values of a proof of `circuit_type`, for the origin
+/// chain of the domain the proof is bound to
+pub fn hash_domain(circuit_type: u8, public_values: &[u8]) -> Result<HashDomain, ZkError> {
+    let domain = CircuitOutput::decode(circuit_type, public_values)?.domain().unwrap_or_default();
+    Ok(domain.hash_domain(circuit_type))
+}
+
+/// Domain-separated commitment to the public values of a proof of `circuit_type`
+///
+/// Equal public values of two circuits, or bound to two origin chains, have
+/// different commitments.
+pub fn commitment(circuit_type: u8, public_values: &[u8]) -> Result<[u8; 32], ZkError> {
+    Ok(crate::merkle::tagged_hash(&hash_domain(circuit_type, public_values)?, public_values))
+}
+
+/// Cursor over public values with a known fixed-size prefix
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    len: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], len: usize) -> Result<Self, ZkError> {
+        if bytes.len() != len && bytes.len() != len + DomainSeparator::LEN {
+            return Err(ZkError::ProofVerification(format!(
+                "Malformed public values: expected {} or {} bytes, got {}",
+                len,
+                len + DomainSeparator::LEN,
+                bytes.len()
+            )));
+        }
+        Ok(Self { bytes, offset: 0, len })
+    }
+
+    /// Reader of outputs followed by a [`MessageNonce`] if they carry a domain as well
+    fn with_nonce(bytes: &'a [u8], len: usize) -> Result<Self, ZkError> {
+        let with_nonce = len + MessageNonce::LEN;
+        Self::new(bytes, if bytes.len() == with_nonce + DomainSeparator::LEN { with_nonce } else { len })
+    }
+
+    /// Message nonce at the end of the fixed-size prefix, if there is room for one
+    fn nonce(&mut self) -> Option<MessageNonce> {
+        if self.len - self.offset != MessageNonce::LEN {
+            return None;
+        }
+        Some(MessageNonce::from_bytes(&self.array::<{ MessageNonce::LEN }>()).expect("nonce length checked"))
+    }
+
+    fn array<const N: usize>(&mut self) -> [u8; N] {
+        let mut array = [0u8; N];
+        array.copy_from_slice(&self.bytes[self.offset..self.offset + N]);
+        self.offset += N;
+        array
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.array())
+    }
+
+    fn domain(&self) -> Result<Option<DomainSeparator>, ZkError> {
+        debug_assert_eq!(self.offset, self.len);
+        if self.bytes.len() == self.len {
+            return Ok(None);
+        }
+        DomainSeparator::from_bytes(&self.bytes[self.len..])
+            .map(Some)
+            .ok_or_else(|| ZkError::ProofVerification("Malformed domain separator".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ChainId;
+
+    #[test]
+    fn test_block_output() {
+        let domain = DomainSeparator::new(ChainId(1), ChainId(137));
+        let mut bytes = vec![0xAA; 32];
+        for value in [0x1234u64, 1_640_000_000, 100, 200] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&domain.to_bytes());
+
+        let output = BlockVerifyOutput::decode(&bytes).unwrap();
+        assert_eq!(output.hash, [0xAA; 32]);
+        assert_eq!(output.number, 0x1234);
+        assert_eq!(output.timestamp, 1_640_000_000);
+        assert_eq!((output.gas_used, output.gas_limit), (100, 200));
+        assert_eq!(output.domain, Some(domain));
+        assert_eq!(CircuitOutput::decode(BLOCK_VERIFY, &bytes).unwrap(), CircuitOutput::Block(output));
+
+        // Without a domain and truncated
+        assert_eq!(BlockVerifyOutput::decode(&bytes[..64]).unwrap().domain, None);
+        assert!(BlockVerifyOutput::decode(&bytes[..40]).is_err());
+    }
+
+    #[test]
+    fn test_message_and_tx_outputs() {
+        let message = MessageVerifyOutput::decode(&[0x11; 32]).unwrap();
+        assert_eq!(message.hash, [0x11; 32]);
+
+        let mut bytes = vec![0x22; 32];
+        bytes.extend_from_slice(&[5, 5, 3]);
+        let tx = TxVerifyOutput::decode(&bytes).unwrap();
+        assert_eq!((tx.from_len, tx.to_len, tx.value_len), (5, 5, 3));
+        assert!(CircuitOutput::decode(0x7F, &bytes).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_output() {
+        let mut bytes = vec![0x33; 32];
+        bytes.extend_from_slice(&64u64.to_le_bytes());
+        let output = MessageAggregateOutput::decode(&bytes).unwrap();
+        assert_eq!((output.root, output.count, output.nonce, output.domain), ([0x33; 32], 64, None, None));
+        assert_eq!(CircuitOutput::decode(MESSAGE_AGGREGATE, &bytes).unwrap().hash(), [0x33; 32]);
+    }
+
+    #[test]
+    fn test_message_nonces() {
+        let nonce = MessageNonce::new(ChainId(1), 9);
+        let domain = DomainSeparator::new(ChainId(1), ChainId(10));
+        let bytes = [&[0x11; 32][..], &nonce.to_bytes(), &domain.to_bytes()].concat();
+        let output = MessageVerifyOutput::decode(&bytes).unwrap();
+        assert_eq!((output.hash, output.nonce, output.domain), ([0x11; 32], Some(nonce), Some(domain)));
+
+        // Outputs predating nonces still decode
+        let output = MessageVerifyOutput::decode(&[&[0x11; 32][..], &domain.to_bytes()].concat()).unwrap();
+        assert_eq!((output.nonce, output.domain), (None, Some(domain)));
+
+        let bytes = [&[0x33; 32][..], &3u64.to_le_bytes(), &nonce.to_bytes(), &domain.to_bytes()].concat();
+        let output = MessageAggregateOutput::decode(&bytes).unwrap();
+        assert_eq!((output.count, output.nonce, output.domain), (3, Some(nonce), Some(domain)));
+    }
+
+    #[test]
+    fn test_chain_output() {
+        let statement = ChainStatement {
+            anchor: [0x44; 32],
+            number: 7,
+            block_hash: [0x55; 32],
+            commitment: [0x66; 32],
+        };
+        let output = CircuitOutput::decode(SOL_VERIFY, &statement.to_bytes()).unwrap();
+        assert_eq!(output, CircuitOutput::Chain(ChainVerifyOutput { statement, domain: None }));
+        assert_eq!(output.hash(), [0x55; 32]);
+    }
+
+    #[test]
+    fn test_state_output() {
+        let statement = StorageStatement {
+            state_root: [0x77; 32],
+            address: [0x88; 20],
+            slot: [0; 32],
+            value: [0x99; 32],
+        };
+        let output = CircuitOutput::decode(STATE_PROOF, &statement.to_bytes()).unwrap();
+        assert_eq!(output, CircuitOutput::State(StateProofOutput { statement, domain: None }));
+        assert_eq!(output.hash(), [0x77; 32]);
+    }
+    #[test]
+    fn test_signed_output() {
+        let statement = SignedMessage {
+            struct_hash: [0xAA; 32],
+            domain_hash: [0xBB; 32],
+            signer: [0xCC; 20],
+        };
+        let domain = DomainSeparator::new(ChainId(1), ChainId(10));
+        let mut bytes = statement.to_bytes().to_vec();
+        bytes.extend_from_slice(&domain.to_bytes());
+        let output = CircuitOutput::decode(EIP712_VERIFY, &bytes).unwrap();
+        assert_eq!(output, CircuitOutput::Signed(SignedMessageOutput { statement, domain: Some(domain) }));
+        assert_eq!(output.hash(), [0xAA; 32]);
+    }
+    #[test]
+    fn test_ecdsa_output() {
+        let statement = VerifiedSignature {
+            message_hash: [0xDD; 32],
+            public_key: [0x02; 33],
+        };
+        let output = CircuitOutput::decode(ECDSA_VERIFY, &statement.to_bytes()).unwrap();
+        assert_eq!(output, CircuitOutput::Ecdsa(EcdsaVerifyOutput { statement, domain: None }));
+        assert_eq!(output.hash(), [0xDD; 32]);
+    }
+    #[test]
+    fn test_event_output() {
+        let event = crate::message::MessageEvent {
+            source_chain: ChainId(1),
+            nonce: 1,
+            payload_hash: [0xEE; 32],
+            destination: ChainId(10),
+        };
+        let statement = VerifiedEvent { digest: event.digest(), event };
+        let output = CircuitOutput::decode(MESSAGE_EVENT, &statement.to_bytes()).unwrap();
+        assert_eq!(output, CircuitOutput::Event(MessageEventOutput { statement, domain: None }));
+        assert_eq!(output.hash(), event.digest());
+    }
+
+    #[test]
+    fn test_commitment() {
+        let domain = DomainSeparator::new(ChainId(1), ChainId(10));
+        let mut bytes = [0x11u8; 32].to_vec();
+        bytes.extend_from_slice(&domain.to_bytes());
+        assert_eq!(hash_domain(MESSAGE_VERIFY, &bytes).unwrap(), HashDomain::new(MESSAGE_VERIFY, ChainId(1)));
+
+        // The same hash commits differently for another circuit or origin chain
+        assert_ne!(commitment(MESSAGE_VERIFY, &bytes).unwrap(), commitment(POSEIDON_COMMIT, &bytes).unwrap());
+        let mut other_origin = bytes.clone();
+        other_origin[32..].copy_from_slice(&DomainSeparator::new(ChainId(5), ChainId(10)).to_bytes());
+        assert_ne!(commitment(MESSAGE_VERIFY, &bytes).unwrap(), commitment(MESSAGE_VERIFY, &other_origin).unwrap());
+        assert!(commitment(MESSAGE_VERIFY, &bytes[..20]).is_err());
+    }
+}
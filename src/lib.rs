@@ -17,6 +17,18 @@
 //! - Circuit caching for improved performance
 //! - Resource usage tracking
 //! - Batch operations support
+//! - Recursive aggregation of many proofs into one
+//! - Bridging of RISC0 receipts into SP1 proofs, see [`bridge`]
+//! - Application guest programs registered at runtime, see [`guest`]
+//! - Ethereum (execution and beacon chain), Polkadot, Solana and Tendermint
+//!   light-client verifiers, Ethereum header chains and storage proofs, see [`chains`]
+//! - Proofs of secp256k1 ECDSA signatures and of EIP-712 typed data signed by an
+//!   Ethereum address, see [`signatures`]
+//! - Poseidon commitments to messages that other SNARKs can re-verify, see
+//!   [`commitment`]
+//! - Proofs of canonical cross-chain message events, see [`message`]
+//! - Inclusion proofs for binary Merkle trees with a configurable hash function,
+//!   see [`inclusion`]
 //!
 //! ```rust,no_run
 //! use frostgate_circuits::Sp1Backend;
@@ -36,6 +48,10 @@
 //! - RISC-V based proving system
 //! - Configurable through [`Risc0Config`]
 //! - Support for complex computations
+//! - Optional remote proving through Bonsai with local fallback
+//! - Verification of RLP-encoded Ethereum block headers, see
+//!   [`risc0::EthHeaderCircuit`]
+//! - secp256k1 ECDSA signature verification, see [`risc0::EcdsaVerifyCircuit`]
 //!
 //! ```rust,no_run
 //! use frostgate_circuits::{Risc0Backend, Risc0Config};
@@ -47,6 +63,21 @@
 //!
 //! - `std`: Enables standard library features (default)
 //! - `prove`: Enables proof generation capabilities
+//! - `verify-only`: Builds without the provers for nodes that only verify proofs;
+//!   combine with `default-features = false` and use the [`verify`] module. It
+//!   cannot be enabled together with `prove`
+//! - `integration-tests`: Enables end-to-end tests against the real guest programs,
+//!   skipped when the guest toolchains are not installed
+//! - `api`: Enables the [`api`] module serving a backend over HTTP/JSON
+//! - `webhooks`: Lets proving jobs notify a URL when they finish
+//! - `server`: Enables the [`server`] module and the `frostgate-prover-server` gRPC
+//!   proving service
+//! - `remote`: Enables `RemoteBackend`, a client of the gRPC proving service that
+//!   needs neither prover SDK
+//! - `proto`: Enables the `proto` module with the protobuf wire format of proofs,
+//!   metadata and proving jobs for components not written in Rust
+//! - `groth16`: Enables the `groth16` module proving message verification with a
+//!   hand-written Groth16 circuit instead of a zkVM
 //!
 //! ## Performance Considerations
 //!
@@ -55,16 +86,101 @@
 //! - SP1: Optimized for small to medium circuits with frequent proof generation
 //! - RISC0: Better for complex computations where circuit size is less critical
 //!
+//! Both backends draw from one process-wide CPU budget, see [`threads`].
+//!
+//! ## Tracing
+//!
+//! Both backends trace each request in a `prove_request` or `verify_request` span
+//! carrying the backend and program hash, with `setup`, `execute`, `prove`,
+//! `serialize` and `cache` spans nested below it. Proving jobs and batch items add
+//! a `job` span with the job ID and a `batch_item` span with the item's index. Any
+//! `tracing` subscriber receives them, including `tracing-opentelemetry` to export
+//! them as OpenTelemetry traces.
+//!
 //! ## Error Handling
 //!
 //! The crate uses the error types from `frostgate-zkip`:
 //! - [`ZkError`] for error conditions
 //! - [`ZkResult`] as a convenience type alias
 
+extern crate alloc;
+
+#[cfg(all(feature = "verify-only", feature = "prove"))]
+compile_error!(
+    "features `verify-only` and `prove` are mutually exclusive; \
+     build `verify-only` with `default-features = false`"
+);
+
 // Backend implementations
+#[cfg(feature = "prove")]
 pub mod sp1;
+#[cfg(feature = "prove")]
 pub mod risc0;
+#[cfg(feature = "groth16")]
+pub mod groth16;
 pub mod error;
+pub mod codec;
+pub mod chains;
+pub mod signatures;
+pub mod commitment;
+pub mod message;
+pub mod inclusion;
+pub mod replay;
+pub mod descriptor;
+pub mod domain;
+pub mod events;
+pub mod execution;
+pub mod envelope;
+pub mod compression;
+#[cfg(feature = "prove")]
+pub mod crosscheck;
+pub mod metadata;
+pub mod options;
+pub mod progress;
+pub mod profiles;
+pub mod stats;
+#[cfg(feature = "prove")]
+pub mod dashboard;
+pub mod info;
+pub mod gas;
+pub mod scheduler;
+pub mod jobs;
+pub mod singleflight;
+pub mod threads;
+pub mod sysmon;
+pub mod probe;
+pub mod cluster;
+pub mod aggregation;
+pub mod bridge;
+pub mod programs;
+pub mod guest;
+pub mod manifest;
+pub mod support;
+pub mod store;
+pub mod persist;
+pub mod vk;
+pub mod refresh;
+pub mod hasher;
+pub mod merkle;
+pub mod journal;
+pub mod backends;
+pub mod verify;
+pub mod ink;
+pub mod solana;
+#[cfg(feature = "prove")]
+pub mod evm;
+#[cfg(feature = "worker")]
+pub mod worker;
+#[cfg(any(feature = "grpc", feature = "api"))]
+pub mod signing;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "proto")]
+pub mod proto;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "api")]
+pub mod api;
 
 // Re-export core types from zkip
 pub use frostgate_zkip::{
@@ -73,10 +189,79 @@ pub use frostgate_zkip::{
 };
 
 // Re-export backend implementations
+#[cfg(feature = "prove")]
 pub use sp1::Sp1Backend;
+#[cfg(feature = "prove")]
 pub use risc0::{Risc0Backend, Risc0Config};
+#[cfg(feature = "groth16")]
+pub use groth16::Groth16Backend;
+
+// Re-export event types
+pub use events::{BackendEvent, EventBus};
+pub use execution::ExecutionReport;
+pub use envelope::ProofEnvelope;
+pub use metadata::{ExtendedProofMetadata, ProofMode};
+pub use options::{CancellationToken, ProveOptions};
+pub use progress::ProveProgress;
+pub use descriptor::ProgramDescriptor;
+pub use domain::{ChainId, DomainSeparator, HashDomain, MessageNonce};
+pub use profiles::{CircuitProfile, PriorityClass, ProfileRegistry};
+#[cfg(feature = "prove")]
+pub use crosscheck::{CrossCheckPolicy, DifferentialReport, DualProver, DualProofBundle, GoldenVector};
+#[cfg(feature = "prove")]
+pub use dashboard::BackendDashboard;
+pub use info::BackendInfo;
+pub use gas::{GasEstimate, GasEstimator, TargetChain};
+pub use scheduler::{ProofJob, Scheduler, SchedulingPolicy};
+pub use jobs::{JobCallback, JobId, JobManager, JobNotification, JobReport, JobStatus};
+pub use cluster::{BatchStatement, Cluster, ShardProofs};
+pub use aggregation::{AggregateProof, AggregateStatement, Aggregator, InclusionProof};
+pub use bridge::BridgedReceipt;
+pub use programs::{ProgramLease, ProgramRegistry, ProgramVersion};
+pub use guest::{GuestProgram, GuestPrograms, JournalValidator};
+pub use manifest::{ManifestProgram, ProgramManifest};
+pub use support::SupportBundle;
+pub use store::ArtifactStore;
+pub use vk::{ExportedKey, KeyKind};
+pub use ink::InkVerifierExport;
+pub use solana::{SolanaProof, SolanaVerifierExport};
+#[cfg(feature = "prove")]
+pub use evm::encode_for_evm;
+pub use refresh::{RefreshPolicy, RefreshTracker};
+pub use probe::{HealthProbe, ProbeMode, ProbePolicy, ProbeResult};
+pub use hasher::{HashFunction, Hasher, PoseidonParams, POSEIDON_PARAMS};
+pub use journal::{
+    BlockVerifyOutput, ChainVerifyOutput, CircuitOutput, EcdsaVerifyOutput, MerkleInclusionOutput, MessageAggregateOutput, MessageEventOutput, MessageVerifyOutput,
+    SignedMessageOutput, StateProofOutput, TxVerifyOutput,
+};
+pub use chains::ChainStatement;
+pub use chains::state::{StateProof, StorageKey, StorageStatement};
+#[cfg(feature = "std")]
+pub use chains::circuit::{
+    BeaconUpdateCircuit, Chain, ChainVerifierCircuit, ChainWitness, GrandpaCircuit, HeaderChainCircuit, LogInclusionCircuit,
+    StateProofCircuit, TmHeaderCircuit,
+};
+pub use signatures::ecdsa::{EcdsaWitness, VerifiedSignature};
+pub use signatures::eip712::{Eip712Witness, SignedMessage, TypedData};
+#[cfg(feature = "std")]
+pub use signatures::circuit::{EcdsaCircuit, Eip712Circuit};
+pub use commitment::PoseidonCommitCircuit;
+pub use message::{MessageEvent, VerifiedEvent};
+pub use replay::ReplayGuard;
+pub use inclusion::{MerkleInclusion, MerkleWitness};
+#[cfg(feature = "std")]
+pub use inclusion::circuit::MerkleInclusionCircuit;
+#[cfg(feature = "std")]
+pub use message::circuit::MessageEventCircuit;
+pub use backends::mock::MockBackend;
+pub use backends::multi::{MultiBackend, MultiProofMetadata};
+pub use backends::universal::UniversalVerifier;
+#[cfg(feature = "remote")]
+pub use backends::remote::RemoteBackend;
+#[cfg(feature = "prove")]
+pub use backends::registry::{create_backend, register_backend, BackendFactory, BackendRegistry};
 
-#[cfg(test)]
+#[cfg(all(test, feature = "prove"))]
 mod tests {
     use super::*;
     use frostgate_zkip::*;
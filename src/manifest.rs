@@ -0,0 +1,186 @@
+//! Declarative program manifests
+//!
+//! Deployments list the guest programs they run in a `programs.toml` manifest
+//! instead of wiring each circuit up in code:
+//!
+//! ```toml
+//! [[program]]
+//! name = "message_verify"
+//! circuit_type = 0x01
+//! elf = "guests/risc0/message_verify.elf"
+//! image_id = "5c1d…"
+//! backends = ["risc0"]
+//! ```
+//!
+//! Relative ELF paths are resolved against the manifest's directory. `image_id` is
+//! the RISC0 image ID or the SP1 verifying key hash of the ELF, and is checked when
+//! a backend loads the program. An ELF is built for one zkVM, so a program proven by
+//! both backends is listed once per backend. Backends load their entries with
+//! `load_manifest`.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+
+use crate::error::ZkError;
+
+/// Backends a manifest entry can name
+pub const BACKENDS: &[&str] = &["risc0", "sp1"];
+
+/// Program listed in a manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestProgram {
+    /// Circuit name
+    pub name: String,
+    /// Circuit type identifier
+    pub circuit_type: u8,
+    /// Path of the guest ELF
+    pub elf: PathBuf,
+    /// Expected RISC0 image ID or SP1 verifying key hash of the ELF
+    pub image_id: Option<[u8; 32]>,
+    /// Backends proving the program
+    pub backends: Vec<String>,
+}
+
+impl ManifestProgram {
+    /// Read the guest ELF
+    pub fn read_elf(&self) -> Result<Vec<u8>, ZkError> {
+        fs::read(&self.elf).map_err(|e| {
+            ZkError::InvalidManifest(format!("Failed to read ELF of {} at {}: {}", self.name, self.elf.display(), e))
+        })
+    }
+
+    /// Whether the program is proven by `backend`
+    pub fn supports(&self, backend: &str) -> bool {
+        self.backends.iter().any(|name| name == backend)
+    }
+}
+
+/// Programs listed in a `programs.toml` manifest
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgramManifest {
+    /// Listed programs, in manifest order
+    pub programs: Vec<ManifestProgram>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawManifest {
+    #[serde(default, rename = "program")]
+    programs: Vec<RawProgram>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawProgram {
+    name: String,
+    circuit_type: u8,
+    elf: PathBuf,
+    image_id: Option<String>,
+    backends: Vec<String>,
+}
+
+impl ProgramManifest {
+    /// Load a manifest file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ZkError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|e| ZkError::InvalidManifest(format!("Failed to read {}: {}", path.display(), e)))?;
+        Self::parse(&text, path.parent().unwrap_or_else(|| Path::new(".")))
+    }
+
+    /// Parse a manifest, resolving relative ELF paths against `base_dir`
+    pub fn parse(text: &str, base_dir: &Path) -> Result<Self, ZkError> {
+        let raw: RawManifest = toml::from_str(text)
+            .map_err(|e| ZkError::InvalidManifest(e.to_string()))?;
+
+        let mut names = HashSet::new();
+        let mut circuit_types = HashSet::new();
+        let mut programs = Vec::with_capacity(raw.programs.len());
+        for program in raw.programs {
+            let invalid = |reason: String| ZkError::InvalidManifest(format!("Program {}: {}", program.name, reason));
+            if program.backends.is_empty() {
+                return Err(invalid("no backends listed".into()));
+            }
+            for backend in &program.backends {
+                if !BACKENDS.contains(&backend.as_str()) {
+                    return Err(invalid(format!("unknown backend {}", backend)));
+                }
+                // Each backend runs one ELF per name and per circuit type
+                if !names.insert((backend.clone(), program.name.clone())) {
+                    return Err(invalid(format!("listed twice for {}", backend)));
+                }
+                if !circuit_types.insert((backend.clone(), program.circuit_type)) {
+                    return Err(invalid(format!("circuit type 0x{:02x} listed twice for {}", program.circuit_type, backend)));
+                }
+            }
+            let image_id = program.image_id.as_deref()
+                .map(|image_id| {
+                    hex::decode(image_id.trim_start_matches("0x")).ok()
+                        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                        .ok_or_else(|| invalid("image_id is not 32 hex-encoded bytes".into()))
+                })
+                .transpose()?;
+            programs.push(ManifestProgram {
+                elf: base_dir.join(&program.elf),
+                name: program.name,
+                circuit_type: program.circuit_type,
+                image_id,
+                backends: program.backends,
+            });
+        }
+        Ok(Self { programs })
+    }
+
+    /// Programs proven by `backend`
+    pub fn for_backend<'a>(&'a self, backend: &'a str) -> impl Iterator<Item = &'a ManifestProgram> + 'a {
+        self.programs.iter().filter(move |program| program.supports(backend))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+        [[program]]
+        name = "message_verify"
+        circuit_type = 0x01
+        elf = "risc0/message_verify.elf"
+        image_id = "0x0101010101010101010101010101010101010101010101010101010101010101"
+        backends = ["risc0"]
+
+        [[program]]
+        name = "message_verify"
+        circuit_type = 0x01
+        elf = "/opt/guests/sp1/message_verify.elf"
+        backends = ["sp1"]
+    "#;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = ProgramManifest::parse(MANIFEST, Path::new("/etc/frostgate")).unwrap();
+        let risc0: Vec<_> = manifest.for_backend("risc0").collect();
+        assert_eq!(risc0.len(), 1);
+        assert_eq!(risc0[0].elf, PathBuf::from("/etc/frostgate/risc0/message_verify.elf"));
+        assert_eq!(risc0[0].image_id, Some([1u8; 32]));
+
+        let sp1: Vec<_> = manifest.for_backend("sp1").collect();
+        assert_eq!(sp1[0].elf, PathBuf::from("/opt/guests/sp1/message_verify.elf"));
+        assert_eq!(sp1[0].image_id, None);
+    }
+
+    #[test]
+    fn test_invalid_manifests() {
+        let base = Path::new(".");
+        let duplicate = MANIFEST.replace(r#"backends = ["sp1"]"#, r#"backends = ["risc0"]"#);
+        assert!(ProgramManifest::parse(&duplicate, base).is_err());
+        let unknown = MANIFEST.replace(r#"backends = ["sp1"]"#, r#"backends = ["zkwasm"]"#);
+        assert!(ProgramManifest::parse(&unknown, base).is_err());
+        let short_id = MANIFEST.replace("0x0101", "0x");
+        assert!(ProgramManifest::parse(&short_id, base).is_err());
+        assert!(ProgramManifest::parse("[[program]]\nname = \"x\"", base).is_err());
+        assert_eq!(ProgramManifest::parse("", base).unwrap(), ProgramManifest::default());
+    }
+}
@@ -0,0 +1,102 @@
+//! Binary SHA-256 Merkle trees over message and proof hashes
+//!
+//! The aggregation circuits commit to the root of a tree whose leaves are the hashes
+//! of the aggregated messages or proofs. Leaves and inner nodes are hashed with
+//! distinct prefixes so an inner node can never be passed off as a leaf. A node
+//! without a sibling at the end of a level moves up unchanged.
+//!
+//! [`tagged_hash`] hashes other guest structures under their [`HashDomain`].
+//!
+//! Like [`crate::domain`], this module only depends on `core` and `sha2` so the guest
+//! programs in `circuits/` include the very same source file.
+
+use sha2::{Digest, Sha256};
+
+use crate::domain::HashDomain;
+
+/// Prefix of hashed leaves
+pub const LEAF_PREFIX: u8 = 0x00;
+
+/// Prefix of hashed inner nodes
+pub const NODE_PREFIX: u8 = 0x01;
+
+/// Leaf of the message or proof with `hash`
+pub fn leaf(hash: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(hash);
+    hasher.finalize().into()
+}
+
+/// Hash of a proof by the program with verifying key digest `vkey` that committed
+/// `public_values`
+pub fn proof_hash(vkey: &[u32; 8], public_values: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for word in vkey {
+        hasher.update(word.to_le_bytes());
+    }
+    hasher.update(Sha256::digest(public_values));
+    hasher.finalize().into()
+}
+
+/// SHA-256 of `data` prefixed with the tag of `domain`
+pub fn tagged_hash(domain: &HashDomain, data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(domain.tag());
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// Inner node over two children
+pub fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Root of the tree over `leaves`, overwriting them while folding level by level
+///
+/// The root of an empty tree is all zeroes.
+pub fn root_in_place(leaves: &mut [[u8; 32]]) -> [u8; 32] {
+    let mut len = leaves.len();
+    if len == 0 {
+        return [0u8; 32];
+    }
+    while len > 1 {
+        let mut next = 0;
+        for i in (0..len).step_by(2) {
+            leaves[next] = if i + 1 < len { node(&leaves[i], &leaves[i + 1]) } else { leaves[i] };
+            next += 1;
+        }
+        len = next;
+    }
+    leaves[0]
+}
+
+/// Check that `leaf` is leaf `index` of a tree of `count` leaves with `root`,
+/// given the siblings on its path from the bottom up
+pub fn verify_path(leaf: &[u8; 32], mut index: usize, mut count: usize, path: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    if index >= count {
+        return false;
+    }
+    let mut hash = *leaf;
+    let mut siblings = path.iter();
+    while count > 1 {
+        if index % 2 == 1 {
+            match siblings.next() {
+                Some(sibling) => hash = node(sibling, &hash),
+                None => return false,
+            }
+        } else if index + 1 < count {
+            match siblings.next() {
+                Some(sibling) => hash = node(&hash, sibling),
+                None => return false,
+            }
+        }
+        index /= 2;
+        count = (count + 1) / 2;
+    }
+    siblings.next().is_none() && hash == *root
+}
@@ -0,0 +1,87 @@
+//! Program and input driving the message event guest
+//!
+//! A [`MessageEventCircuit`] proves one canonical [`MessageEvent`]. Its program is a
+//! [`ProgramDescriptor`] of [`MESSAGE_EVENT`] with the event's digest as parameters;
+//! the SP1 backend runs the `message_event` guest listed for that type in the
+//! program manifest. The proving input is the encoded event, and proofs should be
+//! bound to the event's [`MessageEvent::domain`].
+
+use crate::descriptor::ProgramDescriptor;
+use crate::error::ZkError;
+use crate::journal::MessageEventOutput;
+use crate::profiles::MESSAGE_EVENT;
+use super::MessageEvent;
+
+/// A canonical message event proven by its digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageEventCircuit {
+    /// The event to prove
+    pub event: MessageEvent,
+}
+
+impl MessageEventCircuit {
+    /// Create a prover and verifier of `event`, checked to be valid
+    pub fn new(event: MessageEvent) -> Result<Self, ZkError> {
+        event.validate()
+            .map_err(|e| ZkError::InvalidInput(format!("Invalid message event: {}", e)))?;
+        Ok(Self { event })
+    }
+
+    /// Descriptor of the message event program for the event
+    pub fn descriptor(&self) -> ProgramDescriptor {
+        ProgramDescriptor::new(MESSAGE_EVENT, self.event.digest())
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.descriptor().encode()
+    }
+
+    /// Proving input of the event
+    pub fn input(&self) -> Vec<u8> {
+        self.event.to_bytes().to_vec()
+    }
+
+    /// Decode the public values of a proof, rejecting proofs of any other event
+    pub fn output(&self, public_values: &[u8]) -> Result<MessageEventOutput, ZkError> {
+        let output = MessageEventOutput::decode(public_values)?;
+        if output.statement.event != self.event || output.statement.digest != self.event.digest() {
+            return Err(ZkError::ProofVerification(format!(
+                "Proof is of event {}, expected {}",
+                hex::encode(output.statement.digest),
+                hex::encode(self.event.digest())
+            )));
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ChainId;
+    use crate::message::verify;
+
+    #[test]
+    fn test_message_event_circuit() {
+        let event = MessageEvent {
+            source_chain: ChainId(1),
+            nonce: 7,
+            payload_hash: [0x22; 32],
+            destination: ChainId(10),
+        };
+        let circuit = MessageEventCircuit::new(event).unwrap();
+        assert_eq!(event.hash_domain().circuit_type, MESSAGE_EVENT);
+        assert_eq!(ProgramDescriptor::decode(&circuit.program()).unwrap().circuit_type, MESSAGE_EVENT);
+
+        // Public values as the guest commits them
+        let statement = verify(&circuit.input(), &event.domain()).unwrap();
+        let mut public_values = statement.to_bytes().to_vec();
+        public_values.extend_from_slice(&event.domain().to_bytes());
+        assert_eq!(circuit.output(&public_values).unwrap().domain, Some(event.domain()));
+
+        let other = MessageEventCircuit::new(MessageEvent { nonce: 8, ..event }).unwrap();
+        assert!(other.output(&public_values).is_err());
+        assert!(MessageEventCircuit::new(MessageEvent { destination: ChainId(1), ..event }).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! Canonical cross-chain message events
+//!
+//! Every Frostgate component proves the same structure for a message: a
+//! [`MessageEvent`] naming its source chain, nonce, payload hash and destination
+//! chain, in the fixed [`MessageEvent::to_bytes`] encoding. The `message_event`
+//! guest decodes and validates an event and commits a [`VerifiedEvent`]: the typed
+//! [`MessageEvent::digest`] followed by the event itself, so verifiers can match
+//! either.
+//!
+//! Like the chain verifiers, the logic only depends on `core` and `sha2` so the guest
+//! includes the very same source file, together with [`crate::domain`] and
+//! [`crate::merkle`]; the host-side program and input driving the guest are in
+//! [`circuit`].
+
+#[cfg(feature = "std")]
+pub mod circuit;
+
+use core::fmt;
+
+use crate::domain::{ChainId, DomainSeparator, HashDomain};
+use crate::merkle::tagged_hash;
+
+/// Circuit type of the message event guest, [`crate::profiles::MESSAGE_EVENT`] on the
+/// host
+const CIRCUIT_TYPE: u8 = 0x12;
+
+/// Message sent from one chain to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageEvent {
+    /// Chain the message originates from
+    pub source_chain: ChainId,
+    /// Sequence number of the message on its source chain
+    pub nonce: u64,
+    /// Hash of the message payload
+    pub payload_hash: [u8; 32],
+    /// Chain the message is delivered to
+    pub destination: ChainId,
+}
+
+impl MessageEvent {
+    /// Length of an encoded event
+    pub const LEN: usize = 56;
+
+    /// Encode canonically: source chain, nonce (little endian), payload hash,
+    /// destination chain
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..8].copy_from_slice(&self.source_chain.0.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.nonce.to_le_bytes());
+        bytes[16..48].copy_from_slice(&self.payload_hash);
+        bytes[48..].copy_from_slice(&self.destination.0.to_le_bytes());
+        bytes
+    }
+
+    /// Decode an event, `None` if `bytes` is not [`Self::LEN`] long
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        Some(Self {
+            source_chain: ChainId(u64::from_le_bytes(bytes[..8].try_into().ok()?)),
+            nonce: u64::from_le_bytes(bytes[8..16].try_into().ok()?),
+            payload_hash: bytes[16..48].try_into().ok()?,
+            destination: ChainId(u64::from_le_bytes(bytes[48..].try_into().ok()?)),
+        })
+    }
+
+    /// Domain of proofs about the event
+    pub fn domain(&self) -> DomainSeparator {
+        DomainSeparator::new(self.source_chain, self.destination)
+    }
+
+    /// Hash domain of the event's digest: the message event circuit on the source
+    /// chain
+    pub fn hash_domain(&self) -> HashDomain {
+        HashDomain::new(CIRCUIT_TYPE, self.source_chain)
+    }
+
+    /// SHA-256 of the encoded event, tagged with its [`Self::hash_domain`]
+    pub fn digest(&self) -> [u8; 32] {
+        tagged_hash(&self.hash_domain(), &self.to_bytes())
+    }
+
+    /// Check the event names two distinct chains and a payload
+    pub fn validate(&self) -> Result<(), MessageError> {
+        if self.source_chain == ChainId::UNBOUND || self.destination == ChainId::UNBOUND {
+            return Err(MessageError::Invalid("event chains must not be the reserved chain 0"));
+        }
+        if self.source_chain == self.destination {
+            return Err(MessageError::Invalid("event source and destination are the same chain"));
+        }
+        if self.payload_hash == [0; 32] {
+            return Err(MessageError::Invalid("event has no payload hash"));
+        }
+        Ok(())
+    }
+}
+
+/// Statement proven by the message event guest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifiedEvent {
+    /// [`MessageEvent::digest`] of the event
+    pub digest: [u8; 32],
+    /// The validated event
+    pub event: MessageEvent,
+}
+
+impl VerifiedEvent {
+    /// Length of an encoded statement
+    pub const LEN: usize = 32 + MessageEvent::LEN;
+
+    /// Encode as the guest commits it: digest, event
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..32].copy_from_slice(&self.digest);
+        bytes[32..].copy_from_slice(&self.event.to_bytes());
+        bytes
+    }
+
+    /// Decode a statement, `None` if `bytes` is not [`Self::LEN`] long
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        Some(Self {
+            digest: bytes[..32].try_into().ok()?,
+            event: MessageEvent::from_bytes(&bytes[32..])?,
+        })
+    }
+}
+
+/// Reason encoded bytes are not a valid message event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageError {
+    /// The bytes are not an encoded event
+    Malformed(&'static str),
+    /// The event is structurally invalid
+    Invalid(&'static str),
+    /// The proof is bound to another domain than the event's chains
+    DomainMismatch,
+}
+
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageError::Malformed(what) => write!(f, "malformed {}", what),
+            MessageError::Invalid(reason) => write!(f, "invalid message: {}", reason),
+            MessageError::DomainMismatch => write!(f, "proof domain does not match the event's chains"),
+        }
+    }
+}
+
+/// Decode and validate an encoded event
+///
+/// A proof bound to a domain must be bound to the event's own source and
+/// destination; unbound proofs are accepted for any event.
+pub fn verify(bytes: &[u8], domain: &DomainSeparator) -> Result<VerifiedEvent, MessageError> {
+    let event = MessageEvent::from_bytes(bytes).ok_or(MessageError::Malformed("message event"))?;
+    event.validate()?;
+    if domain.is_bound() && *domain != event.domain() {
+        return Err(MessageError::DomainMismatch);
+    }
+    Ok(VerifiedEvent {
+        digest: event.digest(),
+        event,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> MessageEvent {
+        MessageEvent {
+            source_chain: ChainId(1),
+            nonce: 42,
+            payload_hash: [0x11; 32],
+            destination: ChainId(137),
+        }
+    }
+
+    #[test]
+    fn test_event_round_trip() {
+        let event = event();
+        assert_eq!(MessageEvent::from_bytes(&event.to_bytes()), Some(event));
+        assert_eq!(MessageEvent::from_bytes(&[0; 40]), None);
+
+        let statement = verify(&event.to_bytes(), &DomainSeparator::UNBOUND).unwrap();
+        assert_eq!(statement.digest, event.digest());
+        assert_eq!(VerifiedEvent::from_bytes(&statement.to_bytes()), Some(statement));
+
+        // The digest covers every field and is tagged with the event's domain
+        let other = MessageEvent { nonce: 43, ..event };
+        assert_ne!(other.digest(), event.digest());
+        assert_ne!(event.digest(), crate::merkle::tagged_hash(&HashDomain::new(0x01, ChainId(1)), &event.to_bytes()));
+    }
+
+    #[test]
+    fn test_event_validation() {
+        let event = event();
+        assert!(verify(&event.to_bytes(), &event.domain()).is_ok());
+        let reversed = DomainSeparator::new(ChainId(137), ChainId(1));
+        assert_eq!(verify(&event.to_bytes(), &reversed), Err(MessageError::DomainMismatch));
+        assert_eq!(verify(&[0; 55], &DomainSeparator::UNBOUND), Err(MessageError::Malformed("message event")));
+
+        let looped = MessageEvent { destination: ChainId(1), ..event };
+        assert!(matches!(looped.validate(), Err(MessageError::Invalid(_))));
+        let unbound = MessageEvent { source_chain: ChainId::UNBOUND, ..event };
+        assert!(matches!(unbound.validate(), Err(MessageError::Invalid(_))));
+        let empty = MessageEvent { payload_hash: [0; 32], ..event };
+        assert!(matches!(empty.validate(), Err(MessageError::Invalid(_))));
+    }
+}
@@ -0,0 +1,127 @@
+//! Extended proof metadata
+//!
+//! [`ProofMetadata`] from `frostgate-zkip` only records size and timing. The types in
+//! this module carry the additional details this crate knows about a proof, such as
+//! the proof mode that was actually used to produce it.
+
+use serde::{Serialize, Deserialize};
+use frostgate_zkip::ProofMetadata;
+
+use crate::domain::DomainSeparator;
+use crate::journal::CircuitOutput;
+
+/// Proof representation produced by a backend
+///
+/// Modes are ordered from largest to most succinct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ProofMode {
+    /// Uncompressed STARK proof (SP1 core, RISC0 composite receipt)
+    Core,
+    /// Recursively compressed STARK proof of constant size
+    Compressed,
+    /// PLONK SNARK over BN254 wrapping a compressed proof, verifiable on EVM chains
+    /// without a per-circuit trusted setup
+    Plonk,
+    /// Groth16 SNARK over BN254 wrapping a compressed proof, verifiable on EVM chains
+    Groth16,
+}
+
+impl ProofMode {
+    /// The next more succinct mode, if any
+    ///
+    /// Escalation goes straight from compressed to Groth16; PLONK is only used when
+    /// requested explicitly.
+    pub fn more_succinct(self) -> Option<ProofMode> {
+        match self {
+            ProofMode::Core => Some(ProofMode::Compressed),
+            ProofMode::Compressed | ProofMode::Plonk => Some(ProofMode::Groth16),
+            ProofMode::Groth16 => None,
+        }
+    }
+
+    /// Whether proofs in this mode can be verified by the EVM verifier contracts
+    pub fn is_onchain_verifiable(self) -> bool {
+        matches!(self, ProofMode::Plonk | ProofMode::Groth16)
+    }
+}
+
+impl Default for ProofMode {
+    fn default() -> Self {
+        ProofMode::Core
+    }
+}
+
+/// Proof metadata extended with backend-specific details
+#[derive(Debug, Clone)]
+pub struct ExtendedProofMetadata {
+    /// Base metadata reported through `ZkBackend`
+    pub base: ProofMetadata,
+    /// Mode the proof was produced in
+    pub proof_mode: ProofMode,
+    /// Mode originally requested by the caller
+    pub requested_mode: ProofMode,
+    /// Proof-size budget the proof was checked against
+    pub max_proof_size: Option<usize>,
+    /// Backend that produced the proof
+    pub backend: String,
+    /// Prover SDK version that produced the proof
+    pub sdk_version: String,
+    /// Circuit type identifier of the program that was proven
+    pub circuit_type: Option<u8>,
+    /// Version of the circuit that was proven
+    pub circuit_version: Option<u32>,
+    /// Verifying key hash (SP1) or image ID (RISC0) on-chain verifiers check the proof against
+    pub vkey_hash: Option<[u8; 32]>,
+    /// Public values (SP1) or journal (RISC0) committed by the guest
+    pub public_values: Vec<u8>,
+    /// Number of cycles the guest executed, if reported by the prover
+    pub cycles: Option<u64>,
+    /// Number of segments (RISC0) or shards (SP1) proven, if known
+    pub segments: Option<usize>,
+    /// Peak resident memory of the process sampled while proving, if sampled
+    pub peak_memory: Option<usize>,
+}
+
+/// Proof produced by a single proving run, with the counts the prover reported
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProvingRun {
+    /// Serialized proof
+    pub proof: Vec<u8>,
+    /// Number of cycles the guest executed
+    pub cycles: Option<u64>,
+    /// Number of segments or shards proven
+    pub segments: Option<usize>,
+    /// Hash of the verifying key the proof checks against, reported by SP1 runs
+    pub vkey_hash: Option<[u8; 32]>,
+}
+
+impl ExtendedProofMetadata {
+    /// Whether the backend had to escalate to a more succinct mode to meet the budget
+    pub fn escalated(&self) -> bool {
+        self.proof_mode != self.requested_mode
+    }
+
+    /// Decoded outputs committed by the guest
+    pub fn output(&self) -> Option<CircuitOutput> {
+        CircuitOutput::decode(self.circuit_type?, &self.public_values).ok()
+    }
+
+    /// Hash of the statement the guest verified, committed first by every circuit
+    pub fn committed_hash(&self) -> Option<[u8; 32]> {
+        self.public_values.get(..32)?.try_into().ok()
+    }
+
+    /// Block number committed by a block or chain verification proof, the slot for Solana
+    pub fn block_number(&self) -> Option<u64> {
+        match self.output()? {
+            CircuitOutput::Block(output) => Some(output.number),
+            CircuitOutput::Chain(output) => Some(output.statement.number),
+            _ => None,
+        }
+    }
+
+    /// Domain the proof is bound to
+    pub fn domain(&self) -> Option<DomainSeparator> {
+        DomainSeparator::from_public_values(&self.public_values)
+    }
+}
@@ -0,0 +1,117 @@
+//! Per-request proving options
+
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::domain::DomainSeparator;
+use crate::metadata::ProofMode;
+
+#[cfg(feature = "std")]
+pub use tokio_util::sync::CancellationToken;
+
+/// Options applying to a single prove request
+///
+/// Unset fields fall back to the backend configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProveOptions {
+    /// Proof mode to start from
+    pub proof_mode: Option<ProofMode>,
+    /// Maximum acceptable proof size in bytes.
+    ///
+    /// If the proof would exceed it, the backend escalates to a more succinct mode
+    /// where it can, and otherwise fails with [`crate::error::ZkError::ProofTooLarge`].
+    pub max_proof_size: Option<usize>,
+    /// Domain the proof is bound to, unbound if unset
+    #[serde(default)]
+    pub domain: Option<DomainSeparator>,
+    /// Maximum time proving may take.
+    ///
+    /// Falls back to the circuit profile's timeout, then to the backend's default.
+    #[serde(default)]
+    pub timeout: Option<Duration>,
+}
+
+impl ProveOptions {
+    /// Set the starting proof mode
+    pub fn with_proof_mode(mut self, mode: ProofMode) -> Self {
+        self.proof_mode = Some(mode);
+        self
+    }
+
+    /// Set the maximum acceptable proof size
+    pub fn with_max_proof_size(mut self, max_proof_size: usize) -> Self {
+        self.max_proof_size = Some(max_proof_size);
+        self
+    }
+
+    /// Bind the proof to a domain
+    pub fn with_domain(mut self, domain: DomainSeparator) -> Self {
+        self.domain = Some(domain);
+        self
+    }
+
+    /// Bound the time proving may take
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Domain the proof is bound to
+    pub fn domain(&self) -> DomainSeparator {
+        self.domain.unwrap_or_default()
+    }
+
+    /// Whether a proof of `size` bytes fits the budget
+    pub fn fits(&self, size: usize) -> bool {
+        self.max_proof_size.map_or(true, |limit| size <= limit)
+    }
+}
+
+/// Run `proving` until it finishes, `timeout` elapses or `cancel` fires.
+///
+/// On timeout `cancel` is fired as well. `proving` must observe the token and return
+/// promptly once it fires, so that it can release what it holds before the caller
+/// gets the error.
+#[cfg(feature = "std")]
+pub(crate) async fn bounded<F, T>(
+    proving: F,
+    timeout: Option<Duration>,
+    cancel: &CancellationToken,
+) -> Result<T, crate::error::ZkError>
+where
+    F: std::future::Future<Output = Result<T, crate::error::ZkError>>,
+{
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return proving.await,
+    };
+    tokio::pin!(proving);
+    tokio::select! {
+        result = &mut proving => result,
+        _ = tokio::time::sleep(timeout) => {
+            cancel.cancel();
+            let _ = proving.await;
+            Err(crate::error::ZkError::Timeout(timeout))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::error::ZkError;
+
+    #[tokio::test]
+    async fn test_bounded_cancels_on_timeout() {
+        let cancel = CancellationToken::new();
+        let proving = async {
+            cancel.cancelled().await;
+            Err::<(), _>(ZkError::Cancelled)
+        };
+        let result = bounded(proving, Some(Duration::from_millis(10)), &cancel).await;
+        assert!(matches!(result, Err(ZkError::Timeout(_))));
+        assert!(cancel.is_cancelled());
+
+        let result = bounded(async { Ok(1) }, None, &CancellationToken::new()).await;
+        assert_eq!(result.unwrap(), 1);
+    }
+}
@@ -0,0 +1,217 @@
+//! Disk persistence for the circuit and proof caches
+//!
+//! Cache contents are stored as blobs in an [`ArtifactStore`] under the configured
+//! persist path, alongside an `index.json` describing every entry. On startup the
+//! caches reload the index; entries whose blobs are missing or fail their content
+//! hash are dropped rather than failing the reload.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+
+use crate::error::ZkError;
+use crate::events::CacheKind;
+use crate::store::ArtifactStore;
+
+const INDEX_FILE: &str = "index.json";
+
+/// Index record of a persisted cache entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedEntry {
+    /// Cache the entry belongs to
+    pub kind: CacheKind,
+    /// Hex-encoded cache key
+    pub key: String,
+    /// Hex-encoded content hash of the blob in the artifact store
+    pub blob: String,
+    /// Hex-encoded program hash
+    pub program_hash: String,
+    /// Hex-encoded input hash, for proofs
+    pub input_hash: Option<String>,
+    /// Time it took to produce the entry
+    pub generation_time: Duration,
+    /// Time the entry was stored
+    pub stored_at: SystemTime,
+    /// Time this entry stays valid, for proofs
+    pub ttl: Option<Duration>,
+}
+
+impl PersistedEntry {
+    /// Decode the cache key
+    pub fn key_bytes(&self) -> Option<[u8; 32]> {
+        decode_hash(&self.key)
+    }
+
+    /// Decode the program hash
+    pub fn program_hash_bytes(&self) -> Option<[u8; 32]> {
+        decode_hash(&self.program_hash)
+    }
+
+    /// Decode the input hash
+    pub fn input_hash_bytes(&self) -> Option<[u8; 32]> {
+        self.input_hash.as_deref().and_then(decode_hash)
+    }
+}
+
+fn decode_hash(hex_hash: &str) -> Option<[u8; 32]> {
+    hex::decode(hex_hash).ok()?.try_into().ok()
+}
+
+/// Persistent backing store of a cache
+#[derive(Debug)]
+pub struct CachePersistence {
+    store: ArtifactStore,
+    index: Mutex<HashMap<String, PersistedEntry>>,
+    index_path: PathBuf,
+}
+
+impl CachePersistence {
+    /// Open the persistence directory, creating it if needed
+    ///
+    /// A corrupt index is discarded with a warning.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ZkError> {
+        let path = path.as_ref();
+        let store = ArtifactStore::open(path)?;
+        let index_path = path.join(INDEX_FILE);
+        let index = match fs::read(&index_path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                tracing::warn!(path = %index_path.display(), "discarding corrupt cache index: {}", e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Ok(Self {
+            store,
+            index: Mutex::new(index),
+            index_path,
+        })
+    }
+
+    fn index_key(kind: CacheKind, key: &[u8; 32]) -> String {
+        format!("{:?}/{}", kind, hex::encode(key))
+    }
+
+    /// Persist an entry, replacing any previous entry with the same key
+    pub fn persist(&self, mut entry: PersistedEntry, contents: &[u8]) -> Result<(), ZkError> {
+        let key = entry.key_bytes()
+            .ok_or_else(|| ZkError::InvalidInput(format!("Invalid cache key: {}", entry.key)))?;
+        let blob = self.store.put_retained(contents)?;
+        entry.blob = hex::encode(blob);
+
+        let mut index = self.index.lock();
+        if let Some(previous) = index.insert(Self::index_key(entry.kind, &key), entry) {
+            self.release(&previous);
+        }
+        self.write_index(&index)
+    }
+
+    /// Remove an entry
+    pub fn remove(&self, kind: CacheKind, key: &[u8; 32]) -> Result<(), ZkError> {
+        let mut index = self.index.lock();
+        if let Some(previous) = index.remove(&Self::index_key(kind, key)) {
+            self.release(&previous);
+            self.write_index(&index)?;
+        }
+        Ok(())
+    }
+
+    /// Load all readable entries of a cache, dropping corrupt ones
+    pub fn load(&self, kind: CacheKind) -> Vec<(PersistedEntry, Vec<u8>)> {
+        let mut index = self.index.lock();
+        let mut loaded = Vec::new();
+        let mut corrupt = Vec::new();
+        for (index_key, entry) in index.iter().filter(|(_, entry)| entry.kind == kind) {
+            let contents = decode_hash(&entry.blob)
+                .and_then(|blob| self.store.get(&blob).ok().flatten());
+            match contents {
+                Some(contents) => loaded.push((entry.clone(), contents)),
+                None => corrupt.push(index_key.clone()),
+            }
+        }
+
+        if !corrupt.is_empty() {
+            tracing::warn!(count = corrupt.len(), "dropping unreadable cache entries");
+            for index_key in corrupt {
+                if let Some(entry) = index.remove(&index_key) {
+                    self.release(&entry);
+                }
+            }
+            if let Err(e) = self.write_index(&index) {
+                tracing::warn!("failed to write cache index: {}", e);
+            }
+        }
+        loaded
+    }
+
+    /// Remove every entry and collect the blobs
+    pub fn clear(&self) -> Result<(), ZkError> {
+        let mut index = self.index.lock();
+        for (_, entry) in index.drain() {
+            self.release(&entry);
+        }
+        self.write_index(&index)?;
+        self.store.gc()?;
+        Ok(())
+    }
+
+    fn release(&self, entry: &PersistedEntry) {
+        if let Some(blob) = decode_hash(&entry.blob) {
+            if let Err(e) = self.store.release(&blob) {
+                tracing::warn!(blob = %entry.blob, "failed to release cache blob: {}", e);
+            }
+        }
+    }
+
+    fn write_index(&self, index: &HashMap<String, PersistedEntry>) -> Result<(), ZkError> {
+        let tmp = self.index_path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(index)?)?;
+        fs::rename(&tmp, &self.index_path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: CacheKind, key: [u8; 32]) -> PersistedEntry {
+        PersistedEntry {
+            kind,
+            key: hex::encode(key),
+            blob: String::new(),
+            program_hash: hex::encode([1u8; 32]),
+            input_hash: None,
+            generation_time: Duration::from_secs(1),
+            stored_at: SystemTime::now(),
+            ttl: None,
+        }
+    }
+
+    #[test]
+    fn test_reload_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let persistence = CachePersistence::open(dir.path()).unwrap();
+            persistence.persist(entry(CacheKind::Proof, [2u8; 32]), b"proof").unwrap();
+            persistence.persist(entry(CacheKind::Circuit, [3u8; 32]), b"circuit").unwrap();
+        }
+
+        let persistence = CachePersistence::open(dir.path()).unwrap();
+        let proofs = persistence.load(CacheKind::Proof);
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].1, b"proof");
+        assert_eq!(proofs[0].0.key_bytes(), Some([2u8; 32]));
+    }
+
+    #[test]
+    fn test_corrupt_index_is_discarded() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(INDEX_FILE), b"{not json").unwrap();
+
+        let persistence = CachePersistence::open(dir.path()).unwrap();
+        assert!(persistence.load(CacheKind::Proof).is_empty());
+    }
+}
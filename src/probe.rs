@@ -0,0 +1,183 @@
+//! Health probing with a tiny built-in circuit
+//!
+//! Resource usage says nothing about whether the prover toolchain still works: a
+//! missing guest, a broken SDK install or a wedged prover all leave a backend idle
+//! and reporting itself healthy. A [`HealthProbe`] holds the outcome of running a
+//! small known-good program through the backend. The backends' probe task runs it
+//! every [`ProbePolicy::interval`], and `health_check()` reports the backend
+//! degraded after a failed probe and unhealthy once
+//! [`ProbePolicy::unhealthy_after`] probes in a row failed, with the failure reason.
+//!
+//! By default the probe executes a message verification program for
+//! [`PROBE_MESSAGE`]. RISC0 runs its embedded guest; SP1 runs the message
+//! verification guest of the program manifest, so SP1 backends without one should
+//! probe a program of their own.
+
+use std::time::{Duration, SystemTime};
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use frostgate_zkip::HealthStatus;
+
+use crate::descriptor::ProgramDescriptor;
+use crate::profiles::MESSAGE_VERIFY;
+
+/// Message the default probe program verifies
+pub const PROBE_MESSAGE: &[u8] = b"frostgate health probe";
+
+/// How far the probe runs its program
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProbeMode {
+    /// Execute the guest without proving, checking the guest and the executor
+    #[default]
+    Execute,
+    /// Prove the guest as well, bypassing the proof cache; set `RISC0_DEV_MODE` to
+    /// check the RISC0 pipeline without paying for a real proof
+    Prove,
+}
+
+/// What the probe runs and how often
+#[derive(Debug, Clone)]
+pub struct ProbePolicy {
+    /// Interval between probes
+    pub interval: Duration,
+    /// Probes running longer than this fail
+    pub timeout: Duration,
+    /// How far the probe runs its program
+    pub mode: ProbeMode,
+    /// Consecutive failed probes after which the backend is unhealthy
+    pub unhealthy_after: u32,
+    /// Program bytes
+    pub program: Vec<u8>,
+    /// Program input
+    pub input: Vec<u8>,
+}
+
+impl ProbePolicy {
+    /// Probe `program` with `input` in place of the built-in program
+    pub fn with_program(mut self, program: Vec<u8>, input: Vec<u8>) -> Self {
+        self.program = program;
+        self.input = input;
+        self
+    }
+}
+
+impl Default for ProbePolicy {
+    fn default() -> Self {
+        let program = ProgramDescriptor::new(MESSAGE_VERIFY, Sha256::digest(PROBE_MESSAGE).to_vec());
+        Self {
+            interval: Duration::from_secs(60),
+            timeout: Duration::from_secs(120),
+            mode: ProbeMode::Execute,
+            unhealthy_after: 3,
+            program: program.encode(),
+            input: PROBE_MESSAGE.to_vec(),
+        }
+    }
+}
+
+/// Outcome of one probe
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeResult {
+    /// Time the probe finished
+    pub checked_at: SystemTime,
+    /// Time the probe took
+    pub duration: Duration,
+    /// Failure reason, `None` if the probe passed
+    pub error: Option<String>,
+}
+
+/// Latest probe outcome of a backend
+#[derive(Debug)]
+pub struct HealthProbe {
+    policy: ProbePolicy,
+    state: Mutex<ProbeState>,
+}
+
+#[derive(Debug, Default)]
+struct ProbeState {
+    last: Option<ProbeResult>,
+    failures: u32,
+}
+
+impl HealthProbe {
+    /// Create a probe with the given policy
+    pub fn new(policy: ProbePolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::new(ProbeState::default()),
+        }
+    }
+
+    /// Probe policy
+    pub fn policy(&self) -> &ProbePolicy {
+        &self.policy
+    }
+
+    /// Record the outcome of a probe taking `duration`
+    pub fn record(&self, duration: Duration, outcome: Result<(), String>) -> ProbeResult {
+        let mut state = self.state.lock();
+        state.failures = match outcome {
+            Ok(()) => 0,
+            Err(_) => state.failures.saturating_add(1),
+        };
+        let result = ProbeResult {
+            checked_at: SystemTime::now(),
+            duration,
+            error: outcome.err(),
+        };
+        state.last = Some(result.clone());
+        result
+    }
+
+    /// Outcome of the latest probe, `None` before the first one finished
+    pub fn last(&self) -> Option<ProbeResult> {
+        self.state.lock().last.clone()
+    }
+
+    /// Number of probes in a row that failed
+    pub fn consecutive_failures(&self) -> u32 {
+        self.state.lock().failures
+    }
+
+    /// Health the probes call for, `None` unless the latest probe failed
+    pub fn status(&self) -> Option<HealthStatus> {
+        let state = self.state.lock();
+        let error = state.last.as_ref()?.error.as_ref()?;
+        Some(if state.failures >= self.policy.unhealthy_after {
+            HealthStatus::Unhealthy(format!("Health probe failed {} times in a row: {}", state.failures, error))
+        } else {
+            HealthStatus::Degraded(format!("Health probe failed: {}", error))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_probes_escalate() {
+        let probe = HealthProbe::new(ProbePolicy { unhealthy_after: 2, ..ProbePolicy::default() });
+        assert!(probe.status().is_none());
+
+        probe.record(Duration::from_millis(5), Err("guest missing".into()));
+        assert!(matches!(probe.status(), Some(HealthStatus::Degraded(reason)) if reason.contains("guest missing")));
+        probe.record(Duration::from_millis(5), Err("guest missing".into()));
+        assert!(matches!(probe.status(), Some(HealthStatus::Unhealthy(_))));
+        assert_eq!(probe.consecutive_failures(), 2);
+
+        // A passing probe clears the failures
+        let result = probe.record(Duration::from_millis(5), Ok(()));
+        assert_eq!(result.error, None);
+        assert!(probe.status().is_none());
+        assert_eq!(probe.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn test_default_probe_program() {
+        let policy = ProbePolicy::default();
+        let descriptor = ProgramDescriptor::decode(&policy.program).unwrap();
+        assert_eq!(descriptor.circuit_type, MESSAGE_VERIFY);
+        assert_eq!(descriptor.public_params, Sha256::digest(&policy.input).to_vec());
+    }
+}
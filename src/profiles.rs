@@ -0,0 +1,201 @@
+//! Per-circuit configuration profiles
+//!
+//! Circuits have very different resource needs: `block_verify` runs for far longer
+//! and needs more memory than `message_verify`. A [`CircuitProfile`] registered for a
+//! circuit type is applied automatically whenever that circuit is proven, so callers
+//! don't have to pass per-call configuration.
+
+use std::collections::HashMap;
+use std::time::Duration;
+use parking_lot::RwLock;
+use serde::{Serialize, Deserialize};
+
+use crate::descriptor;
+use crate::hasher::HashFunction;
+use crate::metadata::ProofMode;
+
+/// Circuit type identifier for message verification
+pub const MESSAGE_VERIFY: u8 = 0x01;
+/// Circuit type identifier for transaction verification
+pub const TX_VERIFY: u8 = 0x02;
+/// Circuit type identifier for block verification
+pub const BLOCK_VERIFY: u8 = 0x03;
+/// Circuit type identifier for message aggregation
+pub const MESSAGE_AGGREGATE: u8 = 0x04;
+/// Circuit type identifier for composition of message verification receipts (RISC0)
+pub const MESSAGE_COMPOSE: u8 = 0x05;
+/// Circuit type identifier for Ethereum receipt inclusion (SP1), see [`crate::chains::eth`]
+pub const ETH_VERIFY: u8 = 0x06;
+/// Circuit type identifier for GRANDPA finality (SP1), see [`crate::chains::dot`]
+pub const DOT_VERIFY: u8 = 0x07;
+/// Circuit type identifier for Solana slot confirmation (SP1), see [`crate::chains::sol`]
+pub const SOL_VERIFY: u8 = 0x08;
+/// Circuit type identifier for RLP-encoded Ethereum header verification (RISC0), see
+/// [`crate::chains::eth::EthHeader`]
+pub const ETH_HEADER_VERIFY: u8 = 0x09;
+/// Circuit type identifier for beacon chain light-client updates (SP1), see [`crate::chains::beacon`]
+pub const BEACON_VERIFY: u8 = 0x0A;
+/// Circuit type identifier for Ethereum account and storage proofs (SP1), see [`crate::chains::state`]
+pub const STATE_PROOF: u8 = 0x0B;
+/// Circuit type identifier for Ethereum event log inclusion (SP1), see [`crate::chains::eth::verify_log`]
+pub const ETH_LOG_VERIFY: u8 = 0x0C;
+/// Circuit type identifier for EIP-712 typed data signatures (SP1), see [`crate::signatures::eip712`]
+pub const EIP712_VERIFY: u8 = 0x0D;
+/// Circuit type identifier for secp256k1 ECDSA signatures, see [`crate::signatures::ecdsa`]
+pub const ECDSA_VERIFY: u8 = 0x0E;
+/// Circuit type identifier for Tendermint / CometBFT commits (SP1), see [`crate::chains::tm`]
+pub const TM_VERIFY: u8 = 0x0F;
+/// Circuit type identifier for chains of consecutive Ethereum headers (SP1), see
+/// [`crate::chains::eth::verify_header_chain`]
+pub const ETH_HEADER_CHAIN: u8 = 0x10;
+/// Circuit type identifier for Poseidon message commitments (SP1), see [`crate::commitment`]
+pub const POSEIDON_COMMIT: u8 = 0x11;
+/// Circuit type identifier for canonical message events (SP1), see [`crate::message`]
+pub const MESSAGE_EVENT: u8 = 0x12;
+/// Circuit type identifier for binary Merkle tree inclusion (SP1), see [`crate::inclusion`]
+pub const MERKLE_INCLUSION: u8 = 0x13;
+/// Circuit type identifier for guest programs registered by applications, see [`crate::guest`]
+pub const USER_PROGRAM: u8 = 0xF0;
+
+/// Scheduling priority class
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PriorityClass {
+    /// Background work such as re-proofs
+    Background,
+    /// Regular proving traffic
+    Normal,
+    /// Latency-sensitive proofs such as finality proofs
+    Urgent,
+}
+
+impl Default for PriorityClass {
+    fn default() -> Self {
+        PriorityClass::Normal
+    }
+}
+
+/// Resource profile applied to every proof of a circuit type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitProfile {
+    /// Default proof mode
+    pub proof_mode: ProofMode,
+    /// Maximum segment size as a power of two cycles (RISC0), bounding the memory a
+    /// proof needs
+    pub segment_limit_po2: Option<u32>,
+    /// Maximum proving time
+    pub timeout: Option<Duration>,
+    /// Scheduling priority class
+    pub priority: PriorityClass,
+    /// Time cached proofs stay valid
+    pub cache_ttl: Option<Duration>,
+    /// Hash function the target chain identifies programs with
+    #[serde(default)]
+    pub hash_function: HashFunction,
+}
+
+impl Default for CircuitProfile {
+    fn default() -> Self {
+        Self {
+            proof_mode: ProofMode::Core,
+            segment_limit_po2: None,
+            timeout: None,
+            priority: PriorityClass::Normal,
+            cache_ttl: None,
+            hash_function: HashFunction::Sha256,
+        }
+    }
+}
+
+/// Registry of circuit profiles keyed by circuit type identifier
+///
+/// Circuit types without a registered profile use the backend configuration.
+#[derive(Debug)]
+pub struct ProfileRegistry {
+    profiles: RwLock<HashMap<u8, CircuitProfile>>,
+}
+
+impl ProfileRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            profiles: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Create a registry with profiles for the built-in circuits
+    pub fn with_builtin_profiles() -> Self {
+        let registry = Self::new();
+        registry.register(MESSAGE_VERIFY, CircuitProfile {
+            timeout: Some(Duration::from_secs(10 * 60)),
+            ..CircuitProfile::default()
+        });
+        registry.register(TX_VERIFY, CircuitProfile {
+            timeout: Some(Duration::from_secs(15 * 60)),
+            ..CircuitProfile::default()
+        });
+        registry.register(BLOCK_VERIFY, CircuitProfile {
+            segment_limit_po2: Some(21),
+            timeout: Some(Duration::from_secs(60 * 60)),
+            cache_ttl: Some(Duration::from_secs(6 * 3600)),
+            ..CircuitProfile::default()
+        });
+        registry
+    }
+
+    /// Register or replace the profile for a circuit type
+    pub fn register(&self, circuit_type: u8, profile: CircuitProfile) {
+        self.profiles.write().insert(circuit_type, profile);
+    }
+
+    /// Remove the profile for a circuit type
+    pub fn unregister(&self, circuit_type: u8) -> Option<CircuitProfile> {
+        self.profiles.write().remove(&circuit_type)
+    }
+
+    /// Get the profile for a circuit type
+    pub fn get(&self, circuit_type: u8) -> Option<CircuitProfile> {
+        self.profiles.read().get(&circuit_type).cloned()
+    }
+
+    /// Get the profile for the circuit type encoded in a program
+    pub fn for_program(&self, program: &[u8]) -> Option<CircuitProfile> {
+        descriptor::circuit_type(program).and_then(|circuit_type| self.get(circuit_type))
+    }
+
+    /// Hex-encoded hash of a program under the hash function of its profile
+    ///
+    /// Proof metadata, events and per-program statistics identify programs by it.
+    pub fn program_hash(&self, program: &[u8]) -> String {
+        let hash_function = self.for_program(program)
+            .map(|profile| profile.hash_function)
+            .unwrap_or_default();
+        hex::encode(hash_function.hash(program))
+    }
+}
+
+impl Default for ProfileRegistry {
+    fn default() -> Self {
+        Self::with_builtin_profiles()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_profiles() {
+        let registry = ProfileRegistry::default();
+        let block = registry.get(BLOCK_VERIFY).unwrap();
+        let message = registry.get(MESSAGE_VERIFY).unwrap();
+        assert!(block.timeout > message.timeout);
+        assert_eq!(block.segment_limit_po2, Some(21));
+    }
+
+    #[test]
+    fn test_unregistered_profile() {
+        let registry = ProfileRegistry::default();
+        assert!(registry.for_program(&[0xFF]).is_none());
+        assert!(registry.for_program(&[]).is_none());
+    }
+}
@@ -0,0 +1,396 @@
+//! Versioned guest program registry
+//!
+//! Guest programs can be upgraded at runtime. Registering a new version of a
+//! program routes new requests to it, while jobs already pinned to the previous
+//! version hold a [`ProgramLease`] and run to completion. Superseded versions stay
+//! verifiable for a configurable overlap window so proofs produced just before an
+//! upgrade are still accepted.
+//!
+//! With an [`ArtifactStore`] attached, every registered ELF is also kept in the
+//! store and referenced until its version is pruned.
+//!
+//! A backend given a registry runs the guest of a built-in circuit type from the
+//! active version registered under [`circuit_program_name`], holding a lease while
+//! it proves, and accepts proofs of any version that is still verifiable. The
+//! backend also checks every registered ELF against its image ID.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+use parking_lot::RwLock;
+use tokio::sync::Notify;
+
+use crate::error::ZkError;
+use crate::profiles::{
+    BEACON_VERIFY, BLOCK_VERIFY, DOT_VERIFY, ECDSA_VERIFY, EIP712_VERIFY, ETH_HEADER_CHAIN, ETH_HEADER_VERIFY, ETH_LOG_VERIFY, ETH_VERIFY, MERKLE_INCLUSION, MESSAGE_AGGREGATE, MESSAGE_COMPOSE,
+    MESSAGE_EVENT, MESSAGE_VERIFY, POSEIDON_COMMIT, SOL_VERIFY, STATE_PROOF, TM_VERIFY, TX_VERIFY,
+};
+use crate::store::{ArtifactHash, ArtifactStore};
+
+/// Default time a superseded version stays verifiable
+pub const DEFAULT_OVERLAP_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Computes the image ID of a guest ELF: the RISC0 image ID or SP1 verifying key hash
+pub type ImageIdFn = Arc<dyn Fn(&[u8]) -> Result<[u8; 32], ZkError> + Send + Sync>;
+
+/// Name under which the versions of a built-in circuit type's guest are registered
+pub fn circuit_program_name(circuit_type: u8) -> Option<&'static str> {
+    Some(match circuit_type {
+        MESSAGE_VERIFY => "message_verify",
+        TX_VERIFY => "tx_verify",
+        BLOCK_VERIFY => "block_verify",
+        MESSAGE_AGGREGATE => "message_aggregate",
+        MESSAGE_COMPOSE => "message_compose",
+        ETH_VERIFY => "eth_verify",
+        DOT_VERIFY => "dot_verify",
+        SOL_VERIFY => "sol_verify",
+        ETH_HEADER_VERIFY => "eth_header_verify",
+        BEACON_VERIFY => "beacon_verify",
+        STATE_PROOF => "state_proof",
+        ETH_LOG_VERIFY => "eth_log_verify",
+        EIP712_VERIFY => "eip712_verify",
+        ECDSA_VERIFY => "ecdsa_verify",
+        TM_VERIFY => "tm_verify",
+        ETH_HEADER_CHAIN => "eth_header_chain",
+        POSEIDON_COMMIT => "poseidon_commit",
+        MESSAGE_EVENT => "message_event",
+        MERKLE_INCLUSION => "merkle_inclusion",
+        _ => return None,
+    })
+}
+
+/// A registered version of a guest program
+#[derive(Debug, Clone)]
+pub struct ProgramVersion {
+    /// Program name
+    pub name: String,
+    /// Version number, starting at 1
+    pub version: u32,
+    /// Guest ELF
+    pub elf: Arc<[u8]>,
+    /// Content hash of the ELF
+    pub elf_hash: ArtifactHash,
+    /// RISC0 image ID or SP1 verifying key hash
+    pub image_id: [u8; 32],
+    /// Time the version was registered
+    pub registered_at: SystemTime,
+    /// Time the version was superseded by a newer one
+    pub superseded_at: Option<SystemTime>,
+}
+
+#[derive(Debug, Default)]
+struct InFlight {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+#[derive(Debug)]
+struct VersionEntry {
+    version: ProgramVersion,
+    in_flight: Arc<InFlight>,
+}
+
+/// Handle pinning a job to a program version
+///
+/// The version cannot finish draining while a lease for it is alive.
+#[derive(Debug)]
+pub struct ProgramLease {
+    version: ProgramVersion,
+    in_flight: Arc<InFlight>,
+}
+
+impl ProgramLease {
+    /// Program version the job is pinned to
+    pub fn version(&self) -> &ProgramVersion {
+        &self.version
+    }
+}
+
+impl Drop for ProgramLease {
+    fn drop(&mut self) {
+        if self.in_flight.count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.in_flight.idle.notify_waiters();
+        }
+    }
+}
+
+/// Registry of guest program versions keyed by program name
+pub struct ProgramRegistry {
+    programs: RwLock<HashMap<String, BTreeMap<u32, VersionEntry>>>,
+    overlap: Duration,
+    store: Option<Arc<ArtifactStore>>,
+    image_id: Option<ImageIdFn>,
+}
+
+impl ProgramRegistry {
+    /// Create an empty registry with the default overlap window
+    pub fn new() -> Self {
+        Self::with_overlap(DEFAULT_OVERLAP_WINDOW)
+    }
+
+    /// Create an empty registry keeping superseded versions verifiable for `overlap`
+    pub fn with_overlap(overlap: Duration) -> Self {
+        Self {
+            programs: RwLock::new(HashMap::new()),
+            overlap,
+            store: None,
+            image_id: None,
+        }
+    }
+
+    /// Keep registered ELFs in an artifact store
+    pub fn with_store(mut self, store: Arc<ArtifactStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Check the image ID of every version registered from now on against its ELF
+    ///
+    /// Backends set this when they are given the registry.
+    pub fn with_image_id(mut self, image_id: ImageIdFn) -> Self {
+        self.image_id = Some(image_id);
+        self
+    }
+
+    /// Register a new version of a program and route new requests to it
+    ///
+    /// Returns the assigned version number. Fails without registering anything if
+    /// the ELF does not have `image_id`.
+    pub fn register_version(&self, name: &str, elf: Vec<u8>, image_id: [u8; 32]) -> Result<u32, ZkError> {
+        if let Some(compute) = &self.image_id {
+            let found = compute(&elf)?;
+            if found != image_id {
+                return Err(ZkError::ImageMismatch {
+                    expected: hex::encode(image_id),
+                    found: hex::encode(found),
+                });
+            }
+        }
+        let elf_hash = match &self.store {
+            Some(store) => store.put_retained(&elf)?,
+            None => ArtifactStore::hash(&elf),
+        };
+        let now = SystemTime::now();
+        let mut programs = self.programs.write();
+        let versions = programs.entry(name.to_string()).or_default();
+
+        if let Some((_, current)) = versions.iter_mut().next_back() {
+            current.version.superseded_at = Some(now);
+        }
+        let version = versions.keys().next_back().map_or(1, |version| version + 1);
+        versions.insert(version, VersionEntry {
+            version: ProgramVersion {
+                name: name.to_string(),
+                version,
+                elf: elf.into(),
+                elf_hash,
+                image_id,
+                registered_at: now,
+                superseded_at: None,
+            },
+            in_flight: Arc::new(InFlight::default()),
+        });
+        Ok(version)
+    }
+
+    /// Get the version new requests are routed to
+    pub fn active(&self, name: &str) -> Option<ProgramVersion> {
+        self.programs.read()
+            .get(name)
+            .and_then(|versions| versions.values().next_back())
+            .map(|entry| entry.version.clone())
+    }
+
+    /// Get a specific version of a program
+    pub fn get(&self, name: &str, version: u32) -> Option<ProgramVersion> {
+        self.programs.read()
+            .get(name)
+            .and_then(|versions| versions.get(&version))
+            .map(|entry| entry.version.clone())
+    }
+
+    /// Versions whose proofs are still accepted, newest first
+    pub fn verifiable(&self, name: &str) -> Vec<ProgramVersion> {
+        self.programs.read()
+            .get(name)
+            .map(|versions| versions.values()
+                .rev()
+                .filter(|entry| match entry.version.superseded_at {
+                    None => true,
+                    Some(superseded_at) => superseded_at.elapsed().unwrap_or_default() < self.overlap,
+                })
+                .map(|entry| entry.version.clone())
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// Pin a job to the active version of a program
+    pub fn lease(&self, name: &str) -> Result<ProgramLease, ZkError> {
+        let programs = self.programs.read();
+        let entry = programs.get(name)
+            .and_then(|versions| versions.values().next_back())
+            .ok_or_else(|| ZkError::UnknownProgram(name.to_string()))?;
+        entry.in_flight.count.fetch_add(1, Ordering::AcqRel);
+        Ok(ProgramLease {
+            version: entry.version.clone(),
+            in_flight: entry.in_flight.clone(),
+        })
+    }
+
+    /// Number of jobs pinned to a version
+    pub fn in_flight(&self, name: &str, version: u32) -> usize {
+        self.programs.read()
+            .get(name)
+            .and_then(|versions| versions.get(&version))
+            .map_or(0, |entry| entry.in_flight.count.load(Ordering::Acquire))
+    }
+
+    /// Wait until no jobs are pinned to a version
+    pub async fn drain(&self, name: &str, version: u32) {
+        let in_flight = match self.programs.read()
+            .get(name)
+            .and_then(|versions| versions.get(&version))
+        {
+            Some(entry) => entry.in_flight.clone(),
+            None => return,
+        };
+
+        loop {
+            let idle = in_flight.idle.notified();
+            tokio::pin!(idle);
+            idle.as_mut().enable();
+            if in_flight.count.load(Ordering::Acquire) == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+
+    /// Whether proofs produced by a version are still accepted
+    pub fn is_verifiable(&self, name: &str, version: u32) -> Result<bool, ZkError> {
+        let version = self.get(name, version)
+            .ok_or_else(|| ZkError::UnknownProgram(name.to_string()))?;
+        Ok(match version.superseded_at {
+            None => true,
+            Some(superseded_at) => superseded_at.elapsed().unwrap_or_default() < self.overlap,
+        })
+    }
+
+    /// Remove superseded versions that are drained and past the overlap window
+    ///
+    /// Returns the number of versions removed.
+    pub fn prune(&self) -> usize {
+        let mut programs = self.programs.write();
+        let mut removed = Vec::new();
+        for versions in programs.values_mut() {
+            versions.retain(|_, entry| {
+                let keep = match entry.version.superseded_at {
+                    None => true,
+                    Some(superseded_at) => {
+                        superseded_at.elapsed().unwrap_or_default() < self.overlap
+                            || entry.in_flight.count.load(Ordering::Acquire) > 0
+                    }
+                };
+                if !keep {
+                    removed.push(entry.version.elf_hash);
+                }
+                keep
+            });
+        }
+
+        // Release the ELFs of pruned versions
+        if let Some(store) = &self.store {
+            for elf_hash in &removed {
+                if let Err(e) = store.release(elf_hash) {
+                    tracing::warn!(hash = %hex::encode(elf_hash), "failed to release artifact: {}", e);
+                }
+            }
+        }
+        removed.len()
+    }
+}
+
+impl Default for ProgramRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ProgramRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgramRegistry")
+            .field("programs", &self.programs.read().keys().collect::<Vec<_>>())
+            .field("overlap", &self.overlap)
+            .field("checks_image_id", &self.image_id.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_version_is_routed() {
+        let registry = ProgramRegistry::new();
+        assert_eq!(registry.register_version("message_verify", vec![1], [1u8; 32]).unwrap(), 1);
+        assert_eq!(registry.register_version("message_verify", vec![2], [2u8; 32]).unwrap(), 2);
+
+        let active = registry.active("message_verify").unwrap();
+        assert_eq!(active.version, 2);
+        assert_eq!(active.image_id, [2u8; 32]);
+
+        // The old version stays verifiable during the overlap window
+        assert!(registry.is_verifiable("message_verify", 1).unwrap());
+        let verifiable: Vec<u32> = registry.verifiable("message_verify").iter().map(|version| version.version).collect();
+        assert_eq!(verifiable, [2, 1]);
+    }
+
+    #[test]
+    fn test_image_id_checked_on_register() {
+        let registry = ProgramRegistry::new()
+            .with_image_id(Arc::new(|elf: &[u8]| Ok([elf[0]; 32])));
+        assert_eq!(registry.register_version("tx_verify", vec![1], [1u8; 32]).unwrap(), 1);
+
+        let result = registry.register_version("tx_verify", vec![2], [3u8; 32]);
+        assert!(matches!(result, Err(ZkError::ImageMismatch { .. })));
+        assert_eq!(registry.active("tx_verify").unwrap().version, 1);
+    }
+
+    #[tokio::test]
+    async fn test_drain_and_prune() {
+        let registry = ProgramRegistry::with_overlap(Duration::ZERO);
+        registry.register_version("tx_verify", vec![1], [1u8; 32]).unwrap();
+        let lease = registry.lease("tx_verify").unwrap();
+        registry.register_version("tx_verify", vec![2], [2u8; 32]).unwrap();
+
+        assert_eq!(lease.version().version, 1);
+        assert_eq!(registry.in_flight("tx_verify", 1), 1);
+        assert!(!registry.is_verifiable("tx_verify", 1).unwrap());
+
+        // Pinned versions are not pruned
+        assert_eq!(registry.prune(), 0);
+
+        drop(lease);
+        registry.drain("tx_verify", 1).await;
+        assert_eq!(registry.prune(), 1);
+        assert!(registry.get("tx_verify", 1).is_none());
+    }
+
+    #[test]
+    fn test_pruned_elf_released_from_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(ArtifactStore::open(dir.path()).unwrap());
+        let registry = ProgramRegistry::with_overlap(Duration::ZERO).with_store(store.clone());
+
+        registry.register_version("block_verify", vec![1], [1u8; 32]).unwrap();
+        let old = registry.get("block_verify", 1).unwrap().elf_hash;
+        registry.register_version("block_verify", vec![2], [2u8; 32]).unwrap();
+        assert_eq!(store.ref_count(&old), 1);
+
+        registry.prune();
+        assert_eq!(store.ref_count(&old), 0);
+        assert_eq!(store.gc().unwrap(), 1);
+    }
+}
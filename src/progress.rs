@@ -0,0 +1,77 @@
+//! Progress reporting for long-running proofs
+//!
+//! A proof can take many minutes. `prove_with_progress` on either backend sends
+//! [`ProveProgress`] events over a channel while it runs, so operators can tell a
+//! slow proof from a wedged one. Which events are sent depends on the hooks the
+//! prover offers: SP1 reports setup, execution and the shard count once proving
+//! finishes, RISC0 reports execution and every segment as it is proven.
+
+use serde::{Serialize, Deserialize};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::metadata::ProofMode;
+
+/// Stage reached by a proof in progress
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProveProgress {
+    /// Proving and verifying keys are available
+    SetupDone {
+        /// Whether the keys came from the key cache
+        cached: bool,
+    },
+    /// The guest program was executed
+    Executed {
+        /// Number of cycles executed, if reported by the prover
+        cycles: Option<u64>,
+        /// Number of segments to prove, if known before proving
+        segments: Option<usize>,
+    },
+    /// Segments (RISC0) or shards (SP1) proven so far
+    SegmentsProven {
+        /// Segments proven
+        proven: usize,
+        /// Segments in total
+        total: usize,
+    },
+    /// The proof exceeded the size budget and is regenerated in a more succinct mode
+    Escalated {
+        /// Mode of the oversized proof
+        from: ProofMode,
+        /// Mode of the next attempt
+        to: ProofMode,
+    },
+    /// The proof was generated and serialized
+    Serialized {
+        /// Size of the serialized proof in bytes
+        proof_size: usize,
+    },
+}
+
+/// Destination of progress events, if anyone is listening
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Progress(Option<UnboundedSender<ProveProgress>>);
+
+impl Progress {
+    /// Report progress over `sender`
+    pub(crate) fn new(sender: UnboundedSender<ProveProgress>) -> Self {
+        Self(Some(sender))
+    }
+
+    /// Discard progress
+    pub(crate) fn none() -> Self {
+        Self(None)
+    }
+
+    /// Whether progress is observed, so that reporting it is worth extra work
+    pub(crate) fn is_observed(&self) -> bool {
+        self.0.as_ref().map_or(false, |sender| !sender.is_closed())
+    }
+
+    /// Report a progress event
+    pub(crate) fn report(&self, event: ProveProgress) {
+        if let Some(sender) = &self.0 {
+            // The receiver going away is not an error
+            let _ = sender.send(event);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Protobuf wire format of proofs, metadata and proving jobs
+//!
+//! `proto/frostgate.proto` gives Frostgate components not written in Rust, such as
+//! Go relayers and TypeScript frontends, a stable encoding of what this crate
+//! produces. The prost-generated types live in [`v1`]; this module converts them
+//! from and to the crate's own types. Decoding applies the same checks as the
+//! crate's native formats, so a protobuf envelope is as trustworthy as a binary one.
+
+use std::time::{Duration, UNIX_EPOCH};
+use frostgate_zkip::ProofMetadata;
+use prost::Message;
+
+use crate::envelope::{self, ProofEnvelope};
+use crate::error::ZkError;
+use crate::jobs::{JobId, JobReport, JobStatus};
+use crate::metadata::{ExtendedProofMetadata, ProofMode};
+
+/// Types generated from `proto/frostgate.proto`
+pub mod v1 {
+    include!(concat!(env!("OUT_DIR"), "/frostgate.v1.rs"));
+}
+
+/// Encode an envelope as a protobuf `ProofEnvelope`
+pub fn encode_envelope(envelope: &ProofEnvelope) -> Vec<u8> {
+    v1::ProofEnvelope::from(envelope).encode_to_vec()
+}
+
+/// Decode and check a protobuf `ProofEnvelope`
+pub fn decode_envelope(bytes: &[u8]) -> Result<ProofEnvelope, ZkError> {
+    let envelope = v1::ProofEnvelope::decode(bytes).map_err(invalid)?;
+    ProofEnvelope::try_from(envelope)
+}
+
+/// Job message reporting `report`
+pub fn job(job_id: JobId, report: &JobReport) -> v1::Job {
+    let millis = |time: std::time::SystemTime| time.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+    let mut job = v1::Job {
+        job_id: job_id.to_string(),
+        submitted_ms: millis(report.submitted),
+        started_ms: report.started.map(millis),
+        finished_ms: report.finished.map(millis),
+        ..Default::default()
+    };
+    let state = match &report.status {
+        JobStatus::Queued => v1::JobState::Queued,
+        JobStatus::Running => v1::JobState::Running,
+        JobStatus::Completed { proof, metadata } => {
+            job.proof = proof.clone();
+            job.metadata = Some(metadata.into());
+            v1::JobState::Completed
+        }
+        JobStatus::Failed(error) => {
+            job.error = Some(error.clone());
+            v1::JobState::Failed
+        }
+    };
+    job.set_state(state);
+    job
+}
+
+impl From<ProofMode> for v1::ProofMode {
+    fn from(mode: ProofMode) -> Self {
+        match mode {
+            ProofMode::Core => v1::ProofMode::Core,
+            ProofMode::Compressed => v1::ProofMode::Compressed,
+            ProofMode::Plonk => v1::ProofMode::Plonk,
+            ProofMode::Groth16 => v1::ProofMode::Groth16,
+        }
+    }
+}
+
+impl TryFrom<v1::ProofMode> for ProofMode {
+    type Error = ZkError;
+
+    fn try_from(mode: v1::ProofMode) -> Result<Self, ZkError> {
+        match mode {
+            v1::ProofMode::Core => Ok(ProofMode::Core),
+            v1::ProofMode::Compressed => Ok(ProofMode::Compressed),
+            v1::ProofMode::Plonk => Ok(ProofMode::Plonk),
+            v1::ProofMode::Groth16 => Ok(ProofMode::Groth16),
+            v1::ProofMode::Unspecified => Err(invalid("unspecified proof mode")),
+        }
+    }
+}
+
+impl From<&ProofEnvelope> for v1::ProofEnvelope {
+    fn from(envelope: &ProofEnvelope) -> Self {
+        let mut message = Self {
+            version: u32::from(envelope.version),
+            backend_id: envelope.backend_id.clone(),
+            sdk_version: envelope.sdk_version.clone(),
+            program_hash: envelope.program_hash.clone(),
+            public_values: envelope.public_values.clone(),
+            proof: envelope.proof_bytes.clone(),
+            ..Default::default()
+        };
+        message.set_proof_mode(envelope.proof_mode.into());
+        message
+    }
+}
+
+impl TryFrom<v1::ProofEnvelope> for ProofEnvelope {
+    type Error = ZkError;
+
+    fn try_from(message: v1::ProofEnvelope) -> Result<Self, ZkError> {
+        let version = u16::try_from(message.version).unwrap_or(u16::MAX);
+        envelope::check_version(version)?;
+        let envelope = ProofEnvelope {
+            version,
+            backend_id: message.backend_id,
+            sdk_version: message.sdk_version,
+            proof_mode: mode(message.proof_mode)?,
+            program_hash: message.program_hash,
+            public_values: message.public_values,
+            proof_bytes: message.proof,
+        };
+        envelope.check()?;
+        Ok(envelope)
+    }
+}
+
+impl From<&ProofMetadata> for v1::ProofMetadata {
+    fn from(metadata: &ProofMetadata) -> Self {
+        Self {
+            generation_time_ms: metadata.generation_time.as_millis() as u64,
+            proof_size: metadata.proof_size as u64,
+            program_hash: metadata.program_hash.clone(),
+            timestamp: metadata.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&ExtendedProofMetadata> for v1::ProofMetadata {
+    fn from(metadata: &ExtendedProofMetadata) -> Self {
+        let mut message = Self {
+            max_proof_size: metadata.max_proof_size.map(|size| size as u64),
+            backend: metadata.backend.clone(),
+            sdk_version: metadata.sdk_version.clone(),
+            circuit_type: metadata.circuit_type.map(u32::from),
+            circuit_version: metadata.circuit_version,
+            vkey_hash: metadata.vkey_hash.map(|hash| hash.to_vec()).unwrap_or_default(),
+            public_values: metadata.public_values.clone(),
+            cycles: metadata.cycles,
+            segments: metadata.segments.map(|segments| segments as u64),
+            peak_memory: metadata.peak_memory.map(|memory| memory as u64),
+            ..Self::from(&metadata.base)
+        };
+        message.set_proof_mode(metadata.proof_mode.into());
+        message.set_requested_mode(metadata.requested_mode.into());
+        message
+    }
+}
+
+impl TryFrom<v1::ProofMetadata> for ExtendedProofMetadata {
+    type Error = ZkError;
+
+    fn try_from(message: v1::ProofMetadata) -> Result<Self, ZkError> {
+        let proof_mode = mode(message.proof_mode)?;
+        // Metadata of proofs that were not escalated may leave the requested mode out
+        let requested_mode = match message.requested_mode {
+            0 => proof_mode,
+            requested_mode => mode(requested_mode)?,
+        };
+        let vkey_hash = match message.vkey_hash.len() {
+            0 => None,
+            32 => message.vkey_hash.as_slice().try_into().ok(),
+            len => return Err(invalid(format!("verifying key hash is {} bytes", len))),
+        };
+        let circuit_type = message.circuit_type
+            .map(|circuit_type| u8::try_from(circuit_type).map_err(|_| invalid(format!("circuit type {}", circuit_type))))
+            .transpose()?;
+        Ok(ExtendedProofMetadata {
+            base: ProofMetadata {
+                generation_time: Duration::from_millis(message.generation_time_ms),
+                proof_size: message.proof_size as usize,
+                program_hash: message.program_hash,
+                timestamp: UNIX_EPOCH + Duration::from_secs(message.timestamp),
+            },
+            proof_mode,
+            requested_mode,
+            max_proof_size: message.max_proof_size.map(|size| size as usize),
+            backend: message.backend,
+            sdk_version: message.sdk_version,
+            circuit_type,
+            circuit_version: message.circuit_version,
+            vkey_hash,
+            public_values: message.public_values,
+            cycles: message.cycles,
+            segments: message.segments.map(|segments| segments as usize),
+            peak_memory: message.peak_memory.map(|memory| memory as usize),
+        })
+    }
+}
+
+/// Proof mode of a wire enum value
+fn mode(value: i32) -> Result<ProofMode, ZkError> {
+    let mode = v1::ProofMode::try_from(value).map_err(|_| invalid(format!("unknown proof mode {}", value)))?;
+    ProofMode::try_from(mode)
+}
+
+fn invalid(reason: impl std::fmt::Display) -> ZkError {
+    ZkError::InvalidInput(format!("Invalid protobuf message: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let envelope = ProofEnvelope::new("sp1", "5.0.0", ProofMode::Groth16, hex::encode([7u8; 32]), vec![1, 2], vec![3, 4, 5]);
+        assert_eq!(decode_envelope(&encode_envelope(&envelope)).unwrap(), envelope);
+
+        // Checked as binary envelopes are
+        let mut message = v1::ProofEnvelope::from(&envelope);
+        message.proof_mode = v1::ProofMode::Unspecified as i32;
+        assert!(decode_envelope(&message.encode_to_vec()).is_err());
+        let mut message = v1::ProofEnvelope::from(&envelope);
+        message.version = 3;
+        assert!(matches!(
+            decode_envelope(&message.encode_to_vec()),
+            Err(ZkError::UnsupportedEnvelopeVersion { version: 3, .. })
+        ));
+        let mut message = v1::ProofEnvelope::from(&envelope);
+        message.proof.clear();
+        assert!(decode_envelope(&message.encode_to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_metadata_roundtrip() {
+        let message = v1::ProofMetadata {
+            proof_size: 3,
+            program_hash: hex::encode([1u8; 32]),
+            timestamp: 1_700_000_000,
+            proof_mode: v1::ProofMode::Compressed as i32,
+            backend: "risc0".into(),
+            circuit_type: Some(0x01),
+            vkey_hash: vec![2; 32],
+            cycles: Some(1_000),
+            ..Default::default()
+        };
+        let metadata = ExtendedProofMetadata::try_from(message.clone()).unwrap();
+        assert_eq!(metadata.proof_mode, ProofMode::Compressed);
+        assert_eq!(metadata.requested_mode, ProofMode::Compressed);
+        assert_eq!(metadata.vkey_hash, Some([2; 32]));
+        assert_eq!(v1::ProofMetadata::from(&metadata), v1::ProofMetadata {
+            requested_mode: v1::ProofMode::Compressed as i32,
+            ..message.clone()
+        });
+
+        let truncated = v1::ProofMetadata { vkey_hash: vec![2; 31], ..message };
+        assert!(ExtendedProofMetadata::try_from(truncated).is_err());
+    }
+}
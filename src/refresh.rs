@@ -0,0 +1,205 @@
+//! Background refresh of expiring cached proofs
+//!
+//! Cached proofs expire after their time to live, and the next request for the same
+//! (program, input) pair then pays the full proving time. [`RefreshTracker`]
+//! remembers which pairs are still being requested so the backends' refresh task
+//! can regenerate their proofs at low priority shortly before they expire. Failed
+//! refreshes back off exponentially.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+
+use crate::domain::DomainSeparator;
+
+/// When and how eagerly expiring proofs are refreshed
+#[derive(Debug, Clone)]
+pub struct RefreshPolicy {
+    /// Refresh proofs expiring within this window
+    pub window: Duration,
+    /// Pairs not requested for this long are no longer refreshed
+    pub relevance: Duration,
+    /// Minimum number of requests before a pair is refreshed
+    pub min_hits: u64,
+    /// Interval between refresh passes
+    pub interval: Duration,
+    /// Backoff after the first failed refresh, doubling with every further failure
+    pub initial_backoff: Duration,
+    /// Maximum backoff between refresh attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RefreshPolicy {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(60),
+            relevance: Duration::from_secs(600),
+            min_hits: 2,
+            interval: Duration::from_secs(15),
+            initial_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(1800),
+        }
+    }
+}
+
+/// A (program, input) pair that may be refreshed
+#[derive(Debug, Clone)]
+pub struct RefreshCandidate {
+    /// Program bytes
+    pub program: Vec<u8>,
+    /// Program input
+    pub input: Vec<u8>,
+    /// Domain the proof is bound to
+    pub domain: DomainSeparator,
+    /// Number of requests for the pair
+    pub hits: u64,
+    /// Time of the last request
+    pub last_hit: SystemTime,
+    /// Consecutive failed refreshes
+    pub failures: u32,
+    /// Time before which the pair is not retried
+    pub retry_at: Option<SystemTime>,
+}
+
+impl RefreshCandidate {
+    /// Input the proof is cached under
+    pub fn cache_input(&self) -> Vec<u8> {
+        self.domain.cache_input(&self.input)
+    }
+}
+
+/// Tracks recently requested pairs and decides which are due for a refresh
+#[derive(Debug)]
+pub struct RefreshTracker {
+    policy: RefreshPolicy,
+    candidates: Mutex<HashMap<[u8; 32], RefreshCandidate>>,
+}
+
+impl RefreshTracker {
+    /// Create a tracker with the given policy
+    pub fn new(policy: RefreshPolicy) -> Self {
+        Self {
+            policy,
+            candidates: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Refresh policy
+    pub fn policy(&self) -> &RefreshPolicy {
+        &self.policy
+    }
+
+    fn key(program: &[u8], input: &[u8], domain: DomainSeparator) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update((program.len() as u64).to_le_bytes());
+        hasher.update(program);
+        hasher.update(domain.to_bytes());
+        hasher.update(input);
+        hasher.finalize().into()
+    }
+
+    /// Record a request for a pair
+    pub fn record_hit(&self, program: &[u8], input: &[u8], domain: DomainSeparator) {
+        let key = Self::key(program, input, domain);
+        let mut candidates = self.candidates.lock();
+        let candidate = candidates.entry(key).or_insert_with(|| RefreshCandidate {
+            program: program.to_vec(),
+            input: input.to_vec(),
+            domain,
+            hits: 0,
+            last_hit: SystemTime::now(),
+            failures: 0,
+            retry_at: None,
+        });
+        candidate.hits += 1;
+        candidate.last_hit = SystemTime::now();
+    }
+
+    /// Pairs due for a refresh, given the time until each cached proof expires
+    ///
+    /// Pairs that are no longer relevant are forgotten. A pair whose proof is not
+    /// cached at all is due as well.
+    pub fn due(&self, expires_in: impl Fn(&RefreshCandidate) -> Option<Duration>) -> Vec<RefreshCandidate> {
+        let now = SystemTime::now();
+        let mut candidates = self.candidates.lock();
+        candidates.retain(|_, candidate| {
+            now.duration_since(candidate.last_hit).unwrap_or_default() < self.policy.relevance
+        });
+        candidates.values()
+            .filter(|candidate| candidate.hits >= self.policy.min_hits)
+            .filter(|candidate| candidate.retry_at.map_or(true, |retry_at| retry_at <= now))
+            .filter(|candidate| expires_in(candidate).map_or(true, |remaining| remaining <= self.policy.window))
+            .cloned()
+            .collect()
+    }
+
+    /// Record a successful refresh
+    pub fn record_success(&self, candidate: &RefreshCandidate) {
+        let key = Self::key(&candidate.program, &candidate.input, candidate.domain);
+        if let Some(candidate) = self.candidates.lock().get_mut(&key) {
+            candidate.failures = 0;
+            candidate.retry_at = None;
+        }
+    }
+
+    /// Record a failed refresh and back off
+    pub fn record_failure(&self, candidate: &RefreshCandidate) {
+        let key = Self::key(&candidate.program, &candidate.input, candidate.domain);
+        if let Some(candidate) = self.candidates.lock().get_mut(&key) {
+            let backoff = self.policy.initial_backoff
+                .saturating_mul(2u32.saturating_pow(candidate.failures))
+                .min(self.policy.max_backoff);
+            candidate.failures += 1;
+            candidate.retry_at = Some(SystemTime::now() + backoff);
+        }
+    }
+
+    /// Number of tracked pairs
+    pub fn len(&self) -> usize {
+        self.candidates.lock().len()
+    }
+
+    /// Whether no pairs are tracked
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_hot_expiring_pairs_are_due() {
+        let tracker = RefreshTracker::new(RefreshPolicy::default());
+        let domain = DomainSeparator::UNBOUND;
+        tracker.record_hit(b"program", b"hot", domain);
+        tracker.record_hit(b"program", b"hot", domain);
+        tracker.record_hit(b"program", b"cold", domain);
+
+        // Far from expiry, nothing is due
+        assert!(tracker.due(|_| Some(Duration::from_secs(3600))).is_empty());
+
+        // Close to expiry, only the pair requested often enough is due
+        let due = tracker.due(|_| Some(Duration::from_secs(1)));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].input, b"hot");
+    }
+
+    #[test]
+    fn test_failed_refresh_backs_off() {
+        let tracker = RefreshTracker::new(RefreshPolicy {
+            min_hits: 1,
+            ..RefreshPolicy::default()
+        });
+        tracker.record_hit(b"program", b"input", DomainSeparator::UNBOUND);
+        let candidate = tracker.due(|_| None).pop().unwrap();
+
+        tracker.record_failure(&candidate);
+        assert!(tracker.due(|_| None).is_empty());
+
+        tracker.record_success(&candidate);
+        assert_eq!(tracker.due(|_| None).len(), 1);
+    }
+}
@@ -0,0 +1,151 @@
+//! Replay protection for message proofs
+//!
+//! A valid proof of a message stays valid forever, so a bridge that acts on every
+//! proof it verifies would deliver a message again each time its proof is
+//! resubmitted. The message event guest commits the source chain and nonce of its
+//! [`MessageEvent`](crate::message::MessageEvent), and the message verification,
+//! aggregation and composition guests the [`MessageNonce`] of their program; a
+//! [`ReplayGuard`] remembers the (source chain, nonce) pairs of accepted proofs and
+//! rejects any later proof of the same pair. Backends configured with a guard
+//! consult it after every successful verification, single or batched, see
+//! `Sp1Backend::with_replay_guard` and `Risc0Backend::with_replay_guard`.
+
+use std::collections::HashSet;
+use parking_lot::Mutex;
+
+use crate::domain::{ChainId, MessageNonce};
+use crate::error::ZkError;
+use crate::journal::{MessageAggregateOutput, MessageEventOutput, MessageVerifyOutput};
+use crate::profiles::{MESSAGE_AGGREGATE, MESSAGE_COMPOSE, MESSAGE_EVENT, MESSAGE_VERIFY};
+
+/// Set of message nonces already accepted, per source chain
+#[derive(Debug, Default)]
+pub struct ReplayGuard {
+    accepted: Mutex<HashSet<(ChainId, u64)>>,
+}
+
+impl ReplayGuard {
+    /// Create a guard that has not accepted any message
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a message with `nonce` from `source_chain` was already accepted
+    pub fn is_accepted(&self, source_chain: ChainId, nonce: u64) -> bool {
+        self.accepted.lock().contains(&(source_chain, nonce))
+    }
+
+    /// Accept a message, rejecting it if its nonce was already accepted
+    pub fn accept(&self, source_chain: ChainId, nonce: u64) -> Result<(), ZkError> {
+        if !self.accepted.lock().insert((source_chain, nonce)) {
+            return Err(ZkError::Replayed { source_chain, nonce });
+        }
+        Ok(())
+    }
+
+    /// Accept `count` messages with consecutive nonces, the first one `first`
+    ///
+    /// Either all messages are accepted or, if any of them already was, none.
+    pub fn accept_range(&self, first: MessageNonce, count: u64) -> Result<(), ZkError> {
+        let nonces = || (0..count).map(|offset| first.nonce.wrapping_add(offset));
+        let mut accepted = self.accepted.lock();
+        if let Some(nonce) = nonces().find(|nonce| accepted.contains(&(first.source_chain, *nonce))) {
+            return Err(ZkError::Replayed { source_chain: first.source_chain, nonce });
+        }
+        accepted.extend(nonces().map(|nonce| (first.source_chain, nonce)));
+        Ok(())
+    }
+
+    /// Accept the messages committed in the public values of a verified proof
+    ///
+    /// Proofs of message circuits must commit a nonce; a message proof without one
+    /// could be delivered any number of times. Proofs of other circuits are not
+    /// subject to replay protection and are always accepted.
+    pub fn accept_public_values(&self, circuit_type: u8, public_values: &[u8]) -> Result<(), ZkError> {
+        let (nonce, count) = match circuit_type {
+            MESSAGE_EVENT => {
+                let event = MessageEventOutput::decode(public_values)?.statement.event;
+                return self.accept(event.source_chain, event.nonce);
+            }
+            MESSAGE_VERIFY => (MessageVerifyOutput::decode(public_values)?.nonce, 1),
+            MESSAGE_AGGREGATE | MESSAGE_COMPOSE => {
+                let output = MessageAggregateOutput::decode(public_values)?;
+                (output.nonce, output.count)
+            }
+            _ => return Ok(()),
+        };
+        let nonce = nonce.ok_or_else(|| {
+            ZkError::ProofVerification("Message proof commits no nonce to protect against replay".into())
+        })?;
+        self.accept_range(nonce, count)
+    }
+
+    /// Number of accepted messages
+    pub fn len(&self) -> usize {
+        self.accepted.lock().len()
+    }
+
+    /// Whether no message was accepted yet
+    pub fn is_empty(&self) -> bool {
+        self.accepted.lock().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{self, MessageEvent};
+
+    #[test]
+    fn test_replay_rejected() {
+        let guard = ReplayGuard::new();
+        guard.accept(ChainId(1), 7).unwrap();
+        assert!(guard.is_accepted(ChainId(1), 7));
+        assert!(matches!(guard.accept(ChainId(1), 7), Err(ZkError::Replayed { nonce: 7, .. })));
+
+        // Nonces are per source chain
+        guard.accept(ChainId(2), 7).unwrap();
+        assert_eq!(guard.len(), 2);
+    }
+
+    #[test]
+    fn test_accept_public_values() {
+        let event = MessageEvent {
+            source_chain: ChainId(1),
+            nonce: 3,
+            payload_hash: [0x11; 32],
+            destination: ChainId(10),
+        };
+        let public_values = message::verify(&event.to_bytes(), &event.domain()).unwrap().to_bytes();
+        let guard = ReplayGuard::new();
+        guard.accept_public_values(MESSAGE_EVENT, &public_values).unwrap();
+        assert!(guard.accept_public_values(MESSAGE_EVENT, &public_values).is_err());
+
+        // Message hashes must carry a nonce
+        assert!(guard.accept_public_values(MESSAGE_VERIFY, &[0x22; 32]).is_err());
+        let domain = crate::domain::DomainSeparator::new(ChainId(1), ChainId(10));
+        let nonce = MessageNonce::new(ChainId(1), 4);
+        let public_values = [&[0x22; 32][..], &nonce.to_bytes(), &domain.to_bytes()].concat();
+        guard.accept_public_values(MESSAGE_VERIFY, &public_values).unwrap();
+        assert!(guard.accept_public_values(MESSAGE_VERIFY, &public_values).is_err());
+        assert_eq!(guard.len(), 2);
+    }
+
+    #[test]
+    fn test_aggregate_accepts_every_message() {
+        let guard = ReplayGuard::new();
+        let mut public_values = vec![0x33; 32];
+        public_values.extend_from_slice(&3u64.to_le_bytes());
+        public_values.extend_from_slice(&MessageNonce::new(ChainId(1), 10).to_bytes());
+        public_values.extend_from_slice(&crate::domain::DomainSeparator::new(ChainId(1), ChainId(10)).to_bytes());
+        guard.accept_public_values(MESSAGE_AGGREGATE, &public_values).unwrap();
+        assert!((10..13).all(|nonce| guard.is_accepted(ChainId(1), nonce)));
+
+        // An overlapping range is rejected as a whole
+        assert!(matches!(
+            guard.accept_range(MessageNonce::new(ChainId(1), 12), 4),
+            Err(ZkError::Replayed { nonce: 12, .. })
+        ));
+        assert!(!guard.is_accepted(ChainId(1), 13));
+    }
+}
@@ -4,20 +4,27 @@
 
 //! RISC0 backend implementation
 
+use std::borrow::Cow;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::collections::HashMap;
 use std::time::{Duration, Instant, SystemTime};
 use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 use parking_lot::RwLock;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use lru::LruCache;
 use rayon::prelude::*;
 use futures::future::join_all;
 use serde::{Serialize, Deserialize};
 use risc0_zkvm::{
     ExecutorEnv, ExecutorEnvBuilder,
-    Receipt, ProverOpts,
+    Receipt,
     sha::Digest, Journal,
-    default_prover,
+    default_prover, compute_image_id, get_prover_server,
+    ExecutorImpl, VerifierContext, InnerReceipt, CompositeReceipt,
+    serde::to_vec,
 };
 use thiserror::Error;
 use async_trait::async_trait;
@@ -27,23 +34,57 @@ use frostgate_zkip::{
     HealthStatus, ProofMetadata, ResourceUsage, ZkConfig, ZkStats,
 };
 use bincode::{serialize, deserialize};
+use futures::stream::{FuturesUnordered, Stream};
 use futures::TryFutureExt;
+use tracing::Instrument;
 
-use super::types::{Risc0Circuit, Risc0Options};
-use super::circuit::MessageVerifyCircuit;
+use super::types::{Risc0Circuit, Risc0Options, ReceiptKind};
+use super::circuit::MessageComposeCircuit;
+use super::guests::{GuestElfs, GuestLoader, LoadedCircuit};
+use super::registry::CircuitRegistry;
 use super::cache::{CircuitCache, CacheConfig, CacheStats};
+use super::checkpoint::{ProvingCheckpoint, ProvingOutcome};
+use super::remote::{self, RemoteProving};
 
 use crate::error::ZkError as CustomZkError;
+use crate::descriptor::{self, ProgramDescriptor};
+use crate::domain::DomainSeparator;
+use crate::events::{BackendEvent, CacheKind, EventBus};
+use crate::guest::GuestPrograms;
+use crate::manifest::ProgramManifest;
+use crate::execution::{self, ExecutionReport};
+use crate::envelope::{self, ProofEnvelope};
+use crate::metadata::{ExtendedProofMetadata, ProofMode, ProvingRun};
+use crate::options::{bounded, CancellationToken, ProveOptions};
+use crate::profiles::{CircuitProfile, ProfileRegistry, MESSAGE_COMPOSE};
+use crate::programs::{self, ProgramRegistry};
+use crate::cluster::encode_shard;
+use crate::progress::{Progress, ProveProgress};
+use crate::probe::{HealthProbe, ProbeMode, ProbePolicy, ProbeResult};
+use crate::refresh::{RefreshPolicy, RefreshTracker};
+use crate::replay::ReplayGuard;
+use crate::singleflight::{flight_key, SingleFlight};
+use crate::sysmon;
+use crate::threads;
+use crate::stats::{FailureCategory, FailureStats, LatencyStats, ProgramStatsTracker, StatsSnapshot, DEFAULT_LATENCY_WINDOW};
+use crate::dashboard::BackendDashboard;
+use crate::info::BackendInfo;
+use crate::support::SupportBundle;
+use crate::vk::{ExportedKey, KeyKind};
 
 /// RISC0 backend configuration
 #[derive(Debug, Clone)]
 pub struct Risc0Config {
-    /// Maximum number of parallel proving threads
+    /// Maximum number of proofs generated, or batch proofs verified, at once
     pub max_threads: usize,
     /// Memory limit per proof in bytes
     pub memory_limit: usize,
     /// Whether to enable proof caching
     pub enable_cache: bool,
+    /// Remote proving service, if proofs should not be generated locally
+    pub remote: Option<RemoteProving>,
+    /// Pinned guest ELFs to load instead of the embedded ones
+    pub guests: Option<GuestElfs>,
 }
 
 impl Default for Risc0Config {
@@ -52,6 +93,8 @@ impl Default for Risc0Config {
             max_threads: 4,
             memory_limit: 1024 * 1024 * 1024, // 1GB
             enable_cache: true,
+            remote: None,
+            guests: None,
         }
     }
 }
@@ -69,12 +112,47 @@ pub struct Risc0Backend {
     options: Risc0Options,
     /// Circuit and proof cache
     cache: Arc<CircuitCache>,
+    /// Backend event bus
+    events: EventBus,
+    /// Last reported health status
+    last_health: RwLock<Option<String>>,
+    /// Per-circuit configuration profiles
+    profiles: Arc<ProfileRegistry>,
+    /// Failure counters per category
+    failures: Arc<FailureStats>,
+    /// Per-program proving statistics
+    program_stats: Arc<ProgramStatsTracker>,
+    /// Proving and verification latency histograms
+    latency: Arc<LatencyStats>,
+    /// Tracker of proofs to refresh before they expire, if enabled
+    refresh: Option<Arc<RefreshTracker>>,
+    /// Health probe consulted by `health_check()`, if enabled
+    probe: Option<Arc<HealthProbe>>,
+    /// Nonces of verified messages, rejecting replayed proofs if set
+    replay_guard: Option<Arc<ReplayGuard>>,
+    /// Proving runs shared by identical concurrent requests
+    in_flight: SingleFlight<(Vec<u8>, ExtendedProofMetadata)>,
+    /// Slots for concurrent local proofs and batch verifications
+    permits: Arc<Semaphore>,
+    /// Circuit constructors by circuit type
+    circuits: Arc<CircuitRegistry>,
+    /// Guest programs registered by applications
+    programs: Arc<GuestPrograms>,
+    /// Built-in guests loaded from the filesystem
+    guests: Arc<GuestLoader>,
+    /// Versions of built-in guests upgraded at runtime, if enabled
+    versions: Option<Arc<ProgramRegistry>>,
+    /// File the statistics are persisted to, if enabled
+    stats_path: Option<PathBuf>,
 }
 
 impl Risc0Backend {
     /// Create a new RISC0 backend
     pub fn new(config: Risc0Config) -> Self {
+        let programs = Arc::new(GuestPrograms::new());
+        let guests = Arc::new(GuestLoader::new(config.guests.clone().unwrap_or_default()));
         Self {
+            permits: Arc::new(Semaphore::new(config.max_threads.max(1))),
             config,
             stats: RwLock::new(ZkStats::default()),
             resources: Arc::new(RwLock::new(ResourceUsage {
@@ -88,18 +166,42 @@ impl Risc0Backend {
                 num_threads: Some(4),
                 memory_limit: Some(1024 * 1024 * 1024), // 1GB
                 custom_params: None,
+                receipt_kind: ReceiptKind::default(),
+                prove_timeout: None,
+                latency_window: None,
             },
             cache: Arc::new(CircuitCache::new(CacheConfig::default())),
+            events: EventBus::default(),
+            last_health: RwLock::new(None),
+            profiles: Arc::new(ProfileRegistry::default()),
+            failures: Arc::new(FailureStats::new()),
+            program_stats: Arc::new(ProgramStatsTracker::new()),
+            latency: Arc::new(LatencyStats::new()),
+            refresh: None,
+            probe: None,
+            replay_guard: None,
+            in_flight: SingleFlight::new(),
+            circuits: Arc::new(CircuitRegistry::with_guests(guests.clone()).with_programs(programs.clone())),
+            programs,
+            guests,
+            versions: None,
+            stats_path: None,
         }
     }
 
     /// Create a new RISC0 backend with custom configuration
     pub fn with_config(options: Risc0Options, cache_config: CacheConfig) -> Self {
+        let latency_window = options.latency_window.unwrap_or(DEFAULT_LATENCY_WINDOW);
+        let programs = Arc::new(GuestPrograms::new());
+        let guests = Arc::new(GuestLoader::default());
         Self {
+            permits: Arc::new(Semaphore::new(options.num_threads.unwrap_or(4).max(1))),
             config: Risc0Config {
                 max_threads: options.num_threads.unwrap_or(4),
                 memory_limit: options.memory_limit.unwrap_or(1024 * 1024 * 1024),
                 enable_cache: true,
+                remote: None,
+                guests: None,
             },
             stats: RwLock::new(ZkStats::default()),
             resources: Arc::new(RwLock::new(ResourceUsage {
@@ -111,11 +213,229 @@ impl Risc0Backend {
             })),
             options,
             cache: Arc::new(CircuitCache::new(cache_config)),
+            events: EventBus::default(),
+            last_health: RwLock::new(None),
+            profiles: Arc::new(ProfileRegistry::default()),
+            failures: Arc::new(FailureStats::new()),
+            program_stats: Arc::new(ProgramStatsTracker::new()),
+            latency: Arc::new(LatencyStats::with_window(latency_window)),
+            refresh: None,
+            probe: None,
+            replay_guard: None,
+            in_flight: SingleFlight::new(),
+            circuits: Arc::new(CircuitRegistry::with_guests(guests.clone()).with_programs(programs.clone())),
+            programs,
+            guests,
+            versions: None,
+            stats_path: None,
+        }
+    }
+
+    /// Refresh cached proofs of frequently requested inputs before they expire
+    ///
+    /// Refreshing happens in the task started by [`Risc0Backend::spawn_cache_refresh`].
+    pub fn with_refresh_policy(mut self, policy: RefreshPolicy) -> Self {
+        self.refresh = Some(Arc::new(RefreshTracker::new(policy)));
+        self
+    }
+
+    /// Probe the prover toolchain by running a tiny circuit
+    ///
+    /// Probing happens in the task started by [`Risc0Backend::spawn_health_probe`], and
+    /// [`ZkBackend::health_check`] reports failed probes.
+    pub fn with_health_probe(mut self, policy: ProbePolicy) -> Self {
+        self.probe = Some(Arc::new(HealthProbe::new(policy)));
+        self
+    }
+
+    /// Reject proofs of messages whose nonce `guard` already accepted
+    ///
+    /// Every successful verification, single or batched, of a message proof records
+    /// the nonces it commits in the guard; verifying a proof of the same message
+    /// again fails. Share one guard between all backends verifying for a bridge.
+    pub fn with_replay_guard(mut self, guard: Arc<ReplayGuard>) -> Self {
+        self.replay_guard = Some(guard);
+        self
+    }
+
+    /// Persist the statistics to `path`, restoring those a previous run saved there
+    ///
+    /// Statistics are saved by [`Risc0Backend::persist_stats`] and periodically by the
+    /// task started by [`Risc0Backend::spawn_stats_persistence`]. An unreadable file is
+    /// ignored and overwritten.
+    pub fn with_stats_persistence(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        match StatsSnapshot::load(&path) {
+            Ok(Some(snapshot)) => self.restore_stats(&snapshot),
+            Ok(None) => {}
+            Err(e) => tracing::warn!(path = %path.display(), "discarding unreadable stats: {}", e),
+        }
+        self.stats_path = Some(path);
+        self
+    }
+
+    /// Run built-in circuits with the guest versions registered in `registry`
+    ///
+    /// Programs of a circuit type with a version registered under
+    /// [`programs::circuit_program_name`] run its active version, unless they embed
+    /// or pin their own guest, and proofs of superseded versions verify until their
+    /// overlap window ends. Registered ELFs are checked against their image ID.
+    pub fn with_program_versions(mut self, registry: ProgramRegistry) -> Self {
+        let registry = registry.with_image_id(Arc::new(|elf: &[u8]| {
+            let image_id = compute_image_id(elf)
+                .map_err(|e| CustomZkError::CircuitCompilation(format!("Invalid guest: {}", e)))?;
+            image_id.as_bytes().try_into()
+                .map_err(|_| CustomZkError::Backend("Invalid image ID length".into()))
+        }));
+        self.versions = Some(Arc::new(registry));
+        self
+    }
+
+    /// Get the registry of guest versions, if enabled
+    pub fn program_versions(&self) -> Option<&ProgramRegistry> {
+        self.versions.as_deref()
+    }
+
+    /// Get the per-circuit profile registry
+    pub fn profiles(&self) -> &ProfileRegistry {
+        &self.profiles
+    }
+
+    /// Get the registry of circuit types this backend can prove
+    pub fn circuits(&self) -> &CircuitRegistry {
+        &self.circuits
+    }
+
+    /// Register a guest program compiled by the application
+    ///
+    /// Returns the program bytes to prove and verify it with through the standard
+    /// API. A proof only verifies if `journal_validator` accepts its journal.
+    pub fn register_program(
+        &self,
+        name: &str,
+        elf_bytes: Vec<u8>,
+        journal_validator: impl Fn(&[u8]) -> bool + Send + Sync + 'static,
+    ) -> Result<Vec<u8>, CustomZkError> {
+        compute_image_id(&elf_bytes)
+            .map_err(|e| CustomZkError::CircuitCompilation(format!("Invalid guest program {}: {}", name, e)))?;
+        let program = self.programs.register(name, elf_bytes, Arc::new(journal_validator))?;
+        Ok(program.program())
+    }
+
+    /// Get the guest programs registered by the application
+    pub fn programs(&self) -> &GuestPrograms {
+        &self.programs
+    }
+
+    /// Register the RISC0 programs of a manifest with the circuit registry
+    ///
+    /// Every ELF is read and checked against its image ID up front. Returns the
+    /// number of programs loaded.
+    pub fn load_manifest(&self, manifest: &ProgramManifest) -> Result<usize, CustomZkError> {
+        let mut loaded = 0;
+        for program in manifest.for_backend("risc0") {
+            let elf = program.read_elf()?;
+            let image_id = compute_image_id(&elf)
+                .map_err(|e| CustomZkError::CircuitCompilation(format!("Invalid guest {}: {}", program.name, e)))?;
+            if let Some(expected) = program.image_id {
+                if image_id.as_bytes() != &expected[..] {
+                    return Err(CustomZkError::ImageMismatch {
+                        expected: hex::encode(expected),
+                        found: hex::encode(image_id),
+                    });
+                }
+            }
+            self.circuits.register_elf(program.circuit_type, &program.name, elf);
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Hash of a program under the hash function of its circuit profile
+    pub fn program_hash(&self, program: &[u8]) -> String {
+        self.profiles.program_hash(program)
+    }
+
+    /// Get the per-program proving statistics
+    pub fn program_stats(&self) -> Arc<ProgramStatsTracker> {
+        self.program_stats.clone()
+    }
+
+    /// Get the proving and verification latency histograms
+    pub fn latency_stats(&self) -> Arc<LatencyStats> {
+        self.latency.clone()
+    }
+
+    /// Get failure counts per category
+    pub fn failure_stats(&self) -> HashMap<FailureCategory, u64> {
+        self.failures.snapshot()
+    }
+
+    /// Subscribe to backend events
+    pub fn events(&self) -> impl futures::Stream<Item = BackendEvent> + Send + 'static {
+        self.events.subscribe()
+    }
+
+    /// Get the SDK, toolchain and circuit versions of this backend
+    pub fn info(&self) -> BackendInfo {
+        BackendInfo::risc0()
+    }
+
+    /// Write a tarball of diagnostics for bug reports
+    pub async fn export_support_bundle(&self, path: impl AsRef<Path>) -> Result<(), CustomZkError> {
+        self.support_bundle().await?.write_async(path.as_ref().to_path_buf()).await
+    }
+
+    /// Collect the diagnostics of [`Self::export_support_bundle`] without writing them
+    ///
+    /// Services running a [`crate::jobs::JobManager`] can add its failed jobs
+    /// before writing the bundle.
+    pub async fn support_bundle(&self) -> Result<SupportBundle, CustomZkError> {
+        let mut bundle = SupportBundle::new();
+        bundle.add_json("version.json", &self.info())?;
+        bundle.add_json("diagnostics.json", &self.dashboard().await)?;
+        bundle.add_json("events.json", &self.events.recent())?;
+        bundle.add_failures("failures.json", &self.failures)?;
+        bundle.add_config("config.json", &self.options)?;
+        match self.config.guests.as_ref().and_then(|guests| guests.dir.clone()) {
+            Some(dir) => bundle.add_artifact_manifest_async("artifacts.json", dir).await?,
+            // The embedded guests are part of the binary named in version.json
+            None => bundle.add_artifacts("artifacts.json", [])?,
+        }
+        Ok(bundle)
+    }
+
+    /// Build a snapshot of the backend's operational state
+    pub async fn dashboard(&self) -> BackendDashboard {
+        let health = self.health_check().await;
+        let stats = self.stats.read().clone();
+        let resources = self.resources.read().clone();
+        BackendDashboard::new(
+            "risc0",
+            &health,
+            &stats,
+            &self.latency,
+            self.program_stats.snapshot(),
+            self.cache.stats().into(),
+            &resources,
+            self.failures.snapshot(),
+            self.failures.recent(),
+        )
+    }
+
+    /// Publish a cache eviction event if an entry was evicted
+    fn publish_eviction(&self, cache: CacheKind, evicted: Option<[u8; 32]>) {
+        if let Some(key) = evicted {
+            self.events.publish(BackendEvent::CacheEvicted {
+                cache,
+                key: hex::encode(key),
+            });
         }
     }
 
     /// Update statistics after a proving operation
     async fn update_proving_stats(&self, duration: Duration, success: bool) {
+        self.latency.record_proving(duration);
         let mut stats = self.stats.write();
         stats.total_proofs += 1;
         if !success {
@@ -130,6 +450,7 @@ impl Risc0Backend {
 
     /// Update statistics after a verification operation
     async fn update_verification_stats(&self, duration: Duration, success: bool) {
+        self.latency.record_verification(duration);
         let mut stats = self.stats.write();
         stats.total_verifications += 1;
         if !success {
@@ -144,76 +465,683 @@ impl Risc0Backend {
 
     /// Create a circuit from program bytes and input
     fn create_circuit(&self, program: &[u8], input: &[u8]) -> ZkResult<Box<dyn Risc0Circuit>> {
-        // Check cache first
-        if let Some(entry) = self.cache.get_circuit(program) {
-            let circuit: Box<dyn Risc0Circuit> = match program[0] {
-                0x01 => {
-                    let mut expected_hash = [0u8; 32];
-                    expected_hash.copy_from_slice(&program[1..33]);
-                    Box::new(MessageVerifyCircuit::new(input).map_err(|e| ZkError::Backend(e.to_string()))?)
-                }
-                _ => return Err(ZkError::Backend("Unknown circuit type".into())),
-            };
-            return Ok(circuit);
-        }
-
-        // Not in cache, create new circuit
+        // Cached circuits only skip recording the compile time
+        let cached = self.cache.get_circuit(program).is_some();
         let start = SystemTime::now();
-        let circuit: Box<dyn Risc0Circuit> = match program[0] {
-            0x01 => {
-                let mut expected_hash = [0u8; 32];
-                if program.len() < 33 {
-                    return Err(ZkError::Backend("Program too short for message verification".into()));
-                }
-                expected_hash.copy_from_slice(&program[1..33]);
-                Box::new(MessageVerifyCircuit::new(input).map_err(|e| ZkError::Backend(e.to_string()))?)
-            }
-            _ => return Err(ZkError::Backend("Unknown circuit type".into())),
-        };
+        let circuit = self.circuits.create(program, input)
+            .and_then(|circuit| self.route_version(program, circuit))
+            .map_err(|e| ZkError::Backend(e.to_string()))?;
 
         // Store in cache
-        let compile_time = start.elapsed().unwrap_or_default();
-        self.cache.store_circuit(program, circuit.elf().to_vec(), compile_time);
+        if !cached {
+            let compile_time = start.elapsed().unwrap_or_default();
+            let evicted = self.cache.store_circuit(program, circuit.elf().to_vec(), compile_time);
+            self.publish_eviction(CacheKind::Circuit, evicted);
+        }
 
         Ok(circuit)
     }
 
+    /// Registry name of the guest `program` runs, if it may be routed to a registered version
+    ///
+    /// Programs embedding or pinning their own guest are never routed.
+    fn version_name(&self, program: &[u8]) -> Option<&'static str> {
+        self.versions.as_ref()?;
+        let descriptor = ProgramDescriptor::decode(program).ok()?;
+        if descriptor.elf.is_some() || descriptor.image_id.is_some() {
+            return None;
+        }
+        programs::circuit_program_name(descriptor.circuit_type)
+    }
+
+    /// Run the active registered version of the guest `program` runs, pinning it
+    /// until the circuit is dropped
+    fn route_version(&self, program: &[u8], circuit: Box<dyn Risc0Circuit>) -> Result<Box<dyn Risc0Circuit>, CustomZkError> {
+        let (Some(versions), Some(name)) = (&self.versions, self.version_name(program)) else {
+            return Ok(circuit);
+        };
+        match versions.lease(name) {
+            Ok(lease) => Ok(Box::new(LoadedCircuit::leased(circuit, lease))),
+            // No version of the guest has been registered
+            Err(CustomZkError::UnknownProgram(_)) => Ok(circuit),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Circuits a proof of `program` may verify against: the current one, then those
+    /// running superseded versions that are still within their overlap window
+    fn verification_circuits(&self, program: &[u8]) -> ZkResult<Vec<Box<dyn Risc0Circuit>>> {
+        let mut circuits = vec![self.create_circuit(program, &[])?];
+        if let (Some(versions), Some(name)) = (&self.versions, self.version_name(program)) {
+            for version in versions.verifiable(name).into_iter().skip(1) {
+                let circuit = self.circuits.create(program, &[])
+                    .map_err(|e| ZkError::Backend(e.to_string()))?;
+                circuits.push(Box::new(LoadedCircuit::new(circuit, version.elf)));
+            }
+        }
+        Ok(circuits)
+    }
+
     /// Create executor environment for a circuit
-    fn create_env(&self, circuit: &dyn Risc0Circuit) -> ExecutorEnv {
+    fn create_env(
+        &self,
+        circuit: &dyn Risc0Circuit,
+        profile: Option<&CircuitProfile>,
+        domain: DomainSeparator,
+    ) -> ExecutorEnv<'static> {
+        Self::env(
+            &Self::stdin(circuit, domain),
+            profile.and_then(|profile| profile.segment_limit_po2),
+            circuit.assumptions(),
+        )
+    }
+
+    /// Create executor environment reading `stdin`, with the receipts the guest
+    /// verifies as assumptions
+    fn env(stdin: &[u8], segment_limit_po2: Option<u32>, assumptions: Vec<Receipt>) -> ExecutorEnv<'static> {
         let mut builder = ExecutorEnvBuilder::default();
         
+        // Apply the circuit profile's segment limit
+        if let Some(po2) = segment_limit_po2 {
+            builder.segment_limit_po2(po2);
+        }
+        
+        for receipt in assumptions {
+            builder.add_assumption(receipt);
+        }
+        
+        builder.write_slice(stdin);
+        builder.build().unwrap()
+    }
+
+    /// Encode the guest's input stream
+    fn stdin(circuit: &dyn Risc0Circuit, domain: DomainSeparator) -> Vec<u8> {
+        fn words(value: &impl Serialize) -> Vec<u8> {
+            to_vec(value).unwrap().iter().flat_map(|word| word.to_le_bytes()).collect()
+        }
+        let mut stdin = Vec::new();
+        
         // Add public inputs
         for input in circuit.public_inputs() {
-            builder.write(&input);
+            stdin.extend(words(&input));
         }
         
         // Add private inputs
-        builder.write_slice(&circuit.private_inputs());
+        stdin.extend_from_slice(&circuit.private_inputs());
         
-        builder.build().unwrap()
+        // Add the domain the guest commits to
+        stdin.extend(words(&domain.to_bytes()));
+        
+        stdin
     }
 
-    async fn prove_internal(&self, circuit: &dyn Risc0Circuit) -> Result<Vec<u8>, CustomZkError> {
-        // Create environment
-        let env = self.create_env(circuit);
+    async fn prove_internal(
+        &self,
+        circuit: &dyn Risc0Circuit,
+        circuit_type: u8,
+        profile: Option<&CircuitProfile>,
+        receipt_kind: ReceiptKind,
+        domain: DomainSeparator,
+        cancel: &CancellationToken,
+        progress: &Progress,
+    ) -> Result<ProvingRun, CustomZkError> {
+        // Prove remotely if configured; receipts to compose are only available locally
+        let assumptions = circuit.assumptions();
+        if let Some(remote_config) = self.config.remote.as_ref().filter(|_| assumptions.is_empty()) {
+            let proving = remote::prove(remote_config, circuit.elf(), &Self::stdin(circuit, domain))
+                .instrument(tracing::info_span!("prove", endpoint = %remote_config.endpoint));
+            let remote = tokio::select! {
+                remote = proving => remote,
+                _ = cancel.cancelled() => return Err(CustomZkError::Cancelled),
+            };
+            match remote {
+                Ok(receipt) => {
+                    let proof_bytes = tracing::debug_span!("serialize")
+                        .in_scope(|| serialize(&receipt))
+                        .map_err(|e| CustomZkError::Backend(format!("Failed to serialize receipt: {}", e)))?;
+                    progress.report(ProveProgress::Serialized { proof_size: proof_bytes.len() });
+                    return Ok(ProvingRun { segments: receipt_segments(&receipt), proof: proof_bytes, cycles: None, vkey_hash: None });
+                }
+                Err(e) if remote_config.fallback_to_local => {
+                    tracing::warn!(endpoint = %remote_config.endpoint, "remote proving failed, proving locally: {}", e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
         
-        // Create prover instance
-        let prover = default_prover();
-        let receipt = prover.prove_elf(env, &circuit.elf().to_vec())
-            .map_err(|e| CustomZkError::Backend(format!("Failed to generate proof: {}", e)))?;
+        if cancel.is_cancelled() {
+            return Err(CustomZkError::Cancelled);
+        }
+
+        // Bound concurrent local proofs, then hold this proof's share of the CPU budget
+        // shared with other backends
+        let slot = self.acquire_slot(cancel).await?;
+        let cpu = threads::budget().acquire(self.options.num_threads.unwrap_or(4), cancel).await?;
+
+        // Prove on the blocking pool; the task keeps its slot until the prover returns
+        let elf = circuit.elf().to_vec();
+        let stdin = Self::stdin(circuit, domain);
+        let segment_limit_po2 = profile.and_then(|profile| profile.segment_limit_po2);
+        let (task_cancel, task_progress) = (cancel.clone(), progress.clone());
+        let span = tracing::Span::current();
+        let handle = tokio::task::spawn_blocking(move || {
+            let _permits = (slot, cpu);
+            let _span = span.enter();
+            // Skip proofs cancelled while queued on the blocking pool
+            if task_cancel.is_cancelled() {
+                return Err(CustomZkError::Cancelled);
+            }
+            // Segment-wise composite receipts cannot carry assumptions
+            let composed = !assumptions.is_empty();
+            let env = Self::env(&stdin, segment_limit_po2, assumptions);
+            if task_progress.is_observed() && !composed {
+                Self::prove_segments(&elf, receipt_kind, circuit_type, env, &task_cancel, &task_progress)
+            } else {
+                // The default prover executes and proves in one call
+                tracing::info_span!("prove", receipt_kind = ?receipt_kind)
+                    .in_scope(|| default_prover().prove_elf_with_ctx(env, &VerifierContext::default(), &elf, &receipt_kind.prover_opts()))
+                    .map(|receipt| (receipt, None))
+                    .map_err(|e| CustomZkError::from_prover_error(circuit_type, e))
+            }
+        });
+
+        self.resources.write().active_tasks += 1;
+        let result = tokio::select! {
+            result = handle => result
+                .map_err(|e| CustomZkError::Backend(format!("Proving task failed: {}", e)))
+                .and_then(|result| result),
+            _ = cancel.cancelled() => Err(CustomZkError::Cancelled),
+        };
+        self.resources.write().active_tasks -= 1;
         
         // Serialize receipt
+        let (receipt, cycles) = result?;
+        let proof_bytes = tracing::debug_span!("serialize")
+            .in_scope(|| serialize(&receipt))
+            .map_err(|e| CustomZkError::Backend(format!("Failed to serialize receipt: {}", e)))?;
+        progress.report(ProveProgress::Serialized { proof_size: proof_bytes.len() });
+        Ok(ProvingRun { segments: receipt_segments(&receipt), proof: proof_bytes, cycles, vkey_hash: None })
+    }
+
+    /// Wait for a proving slot, counting the wait towards the queue depth
+    async fn acquire_slot(&self, cancel: &CancellationToken) -> Result<OwnedSemaphorePermit, CustomZkError> {
+        self.resources.write().queue_depth += 1;
+        let permit = tokio::select! {
+            permit = self.permits.clone().acquire_owned() => {
+                permit.map_err(|e| CustomZkError::Backend(format!("Proving slots closed: {}", e)))
+            }
+            _ = cancel.cancelled() => Err(CustomZkError::Cancelled),
+        };
+        self.resources.write().queue_depth -= 1;
+        permit
+    }
+
+    /// Execute, then prove segment by segment, reporting each proven segment
+    ///
+    /// Returns the receipt with the number of cycles executed.
+    fn prove_segments(
+        elf: &[u8],
+        receipt_kind: ReceiptKind,
+        circuit_type: u8,
+        env: ExecutorEnv<'_>,
+        cancel: &CancellationToken,
+        progress: &Progress,
+    ) -> Result<(Receipt, Option<u64>), CustomZkError> {
+        let session = tracing::info_span!("execute")
+            .in_scope(|| ExecutorImpl::from_elf(env, elf).and_then(|mut executor| executor.run()))
+            .map_err(|e| CustomZkError::from_prover_error(circuit_type, e))?;
+        let total = session.segments.len();
+        let cycles = session.get_cycles().ok();
+        progress.report(ProveProgress::Executed { cycles, segments: Some(total) });
+
+        let prover = get_prover_server(&receipt_kind.prover_opts())
+            .map_err(|e| CustomZkError::Backend(format!("Failed to create prover: {}", e)))?;
+        let ctx = VerifierContext::default();
+        let _span = tracing::info_span!("prove", receipt_kind = ?receipt_kind, segments = total).entered();
+
+        // Recursion needs the whole session, so only composite receipts report segments
+        if receipt_kind != ReceiptKind::Composite {
+            return prover.prove_session(&ctx, &session)
+                .map(|receipt| (receipt, cycles))
+                .map_err(|e| CustomZkError::from_prover_error(circuit_type, e));
+        }
+
+        let mut segments = Vec::with_capacity(total);
+        for segment in &session.segments {
+            if cancel.is_cancelled() {
+                return Err(CustomZkError::Cancelled);
+            }
+            let segment = segment.resolve()
+                .map_err(|e| CustomZkError::Backend(format!("Failed to load segment: {}", e)))?;
+            let receipt = prover.prove_segment(&ctx, &segment)
+                .map_err(|e| CustomZkError::from_prover_error(circuit_type, e))?;
+            segments.push(receipt);
+            progress.report(ProveProgress::SegmentsProven { proven: segments.len(), total });
+        }
+        let receipt = Receipt::new(
+            InnerReceipt::Composite(CompositeReceipt {
+                segments,
+                assumptions: Vec::new(),
+                journal_digest: None,
+            }),
+            session.journal.bytes.clone(),
+        );
+        Ok((receipt, cycles))
+    }
+
+    /// Regenerate cached proofs that are about to expire, returning the number refreshed.
+    ///
+    /// Refreshing runs at low priority: the pass stops as soon as a request is being
+    /// proven.
+    pub async fn refresh_expiring_proofs(&self) -> usize {
+        let refresh = match &self.refresh {
+            Some(refresh) => refresh.clone(),
+            None => return 0,
+        };
+        let due = refresh.due(|candidate| {
+            self.cache.proof_expires_in(&candidate.program, &candidate.cache_input())
+        });
+
+        let mut refreshed = 0;
+        for candidate in due {
+            if self.resources.read().active_tasks > 0 {
+                break;
+            }
+            let start = SystemTime::now();
+            let profile = self.profiles.for_program(&candidate.program);
+            let result = match self.create_circuit(&candidate.program, &candidate.input) {
+                Ok(circuit) => self.prove_internal(circuit.as_ref(), descriptor::circuit_type(&candidate.program).unwrap_or_default(), profile.as_ref(), self.options.receipt_kind, candidate.domain, &CancellationToken::new(), &Progress::none())
+                    .await
+                    .map(|run| run.proof),
+                Err(e) => Err(CustomZkError::from(e)),
+            };
+            match result {
+                Ok(proof_bytes) => {
+                    let cache_ttl = profile.as_ref().and_then(|profile| profile.cache_ttl);
+                    let evicted = self.cache.store_proof_with_ttl(
+                        &candidate.program,
+                        &candidate.cache_input(),
+                        proof_bytes,
+                        start.elapsed().unwrap_or_default(),
+                        cache_ttl,
+                    );
+                    self.publish_eviction(CacheKind::Proof, evicted);
+                    refresh.record_success(&candidate);
+                    refreshed += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(program_hash = %hex::encode(&candidate.program), "proof refresh failed: {}", e);
+                    refresh.record_failure(&candidate);
+                }
+            }
+        }
+        refreshed
+    }
+
+    /// Spawn the background task refreshing expiring proofs
+    ///
+    /// Returns `None` if no refresh policy is configured.
+    pub fn spawn_cache_refresh(self: Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let interval = self.refresh.as_ref()?.policy().interval;
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.refresh_expiring_proofs().await;
+            }
+        }))
+    }
+
+    /// Run the health probe once, recording its outcome
+    ///
+    /// Returns `None` if no health probe is configured.
+    pub async fn run_health_probe(&self) -> Option<ProbeResult> {
+        let probe = self.probe.as_ref()?;
+        let policy = probe.policy();
+        let start = SystemTime::now();
+        let cancel = CancellationToken::new();
+        let probing = async {
+            match policy.mode {
+                ProbeMode::Execute => self.execute(&policy.program, &policy.input, DomainSeparator::UNBOUND).await.map(drop),
+                ProbeMode::Prove => {
+                    let circuit = self.create_circuit(&policy.program, &policy.input)
+                        .map_err(CustomZkError::from)?;
+                    let profile = self.profiles.for_program(&policy.program);
+                    let circuit_type = descriptor::circuit_type(&policy.program).unwrap_or_default();
+                    self.prove_internal(circuit.as_ref(), circuit_type, profile.as_ref(), self.options.receipt_kind, DomainSeparator::UNBOUND, &cancel, &Progress::none())
+                        .await
+                        .map(drop)
+                }
+            }
+        };
+        let outcome = match tokio::time::timeout(policy.timeout, probing).await {
+            Ok(result) => result.map_err(|e| e.to_string()),
+            Err(_) => {
+                cancel.cancel();
+                Err(CustomZkError::Timeout(policy.timeout).to_string())
+            }
+        };
+        if let Err(e) = &outcome {
+            tracing::warn!(backend = "risc0", "health probe failed: {}", e);
+        }
+        Some(probe.record(start.elapsed().unwrap_or_default(), outcome))
+    }
+
+    /// Spawn the background task running the health probe
+    ///
+    /// Returns `None` if no health probe is configured.
+    pub fn spawn_health_probe(self: Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let interval = self.probe.as_ref()?.policy().interval;
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.run_health_probe().await;
+            }
+        }))
+    }
+
+    /// Execute a program without proving it
+    pub async fn execute(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        domain: DomainSeparator,
+    ) -> Result<ExecutionReport, CustomZkError> {
+        let circuit = self.create_circuit(program, input)
+            .map_err(CustomZkError::from)?;
+        let circuit_type = descriptor::circuit_type(program).unwrap_or_default();
+        let stdin = Self::stdin(circuit.as_ref(), domain);
+        let segment_limit_po2 = self.profiles.for_program(program).and_then(|profile| profile.segment_limit_po2);
+
+        // Execution is CPU-bound, keep it off the async executor
+        let span = tracing::Span::current();
+        tokio::task::spawn_blocking(move || {
+            let _span = span.enter();
+            let env = Self::env(&stdin, segment_limit_po2, circuit.assumptions());
+            let session = tracing::info_span!("execute")
+                .in_scope(|| ExecutorImpl::from_elf(env, circuit.elf()).and_then(|mut executor| executor.run()))
+                .map_err(|e| CustomZkError::from_prover_error(circuit_type, e))?;
+            Ok(ExecutionReport {
+                public_values: session.journal.bytes.clone(),
+                cycles: None,
+                segments: Some(session.segments.len()),
+                memory: None,
+                projected_proving_time: None,
+            })
+        })
+        .await
+        .map_err(|e| CustomZkError::Backend(format!("Execution task failed: {}", e)))?
+    }
+
+    /// Estimate the cost of proving a program without proving it
+    ///
+    /// Executes the guest and projects the proving time for the configured receipt
+    /// kind, from the observed average if the program was proven before and from its
+    /// segment count otherwise.
+    pub async fn estimate(&self, program: &[u8], input: &[u8]) -> Result<ExecutionReport, CustomZkError> {
+        let mut report = self.execute(program, input, DomainSeparator::UNBOUND).await?;
+        let mode = self.options.receipt_kind.proof_mode();
+        let observed = self.program_stats.snapshot()
+            .get(&self.program_hash(program))
+            .filter(|stats| stats.proofs > 0)
+            .map(|stats| stats.avg_proving_time());
+        report.projected_proving_time = observed
+            .or_else(|| report.segments.map(|segments| execution::project_risc0(segments, mode)));
+        Ok(report)
+    }
+
+    /// Execute a program and capture its segments for resumable proving
+    pub fn checkpoint(&self, program: &[u8], input: &[u8]) -> Result<ProvingCheckpoint, CustomZkError> {
+        let circuit = self.create_circuit(program, input)
+            .map_err(CustomZkError::from)?;
+        let profile = self.profiles.for_program(program);
+        let env = self.create_env(circuit.as_ref(), profile.as_ref(), DomainSeparator::UNBOUND);
+
+        // Execute once; the segments carry the executor state needed to prove them
+        let session = ExecutorImpl::from_elf(env, circuit.elf())
+            .and_then(|mut executor| executor.run())
+            .map_err(|e| CustomZkError::from_prover_error(descriptor::circuit_type(program).unwrap_or_default(), e))?;
+        let segments = session.segments.iter()
+            .map(|segment| {
+                let segment = segment.resolve()
+                    .map_err(|e| CustomZkError::Backend(format!("Failed to load segment: {}", e)))?;
+                bincode::serialize(&segment)
+                    .map_err(|e| CustomZkError::Backend(format!("Failed to serialize segment: {}", e)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ProvingCheckpoint {
+            program: program.to_vec(),
+            journal: session.journal.bytes.clone(),
+            segments,
+            receipts: Vec::new(),
+        })
+    }
+
+    /// Prove the remaining segments of a checkpoint.
+    ///
+    /// Once `drain` is set, proving stops after the current segment and the updated
+    /// checkpoint is returned so the job can be handed to another worker. Segments
+    /// are proven on the blocking pool, holding a proving slot and CPU budget like
+    /// any local proof, and the finished receipt is of the configured receipt kind.
+    pub async fn resume(
+        &self,
+        mut checkpoint: ProvingCheckpoint,
+        drain: Arc<AtomicBool>,
+    ) -> Result<ProvingOutcome, CustomZkError> {
+        let cancel = CancellationToken::new();
+        let slot = self.acquire_slot(&cancel).await?;
+        let cpu = threads::budget().acquire(self.options.num_threads.unwrap_or(4), &cancel).await?;
+
+        let circuit_type = descriptor::circuit_type(&checkpoint.program).unwrap_or_default();
+        let receipt_kind = self.options.receipt_kind;
+        let span = tracing::Span::current();
+        let handle = tokio::task::spawn_blocking(move || {
+            let _permits = (slot, cpu);
+            let _span = span.enter();
+            let opts = receipt_kind.prover_opts();
+            let prover = get_prover_server(&opts)
+                .map_err(|e| CustomZkError::Backend(format!("Failed to create prover: {}", e)))?;
+            let ctx = VerifierContext::default();
+
+            while !checkpoint.is_complete() {
+                if drain.load(Ordering::Acquire) {
+                    return Ok(ProvingOutcome::Suspended(checkpoint));
+                }
+                let segment = checkpoint.next_segment()?;
+                let receipt = prover.prove_segment(&ctx, &segment)
+                    .map_err(|e| CustomZkError::from_prover_error(circuit_type, e))?;
+                checkpoint.push_receipt(&receipt)?;
+            }
+
+            // Assemble the composite receipt from the segment receipts, then recurse
+            // it into the configured kind
+            let receipt = Receipt::new(
+                InnerReceipt::Composite(CompositeReceipt {
+                    segments: checkpoint.segment_receipts()?,
+                    assumptions: Vec::new(),
+                    journal_digest: None,
+                }),
+                checkpoint.journal,
+            );
+            let receipt = match receipt_kind {
+                ReceiptKind::Composite => receipt,
+                _ => prover.compress(&opts, &receipt)
+                    .map_err(|e| CustomZkError::from_prover_error(circuit_type, e))?,
+            };
+            serialize(&receipt)
+                .map(ProvingOutcome::Finished)
+                .map_err(|e| CustomZkError::Backend(format!("Failed to serialize receipt: {}", e)))
+        });
+
+        self.resources.write().active_tasks += 1;
+        let outcome = handle.await
+            .map_err(|e| CustomZkError::Backend(format!("Proving task failed: {}", e)))
+            .and_then(|outcome| outcome);
+        self.resources.write().active_tasks -= 1;
+        outcome
+    }
+
+    /// Convert a serialized receipt of `program` into the more succinct `target` mode
+    ///
+    /// Composite receipts can be recursed into succinct ones and succinct receipts
+    /// wrapped into Groth16 after the fact, so that segments are proven off-peak and
+    /// wrapped just before on-chain submission. The receipt is verified first so that
+    /// no recursion is spent on an invalid one.
+    pub async fn convert(&self, program: &[u8], proof: &[u8], target: ProofMode) -> Result<Vec<u8>, CustomZkError> {
+        let kind = ReceiptKind::from_proof_mode(target)
+            .ok_or_else(|| CustomZkError::InvalidInput(format!("RISC0 has no {:?} receipts", target)))?;
+        let receipt: Receipt = deserialize(proof)
+            .map_err(|e| CustomZkError::InvalidInput(format!("Invalid receipt: {}", e)))?;
+        let mode = receipt_mode(&receipt)
+            .ok_or_else(|| CustomZkError::InvalidInput("Receipt kind cannot be converted".into()))?;
+        if mode == target {
+            return Ok(proof.to_vec());
+        }
+        if mode > target {
+            return Err(CustomZkError::InvalidInput(format!("Cannot convert a {:?} receipt into {:?}", mode, target)));
+        }
+        let circuit = self.create_circuit(program, &[])
+            .map_err(CustomZkError::from)?;
+        if !verify_seal(circuit.as_ref(), &receipt) {
+            return Err(CustomZkError::ProofVerification("Receipt does not verify against its program".into()));
+        }
+
+        let circuit_type = descriptor::circuit_type(program).unwrap_or_default();
+        let receipt = tokio::task::spawn_blocking(move || {
+            let opts = kind.prover_opts();
+            get_prover_server(&opts)
+                .map_err(|e| CustomZkError::Backend(format!("Failed to create prover: {}", e)))?
+                .compress(&opts, &receipt)
+                .map_err(|e| CustomZkError::from_prover_error(circuit_type, e))
+        })
+        .await
+        .map_err(|e| CustomZkError::Backend(format!("Conversion task failed: {}", e)))??;
         serialize(&receipt)
             .map_err(|e| CustomZkError::Backend(format!("Failed to serialize receipt: {}", e)))
     }
 
-    async fn verify_internal(&self, circuit: &dyn Risc0Circuit, proof: &[u8]) -> Result<bool, CustomZkError> {
-        // Deserialize receipt
+    /// Verify a proof, first rejecting proofs produced by an incompatible SDK
+    pub async fn verify_with_metadata(
+        &self,
+        program: &[u8],
+        proof: &[u8],
+        metadata: &ExtendedProofMetadata,
+    ) -> Result<bool, CustomZkError> {
+        self.info().check_metadata(metadata)?;
+        ZkBackend::verify(self, program, proof, None)
+            .await
+            .map_err(CustomZkError::from)
+    }
+
+    /// Verify a proof and check that it is bound to `domain`
+    pub async fn verify_with_domain(
+        &self,
+        program: &[u8],
+        proof: &[u8],
+        domain: DomainSeparator,
+    ) -> Result<bool, CustomZkError> {
+        if !ZkBackend::verify(self, program, proof, None).await? {
+            return Ok(false);
+        }
+        let proof = self.open_envelope(program, proof)?;
+        let receipt: Receipt = deserialize(&proof)
+            .map_err(|e| CustomZkError::ProofVerification(format!("Failed to parse receipt: {}", e)))?;
+        let found = DomainSeparator::from_public_values(&receipt.journal.bytes)
+            .ok_or_else(|| CustomZkError::ProofVerification("Receipt does not commit to a domain".into()))?;
+        if found != domain {
+            return Err(CustomZkError::DomainMismatch { expected: domain, found });
+        }
+        Ok(true)
+    }
+
+    /// Export the image ID of a program without generating a proof
+    ///
+    /// The image ID of the circuit `program` builds for `input` is returned in the
+    /// [`ExportedKey`] format, bound to the program hash under the hash function of
+    /// its profile.
+    pub fn export_image_id(&self, program: &[u8], input: &[u8]) -> Result<Vec<u8>, CustomZkError> {
+        let circuit = self.create_circuit(program, input)?;
+        let image_id = compute_image_id(circuit.elf())
+            .map_err(|e| CustomZkError::CircuitCompilation(format!("Failed to compute image ID: {}", e)))?;
+        let image_id: [u8; 32] = image_id.as_bytes().try_into()
+            .map_err(|_| CustomZkError::Backend("Invalid image ID length".into()))?;
+        let hash_function = self.profiles.for_program(program)
+            .map(|profile| profile.hash_function)
+            .unwrap_or_default();
+        Ok(ExportedKey::new(KeyKind::Risc0ImageId, program, hash_function, image_id, image_id.to_vec()).to_bytes())
+    }
+
+    /// Extract the journal committed by the guest from a serialized receipt
+    pub fn public_values(&self, proof: &[u8]) -> Result<Vec<u8>, CustomZkError> {
         let receipt: Receipt = deserialize(proof)
             .map_err(|e| CustomZkError::ProofVerification(format!("Failed to parse receipt: {}", e)))?;
-        
-        // Verify receipt
-        Ok(circuit.verify_receipt(&receipt))
+        // Guests commit raw bytes with `env::commit_slice`
+        Ok(receipt.journal.bytes)
+    }
+
+    /// Check a serialized receipt, returning its journal if one of the circuits accepts it
+    async fn verify_internal(&self, circuits: &[Box<dyn Risc0Circuit>], proof: &[u8]) -> Result<Option<Vec<u8>>, CustomZkError> {
+        check_receipt(circuits, proof)
+    }
+
+    /// Record the messages committed in the journal of a valid proof of `program`,
+    /// rejecting replays if a guard is configured
+    fn accept_messages(&self, program: &[u8], journal: &[u8]) -> Result<(), CustomZkError> {
+        if let (Some(guard), Some(circuit_type)) = (&self.replay_guard, descriptor::circuit_type(program)) {
+            guard.accept_public_values(circuit_type, journal)?;
+        }
+        Ok(())
+    }
+
+    /// Verify a batch of proofs, reporting the outcome of each separately
+    ///
+    /// Every proof is opened, decoded and checked on its own, so a malformed proof, an
+    /// unknown program or a replayed message fails its own item rather than the
+    /// whole batch. Each distinct circuit is built once. Proofs are checked on the
+    /// blocking thread pool, at most `max_threads` at once; proofs waiting for a
+    /// slot count towards the queue depth and proofs being checked towards the
+    /// active tasks.
+    #[tracing::instrument(name = "batch_verify", skip_all, fields(backend = "risc0", batch_size = verifications.len()))]
+    pub async fn batch_verify_detailed(&self, verifications: &[(&[u8], &[u8])]) -> Vec<Result<bool, CustomZkError>> {
+        let start = SystemTime::now();
+
+        // Build each distinct program's circuits once and share them across the batch
+        let mut circuits: HashMap<&[u8], Result<Arc<Vec<Box<dyn Risc0Circuit>>>, String>> = HashMap::new();
+        for (program, _) in verifications {
+            circuits.entry(*program).or_insert_with(|| {
+                self.verification_circuits(program).map(Arc::new).map_err(|e| e.to_string())
+            });
+        }
+
+        let results = join_all(verifications.iter().enumerate().map(|(index, (program, proof))| {
+            let circuit = &circuits[program];
+            async move {
+                let circuit = circuit.clone().map_err(CustomZkError::Backend)?;
+                let proof = self.open_envelope(program, proof)?.into_owned();
+                let slot = self.acquire_slot(&CancellationToken::new()).await?;
+                let span = tracing::Span::current();
+                let handle = tokio::task::spawn_blocking(move || {
+                    let _slot = slot;
+                    let _span = span.enter();
+                    check_receipt(&circuit, &proof)
+                });
+                self.resources.write().active_tasks += 1;
+                let result = handle.await
+                    .map_err(|e| CustomZkError::Backend(format!("Verification task failed: {}", e)))
+                    .and_then(|result| result);
+                self.resources.write().active_tasks -= 1;
+                match result? {
+                    Some(journal) => self.accept_messages(program, &journal).map(|()| true),
+                    None => Ok(false),
+                }
+            }.instrument(tracing::debug_span!("batch_item", index, program_hash = %self.program_hash(program)))
+        })).await;
+
+        let all_valid = results.iter().all(|result| matches!(result, Ok(true)));
+        self.update_verification_stats(start.elapsed().unwrap_or_default(), all_valid).await;
+        results
     }
 
     /// Generate a proof for a circuit
@@ -221,11 +1149,12 @@ impl Risc0Backend {
         let start = SystemTime::now();
         
         // Create environment
-        let env = self.create_env(circuit);
+        let env = self.create_env(circuit, None, DomainSeparator::UNBOUND);
         
         // Create prover instance
         let prover = default_prover();
-        let receipt = prover.prove_elf(env, &circuit.elf().to_vec())
+        let opts = self.options.receipt_kind.prover_opts();
+        let receipt = prover.prove_elf_with_ctx(env, &VerifierContext::default(), &circuit.elf().to_vec(), &opts)
             .map_err(|e| CustomZkError::Backend(format!("Failed to generate proof: {}", e)))?;
 
         // Serialize receipt
@@ -252,7 +1181,7 @@ impl Risc0Backend {
             .map_err(|e| CustomZkError::ProofVerification(format!("Failed to parse receipt: {}", e)))?;
 
         // Verify receipt
-        let is_valid = circuit.verify_receipt(&receipt);
+        let is_valid = verify_seal(circuit, &receipt) && circuit.verify_receipt(&receipt);
 
         // Update statistics
         let duration = start.elapsed().unwrap_or_default();
@@ -268,11 +1197,336 @@ impl Risc0Backend {
         Ok(is_valid)
     }
 
+    /// Generate a proof with per-request options.
+    ///
+    /// Receipts are produced in the mode of the configured [`ReceiptKind`], so a
+    /// proof that exceeds the size budget fails with [`CustomZkError::ProofTooLarge`].
+    pub async fn prove_with_options(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        options: &ProveOptions,
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        self.prove_cancellable(program, input, options, &CancellationToken::new()).await
+    }
+
+    /// Generate a proof wrapped in a [`ProofEnvelope`] describing it
+    pub async fn prove_envelope(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        options: &ProveOptions,
+    ) -> Result<ProofEnvelope, CustomZkError> {
+        let (proof, metadata) = self.prove_with_options(program, input, options).await?;
+        Ok(ProofEnvelope::from_metadata(&metadata, proof))
+    }
+
+    /// Unwrap an enveloped proof of `program`, passing raw proofs through
+    ///
+    /// The envelope must have been produced by this backend for `program`, and its
+    /// public values must be those committed by the proof it carries.
+    pub fn open_envelope<'a>(&self, program: &[u8], proof: &'a [u8]) -> Result<Cow<'a, [u8]>, CustomZkError> {
+        if !envelope::is_envelope(proof) {
+            return Ok(Cow::Borrowed(proof));
+        }
+        let envelope = ProofEnvelope::from_bytes(proof)?;
+        self.info().compatibility_check(&envelope)?;
+        let proof = envelope.open(&self.info().backend, &self.program_hash(program))?;
+        if self.public_values(proof)? != envelope.public_values {
+            return Err(CustomZkError::ProofVerification("Envelope public values do not match its proof".into()));
+        }
+        Ok(Cow::Owned(proof.to_vec()))
+    }
+
+    /// Compose message verification proofs into one proof of all messages
+    ///
+    /// The composed proof commits to the same Merkle root over the message hashes as
+    /// the aggregation circuit, and to the nonce of the first message if the proofs
+    /// commit consecutive nonces. Composition is only resolved in succinct and Groth16
+    /// receipts; a composite receipt still carries every input receipt.
+    pub async fn compose_messages(
+        &self,
+        proofs: &[Vec<u8>],
+        options: &ProveOptions,
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        let receipts = proofs.iter()
+            .map(|proof| deserialize::<Receipt>(proof)
+                .map_err(|e| CustomZkError::InvalidInput(format!("Invalid receipt: {}", e))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut circuit = MessageComposeCircuit::from_receipts(receipts)?;
+        if let Some(image_id) = self.guests.image_id("message_verify")? {
+            circuit = circuit.with_verify_image_id(image_id);
+        }
+        let input = encode_shard(proofs);
+        let program = match circuit.nonce() {
+            Some(nonce) => ProgramDescriptor::new(MESSAGE_COMPOSE, circuit.root()).with_nonce(nonce)?,
+            None => ProgramDescriptor::new(MESSAGE_COMPOSE, circuit.root()),
+        };
+        self.prove_with_options(&program.encode(), &input, options).await
+    }
+
+    /// Generate a proof with per-request options until `cancel` fires.
+    ///
+    /// Once `cancel` fires the request fails with [`CustomZkError::Cancelled`] right
+    /// away. Remote proving is abandoned; a local prover thread that already started
+    /// finishes in the background, still holding its proving slot.
+    pub async fn prove_cancellable(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        options: &ProveOptions,
+        cancel: &CancellationToken,
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        self.prove_observed(program, input, options, cancel, &Progress::none()).await
+    }
+
+    /// Generate a proof with per-request options, sending progress events to `progress`.
+    ///
+    /// Local composite proving reports every segment; remote proving and a proof
+    /// served from the cache send no events until the proof is serialized. A proof
+    /// shared with an identical request already in flight sends no events.
+    pub async fn prove_with_progress(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        options: &ProveOptions,
+        progress: UnboundedSender<ProveProgress>,
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        self.prove_observed(program, input, options, &CancellationToken::new(), &Progress::new(progress)).await
+    }
+
+    /// Generate proofs for a batch, yielding each proof with its index in `programs`
+    /// as soon as it is ready
+    ///
+    /// Unlike [`ZkBackendExt::batch_prove`], a slow item does not hold back the
+    /// results of the others, and a failed item does not fail the batch.
+    pub fn batch_prove_stream<'a>(
+        &'a self,
+        programs: &'a [(&'a [u8], &'a [u8])],
+    ) -> impl Stream<Item = (usize, ZkResult<(Vec<u8>, ProofMetadata)>)> + 'a {
+        programs.iter()
+            .enumerate()
+            .map(|(index, (program, input))| async move {
+                let result = self.prove_with_options(program, input, &ProveOptions::default())
+                    .await
+                    .map(|(proof, metadata)| (proof, metadata.base))
+                    .map_err(|e| frostgate_zkip::ZkError::Backend(e.to_string()));
+                (index, result)
+            }.instrument(tracing::info_span!("batch_item", index)))
+            .collect::<FuturesUnordered<_>>()
+    }
+
+    #[tracing::instrument(name = "prove_request", skip_all, fields(backend = "risc0", program_hash = tracing::field::Empty))]
+    async fn prove_observed(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        options: &ProveOptions,
+        cancel: &CancellationToken,
+        progress: &Progress,
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        let start = SystemTime::now();
+        let info = self.info();
+        let circuit_type = descriptor::circuit_type(program);
+        let circuit_version = circuit_type.and_then(|circuit_type| info.circuit_version(circuit_type));
+        let profile = self.profiles.for_program(program);
+        let program_hash = self.program_hash(program);
+        tracing::Span::current().record("program_hash", program_hash.as_str());
+        let domain = options.domain();
+        let requested_mode = options.proof_mode
+            .or(profile.as_ref().map(|profile| profile.proof_mode))
+            .unwrap_or(self.options.receipt_kind.proof_mode());
+        let receipt_kind = ReceiptKind::from_proof_mode(requested_mode).ok_or_else(|| {
+            CustomZkError::InvalidInput(format!("RISC0 cannot produce {:?} proofs", requested_mode))
+        })?;
+        
+        let cache_input = domain.cache_input(input);
+        if let Some(refresh) = &self.refresh {
+            refresh.record_hit(program, input, domain);
+        }
+        
+        // Check proof cache first (cached proofs are of the configured receipt kind)
+        let cached = (receipt_kind == self.options.receipt_kind).then(|| {
+            tracing::debug_span!("cache", op = "lookup")
+                .in_scope(|| self.cache.get_proof(program, &cache_input))
+        }).flatten();
+        if let Some(entry) = cached {
+            let proof = entry.proof.clone();
+            if options.fits(proof.len()) {
+                return Ok((proof.clone(), ExtendedProofMetadata {
+                    base: ProofMetadata {
+                        generation_time: entry.generation_time,
+                        proof_size: proof.len(),
+                        program_hash: program_hash.clone(),
+                        timestamp: start,
+                    },
+                    proof_mode: requested_mode,
+                    requested_mode,
+                    max_proof_size: options.max_proof_size,
+                    backend: info.backend.clone(),
+                    sdk_version: info.sdk_version.clone(),
+                    circuit_type,
+                    circuit_version,
+                    vkey_hash: self.create_circuit(program, input).ok()
+                        .and_then(|circuit| image_id(circuit.as_ref())),
+                    public_values: self.public_values(&proof).unwrap_or_default(),
+                    cycles: None,
+                    segments: None,
+                    peak_memory: None,
+                }));
+            }
+        }
+        
+        // Identical requests in flight share a single proving run
+        let timeout = options.timeout
+            .or(profile.as_ref().and_then(|profile| profile.timeout))
+            .or(self.options.prove_timeout);
+        let key = flight_key(&[
+            program,
+            &cache_input,
+            format!("{:?}", requested_mode).as_bytes(),
+            &options.max_proof_size.map(|size| size as u64).unwrap_or(u64::MAX).to_le_bytes(),
+            &timeout.map(|timeout| timeout.as_millis() as u64).unwrap_or(u64::MAX).to_le_bytes(),
+        ]);
+        self.in_flight.run(key, cancel, || async {
+            // Create circuit
+            let circuit = tracing::info_span!("setup")
+                .in_scope(|| self.create_circuit(program, input))
+                .map_err(CustomZkError::from)?;
+            self.events.publish(BackendEvent::ProofStarted {
+                program_hash: program_hash.clone(),
+                timestamp: start,
+            });
+        
+            // Generate proof
+            let cancel = cancel.child_token();
+            let proving = self.prove_internal(circuit.as_ref(), descriptor::circuit_type(program).unwrap_or_default(), profile.as_ref(), receipt_kind, domain, &cancel, progress);
+            let peak_memory = sysmon::monitor().track_peak();
+            let result = bounded(proving, timeout, &cancel).await;
+            let duration = start.elapsed().unwrap_or_default();
+
+            let ProvingRun { proof: proof_bytes, cycles, segments, .. } = match result {
+                Ok(run) => run,
+                Err(e) => {
+                    self.events.publish(BackendEvent::proof_failed(program_hash.clone(), duration, &e));
+                    self.failures.record(&program_hash, &e);
+                    self.program_stats.record(&program_hash, duration, false);
+                    self.update_proving_stats(duration, false).await;
+                    return Err(e);
+                }
+            };
+
+            // Enforce the proof-size budget
+            if !options.fits(proof_bytes.len()) {
+                let e = CustomZkError::ProofTooLarge {
+                    size: proof_bytes.len(),
+                    limit: options.max_proof_size.unwrap_or_default(),
+                    mode: requested_mode,
+                };
+                self.events.publish(BackendEvent::proof_failed(program_hash.clone(), duration, &e));
+                self.failures.record(&program_hash, &e);
+                self.program_stats.record(&program_hash, duration, false);
+                self.update_proving_stats(duration, false).await;
+                return Err(e);
+            }
+        
+            // Create metadata
+            let metadata = ExtendedProofMetadata {
+                base: ProofMetadata {
+                    generation_time: duration,
+                    proof_size: proof_bytes.len(),
+                    program_hash: program_hash.clone(),
+                    timestamp: start,
+                },
+                proof_mode: requested_mode,
+                requested_mode,
+                max_proof_size: options.max_proof_size,
+                backend: info.backend.clone(),
+                sdk_version: info.sdk_version.clone(),
+                circuit_type,
+                circuit_version,
+                vkey_hash: image_id(circuit.as_ref()),
+                public_values: self.public_values(&proof_bytes).unwrap_or_default(),
+                cycles,
+                segments,
+                peak_memory: peak_memory.peak(),
+            };
+            self.events.publish(BackendEvent::ProofCompleted {
+                program_hash: program_hash.clone(),
+                duration,
+                proof_size: proof_bytes.len(),
+                proof_mode: metadata.proof_mode,
+            });
+
+            // Store in cache
+            if receipt_kind == self.options.receipt_kind {
+                let cache_ttl = profile.as_ref().and_then(|profile| profile.cache_ttl);
+                let evicted = tracing::debug_span!("cache", op = "store")
+                    .in_scope(|| self.cache.store_proof_with_ttl(program, &cache_input, proof_bytes.clone(), duration, cache_ttl));
+                self.publish_eviction(CacheKind::Proof, evicted);
+            }
+
+            // Update stats
+            self.program_stats.record(&program_hash, duration, true);
+            self.update_proving_stats(duration, true).await;
+        
+            Ok((proof_bytes, metadata))
+        }).await
+    }
+
     /// Get backend statistics
     pub fn stats(&self) -> ZkStats {
         self.stats.read().clone()
     }
 
+    /// Copy of all statistics of the backend
+    pub fn stats_snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot::new(&self.stats.read(), &self.program_stats, &self.failures, &self.latency)
+    }
+
+    /// Reset all statistics, returning those of the interval that just ended
+    pub fn reset_stats(&self) -> StatsSnapshot {
+        let mut stats = self.stats.write();
+        let snapshot = StatsSnapshot::new(&stats, &self.program_stats, &self.failures, &self.latency);
+        *stats = ZkStats::default();
+        self.program_stats.reset();
+        self.failures.reset();
+        self.latency.reset();
+        snapshot
+    }
+
+    /// Replace all statistics with those of `snapshot`
+    pub fn restore_stats(&self, snapshot: &StatsSnapshot) {
+        *self.stats.write() = snapshot.zk_stats();
+        self.program_stats.restore(snapshot.programs.clone());
+        self.failures.restore(snapshot.failure_counts.clone(), snapshot.recent_failures.clone());
+        self.latency.restore(&snapshot.proving_latency, &snapshot.verification_latency);
+    }
+
+    /// Save the statistics if persistence is enabled
+    pub fn persist_stats(&self) -> Result<(), CustomZkError> {
+        match &self.stats_path {
+            Some(path) => self.stats_snapshot().save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Spawn the background task saving the statistics every `interval`
+    ///
+    /// Returns `None` if stats persistence is not enabled.
+    pub fn spawn_stats_persistence(self: Arc<Self>, interval: Duration) -> Option<tokio::task::JoinHandle<()>> {
+        let path = self.stats_path.clone()?;
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.persist_stats() {
+                    tracing::warn!(path = %path.display(), "failed to persist stats: {}", e);
+                }
+            }
+        }))
+    }
+
     /// Clear the backend cache
     pub async fn clear_cache(&mut self) -> Result<(), CustomZkError> {
         // No cache to clear in this implementation
@@ -286,6 +1540,10 @@ impl Risc0Backend {
             "message_verify".to_string(),
             "tx_verify".to_string(),
             "block_verify".to_string(),
+            "message_aggregate".to_string(),
+            "message_compose".to_string(),
+            "eth_header_verify".to_string(),
+            "ecdsa_verify".to_string(),
         ]
     }
 }
@@ -296,6 +1554,61 @@ impl Default for Risc0Backend {
     }
 }
 
+/// Image ID of a circuit, as exported for on-chain verifiers
+fn image_id(circuit: &dyn Risc0Circuit) -> Option<[u8; 32]> {
+    let image_id = compute_image_id(circuit.elf()).ok()?;
+    image_id.as_bytes().try_into().ok()
+}
+
+/// Number of segments proven by a receipt, only known for composite receipts
+fn receipt_segments(receipt: &Receipt) -> Option<usize> {
+    match &receipt.inner {
+        InnerReceipt::Composite(composite) => Some(composite.segments.len()),
+        _ => None,
+    }
+}
+
+/// Mode of a receipt, `None` for kinds the backend does not produce
+fn receipt_mode(receipt: &Receipt) -> Option<ProofMode> {
+    match &receipt.inner {
+        InnerReceipt::Composite(_) => Some(ProofMode::Core),
+        InnerReceipt::Succinct(_) => Some(ProofMode::Compressed),
+        InnerReceipt::Groth16(_) => Some(ProofMode::Groth16),
+        _ => None,
+    }
+}
+
+/// Decode a serialized receipt and check it against a circuit, returning its
+/// journal if the circuit accepts it
+fn check_receipt(circuits: &[Box<dyn Risc0Circuit>], proof: &[u8]) -> Result<Option<Vec<u8>>, CustomZkError> {
+    let receipt: Receipt = deserialize(proof)
+        .map_err(|e| CustomZkError::ProofVerification(format!("Failed to parse receipt: {}", e)))?;
+    let accepted = circuits.iter()
+        .any(|circuit| verify_seal(circuit.as_ref(), &receipt) && circuit.verify_receipt(&receipt));
+    Ok(accepted.then_some(receipt.journal.bytes))
+}
+
+/// Check the receipt's seal against the circuit's image ID
+///
+/// `Receipt::verify` dispatches on the inner receipt, so composite, succinct and
+/// Groth16 receipts are all accepted.
+fn verify_seal(circuit: &dyn Risc0Circuit, receipt: &Receipt) -> bool {
+    let image_id = match compute_image_id(circuit.elf()) {
+        Ok(image_id) => image_id,
+        Err(e) => {
+            tracing::debug!("failed to compute image ID: {}", e);
+            return false;
+        }
+    };
+    match receipt.verify(image_id) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!("receipt rejected: {}", e);
+            false
+        }
+    }
+}
+
 #[async_trait]
 impl ZkBackend for Risc0Backend {
     async fn prove(
@@ -304,44 +1617,14 @@ impl ZkBackend for Risc0Backend {
         input: &[u8],
         config: Option<&ZkConfig>,
     ) -> ZkResult<(Vec<u8>, ProofMetadata)> {
-        let start = SystemTime::now();
-        
-        // Check proof cache first
-        if let Some(entry) = self.cache.get_proof(program, input) {
-            let proof = entry.proof.clone();
-            return Ok((proof.clone(), ProofMetadata {
-                generation_time: entry.generation_time,
-                proof_size: proof.len(),
-                program_hash: hex::encode(&entry.program_hash),
-                timestamp: start,
-            }));
-        }
-        
-        // Create circuit
-        let circuit = self.create_circuit(program, input)?;
-        
-        // Generate proof
-        let proof_bytes = self.prove_internal(circuit.as_ref()).await
+        let (proof_bytes, metadata) = self.prove_with_options(program, input, &ProveOptions::default())
+            .await
             .map_err(|e| ZkError::Backend(e.to_string()))?;
         
-        // Create metadata
-        let duration = start.elapsed().unwrap_or_default();
-        let metadata = ProofMetadata {
-            generation_time: duration,
-            proof_size: proof_bytes.len(),
-            program_hash: hex::encode(circuit.elf()),
-            timestamp: SystemTime::now(),
-        };
-
-        // Store in cache
-        self.cache.store_proof(program, input, proof_bytes.clone(), duration);
-
-        // Update stats
-        self.update_proving_stats(duration, true).await;
-        
-        Ok((proof_bytes, metadata))
+        Ok((proof_bytes, metadata.base))
     }
 
+    #[tracing::instrument(name = "verify_request", skip_all, fields(backend = "risc0", program_hash = %self.program_hash(program)))]
     async fn verify(
         &self,
         program: &[u8],
@@ -349,13 +1632,18 @@ impl ZkBackend for Risc0Backend {
         config: Option<&ZkConfig>,
     ) -> ZkResult<bool> {
         let start = SystemTime::now();
+        let proof = &*self.open_envelope(program, proof).map_err(|e| ZkError::Backend(e.to_string()))?;
         
-        // Create circuit
-        let circuit = self.create_circuit(program, &[])?;
+        // Create the circuits of the current and still verifiable program versions
+        let circuits = self.verification_circuits(program)?;
         
-        // Verify proof
-        let result = self.verify_internal(circuit.as_ref(), proof).await
+        // Verify proof, then record the messages it commits
+        let journal = self.verify_internal(&circuits, proof).await
             .map_err(|e| ZkError::Backend(e.to_string()))?;
+        let result = journal.is_some();
+        if let Some(journal) = journal {
+            self.accept_messages(program, &journal).map_err(|e| ZkError::Backend(e.to_string()))?;
+        }
         
         // Update stats
         self.update_verification_stats(start.elapsed().unwrap_or_default(), result).await;
@@ -364,25 +1652,44 @@ impl ZkBackend for Risc0Backend {
     }
 
     fn resource_usage(&self) -> ResourceUsage {
-        futures::executor::block_on(async {
-            self.resources.read().clone()
-        })
+        sysmon::monitor().usage(self.resources.read().clone())
     }
 
     async fn health_check(&self) -> HealthStatus {
-        let resources = self.resources.read().clone();
-        if resources.cpu_usage > 90.0 {
+        let resources = sysmon::monitor().usage(self.resources.read().clone());
+        let status = if resources.cpu_usage > sysmon::HIGH_CPU_USAGE {
             HealthStatus::Degraded("High CPU usage".into())
         } else if resources.memory_usage > self.options.memory_limit.unwrap_or(usize::MAX) {
             HealthStatus::Degraded("High memory usage".into())
         } else {
             HealthStatus::Healthy
+        };
+        // A failing probe outweighs resource pressure
+        let status = self.probe.as_ref()
+            .and_then(|probe| probe.status())
+            .unwrap_or(status);
+
+        // Publish health transitions
+        let current = format!("{:?}", status);
+        let mut last_health = self.last_health.write();
+        if last_health.as_deref() != Some(current.as_str()) {
+            if let HealthStatus::Degraded(reason) | HealthStatus::Unhealthy(reason) = &status {
+                self.events.publish(BackendEvent::Alert { message: reason.clone() });
+            }
+            self.events.publish(BackendEvent::HealthChanged {
+                previous: last_health.clone().unwrap_or_else(|| "Unknown".into()),
+                current: current.clone(),
+            });
+            *last_health = Some(current);
         }
+
+        status
     }
 }
 
 #[async_trait]
 impl ZkBackendExt for Risc0Backend {
+    #[tracing::instrument(name = "batch_prove", skip_all, fields(backend = "risc0", batch_size = programs.len()))]
     async fn batch_prove(
         &self,
         programs: &[(&[u8], &[u8])],
@@ -390,31 +1697,25 @@ impl ZkBackendExt for Risc0Backend {
     ) -> ZkResult<Vec<(Vec<u8>, ProofMetadata)>> {
         let start = SystemTime::now();
 
-        // Update resource tracking
-        {
-            let mut resources = self.resources.write();
-            resources.active_tasks += programs.len();
-            resources.queue_depth = programs.len();
-        }
-
-        // Create futures for all proofs
-        let futures: Vec<_> = programs.iter().map(|(program, input)| async {
+        // Create futures for all proofs, each proven once a slot is free
+        let futures: Vec<_> = programs.iter().enumerate().map(|(index, (program, input))| async move {
             let circuit = self.create_circuit(program, input)?;
             let proof_start = SystemTime::now();
             
             // Generate proof
-            let proof_bytes = self.prove_internal(circuit.as_ref()).await.map_err(|e| 
-                frostgate_zkip::ZkError::Backend(e.to_string()))?;
+            let profile = self.profiles.for_program(program);
+            let proof_bytes = self.prove_internal(circuit.as_ref(), descriptor::circuit_type(program).unwrap_or_default(), profile.as_ref(), self.options.receipt_kind, DomainSeparator::UNBOUND, &CancellationToken::new(), &Progress::none()).await.map_err(|e| 
+                frostgate_zkip::ZkError::Backend(e.to_string()))?.proof;
             
             let duration = proof_start.elapsed().unwrap_or_default();
             let size = proof_bytes.len();
             Ok((proof_bytes, ProofMetadata {
                 generation_time: duration,
                 proof_size: size,
-                program_hash: hex::encode(circuit.elf()),
+                program_hash: self.program_hash(program),
                 timestamp: SystemTime::now(),
             }))
-        }).collect();
+        }.instrument(tracing::info_span!("batch_item", index, program_hash = %self.program_hash(program)))).collect();
 
         // Execute all futures concurrently
         let results = join_all(futures).await;
@@ -429,29 +1730,14 @@ impl ZkBackendExt for Risc0Backend {
         verifications: &[(&[u8], &[u8])],
         config: Option<&ZkConfig>,
     ) -> ZkResult<Vec<bool>> {
-        let start = SystemTime::now();
-
-        // Update resource tracking
-        {
-            let mut resources = self.resources.write();
-            resources.active_tasks += verifications.len();
-            resources.queue_depth = verifications.len();
-        }
-
-        // Create futures for all verifications
-        let futures: Vec<_> = verifications.iter().map(|(program, proof)| async {
-            let circuit = self.create_circuit(program, &[]).map_err(|e| 
-                frostgate_zkip::ZkError::Backend(e.to_string()))?;
-            self.verify_internal(circuit.as_ref(), proof).await.map_err(|e| 
-                frostgate_zkip::ZkError::Backend(e.to_string()))
-        }).collect();
-
-        // Execute all futures concurrently
-        let results = join_all(futures).await;
-
-        // Update stats and return
-        self.update_verification_stats(start.elapsed().unwrap_or_default(), results.iter().all(|r| r.is_ok())).await;
-        results.into_iter().collect()
+        // Malformed proofs and unknown programs are reported invalid
+        let results = self.batch_verify_detailed(verifications).await;
+        Ok(results.into_iter().enumerate().map(|(index, result)| {
+            result.unwrap_or_else(|e| {
+                tracing::debug!(index, "batch proof rejected: {}", e);
+                false
+            })
+        }).collect())
     }
 
     async fn clear_cache(&mut self) -> ZkResult<()> {
@@ -460,11 +1746,82 @@ impl ZkBackendExt for Risc0Backend {
     }
 
     fn capabilities(&self) -> Vec<String> {
-        vec![
-            "risc0".to_string(),
-            "message_verify".to_string(),
-            "tx_verify".to_string(),
-            "block_verify".to_string(),
-        ]
+        Risc0Backend::capabilities(self)
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serialized receipt committing `journal`, without any segments
+    fn receipt_with_journal(journal: &[u8]) -> Vec<u8> {
+        let receipt = Receipt::new(
+            InnerReceipt::Composite(CompositeReceipt {
+                segments: Vec::new(),
+                assumptions: Vec::new(),
+                journal_digest: None,
+            }),
+            journal.to_vec(),
+        );
+        serialize(&receipt).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_public_values_are_the_raw_journal() {
+        let backend = Risc0Backend::new(Risc0Config::default());
+        let mut journal = vec![0xAB; 32];
+        journal.extend_from_slice(&DomainSeparator::UNBOUND.to_bytes());
+
+        let proof = receipt_with_journal(&journal);
+        assert_eq!(backend.public_values(&proof).unwrap(), journal);
+        assert!(backend.public_values(b"not a receipt").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_envelope_with_journal() {
+        let backend = Risc0Backend::new(Risc0Config::default());
+        let program = ProgramDescriptor::new(crate::profiles::MESSAGE_VERIFY, [0x11; 32]).encode();
+        let mut journal = vec![0xCD; 32];
+        journal.extend_from_slice(&DomainSeparator::UNBOUND.to_bytes());
+        let proof = receipt_with_journal(&journal);
+
+        // An envelope built from the committed journal opens to its proof
+        let envelope = ProofEnvelope::new(
+            backend.info().backend,
+            backend.info().sdk_version,
+            ReceiptKind::Composite.proof_mode(),
+            backend.program_hash(&program),
+            journal.clone(),
+            proof.clone(),
+        );
+        let opened = backend.open_envelope(&program, &envelope.to_bytes()).unwrap();
+        assert_eq!(opened.as_ref(), proof.as_slice());
+
+        // Public values the proof does not commit are rejected
+        let forged = ProofEnvelope { public_values: vec![0xCD; 32], ..envelope };
+        assert!(backend.open_envelope(&program, &forged.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_program_versions_route_circuits() {
+        let mut backend = Risc0Backend::new(Risc0Config::default());
+        let registry = Arc::new(ProgramRegistry::new());
+        registry.register_version("message_verify", vec![0x7f, 1], [1; 32]).unwrap();
+        backend.versions = Some(registry.clone());
+
+        // The active version runs, pinned while the circuit lives
+        let program = ProgramDescriptor::new(crate::profiles::MESSAGE_VERIFY, [0x11; 32]).encode();
+        let circuit = backend.create_circuit(&program, &[]).unwrap();
+        assert_eq!(circuit.elf(), &[0x7f, 1]);
+        assert_eq!(registry.in_flight("message_verify", 1), 1);
+        drop(circuit);
+        assert_eq!(registry.in_flight("message_verify", 1), 0);
+
+        // Superseded versions are still checked during their overlap window
+        registry.register_version("message_verify", vec![0x7f, 2], [2; 32]).unwrap();
+        let circuits = backend.verification_circuits(&program).unwrap();
+        let elfs: Vec<&[u8]> = circuits.iter().map(|circuit| circuit.elf()).collect();
+        assert_eq!(elfs, [&[0x7f, 2][..], &[0x7f, 1][..]]);
+    }
+}
@@ -11,6 +11,7 @@
 //! Cache implementation for RISC0 circuits and proofs
 
 use std::sync::Arc;
+use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 use parking_lot::RwLock;
 use lru::LruCache;
@@ -19,6 +20,10 @@ use sha2::{Sha256, Digest};
 use risc0_zkvm::{Receipt, ProverOpts};
 
 use super::types::Risc0Circuit;
+use crate::compression;
+use crate::events::CacheKind;
+use crate::hasher::HashFunction;
+use crate::persist::{CachePersistence, PersistedEntry};
 
 /// Cache entry for a compiled circuit
 #[derive(Clone)]
@@ -38,8 +43,10 @@ pub struct CircuitCacheEntry {
 /// Cache entry for a proof
 #[derive(Clone)]
 pub struct ProofCacheEntry {
-    /// Proof bytes
+    /// Proof bytes, zstd-compressed while stored if compression is enabled
     pub proof: Vec<u8>,
+    /// Size of the proof before compression
+    pub proof_size: usize,
     /// Program hash
     pub program_hash: [u8; 32],
     /// Input hash
@@ -50,6 +57,8 @@ pub struct ProofCacheEntry {
     pub last_access: SystemTime,
     /// Number of times accessed
     pub access_count: u64,
+    /// Time this entry stays valid, overriding the cache-wide maximum age
+    pub ttl: Option<Duration>,
 }
 
 /// Cache configuration
@@ -63,6 +72,12 @@ pub struct CacheConfig {
     pub max_age: Duration,
     /// Whether to enable proof caching
     pub enable_proof_cache: bool,
+    /// Directory cache entries are persisted to so they survive restarts
+    pub persist_path: Option<PathBuf>,
+    /// Whether to store proofs zstd-compressed, in memory and on disk
+    pub compress_proofs: bool,
+    /// Hash function for program hashes and cache keys
+    pub hash_function: HashFunction,
 }
 
 impl Default for CacheConfig {
@@ -72,6 +87,9 @@ impl Default for CacheConfig {
             max_proofs: 1000,
             max_age: Duration::from_secs(3600), // 1 hour
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: HashFunction::Sha256,
         }
     }
 }
@@ -81,20 +99,33 @@ impl Default for CacheConfig {
 pub struct CircuitCache {
     /// Cached compiled circuits
     circuits: RwLock<LruCache<[u8; 32], CircuitCacheEntry>>,
-    /// Cached proofs
+    /// Cached proofs keyed by the combined program and input hash
     proofs: RwLock<LruCache<[u8; 32], ProofCacheEntry>>,
+    /// Disk persistence, if configured
+    persistence: Option<CachePersistence>,
     /// Cache configuration
     config: CacheConfig,
 }
 
 impl CircuitCache {
-    /// Create a new circuit cache with the given configuration
+    /// Create a new circuit cache with the given configuration.
+    ///
+    /// If a persist path is configured, entries persisted by a previous process are
+    /// reloaded. A persist path that cannot be opened disables persistence.
     pub fn new(config: CacheConfig) -> Self {
-        Self {
+        let persistence = config.persist_path.as_ref().and_then(|path| {
+            CachePersistence::open(path)
+                .map_err(|e| tracing::warn!(path = %path.display(), "cache persistence disabled: {}", e))
+                .ok()
+        });
+        let cache = Self {
             circuits: RwLock::new(LruCache::new(NonZeroUsize::new(config.max_circuits).unwrap())),
             proofs: RwLock::new(LruCache::new(NonZeroUsize::new(config.max_proofs).unwrap())),
+            persistence,
             config,
-        }
+        };
+        cache.reload();
+        cache
     }
 
     /// Get circuit ELF bytes from cache
@@ -109,13 +140,15 @@ impl CircuitCache {
                 }
             }
             circuits.pop(&hash);
+            self.unpersist(CacheKind::Circuit, &hash);
         }
         None
     }
 
-    /// Store circuit ELF bytes in cache
-    pub fn store_circuit(&self, program: &[u8], elf_bytes: Vec<u8>, compile_time: Duration) {
+    /// Store circuit ELF bytes in cache, returning the key of any evicted entry
+    pub fn store_circuit(&self, program: &[u8], elf_bytes: Vec<u8>, compile_time: Duration) -> Option<[u8; 32]> {
         let hash = self.hash_program(program);
+        self.persist(CacheKind::Circuit, hash, hash, None, compile_time, None, &elf_bytes);
         let entry = CircuitCacheEntry {
             elf_bytes,
             hash,
@@ -123,51 +156,98 @@ impl CircuitCache {
             access_count: 1,
             compile_time,
         };
-        self.circuits.write().put(hash, entry);
+        let evicted = self.circuits.write().push(hash, entry)
+            .map(|(key, _)| key)
+            .filter(|key| *key != hash);
+        if let Some(key) = &evicted {
+            self.unpersist(CacheKind::Circuit, key);
+        }
+        evicted
     }
 
-    /// Get proof from cache
+    /// Get proof from cache, decompressed
     pub fn get_proof(&self, program: &[u8], input: &[u8]) -> Option<ProofCacheEntry> {
         if !self.config.enable_proof_cache {
             return None;
         }
 
-        let hash = self.hash_program(program);
+        let key = self.proof_key(program, input);
         let mut proofs = self.proofs.write();
         
-        if let Some(entry) = proofs.get(&hash) {
+        if let Some(entry) = proofs.get(&key) {
             if let Ok(age) = SystemTime::now().duration_since(entry.last_access) {
-                if age < self.config.max_age {
-                    return Some(entry.clone());
+                if age < entry.ttl.unwrap_or(self.config.max_age) {
+                    if !compression::is_compressed(&entry.proof) {
+                        return Some(entry.clone());
+                    }
+                    match compression::decompress(&entry.proof) {
+                        Ok(proof) => return Some(ProofCacheEntry { proof, ..entry.clone() }),
+                        Err(e) => tracing::warn!("dropping undecodable cached proof: {}", e),
+                    }
                 }
             }
-            proofs.pop(&hash);
+            proofs.pop(&key);
+            self.unpersist(CacheKind::Proof, &key);
         }
         None
     }
 
-    /// Store proof in cache
+    /// Time until a cached proof expires, without counting as an access
+    pub fn proof_expires_in(&self, program: &[u8], input: &[u8]) -> Option<Duration> {
+        let key = self.proof_key(program, input);
+        let proofs = self.proofs.read();
+        let entry = proofs.peek(&key)?;
+        let age = SystemTime::now().duration_since(entry.last_access).unwrap_or_default();
+        entry.ttl.unwrap_or(self.config.max_age).checked_sub(age)
+    }
+
+    /// Store proof in cache, returning the key of any evicted entry
     pub fn store_proof(
         &self,
         program: &[u8],
         input: &[u8],
         proof: Vec<u8>,
         generation_time: Duration,
-    ) {
+    ) -> Option<[u8; 32]> {
+        self.store_proof_with_ttl(program, input, proof, generation_time, None)
+    }
+
+    /// Store proof in cache with a per-entry time to live
+    pub fn store_proof_with_ttl(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        proof: Vec<u8>,
+        generation_time: Duration,
+        ttl: Option<Duration>,
+    ) -> Option<[u8; 32]> {
         if !self.config.enable_proof_cache {
-            return;
+            return None;
         }
 
-        let hash = self.hash_program(program);
+        let program_hash = self.hash_program(program);
+        let input_hash = self.hash_program(input);
+        let key = self.combine_hashes(&program_hash, &input_hash);
+        let proof_size = proof.len();
+        let proof = self.compress_proof(proof);
+        self.persist(CacheKind::Proof, key, program_hash, Some(input_hash), generation_time, ttl, &proof);
         let entry = ProofCacheEntry {
             proof,
-            program_hash: hash,
-            input_hash: self.hash_program(input),
+            proof_size,
+            program_hash,
+            input_hash,
             generation_time,
             last_access: SystemTime::now(),
             access_count: 1,
+            ttl,
         };
-        self.proofs.write().put(hash, entry);
+        let evicted = self.proofs.write().push(key, entry)
+            .map(|(evicted, _)| evicted)
+            .filter(|evicted| *evicted != key);
+        if let Some(evicted) = &evicted {
+            self.unpersist(CacheKind::Proof, evicted);
+        }
+        evicted
     }
 
     /// Clear expired cache entries
@@ -182,16 +262,18 @@ impl CircuitCache {
             .collect();
         for k in expired {
             circuits.pop(&k);
+            self.unpersist(CacheKind::Circuit, &k);
         }
 
         // Clear expired proofs
         let mut proofs = self.proofs.write();
         let expired: Vec<_> = proofs.iter()
-            .filter(|(_, entry)| entry.last_access.elapsed().unwrap() >= self.config.max_age)
+            .filter(|(_, entry)| entry.last_access.elapsed().unwrap() >= entry.ttl.unwrap_or(self.config.max_age))
             .map(|(k, _)| *k)
             .collect();
         for k in expired {
             proofs.pop(&k);
+            self.unpersist(CacheKind::Proof, &k);
         }
     }
 
@@ -199,6 +281,11 @@ impl CircuitCache {
     pub fn clear_all(&self) {
         self.circuits.write().clear();
         self.proofs.write().clear();
+        if let Some(persistence) = &self.persistence {
+            if let Err(e) = persistence.clear() {
+                tracing::warn!("failed to clear persisted cache: {}", e);
+            }
+        }
     }
 
     /// Get cache statistics
@@ -213,15 +300,111 @@ impl CircuitCache {
             max_proofs: self.config.max_proofs,
             circuit_hits: circuits.iter().map(|e| e.1.access_count).sum(),
             proof_hits: proofs.iter().map(|e| e.1.access_count).sum(),
+            proof_bytes: proofs.iter().map(|e| e.1.proof_size).sum(),
+            stored_proof_bytes: proofs.iter().map(|e| e.1.proof.len()).sum(),
+        }
+    }
+
+    /// Reload entries persisted by a previous process
+    fn reload(&self) {
+        let persistence = match &self.persistence {
+            Some(persistence) => persistence,
+            None => return,
+        };
+
+        for (entry, bytes) in persistence.load(CacheKind::Circuit) {
+            if let Some(hash) = entry.key_bytes() {
+                self.circuits.write().push(hash, CircuitCacheEntry {
+                    elf_bytes: bytes,
+                    hash,
+                    last_access: entry.stored_at,
+                    access_count: 0,
+                    compile_time: entry.generation_time,
+                });
+            }
+        }
+        for (entry, bytes) in persistence.load(CacheKind::Proof) {
+            if let (Some(key), Some(program_hash), Some(input_hash)) =
+                (entry.key_bytes(), entry.program_hash_bytes(), entry.input_hash_bytes())
+            {
+                // Entries are reloaded as persisted, whether compressed or not
+                self.proofs.write().push(key, ProofCacheEntry {
+                    proof_size: compression::decompressed_size(&bytes).unwrap_or(bytes.len()),
+                    proof: bytes,
+                    program_hash,
+                    input_hash,
+                    generation_time: entry.generation_time,
+                    last_access: entry.stored_at,
+                    access_count: 0,
+                    ttl: entry.ttl,
+                });
+            }
+        }
+    }
+
+    /// Compress a proof for storage if compression is enabled
+    fn compress_proof(&self, proof: Vec<u8>) -> Vec<u8> {
+        if !self.config.compress_proofs {
+            return proof;
+        }
+        match compression::compress(&proof) {
+            Ok(compressed) => compressed,
+            Err(e) => {
+                tracing::warn!("storing proof uncompressed: {}", e);
+                proof
+            }
+        }
+    }
+
+    /// Persist an entry if persistence is configured
+    #[allow(clippy::too_many_arguments)]
+    fn persist(
+        &self,
+        kind: CacheKind,
+        key: [u8; 32],
+        program_hash: [u8; 32],
+        input_hash: Option<[u8; 32]>,
+        generation_time: Duration,
+        ttl: Option<Duration>,
+        contents: &[u8],
+    ) {
+        if let Some(persistence) = &self.persistence {
+            let entry = PersistedEntry {
+                kind,
+                key: hex::encode(key),
+                blob: String::new(),
+                program_hash: hex::encode(program_hash),
+                input_hash: input_hash.map(hex::encode),
+                generation_time,
+                stored_at: SystemTime::now(),
+                ttl,
+            };
+            if let Err(e) = persistence.persist(entry, contents) {
+                tracing::warn!("failed to persist cache entry: {}", e);
+            }
         }
     }
 
+    /// Remove a persisted entry if persistence is configured
+    fn unpersist(&self, kind: CacheKind, key: &[u8; 32]) {
+        if let Some(persistence) = &self.persistence {
+            if let Err(e) = persistence.remove(kind, key) {
+                tracing::warn!("failed to remove persisted cache entry: {}", e);
+            }
+        }
+    }
+
+    /// Proof cache key covering both the program and its input
+    fn proof_key(&self, program: &[u8], input: &[u8]) -> [u8; 32] {
+        self.combine_hashes(&self.hash_program(program), &self.hash_program(input))
+    }
+
+    fn combine_hashes(&self, program_hash: &[u8; 32], input_hash: &[u8; 32]) -> [u8; 32] {
+        self.config.hash_function.combine(program_hash, input_hash)
+    }
+
     fn hash_program(&self, program: &[u8]) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(program);
-        let mut hash = [0u8; 32];
-        hash.copy_from_slice(&hasher.finalize());
-        hash
+        self.config.hash_function.hash(program)
     }
 }
 
@@ -240,4 +423,19 @@ pub struct CacheStats {
     pub circuit_hits: u64,
     /// Total number of proof cache hits
     pub proof_hits: u64,
-} 
\ No newline at end of file
+    /// Total size of the cached proofs before compression
+    pub proof_bytes: usize,
+    /// Total size of the cached proofs as stored
+    pub stored_proof_bytes: usize,
+} 
+
+impl CacheStats {
+    /// Ratio of the stored to the uncompressed size of the cached proofs, 1.0 if
+    /// no proofs are cached
+    pub fn compression_ratio(&self) -> f64 {
+        if self.proof_bytes == 0 {
+            return 1.0;
+        }
+        self.stored_proof_bytes as f64 / self.proof_bytes as f64
+    }
+}
@@ -0,0 +1,75 @@
+//! Checkpoints of segmented RISC0 proving jobs
+//!
+//! The RISC0 executor splits long executions into segments that are proven one at a
+//! time. A [`ProvingCheckpoint`] carries the executed segments together with the
+//! receipts of the segments proven so far, so a worker that is being drained can
+//! hand a long-running job to another worker, which resumes at the first unproven
+//! segment instead of starting over.
+
+use serde::{Serialize, Deserialize};
+use risc0_zkvm::{Segment, SegmentReceipt};
+
+use crate::error::ZkError;
+
+/// Executed segments and completed segment receipts of a proving job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvingCheckpoint {
+    /// Program bytes
+    pub program: Vec<u8>,
+    /// Journal committed by the guest during execution
+    pub journal: Vec<u8>,
+    /// Bincode-encoded segments produced by the executor
+    pub segments: Vec<Vec<u8>>,
+    /// Bincode-encoded receipts of the segments proven so far, in segment order
+    pub receipts: Vec<Vec<u8>>,
+}
+
+impl ProvingCheckpoint {
+    /// Number of segments of the execution
+    pub fn total_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Number of segments already proven
+    pub fn proven_segments(&self) -> usize {
+        self.receipts.len()
+    }
+
+    /// Whether every segment is proven
+    pub fn is_complete(&self) -> bool {
+        self.proven_segments() >= self.total_segments()
+    }
+
+    /// Decode the first unproven segment
+    pub(crate) fn next_segment(&self) -> Result<Segment, ZkError> {
+        let bytes = self.segments.get(self.proven_segments())
+            .ok_or_else(|| ZkError::InvalidInput("Checkpoint has no unproven segments".into()))?;
+        bincode::deserialize(bytes)
+            .map_err(|e| ZkError::InvalidInput(format!("Invalid checkpoint segment: {}", e)))
+    }
+
+    /// Record the receipt of the first unproven segment
+    pub(crate) fn push_receipt(&mut self, receipt: &SegmentReceipt) -> Result<(), ZkError> {
+        let bytes = bincode::serialize(receipt)
+            .map_err(|e| ZkError::Backend(format!("Failed to serialize segment receipt: {}", e)))?;
+        self.receipts.push(bytes);
+        Ok(())
+    }
+
+    /// Decode the receipts of all proven segments
+    pub(crate) fn segment_receipts(&self) -> Result<Vec<SegmentReceipt>, ZkError> {
+        self.receipts.iter()
+            .map(|bytes| bincode::deserialize(bytes)
+                .map_err(|e| ZkError::InvalidInput(format!("Invalid checkpoint receipt: {}", e))))
+            .collect()
+    }
+}
+
+/// Result of resuming a checkpoint
+#[derive(Debug, Clone)]
+pub enum ProvingOutcome {
+    /// Every segment is proven; holds the serialized receipt
+    Finished(Vec<u8>),
+    /// Proving stopped because the worker is draining
+    Suspended(ProvingCheckpoint),
+}
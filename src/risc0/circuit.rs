@@ -9,6 +9,7 @@
 #![allow(unused_macros)]
 
 use std::error::Error;
+use std::sync::Arc;
 use serde::{Serialize, Deserialize};
 use risc0_zkvm::{
     Prover, ProverOpts,
@@ -16,12 +17,36 @@ use risc0_zkvm::{
     ExecutorEnv,
     sha::Digest,
     Journal,
+    compute_image_id,
 };
 use sha2::{Sha256, Digest as ShaDigest};
 
+use crate::chains::eth::EthHeader;
+use crate::cluster::decode_shard;
+use crate::domain::MessageNonce;
 use crate::error::ZkError;
+use crate::guest::GuestProgram;
+use crate::hasher::HashFunction;
+use crate::journal::{BlockVerifyOutput, EcdsaVerifyOutput, MessageAggregateOutput, MessageVerifyOutput, TxVerifyOutput};
+use crate::merkle;
+use crate::signatures::ecdsa::{self, EcdsaWitness, VerifiedSignature};
 use super::Risc0Circuit;
 
+/// Guest program of the message verification circuit
+pub const MESSAGE_VERIFY_ELF: &[u8] = include_bytes!("../../target/riscv/message_verify.elf");
+
+/// Guest program of the transaction verification circuit
+pub const TX_VERIFY_ELF: &[u8] = include_bytes!("../../target/riscv/tx_verify.elf");
+
+/// Guest program of the block verification circuit
+pub const BLOCK_VERIFY_ELF: &[u8] = include_bytes!("../../target/riscv/block_verify.elf");
+
+/// Guest program of the Ethereum header verification circuit
+pub const ETH_HEADER_VERIFY_ELF: &[u8] = include_bytes!("../../target/riscv/eth_header_verify.elf");
+
+/// Guest program of the ECDSA signature circuit
+pub const ECDSA_VERIFY_ELF: &[u8] = include_bytes!("../../target/riscv/ecdsa_verify.elf");
+
 /// Message verification circuit for RISC0
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageVerifyCircuit {
@@ -29,10 +54,14 @@ pub struct MessageVerifyCircuit {
     message_bytes: Vec<u8>,
     /// Expected hash of the message
     expected_hash: Digest,
+    /// Function the message is hashed with
+    hash_function: HashFunction,
+    /// Source chain and nonce of the message the proof commits to
+    nonce: Option<MessageNonce>,
 }
 
 impl MessageVerifyCircuit {
-    /// Create a new message verification circuit
+    /// Create a new message verification circuit hashing with SHA-256
     pub fn new(program: &[u8]) -> Result<Self, ZkError> {
         if program.len() < 32 {
             return Err(ZkError::InvalidInput("program too short".to_string()));
@@ -45,9 +74,31 @@ impl MessageVerifyCircuit {
         Ok(Self {
             message_bytes,
             expected_hash,
+            hash_function: HashFunction::Sha256,
+            nonce: None,
         })
     }
 
+    /// Hash the message with `hash_function`: SHA-256, Keccak-256, Blake2b or BLAKE3
+    pub fn with_hash_function(mut self, hash_function: HashFunction) -> Result<Self, ZkError> {
+        if hash_function.message_hash_id().is_none() {
+            return Err(ZkError::InvalidInput(format!("Message verification does not support {}", hash_function)));
+        }
+        self.hash_function = hash_function;
+        Ok(self)
+    }
+
+    /// Function the message is hashed with
+    pub fn hash_function(&self) -> HashFunction {
+        self.hash_function
+    }
+
+    /// Commit the proof to the source chain and nonce of the message
+    pub fn with_nonce(mut self, nonce: MessageNonce) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
     /// Get the program bytes for this circuit
     pub fn get_program_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(32 + self.message_bytes.len());
@@ -59,11 +110,14 @@ impl MessageVerifyCircuit {
 
 impl Risc0Circuit for MessageVerifyCircuit {
     fn elf(&self) -> &[u8] {
-        include_bytes!("../../target/riscv/message_verify.elf")
+        MESSAGE_VERIFY_ELF
     }
 
     fn public_inputs(&self) -> Vec<u32> {
-        self.expected_hash.as_words().to_vec()
+        let mut inputs = self.expected_hash.as_words().to_vec();
+        inputs.push(u32::from(self.hash_function.message_hash_id().expect("checked on construction")));
+        inputs.extend(nonce_words(self.nonce));
+        inputs
     }
 
     fn private_inputs(&self) -> Vec<u8> {
@@ -72,10 +126,200 @@ impl Risc0Circuit for MessageVerifyCircuit {
 
     fn verify_receipt(&self, receipt: &Receipt) -> bool {
         // Verify that the receipt contains the expected hash
-        let journal_bytes: Vec<u8> = receipt.journal.decode().unwrap_or_default();
-        let computed_hash = Digest::try_from(&journal_bytes[..32])
-            .unwrap_or_default();
-        computed_hash == self.expected_hash
+        match MessageVerifyOutput::decode(&receipt.journal.bytes) {
+            Ok(output) => Digest::from(output.hash) == self.expected_hash && output.nonce == self.nonce,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Message aggregation circuit proving many messages at once
+#[derive(Debug, Clone)]
+pub struct MessageAggregateCircuit {
+    /// Aggregated messages
+    messages: Vec<Vec<u8>>,
+    /// SHA-256 of every message
+    hashes: Vec<[u8; 32]>,
+    /// Merkle root over the message hashes
+    root: [u8; 32],
+    /// Source chain and nonce of the first message
+    nonce: Option<MessageNonce>,
+}
+
+impl MessageAggregateCircuit {
+    /// Create an aggregation circuit from shard-encoded messages, checking they
+    /// make up the tree with `expected_root`
+    pub fn new(expected_root: &[u8], input: &[u8]) -> Result<Self, ZkError> {
+        let messages = decode_shard(input)?;
+        if messages.is_empty() {
+            return Err(ZkError::InvalidInput("no messages to aggregate".to_string()));
+        }
+
+        let hashes: Vec<[u8; 32]> = messages.iter().map(|message| Sha256::digest(message).into()).collect();
+        let mut leaves: Vec<_> = hashes.iter().map(merkle::leaf).collect();
+        let root = merkle::root_in_place(&mut leaves);
+        if expected_root != root {
+            return Err(ZkError::InvalidInput("messages do not match the expected root".to_string()));
+        }
+
+        Ok(Self { messages, hashes, root, nonce: None })
+    }
+
+    /// Commit the proof to the source chain and nonce of the first message, the
+    /// others following consecutively
+    pub fn with_nonce(mut self, nonce: MessageNonce) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+}
+
+impl Risc0Circuit for MessageAggregateCircuit {
+    fn elf(&self) -> &[u8] {
+        include_bytes!("../../target/riscv/message_aggregate.elf")
+    }
+
+    fn public_inputs(&self) -> Vec<u32> {
+        // Message count followed by every expected hash
+        let mut inputs = vec![self.messages.len() as u32];
+        for hash in &self.hashes {
+            inputs.extend(hash.chunks(4).map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap())));
+        }
+        inputs.extend(nonce_words(self.nonce));
+        inputs
+    }
+
+    fn private_inputs(&self) -> Vec<u8> {
+        // The guest reads every message as a separate value
+        self.messages.iter()
+            .flat_map(|message| risc0_zkvm::serde::to_vec(message).unwrap())
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    fn verify_receipt(&self, receipt: &Receipt) -> bool {
+        match MessageAggregateOutput::decode(&receipt.journal.bytes) {
+            Ok(output) => output.root == self.root && output.count == self.messages.len() as u64 && output.nonce == self.nonce,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Composition of message verification receipts into one proof
+///
+/// The guest verifies every receipt as an assumption and commits to the same Merkle
+/// root over the message hashes as [`MessageAggregateCircuit`]. All receipts must be
+/// bound to the domain the composed proof is generated for.
+#[derive(Debug, Clone)]
+pub struct MessageComposeCircuit {
+    /// Message verification receipts
+    receipts: Vec<Receipt>,
+    /// Image ID of the message verification guest
+    image_id: Digest,
+    /// Merkle root over the message hashes
+    root: [u8; 32],
+    /// Source chain and nonce of the first message
+    nonce: Option<MessageNonce>,
+}
+
+impl MessageComposeCircuit {
+    /// Create a composition circuit from shard-encoded serialized receipts, checking
+    /// they make up the tree with `expected_root`
+    pub fn new(expected_root: &[u8], input: &[u8]) -> Result<Self, ZkError> {
+        let receipts = decode_shard(input)?
+            .iter()
+            .map(|receipt| bincode::deserialize::<Receipt>(receipt)
+                .map_err(|e| ZkError::InvalidInput(format!("invalid receipt: {}", e))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let circuit = Self::from_receipts(receipts)?;
+        if expected_root != circuit.root {
+            return Err(ZkError::InvalidInput("receipts do not match the expected root".to_string()));
+        }
+        Ok(circuit)
+    }
+
+    /// Create a composition circuit over message verification receipts
+    pub fn from_receipts(receipts: Vec<Receipt>) -> Result<Self, ZkError> {
+        if receipts.is_empty() {
+            return Err(ZkError::InvalidInput("no receipts to compose".to_string()));
+        }
+
+        let outputs = receipts.iter()
+            .map(|receipt| MessageVerifyOutput::decode(&receipt.journal.bytes))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut leaves: Vec<_> = outputs.iter().map(|output| merkle::leaf(&output.hash)).collect();
+        let root = merkle::root_in_place(&mut leaves);
+
+        // Messages carry consecutive nonces from one chain, or none at all
+        let nonce = outputs[0].nonce;
+        for (offset, output) in outputs.iter().enumerate() {
+            let expected = nonce.map(|first| MessageNonce::new(first.source_chain, first.nonce.wrapping_add(offset as u64)));
+            if output.nonce != expected {
+                return Err(ZkError::InvalidInput("receipts do not carry consecutive message nonces".to_string()));
+            }
+        }
+
+        let image_id = compute_image_id(MESSAGE_VERIFY_ELF)
+            .map_err(|e| ZkError::Backend(format!("failed to compute image ID: {}", e)))?;
+        Ok(Self { receipts, image_id, root, nonce })
+    }
+
+    /// Verify receipts of the message verification guest with `image_id` instead of
+    /// the built-in one
+    pub fn with_verify_image_id(mut self, image_id: Digest) -> Self {
+        self.image_id = image_id;
+        self
+    }
+
+    /// Merkle root over the message hashes
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Source chain and nonce of the first message, if the receipts commit nonces
+    pub fn nonce(&self) -> Option<MessageNonce> {
+        self.nonce
+    }
+}
+
+impl Risc0Circuit for MessageComposeCircuit {
+    fn elf(&self) -> &[u8] {
+        include_bytes!("../../target/riscv/message_compose.elf")
+    }
+
+    fn public_inputs(&self) -> Vec<u32> {
+        // Image ID of the composed guest followed by the receipt count
+        let mut inputs = self.image_id.as_words().to_vec();
+        inputs.push(self.receipts.len() as u32);
+        inputs
+    }
+
+    fn private_inputs(&self) -> Vec<u8> {
+        // The guest reads every journal as a separate value
+        self.receipts.iter()
+            .flat_map(|receipt| risc0_zkvm::serde::to_vec(&receipt.journal.bytes).unwrap())
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    fn verify_receipt(&self, receipt: &Receipt) -> bool {
+        match MessageAggregateOutput::decode(&receipt.journal.bytes) {
+            Ok(output) => output.root == self.root && output.count == self.receipts.len() as u64 && output.nonce == self.nonce,
+            Err(_) => false,
+        }
+    }
+
+    fn assumptions(&self) -> Vec<Receipt> {
+        self.receipts.clone()
+    }
+}
+
+/// Guest input words of an optional message nonce: a flag, then the nonce
+fn nonce_words(nonce: Option<MessageNonce>) -> Vec<u32> {
+    match nonce {
+        Some(nonce) => std::iter::once(1)
+            .chain(nonce.to_bytes().chunks(4).map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap())))
+            .collect(),
+        None => vec![0],
     }
 }
 
@@ -119,20 +363,10 @@ impl Risc0Circuit for TxVerifyCircuit {
     
     fn verify_receipt(&self, receipt: &Receipt) -> bool {
         // Check that the journal contains our expected hash
-        // and any additional transaction verification data
-        let journal_bytes: Vec<u8> = receipt.journal.decode().unwrap_or_default();
-        
-        if journal_bytes.len() < 64 {
-            return false;
+        match TxVerifyOutput::decode(&receipt.journal.bytes) {
+            Ok(output) => output.hash == self.expected_hash,
+            Err(_) => false,
         }
-        
-        let mut computed_hash = [0u8; 32];
-        computed_hash.copy_from_slice(&journal_bytes[0..32]);
-        
-        // Additional transaction verification could be done here
-        // using the rest of the journal data
-        
-        computed_hash == self.expected_hash
     }
 }
 
@@ -187,44 +421,201 @@ impl Risc0Circuit for BlockVerifyCircuit {
     
     fn verify_receipt(&self, receipt: &Receipt) -> bool {
         // Check that the journal contains our expected hash and block data
-        let journal_bytes: Vec<u8> = receipt.journal.decode().unwrap_or_default();
-        
-        if journal_bytes.len() < 56 { // 32 + 8 + 8 + 8
-            return false;
-        }
-        
-        // Verify hash
-        let mut computed_hash = [0u8; 32];
-        computed_hash.copy_from_slice(&journal_bytes[0..32]);
-        if computed_hash != self.expected_hash {
-            return false;
-        }
-        
-        // Verify block number
-        let mut block_number_bytes = [0u8; 8];
-        block_number_bytes.copy_from_slice(&journal_bytes[32..40]);
-        let block_number = u64::from_le_bytes(block_number_bytes);
-        if block_number != self.expected_number {
+        let output = match BlockVerifyOutput::decode(&receipt.journal.bytes) {
+            Ok(output) => output,
+            Err(_) => return false,
+        };
+
+        // Verify hash and block number
+        if output.hash != self.expected_hash || output.number != self.expected_number {
             return false;
         }
-        
+
         // Verify timestamp is reasonable
-        let mut timestamp_bytes = [0u8; 8];
-        timestamp_bytes.copy_from_slice(&journal_bytes[40..48]);
-        let timestamp = u64::from_le_bytes(timestamp_bytes);
-        if timestamp < 1600000000 || timestamp > 2000000000 {
+        if output.timestamp < 1600000000 || output.timestamp > 2000000000 {
             return false;
         }
-        
+
         // Verify gas used <= gas limit
-        let mut gas_bytes = [0u8; 16];
-        gas_bytes.copy_from_slice(&journal_bytes[48..64]);
-        let gas_used = u64::from_le_bytes(gas_bytes[0..8].try_into().unwrap());
-        let gas_limit = u64::from_le_bytes(gas_bytes[8..16].try_into().unwrap());
-        if gas_used > gas_limit {
-            return false;
+        output.gas_used <= output.gas_limit
+    }
+}
+
+/// Ethereum header verification circuit
+///
+/// The guest RLP-decodes a real Ethereum block header, checks its Keccak-256 hash
+/// and number against the expected ones and commits the header's number,
+/// timestamp and gas in the [`BlockVerifyOutput`] layout, with the block hash as
+/// the hash.
+pub struct EthHeaderCircuit {
+    /// RLP-encoded block header
+    header_bytes: Vec<u8>,
+    /// Header decoded on the host
+    header: EthHeader,
+    /// Circuit ELF bytes
+    elf_bytes: Vec<u8>,
+}
+
+impl EthHeaderCircuit {
+    /// Create a header verification circuit, checking on the host that the header
+    /// decodes and has the expected hash and number
+    pub fn new(header_bytes: Vec<u8>, expected_hash: [u8; 32], expected_number: u64, elf_bytes: Vec<u8>) -> Result<Self, ZkError> {
+        let header = EthHeader::decode(&header_bytes)
+            .map_err(|e| ZkError::InvalidInput(format!("Invalid Ethereum header: {}", e)))?;
+        if header.hash != expected_hash {
+            return Err(ZkError::InvalidInput(format!(
+                "Header hashes to {}, expected {}",
+                hex::encode(header.hash),
+                hex::encode(expected_hash)
+            )));
         }
-        
-        true
+        if header.number != expected_number {
+            return Err(ZkError::InvalidInput(format!(
+                "Header is block {}, expected {}",
+                header.number, expected_number
+            )));
+        }
+        Ok(Self { header_bytes, header, elf_bytes })
+    }
+
+    /// Decoded header
+    pub fn header(&self) -> &EthHeader {
+        &self.header
+    }
+}
+
+impl Risc0Circuit for EthHeaderCircuit {
+    fn elf(&self) -> &[u8] {
+        &self.elf_bytes
+    }
+
+    fn public_inputs(&self) -> Vec<u32> {
+        // Block hash as u32 words followed by the block number as two u32s
+        let mut inputs = self.header.hash.chunks(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        inputs.extend_from_slice(&[
+            (self.header.number & 0xFFFFFFFF) as u32,
+            (self.header.number >> 32) as u32,
+        ]);
+        inputs
+    }
+
+    fn private_inputs(&self) -> Vec<u8> {
+        // The guest reads the header as a single value
+        risc0_zkvm::serde::to_vec(&self.header_bytes).unwrap()
+            .into_iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    fn verify_receipt(&self, receipt: &Receipt) -> bool {
+        match BlockVerifyOutput::decode(&receipt.journal.bytes) {
+            Ok(output) => {
+                output.hash == self.header.hash
+                    && output.number == self.header.number
+                    && output.timestamp == self.header.timestamp
+                    && output.gas_used == self.header.gas_used
+                    && output.gas_limit == self.header.gas_limit
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// secp256k1 ECDSA signature circuit
+///
+/// The guest checks the signature of a public key over the expected message hash
+/// and commits the hash and the compressed key, see [`VerifiedSignature`].
+pub struct EcdsaVerifyCircuit {
+    /// Public key and signature over the message hash
+    witness: EcdsaWitness,
+    /// Statement checked on the host
+    statement: VerifiedSignature,
+    /// Circuit ELF bytes
+    elf_bytes: Vec<u8>,
+}
+
+impl EcdsaVerifyCircuit {
+    /// Create a signature circuit, checking on the host that the signature is over
+    /// the expected message hash and verifies
+    pub fn new(witness: EcdsaWitness, expected_hash: [u8; 32], elf_bytes: Vec<u8>) -> Result<Self, ZkError> {
+        if witness.message_hash != expected_hash {
+            return Err(ZkError::InvalidInput(format!(
+                "Signature is over {}, expected {}",
+                hex::encode(witness.message_hash),
+                hex::encode(expected_hash)
+            )));
+        }
+        let statement = ecdsa::verify(&witness)
+            .map_err(|e| ZkError::InvalidInput(format!("Invalid ECDSA witness: {}", e)))?;
+        Ok(Self { witness, statement, elf_bytes })
     }
-} 
\ No newline at end of file
+
+    /// Statement the guest will commit
+    pub fn statement(&self) -> &VerifiedSignature {
+        &self.statement
+    }
+}
+
+impl Risc0Circuit for EcdsaVerifyCircuit {
+    fn elf(&self) -> &[u8] {
+        &self.elf_bytes
+    }
+
+    fn public_inputs(&self) -> Vec<u32> {
+        // Message hash as u32 words
+        self.witness.message_hash.chunks(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    fn private_inputs(&self) -> Vec<u8> {
+        // The guest reads the public key and the signature as one value each
+        let mut words = risc0_zkvm::serde::to_vec(&self.witness.public_key).unwrap();
+        words.extend(risc0_zkvm::serde::to_vec(&self.witness.signature).unwrap());
+        words.into_iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    fn verify_receipt(&self, receipt: &Receipt) -> bool {
+        match EcdsaVerifyOutput::decode(&receipt.journal.bytes) {
+            Ok(output) => output.statement == self.statement,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Circuit of a guest program registered by an application
+pub struct GuestProgramCircuit {
+    /// Registered program
+    program: Arc<GuestProgram>,
+    /// Input passed to the guest as-is
+    input: Vec<u8>,
+}
+
+impl GuestProgramCircuit {
+    /// Create a circuit running `program` on `input`
+    pub fn new(program: Arc<GuestProgram>, input: Vec<u8>) -> Self {
+        Self { program, input }
+    }
+}
+
+impl Risc0Circuit for GuestProgramCircuit {
+    fn elf(&self) -> &[u8] {
+        &self.program.elf
+    }
+
+    fn public_inputs(&self) -> Vec<u32> {
+        Vec::new()
+    }
+
+    fn private_inputs(&self) -> Vec<u8> {
+        self.input.clone()
+    }
+
+    fn verify_receipt(&self, receipt: &Receipt) -> bool {
+        self.program.validate(&receipt.journal.bytes)
+    }
+}
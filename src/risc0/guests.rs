@@ -0,0 +1,212 @@
+//! Built-in guest programs loaded from the filesystem
+//!
+//! The built-in circuits embed the guest ELFs found in `target/riscv` when the
+//! crate was compiled. Deployments building their guests reproducibly can instead
+//! point [`super::Risc0Config::guests`] at the ELFs they ship. Only guests with a
+//! pinned image ID or SHA-256 hash are loaded, and an ELF that does not match its
+//! pin is refused, so a tampered file never gets proven or trusted for
+//! verification.
+//!
+//! Guests are named after their circuit: `message_verify`, `tx_verify`,
+//! `block_verify`, `message_aggregate`, `message_compose`, `eth_header_verify` and
+//! `ecdsa_verify`. Unless given an explicit path, a guest is read from `<dir>/<name>.elf`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use parking_lot::RwLock;
+use risc0_zkvm::{compute_image_id, Receipt};
+use sha2::{Digest, Sha256};
+
+use crate::error::ZkError;
+use crate::programs::ProgramLease;
+use crate::profiles::{BLOCK_VERIFY, ECDSA_VERIFY, ETH_HEADER_VERIFY, MESSAGE_AGGREGATE, MESSAGE_COMPOSE, MESSAGE_VERIFY, TX_VERIFY};
+use super::types::Risc0Circuit;
+
+/// Expected identity of a guest ELF
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfPin {
+    /// RISC0 image ID of the ELF
+    ImageId([u8; 32]),
+    /// SHA-256 of the ELF file
+    Sha256([u8; 32]),
+}
+
+/// Location and pins of the guest ELFs to load
+#[derive(Debug, Clone, Default)]
+pub struct GuestElfs {
+    /// Directory holding `<name>.elf` files
+    pub dir: Option<PathBuf>,
+    /// Paths of guests stored elsewhere than in `dir`
+    pub paths: HashMap<String, PathBuf>,
+    /// Pins of the guests to load
+    pub pins: HashMap<String, ElfPin>,
+}
+
+impl GuestElfs {
+    /// Load guests from `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: Some(dir.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Load guest `name`, checking it against `pin`
+    pub fn with_pin(mut self, name: impl Into<String>, pin: ElfPin) -> Self {
+        self.pins.insert(name.into(), pin);
+        self
+    }
+
+    /// Read guest `name` from `path` instead of the directory
+    pub fn with_path(mut self, name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        self.paths.insert(name.into(), path.into());
+        self
+    }
+
+    fn path(&self, name: &str) -> Option<PathBuf> {
+        self.paths.get(name).cloned()
+            .or_else(|| self.dir.as_ref().map(|dir| dir.join(format!("{}.elf", name))))
+    }
+}
+
+/// Loader of pinned guest ELFs, verifying each file once
+#[derive(Debug, Default)]
+pub struct GuestLoader {
+    config: GuestElfs,
+    loaded: RwLock<HashMap<String, Arc<[u8]>>>,
+}
+
+impl GuestLoader {
+    /// Create a loader for the guests in `config`
+    pub fn new(config: GuestElfs) -> Self {
+        Self {
+            config,
+            loaded: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Verified ELF of guest `name`, or `None` if the guest is not pinned and the
+    /// built-in one is used
+    pub fn load(&self, name: &str) -> Result<Option<Arc<[u8]>>, ZkError> {
+        let pin = match self.config.pins.get(name) {
+            Some(pin) => *pin,
+            None => return Ok(None),
+        };
+        if let Some(elf) = self.loaded.read().get(name) {
+            return Ok(Some(elf.clone()));
+        }
+
+        let path = self.config.path(name)
+            .ok_or_else(|| ZkError::InvalidInput(format!("No path configured for guest {}", name)))?;
+        let elf = fs::read(&path)?;
+        let found = match pin {
+            ElfPin::ImageId(_) => {
+                let image_id = compute_image_id(&elf)
+                    .map_err(|e| ZkError::CircuitCompilation(format!("Invalid guest {}: {}", name, e)))?;
+                ElfPin::ImageId(image_id.as_bytes().try_into().expect("image ID is 32 bytes"))
+            }
+            ElfPin::Sha256(_) => ElfPin::Sha256(Sha256::digest(&elf).into()),
+        };
+        if found != pin {
+            return Err(ZkError::GuestPinMismatch {
+                guest: name.to_string(),
+                path: path.display().to_string(),
+            });
+        }
+
+        let elf: Arc<[u8]> = elf.into();
+        self.loaded.write().insert(name.to_string(), elf.clone());
+        Ok(Some(elf))
+    }
+
+    /// Image ID of guest `name` if it is loaded from the filesystem
+    pub fn image_id(&self, name: &str) -> Result<Option<risc0_zkvm::sha::Digest>, ZkError> {
+        self.load(name)?
+            .map(|elf| compute_image_id(&elf)
+                .map_err(|e| ZkError::CircuitCompilation(format!("Invalid guest {}: {}", name, e))))
+            .transpose()
+    }
+}
+
+/// Name of the guest of a built-in circuit type
+pub(crate) fn guest_name(circuit_type: u8) -> Option<&'static str> {
+    match circuit_type {
+        MESSAGE_VERIFY => Some("message_verify"),
+        TX_VERIFY => Some("tx_verify"),
+        BLOCK_VERIFY => Some("block_verify"),
+        MESSAGE_AGGREGATE => Some("message_aggregate"),
+        MESSAGE_COMPOSE => Some("message_compose"),
+        ETH_HEADER_VERIFY => Some("eth_header_verify"),
+        ECDSA_VERIFY => Some("ecdsa_verify"),
+        _ => None,
+    }
+}
+
+/// Built-in circuit running a guest loaded from the filesystem or a program registry
+pub(crate) struct LoadedCircuit {
+    inner: Box<dyn Risc0Circuit>,
+    elf: Arc<[u8]>,
+    _lease: Option<ProgramLease>,
+}
+
+impl LoadedCircuit {
+    pub(crate) fn new(inner: Box<dyn Risc0Circuit>, elf: Arc<[u8]>) -> Self {
+        Self { inner, elf, _lease: None }
+    }
+
+    /// Run the guest of the program version `lease` pins, keeping it pinned for as
+    /// long as the circuit lives
+    pub(crate) fn leased(inner: Box<dyn Risc0Circuit>, lease: ProgramLease) -> Self {
+        Self { inner, elf: lease.version().elf.clone(), _lease: Some(lease) }
+    }
+}
+
+impl Risc0Circuit for LoadedCircuit {
+    fn elf(&self) -> &[u8] {
+        &self.elf
+    }
+
+    fn public_inputs(&self) -> Vec<u32> {
+        self.inner.public_inputs()
+    }
+
+    fn private_inputs(&self) -> Vec<u8> {
+        self.inner.private_inputs()
+    }
+
+    fn verify_receipt(&self, receipt: &Receipt) -> bool {
+        self.inner.verify_receipt(receipt)
+    }
+
+    fn assumptions(&self) -> Vec<Receipt> {
+        self.inner.assumptions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_guests() {
+        let dir = tempfile::tempdir().unwrap();
+        let elf = b"\x7fELF guest".to_vec();
+        fs::write(dir.path().join("tx_verify.elf"), &elf).unwrap();
+        let pin = ElfPin::Sha256(Sha256::digest(&elf).into());
+
+        let loader = GuestLoader::new(GuestElfs::new(dir.path()).with_pin("tx_verify", pin));
+        assert_eq!(loader.load("tx_verify").unwrap().as_deref(), Some(&elf[..]));
+        // Unpinned guests use the built-in ELF
+        assert!(loader.load("block_verify").unwrap().is_none());
+
+        // A file not matching its pin is refused
+        let loader = GuestLoader::new(GuestElfs::new(dir.path()).with_pin("tx_verify", ElfPin::Sha256([0; 32])));
+        assert!(matches!(loader.load("tx_verify"), Err(ZkError::GuestPinMismatch { .. })));
+
+        // A pinned guest that is missing is an error
+        let loader = GuestLoader::new(GuestElfs::new(dir.path()).with_pin("block_verify", pin));
+        assert!(loader.load("block_verify").is_err());
+    }
+}
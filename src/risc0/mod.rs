@@ -3,8 +3,17 @@
 mod backend;
 mod circuit;
 mod cache;
+mod checkpoint;
+mod guests;
+mod registry;
+mod remote;
 mod types;
 
 pub use backend::{Risc0Backend, Risc0Config};
-pub use circuit::MessageVerifyCircuit;
-pub use types::{Risc0Circuit, Risc0Options}; 
\ No newline at end of file
+pub use circuit::{MessageVerifyCircuit, TxVerifyCircuit, BlockVerifyCircuit, EthHeaderCircuit, EcdsaVerifyCircuit, MessageAggregateCircuit, MessageComposeCircuit, GuestProgramCircuit};
+pub use registry::{CircuitConstructor, CircuitRegistry};
+pub use types::{Risc0Circuit, Risc0Options, ReceiptKind};
+pub use cache::CacheStats;
+pub use checkpoint::{ProvingCheckpoint, ProvingOutcome};
+pub use remote::RemoteProving;
+pub use guests::{ElfPin, GuestElfs, GuestLoader};
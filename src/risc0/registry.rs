@@ -0,0 +1,353 @@
+//! Registry of RISC0 circuit types
+//!
+//! A program's [`ProgramDescriptor`] names its circuit type. The [`CircuitRegistry`]
+//! maps each type to a constructor building the circuit from the descriptor and the
+//! proving input.
+//! The built-in circuits are registered by [`CircuitRegistry::new`], and services can
+//! register their own types at runtime. Guest programs registered by applications
+//! share the [`USER_PROGRAM`] type, see [`CircuitRegistry::with_programs`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use parking_lot::RwLock;
+use risc0_zkvm::compute_image_id;
+
+use crate::descriptor::ProgramDescriptor;
+use crate::error::ZkError;
+use crate::guest::{GuestProgram, GuestPrograms};
+use crate::profiles::{BLOCK_VERIFY, ECDSA_VERIFY, ETH_HEADER_VERIFY, MESSAGE_AGGREGATE, MESSAGE_COMPOSE, MESSAGE_VERIFY, TX_VERIFY, USER_PROGRAM};
+use super::circuit::{
+    BlockVerifyCircuit, EcdsaVerifyCircuit, EthHeaderCircuit, GuestProgramCircuit, MessageAggregateCircuit, MessageComposeCircuit,
+    MessageVerifyCircuit, TxVerifyCircuit, BLOCK_VERIFY_ELF, ECDSA_VERIFY_ELF, ETH_HEADER_VERIFY_ELF, TX_VERIFY_ELF,
+};
+use super::guests::{guest_name, GuestLoader, LoadedCircuit};
+use super::types::Risc0Circuit;
+
+/// Builds a circuit from the program's descriptor and the proving input
+pub type CircuitConstructor = Arc<dyn Fn(&ProgramDescriptor, &[u8]) -> Result<Box<dyn Risc0Circuit>, ZkError> + Send + Sync>;
+
+/// Circuit constructors by circuit type
+pub struct CircuitRegistry {
+    constructors: RwLock<HashMap<u8, CircuitConstructor>>,
+}
+
+impl CircuitRegistry {
+    /// Create a registry holding the built-in circuits
+    pub fn new() -> Self {
+        Self::with_guests(Arc::new(GuestLoader::default()))
+    }
+
+    /// Create a registry holding the built-in circuits, running the guests `guests`
+    /// loads from the filesystem in place of the embedded ones
+    pub fn with_guests(guests: Arc<GuestLoader>) -> Self {
+        let registry = Self::empty();
+        registry.register(MESSAGE_VERIFY, builtin(&guests, MESSAGE_VERIFY, message_verify));
+        registry.register(TX_VERIFY, builtin(&guests, TX_VERIFY, tx_verify));
+        registry.register(BLOCK_VERIFY, builtin(&guests, BLOCK_VERIFY, block_verify));
+        registry.register(MESSAGE_AGGREGATE, builtin(&guests, MESSAGE_AGGREGATE, |program: &ProgramDescriptor, input: &[u8]| {
+            let circuit = MessageAggregateCircuit::new(&hash(program)?, input)?;
+            Ok(Box::new(match program.message_nonce() {
+                Some(nonce) => circuit.with_nonce(nonce),
+                None => circuit,
+            }) as Box<dyn Risc0Circuit>)
+        }));
+        let loader = guests.clone();
+        registry.register(MESSAGE_COMPOSE, builtin(&guests, MESSAGE_COMPOSE, move |program: &ProgramDescriptor, input: &[u8]| {
+            // The receipts must start at the nonce the program commits to
+            let circuit = MessageComposeCircuit::new(&hash(program)?, input)?;
+            if circuit.nonce() != program.message_nonce() {
+                return Err(ZkError::InvalidInput("receipts do not start at the program's message nonce".to_string()));
+            }
+            // Composed receipts come from the message verification guest in use
+            Ok(Box::new(match loader.image_id("message_verify")? {
+                Some(image_id) => circuit.with_verify_image_id(image_id),
+                None => circuit,
+            }) as Box<dyn Risc0Circuit>)
+        }));
+        registry.register(ETH_HEADER_VERIFY, builtin(&guests, ETH_HEADER_VERIFY, eth_header_verify));
+        registry.register(ECDSA_VERIFY, builtin(&guests, ECDSA_VERIFY, ecdsa_verify));
+        registry
+    }
+
+    /// Add the guest programs in `programs`
+    pub fn with_programs(self, programs: Arc<GuestPrograms>) -> Self {
+        self.register(USER_PROGRAM, Arc::new(move |program: &ProgramDescriptor, input: &[u8]| {
+            let program = programs.for_descriptor(program)?;
+            Ok(Box::new(GuestProgramCircuit::new(program, input.to_vec())) as Box<dyn Risc0Circuit>)
+        }));
+        self
+    }
+
+    /// Create a registry without any circuits
+    pub fn empty() -> Self {
+        Self {
+            constructors: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Register a circuit type, replacing any constructor registered for it
+    pub fn register(&self, circuit_type: u8, constructor: CircuitConstructor) {
+        self.constructors.write().insert(circuit_type, constructor);
+    }
+
+    /// Run circuit type `circuit_type` with the guest `elf`
+    ///
+    /// Registered types keep their circuit and only swap the guest, unless a program
+    /// embeds its own. Other types run `elf` on the proving input as-is, like
+    /// application guest programs.
+    pub fn register_elf(&self, circuit_type: u8, name: &str, elf: Vec<u8>) {
+        let elf: Arc<[u8]> = elf.into();
+        let base = self.constructors.read().get(&circuit_type).cloned();
+        let constructor: CircuitConstructor = match base {
+            Some(base) => Arc::new(move |program: &ProgramDescriptor, input: &[u8]| {
+                let circuit = base(program, input)?;
+                if program.elf.is_some() {
+                    return Ok(circuit);
+                }
+                Ok(Box::new(LoadedCircuit::new(circuit, elf.clone())) as Box<dyn Risc0Circuit>)
+            }),
+            None => {
+                let guest = Arc::new(GuestProgram::new(name, elf, Arc::new(|_: &[u8]| true)));
+                Arc::new(move |_: &ProgramDescriptor, input: &[u8]| {
+                    Ok(Box::new(GuestProgramCircuit::new(guest.clone(), input.to_vec())) as Box<dyn Risc0Circuit>)
+                })
+            }
+        };
+        self.register(circuit_type, constructor);
+    }
+
+    /// Remove a circuit type, returning whether it was registered
+    pub fn unregister(&self, circuit_type: u8) -> bool {
+        self.constructors.write().remove(&circuit_type).is_some()
+    }
+
+    /// Build the circuit of an encoded `program` for `input`
+    pub fn create(&self, program: &[u8], input: &[u8]) -> Result<Box<dyn Risc0Circuit>, ZkError> {
+        self.create_from(&ProgramDescriptor::decode(program)?, input)
+    }
+
+    /// Build the circuit described by `program` for `input`
+    pub fn create_from(&self, program: &ProgramDescriptor, input: &[u8]) -> Result<Box<dyn Risc0Circuit>, ZkError> {
+        program.validate()?;
+        let constructor = self.constructors.read()
+            .get(&program.circuit_type)
+            .cloned()
+            .ok_or_else(|| ZkError::InvalidProgram(format!("Unknown circuit type 0x{:02x}", program.circuit_type)))?;
+        let circuit = constructor(program, input)?;
+
+        // A swapped or corrupted guest must not prove or verify in place of the expected one
+        if let Some(expected) = program.image_id {
+            let found = compute_image_id(circuit.elf())
+                .map_err(|e| ZkError::CircuitCompilation(format!("Failed to compute image ID: {}", e)))?;
+            if found.as_bytes() != &expected[..] {
+                return Err(ZkError::ImageMismatch {
+                    expected: hex::encode(expected),
+                    found: hex::encode(found),
+                });
+            }
+        }
+        Ok(circuit)
+    }
+
+    /// Whether a circuit type is registered
+    pub fn contains(&self, circuit_type: u8) -> bool {
+        self.constructors.read().contains_key(&circuit_type)
+    }
+
+    /// Registered circuit types, sorted
+    pub fn circuit_types(&self) -> Vec<u8> {
+        let mut types: Vec<u8> = self.constructors.read().keys().copied().collect();
+        types.sort_unstable();
+        types
+    }
+}
+
+impl Default for CircuitRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for CircuitRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CircuitRegistry")
+            .field("circuit_types", &self.circuit_types())
+            .finish()
+    }
+}
+
+/// Constructor of a built-in circuit, running its guest from the filesystem if one
+/// is loaded and the program does not embed its own
+fn builtin(
+    guests: &Arc<GuestLoader>,
+    circuit_type: u8,
+    build: impl Fn(&ProgramDescriptor, &[u8]) -> Result<Box<dyn Risc0Circuit>, ZkError> + Send + Sync + 'static,
+) -> CircuitConstructor {
+    let guests = guests.clone();
+    let name = guest_name(circuit_type).expect("built-in circuit type");
+    Arc::new(move |program: &ProgramDescriptor, input: &[u8]| {
+        let circuit = build(program, input)?;
+        if program.elf.is_some() {
+            return Ok(circuit);
+        }
+        Ok(match guests.load(name)? {
+            Some(elf) => Box::new(LoadedCircuit::new(circuit, elf)),
+            None => circuit,
+        })
+    })
+}
+
+/// Parameters: expected hash (32), then the message hash function (1) unless SHA-256,
+/// then the message nonce (16) if committed
+fn message_verify(program: &ProgramDescriptor, input: &[u8]) -> Result<Box<dyn Risc0Circuit>, ZkError> {
+    let circuit = MessageVerifyCircuit::new(&[&hash(program)?[..], input].concat())?
+        .with_hash_function(program.message_hash_function())?;
+    Ok(Box::new(match program.message_nonce() {
+        Some(nonce) => circuit.with_nonce(nonce),
+        None => circuit,
+    }))
+}
+
+/// Parameters: expected hash (32)
+fn tx_verify(program: &ProgramDescriptor, input: &[u8]) -> Result<Box<dyn Risc0Circuit>, ZkError> {
+    Ok(Box::new(TxVerifyCircuit::new(
+        input.to_vec(),
+        hash(program)?,
+        guest(program, TX_VERIFY_ELF),
+    )))
+}
+
+/// Parameters: expected hash (32), block number (8)
+fn block_verify(program: &ProgramDescriptor, input: &[u8]) -> Result<Box<dyn Risc0Circuit>, ZkError> {
+    let mut number = [0u8; 8];
+    number.copy_from_slice(&program.public_params[32..40]);
+    Ok(Box::new(BlockVerifyCircuit::new(
+        input.to_vec(),
+        hash(program)?,
+        u64::from_le_bytes(number),
+        guest(program, BLOCK_VERIFY_ELF),
+    )))
+}
+
+/// Parameters: expected block hash (32), block number (8)
+fn eth_header_verify(program: &ProgramDescriptor, input: &[u8]) -> Result<Box<dyn Risc0Circuit>, ZkError> {
+    let mut number = [0u8; 8];
+    number.copy_from_slice(&program.public_params[32..40]);
+    Ok(Box::new(EthHeaderCircuit::new(
+        input.to_vec(),
+        hash(program)?,
+        u64::from_le_bytes(number),
+        guest(program, ETH_HEADER_VERIFY_ELF),
+    )?))
+}
+
+/// Parameters: signed message hash (32). Input: bincode-encoded `EcdsaWitness`, as
+/// for the SP1 guest
+fn ecdsa_verify(program: &ProgramDescriptor, input: &[u8]) -> Result<Box<dyn Risc0Circuit>, ZkError> {
+    let witness = bincode::deserialize(input)
+        .map_err(|e| ZkError::InvalidInput(format!("Malformed ECDSA witness: {}", e)))?;
+    Ok(Box::new(EcdsaVerifyCircuit::new(witness, hash(program)?, guest(program, ECDSA_VERIFY_ELF))?))
+}
+
+/// Leading 32-byte hash of the program's parameters
+fn hash(program: &ProgramDescriptor) -> Result<[u8; 32], ZkError> {
+    program.public_params.get(..32)
+        .and_then(|hash| hash.try_into().ok())
+        .ok_or_else(|| ZkError::InvalidProgram(format!("Program too short for circuit 0x{:02x}", program.circuit_type)))
+}
+
+/// ELF embedded in the program, or the built-in guest if there is none
+fn guest(program: &ProgramDescriptor, builtin: &[u8]) -> Vec<u8> {
+    program.elf.clone().unwrap_or_else(|| builtin.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_and_custom_circuits() {
+        let registry = CircuitRegistry::new();
+        assert_eq!(registry.circuit_types(), vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x09, 0x0E]);
+
+        let mut program = vec![TX_VERIFY];
+        program.extend_from_slice(&[0xAA; 32]);
+        assert!(registry.create(&program, b"{}").is_ok());
+        assert!(registry.create(&program[..10], b"{}").is_err());
+        assert!(registry.create(&[], b"").is_err());
+        assert!(registry.create(&[0x7F], b"").is_err());
+
+        // A custom type reusing the message verification guest
+        registry.register(0x7F, Arc::new(message_verify));
+        let custom = ProgramDescriptor::new(0x7F, [0xBB; 32]);
+        assert!(registry.create(&custom.encode(), b"message").is_ok());
+        // The guest must have the image ID the program pins
+        let pinned = custom.with_image_id([0xCC; 32]);
+        assert!(matches!(registry.create(&pinned.encode(), b"message"), Err(ZkError::ImageMismatch { .. })));
+        assert!(registry.unregister(0x7F));
+        assert!(!registry.contains(0x7F));
+    }
+
+    #[test]
+    fn test_message_hash_function() {
+        use crate::hasher::HashFunction;
+
+        let registry = CircuitRegistry::new();
+        let hash = HashFunction::Keccak256.hash(b"message");
+        let program = ProgramDescriptor::message_verify(hash, HashFunction::Keccak256).unwrap().encode();
+        let circuit = registry.create(&program, b"message").unwrap();
+        // The guest reads the hash function after the expected hash
+        assert_eq!(circuit.public_inputs().len(), 9);
+        assert_eq!(circuit.public_inputs()[8], 1);
+
+        let program = ProgramDescriptor::message_verify(hash, HashFunction::Sha256).unwrap().encode();
+        assert_eq!(registry.create(&program, b"message").unwrap().public_inputs()[8], 0);
+    }
+
+    #[test]
+    fn test_eth_header_circuit() {
+        use crate::chains::eth::keccak256;
+        use crate::codec::rlp::{self, RlpItem};
+
+        let mut fields = vec![RlpItem::Bytes(vec![0; 32]); 15];
+        fields[8] = RlpItem::uint(19_000_000);
+        fields[9] = RlpItem::uint(30_000_000);
+        fields[10] = RlpItem::uint(12_000_000);
+        fields[11] = RlpItem::uint(1_700_000_000);
+        let header = rlp::encode(&RlpItem::List(fields));
+
+        let registry = CircuitRegistry::new();
+        let mut params = keccak256(&header).to_vec();
+        params.extend_from_slice(&19_000_000u64.to_le_bytes());
+        let program = ProgramDescriptor::new(ETH_HEADER_VERIFY, params.clone()).encode();
+        assert!(registry.create(&program, &header).is_ok());
+
+        // The header must hash to the expected block hash and carry its number
+        assert!(registry.create(&program, &header[..header.len() - 1]).is_err());
+        params[32..].copy_from_slice(&19_000_001u64.to_le_bytes());
+        let program = ProgramDescriptor::new(ETH_HEADER_VERIFY, params).encode();
+        assert!(matches!(registry.create(&program, &header), Err(ZkError::InvalidInput(_))));
+    }
+    #[test]
+    fn test_ecdsa_circuit() {
+        use crate::signatures::ecdsa::EcdsaWitness;
+        use k256::ecdsa::SigningKey;
+
+        let key = SigningKey::from_slice(&[0x22; 32]).unwrap();
+        let (signature, _) = key.sign_prehash_recoverable(&[0x33; 32]).unwrap();
+        let mut witness = EcdsaWitness {
+            message_hash: [0x33; 32],
+            public_key: key.verifying_key().to_encoded_point(true).as_bytes().to_vec(),
+            signature: signature.to_vec(),
+        };
+
+        let registry = CircuitRegistry::new();
+        let program = ProgramDescriptor::new(ECDSA_VERIFY, vec![0x33; 32]).encode();
+        assert!(registry.create(&program, &bincode::serialize(&witness).unwrap()).is_ok());
+        assert!(registry.create(&program, b"not a witness").is_err());
+
+        // The signature must be over the expected hash
+        witness.message_hash = [0x44; 32];
+        assert!(matches!(registry.create(&program, &bincode::serialize(&witness).unwrap()), Err(ZkError::InvalidInput(_))));
+    }
+}
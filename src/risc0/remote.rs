@@ -0,0 +1,130 @@
+//! Remote proving through Bonsai
+//!
+//! When [`super::Risc0Config::remote`] is set, proofs are generated by a Bonsai (or
+//! API-compatible) service instead of on the local machine. Failed requests are
+//! retried with a linear backoff; once the retries are exhausted the backend falls
+//! back to local proving unless that is disabled.
+
+use std::time::Duration;
+use bonsai_sdk::alpha::{Client, SdkErr};
+use risc0_zkvm::{compute_image_id, Receipt};
+
+use crate::error::ZkError;
+
+/// Remote proving service configuration
+#[derive(Clone)]
+pub struct RemoteProving {
+    /// Base URL of the proving service
+    pub endpoint: String,
+    /// API key sent with every request
+    pub api_key: String,
+    /// Retries after a failed request
+    pub max_retries: u32,
+    /// Delay before the first retry; later retries wait proportionally longer
+    pub retry_delay: Duration,
+    /// Interval between session status polls
+    pub poll_interval: Duration,
+    /// Whether to prove locally once all retries failed
+    pub fallback_to_local: bool,
+}
+
+impl RemoteProving {
+    /// Prove through the service at `endpoint`
+    pub fn new(endpoint: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            api_key: api_key.into(),
+            max_retries: 3,
+            retry_delay: Duration::from_secs(2),
+            poll_interval: Duration::from_secs(1),
+            fallback_to_local: true,
+        }
+    }
+
+    /// Set the number of retries after a failed request
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Enable or disable falling back to local proving
+    pub fn with_fallback(mut self, fallback_to_local: bool) -> Self {
+        self.fallback_to_local = fallback_to_local;
+        self
+    }
+}
+
+impl std::fmt::Debug for RemoteProving {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteProving")
+            .field("endpoint", &self.endpoint)
+            .field("api_key", &"<redacted>")
+            .field("max_retries", &self.max_retries)
+            .field("retry_delay", &self.retry_delay)
+            .field("poll_interval", &self.poll_interval)
+            .field("fallback_to_local", &self.fallback_to_local)
+            .finish()
+    }
+}
+
+/// Prove `elf` on `stdin` remotely, retrying failed requests
+pub(crate) async fn prove(remote: &RemoteProving, elf: &[u8], stdin: &[u8]) -> Result<Receipt, ZkError> {
+    let mut attempt = 0;
+    loop {
+        let (config, elf_bytes, input) = (remote.clone(), elf.to_vec(), stdin.to_vec());
+        let result = tokio::task::spawn_blocking(move || prove_blocking(&config, &elf_bytes, &input))
+            .await
+            .map_err(|e| ZkError::Backend(format!("Remote proving task failed: {}", e)))?;
+
+        match result {
+            Ok(receipt) => return Ok(receipt),
+            Err(e) if attempt < remote.max_retries => {
+                attempt += 1;
+                tracing::warn!(endpoint = %remote.endpoint, attempt, "remote proving failed, retrying: {}", e);
+                tokio::time::sleep(remote.retry_delay * attempt).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn prove_blocking(remote: &RemoteProving, elf: &[u8], stdin: &[u8]) -> Result<Receipt, ZkError> {
+    let client = Client::from_parts(remote.endpoint.clone(), remote.api_key.clone(), risc0_zkvm::VERSION)
+        .map_err(remote_error)?;
+
+    // Upload the program and its input
+    let image_id = compute_image_id(elf)
+        .map_err(|e| ZkError::ProofGeneration(format!("Failed to compute image ID: {}", e)))?;
+    let image_id = hex::encode(image_id);
+    client.upload_img(&image_id, elf.to_vec()).map_err(remote_error)?;
+    let input_id = client.upload_input(stdin.to_vec()).map_err(remote_error)?;
+
+    // Wait for the session to finish
+    let session = client.create_session(image_id, input_id).map_err(remote_error)?;
+    loop {
+        let status = session.status(&client).map_err(remote_error)?;
+        match status.status.as_str() {
+            "RUNNING" => std::thread::sleep(remote.poll_interval),
+            "SUCCEEDED" => {
+                let url = status.receipt_url.ok_or_else(|| {
+                    ZkError::ProofGeneration("Remote session finished without a receipt".to_string())
+                })?;
+                let bytes = client.download(&url).map_err(remote_error)?;
+                return bincode::deserialize(&bytes)
+                    .map_err(|e| ZkError::ProofGeneration(format!("Failed to parse remote receipt: {}", e)));
+            }
+            other => {
+                return Err(ZkError::ProofGeneration(format!(
+                    "Remote session {} {}: {}",
+                    session.uuid,
+                    other.to_lowercase(),
+                    status.error_msg.unwrap_or_default()
+                )))
+            }
+        }
+    }
+}
+
+fn remote_error(e: SdkErr) -> ZkError {
+    ZkError::ProofGeneration(format!("Remote proving request failed: {}", e))
+}
@@ -95,6 +95,13 @@ async fn test_batch_operations() {
     let verify_results = backend.batch_verify(&verify_refs, None).await.unwrap();
     assert_eq!(verify_results.len(), messages.len());
     assert!(verify_results.iter().all(|&r| r));
+
+    // A malformed proof fails only its own item
+    let mut verify_refs = verify_refs;
+    verify_refs.push((programs[0].0.as_slice(), &[1u8, 2, 3][..]));
+    let verify_results = backend.batch_verify_detailed(&verify_refs).await;
+    assert!(verify_results[..messages.len()].iter().all(|result| matches!(result, Ok(true))));
+    assert!(verify_results[messages.len()].is_err());
 }
 
 #[tokio::test]
@@ -192,6 +199,12 @@ async fn test_stats_tracking() {
     assert_eq!(stats.total_failures, 0);
     assert!(stats.avg_proving_time > std::time::Duration::from_nanos(0));
     assert!(stats.avg_verification_time > std::time::Duration::from_nanos(0));
+
+    // Latency percentiles cover the same operations
+    let latency = backend.latency_stats();
+    assert_eq!(latency.proving().count(), 1);
+    assert_eq!(latency.verification().count(), 1);
+    assert!(latency.proving().p99().is_some());
 }
 
 #[tokio::test]
@@ -213,9 +226,9 @@ async fn test_block_verification() {
     
     let header_bytes = serde_json::to_vec(&block_header).unwrap();
     
-    // Compute expected hash
+    // Compute expected hash over the canonical encoding
     let mut hasher = Sha256::new();
-    hasher.update(&header_bytes);
+    hasher.update(&crate::codec::json::canonicalize(&header_bytes).unwrap());
     let expected_hash = hasher.finalize();
     
     // Create program bytes (0x03 for block verification)
@@ -281,7 +294,7 @@ async fn test_invalid_block() {
         
         // Compute hash
         let mut hasher = Sha256::new();
-        hasher.update(&header_bytes);
+        hasher.update(&crate::codec::json::canonicalize(&header_bytes).unwrap());
         let expected_hash = hasher.finalize();
         
         // Create program
@@ -317,7 +330,7 @@ async fn test_block_number_mismatch() {
     
     // Compute hash
     let mut hasher = Sha256::new();
-    hasher.update(&header_bytes);
+    hasher.update(&crate::codec::json::canonicalize(&header_bytes).unwrap());
     let expected_hash = hasher.finalize();
     
     // Create program with mismatched block number
@@ -340,6 +353,9 @@ async fn test_circuit_caching() {
             max_proofs: 10,
             max_age: Duration::from_secs(60),
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: crate::hasher::HashFunction::Sha256,
         },
     );
     
@@ -381,6 +397,9 @@ async fn test_proof_caching() {
             max_proofs: 10,
             max_age: Duration::from_secs(60),
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: crate::hasher::HashFunction::Sha256,
         },
     );
     
@@ -422,6 +441,9 @@ async fn test_cache_expiration() {
             max_proofs: 10,
             max_age: Duration::from_millis(100), // Very short expiration
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: crate::hasher::HashFunction::Sha256,
         },
     );
     
@@ -466,6 +488,9 @@ async fn test_cache_limits() {
             max_proofs: 2,
             max_age: Duration::from_secs(60),
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: crate::hasher::HashFunction::Sha256,
         },
     );
     
@@ -504,6 +529,9 @@ async fn test_cache_clear() {
             max_proofs: 10,
             max_age: Duration::from_secs(60),
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: crate::hasher::HashFunction::Sha256,
         },
     );
     
@@ -539,4 +567,163 @@ async fn test_cache_clear() {
     assert_eq!(stats.proof_entries, 1); // New entry after clear
     assert_eq!(stats.circuit_hits, 0); // No hits after clear
     assert_eq!(stats.proof_hits, 0); // No hits after clear
-} 
\ No newline at end of file
+} 
+#[tokio::test]
+async fn test_compose_rejects_invalid_receipts() {
+    let backend = Risc0Backend::new(Risc0Config::default());
+    let options = crate::options::ProveOptions::default();
+
+    // Nothing to compose
+    assert!(backend.compose_messages(&[], &options).await.is_err());
+
+    // Not a receipt
+    assert!(backend.compose_messages(&[vec![0xAB; 16]], &options).await.is_err());
+
+    // Composition program with a root the input does not match
+    let mut program = vec![crate::profiles::MESSAGE_COMPOSE];
+    program.extend_from_slice(&[0u8; 32]);
+    assert!(backend.prove(&program, &crate::cluster::encode_shard(&[]), None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_convert_rejects_invalid_targets() {
+    let backend = Risc0Backend::new(Risc0Config::default());
+    let mut program = vec![crate::profiles::MESSAGE_VERIFY];
+    program.extend_from_slice(&[0u8; 32]);
+
+    // Not a receipt, and a mode RISC0 has no receipts in
+    assert!(backend.convert(&program, &[0xAB; 16], crate::metadata::ProofMode::Compressed).await.is_err());
+    assert!(backend.convert(&program, &[0xAB; 16], crate::metadata::ProofMode::Plonk).await.is_err());
+    for kind in [ReceiptKind::Composite, ReceiptKind::Succinct, ReceiptKind::Groth16] {
+        assert_eq!(ReceiptKind::from_proof_mode(kind.proof_mode()), Some(kind));
+    }
+}
+
+#[tokio::test]
+async fn test_register_program() {
+    let backend = Risc0Backend::new(Risc0Config::default());
+    assert!(backend.register_program("custom", vec![0xAB; 64], |_: &[u8]| true).is_err());
+
+    // The built-in guest registered under a name proves through the standard API
+    let program = backend.register_program("echo", super::circuit::MESSAGE_VERIFY_ELF.to_vec(), |journal: &[u8]| !journal.is_empty())
+        .unwrap();
+    assert_eq!(backend.programs().names(), vec!["echo".to_string()]);
+    assert!(backend.circuits().create(&program, b"input").is_ok());
+
+    let unknown = crate::descriptor::ProgramDescriptor::new(crate::profiles::USER_PROGRAM, "missing").encode();
+    assert!(backend.prove(&unknown, b"input", None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_load_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("custom.elf"), super::circuit::MESSAGE_VERIFY_ELF).unwrap();
+    let manifest = crate::manifest::ProgramManifest::parse(
+        "[[program]]\nname = \"custom\"\ncircuit_type = 0x42\nelf = \"custom.elf\"\nbackends = [\"risc0\"]\n",
+        dir.path(),
+    ).unwrap();
+
+    let backend = Risc0Backend::new(Risc0Config::default());
+    assert_eq!(backend.load_manifest(&manifest).unwrap(), 1);
+    assert!(backend.circuits().contains(0x42));
+
+    // A manifest pinning another image ID is refused
+    let mut pinned = manifest.clone();
+    pinned.programs[0].image_id = Some([0u8; 32]);
+    assert!(matches!(
+        backend.load_manifest(&pinned),
+        Err(crate::error::ZkError::ImageMismatch { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_estimate_uses_stats_of_descriptor_programs() {
+    let backend = Risc0Backend::new(Risc0Config::default());
+    let message = b"estimate me";
+    let program = crate::descriptor::ProgramDescriptor::message_verify_for(message, crate::hasher::HashFunction::Sha256)
+        .unwrap()
+        .encode();
+
+    let (_, metadata) = backend.prove_with_options(&program, message, &crate::options::ProveOptions::default()).await.unwrap();
+    let stats = backend.program_stats().snapshot();
+    let observed = stats.get(&metadata.base.program_hash).unwrap();
+    assert_eq!(observed.proofs, 1);
+
+    // The projection comes from the recorded proof, not from the segment count
+    let report = backend.estimate(&program, message).await.unwrap();
+    assert_eq!(report.projected_proving_time, Some(observed.avg_proving_time()));
+}
+
+#[tokio::test]
+async fn test_health_probe() {
+    let backend = Risc0Backend::new(Risc0Config::default());
+    assert!(backend.run_health_probe().await.is_none());
+
+    // The built-in probe program executes
+    let backend = Risc0Backend::new(Risc0Config::default())
+        .with_health_probe(crate::probe::ProbePolicy::default());
+    assert_eq!(backend.run_health_probe().await.unwrap().error, None);
+    assert_eq!(backend.probe.as_ref().unwrap().consecutive_failures(), 0);
+
+    // A probe that cannot run makes the backend unhealthy, with the reason
+    let broken = crate::descriptor::ProgramDescriptor::new(0xEE, Vec::new()).encode();
+    let policy = crate::probe::ProbePolicy { unhealthy_after: 1, ..Default::default() }
+        .with_program(broken, Vec::new());
+    let backend = Risc0Backend::new(Risc0Config::default()).with_health_probe(policy);
+    assert!(backend.run_health_probe().await.unwrap().error.is_some());
+    assert!(matches!(backend.health_check().await, HealthStatus::Unhealthy(reason) if reason.contains("probe")));
+}
+
+#[tokio::test]
+async fn test_replay_guard() {
+    use crate::domain::{ChainId, DomainSeparator, MessageNonce};
+
+    let guard = std::sync::Arc::new(crate::replay::ReplayGuard::new());
+    let backend = Risc0Backend::new(Risc0Config::default()).with_replay_guard(guard.clone());
+    let message = b"deliver once";
+    let program = crate::descriptor::ProgramDescriptor::message_verify_for(message, crate::hasher::HashFunction::Sha256)
+        .unwrap()
+        .with_nonce(MessageNonce::new(ChainId(1), 5))
+        .unwrap()
+        .encode();
+    let options = crate::options::ProveOptions::default().with_domain(DomainSeparator::new(ChainId(1), ChainId(10)));
+    let (proof, _) = backend.prove_with_options(&program, message, &options).await.unwrap();
+
+    // The first verification accepts the message, any later one is a replay
+    assert!(ZkBackend::verify(&backend, &program, &proof, None).await.unwrap());
+    assert!(guard.is_accepted(ChainId(1), 5));
+    assert!(ZkBackend::verify(&backend, &program, &proof, None).await.is_err());
+
+    // Batches accept a message once, whether it was verified before or twice in the batch
+    let results = backend.batch_verify_detailed(&[(&program, &proof)]).await;
+    assert!(results[0].is_err());
+    let backend = Risc0Backend::new(Risc0Config::default()).with_replay_guard(std::sync::Arc::new(crate::replay::ReplayGuard::new()));
+    let results = backend.batch_verify_detailed(&[(&program, &proof), (&program, &proof)]).await;
+    assert_eq!(results.iter().filter(|result| matches!(result, Ok(true))).count(), 1);
+    assert_eq!(results.iter().filter(|result| result.is_err()).count(), 1);
+}
+
+#[tokio::test]
+async fn test_requested_proof_mode() {
+    let backend = Risc0Backend::new(Risc0Config::default());
+    let message = b"succinct please";
+    let program = crate::descriptor::ProgramDescriptor::message_verify_for(message, crate::hasher::HashFunction::Sha256)
+        .unwrap()
+        .encode();
+
+    // The requested mode selects the receipt kind, so the proof is not escalated
+    let options = crate::options::ProveOptions::default().with_proof_mode(crate::metadata::ProofMode::Compressed);
+    let (proof, metadata) = backend.prove_with_options(&program, message, &options).await.unwrap();
+    assert_eq!(metadata.proof_mode, crate::metadata::ProofMode::Compressed);
+    assert_eq!(metadata.requested_mode, crate::metadata::ProofMode::Compressed);
+    assert!(!metadata.escalated());
+    let receipt: risc0_zkvm::Receipt = bincode::deserialize(&proof).unwrap();
+    assert!(matches!(receipt.inner, risc0_zkvm::InnerReceipt::Succinct(_)));
+
+    // RISC0 has no PLONK receipts
+    let options = crate::options::ProveOptions::default().with_proof_mode(crate::metadata::ProofMode::Plonk);
+    assert!(matches!(
+        backend.prove_with_options(&program, message, &options).await,
+        Err(crate::error::ZkError::InvalidInput(_))
+    ));
+}
@@ -3,6 +3,7 @@
 
 //! Type definitions for RISC0 backend
 
+use std::time::Duration;
 use serde::{Serialize, Deserialize};
 use risc0_zkvm::{
     Prover, ProverOpts,
@@ -10,6 +11,8 @@ use risc0_zkvm::{
     ExecutorEnv,
 };
 
+use crate::metadata::ProofMode;
+
 /// RISC0 circuit trait
 pub trait Risc0Circuit: Send + Sync {
     /// Get the ELF binary for this circuit
@@ -23,6 +26,15 @@ pub trait Risc0Circuit: Send + Sync {
     
     /// Verify circuit-specific conditions in the receipt
     fn verify_receipt(&self, receipt: &Receipt) -> bool;
+
+    /// Receipts of other proofs the guest verifies with `env::verify`
+    ///
+    /// They are added to the executor environment as assumptions. A composite receipt
+    /// of such a circuit carries them unresolved; succinct and Groth16 receipts
+    /// resolve them into a single unconditional proof.
+    fn assumptions(&self) -> Vec<Receipt> {
+        Vec::new()
+    }
 }
 
 /// RISC0-specific configuration options
@@ -34,6 +46,15 @@ pub struct Risc0Options {
     pub memory_limit: Option<usize>,
     /// Custom proving parameters
     pub custom_params: Option<Vec<u8>>,
+    /// Kind of receipt to produce
+    #[serde(default)]
+    pub receipt_kind: ReceiptKind,
+    /// Default maximum time a proof may take
+    #[serde(default)]
+    pub prove_timeout: Option<Duration>,
+    /// Time proving and verification latencies are retained for, an hour if unset
+    #[serde(default)]
+    pub latency_window: Option<Duration>,
 }
 
 impl Default for Risc0Options {
@@ -42,6 +63,60 @@ impl Default for Risc0Options {
             num_threads: Some(4),
             memory_limit: Some(1024 * 1024 * 1024), // 1GB
             custom_params: None,
+            receipt_kind: ReceiptKind::default(),
+            prove_timeout: None,
+            latency_window: None,
+        }
+    }
+}
+
+/// Kind of receipt the prover produces
+///
+/// Each step trades more proving time for a smaller receipt: composite receipts
+/// hold one proof per segment, succinct receipts recurse them into a single STARK
+/// and Groth16 receipts wrap that STARK in a BN254 SNARK.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReceiptKind {
+    /// One receipt per segment
+    Composite,
+    /// Single recursive STARK receipt
+    Succinct,
+    /// Groth16 receipt over BN254
+    Groth16,
+}
+
+impl ReceiptKind {
+    /// Prover options producing this kind of receipt
+    pub fn prover_opts(self) -> ProverOpts {
+        match self {
+            ReceiptKind::Composite => ProverOpts::composite(),
+            ReceiptKind::Succinct => ProverOpts::succinct(),
+            ReceiptKind::Groth16 => ProverOpts::groth16(),
         }
     }
+
+    /// Proof mode reported in proof metadata
+    pub fn proof_mode(self) -> ProofMode {
+        match self {
+            ReceiptKind::Composite => ProofMode::Core,
+            ReceiptKind::Succinct => ProofMode::Compressed,
+            ReceiptKind::Groth16 => ProofMode::Groth16,
+        }
+    }
+
+    /// Receipt kind reported as `mode`, if RISC0 has one
+    pub fn from_proof_mode(mode: ProofMode) -> Option<Self> {
+        match mode {
+            ProofMode::Core => Some(ReceiptKind::Composite),
+            ProofMode::Compressed => Some(ReceiptKind::Succinct),
+            ProofMode::Groth16 => Some(ReceiptKind::Groth16),
+            ProofMode::Plonk => None,
+        }
+    }
+}
+
+impl Default for ReceiptKind {
+    fn default() -> Self {
+        ReceiptKind::Composite
+    }
 } 
\ No newline at end of file
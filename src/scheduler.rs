@@ -0,0 +1,325 @@
+//! Proving job scheduler
+//!
+//! The [`Scheduler`] queues proving jobs and hands them to a backend one at a time
+//! according to a [`SchedulingPolicy`]. Jobs may carry a deadline; with the
+//! earliest-deadline-first policy the most urgent job runs next, and jobs that the
+//! prediction model says cannot finish in time are rejected before any prover time
+//! is spent on them.
+//!
+//! With the priority policy, urgent jobs such as finality proofs run ahead of
+//! regular traffic and background re-proofs. Jobs of the same priority are shared
+//! fairly between tenants: the tenant that has had the fewest jobs run goes next.
+//! A job's priority defaults to the [`PriorityClass`] of its circuit profile.
+//!
+//! The queue can be bounded with [`Scheduler::with_capacity`]; submissions beyond
+//! it fail with [`ZkError::QueueFull`] so callers can shed load.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use parking_lot::Mutex;
+use uuid::Uuid;
+use frostgate_zkip::{ProofMetadata, ResourceUsage, ZkBackend};
+
+use crate::error::ZkError;
+use crate::hasher::HashFunction;
+use crate::profiles::{PriorityClass, ProfileRegistry};
+use crate::stats::ProgramStatsTracker;
+
+/// Order in which queued jobs are run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedulingPolicy {
+    /// Run jobs in submission order
+    Fifo,
+    /// Run the job with the earliest deadline first; jobs without a deadline run last
+    EarliestDeadlineFirst,
+    /// Run the highest-priority job first, sharing each priority fairly between
+    /// tenants and breaking ties by deadline
+    Priority,
+}
+
+impl Default for SchedulingPolicy {
+    fn default() -> Self {
+        SchedulingPolicy::Fifo
+    }
+}
+
+/// Queued proving job
+#[derive(Debug, Clone)]
+pub struct ProofJob {
+    /// Job identifier
+    pub id: Uuid,
+    /// Program to prove
+    pub program: Vec<u8>,
+    /// Program input
+    pub input: Vec<u8>,
+    /// Time the proof must be ready by
+    pub deadline: Option<SystemTime>,
+    /// Time the job was submitted
+    pub submitted: SystemTime,
+    /// Priority, defaulting to the circuit profile's
+    pub priority: Option<PriorityClass>,
+    /// Tenant the job is run for
+    pub tenant: Option<String>,
+}
+
+impl ProofJob {
+    /// Create a job without a deadline
+    pub fn new(program: Vec<u8>, input: Vec<u8>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            program,
+            input,
+            deadline: None,
+            submitted: SystemTime::now(),
+            priority: None,
+            tenant: None,
+        }
+    }
+
+    /// Set the time the proof must be ready by
+    pub fn with_deadline(mut self, deadline: SystemTime) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set the priority
+    pub fn with_priority(mut self, priority: PriorityClass) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Set the tenant the job is run for
+    pub fn with_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+}
+
+/// Deadline-aware proving job scheduler
+#[derive(Debug)]
+pub struct Scheduler {
+    policy: SchedulingPolicy,
+    queue: Mutex<VecDeque<ProofJob>>,
+    predictor: Arc<ProgramStatsTracker>,
+    profiles: Option<Arc<ProfileRegistry>>,
+    capacity: Option<usize>,
+    /// Jobs taken from the queue per tenant
+    served: Mutex<HashMap<String, u64>>,
+}
+
+impl Scheduler {
+    /// Create a scheduler predicting proving times from a backend's program statistics
+    pub fn new(policy: SchedulingPolicy, predictor: Arc<ProgramStatsTracker>) -> Self {
+        Self {
+            policy,
+            queue: Mutex::new(VecDeque::new()),
+            predictor,
+            profiles: None,
+            capacity: None,
+            served: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take the default priority of jobs from their circuit profiles
+    pub fn with_profiles(mut self, profiles: Arc<ProfileRegistry>) -> Self {
+        self.profiles = Some(profiles);
+        self
+    }
+
+    /// Hold at most `capacity` queued jobs
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Active scheduling policy
+    pub fn policy(&self) -> SchedulingPolicy {
+        self.policy
+    }
+
+    /// Number of queued jobs
+    pub fn len(&self) -> usize {
+        self.queue.lock().len()
+    }
+
+    /// Whether no jobs are queued
+    pub fn is_empty(&self) -> bool {
+        self.queue.lock().is_empty()
+    }
+
+    /// Predicted proving time of a program, if it has been proven before
+    ///
+    /// Statistics are looked up by program hash, under the hash functions of the
+    /// profiles set with [`Scheduler::with_profiles`]; pass the backend's profiles
+    /// when they select hash functions other than SHA-256.
+    pub fn predict(&self, program: &[u8]) -> Option<Duration> {
+        let program_hash = match &self.profiles {
+            Some(profiles) => profiles.program_hash(program),
+            None => hex::encode(HashFunction::default().hash(program)),
+        };
+        self.predictor.snapshot()
+            .get(&program_hash)
+            .filter(|stats| stats.proofs > 0)
+            .map(|stats| stats.avg_proving_time())
+    }
+
+    /// Resource usage of `backend` including the jobs waiting in this queue
+    pub fn resource_usage<B: ZkBackend + ?Sized>(&self, backend: &B) -> ResourceUsage {
+        let mut usage = backend.resource_usage();
+        usage.queue_depth += self.len();
+        usage
+    }
+
+    /// Priority a job runs with
+    pub fn priority(&self, job: &ProofJob) -> PriorityClass {
+        job.priority
+            .or_else(|| {
+                let profile = self.profiles.as_ref()?.for_program(&job.program)?;
+                Some(profile.priority)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Queue a job, rejecting it if it provably cannot meet its deadline or the queue is full
+    pub fn submit(&self, job: ProofJob) -> Result<Uuid, ZkError> {
+        self.check_deadline(&job)?;
+        let id = job.id;
+        let mut queue = self.queue.lock();
+        if let Some(capacity) = self.capacity {
+            if queue.len() >= capacity {
+                return Err(ZkError::QueueFull { capacity });
+            }
+        }
+        queue.push_back(job);
+        Ok(id)
+    }
+
+    /// Remove a queued job
+    pub fn cancel(&self, id: Uuid) -> Option<ProofJob> {
+        let mut queue = self.queue.lock();
+        let index = queue.iter().position(|job| job.id == id)?;
+        queue.remove(index)
+    }
+
+    /// Take the next job to run according to the policy
+    pub fn next_job(&self) -> Option<ProofJob> {
+        let mut queue = self.queue.lock();
+        let mut served = self.served.lock();
+        let index = match self.policy {
+            SchedulingPolicy::Fifo => 0,
+            SchedulingPolicy::EarliestDeadlineFirst => queue.iter()
+                .enumerate()
+                .min_by_key(|(index, job)| (job.deadline.is_none(), job.deadline, *index))
+                .map(|(index, _)| index)?,
+            SchedulingPolicy::Priority => queue.iter()
+                .enumerate()
+                .min_by_key(|(index, job)| {
+                    let tenant_served = job.tenant.as_ref()
+                        .and_then(|tenant| served.get(tenant))
+                        .copied()
+                        .unwrap_or_default();
+                    (
+                        std::cmp::Reverse(self.priority(job)),
+                        tenant_served,
+                        job.deadline.is_none(),
+                        job.deadline,
+                        *index,
+                    )
+                })
+                .map(|(index, _)| index)?,
+        };
+        let job = queue.remove(index)?;
+        if let Some(tenant) = &job.tenant {
+            *served.entry(tenant.clone()).or_default() += 1;
+        }
+        Some(job)
+    }
+
+    /// Run the next job on a backend
+    ///
+    /// The deadline is checked again before proving, since the job may have waited
+    /// in the queue long enough to become unreachable.
+    pub async fn run_next<B: ZkBackend>(&self, backend: &B) -> Option<(Uuid, Result<(Vec<u8>, ProofMetadata), ZkError>)> {
+        let job = self.next_job()?;
+        if let Err(e) = self.check_deadline(&job) {
+            return Some((job.id, Err(e)));
+        }
+        let result = backend.prove(&job.program, &job.input, None)
+            .await
+            .map_err(ZkError::from);
+        Some((job.id, result))
+    }
+
+    fn check_deadline(&self, job: &ProofJob) -> Result<(), ZkError> {
+        let deadline = match job.deadline {
+            Some(deadline) => deadline,
+            None => return Ok(()),
+        };
+        let predicted = self.predict(&job.program).unwrap_or_default();
+        if SystemTime::now() + predicted > deadline {
+            return Err(ZkError::DeadlineUnreachable { deadline, predicted });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_earliest_deadline_first() {
+        let scheduler = Scheduler::new(SchedulingPolicy::EarliestDeadlineFirst, Arc::new(ProgramStatsTracker::new()));
+        let now = SystemTime::now();
+
+        let background = scheduler.submit(ProofJob::new(vec![1], vec![])).unwrap();
+        let late = scheduler.submit(ProofJob::new(vec![2], vec![]).with_deadline(now + Duration::from_secs(600))).unwrap();
+        let urgent = scheduler.submit(ProofJob::new(vec![3], vec![]).with_deadline(now + Duration::from_secs(60))).unwrap();
+
+        assert_eq!(scheduler.next_job().unwrap().id, urgent);
+        assert_eq!(scheduler.next_job().unwrap().id, late);
+        assert_eq!(scheduler.next_job().unwrap().id, background);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn test_unreachable_deadline_rejected() {
+        let predictor = Arc::new(ProgramStatsTracker::new());
+        predictor.record(&ProfileRegistry::new().program_hash(&[1]), Duration::from_secs(300), true);
+        let scheduler = Scheduler::new(SchedulingPolicy::EarliestDeadlineFirst, predictor);
+
+        let job = ProofJob::new(vec![1], vec![]).with_deadline(SystemTime::now() + Duration::from_secs(60));
+        assert!(matches!(scheduler.submit(job), Err(ZkError::DeadlineUnreachable { .. })));
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn test_priority_and_tenant_fair_share() {
+        let profiles = Arc::new(ProfileRegistry::new());
+        profiles.register(0x03, crate::profiles::CircuitProfile {
+            priority: PriorityClass::Urgent,
+            ..Default::default()
+        });
+        let scheduler = Scheduler::new(SchedulingPolicy::Priority, Arc::new(ProgramStatsTracker::new()))
+            .with_profiles(profiles)
+            .with_capacity(5);
+
+        let background = scheduler.submit(ProofJob::new(vec![1], vec![]).with_priority(PriorityClass::Background)).unwrap();
+        let a1 = scheduler.submit(ProofJob::new(vec![1], vec![]).with_tenant("a")).unwrap();
+        let a2 = scheduler.submit(ProofJob::new(vec![1], vec![]).with_tenant("a")).unwrap();
+        let b1 = scheduler.submit(ProofJob::new(vec![1], vec![]).with_tenant("b")).unwrap();
+        let finality = scheduler.submit(ProofJob::new(vec![0x03], vec![]).with_tenant("a")).unwrap();
+        assert!(matches!(
+            scheduler.submit(ProofJob::new(vec![1], vec![])),
+            Err(ZkError::QueueFull { capacity: 5 })
+        ));
+
+        // Urgent by profile first, then tenant b before a's second job
+        assert_eq!(scheduler.next_job().unwrap().id, finality);
+        assert_eq!(scheduler.next_job().unwrap().id, b1);
+        assert_eq!(scheduler.next_job().unwrap().id, a1);
+        assert_eq!(scheduler.next_job().unwrap().id, a2);
+        assert_eq!(scheduler.next_job().unwrap().id, background);
+    }
+}
@@ -0,0 +1,403 @@
+//! gRPC proving service
+//!
+//! [`ProverService`] exposes a backend over the `frostgate.prover.v1.Prover` service
+//! defined in `proto/prover.proto`, turning the crate into a proving sidecar for
+//! relayers written in any language. The `frostgate-prover-server` binary serves it.
+//!
+//! Any [`ZkBackend`] can be served. The SP1 and RISC0 backends additionally honour
+//! per-request options, stream progress and execute programs without proving;
+//! other backends stream only the finished proof and reject execution.
+//!
+//! With [`ProverService::with_signing_key`] every request must carry a valid
+//! signature made with the same key, see [`crate::grpc`].
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use futures::future::join_all;
+use futures::Stream;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::{Request, Response, Status};
+use frostgate_zkip::{ProofMetadata, ZkBackend};
+
+use crate::compression;
+use crate::domain::DomainSeparator;
+use crate::error::ZkError;
+use crate::execution::ExecutionReport;
+use crate::grpc::{check_signature, domain, mode_name, parse_mode, pb};
+use crate::metadata::ExtendedProofMetadata;
+use crate::options::ProveOptions;
+use crate::progress::ProveProgress;
+use crate::risc0::Risc0Backend;
+use crate::sp1::Sp1Backend;
+
+use pb::prover_server::{Prover, ProverServer};
+
+/// Backend served over gRPC
+#[derive(Clone)]
+pub enum ServedBackend {
+    /// SP1 backend
+    Sp1(Arc<Sp1Backend>),
+    /// RISC0 backend
+    Risc0(Arc<Risc0Backend>),
+    /// Any other backend, served through the `ZkBackend` interface only
+    Other(Arc<dyn ZkBackend>),
+}
+
+impl ServedBackend {
+    async fn prove(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        options: &ProveOptions,
+        compress: bool,
+        progress: Option<mpsc::UnboundedSender<ProveProgress>>,
+    ) -> Result<pb::ProveResponse, ZkError> {
+        let (proof, metadata) = match (self, progress) {
+            (ServedBackend::Sp1(backend), Some(progress)) => {
+                let (proof, metadata) = backend.prove_with_progress(program, input, options, progress).await?;
+                (proof, extended_metadata(&metadata))
+            }
+            (ServedBackend::Sp1(backend), None) => {
+                let (proof, metadata) = backend.prove_with_options(program, input, options).await?;
+                (proof, extended_metadata(&metadata))
+            }
+            (ServedBackend::Risc0(backend), Some(progress)) => {
+                let (proof, metadata) = backend.prove_with_progress(program, input, options, progress).await?;
+                (proof, extended_metadata(&metadata))
+            }
+            (ServedBackend::Risc0(backend), None) => {
+                let (proof, metadata) = backend.prove_with_options(program, input, options).await?;
+                (proof, extended_metadata(&metadata))
+            }
+            (ServedBackend::Other(backend), _) => {
+                let (proof, metadata) = backend.prove(program, input, None).await?;
+                (proof, base_metadata(&metadata))
+            }
+        };
+        if !compress {
+            return Ok(pb::ProveResponse { proof, metadata: Some(metadata), compressed: false });
+        }
+        let proof = compression::compress(&proof)
+            .map_err(|e| ZkError::Backend(format!("Failed to compress proof: {}", e)))?;
+        Ok(pb::ProveResponse { proof, metadata: Some(metadata), compressed: true })
+    }
+
+    async fn verify(&self, program: &[u8], proof: &[u8], domain: Option<DomainSeparator>) -> Result<bool, ZkError> {
+        let result = match (self, domain) {
+            (ServedBackend::Sp1(backend), Some(domain)) => backend.verify_with_domain(program, proof, domain).await,
+            (ServedBackend::Risc0(backend), Some(domain)) => backend.verify_with_domain(program, proof, domain).await,
+            (ServedBackend::Other(_), Some(_)) => {
+                return Err(ZkError::InvalidInput("Backend cannot check proof domains".into()))
+            }
+            (ServedBackend::Sp1(backend), None) => ZkBackend::verify(backend.as_ref(), program, proof, None)
+                .await
+                .map_err(ZkError::from),
+            (ServedBackend::Risc0(backend), None) => ZkBackend::verify(backend.as_ref(), program, proof, None)
+                .await
+                .map_err(ZkError::from),
+            (ServedBackend::Other(backend), None) => backend.verify(program, proof, None)
+                .await
+                .map_err(ZkError::from),
+        };
+        match result {
+            Err(ZkError::DomainMismatch { .. }) => Ok(false),
+            result => result,
+        }
+    }
+
+    async fn execute(&self, program: &[u8], input: &[u8], domain: DomainSeparator) -> Result<ExecutionReport, ZkError> {
+        match self {
+            ServedBackend::Sp1(backend) => backend.execute(program, input, domain).await,
+            ServedBackend::Risc0(backend) => backend.execute(program, input, domain).await,
+            ServedBackend::Other(_) => Err(ZkError::Backend("Backend does not support execution".into())),
+        }
+    }
+}
+
+/// gRPC service proving with a single backend
+#[derive(Clone)]
+pub struct ProverService {
+    backend: ServedBackend,
+    signing_key: Option<Arc<Vec<u8>>>,
+}
+
+impl ProverService {
+    /// Serve `backend`
+    pub fn new(backend: ServedBackend) -> Self {
+        Self { backend, signing_key: None }
+    }
+
+    /// Only accept requests signed with `key`
+    pub fn with_signing_key(mut self, key: impl Into<Vec<u8>>) -> Self {
+        self.signing_key = Some(Arc::new(key.into()));
+        self
+    }
+
+    /// Wrap the service for a tonic server
+    pub fn into_server(self) -> ProverServer<Self> {
+        ProverServer::new(self)
+    }
+
+    /// Check the request signature if signing is required
+    fn authenticate<M: prost::Message>(&self, method: &str, request: &Request<M>) -> Result<(), Status> {
+        match &self.signing_key {
+            Some(key) => check_signature(key, method, request.get_ref(), request.metadata()),
+            None => Ok(()),
+        }
+    }
+}
+
+type UpdateStream = Pin<Box<dyn Stream<Item = Result<pb::ProveUpdate, Status>> + Send + 'static>>;
+
+#[tonic::async_trait]
+impl Prover for ProverService {
+    async fn prove(&self, request: Request<pb::ProveRequest>) -> Result<Response<pb::ProveResponse>, Status> {
+        self.authenticate("prove", &request)?;
+        let request = request.into_inner();
+        let options = prove_options(&request)?;
+        let response = self.backend.prove(&request.program, &request.input, &options, request.compress_proof, None).await?;
+        Ok(Response::new(response))
+    }
+
+    type ProveStreamStream = UpdateStream;
+
+    async fn prove_stream(&self, request: Request<pb::ProveRequest>) -> Result<Response<UpdateStream>, Status> {
+        self.authenticate("prove_stream", &request)?;
+        let request = request.into_inner();
+        let options = prove_options(&request)?;
+        let (progress, events) = mpsc::unbounded_channel();
+        let (updates, stream) = mpsc::unbounded_channel();
+
+        let backend = self.backend.clone();
+        tokio::spawn(async move {
+            let result = backend.prove(&request.program, &request.input, &options, request.compress_proof, Some(progress)).await;
+            let update = result
+                .map(|response| pb::ProveUpdate { update: Some(pb::prove_update::Update::Result(response)) })
+                .map_err(Status::from);
+            // The client going away is not an error
+            let _ = updates.send(update);
+        });
+
+        // Progress ends when the proving task drops its sender, before the result is sent
+        let progress = UnboundedReceiverStream::new(events).map(|event| Ok(pb::ProveUpdate {
+            update: Some(pb::prove_update::Update::Progress(progress_update(&event))),
+        }));
+        let result = UnboundedReceiverStream::new(stream);
+        Ok(Response::new(Box::pin(progress.chain(result))))
+    }
+
+    async fn verify(&self, request: Request<pb::VerifyRequest>) -> Result<Response<pb::VerifyResponse>, Status> {
+        self.authenticate("verify", &request)?;
+        let request = request.into_inner();
+        let valid = self.backend.verify(&request.program, &request.proof, request.domain.map(domain)).await?;
+        Ok(Response::new(pb::VerifyResponse { valid }))
+    }
+
+    async fn execute(&self, request: Request<pb::ExecuteRequest>) -> Result<Response<pb::ExecuteResponse>, Status> {
+        self.authenticate("execute", &request)?;
+        let request = request.into_inner();
+        let domain = request.domain.map(domain).unwrap_or_default();
+        let report = self.backend.execute(&request.program, &request.input, domain).await?;
+        Ok(Response::new(pb::ExecuteResponse {
+            public_values: report.public_values,
+            cycles: report.cycles,
+            segments: report.segments.map(|segments| segments as u64),
+        }))
+    }
+
+    async fn batch_prove(&self, request: Request<pb::BatchProveRequest>) -> Result<Response<pb::BatchProveResponse>, Status> {
+        self.authenticate("batch_prove", &request)?;
+        let requests = request.into_inner().requests;
+        let items = join_all(requests.iter().map(|request| async move {
+            let outcome = match prove_options(request) {
+                Ok(options) => self.backend.prove(&request.program, &request.input, &options, request.compress_proof, None)
+                    .await
+                    .map(pb::batch_prove_item::Outcome::Proof)
+                    .unwrap_or_else(|e| pb::batch_prove_item::Outcome::Error(e.to_string())),
+                Err(status) => pb::batch_prove_item::Outcome::Error(status.message().to_string()),
+            };
+            pb::BatchProveItem { outcome: Some(outcome) }
+        })).await;
+        Ok(Response::new(pb::BatchProveResponse { items }))
+    }
+
+    async fn batch_verify(&self, request: Request<pb::BatchVerifyRequest>) -> Result<Response<pb::BatchVerifyResponse>, Status> {
+        self.authenticate("batch_verify", &request)?;
+        let requests = request.into_inner().requests;
+        let items = join_all(requests.iter().map(|request| async move {
+            let outcome = self.backend.verify(&request.program, &request.proof, request.domain.map(domain))
+                .await
+                .map(pb::batch_verify_item::Outcome::Valid)
+                .unwrap_or_else(|e| pb::batch_verify_item::Outcome::Error(e.to_string()));
+            pb::BatchVerifyItem { outcome: Some(outcome) }
+        })).await;
+        Ok(Response::new(pb::BatchVerifyResponse { items }))
+    }
+}
+
+impl From<ZkError> for Status {
+    fn from(error: ZkError) -> Self {
+        let message = error.to_string();
+        match error {
+            ZkError::InvalidInput(_)
+            | ZkError::InvalidProgram(_)
+            | ZkError::UnsupportedProgramVersion { .. }
+            | ZkError::DomainMismatch { .. } => Status::invalid_argument(message),
+            ZkError::UnknownProgram(_) | ZkError::UnknownBackend(_) => Status::not_found(message),
+            ZkError::Timeout(_) | ZkError::DeadlineUnreachable { .. } => Status::deadline_exceeded(message),
+            ZkError::Cancelled => Status::cancelled(message),
+            ZkError::QueueFull { .. } => Status::resource_exhausted(message),
+            ZkError::ProofTooLarge { .. }
+            | ZkError::IncompatibleSdk { .. }
+            | ZkError::IncompatibleCircuit { .. }
+            | ZkError::UnsupportedEnvelopeVersion { .. }
+            | ZkError::UnsupportedVerifier { .. } => Status::failed_precondition(message),
+            ZkError::Replayed { .. } => Status::already_exists(message),
+            _ => Status::internal(message),
+        }
+    }
+}
+
+fn prove_options(request: &pb::ProveRequest) -> Result<ProveOptions, Status> {
+    let mut options = ProveOptions::default();
+    if let Some(mode) = &request.proof_mode {
+        options = options.with_proof_mode(parse_mode(mode)?);
+    }
+    if let Some(max_proof_size) = request.max_proof_size {
+        options = options.with_max_proof_size(max_proof_size as usize);
+    }
+    if let Some(timeout_ms) = request.timeout_ms {
+        options = options.with_timeout(Duration::from_millis(timeout_ms));
+    }
+    if let Some(domain) = request.domain.clone() {
+        options = options.with_domain(self::domain(domain));
+    }
+    Ok(options)
+}
+
+fn base_metadata(metadata: &ProofMetadata) -> pb::ProofMetadata {
+    pb::ProofMetadata {
+        generation_time_ms: metadata.generation_time.as_millis() as u64,
+        proof_size: metadata.proof_size as u64,
+        program_hash: metadata.program_hash.clone(),
+        timestamp: metadata.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        ..Default::default()
+    }
+}
+
+fn extended_metadata(metadata: &ExtendedProofMetadata) -> pb::ProofMetadata {
+    pb::ProofMetadata {
+        proof_mode: Some(mode_name(metadata.proof_mode)),
+        backend: Some(metadata.backend.clone()),
+        sdk_version: Some(metadata.sdk_version.clone()),
+        circuit_type: metadata.circuit_type.map(u32::from),
+        circuit_version: metadata.circuit_version,
+        vkey_hash: metadata.vkey_hash.map(|hash| hash.to_vec()).unwrap_or_default(),
+        public_values: metadata.public_values.clone(),
+        cycles: metadata.cycles,
+        segments: metadata.segments.map(|segments| segments as u64),
+        peak_memory: metadata.peak_memory.map(|memory| memory as u64),
+        ..base_metadata(&metadata.base)
+    }
+}
+
+fn progress_update(event: &ProveProgress) -> pb::Progress {
+    match *event {
+        ProveProgress::SetupDone { cached } => pb::Progress {
+            stage: "setup_done".into(),
+            cached: Some(cached),
+            ..Default::default()
+        },
+        ProveProgress::Executed { cycles, segments } => pb::Progress {
+            stage: "executed".into(),
+            cycles,
+            total: segments.map(|segments| segments as u64),
+            ..Default::default()
+        },
+        ProveProgress::SegmentsProven { proven, total } => pb::Progress {
+            stage: "segments_proven".into(),
+            proven: Some(proven as u64),
+            total: Some(total as u64),
+            ..Default::default()
+        },
+        ProveProgress::Escalated { from, to } => pb::Progress {
+            stage: "escalated".into(),
+            from_mode: Some(mode_name(from)),
+            to_mode: Some(mode_name(to)),
+            ..Default::default()
+        },
+        ProveProgress::Serialized { proof_size } => pb::Progress {
+            stage: "serialized".into(),
+            proof_size: Some(proof_size as u64),
+            ..Default::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::mock::MockBackend;
+
+    #[tokio::test]
+    async fn test_batch_reports_items_separately() {
+        let service = ProverService::new(ServedBackend::Other(Arc::new(MockBackend::new())));
+        let proof = MockBackend::proof(&[1], &[2]);
+
+        let request = pb::BatchVerifyRequest {
+            requests: vec![
+                pb::VerifyRequest { program: vec![1], proof: proof.clone(), domain: None },
+                pb::VerifyRequest { program: vec![9], proof, domain: None },
+            ],
+        };
+        let items = service.batch_verify(Request::new(request)).await.unwrap().into_inner().items;
+        let outcomes: Vec<_> = items.into_iter().map(|item| item.outcome.unwrap()).collect();
+        assert_eq!(outcomes, vec![
+            pb::batch_verify_item::Outcome::Valid(true),
+            pb::batch_verify_item::Outcome::Valid(false),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_compressed_proof() {
+        let service = ProverService::new(ServedBackend::Other(Arc::new(MockBackend::new())));
+        let request = pb::ProveRequest {
+            program: vec![1],
+            input: vec![2],
+            compress_proof: true,
+            ..Default::default()
+        };
+        let response = service.prove(Request::new(request)).await.unwrap().into_inner();
+        assert!(response.compressed);
+        assert_eq!(compression::decompress(&response.proof).unwrap(), MockBackend::proof(&[1], &[2]));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_mode_rejected() {
+        let service = ProverService::new(ServedBackend::Other(Arc::new(MockBackend::new())));
+        let request = pb::ProveRequest {
+            program: vec![1],
+            proof_mode: Some("stark".into()),
+            ..Default::default()
+        };
+        let status = service.prove(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_signature_required() {
+        let service = ProverService::new(ServedBackend::Other(Arc::new(MockBackend::new())))
+            .with_signing_key("secret");
+        let message = pb::VerifyRequest { program: vec![1], proof: vec![2], domain: None };
+
+        let status = service.verify(Request::new(message.clone())).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let mut request = Request::new(message.clone());
+        crate::grpc::sign(b"secret", "verify", &message, request.metadata_mut());
+        assert!(service.verify(request).await.is_ok());
+    }
+}
@@ -0,0 +1,216 @@
+//! Programs and inputs driving the signature guests
+//!
+//! An [`Eip712Circuit`] proves EIP-712 signatures under a trusted domain separator
+//! with the `eip712_verifier` guest. Its program is a [`ProgramDescriptor`] of
+//! [`EIP712_VERIFY`] with the domain separator as parameters, so proofs for another
+//! application or chain do not verify against it.
+//!
+//! An [`EcdsaCircuit`] proves secp256k1 signatures over a message hash. Its program
+//! and input are the same for both backends: SP1 runs the `ecdsa_verifier` guest and
+//! RISC0 its built-in `ecdsa_verify` circuit.
+
+use crate::descriptor::ProgramDescriptor;
+use crate::error::ZkError;
+use crate::journal::{EcdsaVerifyOutput, SignedMessageOutput};
+use crate::profiles::{ECDSA_VERIFY, EIP712_VERIFY};
+use super::ecdsa::{self, EcdsaWitness};
+use super::eip712::{self, Eip712Witness, SignedMessage};
+
+/// EIP-712 signatures proven under a trusted domain separator
+///
+/// The statement reveals the signer and the hash of the message, not the message.
+/// A verifier expecting a given signer checks it with [`Eip712Circuit::output_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Eip712Circuit {
+    /// Trusted domain separator
+    pub domain_hash: [u8; 32],
+}
+
+impl Eip712Circuit {
+    /// Create a verifier of messages signed under `domain_hash`
+    pub fn new(domain_hash: [u8; 32]) -> Self {
+        Self { domain_hash }
+    }
+
+    /// Descriptor of the EIP-712 program for the domain
+    pub fn descriptor(&self) -> ProgramDescriptor {
+        ProgramDescriptor::new(EIP712_VERIFY, self.domain_hash)
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.descriptor().encode()
+    }
+
+    /// Check the signature on the host, as the guest will
+    pub fn verify(&self, witness: &Eip712Witness) -> Result<SignedMessage, ZkError> {
+        let statement = eip712::verify(witness)
+            .map_err(|e| ZkError::InvalidInput(format!("Invalid EIP-712 witness: {}", e)))?;
+        if statement.domain_hash != self.domain_hash {
+            return Err(ZkError::InvalidInput("Typed data is not for the trusted domain".into()));
+        }
+        Ok(statement)
+    }
+
+    /// Proving input for `witness`, checked to be signed under the trusted domain
+    pub fn input(&self, witness: &Eip712Witness) -> Result<Vec<u8>, ZkError> {
+        self.verify(witness)?;
+        Ok(bincode::serialize(witness).expect("witness serialization cannot fail"))
+    }
+
+    /// Decode the public values of a proof, rejecting messages of another domain
+    pub fn output(&self, public_values: &[u8]) -> Result<SignedMessageOutput, ZkError> {
+        let output = SignedMessageOutput::decode(public_values)?;
+        if output.statement.domain_hash != self.domain_hash {
+            return Err(ZkError::ProofVerification(format!(
+                "Proof is under domain {}, expected {}",
+                hex::encode(output.statement.domain_hash),
+                hex::encode(self.domain_hash)
+            )));
+        }
+        Ok(output)
+    }
+
+    /// Decode the public values of a proof, rejecting messages signed by any address
+    /// but `signer`
+    pub fn output_for(&self, public_values: &[u8], signer: &[u8; 20]) -> Result<SignedMessageOutput, ZkError> {
+        let output = self.output(public_values)?;
+        if output.statement.signer != *signer {
+            return Err(ZkError::ProofVerification(format!(
+                "Message is signed by 0x{}, expected 0x{}",
+                hex::encode(output.statement.signer),
+                hex::encode(signer)
+            )));
+        }
+        Ok(output)
+    }
+}
+
+/// secp256k1 ECDSA signatures proven over a message hash
+///
+/// A verifier expecting a given signer checks it with [`EcdsaCircuit::output_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EcdsaCircuit {
+    /// Signed message hash
+    pub message_hash: [u8; 32],
+}
+
+impl EcdsaCircuit {
+    /// Create a verifier of signatures over `message_hash`
+    pub fn new(message_hash: [u8; 32]) -> Self {
+        Self { message_hash }
+    }
+
+    /// Descriptor of the ECDSA program for the message hash
+    pub fn descriptor(&self) -> ProgramDescriptor {
+        ProgramDescriptor::new(ECDSA_VERIFY, self.message_hash)
+    }
+
+    /// Program bytes to prove and verify with
+    pub fn program(&self) -> Vec<u8> {
+        self.descriptor().encode()
+    }
+
+    /// Proving input for `witness`, checked to be a valid signature over the message hash
+    pub fn input(&self, witness: &EcdsaWitness) -> Result<Vec<u8>, ZkError> {
+        if witness.message_hash != self.message_hash {
+            return Err(ZkError::InvalidInput("Signature is over another message hash".into()));
+        }
+        ecdsa::verify(witness)
+            .map_err(|e| ZkError::InvalidInput(format!("Invalid ECDSA witness: {}", e)))?;
+        Ok(bincode::serialize(witness).expect("witness serialization cannot fail"))
+    }
+
+    /// Decode the public values of a proof, rejecting signatures over another hash
+    pub fn output(&self, public_values: &[u8]) -> Result<EcdsaVerifyOutput, ZkError> {
+        let output = EcdsaVerifyOutput::decode(public_values)?;
+        if output.statement.message_hash != self.message_hash {
+            return Err(ZkError::ProofVerification(format!(
+                "Signature is over {}, expected {}",
+                hex::encode(output.statement.message_hash),
+                hex::encode(self.message_hash)
+            )));
+        }
+        Ok(output)
+    }
+
+    /// Decode the public values of a proof, rejecting signatures by any key but
+    /// `public_key` (SEC1, compressed or not)
+    pub fn output_for(&self, public_values: &[u8], public_key: &[u8]) -> Result<EcdsaVerifyOutput, ZkError> {
+        let expected = ecdsa::compress(public_key)
+            .map_err(|e| ZkError::InvalidInput(format!("Invalid public key: {}", e)))?;
+        let output = self.output(public_values)?;
+        if output.statement.public_key != expected {
+            return Err(ZkError::ProofVerification("Message is signed by another key".into()));
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signatures::eip712::{Field, StructType, TypedData, Value, DOMAIN_TYPE};
+    use crate::signatures::keccak256;
+    use k256::ecdsa::SigningKey;
+
+    fn struct_type(name: &str, fields: &[(&str, &str)]) -> StructType {
+        StructType {
+            name: name.into(),
+            fields: fields.iter()
+                .map(|(field_type, name)| Field { name: (*name).into(), field_type: (*field_type).into() })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_eip712_circuit() {
+        let typed_data = TypedData {
+            types: vec![
+                struct_type(DOMAIN_TYPE, &[("string", "name"), ("uint256", "chainId")]),
+                struct_type("Intent", &[("address", "asset"), ("uint256", "amount")]),
+            ],
+            primary_type: "Intent".into(),
+            domain: vec![Value::string("Frostgate"), Value::uint(1)],
+            message: vec![Value::address([0xEE; 20]), Value::uint(10u128.pow(18))],
+        };
+        let key = SigningKey::from_slice(&[0x11; 32]).unwrap();
+        let (signature, recovery_id) = key.sign_prehash_recoverable(&typed_data.signing_hash().unwrap()).unwrap();
+        let mut signature = signature.to_vec();
+        signature.push(27 + recovery_id.to_byte());
+        let point = key.verifying_key().to_encoded_point(false);
+        let signer: [u8; 20] = keccak256(&point.as_bytes()[1..])[12..].try_into().unwrap();
+
+        let witness = Eip712Witness { typed_data, signature };
+        let circuit = Eip712Circuit::new(witness.typed_data.domain_hash().unwrap());
+        assert_eq!(ProgramDescriptor::decode(&circuit.program()).unwrap().circuit_type, EIP712_VERIFY);
+        let statement = circuit.verify(&witness).unwrap();
+        assert_eq!(statement.signer, signer);
+        assert!(circuit.input(&witness).is_ok());
+        assert!(Eip712Circuit::new([0; 32]).input(&witness).is_err());
+
+        // Public values of another domain or signer are rejected
+        assert!(circuit.output_for(&statement.to_bytes(), &signer).is_ok());
+        assert!(circuit.output_for(&statement.to_bytes(), &[0; 20]).is_err());
+        assert!(Eip712Circuit::new([0; 32]).output(&statement.to_bytes()).is_err());
+    }
+    #[test]
+    fn test_ecdsa_circuit() {
+        let key = SigningKey::from_slice(&[0x22; 32]).unwrap();
+        let (signature, _) = key.sign_prehash_recoverable(&[0x33; 32]).unwrap();
+        let public_key = key.verifying_key().to_encoded_point(false).as_bytes().to_vec();
+        let witness = EcdsaWitness { message_hash: [0x33; 32], public_key: public_key.clone(), signature: signature.to_vec() };
+
+        let circuit = EcdsaCircuit::new([0x33; 32]);
+        assert_eq!(ProgramDescriptor::decode(&circuit.program()).unwrap().circuit_type, ECDSA_VERIFY);
+        assert!(circuit.input(&witness).is_ok());
+        assert!(EcdsaCircuit::new([0x44; 32]).input(&witness).is_err());
+
+        // Public values over another hash or by another key are rejected
+        let statement = ecdsa::verify(&witness).unwrap();
+        assert!(circuit.output_for(&statement.to_bytes(), &public_key).is_ok());
+        let other = SigningKey::from_slice(&[0x55; 32]).unwrap();
+        assert!(circuit.output_for(&statement.to_bytes(), other.verifying_key().to_encoded_point(true).as_bytes()).is_err());
+        assert!(EcdsaCircuit::new([0x44; 32]).output(&statement.to_bytes()).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! secp256k1 ECDSA signatures
+//!
+//! The witness is a public key and its signature over a message hash. The guests
+//! check the signature and commit a [`VerifiedSignature`]: the message hash and the
+//! compressed public key. The SP1 guest builds k256 against SP1's patched crate, so
+//! its curve arithmetic runs on the secp256k1 precompiles; the RISC0 guest runs it in
+//! software until the guests move to a `risc0-zkvm` with an accelerated k256.
+
+use alloc::vec::Vec;
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use k256::ecdsa::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use super::SignatureError;
+
+/// Length of a compressed SEC1 public key
+pub const PUBLIC_KEY_LEN: usize = 33;
+
+/// Signature witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EcdsaWitness {
+    /// Signed message hash
+    pub message_hash: [u8; 32],
+    /// SEC1 public key, compressed or not
+    pub public_key: Vec<u8>,
+    /// 64-byte `r ++ s` signature
+    pub signature: Vec<u8>,
+}
+
+/// Statement proven by the ECDSA guests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifiedSignature {
+    /// Signed message hash
+    pub message_hash: [u8; 32],
+    /// Compressed SEC1 public key of the signer
+    pub public_key: [u8; PUBLIC_KEY_LEN],
+}
+
+impl VerifiedSignature {
+    /// Length of an encoded statement
+    pub const LEN: usize = 32 + PUBLIC_KEY_LEN;
+
+    /// Encode as the guests commit it: message hash, public key
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..32].copy_from_slice(&self.message_hash);
+        bytes[32..].copy_from_slice(&self.public_key);
+        bytes
+    }
+
+    /// Decode a statement, `None` if `bytes` is not [`Self::LEN`] long
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        Some(Self {
+            message_hash: bytes[..32].try_into().ok()?,
+            public_key: bytes[32..].try_into().ok()?,
+        })
+    }
+}
+
+/// Verify the witness's signature
+///
+/// Signatures with a high `s` are rejected, so every signed message has a single
+/// valid signature as on Bitcoin and Ethereum.
+pub fn verify(witness: &EcdsaWitness) -> Result<VerifiedSignature, SignatureError> {
+    let key = VerifyingKey::from_sec1_bytes(&witness.public_key).map_err(|_| SignatureError::Malformed("public key"))?;
+    if witness.signature.len() != 64 {
+        return Err(SignatureError::Malformed("signature"));
+    }
+    let signature = Signature::from_slice(&witness.signature).map_err(|_| SignatureError::InvalidSignature)?;
+    key.verify_prehash(&witness.message_hash, &signature)
+        .map_err(|_| SignatureError::InvalidSignature)?;
+    Ok(VerifiedSignature {
+        message_hash: witness.message_hash,
+        public_key: compressed(&key),
+    })
+}
+
+/// Compressed form of a SEC1 public key
+pub fn compress(public_key: &[u8]) -> Result<[u8; PUBLIC_KEY_LEN], SignatureError> {
+    VerifyingKey::from_sec1_bytes(public_key)
+        .map(|key| compressed(&key))
+        .map_err(|_| SignatureError::Malformed("public key"))
+}
+
+fn compressed(key: &VerifyingKey) -> [u8; PUBLIC_KEY_LEN] {
+    key.to_encoded_point(true)
+        .as_bytes()
+        .try_into()
+        .expect("compressed points are 33 bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    #[test]
+    fn test_signature() {
+        let key = SigningKey::from_slice(&[0x22; 32]).unwrap();
+        let message_hash = [0x33; 32];
+        let signature: Signature = key.sign_prehash_recoverable(&message_hash).unwrap().0;
+        let public_key = key.verifying_key().to_encoded_point(false).as_bytes().to_vec();
+        let mut witness = EcdsaWitness { message_hash, public_key, signature: signature.to_vec() };
+
+        let statement = verify(&witness).unwrap();
+        assert_eq!(statement.public_key, compress(&witness.public_key).unwrap());
+        assert_eq!(statement.public_key.as_slice(), key.verifying_key().to_encoded_point(true).as_bytes());
+        assert_eq!(VerifiedSignature::from_bytes(&statement.to_bytes()), Some(statement));
+
+        // The malleated signature (r, n - s) is rejected
+        let (r, s) = signature.split_scalars();
+        let high = Signature::from_scalars(r, -*s).unwrap();
+        witness.signature = high.to_vec();
+        assert_eq!(verify(&witness), Err(SignatureError::InvalidSignature));
+
+        // So are signatures over another hash
+        witness.signature = signature.to_vec();
+        witness.message_hash[0] ^= 1;
+        assert_eq!(verify(&witness), Err(SignatureError::InvalidSignature));
+        witness.public_key.truncate(33);
+        assert!(matches!(verify(&witness), Err(SignatureError::Malformed(_))));
+    }
+}
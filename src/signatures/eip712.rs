@@ -0,0 +1,394 @@
+//! EIP-712 typed data signatures
+//!
+//! The witness is the typed data and its signature. The guest hashes the domain and
+//! the message as EIP-712 specifies, recovers the signer of the signing hash and
+//! commits a [`SignedMessage`]: the hash of the message struct, the domain
+//! separator and the signer. The message itself stays private; a verifier holding
+//! it checks its [`TypedData::struct_hash`] against the proof.
+//!
+//! Values are given already encoded for their type: atomic values (`uintN`, `intN`,
+//! `address`, `bool`, `bytesN`) as their 32-byte ABI word, `bytes` and `string` as
+//! their contents, arrays and structs as lists of values.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use super::{ecrecover, keccak256, SignatureError};
+
+/// Name of the domain struct type
+pub const DOMAIN_TYPE: &str = "EIP712Domain";
+
+/// Member of a struct type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Field {
+    /// Member name
+    pub name: String,
+    /// Member type, e.g. `address`, `Person` or `uint256[]`
+    pub field_type: String,
+}
+
+/// Struct type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructType {
+    /// Type name
+    pub name: String,
+    /// Members in declaration order
+    pub fields: Vec<Field>,
+}
+
+/// Value of a member, encoded for its type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Value {
+    /// Atomic value as its 32-byte ABI word
+    Word([u8; 32]),
+    /// Contents of a `bytes` or `string` value
+    Dynamic(Vec<u8>),
+    /// Array elements
+    Array(Vec<Value>),
+    /// Struct members in declaration order
+    Struct(Vec<Value>),
+}
+
+impl Value {
+    /// `uintN` value
+    pub fn uint(value: u128) -> Self {
+        let mut word = [0u8; 32];
+        word[16..].copy_from_slice(&value.to_be_bytes());
+        Value::Word(word)
+    }
+
+    /// `address` value
+    pub fn address(address: [u8; 20]) -> Self {
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(&address);
+        Value::Word(word)
+    }
+
+    /// `bool` value
+    pub fn boolean(value: bool) -> Self {
+        Self::uint(u128::from(value))
+    }
+
+    /// `string` value
+    pub fn string(value: &str) -> Self {
+        Value::Dynamic(value.as_bytes().to_vec())
+    }
+}
+
+/// Typed data to sign
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypedData {
+    /// Struct types, including [`DOMAIN_TYPE`]
+    pub types: Vec<StructType>,
+    /// Type of the message
+    pub primary_type: String,
+    /// Members of the domain
+    pub domain: Vec<Value>,
+    /// Members of the message
+    pub message: Vec<Value>,
+}
+
+impl TypedData {
+    /// Domain separator, `hashStruct(domain)`
+    pub fn domain_hash(&self) -> Result<[u8; 32], SignatureError> {
+        self.hash_struct(DOMAIN_TYPE, &self.domain)
+    }
+
+    /// `hashStruct(message)`
+    pub fn struct_hash(&self) -> Result<[u8; 32], SignatureError> {
+        self.hash_struct(&self.primary_type, &self.message)
+    }
+
+    /// Hash signed by the signer: `keccak256(0x19 0x01 ++ domain separator ++ hashStruct(message))`
+    pub fn signing_hash(&self) -> Result<[u8; 32], SignatureError> {
+        Ok(signing_hash(&self.domain_hash()?, &self.struct_hash()?))
+    }
+
+    /// `encodeType` of a struct type: the type followed by the types it references,
+    /// sorted by name
+    pub fn encode_type(&self, name: &str) -> Result<String, SignatureError> {
+        let mut dependencies = Vec::new();
+        self.dependencies(name, &mut dependencies)?;
+        dependencies.sort_unstable();
+        let mut encoded = String::new();
+        for dependency in core::iter::once(name).chain(dependencies.into_iter().filter(|dependency| *dependency != name)) {
+            let struct_type = self.struct_type(dependency)?;
+            encoded.push_str(&struct_type.name);
+            encoded.push('(');
+            for (i, field) in struct_type.fields.iter().enumerate() {
+                if i > 0 {
+                    encoded.push(',');
+                }
+                encoded.push_str(&field.field_type);
+                encoded.push(' ');
+                encoded.push_str(&field.name);
+            }
+            encoded.push(')');
+        }
+        Ok(encoded)
+    }
+
+    /// `hashStruct` of a value of struct type `name`
+    fn hash_struct(&self, name: &str, members: &[Value]) -> Result<[u8; 32], SignatureError> {
+        let struct_type = self.struct_type(name)?;
+        if members.len() != struct_type.fields.len() {
+            return Err(SignatureError::Malformed("struct members"));
+        }
+        let mut encoded = Vec::with_capacity(32 * (members.len() + 1));
+        encoded.extend_from_slice(&keccak256(self.encode_type(name)?.as_bytes()));
+        for (field, value) in struct_type.fields.iter().zip(members) {
+            encoded.extend_from_slice(&self.encode_value(&field.field_type, value)?);
+        }
+        Ok(keccak256(&encoded))
+    }
+
+    /// `encodeData` word of a value of type `field_type`
+    fn encode_value(&self, field_type: &str, value: &Value) -> Result<[u8; 32], SignatureError> {
+        if let Some((element_type, len)) = array_type(field_type)? {
+            let elements = match value {
+                Value::Array(elements) if len.is_none_or(|len| len == elements.len()) => elements,
+                _ => return Err(SignatureError::Malformed("array value")),
+            };
+            let mut encoded = Vec::with_capacity(32 * elements.len());
+            for element in elements {
+                encoded.extend_from_slice(&self.encode_value(element_type, element)?);
+            }
+            return Ok(keccak256(&encoded));
+        }
+        match value {
+            Value::Struct(members) if self.has_type(field_type) => self.hash_struct(field_type, members),
+            Value::Dynamic(bytes) if field_type == "bytes" || field_type == "string" => Ok(keccak256(bytes)),
+            Value::Word(word) if is_atomic(field_type) => Ok(*word),
+            _ => Err(SignatureError::Malformed("value for its type")),
+        }
+    }
+
+    /// Collect the struct types `name` references, directly or not, into `found`
+    fn dependencies<'a>(&'a self, name: &'a str, found: &mut Vec<&'a str>) -> Result<(), SignatureError> {
+        if found.contains(&name) {
+            return Ok(());
+        }
+        found.push(name);
+        for field in &self.struct_type(name)?.fields {
+            let base = base_type(&field.field_type);
+            if self.has_type(base) {
+                self.dependencies(base, found)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn struct_type(&self, name: &str) -> Result<&StructType, SignatureError> {
+        self.types.iter()
+            .find(|struct_type| struct_type.name == name)
+            .ok_or(SignatureError::Malformed("typed data: undefined struct type"))
+    }
+
+    fn has_type(&self, name: &str) -> bool {
+        self.types.iter().any(|struct_type| struct_type.name == name)
+    }
+}
+
+/// Signed typed data witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Eip712Witness {
+    /// Signed typed data
+    pub typed_data: TypedData,
+    /// 65-byte `r ++ s ++ v` signature over the signing hash
+    pub signature: Vec<u8>,
+}
+
+/// Statement proven by the EIP-712 guest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedMessage {
+    /// `hashStruct(message)`
+    pub struct_hash: [u8; 32],
+    /// Domain separator
+    pub domain_hash: [u8; 32],
+    /// Address of the signer
+    pub signer: [u8; 20],
+}
+
+impl SignedMessage {
+    /// Length of an encoded statement
+    pub const LEN: usize = 84;
+
+    /// Encode as the guest commits it: struct hash, domain separator, signer
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..32].copy_from_slice(&self.struct_hash);
+        bytes[32..64].copy_from_slice(&self.domain_hash);
+        bytes[64..].copy_from_slice(&self.signer);
+        bytes
+    }
+
+    /// Decode a statement, `None` if `bytes` is not [`Self::LEN`] long
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        Some(Self {
+            struct_hash: bytes[..32].try_into().ok()?,
+            domain_hash: bytes[32..64].try_into().ok()?,
+            signer: bytes[64..].try_into().ok()?,
+        })
+    }
+
+    /// Hash the signer signed
+    pub fn signing_hash(&self) -> [u8; 32] {
+        signing_hash(&self.domain_hash, &self.struct_hash)
+    }
+}
+
+/// Recover the signer of the typed data
+pub fn verify(witness: &Eip712Witness) -> Result<SignedMessage, SignatureError> {
+    let statement = SignedMessage {
+        struct_hash: witness.typed_data.struct_hash()?,
+        domain_hash: witness.typed_data.domain_hash()?,
+        signer: [0; 20],
+    };
+    Ok(SignedMessage {
+        signer: ecrecover(&statement.signing_hash(), &witness.signature)?,
+        ..statement
+    })
+}
+
+fn signing_hash(domain_hash: &[u8; 32], struct_hash: &[u8; 32]) -> [u8; 32] {
+    let mut encoded = [0u8; 66];
+    encoded[..2].copy_from_slice(&[0x19, 0x01]);
+    encoded[2..34].copy_from_slice(domain_hash);
+    encoded[34..].copy_from_slice(struct_hash);
+    keccak256(&encoded)
+}
+
+/// Element type and length (`None` if dynamic) of an array type, `None` for other types
+fn array_type(field_type: &str) -> Result<Option<(&str, Option<usize>)>, SignatureError> {
+    let Some(inner) = field_type.strip_suffix(']') else {
+        return Ok(None);
+    };
+    let (element_type, len) = inner.rsplit_once('[').ok_or(SignatureError::Malformed("array type"))?;
+    let len = match len {
+        "" => None,
+        len => Some(len.parse().map_err(|_| SignatureError::Malformed("array type"))?),
+    };
+    Ok(Some((element_type, len)))
+}
+
+/// Type of the innermost elements of a possibly nested array type
+fn base_type(field_type: &str) -> &str {
+    field_type.split('[').next().unwrap_or(field_type)
+}
+
+/// Whether `field_type` names an atomic type
+fn is_atomic(field_type: &str) -> bool {
+    let sized = |prefix: &str, max: u32, step: u32| {
+        field_type.strip_prefix(prefix)
+            .and_then(|bits| bits.parse::<u32>().ok())
+            .is_some_and(|bits| (step..=max).contains(&bits) && bits % step == 0)
+    };
+    matches!(field_type, "address" | "bool") || sized("uint", 256, 8) || sized("int", 256, 8) || sized("bytes", 32, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn hex<const N: usize>(hex: &str) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        bytes
+    }
+
+    fn struct_type(name: &str, fields: &[(&str, &str)]) -> StructType {
+        StructType {
+            name: name.into(),
+            fields: fields.iter()
+                .map(|(field_type, name)| Field { name: (*name).into(), field_type: (*field_type).into() })
+                .collect(),
+        }
+    }
+
+    /// The `Mail` example of the EIP
+    fn mail() -> TypedData {
+        let person = |name: &str, wallet: &str| Value::Struct(vec![Value::string(name), Value::address(hex(wallet))]);
+        TypedData {
+            types: vec![
+                struct_type(DOMAIN_TYPE, &[("string", "name"), ("string", "version"), ("uint256", "chainId"), ("address", "verifyingContract")]),
+                struct_type("Mail", &[("Person", "from"), ("Person", "to"), ("string", "contents")]),
+                struct_type("Person", &[("string", "name"), ("address", "wallet")]),
+            ],
+            primary_type: "Mail".into(),
+            domain: vec![
+                Value::string("Ether Mail"),
+                Value::string("1"),
+                Value::uint(1),
+                Value::address(hex("cccccccccccccccccccccccccccccccccccccccc")),
+            ],
+            message: vec![
+                person("Cow", "cd2a3d9f938e13cd947ec05abc7fe734df8dd826"),
+                person("Bob", "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+                Value::string("Hello, Bob!"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_mail_example() {
+        let mail = mail();
+        assert_eq!(mail.encode_type("Mail").unwrap(), "Mail(Person from,Person to,string contents)Person(string name,address wallet)");
+        assert_eq!(mail.domain_hash().unwrap(), hex("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"));
+        assert_eq!(mail.struct_hash().unwrap(), hex("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"));
+        assert_eq!(mail.signing_hash().unwrap(), hex("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"));
+
+        let mut signature = hex::<65>("4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c").to_vec();
+        let mut witness = Eip712Witness { typed_data: mail, signature: signature.clone() };
+        let statement = verify(&witness).unwrap();
+        assert_eq!(statement.signer, hex("cd2a3d9f938e13cd947ec05abc7fe734df8dd826"));
+        assert_eq!(SignedMessage::from_bytes(&statement.to_bytes()), Some(statement));
+
+        // Another message recovers another address
+        witness.typed_data.message[2] = Value::string("Hello, Alice!");
+        assert_ne!(verify(&witness).unwrap().signer, statement.signer);
+
+        // Values must match their type
+        witness.typed_data.message[2] = Value::uint(1);
+        assert!(matches!(verify(&witness), Err(SignatureError::Malformed(_))));
+        signature[64] = 29;
+        witness.typed_data.message[2] = Value::string("Hello, Bob!");
+        witness.signature = signature;
+        assert!(matches!(verify(&witness), Err(SignatureError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_arrays() {
+        let data = TypedData {
+            types: vec![
+                struct_type(DOMAIN_TYPE, &[("uint256", "chainId")]),
+                struct_type("Batch", &[("uint64[2]", "amounts"), ("Leg[]", "legs")]),
+                struct_type("Leg", &[("bytes32", "id")]),
+            ],
+            primary_type: "Batch".into(),
+            domain: vec![Value::uint(1)],
+            message: vec![
+                Value::Array(vec![Value::uint(1), Value::uint(2)]),
+                Value::Array(vec![Value::Struct(vec![Value::Word([7; 32])])]),
+            ],
+        };
+        assert_eq!(data.encode_type("Batch").unwrap(), "Batch(uint64[2] amounts,Leg[] legs)Leg(bytes32 id)");
+        let expected = keccak256(&[
+            keccak256(data.encode_type("Batch").unwrap().as_bytes()),
+            keccak256(&[Value::uint(1), Value::uint(2)].map(|value| match value { Value::Word(word) => word, _ => unreachable!() }).concat()),
+            keccak256(&data.hash_struct("Leg", &[Value::Word([7; 32])]).unwrap()),
+        ].concat());
+        assert_eq!(data.struct_hash().unwrap(), expected);
+
+        // Fixed-size arrays must have their length
+        let mut short = data;
+        short.message[0] = Value::Array(vec![Value::uint(1)]);
+        assert!(short.struct_hash().is_err());
+    }
+}
@@ -0,0 +1,67 @@
+//! Proofs of signatures over messages
+//!
+//! The signature guests in `circuits/` prove that a key signed a message while
+//! only committing to hashes of the message:
+//!
+//! - [`ecdsa`]: a secp256k1 key signed a message hash
+//! - [`eip712`]: an Ethereum address signed EIP-712 typed data
+//!
+//! Like the chain verifiers, the verification logic only depends on `alloc` and the
+//! hash and curve crates so the guests include the very same source files; the
+//! host-side programs and inputs driving the guests are in [`circuit`].
+
+pub mod ecdsa;
+pub mod eip712;
+#[cfg(feature = "std")]
+pub mod circuit;
+
+use core::fmt;
+use sha3::{Digest, Keccak256};
+
+/// Reason a signature witness does not prove its statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// A structure of the witness could not be decoded or does not match its type
+    Malformed(&'static str),
+    /// The signature does not verify
+    InvalidSignature,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::Malformed(what) => write!(f, "malformed {}", what),
+            SignatureError::InvalidSignature => write!(f, "invalid signature"),
+        }
+    }
+}
+
+/// Address of the secp256k1 key that produced a 65-byte `r ++ s ++ v` signature
+/// over `prehash`, as Ethereum's `ecrecover`
+///
+/// `v` may be 0/1 or 27/28. Signatures with a high `s` are rejected, as
+/// Ethereum transactions do since EIP-2.
+pub fn ecrecover(prehash: &[u8; 32], signature: &[u8]) -> Result<[u8; 20], SignatureError> {
+    use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+
+    if signature.len() != 65 {
+        return Err(SignatureError::Malformed("signature"));
+    }
+    let recovery_id = match signature[64] {
+        0 | 27 => RecoveryId::new(false, false),
+        1 | 28 => RecoveryId::new(true, false),
+        _ => return Err(SignatureError::Malformed("signature recovery id")),
+    };
+    let signature = Signature::from_slice(&signature[..64]).map_err(|_| SignatureError::InvalidSignature)?;
+    let key = VerifyingKey::recover_from_prehash(prehash, &signature, recovery_id)
+        .map_err(|_| SignatureError::InvalidSignature)?;
+    let point = key.to_encoded_point(false);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&keccak256(&point.as_bytes()[1..])[12..]);
+    Ok(address)
+}
+
+/// Keccak-256 of `bytes`
+pub(crate) fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    Keccak256::digest(bytes).into()
+}
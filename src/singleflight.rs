@@ -0,0 +1,183 @@
+//! Deduplication of identical in-flight requests
+//!
+//! Relayers frequently ask for the same proof from several tasks at once. A
+//! [`SingleFlight`] lets the first caller for a key run the work while later callers
+//! with the same key wait for it and share its result. If the leading call fails or
+//! is dropped, the waiting callers do not inherit its error, which may be specific
+//! to the leader's own timeout or cancellation; one of them runs the work again.
+//! A waiting caller whose own token is cancelled stops waiting with
+//! [`ZkError::Cancelled`].
+
+use std::collections::HashMap;
+use std::future::Future;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use tokio::sync::watch;
+
+use crate::error::ZkError;
+use crate::options::CancellationToken;
+
+/// Key identifying identical requests
+pub type FlightKey = [u8; 32];
+
+/// In-flight calls by key
+pub struct SingleFlight<V> {
+    calls: Mutex<HashMap<FlightKey, watch::Receiver<Option<V>>>>,
+}
+
+impl<V: Clone> SingleFlight<V> {
+    /// Create an empty set of in-flight calls
+    pub fn new() -> Self {
+        Self {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `work` unless a call with the same key is in flight, in which case wait
+    /// for that call and share its successful result
+    pub async fn run<F, Fut>(&self, key: FlightKey, cancel: &CancellationToken, work: F) -> Result<V, ZkError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, ZkError>>,
+    {
+        let sender = loop {
+            let leader = {
+                let mut calls = self.calls.lock();
+                match calls.get(&key) {
+                    Some(receiver) => Err(receiver.clone()),
+                    None => {
+                        let (sender, receiver) = watch::channel(None);
+                        calls.insert(key, receiver);
+                        Ok(sender)
+                    }
+                }
+            };
+            match leader {
+                Ok(sender) => break sender,
+                Err(mut receiver) => {
+                    let shared = tokio::select! {
+                        // Fails once the leader finished without a result
+                        shared = receiver.wait_for(Option::is_some) => shared.ok().and_then(|value| value.clone()),
+                        _ = cancel.cancelled() => return Err(ZkError::Cancelled),
+                    };
+                    if let Some(value) = shared {
+                        return Ok(value);
+                    }
+                }
+            }
+        };
+
+        let flight = Flight { calls: &self.calls, key, sender };
+        let result = work().await;
+        if let Ok(value) = &result {
+            flight.sender.send_replace(Some(value.clone()));
+        }
+        result
+    }
+
+    /// Number of distinct calls in flight
+    pub fn in_flight(&self) -> usize {
+        self.calls.lock().len()
+    }
+}
+
+impl<V: Clone> Default for SingleFlight<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> std::fmt::Debug for SingleFlight<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SingleFlight")
+            .field("in_flight", &self.calls.lock().len())
+            .finish()
+    }
+}
+
+/// Leading call, unregistered when it finishes or is dropped
+struct Flight<'a, V> {
+    calls: &'a Mutex<HashMap<FlightKey, watch::Receiver<Option<V>>>>,
+    key: FlightKey,
+    sender: watch::Sender<Option<V>>,
+}
+
+impl<V> Drop for Flight<'_, V> {
+    fn drop(&mut self) {
+        self.calls.lock().remove(&self.key);
+    }
+}
+
+/// Key of a request made up of `parts`
+pub fn flight_key(parts: &[&[u8]]) -> FlightKey {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        // Length prefixes keep differently split parts apart
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_duplicates_share_one_call() {
+        let flights = Arc::new(SingleFlight::<u32>::new());
+        let runs = Arc::new(AtomicUsize::new(0));
+        let key = flight_key(&[b"program", b"input"]);
+
+        let calls = (0..4).map(|_| {
+            let (flights, runs) = (flights.clone(), runs.clone());
+            tokio::spawn(async move {
+                flights.run(key, &CancellationToken::new(), || async {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Ok(7)
+                }).await
+            })
+        });
+        for result in futures::future::join_all(calls).await {
+            assert_eq!(result.unwrap().unwrap(), 7);
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(flights.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failure_is_not_shared() {
+        let flights = Arc::new(SingleFlight::<u32>::new());
+        let key = flight_key(&[b"program"]);
+
+        let leader = {
+            let flights = flights.clone();
+            tokio::spawn(async move {
+                flights.run(key, &CancellationToken::new(), || async {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Err(ZkError::Timeout(Duration::from_millis(50)))
+                }).await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // A cancelled follower stops waiting
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let cancelled = flights.run(key, &cancel, || async { Ok(1) }).await;
+        assert!(matches!(cancelled, Err(ZkError::Cancelled)));
+
+        let follower = flights.run(key, &CancellationToken::new(), || async { Ok(3) }).await;
+        assert!(matches!(leader.await.unwrap(), Err(ZkError::Timeout(_))));
+        assert_eq!(follower.unwrap(), 3);
+    }
+
+    #[test]
+    fn test_key_separates_parts() {
+        assert_ne!(flight_key(&[b"ab", b"c"]), flight_key(&[b"a", b"bc"]));
+    }
+}
@@ -0,0 +1,191 @@
+//! Solana verifier program generation
+//!
+//! Solana deployments verify Groth16-wrapped SP1 proofs in an on-chain program built
+//! on `sp1-solana`. [`SolanaVerifierExport`] generates that program for a set of
+//! programs, and [`SolanaProof`] is the borsh-encoded instruction data it accepts,
+//! built from the output of this crate.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::ZkError;
+use crate::ink::byte_array;
+use crate::vk::{ExportedKey, KeyKind};
+
+/// Version of `solana-program` the generated program depends on
+const SOLANA_PROGRAM_VERSION: &str = "2.0";
+
+/// Version of `sp1-solana` the generated program depends on
+const SP1_SOLANA_VERSION: &str = "0.1";
+
+/// Instruction data of the generated verifier program
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SolanaProof {
+    /// Verifying key hash of the program that was proven
+    pub vkey_hash: [u8; 32],
+    /// Groth16 proof in its on-chain encoding
+    pub proof: Vec<u8>,
+    /// Public values committed by the guest
+    pub public_values: Vec<u8>,
+}
+
+impl SolanaProof {
+    /// Instruction data for a proof of the program `key` was exported for
+    ///
+    /// `proof` is the on-chain encoding returned by `Sp1Backend::onchain_proof`.
+    pub fn new(key: &ExportedKey, proof: Vec<u8>, public_values: Vec<u8>) -> Result<Self, ZkError> {
+        if key.kind != KeyKind::Sp1VerifyingKey {
+            return Err(ZkError::InvalidInput("Solana verifiers require an SP1 verifying key".into()));
+        }
+        Ok(Self {
+            vkey_hash: key.key_hash,
+            proof,
+            public_values,
+        })
+    }
+
+    /// Encode as instruction data
+    pub fn to_bytes(&self) -> Vec<u8> {
+        borsh::to_vec(self).expect("borsh encoding into a vector cannot fail")
+    }
+
+    /// Decode from instruction data
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        borsh::from_slice(bytes)
+            .map_err(|e| ZkError::InvalidInput(format!("Failed to decode Solana proof: {}", e)))
+    }
+}
+
+/// Generator of a Solana verifier program
+#[derive(Debug, Clone)]
+pub struct SolanaVerifierExport {
+    /// Program crate name
+    name: String,
+    /// Program names and verifying key hashes accepted by the program
+    programs: Vec<(String, [u8; 32])>,
+}
+
+impl SolanaVerifierExport {
+    /// Create an export without any accepted programs
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            programs: Vec::new(),
+        }
+    }
+
+    /// Accept proofs of the program the SP1 verifying key was exported for
+    pub fn with_program(mut self, name: impl Into<String>, key: &ExportedKey) -> Result<Self, ZkError> {
+        if key.kind != KeyKind::Sp1VerifyingKey {
+            return Err(ZkError::InvalidInput("Solana verifiers require an SP1 verifying key".into()));
+        }
+        self.programs.push((name.into(), key.key_hash));
+        Ok(self)
+    }
+
+    /// Source of the program's `lib.rs`
+    pub fn program_source(&self) -> String {
+        let mut source = String::new();
+        source.push_str("//! Frostgate proof verifier generated by frostgate-circuits\n\n");
+        source.push_str("use borsh::BorshDeserialize;\n");
+        source.push_str("use solana_program::{\n");
+        source.push_str("    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult,\n");
+        source.push_str("    program_error::ProgramError, pubkey::Pubkey,\n");
+        source.push_str("};\n");
+        source.push_str("use sp1_solana::{verify_proof, GROTH16_VK_5_0_0_BYTES};\n\n");
+
+        source.push_str("/// Verifying key hashes of the accepted programs\n");
+        let _ = writeln!(source, "pub const PROGRAMS: [[u8; 32]; {}] = [", self.programs.len());
+        for (name, hash) in &self.programs {
+            let _ = writeln!(source, "    // {}", name);
+            let _ = writeln!(source, "    {},", byte_array(hash));
+        }
+        source.push_str("];\n\n");
+
+        source.push_str("/// Instruction data, as encoded by `frostgate_circuits::solana::SolanaProof`\n");
+        source.push_str("#[derive(BorshDeserialize)]\n");
+        source.push_str("pub struct SolanaProof {\n");
+        source.push_str("    pub vkey_hash: [u8; 32],\n");
+        source.push_str("    pub proof: Vec<u8>,\n");
+        source.push_str("    pub public_values: Vec<u8>,\n");
+        source.push_str("}\n\n");
+
+        source.push_str("entrypoint!(process_instruction);\n\n");
+        source.push_str(
+            "pub fn process_instruction(\n\
+             \x20   _program_id: &Pubkey,\n\
+             \x20   _accounts: &[AccountInfo],\n\
+             \x20   instruction_data: &[u8],\n\
+             ) -> ProgramResult {\n\
+             \x20   let proof = SolanaProof::try_from_slice(instruction_data)\n\
+             \x20       .map_err(|_| ProgramError::InvalidInstructionData)?;\n\
+             \x20   if !PROGRAMS.contains(&proof.vkey_hash) {\n\
+             \x20       return Err(ProgramError::InvalidArgument);\n\
+             \x20   }\n\
+             \x20   let vkey_hash = format!(\"0x{}\", hex::encode(proof.vkey_hash));\n\
+             \x20   verify_proof(&proof.proof, &proof.public_values, &vkey_hash, &GROTH16_VK_5_0_0_BYTES)\n\
+             \x20       .map_err(|_| ProgramError::InvalidInstructionData)\n\
+             }\n",
+        );
+        source
+    }
+
+    /// Program `Cargo.toml`
+    pub fn cargo_manifest(&self) -> String {
+        format!(
+            "[package]\n\
+             name = \"{name}\"\n\
+             version = \"0.1.0\"\n\
+             edition = \"2021\"\n\n\
+             [dependencies]\n\
+             borsh = {{ version = \"1.5\", features = [\"derive\"] }}\n\
+             hex = \"0.4\"\n\
+             solana-program = \"{solana}\"\n\
+             sp1-solana = \"{sp1}\"\n\n\
+             [lib]\n\
+             crate-type = [\"cdylib\", \"lib\"]\n\
+             path = \"lib.rs\"\n",
+            name = self.name,
+            solana = SOLANA_PROGRAM_VERSION,
+            sp1 = SP1_SOLANA_VERSION,
+        )
+    }
+
+    /// Write the program crate into `dir`
+    pub fn write(&self, dir: impl AsRef<Path>) -> Result<(), ZkError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join("Cargo.toml"), self.cargo_manifest())?;
+        std::fs::write(dir.join("lib.rs"), self.program_source())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof_round_trip() {
+        let key = ExportedKey::new(KeyKind::Sp1VerifyingKey, b"program", crate::hasher::HashFunction::Sha256, [7; 32], vec![1, 2, 3]);
+        let proof = SolanaProof::new(&key, vec![0xAA; 260], vec![0xBB; 48]).unwrap();
+        assert_eq!(SolanaProof::from_bytes(&proof.to_bytes()).unwrap(), proof);
+        assert!(SolanaProof::from_bytes(&[1, 2, 3]).is_err());
+
+        let risc0_key = ExportedKey::new(KeyKind::Risc0ImageId, b"program", crate::hasher::HashFunction::Sha256, [0; 32], vec![0; 32]);
+        assert!(SolanaProof::new(&risc0_key, vec![], vec![]).is_err());
+    }
+
+    #[test]
+    fn test_program_embeds_keys() {
+        let key = ExportedKey::new(KeyKind::Sp1VerifyingKey, b"program", crate::hasher::HashFunction::Sha256, [7; 32], vec![1, 2, 3]);
+        let export = SolanaVerifierExport::new("frostgate-verifier")
+            .with_program("message_verify", &key)
+            .unwrap();
+        let source = export.program_source();
+        assert!(source.contains("pub const PROGRAMS: [[u8; 32]; 1]"));
+        assert!(source.contains(&byte_array(&[7; 32])));
+        assert!(export.cargo_manifest().contains("sp1-solana"));
+    }
+}
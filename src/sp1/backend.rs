@@ -3,26 +3,61 @@
 
 //! SP1 backend implementation
 
+use std::borrow::Cow;
 use std::sync::Arc;
+use std::collections::HashMap;
 use std::time::{Duration, SystemTime};
 use async_trait::async_trait;
 use sp1_sdk::{
-    ProverClient, SP1Stdin, SP1ProofWithPublicValues, CpuProver, SP1ProvingKey,
-    SP1VerifyingKey, Prover,
+    ProverClient, SP1Stdin, SP1Proof, SP1ProofWithPublicValues, CpuProver, SP1ProvingKey,
+    SP1VerifyingKey, Prover, HashableKey,
 };
+use risc0_zkvm::{InnerReceipt, Receipt};
+use risc0_zkvm::sha::Digest;
 use tokio::sync::RwLock;
+use tokio::sync::mpsc::UnboundedSender;
 use rayon::prelude::*;
 use frostgate_zkip::{
     ZkBackend, ZkBackendExt, ZkError, ZkResult,
     HealthStatus, ProofMetadata, ResourceUsage, ZkConfig, ZkStats,
 };
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use futures::stream::{FuturesUnordered, Stream};
 use futures::TryFutureExt;
+use tracing::Instrument;
 
-use super::types::{Sp1Circuit, Sp1Options};
+use super::types::{Sp1Circuit, Sp1Options, Sp1VerificationResult};
 use super::circuit::MessageVerifyCircuit;
 use super::cache::{CircuitCache, CacheConfig, CacheStats};
+use super::executor::ProvingExecutor;
+use super::verifier;
+use super::convert;
+use crate::descriptor::{self, ProgramDescriptor};
+use crate::domain::DomainSeparator;
+use crate::events::{BackendEvent, CacheKind, EventBus};
+use crate::guest::{GuestProgram, GuestPrograms};
+use crate::manifest::ProgramManifest;
+use crate::execution::{self, ExecutionReport};
+use crate::envelope::{self, ProofEnvelope};
+use crate::metadata::{ExtendedProofMetadata, ProofMode, ProvingRun};
+use crate::options::{bounded, CancellationToken, ProveOptions};
+use crate::hasher::HashFunction;
+use crate::profiles::{ProfileRegistry, MESSAGE_VERIFY, USER_PROGRAM};
+use crate::programs::{self, ProgramLease, ProgramRegistry};
+use crate::progress::{Progress, ProveProgress};
+use crate::probe::{HealthProbe, ProbeMode, ProbePolicy, ProbeResult};
+use crate::refresh::{RefreshPolicy, RefreshTracker};
+use crate::replay::ReplayGuard;
+use crate::singleflight::{flight_key, SingleFlight};
+use crate::sysmon;
+use crate::threads;
+use crate::stats::{FailureCategory, FailureStats, LatencyStats, ProgramStatsTracker, StatsSnapshot, DEFAULT_LATENCY_WINDOW};
+use crate::dashboard::BackendDashboard;
+use crate::info::BackendInfo;
+use crate::support::SupportBundle;
+use crate::vk::{ExportedKey, KeyKind};
+use crate::error::ZkError as CustomZkError;
 
 // Create a newtype wrapper for CpuProver to implement Debug
 pub struct DebugCpuProver(CpuProver);
@@ -56,50 +91,458 @@ pub struct Sp1Backend {
     pub cache: Arc<CircuitCache>,
     /// SP1 prover client
     #[allow(dead_code)]
-    pub client: DebugCpuProver,
+    pub client: Arc<DebugCpuProver>,
+    /// Blocking executor for prover work
+    pub executor: ProvingExecutor,
+    /// Backend event bus
+    pub events: EventBus,
+    /// Last reported health status
+    pub last_health: Arc<RwLock<Option<String>>>,
+    /// Per-circuit configuration profiles
+    pub profiles: Arc<ProfileRegistry>,
+    /// Failure counters per category
+    pub failures: Arc<FailureStats>,
+    /// Per-program proving statistics
+    pub program_stats: Arc<ProgramStatsTracker>,
+    /// Proving and verification latency histograms
+    pub latency: Arc<LatencyStats>,
+    /// Tracker of proofs to refresh before they expire, if enabled
+    pub refresh: Option<Arc<RefreshTracker>>,
+    /// Health probe consulted by `health_check()`, if enabled
+    pub probe: Option<Arc<HealthProbe>>,
+    /// Proving runs shared by identical concurrent requests
+    pub in_flight: Arc<SingleFlight<(Vec<u8>, ExtendedProofMetadata)>>,
+    /// Guest verifying other proofs, used by [`Sp1Backend::aggregate_proofs`]
+    pub aggregation_program: Option<Arc<Vec<u8>>>,
+    /// Guest verifying RISC0 receipts, used by [`Sp1Backend::bridge_risc0_receipt`]
+    pub bridge_program: Option<Arc<Vec<u8>>>,
+    /// Guest programs registered by applications
+    pub programs: Arc<GuestPrograms>,
+    /// Guest ELFs of circuit types loaded from a program manifest
+    pub circuit_elfs: Arc<parking_lot::RwLock<HashMap<u8, Arc<[u8]>>>>,
+    /// Versions of circuit type guests upgraded at runtime, if enabled
+    pub versions: Option<Arc<ProgramRegistry>>,
+    /// Nonces of verified messages, rejecting replayed proofs if set
+    pub replay_guard: Option<Arc<ReplayGuard>>,
+    /// File the statistics are persisted to, if enabled
+    pub stats_path: Option<PathBuf>,
 }
 
 impl Sp1Backend {
     /// Create a new SP1 backend with default configuration
     pub fn new() -> Self {
+        let resources = Arc::new(RwLock::new(ResourceUsage {
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            active_tasks: 0,
+            max_concurrent: 4,
+            queue_depth: 0,
+        }));
         Self {
             stats: Arc::new(RwLock::new(ZkStats::default())),
-            resources: Arc::new(RwLock::new(ResourceUsage {
-                cpu_usage: 0.0,
-                memory_usage: 0,
-                active_tasks: 0,
-                max_concurrent: 4,
-                queue_depth: 0,
-            })),
+            executor: ProvingExecutor::new(4, resources.clone()).with_threads_per_task(4),
+            resources,
             options: Sp1Options {
                 num_threads: Some(4),
                 memory_limit: Some(1024 * 1024 * 1024), // 1GB
                 custom_params: None,
+                proof_mode: ProofMode::Core,
+                max_proof_size: None,
+                prove_timeout: None,
+                max_batch_parallelism: None,
+                latency_window: None,
             },
             cache: Arc::new(CircuitCache::new(CacheConfig::default())),
-            client: DebugCpuProver::new(),
+            client: Arc::new(DebugCpuProver::new()),
+            events: EventBus::default(),
+            last_health: Arc::new(RwLock::new(None)),
+            profiles: Arc::new(ProfileRegistry::default()),
+            failures: Arc::new(FailureStats::new()),
+            program_stats: Arc::new(ProgramStatsTracker::new()),
+            latency: Arc::new(LatencyStats::new()),
+            refresh: None,
+            probe: None,
+            in_flight: Arc::new(SingleFlight::new()),
+            aggregation_program: None,
+            bridge_program: None,
+            programs: Arc::new(GuestPrograms::new()),
+            circuit_elfs: Arc::new(parking_lot::RwLock::new(HashMap::new())),
+            versions: None,
+            replay_guard: None,
+            stats_path: None,
         }
     }
 
     /// Create a new SP1 backend with custom configuration
     pub fn with_config(options: Sp1Options, cache_config: CacheConfig) -> Self {
+        let latency_window = options.latency_window.unwrap_or(DEFAULT_LATENCY_WINDOW);
+        let resources = Arc::new(RwLock::new(ResourceUsage {
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            active_tasks: 0,
+            max_concurrent: options.num_threads.unwrap_or(4),
+            queue_depth: 0,
+        }));
         Self {
             stats: Arc::new(RwLock::new(ZkStats::default())),
-            resources: Arc::new(RwLock::new(ResourceUsage {
-                cpu_usage: 0.0,
-                memory_usage: 0,
-                active_tasks: 0,
-                max_concurrent: options.num_threads.unwrap_or(4),
-                queue_depth: 0,
-            })),
+            executor: ProvingExecutor::new(options.num_threads.unwrap_or(4), resources.clone())
+                .with_threads_per_task(options.num_threads.unwrap_or(4)),
+            resources,
             options,
             cache: Arc::new(CircuitCache::new(cache_config)),
-            client: DebugCpuProver::new(),
+            client: Arc::new(DebugCpuProver::new()),
+            events: EventBus::default(),
+            last_health: Arc::new(RwLock::new(None)),
+            profiles: Arc::new(ProfileRegistry::default()),
+            failures: Arc::new(FailureStats::new()),
+            program_stats: Arc::new(ProgramStatsTracker::new()),
+            latency: Arc::new(LatencyStats::with_window(latency_window)),
+            refresh: None,
+            probe: None,
+            in_flight: Arc::new(SingleFlight::new()),
+            aggregation_program: None,
+            bridge_program: None,
+            programs: Arc::new(GuestPrograms::new()),
+            circuit_elfs: Arc::new(parking_lot::RwLock::new(HashMap::new())),
+            versions: None,
+            replay_guard: None,
+            stats_path: None,
+        }
+    }
+
+    /// Refresh cached proofs of frequently requested inputs before they expire
+    ///
+    /// Refreshing happens in the task started by [`Sp1Backend::spawn_cache_refresh`].
+    pub fn with_refresh_policy(mut self, policy: RefreshPolicy) -> Self {
+        self.refresh = Some(Arc::new(RefreshTracker::new(policy)));
+        self
+    }
+
+    /// Probe the prover toolchain by running a tiny circuit
+    ///
+    /// Probing happens in the task started by [`Sp1Backend::spawn_health_probe`], and
+    /// [`ZkBackend::health_check`] reports failed probes.
+    pub fn with_health_probe(mut self, policy: ProbePolicy) -> Self {
+        self.probe = Some(Arc::new(HealthProbe::new(policy)));
+        self
+    }
+
+    /// Reject proofs of messages whose nonce `guard` already accepted
+    ///
+    /// Every successful verification, single or batched, of a message proof records
+    /// the nonces it commits in the guard; verifying a proof of the same message
+    /// again fails. Share one guard between all backends verifying for a bridge.
+    pub fn with_replay_guard(mut self, guard: Arc<ReplayGuard>) -> Self {
+        self.replay_guard = Some(guard);
+        self
+    }
+
+    /// Set the guest program [`Sp1Backend::aggregate_proofs`] proves with
+    ///
+    /// This is the ELF built from `circuits/sp1/proof_aggregate.rs`.
+    pub fn with_aggregation_program(mut self, elf: Vec<u8>) -> Self {
+        self.aggregation_program = Some(Arc::new(elf));
+        self
+    }
+
+    /// Set the guest program [`Sp1Backend::bridge_risc0_receipt`] proves with
+    ///
+    /// This is the ELF built from `circuits/sp1/risc0_bridge.rs`.
+    pub fn with_bridge_program(mut self, elf: Vec<u8>) -> Self {
+        self.bridge_program = Some(Arc::new(elf));
+        self
+    }
+
+    /// Register a guest program compiled by the application
+    ///
+    /// Returns the program bytes to prove and verify it with through the standard
+    /// API. A proof only verifies if `journal_validator` accepts its public values.
+    pub fn register_program(
+        &self,
+        name: &str,
+        elf_bytes: Vec<u8>,
+        journal_validator: impl Fn(&[u8]) -> bool + Send + Sync + 'static,
+    ) -> Result<Vec<u8>, CustomZkError> {
+        let program = self.programs.register(name, elf_bytes, Arc::new(journal_validator))?;
+        Ok(program.program())
+    }
+
+    /// Register the SP1 programs of a manifest
+    ///
+    /// Descriptor-encoded programs of a listed circuit type that embed no ELF then run
+    /// the listed one. Every ELF is read and checked against its verifying key hash up
+    /// front. Returns the number of programs loaded.
+    pub fn load_manifest(&self, manifest: &ProgramManifest) -> Result<usize, CustomZkError> {
+        let mut loaded = 0;
+        for program in manifest.for_backend("sp1") {
+            let elf = program.read_elf()?;
+            if let Some(expected) = program.image_id {
+                let found = self.setup_keys(&elf).1.bytes32_raw();
+                if found != expected {
+                    return Err(CustomZkError::ImageMismatch {
+                        expected: hex::encode(expected),
+                        found: hex::encode(found),
+                    });
+                }
+            }
+            self.circuit_elfs.write().insert(program.circuit_type, elf.into());
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Run circuit types with the guest versions registered in `registry`
+    ///
+    /// Programs of a circuit type with a version registered under
+    /// [`programs::circuit_program_name`] run its active version in place of the
+    /// manifest ELF, unless they embed or pin their own guest, and proofs of
+    /// superseded versions verify until their overlap window ends. Registered ELFs are
+    /// checked against their verifying key hash.
+    pub fn with_program_versions(mut self, registry: ProgramRegistry) -> Self {
+        let client = self.client.clone();
+        let registry = registry.with_image_id(Arc::new(move |elf: &[u8]| {
+            Ok(client.inner().setup(elf).1.bytes32_raw())
+        }));
+        self.versions = Some(Arc::new(registry));
+        self
+    }
+
+    /// Get the registry of guest versions, if enabled
+    pub fn program_versions(&self) -> Option<&ProgramRegistry> {
+        self.versions.as_deref()
+    }
+
+    /// Guest ELF a program runs, with the application program it refers to
+    ///
+    /// Descriptor-encoded programs run their embedded ELF, the registered ELF of an
+    /// application program, the active registered version of their circuit type's
+    /// guest or the manifest ELF of their circuit type, the latter three shared
+    /// rather than copied. Other programs are the ELF itself. The verifying key
+    /// hash a descriptor pins is checked once the keys are set up, see
+    /// [`GuestElf::check`].
+    fn guest_elf(&self, program: &[u8]) -> Result<(GuestElf, Option<Arc<GuestProgram>>), CustomZkError> {
+        if !descriptor::is_encoded(program) {
+            return Ok((GuestElf::raw(program), None));
+        }
+        let descriptor = ProgramDescriptor::decode(program)?;
+        let (elf, guest, lease) = if descriptor.circuit_type == USER_PROGRAM {
+            let guest = self.programs.for_descriptor(&descriptor)?;
+            (guest.elf.clone(), Some(guest), None)
+        } else if let Some(lease) = self.version_lease(&descriptor)? {
+            (lease.version().elf.clone(), None, Some(Arc::new(lease)))
+        } else {
+            let elf = match descriptor.elf {
+                Some(elf) => Arc::from(elf),
+                None => self.circuit_elfs.read().get(&descriptor.circuit_type).cloned()
+                    .ok_or_else(|| CustomZkError::InvalidProgram(format!(
+                        "No SP1 guest for circuit 0x{:02x}: embed its ELF or list it in the program manifest",
+                        descriptor.circuit_type
+                    )))?,
+            };
+            (elf, None, None)
+        };
+        Ok((GuestElf { elf, pinned: descriptor.image_id, _lease: lease }, guest))
+    }
+
+    /// Pin the active registered version of the guest a program runs
+    ///
+    /// Programs embedding or pinning their own guest are never routed to a version.
+    fn version_lease(&self, descriptor: &ProgramDescriptor) -> Result<Option<ProgramLease>, CustomZkError> {
+        let (Some(versions), Some(name)) = (&self.versions, programs::circuit_program_name(descriptor.circuit_type)) else {
+            return Ok(None);
+        };
+        if descriptor.elf.is_some() || descriptor.image_id.is_some() {
+            return Ok(None);
+        }
+        match versions.lease(name) {
+            Ok(lease) => Ok(Some(lease)),
+            Err(CustomZkError::UnknownProgram(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Guests of the superseded versions a proof of `program` still verifies against
+    fn superseded_guests(&self, program: &[u8]) -> Vec<GuestElf> {
+        let descriptor = match descriptor::is_encoded(program).then(|| ProgramDescriptor::decode(program)) {
+            Some(Ok(descriptor)) => descriptor,
+            _ => return Vec::new(),
+        };
+        let (versions, name) = match (&self.versions, programs::circuit_program_name(descriptor.circuit_type)) {
+            (Some(versions), Some(name)) if descriptor.elf.is_none() && descriptor.image_id.is_none() => (versions, name),
+            _ => return Vec::new(),
+        };
+        versions.verifiable(name)
+            .into_iter()
+            .skip(1)
+            .map(|version| GuestElf { elf: version.elf, pinned: None, _lease: None })
+            .collect()
+    }
+
+    /// Get the per-circuit profile registry
+    pub fn profiles(&self) -> &ProfileRegistry {
+        &self.profiles
+    }
+
+    /// Persist the statistics to `path`, restoring those a previous run saved there
+    ///
+    /// Statistics are saved by [`Sp1Backend::persist_stats`] and periodically by the
+    /// task started by [`Sp1Backend::spawn_stats_persistence`]. An unreadable file is
+    /// ignored and overwritten.
+    pub fn with_stats_persistence(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        match StatsSnapshot::load(&path) {
+            Ok(Some(snapshot)) => {
+                // The backend is not shared yet, so the counters are replaced without
+                // waiting for their lock
+                self.stats = Arc::new(RwLock::new(snapshot.zk_stats()));
+                self.restore_trackers(&snapshot);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(path = %path.display(), "discarding unreadable stats: {}", e),
+        }
+        self.stats_path = Some(path);
+        self
+    }
+
+    /// Hash of a program under the hash function of its circuit profile
+    pub fn program_hash(&self, program: &[u8]) -> String {
+        self.profiles.program_hash(program)
+    }
+
+    /// Get the per-program proving statistics
+    pub fn program_stats(&self) -> Arc<ProgramStatsTracker> {
+        self.program_stats.clone()
+    }
+
+    /// Get the proving and verification latency histograms
+    pub fn latency_stats(&self) -> Arc<LatencyStats> {
+        self.latency.clone()
+    }
+
+    /// Get failure counts per category
+    pub fn failure_stats(&self) -> HashMap<FailureCategory, u64> {
+        self.failures.snapshot()
+    }
+
+    /// Subscribe to backend events
+    pub fn events(&self) -> impl futures::Stream<Item = BackendEvent> + Send + 'static {
+        self.events.subscribe()
+    }
+
+    /// Get the SDK, toolchain and circuit versions of this backend
+    pub fn info(&self) -> BackendInfo {
+        BackendInfo::sp1()
+    }
+
+    /// Write a tarball of diagnostics for bug reports
+    pub async fn export_support_bundle(&self, path: impl AsRef<Path>) -> Result<(), CustomZkError> {
+        self.support_bundle().await?.write_async(path.as_ref().to_path_buf()).await
+    }
+
+    /// Collect the diagnostics of [`Self::export_support_bundle`] without writing them
+    ///
+    /// Services running a [`crate::jobs::JobManager`] can add its failed jobs
+    /// before writing the bundle.
+    pub async fn support_bundle(&self) -> Result<SupportBundle, CustomZkError> {
+        let mut bundle = SupportBundle::new();
+        bundle.add_json("version.json", &self.info())?;
+        bundle.add_json("diagnostics.json", &self.dashboard().await)?;
+        bundle.add_json("events.json", &self.events.recent())?;
+        bundle.add_failures("failures.json", &self.failures)?;
+        bundle.add_config("config.json", &self.options)?;
+        let elfs = self.circuit_elfs.read().clone();
+        bundle.add_artifacts(
+            "artifacts.json",
+            elfs.iter().map(|(circuit_type, elf)| (format!("circuit-{}.elf", circuit_type), &elf[..])),
+        )?;
+        Ok(bundle)
+    }
+
+    /// Build a snapshot of the backend's operational state
+    pub async fn dashboard(&self) -> BackendDashboard {
+        let health = self.health_check().await;
+        let stats = self.stats.read().await.clone();
+        let resources = self.resources.read().await.clone();
+        BackendDashboard::new(
+            "sp1",
+            &health,
+            &stats,
+            &self.latency,
+            self.program_stats.snapshot(),
+            self.cache.stats().into(),
+            &resources,
+            self.failures.snapshot(),
+            self.failures.recent(),
+        )
+    }
+
+    /// Copy of all statistics of the backend
+    pub async fn stats_snapshot(&self) -> StatsSnapshot {
+        let stats = self.stats.read().await;
+        StatsSnapshot::new(&stats, &self.program_stats, &self.failures, &self.latency)
+    }
+
+    /// Reset all statistics, returning those of the interval that just ended
+    pub async fn reset_stats(&self) -> StatsSnapshot {
+        let mut stats = self.stats.write().await;
+        let snapshot = StatsSnapshot::new(&stats, &self.program_stats, &self.failures, &self.latency);
+        *stats = ZkStats::default();
+        self.program_stats.reset();
+        self.failures.reset();
+        self.latency.reset();
+        snapshot
+    }
+
+    /// Replace all statistics with those of `snapshot`
+    pub async fn restore_stats(&self, snapshot: &StatsSnapshot) {
+        *self.stats.write().await = snapshot.zk_stats();
+        self.restore_trackers(snapshot);
+    }
+
+    /// Replace the per-program, failure and latency statistics with those of `snapshot`
+    fn restore_trackers(&self, snapshot: &StatsSnapshot) {
+        self.program_stats.restore(snapshot.programs.clone());
+        self.failures.restore(snapshot.failure_counts.clone(), snapshot.recent_failures.clone());
+        self.latency.restore(&snapshot.proving_latency, &snapshot.verification_latency);
+    }
+
+    /// Save the statistics if persistence is enabled
+    pub async fn persist_stats(&self) -> Result<(), CustomZkError> {
+        match &self.stats_path {
+            Some(path) => self.stats_snapshot().await.save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Spawn the background task saving the statistics every `interval`
+    ///
+    /// Returns `None` if stats persistence is not enabled.
+    pub fn spawn_stats_persistence(self: Arc<Self>, interval: Duration) -> Option<tokio::task::JoinHandle<()>> {
+        let path = self.stats_path.clone()?;
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.persist_stats().await {
+                    tracing::warn!(path = %path.display(), "failed to persist stats: {}", e);
+                }
+            }
+        }))
+    }
+
+    /// Publish a cache eviction event if an entry was evicted
+    fn publish_eviction(&self, cache: CacheKind, evicted: Option<[u8; 32]>) {
+        if let Some(key) = evicted {
+            self.events.publish(BackendEvent::CacheEvicted {
+                cache,
+                key: hex::encode(key),
+            });
         }
     }
 
     /// Update statistics after a proving operation
     async fn update_proving_stats(&self, duration: Duration, success: bool) {
+        self.latency.record_proving(duration);
         let mut stats = self.stats.write().await;
         stats.total_proofs += 1;
         if !success {
@@ -116,6 +559,7 @@ impl Sp1Backend {
 
     /// Update statistics after a verification operation
     async fn update_verification_stats(&self, duration: Duration, success: bool) {
+        self.latency.record_verification(duration);
         let mut stats = self.stats.write().await;
         stats.total_verifications += 1;
         if !success {
@@ -137,112 +581,1010 @@ impl Sp1Backend {
             return Err(frostgate_zkip::ZkError::Program("Unsupported circuit type".into()));
         }
         
-        // Extract expected hash from program
+        // Extract expected hash and, unless SHA-256, the hash function from program
         if program.len() < 33 {
             return Err(frostgate_zkip::ZkError::Program("Invalid program format".into()));
         }
         let expected_hash: [u8; 32] = program[1..33].try_into()
             .map_err(|_| frostgate_zkip::ZkError::Program("Invalid hash format".into()))?;
+        let hash_function = match program.get(33) {
+            Some(id) => HashFunction::from_message_hash_id(*id)
+                .ok_or_else(|| frostgate_zkip::ZkError::Program("Unknown message hash function".into()))?,
+            None => HashFunction::Sha256,
+        };
         
         // Create circuit
         MessageVerifyCircuit::new(input.to_vec(), expected_hash)
+            .and_then(|circuit| circuit.with_hash_function(hash_function))
             .map_err(|e| frostgate_zkip::ZkError::Program(e.to_string()))
     }
 
-    async fn prove_internal(&self, program: &[u8], input: &[u8]) -> ZkResult<Vec<u8>> {
-        // Create stdin and write input
-        let mut stdin = SP1Stdin::new();
-        stdin.write_slice(input);
-        
-        // Create proving key and verifying key
+    /// Get the proving and verifying keys of a program, running `setup` on a cache miss
+    fn setup_keys(&self, program: &[u8]) -> (Arc<SP1ProvingKey>, Arc<SP1VerifyingKey>) {
+        if let Some(keys) = self.cache.get_keys(program) {
+            return keys;
+        }
+        let _span = tracing::info_span!("setup").entered();
+        let start = SystemTime::now();
         let (proving_key, verifying_key) = self.client.inner().setup(program);
-        
-        // Generate proof
-        let proof = self.client.inner().prove(&proving_key, &stdin)
-            .run()
-            .map_err(|e| ZkError::Backend(format!("Proof generation failed: {}", e)))?;
-        
-        Ok(proof.bytes().to_vec())
+        let (proving_key, verifying_key) = (Arc::new(proving_key), Arc::new(verifying_key));
+        let evicted = self.cache.store_keys(
+            program,
+            proving_key.clone(),
+            verifying_key.clone(),
+            start.elapsed().unwrap_or_default(),
+        );
+        self.publish_eviction(CacheKind::Keys, evicted);
+        (proving_key, verifying_key)
     }
 
-    async fn verify_internal(&self, program: &[u8], proof: &[u8]) -> ZkResult<bool> {
-        // Create proving key and verifying key
-        let (proving_key, verifying_key) = self.client.inner().setup(program);
-        
-        // Parse proof - create a temporary file since load requires a path
-        let temp_dir = std::env::temp_dir();
-        let temp_path = temp_dir.join("proof.tmp");
-        std::fs::write(&temp_path, proof)
-            .map_err(|e| ZkError::Backend(format!("Failed to write proof to temp file: {}", e)))?;
-        
-        let proof = SP1ProofWithPublicValues::load(&temp_path)
-            .map_err(|e| ZkError::Backend(format!("Failed to parse proof: {}", e)))?;
+    /// Get the proving and verifying keys of a guest, checking the verifying key hash
+    /// it pins
+    ///
+    /// Unlike [`Sp1Backend::setup_keys`], `setup` runs on the proving executor on a
+    /// cache miss.
+    async fn guest_keys(&self, guest: &GuestElf) -> Result<(Arc<SP1ProvingKey>, Arc<SP1VerifyingKey>), CustomZkError> {
+        let (proving_key, verifying_key) = match self.cache.get_keys(&guest.elf) {
+            Some(keys) => keys,
+            None => {
+                let client = self.client.clone();
+                let elf = guest.elf.clone();
+                let (proving_key, verifying_key, setup_time) = self.executor.run(move || {
+                    let _span = tracing::info_span!("setup").entered();
+                    let start = SystemTime::now();
+                    let (proving_key, verifying_key) = client.inner().setup(&elf);
+                    Ok((Arc::new(proving_key), Arc::new(verifying_key), start.elapsed().unwrap_or_default()))
+                }).await?;
+                let evicted = self.cache.store_keys(&guest.elf, proving_key.clone(), verifying_key.clone(), setup_time);
+                self.publish_eviction(CacheKind::Keys, evicted);
+                (proving_key, verifying_key)
+            }
+        };
+        guest.check(&verifying_key)?;
+        Ok((proving_key, verifying_key))
+    }
+
+    async fn prove_internal(
+        &self,
+        guest: &GuestElf,
+        input: &[u8],
+        mode: ProofMode,
+        domain: DomainSeparator,
+        cancel: &CancellationToken,
+        progress: &Progress,
+    ) -> Result<ProvingRun, CustomZkError> {
+        let client = self.client.clone();
+        let cached_keys = self.cache.get_keys(&guest.elf);
+        let guest_elf = guest.clone();
+        let input = input.to_vec();
+        let progress = progress.clone();
         
-        // Clean up temp file
-        let _ = std::fs::remove_file(temp_path);
+        // Setup and proving are CPU-bound, keep them off the async executor
+        let (run, new_keys) = self.executor.run_cancellable(move || {
+            let program: &[u8] = &guest_elf.elf;
+            let stdin = stdin(&input, domain);
+            
+            // Reuse cached keys or run setup
+            let (proving_key, verifying_key, new_keys) = match cached_keys {
+                Some((proving_key, verifying_key)) => (proving_key, verifying_key, None),
+                None => tracing::info_span!("setup").in_scope(|| {
+                    let start = SystemTime::now();
+                    let (proving_key, verifying_key) = client.inner().setup(&program);
+                    let (proving_key, verifying_key) = (Arc::new(proving_key), Arc::new(verifying_key));
+                    let setup_time = start.elapsed().unwrap_or_default();
+                    (proving_key.clone(), verifying_key.clone(), Some((proving_key, verifying_key, setup_time)))
+                }),
+            };
+            guest_elf.check(&verifying_key)?;
+            progress.report(ProveProgress::SetupDone { cached: new_keys.is_none() });
+            let circuit_type = descriptor::circuit_type(&program).unwrap_or_default();
+            
+            // The prover reports nothing until it finishes, so execute separately to
+            // learn the cycle count. Execution is cheap next to proving.
+            let (_, report) = tracing::info_span!("execute")
+                .in_scope(|| client.inner().execute(&program, &stdin).run())
+                .map_err(|e| CustomZkError::from_prover_error(circuit_type, e))?;
+            let cycles = report.total_instruction_count();
+            progress.report(ProveProgress::Executed { cycles: Some(cycles), segments: None });
+            
+            // Generate proof in the requested mode
+            let builder = client.inner().prove(&proving_key, &stdin);
+            let builder = match mode {
+                ProofMode::Core => builder.core(),
+                ProofMode::Compressed => builder.compressed(),
+                ProofMode::Plonk => builder.plonk(),
+                ProofMode::Groth16 => builder.groth16(),
+            };
+            let proof = tracing::info_span!("prove", mode = ?mode, cycles)
+                .in_scope(|| builder.run())
+                .map_err(|e| CustomZkError::from_prover_error(circuit_type, e))?;
+            let segments = match &proof.proof {
+                SP1Proof::Core(shards) => {
+                    progress.report(ProveProgress::SegmentsProven { proven: shards.len(), total: shards.len() });
+                    Some(shards.len())
+                }
+                _ => None,
+            };
+            
+            let proof_bytes = tracing::debug_span!("serialize")
+                .in_scope(|| bincode::serialize(&proof))
+                .map_err(|e| CustomZkError::Backend(format!("Failed to serialize proof: {}", e)))?;
+            progress.report(ProveProgress::Serialized { proof_size: proof_bytes.len() });
+            let run = ProvingRun { proof: proof_bytes, cycles: Some(cycles), segments, vkey_hash: Some(verifying_key.bytes32_raw()) };
+            Ok((run, new_keys))
+        }, cancel).await?;
+
+        // Cache keys produced by setup
+        if let Some((proving_key, verifying_key, setup_time)) = new_keys {
+            let evicted = self.cache.store_keys(&guest.elf, proving_key, verifying_key, setup_time);
+            self.publish_eviction(CacheKind::Keys, evicted);
+        }
         
-        // Verify proof
-        match self.client.inner().verify(&proof, &verifying_key) {
-            Ok(_) => Ok(true),
-            Err(_) => Ok(false)
+        Ok(run)
+    }
+
+    /// Execute a program without proving it
+    pub async fn execute(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        domain: DomainSeparator,
+    ) -> Result<ExecutionReport, CustomZkError> {
+        let client = self.client.clone();
+        let input = guest_input(program, input)?.into_owned();
+        let (guest, _) = self.guest_elf(program)?;
+        if guest.pinned.is_some() {
+            self.guest_keys(&guest).await?;
         }
+        let program = guest.elf;
+        self.executor.run(move || {
+            let circuit_type = descriptor::circuit_type(&program).unwrap_or_default();
+            let (public_values, report) = tracing::info_span!("execute")
+                .in_scope(|| client.inner().execute(&program, &stdin(&input, domain)).run())
+                .map_err(|e| CustomZkError::from_prover_error(circuit_type, e))?;
+            Ok(ExecutionReport {
+                public_values: public_values.to_vec(),
+                cycles: Some(report.total_instruction_count()),
+                segments: None,
+                memory: Some(report.touched_memory_addresses * 4),
+                projected_proving_time: None,
+            })
+        }).await
     }
-}
 
-#[async_trait]
-impl ZkBackend for Sp1Backend {
-    async fn prove(
+    /// Estimate the cost of proving a program without proving it
+    ///
+    /// Executes the guest and projects the proving time in the mode the program's
+    /// profile selects, from the observed average if the program was proven before
+    /// and from its cycle count otherwise.
+    pub async fn estimate(&self, program: &[u8], input: &[u8]) -> Result<ExecutionReport, CustomZkError> {
+        let mut report = self.execute(program, input, DomainSeparator::UNBOUND).await?;
+        let mode = self.profiles.for_program(program)
+            .map(|profile| profile.proof_mode)
+            .unwrap_or(self.options.proof_mode);
+        let observed = self.program_stats.snapshot()
+            .get(&self.program_hash(program))
+            .filter(|stats| stats.proofs > 0)
+            .map(|stats| stats.avg_proving_time());
+        report.projected_proving_time = observed
+            .or_else(|| report.cycles.map(|cycles| execution::project_sp1(cycles, mode)));
+        Ok(report)
+    }
+
+    /// Generate a proof with per-request options.
+    ///
+    /// When a proof-size budget is set and the proof exceeds it, the proof is
+    /// regenerated in the next more succinct mode until it fits. If even the most
+    /// succinct mode is too large, [`CustomZkError::ProofTooLarge`] is returned. The
+    /// mode that was finally used is recorded in the returned metadata.
+    pub async fn prove_with_options(
         &self,
         program: &[u8],
         input: &[u8],
-        config: Option<&ZkConfig>,
-    ) -> ZkResult<(Vec<u8>, ProofMetadata)> {
+        options: &ProveOptions,
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        self.prove_cancellable(program, input, options, &CancellationToken::new()).await
+    }
+
+    /// Generate a proof with per-request options until `cancel` fires.
+    ///
+    /// Once `cancel` fires the request fails with [`CustomZkError::Cancelled`] and
+    /// releases its resource-tracking counters right away; a prover thread that already
+    /// started finishes in the background.
+    pub async fn prove_cancellable(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        options: &ProveOptions,
+        cancel: &CancellationToken,
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        self.prove_observed(program, input, options, cancel, &Progress::none()).await
+    }
+
+    /// Generate a proof with per-request options, sending progress events to `progress`.
+    ///
+    /// A proof served from the cache or shared with an identical request already in
+    /// flight sends no events.
+    pub async fn prove_with_progress(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        options: &ProveOptions,
+        progress: UnboundedSender<ProveProgress>,
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        self.prove_observed(program, input, options, &CancellationToken::new(), &Progress::new(progress)).await
+    }
+
+    /// Generate proofs for a batch, yielding each proof with its index in `programs`
+    /// as soon as it is ready
+    ///
+    /// Unlike [`ZkBackendExt::batch_prove`], a slow item does not hold back the
+    /// results of the others, and a failed item does not fail the batch.
+    pub fn batch_prove_stream<'a>(
+        &'a self,
+        programs: &'a [(&'a [u8], &'a [u8])],
+    ) -> impl Stream<Item = (usize, ZkResult<(Vec<u8>, ProofMetadata)>)> + 'a {
+        programs.iter()
+            .enumerate()
+            .map(|(index, (program, input))| async move {
+                let result = self.prove_with_options(program, input, &ProveOptions::default())
+                    .await
+                    .map(|(proof, metadata)| (proof, metadata.base))
+                    .map_err(|e| ZkError::Backend(e.to_string()));
+                (index, result)
+            }.instrument(tracing::info_span!("batch_item", index)))
+            .collect::<FuturesUnordered<_>>()
+    }
+
+    #[tracing::instrument(name = "prove_request", skip_all, fields(backend = "sp1", program_hash = tracing::field::Empty))]
+    async fn prove_observed(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        options: &ProveOptions,
+        cancel: &CancellationToken,
+        progress: &Progress,
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
         let start = SystemTime::now();
+        let info = self.info();
+        let circuit_type = descriptor::circuit_type(program);
+        let circuit_version = circuit_type.and_then(|circuit_type| info.circuit_version(circuit_type));
+        let profile = self.profiles.for_program(program);
+        // Hash the requested program rather than the guest it runs on
+        let program_hash = self.program_hash(program);
+        tracing::Span::current().record("program_hash", program_hash.as_str());
+        let input = guest_input(program, input)?;
+        let input = input.as_ref();
+        let (guest, _) = self.guest_elf(program)?;
+        let program: &[u8] = &guest.elf;
+        let requested_mode = options.proof_mode
+            .or(profile.as_ref().map(|profile| profile.proof_mode))
+            .unwrap_or(self.options.proof_mode);
+        let timeout = options.timeout
+            .or(profile.as_ref().and_then(|profile| profile.timeout))
+            .or(self.options.prove_timeout);
+        let cache_ttl = profile.as_ref().and_then(|profile| profile.cache_ttl);
+        let max_proof_size = options.max_proof_size.or(self.options.max_proof_size);
+        let domain = options.domain();
+        let options = ProveOptions {
+            proof_mode: Some(requested_mode),
+            max_proof_size,
+            domain: options.domain,
+            timeout,
+        };
         
-        // Check proof cache first
-        if let Some(entry) = self.cache.get_proof(program, input) {
-            let proof = entry.proof.clone();
-            return Ok((proof.clone(), ProofMetadata {
-                generation_time: entry.generation_time,
-                proof_size: proof.len(),
-                program_hash: hex::encode(&entry.program_hash),
-                timestamp: start,
-            }));
+        let cache_input = domain.cache_input(input);
+        if let Some(refresh) = &self.refresh {
+            refresh.record_hit(program, input, domain);
         }
         
-        // Update resource tracking
-        {
-            let mut resources = self.resources.write().await;
-            resources.active_tasks += 1;
+        // Check proof cache first (cached proofs are in the default mode)
+        if requested_mode == self.options.proof_mode {
+            let cached = tracing::debug_span!("cache", op = "lookup")
+                .in_scope(|| self.cache.get_proof(program, &cache_input));
+            if let Some(entry) = cached {
+                if options.fits(entry.proof.len()) {
+                    let proof = entry.proof.clone();
+                    return Ok((proof.clone(), ExtendedProofMetadata {
+                        base: ProofMetadata {
+                            generation_time: entry.generation_time,
+                            proof_size: proof.len(),
+                            program_hash: program_hash.clone(),
+                            timestamp: start,
+                        },
+                        proof_mode: requested_mode,
+                        requested_mode,
+                        max_proof_size,
+                        backend: info.backend.clone(),
+                        sdk_version: info.sdk_version.clone(),
+                        circuit_type,
+                        circuit_version,
+                        // Keys are cached with the proof; setup does not run for a cache hit
+                        vkey_hash: self.cache.peek_verifying_key(program).map(|key| key.bytes32_raw()),
+                        public_values: self.public_values(&proof).unwrap_or_default(),
+                        cycles: None,
+                        segments: None,
+                        peak_memory: None,
+                    }));
+                }
+            }
         }
+        
+        // Identical requests in flight share a single proving run
+        let key = flight_key(&[
+            program,
+            &cache_input,
+            format!("{:?}", requested_mode).as_bytes(),
+            &max_proof_size.map(|size| size as u64).unwrap_or(u64::MAX).to_le_bytes(),
+            &timeout.map(|timeout| timeout.as_millis() as u64).unwrap_or(u64::MAX).to_le_bytes(),
+        ]);
+        self.in_flight.run(key, cancel, || async {
+            // Update resource tracking
+            {
+                let mut resources = self.resources.write().await;
+                resources.active_tasks += 1;
+            }
+            self.events.publish(BackendEvent::ProofStarted {
+                program_hash: program_hash.clone(),
+                timestamp: start,
+            });
 
-        // Generate proof
-        let proof_bytes = self.prove_internal(program, input).await?;
+            // Generate proof, escalating while it exceeds the budget
+            let cancel = cancel.child_token();
+            let proving = async {
+                let mut mode = requested_mode;
+                loop {
+                    match self.prove_internal(&guest, input, mode, domain, &cancel, progress).await {
+                        Ok(run) if options.fits(run.proof.len()) => break Ok((run, mode)),
+                        Ok(run) => match mode.more_succinct() {
+                            Some(next) => {
+                                progress.report(ProveProgress::Escalated { from: mode, to: next });
+                                mode = next;
+                            }
+                            None => break Err(CustomZkError::ProofTooLarge {
+                                size: run.proof.len(),
+                                limit: max_proof_size.unwrap_or_default(),
+                                mode,
+                            }),
+                        },
+                        Err(e) => break Err(e),
+                    }
+                }
+            };
+            let peak_memory = sysmon::monitor().track_peak();
+            let result = bounded(proving, timeout, &cancel).await;
+            let duration = start.elapsed().unwrap_or_default();
         
-        // Create metadata
-        let duration = start.elapsed().unwrap_or_default();
-        let metadata = ProofMetadata {
-            generation_time: duration,
-            proof_size: proof_bytes.len(),
-            program_hash: hex::encode(program),
-            timestamp: start,
-        };
+            // Update resource tracking
+            {
+                let mut resources = self.resources.write().await;
+                resources.active_tasks -= 1;
+            }
 
-        // Store in cache
-        self.cache.store_proof(program, input, proof_bytes.clone(), duration);
+            let (ProvingRun { proof: proof_bytes, cycles, segments, vkey_hash }, mode) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    self.events.publish(BackendEvent::proof_failed(program_hash.clone(), duration, &e));
+                    self.failures.record(&program_hash, &e);
+                    self.program_stats.record(&program_hash, duration, false);
+                    self.update_proving_stats(duration, false).await;
+                    return Err(e);
+                }
+            };
         
-        // Update resource tracking
-        {
-            let mut resources = self.resources.write().await;
-            resources.active_tasks -= 1;
-        }
+            // Create metadata
+            let metadata = ExtendedProofMetadata {
+                base: ProofMetadata {
+                    generation_time: duration,
+                    proof_size: proof_bytes.len(),
+                    program_hash: program_hash.clone(),
+                    timestamp: start,
+                },
+                proof_mode: mode,
+                requested_mode,
+                max_proof_size,
+                backend: info.backend.clone(),
+                sdk_version: info.sdk_version.clone(),
+                circuit_type,
+                circuit_version,
+                vkey_hash,
+                public_values: self.public_values(&proof_bytes).unwrap_or_default(),
+                cycles,
+                segments,
+                peak_memory: peak_memory.peak(),
+            };
+            self.events.publish(BackendEvent::ProofCompleted {
+                program_hash: program_hash.clone(),
+                duration,
+                proof_size: proof_bytes.len(),
+                proof_mode: mode,
+            });
+
+            // Store in cache
+            if mode == self.options.proof_mode {
+                let evicted = tracing::debug_span!("cache", op = "store")
+                    .in_scope(|| self.cache.store_proof_with_ttl(program, &cache_input, proof_bytes.clone(), duration, cache_ttl));
+                self.publish_eviction(CacheKind::Proof, evicted);
+            }
         
-        // Update stats
-        self.update_proving_stats(duration, true).await;
+            // Update stats
+            self.program_stats.record(&program_hash, duration, true);
+            self.update_proving_stats(duration, true).await;
         
+            Ok((proof_bytes, metadata))
+        }).await
+    }
+
+    /// Regenerate cached proofs that are about to expire, returning the number refreshed.
+    ///
+    /// Refreshing runs at low priority: the pass stops as soon as a request is being
+    /// proven.
+    pub async fn refresh_expiring_proofs(&self) -> usize {
+        let refresh = match &self.refresh {
+            Some(refresh) => refresh.clone(),
+            None => return 0,
+        };
+        let due = refresh.due(|candidate| {
+            self.cache.proof_expires_in(&candidate.program, &candidate.cache_input())
+        });
+
+        let mut refreshed = 0;
+        for candidate in due {
+            if self.resources.read().await.active_tasks > 0 {
+                break;
+            }
+            let start = SystemTime::now();
+            match self.prove_internal(&GuestElf::raw(&candidate.program), &candidate.input, self.options.proof_mode, candidate.domain, &CancellationToken::new(), &Progress::none()).await {
+                Ok(ProvingRun { proof: proof_bytes, .. }) => {
+                    let cache_ttl = self.profiles.for_program(&candidate.program)
+                        .and_then(|profile| profile.cache_ttl);
+                    let evicted = self.cache.store_proof_with_ttl(
+                        &candidate.program,
+                        &candidate.cache_input(),
+                        proof_bytes,
+                        start.elapsed().unwrap_or_default(),
+                        cache_ttl,
+                    );
+                    self.publish_eviction(CacheKind::Proof, evicted);
+                    refresh.record_success(&candidate);
+                    refreshed += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(program_hash = %hex::encode(&candidate.program), "proof refresh failed: {}", e);
+                    refresh.record_failure(&candidate);
+                }
+            }
+        }
+        refreshed
+    }
+
+    /// Spawn the background task refreshing expiring proofs
+    ///
+    /// Returns `None` if no refresh policy is configured.
+    pub fn spawn_cache_refresh(self: Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let interval = self.refresh.as_ref()?.policy().interval;
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.refresh_expiring_proofs().await;
+            }
+        }))
+    }
+
+    /// Run the health probe once, recording its outcome
+    ///
+    /// Returns `None` if no health probe is configured.
+    pub async fn run_health_probe(&self) -> Option<ProbeResult> {
+        let probe = self.probe.as_ref()?;
+        let policy = probe.policy();
+        let start = SystemTime::now();
+        let cancel = CancellationToken::new();
+        let probing = async {
+            match policy.mode {
+                ProbeMode::Execute => self.execute(&policy.program, &policy.input, DomainSeparator::UNBOUND).await.map(drop),
+                ProbeMode::Prove => {
+                    let input = guest_input(&policy.program, &policy.input)?;
+                    let (guest, _) = self.guest_elf(&policy.program)?;
+                    self.prove_internal(&guest, &input, ProofMode::Core, DomainSeparator::UNBOUND, &cancel, &Progress::none())
+                        .await
+                        .map(drop)
+                }
+            }
+        };
+        let outcome = match tokio::time::timeout(policy.timeout, probing).await {
+            Ok(result) => result.map_err(|e| e.to_string()),
+            Err(_) => {
+                cancel.cancel();
+                Err(CustomZkError::Timeout(policy.timeout).to_string())
+            }
+        };
+        if let Err(e) = &outcome {
+            tracing::warn!(backend = "sp1", "health probe failed: {}", e);
+        }
+        Some(probe.record(start.elapsed().unwrap_or_default(), outcome))
+    }
+
+    /// Spawn the background task running the health probe
+    ///
+    /// Returns `None` if no health probe is configured.
+    pub fn spawn_health_probe(self: Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let interval = self.probe.as_ref()?.policy().interval;
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.run_health_probe().await;
+            }
+        }))
+    }
+
+    /// Generate a proof wrapped in a [`ProofEnvelope`] describing it
+    pub async fn prove_envelope(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        options: &ProveOptions,
+    ) -> Result<ProofEnvelope, CustomZkError> {
+        let (proof, metadata) = self.prove_with_options(program, input, options).await?;
+        Ok(ProofEnvelope::from_metadata(&metadata, proof))
+    }
+
+    /// Unwrap an enveloped proof of `program`, passing raw proofs through
+    ///
+    /// The envelope must have been produced by this backend for `program`, and its
+    /// public values must be those committed by the proof it carries.
+    pub fn open_envelope<'a>(&self, program: &[u8], proof: &'a [u8]) -> Result<Cow<'a, [u8]>, CustomZkError> {
+        if !envelope::is_envelope(proof) {
+            return Ok(Cow::Borrowed(proof));
+        }
+        let envelope = ProofEnvelope::from_bytes(proof)?;
+        self.info().compatibility_check(&envelope)?;
+        let proof = envelope.open(&self.info().backend, &self.program_hash(program))?;
+        if self.public_values(proof)? != envelope.public_values {
+            return Err(CustomZkError::ProofVerification("Envelope public values do not match its proof".into()));
+        }
+        Ok(Cow::Owned(proof.to_vec()))
+    }
+
+    /// Verify a proof, first rejecting proofs produced by an incompatible SDK
+    pub async fn verify_with_metadata(
+        &self,
+        program: &[u8],
+        proof: &[u8],
+        metadata: &ExtendedProofMetadata,
+    ) -> Result<bool, CustomZkError> {
+        self.info().check_metadata(metadata)?;
+        ZkBackend::verify(self, program, proof, None)
+            .await
+            .map_err(CustomZkError::from)
+    }
+
+    /// Verify a proof and check that it is bound to `domain`
+    pub async fn verify_with_domain(
+        &self,
+        program: &[u8],
+        proof: &[u8],
+        domain: DomainSeparator,
+    ) -> Result<bool, CustomZkError> {
+        if !ZkBackend::verify(self, program, proof, None).await? {
+            return Ok(false);
+        }
+        let proof = self.open_envelope(program, proof)?;
+        let found = DomainSeparator::from_public_values(&self.public_values(&proof)?)
+            .ok_or_else(|| CustomZkError::ProofVerification("Proof does not commit to a domain".into()))?;
+        if found != domain {
+            return Err(CustomZkError::DomainMismatch { expected: domain, found });
+        }
+        Ok(true)
+    }
+
+    /// Export the verifying key of a program without generating a proof
+    ///
+    /// The key of the guest `program` runs on is returned in the [`ExportedKey`]
+    /// format, bound to the program hash under the hash function of its profile.
+    pub fn export_verifying_key(&self, program: &[u8]) -> Result<Vec<u8>, CustomZkError> {
+        let (guest, _) = self.guest_elf(program)?;
+        let (_, verifying_key) = self.setup_keys(&guest.elf);
+        let key = bincode::serialize(verifying_key.as_ref())
+            .map_err(|e| CustomZkError::Backend(format!("Failed to serialize verifying key: {}", e)))?;
+        let hash_function = self.profiles.for_program(program)
+            .map(|profile| profile.hash_function)
+            .unwrap_or_default();
+        Ok(ExportedKey::new(KeyKind::Sp1VerifyingKey, program, hash_function, verifying_key.bytes32_raw(), key).to_bytes())
+    }
+
+    /// Convert a serialized proof of `program` into the more succinct `target` mode
+    ///
+    /// Core proofs can be compressed or wrapped and compressed proofs wrapped after
+    /// the fact, so that the expensive core proof is generated off-peak and wrapped
+    /// just before on-chain submission.
+    pub async fn convert(&self, program: &[u8], proof: &[u8], target: ProofMode) -> Result<Vec<u8>, CustomZkError> {
+        let (guest, _) = self.guest_elf(program)?;
+        let (_, verifying_key) = self.guest_keys(&guest).await?;
+        let proof = verifier::decode_proof(proof)?;
+        let client = self.client.clone();
+        self.executor.run(move || {
+            let proof = convert::convert_proof(client.inner(), &verifying_key, proof, target)?;
+            bincode::serialize(&proof)
+                .map_err(|e| CustomZkError::Backend(format!("Failed to serialize proof: {}", e)))
+        }).await
+    }
+
+    /// Encode a serialized Groth16 or PLONK proof for the on-chain SP1 verifier
+    ///
+    /// The result is the verifier selector followed by the proof points, as expected
+    /// by the `SP1Verifier` contracts.
+    pub fn onchain_proof(&self, proof: &[u8]) -> Result<Vec<u8>, CustomZkError> {
+        let proof = verifier::decode_proof(proof)?;
+        if !verifier::proof_mode(&proof).is_onchain_verifiable() {
+            return Err(CustomZkError::InvalidInput("Only Groth16 and PLONK proofs can be verified on-chain".into()));
+        }
+        Ok(proof.bytes())
+    }
+
+    /// Verify the on-chain encoding of a serialized Groth16 or PLONK proof
+    ///
+    /// This performs the same check as the `SP1Verifier` contracts, using only the
+    /// verifying key hash of the program.
+    pub fn verify_onchain(&self, program: &[u8], proof: &[u8]) -> Result<bool, CustomZkError> {
+        let (_, verifying_key) = self.setup_keys(program);
+        let proof = verifier::decode_proof(&self.open_envelope(program, proof)?)?;
+        verifier::verify_onchain(
+            verifier::proof_mode(&proof),
+            &proof.bytes(),
+            proof.public_values.as_slice(),
+            &verifying_key.bytes32(),
+        )
+    }
+
+    /// Verify a batch of proofs, reporting the outcome of each separately
+    ///
+    /// Every proof goes through the checks of [`ZkBackend::verify`]: it is opened,
+    /// decoded and checked against the key of its program's guest, then its public
+    /// values are checked by the journal validator of application programs and the
+    /// replay guard. A malformed or rejected proof fails its own item rather than the
+    /// whole batch. Each distinct program is set up once on the proving executor and
+    /// its verifying key shared by every proof targeting it. At most
+    /// `max_batch_parallelism` proofs are then checked at once on the blocking pool,
+    /// holding as many threads of the CPU budget.
+    #[tracing::instrument(name = "batch_verify", skip_all, fields(backend = "sp1", batch_size = verifications.len()))]
+    pub async fn batch_verify_detailed(
+        &self,
+        verifications: &[(&[u8], &[u8])],
+    ) -> Result<Vec<Sp1VerificationResult>, CustomZkError> {
+        let parallelism = self.options.max_batch_parallelism
+            .or(self.options.num_threads)
+            .unwrap_or(4)
+            .max(1);
+
+        // Set up the programs before taking the batch's share of the CPU budget,
+        // which the executor running the setups draws from as well
+        let mut programs: Vec<&[u8]> = verifications.iter().map(|(program, _)| *program).collect();
+        programs.sort_unstable();
+        programs.dedup();
+        let guests: HashMap<&[u8], Result<(Option<Arc<GuestProgram>>, Vec<Arc<SP1VerifyingKey>>), String>> =
+            futures::future::join_all(programs.iter().map(|program| async move {
+                let keys = async {
+                    let (elf, guest) = self.verification_guest(program)?;
+                    let mut verifying_keys = vec![self.guest_keys(&elf).await?.1];
+                    for superseded in self.superseded_guests(program) {
+                        verifying_keys.push(self.guest_keys(&superseded).await?.1);
+                    }
+                    Ok::<_, CustomZkError>((guest, verifying_keys))
+                };
+                let span = tracing::debug_span!("batch_setup", program_hash = %self.program_hash(program));
+                (*program, keys.instrument(span).await.map_err(|e| e.to_string()))
+            }))
+            .await
+            .into_iter()
+            .collect();
+
+        let mut results: Vec<Option<Sp1VerificationResult>> = vec![None; verifications.len()];
+        let mut items = Vec::with_capacity(verifications.len());
+        for (index, (program, proof)) in verifications.iter().enumerate() {
+            match (&guests[program], self.open_envelope(program, proof)) {
+                (Ok((_, verifying_keys)), Ok(proof)) => {
+                    items.push((index, self.program_hash(program), proof.into_owned(), verifying_keys.clone()));
+                }
+                (Err(e), _) => results[index] = Some(Sp1VerificationResult::failure(e.clone())),
+                (_, Err(e)) => results[index] = Some(Sp1VerificationResult::failure(e.to_string())),
+            }
+        }
+
+        let _cpu = threads::budget().acquire(parallelism, &CancellationToken::new()).await?;
+        let client = self.client.clone();
+        // Rayon workers do not inherit the batch span, so enter it on each of them
+        let span = tracing::Span::current();
+        let checked = tokio::task::spawn_blocking(move || {
+            // Split the batch into at most `parallelism` runs of consecutive proofs
+            let min_len = items.len().div_ceil(parallelism);
+            items.into_par_iter()
+                .with_min_len(min_len)
+                .map(|(index, program_hash, proof, verifying_keys)| {
+                    let _span = tracing::debug_span!(parent: &span, "batch_item", index, program_hash = %program_hash).entered();
+                    let outcome = verifier::decode_proof(&proof).map(|proof| {
+                        let valid = verifying_keys.iter()
+                            .any(|verifying_key| verifier::verify_proof(client.inner(), &proof, verifying_key));
+                        (valid, proof.public_values.to_vec())
+                    });
+                    (index, outcome)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|e| CustomZkError::Backend(format!("Batch verification task failed: {}", e)))?;
+
+        for (index, outcome) in checked {
+            let program = verifications[index].0;
+            let guest = guests[program].as_ref().ok().and_then(|(guest, _)| guest.as_deref());
+            results[index] = Some(match outcome {
+                Ok((true, public_values)) => match self.accept_verified(program, guest, &public_values) {
+                    Ok(true) => Sp1VerificationResult::success(),
+                    Ok(false) => Sp1VerificationResult::failure("Public values rejected".into()),
+                    Err(e) => Sp1VerificationResult::failure(e.to_string()),
+                },
+                Ok((false, _)) => Sp1VerificationResult::failure("Proof rejected".into()),
+                Err(e) => Sp1VerificationResult::failure(e.to_string()),
+            });
+        }
+        Ok(results.into_iter().map(|result| result.expect("every batch item is checked")).collect())
+    }
+
+    /// Guest a proof of `program` is checked against, with the application program
+    /// it refers to
+    ///
+    /// Programs that are neither descriptors nor ELFs of a known circuit are rejected.
+    fn verification_guest(&self, program: &[u8]) -> Result<(GuestElf, Option<Arc<GuestProgram>>), CustomZkError> {
+        if !descriptor::is_encoded(program) {
+            self.create_circuit(program, &[])?;
+        }
+        self.guest_elf(program)
+    }
+
+    /// Check the public values of a valid proof of `program`
+    ///
+    /// Application programs reject public values their journal validator does not
+    /// accept. A configured replay guard then rejects messages it already accepted.
+    fn accept_verified(
+        &self,
+        program: &[u8],
+        guest: Option<&GuestProgram>,
+        public_values: &[u8],
+    ) -> Result<bool, CustomZkError> {
+        if guest.is_some_and(|guest| !guest.validate(public_values)) {
+            return Ok(false);
+        }
+        // A valid proof of an already accepted message is a replay
+        if let (Some(guard), Some(circuit_type)) = (&self.replay_guard, descriptor::circuit_type(program)) {
+            guard.accept_public_values(circuit_type, public_values)?;
+        }
+        Ok(true)
+    }
+
+    /// Extract the public values committed by the guest from a serialized proof
+    pub fn public_values(&self, proof: &[u8]) -> Result<Vec<u8>, CustomZkError> {
+        let proof: SP1ProofWithPublicValues = bincode::deserialize(proof)
+            .map_err(|e| CustomZkError::ProofVerification(format!("Failed to parse proof: {}", e)))?;
+        Ok(proof.public_values.to_vec())
+    }
+
+    /// Generate one proof attesting that every proof in `proofs` verifies
+    ///
+    /// Each entry is a program and a compressed proof of it. The aggregate proof is
+    /// generated in the default proof mode by the configured aggregation program, and
+    /// commits to the [`merkle`](crate::merkle) root over the [`proof_hash`] of every
+    /// proof in order, followed by the number of proofs as a little-endian `u64`.
+    ///
+    /// [`proof_hash`]: crate::merkle::proof_hash
+    pub async fn aggregate_proofs(
+        &self,
+        proofs: &[(&[u8], &[u8])],
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        let aggregation_program = self.aggregation_program.clone()
+            .ok_or_else(|| CustomZkError::Backend("No aggregation program configured".into()))?;
+        if proofs.is_empty() {
+            return Err(CustomZkError::InvalidInput("No proofs to aggregate".into()));
+        }
+
+        // The guest verifies compressed proofs against the verifying key of their program
+        let mut vkeys = Vec::with_capacity(proofs.len());
+        let mut public_values = Vec::with_capacity(proofs.len());
+        let mut inner = Vec::with_capacity(proofs.len());
+        for (index, (program, proof)) in proofs.iter().enumerate() {
+            let proof = verifier::decode_proof(proof)?;
+            let reduced = match proof.proof {
+                SP1Proof::Compressed(reduced) => reduced,
+                _ => return Err(CustomZkError::InvalidInput(format!(
+                    "Proof {} is not a compressed proof and cannot be aggregated",
+                    index
+                ))),
+            };
+            let (_, verifying_key) = self.setup_keys(program);
+            vkeys.push(verifying_key.hash_u32());
+            public_values.push(proof.public_values.to_vec());
+            inner.push((reduced, verifying_key));
+        }
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&vkeys);
+        stdin.write(&public_values);
+        for (reduced, verifying_key) in inner {
+            stdin.write_proof(*reduced, verifying_key.vk.clone());
+        }
+        self.prove_stdin(&aggregation_program, stdin).await
+    }
+
+    /// Generate an SP1 proof that a RISC0 succinct receipt of `image_id` verifies
+    ///
+    /// The proof is generated in the default proof mode by the configured bridge
+    /// program. Its public values are the image ID followed by the receipt's journal,
+    /// so the journal decodes exactly as it would from the receipt itself; see
+    /// [`BridgedReceipt`](crate::bridge::BridgedReceipt).
+    pub async fn bridge_risc0_receipt(
+        &self,
+        receipt: &[u8],
+        image_id: [u8; 32],
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        let bridge_program = self.bridge_program.clone()
+            .ok_or_else(|| CustomZkError::Backend("No bridge program configured".into()))?;
+        let receipt: Receipt = bincode::deserialize(receipt)
+            .map_err(|e| CustomZkError::InvalidInput(format!("Invalid receipt: {}", e)))?;
+
+        // Only succinct receipts are small enough to verify inside the guest
+        if !matches!(receipt.inner, InnerReceipt::Succinct(_)) {
+            return Err(CustomZkError::InvalidInput("Only succinct RISC0 receipts can be bridged".into()));
+        }
+        let image_id = Digest::from(image_id);
+        receipt.verify(image_id)
+            .map_err(|e| CustomZkError::ProofVerification(format!("Receipt does not verify: {}", e)))?;
+
+        let image_words: [u32; 8] = image_id.as_words().try_into().expect("digests have eight words");
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&image_words);
+        stdin.write(&receipt);
+        self.prove_stdin(&bridge_program, stdin).await
+    }
+
+    /// Prove `program` on a prepared input stream in the default proof mode
+    async fn prove_stdin(
+        &self,
+        program: &[u8],
+        stdin: SP1Stdin,
+    ) -> Result<(Vec<u8>, ExtendedProofMetadata), CustomZkError> {
+        let start = SystemTime::now();
+        let mode = self.options.proof_mode;
+        let (proving_key, verifying_key) = self.setup_keys(program);
+        let client = self.client.clone();
+        let circuit_type = descriptor::circuit_type(program);
+        let proof_bytes = self.executor.run(move || {
+            let builder = client.inner().prove(&proving_key, &stdin);
+            let builder = match mode {
+                ProofMode::Core => builder.core(),
+                ProofMode::Compressed => builder.compressed(),
+                ProofMode::Plonk => builder.plonk(),
+                ProofMode::Groth16 => builder.groth16(),
+            };
+            let proof = builder
+                .run()
+                .map_err(|e| CustomZkError::from_prover_error(circuit_type.unwrap_or_default(), e))?;
+            bincode::serialize(&proof)
+                .map_err(|e| CustomZkError::Backend(format!("Failed to serialize proof: {}", e)))
+        }).await?;
+
+        let info = self.info();
+        let metadata = ExtendedProofMetadata {
+            base: ProofMetadata {
+                generation_time: start.elapsed().unwrap_or_default(),
+                proof_size: proof_bytes.len(),
+                program_hash: self.program_hash(program),
+                timestamp: start,
+            },
+            proof_mode: mode,
+            requested_mode: mode,
+            max_proof_size: None,
+            backend: info.backend.clone(),
+            sdk_version: info.sdk_version.clone(),
+            circuit_type,
+            circuit_version: circuit_type.and_then(|circuit_type| info.circuit_version(circuit_type)),
+            vkey_hash: Some(verifying_key.bytes32_raw()),
+            public_values: self.public_values(&proof_bytes).unwrap_or_default(),
+            cycles: None,
+            segments: None,
+            peak_memory: None,
+        };
         Ok((proof_bytes, metadata))
     }
 
+    async fn verify_internal(&self, guest: &GuestElf, proof: &[u8]) -> ZkResult<bool> {
+        // Get verifying key
+        let (_, verifying_key) = self.guest_keys(guest).await.map_err(|e| ZkError::Backend(e.to_string()))?;
+        
+        let proof = verifier::decode_proof(proof).map_err(|e| ZkError::Backend(e.to_string()))?;
+        let client = self.client.clone();
+        self.executor.run(move || Ok(verifier::verify_proof(client.inner(), &proof, &verifying_key)))
+            .await
+            .map_err(|e| ZkError::Backend(e.to_string()))
+    }
+}
+
+/// ELF of the guest a program runs, with the verifying key hash it must have
+#[derive(Debug, Clone)]
+struct GuestElf {
+    /// Guest ELF, shared with the registered or manifest programs
+    elf: Arc<[u8]>,
+    /// Verifying key hash pinned by the program's descriptor
+    pinned: Option<[u8; 32]>,
+    /// Pin on the registered program version the guest belongs to
+    _lease: Option<Arc<ProgramLease>>,
+}
+
+impl GuestElf {
+    /// Guest of a program that is the ELF itself
+    fn raw(program: &[u8]) -> Self {
+        Self { elf: Arc::from(program), pinned: None, _lease: None }
+    }
+
+    /// Check the guest's verifying key against the pinned hash
+    ///
+    /// A swapped or corrupted guest must not prove or verify in place of the expected
+    /// one.
+    fn check(&self, verifying_key: &SP1VerifyingKey) -> Result<(), CustomZkError> {
+        match self.pinned {
+            Some(expected) if verifying_key.bytes32_raw() != expected => Err(CustomZkError::ImageMismatch {
+                expected: hex::encode(expected),
+                found: hex::encode(verifying_key.bytes32_raw()),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Flag set on the message hash function byte of guest input carrying a message nonce
+const MESSAGE_NONCE_FLAG: u8 = 0x80;
+
+/// Input of the guest a program runs
+///
+/// The message verification guest reads the message hash function the program
+/// selects before the message, so proofs of one message under two functions are
+/// cached apart. Programs committing to a message nonce set
+/// [`MESSAGE_NONCE_FLAG`] on the function and pass the nonce between the two.
+fn guest_input<'a>(program: &[u8], input: &'a [u8]) -> Result<Cow<'a, [u8]>, CustomZkError> {
+    if !descriptor::is_encoded(program) || descriptor::circuit_type(program) != Some(MESSAGE_VERIFY) {
+        return Ok(Cow::Borrowed(input));
+    }
+    let descriptor = ProgramDescriptor::decode(program)?;
+    let id = descriptor.message_hash_function().message_hash_id().expect("descriptors only select supported functions");
+    Ok(Cow::Owned(match descriptor.message_nonce() {
+        Some(nonce) => [&[id | MESSAGE_NONCE_FLAG][..], &nonce.to_bytes(), input].concat(),
+        None => [&[id][..], input].concat(),
+    }))
+}
+
+/// Guest input followed by the domain the guest commits to
+fn stdin(input: &[u8], domain: DomainSeparator) -> SP1Stdin {
+    let mut stdin = SP1Stdin::new();
+    stdin.write_slice(input);
+    stdin.write_slice(&domain.to_bytes());
+    stdin
+}
+
+#[async_trait]
+impl ZkBackend for Sp1Backend {
+    async fn prove(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        config: Option<&ZkConfig>,
+    ) -> ZkResult<(Vec<u8>, ProofMetadata)> {
+        let (proof_bytes, metadata) = self.prove_with_options(program, input, &ProveOptions::default())
+            .await
+            .map_err(|e| ZkError::Backend(e.to_string()))?;
+        
+        Ok((proof_bytes, metadata.base))
+    }
+
+    #[tracing::instrument(name = "verify_request", skip_all, fields(backend = "sp1", program_hash = %self.program_hash(program)))]
     async fn verify(
         &self,
         program: &[u8],
@@ -250,12 +1592,24 @@ impl ZkBackend for Sp1Backend {
         config: Option<&ZkConfig>,
     ) -> ZkResult<bool> {
         let start = SystemTime::now();
+        let proof = &*self.open_envelope(program, proof).map_err(|e| ZkError::Backend(e.to_string()))?;
         
-        // Create circuit
-        let circuit = self.create_circuit(program, &[])?;
-        
-        // Verify proof
-        let result = self.verify_internal(program, proof).await?;
+        // Verify proof, then check its public values
+        let (elf, guest) = self.verification_guest(program).map_err(|e| ZkError::Backend(e.to_string()))?;
+        let mut valid = self.verify_internal(&elf, proof).await?;
+        // Proofs made just before an upgrade verify against the superseded versions
+        for superseded in self.superseded_guests(program) {
+            if valid {
+                break;
+            }
+            valid = self.verify_internal(&superseded, proof).await?;
+        }
+        let result = valid
+            && self.accept_verified(
+                program,
+                guest.as_deref(),
+                &self.public_values(proof).map_err(|e| ZkError::Backend(e.to_string()))?,
+            ).map_err(|e| ZkError::Backend(e.to_string()))?;
         
         // Update stats
         self.update_verification_stats(start.elapsed().unwrap_or_default(), result).await;
@@ -264,72 +1618,85 @@ impl ZkBackend for Sp1Backend {
     }
 
     fn resource_usage(&self) -> ResourceUsage {
-        futures::executor::block_on(async {
+        let tracked = futures::executor::block_on(async {
             self.resources.read().await.clone()
-        })
+        });
+        sysmon::monitor().usage(tracked)
     }
 
     async fn health_check(&self) -> HealthStatus {
-        let resources = self.resources.read().await;
-        let stats = self.stats.read().await;
-        
-        if resources.active_tasks < resources.max_concurrent {
-            HealthStatus::Healthy
-        } else {
-            HealthStatus::Degraded("High resource usage".into())
+        let status = {
+            let resources = sysmon::monitor().usage(self.resources.read().await.clone());
+            
+            if resources.cpu_usage > sysmon::HIGH_CPU_USAGE {
+                HealthStatus::Degraded("High CPU usage".into())
+            } else if resources.memory_usage > self.options.memory_limit.unwrap_or(usize::MAX) {
+                HealthStatus::Degraded("High memory usage".into())
+            } else if resources.active_tasks < resources.max_concurrent {
+                HealthStatus::Healthy
+            } else {
+                HealthStatus::Degraded("High resource usage".into())
+            }
+        };
+        // A failing probe outweighs resource pressure
+        let status = self.probe.as_ref()
+            .and_then(|probe| probe.status())
+            .unwrap_or(status);
+
+        // Publish health transitions
+        let current = format!("{:?}", status);
+        let mut last_health = self.last_health.write().await;
+        if last_health.as_deref() != Some(current.as_str()) {
+            if let HealthStatus::Degraded(reason) | HealthStatus::Unhealthy(reason) = &status {
+                self.events.publish(BackendEvent::Alert { message: reason.clone() });
+            }
+            self.events.publish(BackendEvent::HealthChanged {
+                previous: last_health.clone().unwrap_or_else(|| "Unknown".into()),
+                current: current.clone(),
+            });
+            *last_health = Some(current);
         }
+
+        status
     }
 }
 
 #[async_trait]
 impl ZkBackendExt for Sp1Backend {
+    #[tracing::instrument(name = "batch_prove", skip_all, fields(backend = "sp1", batch_size = programs.len()))]
     async fn batch_prove(
         &self,
         programs: &[(&[u8], &[u8])],
         config: Option<&ZkConfig>,
     ) -> ZkResult<Vec<(Vec<u8>, ProofMetadata)>> {
         let start = SystemTime::now();
-        let thread_pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(self.options.num_threads.unwrap_or(4))
-            .build()
-            .map_err(|e| ZkError::Backend(format!("Failed to create thread pool: {}", e)))?;
 
         // Update resource tracking
         {
             let mut resources = self.resources.write().await;
             resources.active_tasks += programs.len();
-            resources.queue_depth = programs.len();
         }
 
-        // Generate proofs in parallel
+        // Generate proofs concurrently, bounded by the executor
         let results: Vec<ZkResult<(Vec<u8>, ProofMetadata)>> =
-            programs.par_iter().map(|(program, input)| {
-                let circuit = self.create_circuit(program, input)?;
+            futures::future::join_all(programs.iter().enumerate().map(|(index, (program, input))| async move {
+                self.create_circuit(program, input)?;
                 let proof_start = SystemTime::now();
                 
-                // Create stdin and write input
-                let mut stdin = SP1Stdin::new();
-                stdin.write(input);
-                
-                // Create proving key and verifying key
-                let (proving_key, verifying_key) = self.client.inner().setup(program);
-
-                let proof = self.client.inner().prove(&proving_key, &stdin)
-                    .run()
-                    .map_err(|e| ZkError::Backend(format!("Proof generation failed: {}", e)))?;
-                
-                // Get proof bytes and their size
-                let proof_bytes = proof.bytes().to_vec();
+                let proof_bytes = self.prove_internal(&GuestElf::raw(program), input, self.options.proof_mode, DomainSeparator::UNBOUND, &CancellationToken::new(), &Progress::none())
+                    .await
+                    .map_err(|e| ZkError::Backend(format!("Proof generation failed: {}", e)))?
+                    .proof;
                 let proof_size = proof_bytes.len();
                 
                 let duration = proof_start.elapsed().unwrap_or_default();
                 Ok((proof_bytes, ProofMetadata {
                     generation_time: duration,
                     proof_size,
-                    program_hash: hex::encode(program),
+                    program_hash: self.program_hash(program),
                     timestamp: proof_start,
                 }))
-            }).collect();
+            }.instrument(tracing::info_span!("batch_item", index, program_hash = %self.program_hash(program))))).await;
 
         // Update stats
         self.update_proving_stats(
@@ -341,50 +1708,34 @@ impl ZkBackendExt for Sp1Backend {
         {
             let mut resources = self.resources.write().await;
             resources.active_tasks -= programs.len();
-            resources.queue_depth = 0;
         }
 
         // Collect results
         results.into_iter().collect()
     }
 
+    /// Verify a batch of proofs.
+    ///
+    /// Each distinct program is set up once and its verifying key is shared by every
+    /// proof in the batch that targets it, so the cost of `setup` no longer scales with
+    /// the batch size. The SP1 SDK does not expose a batched pairing check, so wrapped
+    /// proofs are still checked individually, in parallel, against the shared key.
+    /// Malformed proofs are reported invalid; [`Sp1Backend::batch_verify_detailed`]
+    /// reports why each proof was rejected.
     async fn batch_verify(
         &self,
         verifications: &[(&[u8], &[u8])],
         config: Option<&ZkConfig>,
     ) -> ZkResult<Vec<bool>> {
-        let thread_pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(self.options.num_threads.unwrap_or(4))
-            .build()
-            .map_err(|e| ZkError::Backend(format!("Failed to create thread pool: {}", e)))?;
-
-        // Verify proofs in parallel
-        let results: Vec<ZkResult<bool>> = thread_pool.install(|| {
-            verifications.par_iter().map(|(program, proof)| {
-                let (proving_key, verifying_key) = self.client.inner().setup(program);
-                
-                // Parse proof - create a temporary file since load requires a path
-                let temp_dir = std::env::temp_dir();
-                let temp_path = temp_dir.join("proof.tmp");
-                std::fs::write(&temp_path, proof)
-                    .map_err(|e| ZkError::Backend(format!("Failed to write proof to temp file: {}", e)))?;
-                
-                let proof = SP1ProofWithPublicValues::load(&temp_path)
-                    .map_err(|e| ZkError::Backend(format!("Failed to parse proof: {}", e)))?;
-                
-                // Clean up temp file
-                let _ = std::fs::remove_file(temp_path);
-                
-                // Verify proof
-                match self.client.inner().verify(&proof, &verifying_key) {
-                    Ok(_) => Ok(true),
-                    Err(_) => Ok(false)
-                }
-            }).collect()
-        });
-
-        // Collect results
-        results.into_iter().collect()
+        let results = self.batch_verify_detailed(verifications)
+            .await
+            .map_err(|e| ZkError::Backend(e.to_string()))?;
+        Ok(results.into_iter().enumerate().map(|(index, result)| {
+            if let Some(error) = &result.error {
+                tracing::debug!(index, "batch proof rejected: {}", error);
+            }
+            result.is_valid
+        }).collect())
     }
 
     async fn clear_cache(&mut self) -> Result<(), ZkError> {
@@ -395,6 +1746,8 @@ impl ZkBackendExt for Sp1Backend {
     fn capabilities(&self) -> Vec<String> {
         vec![
             "sp1".to_string(),
+            "groth16_bn254".to_string(),
+            "plonk_bn254".to_string(),
             "message_verify".to_string(),
             "tx_verify".to_string(),
             "block_verify".to_string(),
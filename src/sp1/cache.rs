@@ -4,14 +4,20 @@
 //! Cache implementation for SP1 circuits and proofs
 
 use std::sync::Arc;
+use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 use parking_lot::RwLock;
 use lru::LruCache;
 use std::num::NonZeroUsize;
 use sha2::{Sha256, Digest};
 use sp1_prover::SP1Prover;
+use sp1_sdk::{SP1ProvingKey, SP1VerifyingKey};
 
 use super::types::Sp1Circuit;
+use crate::compression;
+use crate::events::CacheKind;
+use crate::hasher::HashFunction;
+use crate::persist::{CachePersistence, PersistedEntry};
 
 /// Cache entry for a compiled circuit
 #[derive(Clone, Debug)]
@@ -31,8 +37,10 @@ pub struct CircuitCacheEntry {
 /// Cache entry for a proof
 #[derive(Clone, Debug)]
 pub struct ProofCacheEntry {
-    /// Proof bytes
+    /// Proof bytes, zstd-compressed while stored if compression is enabled
     pub proof: Vec<u8>,
+    /// Size of the proof before compression
+    pub proof_size: usize,
     /// Program hash
     pub program_hash: [u8; 32],
     /// Input hash
@@ -43,6 +51,33 @@ pub struct ProofCacheEntry {
     pub last_access: SystemTime,
     /// Number of times accessed
     pub access_count: u64,
+    /// Time this entry stays valid, overriding the cache-wide maximum age
+    pub ttl: Option<Duration>,
+}
+
+/// Cache entry for the proving and verifying keys of a program
+#[derive(Clone)]
+pub struct KeyCacheEntry {
+    /// Proving key produced by `setup`
+    pub proving_key: Arc<SP1ProvingKey>,
+    /// Verifying key produced by `setup`
+    pub verifying_key: Arc<SP1VerifyingKey>,
+    /// Last access time
+    pub last_access: SystemTime,
+    /// Number of times accessed
+    pub access_count: u64,
+    /// Setup time
+    pub setup_time: Duration,
+}
+
+impl std::fmt::Debug for KeyCacheEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyCacheEntry")
+            .field("last_access", &self.last_access)
+            .field("access_count", &self.access_count)
+            .field("setup_time", &self.setup_time)
+            .finish()
+    }
 }
 
 /// Cache configuration
@@ -52,10 +87,18 @@ pub struct CacheConfig {
     pub max_circuits: usize,
     /// Maximum number of proofs to cache
     pub max_proofs: usize,
+    /// Maximum number of proving/verifying key pairs to cache
+    pub max_keys: usize,
     /// Maximum age of cached items
     pub max_age: Duration,
     /// Whether to enable proof caching
     pub enable_proof_cache: bool,
+    /// Directory cache entries are persisted to so they survive restarts
+    pub persist_path: Option<PathBuf>,
+    /// Whether to store proofs zstd-compressed, in memory and on disk
+    pub compress_proofs: bool,
+    /// Hash function for program hashes and cache keys
+    pub hash_function: HashFunction,
 }
 
 impl Default for CacheConfig {
@@ -63,8 +106,12 @@ impl Default for CacheConfig {
         Self {
             max_circuits: 100,
             max_proofs: 1000,
+            max_keys: 32,
             max_age: Duration::from_secs(3600), // 1 hour
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: HashFunction::Sha256,
         }
     }
 }
@@ -74,20 +121,36 @@ impl Default for CacheConfig {
 pub struct CircuitCache {
     /// Cached compiled circuits
     circuits: RwLock<LruCache<[u8; 32], CircuitCacheEntry>>,
-    /// Cached proofs
+    /// Cached proofs keyed by the combined program and input hash
     proofs: RwLock<LruCache<[u8; 32], ProofCacheEntry>>,
+    /// Cached proving and verifying keys keyed by program hash
+    keys: RwLock<LruCache<[u8; 32], KeyCacheEntry>>,
+    /// Disk persistence, if configured
+    persistence: Option<CachePersistence>,
     /// Cache configuration
     config: CacheConfig,
 }
 
 impl CircuitCache {
-    /// Create a new circuit cache with the given configuration
+    /// Create a new circuit cache with the given configuration.
+    ///
+    /// If a persist path is configured, entries persisted by a previous process are
+    /// reloaded. A persist path that cannot be opened disables persistence.
     pub fn new(config: CacheConfig) -> Self {
-        Self {
+        let persistence = config.persist_path.as_ref().and_then(|path| {
+            CachePersistence::open(path)
+                .map_err(|e| tracing::warn!(path = %path.display(), "cache persistence disabled: {}", e))
+                .ok()
+        });
+        let cache = Self {
             circuits: RwLock::new(LruCache::new(NonZeroUsize::new(config.max_circuits).unwrap())),
             proofs: RwLock::new(LruCache::new(NonZeroUsize::new(config.max_proofs).unwrap())),
+            keys: RwLock::new(LruCache::new(NonZeroUsize::new(config.max_keys).unwrap())),
+            persistence,
             config,
-        }
+        };
+        cache.reload();
+        cache
     }
 
     /// Get circuit bytes from cache
@@ -102,13 +165,15 @@ impl CircuitCache {
                 }
             }
             circuits.pop(&hash);
+            self.unpersist(CacheKind::Circuit, &hash);
         }
         None
     }
 
-    /// Store circuit bytes in cache
-    pub fn store_circuit(&self, program: &[u8], circuit_bytes: Vec<u8>, compile_time: Duration) {
+    /// Store circuit bytes in cache, returning the key of any evicted entry
+    pub fn store_circuit(&self, program: &[u8], circuit_bytes: Vec<u8>, compile_time: Duration) -> Option<[u8; 32]> {
         let hash = self.hash_program(program);
+        self.persist(CacheKind::Circuit, hash, hash, None, compile_time, None, &circuit_bytes);
         let entry = CircuitCacheEntry {
             circuit_bytes,
             hash,
@@ -116,51 +181,155 @@ impl CircuitCache {
             access_count: 1,
             compile_time,
         };
-        self.circuits.write().put(hash, entry);
+        let evicted = self.circuits.write().push(hash, entry)
+            .map(|(key, _)| key)
+            .filter(|key| *key != hash);
+        if let Some(key) = &evicted {
+            self.unpersist(CacheKind::Circuit, key);
+        }
+        evicted
     }
 
-    /// Get proof from cache
+    /// Get proof from cache, decompressed
     pub fn get_proof(&self, program: &[u8], input: &[u8]) -> Option<ProofCacheEntry> {
         if !self.config.enable_proof_cache {
             return None;
         }
 
-        let hash = self.hash_program(program);
+        let key = self.proof_key(program, input);
         let mut proofs = self.proofs.write();
         
-        if let Some(entry) = proofs.get(&hash) {
+        if let Some(entry) = proofs.get(&key) {
             if let Ok(age) = SystemTime::now().duration_since(entry.last_access) {
-                if age < self.config.max_age {
-                    return Some(entry.clone());
+                if age < entry.ttl.unwrap_or(self.config.max_age) {
+                    if !compression::is_compressed(&entry.proof) {
+                        return Some(entry.clone());
+                    }
+                    match compression::decompress(&entry.proof) {
+                        Ok(proof) => return Some(ProofCacheEntry { proof, ..entry.clone() }),
+                        Err(e) => tracing::warn!("dropping undecodable cached proof: {}", e),
+                    }
                 }
             }
-            proofs.pop(&hash);
+            proofs.pop(&key);
+            self.unpersist(CacheKind::Proof, &key);
         }
         None
     }
 
-    /// Store proof in cache
+    /// Time until a cached proof expires, without counting as an access
+    pub fn proof_expires_in(&self, program: &[u8], input: &[u8]) -> Option<Duration> {
+        let key = self.proof_key(program, input);
+        let proofs = self.proofs.read();
+        let entry = proofs.peek(&key)?;
+        let age = SystemTime::now().duration_since(entry.last_access).unwrap_or_default();
+        entry.ttl.unwrap_or(self.config.max_age).checked_sub(age)
+    }
+
+    /// Store proof in cache, returning the key of any evicted entry
     pub fn store_proof(
         &self,
         program: &[u8],
         input: &[u8],
         proof: Vec<u8>,
         generation_time: Duration,
-    ) {
+    ) -> Option<[u8; 32]> {
+        self.store_proof_with_ttl(program, input, proof, generation_time, None)
+    }
+
+    /// Store proof in cache with a per-entry time to live
+    pub fn store_proof_with_ttl(
+        &self,
+        program: &[u8],
+        input: &[u8],
+        proof: Vec<u8>,
+        generation_time: Duration,
+        ttl: Option<Duration>,
+    ) -> Option<[u8; 32]> {
         if !self.config.enable_proof_cache {
-            return;
+            return None;
         }
 
-        let hash = self.hash_program(program);
+        let program_hash = self.hash_program(program);
+        let input_hash = self.hash_program(input);
+        let key = self.combine_hashes(&program_hash, &input_hash);
+        let proof_size = proof.len();
+        let proof = self.compress_proof(proof);
+        self.persist(CacheKind::Proof, key, program_hash, Some(input_hash), generation_time, ttl, &proof);
         let entry = ProofCacheEntry {
             proof,
-            program_hash: hash,
-            input_hash: self.hash_program(input),
+            proof_size,
+            program_hash,
+            input_hash,
             generation_time,
             last_access: SystemTime::now(),
             access_count: 1,
+            ttl,
+        };
+        let evicted = self.proofs.write().push(key, entry)
+            .map(|(evicted, _)| evicted)
+            .filter(|evicted| *evicted != key);
+        if let Some(evicted) = &evicted {
+            self.unpersist(CacheKind::Proof, evicted);
+        }
+        evicted
+    }
+
+    /// Get the proving and verifying keys of a program from cache
+    pub fn get_keys(&self, program: &[u8]) -> Option<(Arc<SP1ProvingKey>, Arc<SP1VerifyingKey>)> {
+        let hash = self.hash_program(program);
+        let mut keys = self.keys.write();
+        
+        if let Some(entry) = keys.get_mut(&hash) {
+            if let Ok(age) = SystemTime::now().duration_since(entry.last_access) {
+                if age < self.config.max_age {
+                    entry.last_access = SystemTime::now();
+                    entry.access_count += 1;
+                    return Some((entry.proving_key.clone(), entry.verifying_key.clone()));
+                }
+            }
+            keys.pop(&hash);
+            self.unpersist(CacheKind::Keys, &hash);
+        }
+        None
+    }
+
+    /// Get the verifying key of a program from cache without counting an access
+    pub fn peek_verifying_key(&self, program: &[u8]) -> Option<Arc<SP1VerifyingKey>> {
+        let hash = self.hash_program(program);
+        let keys = self.keys.read();
+        let entry = keys.peek(&hash)?;
+        let age = SystemTime::now().duration_since(entry.last_access).ok()?;
+        (age < self.config.max_age).then(|| entry.verifying_key.clone())
+    }
+
+    /// Store the proving and verifying keys of a program, returning the key of any evicted entry
+    pub fn store_keys(
+        &self,
+        program: &[u8],
+        proving_key: Arc<SP1ProvingKey>,
+        verifying_key: Arc<SP1VerifyingKey>,
+        setup_time: Duration,
+    ) -> Option<[u8; 32]> {
+        let hash = self.hash_program(program);
+        match bincode::serialize(&(proving_key.as_ref(), verifying_key.as_ref())) {
+            Ok(bytes) => self.persist(CacheKind::Keys, hash, hash, None, setup_time, None, &bytes),
+            Err(e) => tracing::warn!("failed to serialize keys for persistence: {}", e),
+        }
+        let entry = KeyCacheEntry {
+            proving_key,
+            verifying_key,
+            last_access: SystemTime::now(),
+            access_count: 0,
+            setup_time,
         };
-        self.proofs.write().put(hash, entry);
+        let evicted = self.keys.write().push(hash, entry)
+            .map(|(key, _)| key)
+            .filter(|key| *key != hash);
+        if let Some(key) = &evicted {
+            self.unpersist(CacheKind::Keys, key);
+        }
+        evicted
     }
 
     /// Clear expired cache entries
@@ -175,15 +344,27 @@ impl CircuitCache {
             .collect();
         for k in keys_to_remove {
             circuits.pop(&k);
+            self.unpersist(CacheKind::Circuit, &k);
         }
         // Clear expired proofs
         let mut proofs = self.proofs.write();
         let keys_to_remove: Vec<_> = proofs.iter()
-            .filter(|(_, entry)| entry.last_access.elapsed().unwrap() >= max_age)
+            .filter(|(_, entry)| entry.last_access.elapsed().unwrap() >= entry.ttl.unwrap_or(max_age))
             .map(|(k, _)| *k)
             .collect();
         for k in keys_to_remove {
             proofs.pop(&k);
+            self.unpersist(CacheKind::Proof, &k);
+        }
+        // Clear expired keys
+        let mut keys = self.keys.write();
+        let keys_to_remove: Vec<_> = keys.iter()
+            .filter(|(_, entry)| entry.last_access.elapsed().unwrap() >= max_age)
+            .map(|(k, _)| *k)
+            .collect();
+        for k in keys_to_remove {
+            keys.pop(&k);
+            self.unpersist(CacheKind::Keys, &k);
         }
     }
 
@@ -191,29 +372,147 @@ impl CircuitCache {
     pub fn clear_all(&self) {
         self.circuits.write().clear();
         self.proofs.write().clear();
+        self.keys.write().clear();
+        if let Some(persistence) = &self.persistence {
+            if let Err(e) = persistence.clear() {
+                tracing::warn!("failed to clear persisted cache: {}", e);
+            }
+        }
     }
 
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
         let circuits = self.circuits.read();
         let proofs = self.proofs.read();
+        let keys = self.keys.read();
 
         CacheStats {
             circuit_entries: circuits.len(),
             proof_entries: proofs.len(),
+            key_entries: keys.len(),
             max_circuits: self.config.max_circuits,
             max_proofs: self.config.max_proofs,
+            max_keys: self.config.max_keys,
             circuit_hits: circuits.iter().map(|e| e.1.access_count).sum(),
             proof_hits: proofs.iter().map(|e| e.1.access_count).sum(),
+            proof_bytes: proofs.iter().map(|e| e.1.proof_size).sum(),
+            stored_proof_bytes: proofs.iter().map(|e| e.1.proof.len()).sum(),
+            key_hits: keys.iter().map(|e| e.1.access_count).sum(),
+        }
+    }
+
+    /// Reload entries persisted by a previous process
+    fn reload(&self) {
+        let persistence = match &self.persistence {
+            Some(persistence) => persistence,
+            None => return,
+        };
+
+        for (entry, bytes) in persistence.load(CacheKind::Circuit) {
+            if let Some(hash) = entry.key_bytes() {
+                self.circuits.write().push(hash, CircuitCacheEntry {
+                    circuit_bytes: bytes,
+                    hash,
+                    last_access: entry.stored_at,
+                    access_count: 0,
+                    compile_time: entry.generation_time,
+                });
+            }
+        }
+        for (entry, bytes) in persistence.load(CacheKind::Proof) {
+            if let (Some(key), Some(program_hash), Some(input_hash)) =
+                (entry.key_bytes(), entry.program_hash_bytes(), entry.input_hash_bytes())
+            {
+                // Entries are reloaded as persisted, whether compressed or not
+                self.proofs.write().push(key, ProofCacheEntry {
+                    proof_size: compression::decompressed_size(&bytes).unwrap_or(bytes.len()),
+                    proof: bytes,
+                    program_hash,
+                    input_hash,
+                    generation_time: entry.generation_time,
+                    last_access: entry.stored_at,
+                    access_count: 0,
+                    ttl: entry.ttl,
+                });
+            }
+        }
+        for (entry, bytes) in persistence.load(CacheKind::Keys) {
+            let decoded = bincode::deserialize::<(SP1ProvingKey, SP1VerifyingKey)>(&bytes);
+            if let (Some(hash), Ok((proving_key, verifying_key))) = (entry.key_bytes(), decoded) {
+                self.keys.write().push(hash, KeyCacheEntry {
+                    proving_key: Arc::new(proving_key),
+                    verifying_key: Arc::new(verifying_key),
+                    last_access: entry.stored_at,
+                    access_count: 0,
+                    setup_time: entry.generation_time,
+                });
+            }
         }
     }
 
+    /// Compress a proof for storage if compression is enabled
+    fn compress_proof(&self, proof: Vec<u8>) -> Vec<u8> {
+        if !self.config.compress_proofs {
+            return proof;
+        }
+        match compression::compress(&proof) {
+            Ok(compressed) => compressed,
+            Err(e) => {
+                tracing::warn!("storing proof uncompressed: {}", e);
+                proof
+            }
+        }
+    }
+
+    /// Persist an entry if persistence is configured
+    #[allow(clippy::too_many_arguments)]
+    fn persist(
+        &self,
+        kind: CacheKind,
+        key: [u8; 32],
+        program_hash: [u8; 32],
+        input_hash: Option<[u8; 32]>,
+        generation_time: Duration,
+        ttl: Option<Duration>,
+        contents: &[u8],
+    ) {
+        if let Some(persistence) = &self.persistence {
+            let entry = PersistedEntry {
+                kind,
+                key: hex::encode(key),
+                blob: String::new(),
+                program_hash: hex::encode(program_hash),
+                input_hash: input_hash.map(hex::encode),
+                generation_time,
+                stored_at: SystemTime::now(),
+                ttl,
+            };
+            if let Err(e) = persistence.persist(entry, contents) {
+                tracing::warn!("failed to persist cache entry: {}", e);
+            }
+        }
+    }
+
+    /// Remove a persisted entry if persistence is configured
+    fn unpersist(&self, kind: CacheKind, key: &[u8; 32]) {
+        if let Some(persistence) = &self.persistence {
+            if let Err(e) = persistence.remove(kind, key) {
+                tracing::warn!("failed to remove persisted cache entry: {}", e);
+            }
+        }
+    }
+
+    /// Proof cache key covering both the program and its input
+    fn proof_key(&self, program: &[u8], input: &[u8]) -> [u8; 32] {
+        self.combine_hashes(&self.hash_program(program), &self.hash_program(input))
+    }
+
+    fn combine_hashes(&self, program_hash: &[u8; 32], input_hash: &[u8; 32]) -> [u8; 32] {
+        self.config.hash_function.combine(program_hash, input_hash)
+    }
+
     fn hash_program(&self, program: &[u8]) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(program);
-        let mut hash = [0u8; 32];
-        hash.copy_from_slice(&hasher.finalize());
-        hash
+        self.config.hash_function.hash(program)
     }
 }
 
@@ -224,12 +523,33 @@ pub struct CacheStats {
     pub circuit_entries: usize,
     /// Number of cached proofs
     pub proof_entries: usize,
+    /// Number of cached key pairs
+    pub key_entries: usize,
     /// Maximum number of circuits
     pub max_circuits: usize,
     /// Maximum number of proofs
     pub max_proofs: usize,
+    /// Maximum number of key pairs
+    pub max_keys: usize,
     /// Total number of circuit cache hits
     pub circuit_hits: u64,
     /// Total number of proof cache hits
     pub proof_hits: u64,
-} 
\ No newline at end of file
+    /// Total size of the cached proofs before compression
+    pub proof_bytes: usize,
+    /// Total size of the cached proofs as stored
+    pub stored_proof_bytes: usize,
+    /// Total number of key cache hits
+    pub key_hits: u64,
+} 
+
+impl CacheStats {
+    /// Ratio of the stored to the uncompressed size of the cached proofs, 1.0 if
+    /// no proofs are cached
+    pub fn compression_ratio(&self) -> f64 {
+        if self.proof_bytes == 0 {
+            return 1.0;
+        }
+        self.stored_proof_bytes as f64 / self.proof_bytes as f64
+    }
+}
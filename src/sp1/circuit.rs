@@ -9,6 +9,7 @@ use sp1_sdk::{
 };
 use serde::Deserialize;
 use crate::error::ZkError;
+use crate::hasher::HashFunction;
 use crate::sp1::types::Sp1Circuit;
 use std::path::Path;
 
@@ -18,10 +19,12 @@ pub struct MessageVerifyCircuit {
     message: Vec<u8>,
     /// Expected hash
     expected_hash: [u8; 32],
+    /// Function the message is hashed with
+    hash_function: HashFunction,
 }
 
 impl MessageVerifyCircuit {
-    /// Create a new message verification circuit
+    /// Create a new message verification circuit hashing with SHA-256
     pub fn new(message: Vec<u8>, expected_hash: [u8; 32]) -> Result<Self, ZkError> {
         if message.is_empty() {
             return Err(ZkError::InvalidInput("message cannot be empty".to_string()));
@@ -29,26 +32,50 @@ impl MessageVerifyCircuit {
         Ok(Self {
             message,
             expected_hash,
+            hash_function: HashFunction::Sha256,
         })
     }
 
+    /// Hash the message with `hash_function`: SHA-256, Keccak-256, Blake2b or BLAKE3
+    pub fn with_hash_function(mut self, hash_function: HashFunction) -> Result<Self, ZkError> {
+        if hash_function.message_hash_id().is_none() {
+            return Err(ZkError::InvalidInput(format!("Message verification does not support {}", hash_function)));
+        }
+        self.hash_function = hash_function;
+        Ok(self)
+    }
+
+    /// Function the message is hashed with
+    pub fn hash_function(&self) -> HashFunction {
+        self.hash_function
+    }
+
+    /// Identifier of the hash function in programs and guest input
+    fn hash_function_id(&self) -> u8 {
+        self.hash_function.message_hash_id().expect("checked on construction")
+    }
+
     /// Get the program bytes for this circuit
     fn get_program_bytes(&self) -> Vec<u8> {
         // Program format:
         // [0]     - Circuit type identifier (0x01 for MessageVerify)
         // [1..33] - Expected hash
-        let mut program = Vec::with_capacity(33);
+        // [33]    - Hash function identifier, omitted for SHA-256
+        let mut program = Vec::with_capacity(34);
         program.push(0x01); // Circuit type 1
         program.extend_from_slice(&self.expected_hash);
+        if self.hash_function != HashFunction::Sha256 {
+            program.push(self.hash_function_id());
+        }
         program
     }
 }
 
 impl Sp1Circuit for MessageVerifyCircuit {
     fn prove(&self, prover: &CpuProver) -> Vec<u8> {
-        // Create stdin and write message
+        // Create stdin and write the hash function and message
         let mut stdin = SP1Stdin::new();
-        stdin.write_slice(&self.message);
+        stdin.write_slice(&[&[self.hash_function_id()][..], &self.message].concat());
         
         // Get program bytes
         let program = self.get_program_bytes();
@@ -0,0 +1,73 @@
+//! Conversion of SP1 proofs into more succinct modes
+//!
+//! Proving in the core mode and wrapping later lets the expensive part run when
+//! provers are idle and the wrapping happen just before a proof is submitted
+//! on-chain. A core proof is compressed by recursion, a compressed proof is shrunk
+//! and wrapped over BN254 and then into a Groth16 or PLONK SNARK, the same steps
+//! the prover takes when asked for a mode directly.
+
+use sp1_sdk::install::try_install_circuit_artifacts;
+use sp1_sdk::{CpuProver, Prover, SP1Proof, SP1ProofWithPublicValues, SP1VerifyingKey};
+use sp1_prover::{SP1CoreProof, SP1CoreProofData};
+
+use crate::error::ZkError;
+use crate::metadata::ProofMode;
+
+use super::verifier::proof_mode;
+
+/// Convert a proof into the more succinct `target` mode
+///
+/// A proof already in `target` is returned unchanged. Only core and compressed
+/// proofs can be converted, and never into a larger mode.
+pub fn convert_proof(
+    client: &CpuProver,
+    verifying_key: &SP1VerifyingKey,
+    proof: SP1ProofWithPublicValues,
+    target: ProofMode,
+) -> Result<SP1ProofWithPublicValues, ZkError> {
+    let mode = proof_mode(&proof);
+    if mode == target {
+        return Ok(proof);
+    }
+    if mode > target || mode.is_onchain_verifiable() {
+        return Err(ZkError::InvalidInput(format!("Cannot convert a {:?} proof into {:?}", mode, target)));
+    }
+
+    let prover = client.inner();
+    let convert_error = |e: &dyn std::fmt::Display| ZkError::ProofGeneration(format!("Failed to convert proof: {}", e));
+
+    // Recurse the core shards into a single compressed proof
+    let compressed = match proof.proof {
+        SP1Proof::Core(shards) => {
+            let core = SP1CoreProof {
+                proof: SP1CoreProofData(shards),
+                stdin: proof.stdin.clone(),
+                public_values: proof.public_values.clone(),
+                cycles: 0,
+            };
+            let deferred = proof.stdin.proofs.iter().map(|(reduce_proof, _)| reduce_proof.clone()).collect();
+            prover.compress(verifying_key, core, deferred, Default::default()).map_err(|e| convert_error(&e))?
+        }
+        SP1Proof::Compressed(compressed) => *compressed,
+        _ => unreachable!("on-chain proofs are rejected above"),
+    };
+    let converted = match target {
+        ProofMode::Core => unreachable!("core is the largest mode"),
+        ProofMode::Compressed => SP1Proof::Compressed(Box::new(compressed)),
+        ProofMode::Plonk | ProofMode::Groth16 => {
+            let shrunk = prover.shrink(compressed, Default::default()).map_err(|e| convert_error(&e))?;
+            let outer = prover.wrap_bn254(shrunk, Default::default()).map_err(|e| convert_error(&e))?;
+            if target == ProofMode::Plonk {
+                let artifacts = try_install_circuit_artifacts("plonk");
+                SP1Proof::Plonk(prover.wrap_plonk_bn254(outer, &artifacts))
+            } else {
+                let artifacts = try_install_circuit_artifacts("groth16");
+                SP1Proof::Groth16(prover.wrap_groth16_bn254(outer, &artifacts))
+            }
+        }
+    };
+    Ok(SP1ProofWithPublicValues {
+        proof: converted,
+        ..proof
+    })
+}
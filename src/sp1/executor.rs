@@ -0,0 +1,147 @@
+//! Blocking execution layer for the SP1 prover
+//!
+//! SP1 `setup` and `prove` are CPU-bound and can run for minutes. Running them
+//! inside an async task starves the tokio executor, so the [`ProvingExecutor`]
+//! moves them onto the blocking thread pool and bounds how many run at once.
+//! Running tasks also hold their share of the process-wide [`crate::threads`] CPU
+//! budget, which the SP1 and RISC0 backends draw from together.
+//!
+//! A blocking task cannot be interrupted once it runs. Cancelling a task returns
+//! control to the caller right away; the task's result is discarded and its permit
+//! stays held until the prover actually finishes.
+//!
+//! Tasks run inside the span of their caller, so the spans they open nest under the
+//! request that submitted them.
+
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, RwLock, Semaphore};
+use frostgate_zkip::ResourceUsage;
+
+use crate::error::ZkError;
+use crate::options::CancellationToken;
+use crate::threads::{self, CpuPermit};
+
+/// Runs prover work on the blocking thread pool with bounded concurrency
+#[derive(Debug, Clone)]
+pub struct ProvingExecutor {
+    /// Permits for concurrently running prover tasks
+    permits: Arc<Semaphore>,
+    /// Resource usage updated while tasks wait for a permit
+    resources: Arc<RwLock<ResourceUsage>>,
+    /// Threads of the CPU budget each task holds
+    threads_per_task: usize,
+}
+
+impl ProvingExecutor {
+    /// Create an executor running at most `max_concurrent` tasks at once
+    pub fn new(max_concurrent: usize, resources: Arc<RwLock<ResourceUsage>>) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            resources,
+            threads_per_task: 1,
+        }
+    }
+
+    /// Hold `threads` threads of the CPU budget for each running task
+    pub fn with_threads_per_task(mut self, threads: usize) -> Self {
+        self.threads_per_task = threads;
+        self
+    }
+
+    /// Number of tasks that can start without waiting
+    pub fn available_permits(&self) -> usize {
+        self.permits.available_permits()
+    }
+
+    /// Run a task on the blocking thread pool once a permit is available
+    pub async fn run<F, T>(&self, task: F) -> Result<T, ZkError>
+    where
+        F: FnOnce() -> Result<T, ZkError> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.run_cancellable(task, &CancellationToken::new()).await
+    }
+
+    /// Run a task like [`Self::run`], giving up with [`ZkError::Cancelled`] once
+    /// `cancel` fires
+    pub async fn run_cancellable<F, T>(&self, task: F, cancel: &CancellationToken) -> Result<T, ZkError>
+    where
+        F: FnOnce() -> Result<T, ZkError> + Send + 'static,
+        T: Send + 'static,
+    {
+        // Tasks waiting for a permit count towards the queue depth
+        self.resources.write().await.queue_depth += 1;
+        let permits = self.acquire(cancel).await;
+        self.resources.write().await.queue_depth -= 1;
+        let (permit, cpu) = permits?;
+
+        let task_cancel = cancel.clone();
+        let span = tracing::Span::current();
+        let handle = tokio::task::spawn_blocking(move || {
+            let _permits = (permit, cpu);
+            let _span = span.enter();
+            // Skip tasks cancelled while queued on the blocking pool
+            if task_cancel.is_cancelled() {
+                return Err(ZkError::Cancelled);
+            }
+            task()
+        });
+        tokio::select! {
+            result = handle => result
+                .map_err(|e| ZkError::Backend(format!("Proving task failed: {}", e)))?,
+            _ = cancel.cancelled() => Err(ZkError::Cancelled),
+        }
+    }
+
+    /// Wait for an executor permit, then for the task's share of the CPU budget
+    async fn acquire(&self, cancel: &CancellationToken) -> Result<(OwnedSemaphorePermit, CpuPermit), ZkError> {
+        let permit = tokio::select! {
+            permit = self.permits.clone().acquire_owned() => permit
+                .map_err(|e| ZkError::Backend(format!("Executor closed: {}", e)))?,
+            _ = cancel.cancelled() => return Err(ZkError::Cancelled),
+        };
+        let cpu = threads::budget().acquire(self.threads_per_task, cancel).await?;
+        Ok((permit, cpu))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_releases_queue_slot() {
+        let resources = Arc::new(RwLock::new(ResourceUsage {
+            cpu_usage: 0.0,
+            memory_usage: 0,
+            active_tasks: 0,
+            max_concurrent: 1,
+            queue_depth: 0,
+        }));
+        let executor = ProvingExecutor::new(1, resources.clone());
+
+        // Occupy the only permit, then cancel a task waiting behind it
+        let busy = executor.clone();
+        let running = tokio::spawn(async move {
+            busy.run(|| {
+                std::thread::sleep(Duration::from_millis(200));
+                Ok(())
+            }).await
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let cancel = CancellationToken::new();
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            canceller.cancel();
+        });
+        let result = executor.run_cancellable(|| Ok(()), &cancel).await;
+        assert!(matches!(result, Err(ZkError::Cancelled)));
+        assert_eq!(resources.read().await.queue_depth, 0);
+
+        running.await.unwrap().unwrap();
+        assert_eq!(executor.available_permits(), 1);
+    }
+}
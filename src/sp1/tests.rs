@@ -5,7 +5,9 @@
 
 use super::*;
 use super::backend::DebugCpuProver;
-use frostgate_zkip::{ZkBackend, ZkBackendExt};
+use super::cache::CircuitCache;
+use frostgate_zkip::{HealthStatus, ZkBackend, ZkBackendExt};
+use sp1_sdk::HashableKey;
 use sha2::{Sha256, Digest};
 use serde_json::json;
 use std::time::Duration;
@@ -18,7 +20,24 @@ impl Clone for Sp1Backend {
             resources: self.resources.clone(),
             options: self.options.clone(),
             cache: self.cache.clone(),
-            client: DebugCpuProver::new(),
+            client: self.client.clone(),
+            executor: self.executor.clone(),
+            events: self.events.clone(),
+            last_health: self.last_health.clone(),
+            profiles: self.profiles.clone(),
+            failures: self.failures.clone(),
+            program_stats: self.program_stats.clone(),
+            latency: self.latency.clone(),
+            refresh: self.refresh.clone(),
+            probe: self.probe.clone(),
+            in_flight: self.in_flight.clone(),
+            aggregation_program: self.aggregation_program.clone(),
+            bridge_program: self.bridge_program.clone(),
+            programs: self.programs.clone(),
+            circuit_elfs: self.circuit_elfs.clone(),
+            versions: self.versions.clone(),
+            replay_guard: self.replay_guard.clone(),
+            stats_path: self.stats_path.clone(),
         }
     }
 }
@@ -50,7 +69,40 @@ async fn test_message_verification() {
         .expect("Verification failed");
     
     assert!(result, "Proof verification should succeed");
-    assert_eq!(metadata.program_hash, hex::encode(program));
+    assert_eq!(metadata.program_hash, hex::encode(crate::hasher::HashFunction::Sha256.hash(&program)));
+}
+
+#[test]
+fn test_program_hash_follows_profile() {
+    use crate::hasher::HashFunction;
+    use crate::profiles::{CircuitProfile, MESSAGE_VERIFY};
+
+    let backend = Sp1Backend::new();
+    let program = vec![MESSAGE_VERIFY; 33];
+    assert_eq!(backend.program_hash(&program), hex::encode(HashFunction::Sha256.hash(&program)));
+
+    // Circuits verified on EVM chains identify programs by their Keccak-256 hash
+    backend.profiles().register(MESSAGE_VERIFY, CircuitProfile {
+        hash_function: HashFunction::Keccak256,
+        ..CircuitProfile::default()
+    });
+    assert_eq!(backend.program_hash(&program), hex::encode(HashFunction::Keccak256.hash(&program)));
+}
+
+#[test]
+fn test_message_hash_function() {
+    use crate::hasher::HashFunction;
+    use super::circuit::MessageVerifyCircuit;
+
+    let message = b"Hello, World!".to_vec();
+    let hash = HashFunction::Keccak256.hash(&message);
+    let circuit = MessageVerifyCircuit::new(message.clone(), hash).unwrap();
+    assert_eq!(circuit.program().len(), 33);
+
+    // Keccak-256 programs append the function's identifier
+    let circuit = circuit.with_hash_function(HashFunction::Keccak256).unwrap();
+    assert_eq!(circuit.program()[33], HashFunction::Keccak256.message_hash_id().unwrap());
+    assert!(MessageVerifyCircuit::new(message, hash).unwrap().with_hash_function(HashFunction::Poseidon).is_err());
 }
 
 #[tokio::test]
@@ -130,6 +182,14 @@ async fn test_batch_operations() {
     
     assert_eq!(results.len(), messages.len());
     assert!(results.iter().all(|&r| r), "All proofs should verify");
+
+    // A malformed proof fails only its own item
+    let mut verifications = verifications;
+    verifications.push((programs[0].as_slice(), &[1u8, 2, 3][..]));
+    let results = backend.batch_verify_detailed(&verifications).await.unwrap();
+    assert!(results[..messages.len()].iter().all(|result| result.is_valid));
+    assert!(!results[messages.len()].is_valid);
+    assert!(results[messages.len()].error.is_some());
 }
 
 #[tokio::test]
@@ -173,6 +233,25 @@ async fn test_resource_tracking() {
     assert_eq!(final_usage.queue_depth, 0);
 }
 
+#[tokio::test]
+async fn test_stats_persistence() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("stats.json");
+    let backend = Sp1Backend::new().with_stats_persistence(&path);
+    let mut snapshot = backend.stats_snapshot().await;
+    snapshot.total_proofs = 3;
+    backend.restore_stats(&snapshot).await;
+    backend.persist_stats().await.unwrap();
+
+    // A restarted backend resumes from the saved statistics
+    let restarted = Sp1Backend::new().with_stats_persistence(&path);
+    assert_eq!(restarted.stats_snapshot().await.total_proofs, 3);
+
+    // Resetting hands back the interval that just ended
+    assert_eq!(restarted.reset_stats().await.total_proofs, 3);
+    assert_eq!(restarted.stats_snapshot().await.total_proofs, 0);
+}
+
 #[tokio::test]
 async fn test_circuit_caching() {
     let backend = Sp1Backend::with_config(
@@ -180,8 +259,12 @@ async fn test_circuit_caching() {
         CacheConfig {
             max_circuits: 10,
             max_proofs: 10,
+            max_keys: 10,
             max_age: Duration::from_secs(60),
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: crate::hasher::HashFunction::Sha256,
         },
     );
     
@@ -221,8 +304,12 @@ async fn test_proof_caching() {
         CacheConfig {
             max_circuits: 10,
             max_proofs: 10,
+            max_keys: 10,
             max_age: Duration::from_secs(60),
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: crate::hasher::HashFunction::Sha256,
         },
     );
     
@@ -262,8 +349,12 @@ async fn test_cache_expiration() {
         CacheConfig {
             max_circuits: 10,
             max_proofs: 10,
+            max_keys: 10,
             max_age: Duration::from_millis(100), // Very short expiration
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: crate::hasher::HashFunction::Sha256,
         },
     );
     
@@ -306,8 +397,12 @@ async fn test_cache_limits() {
         CacheConfig {
             max_circuits: 2,
             max_proofs: 2,
+            max_keys: 2,
             max_age: Duration::from_secs(60),
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: crate::hasher::HashFunction::Sha256,
         },
     );
     
@@ -344,8 +439,12 @@ async fn test_cache_clear() {
         CacheConfig {
             max_circuits: 10,
             max_proofs: 10,
+            max_keys: 10,
             max_age: Duration::from_secs(60),
             enable_proof_cache: true,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: crate::hasher::HashFunction::Sha256,
         },
     );
     
@@ -381,4 +480,340 @@ async fn test_cache_clear() {
     assert_eq!(stats.proof_entries, 1); // New entry after clear
     assert_eq!(stats.circuit_hits, 0); // No hits after clear
     assert_eq!(stats.proof_hits, 0); // No hits after clear
-} 
\ No newline at end of file
+} 
+#[tokio::test]
+async fn test_proof_size_budget() {
+    let backend = Sp1Backend::new();
+    
+    // Create test message
+    let message = b"Hello, World!";
+    let mut hasher = Sha256::new();
+    hasher.update(message);
+    let expected_hash: [u8; 32] = hasher.finalize().as_slice().try_into().unwrap();
+    
+    // Create program bytes
+    let mut program = Vec::with_capacity(33);
+    program.push(0x01); // Circuit type 1
+    program.extend_from_slice(&expected_hash);
+    
+    // No proof mode fits in a single byte
+    let options = crate::options::ProveOptions::default().with_max_proof_size(1);
+    let result = backend.prove_with_options(&program, message, &options).await;
+    assert!(matches!(result, Err(crate::error::ZkError::ProofTooLarge { limit: 1, .. })));
+    
+    // A generous budget is met without escalation
+    let options = crate::options::ProveOptions::default().with_max_proof_size(usize::MAX);
+    let (proof, metadata) = backend.prove_with_options(&program, message, &options).await.unwrap();
+    assert!(!metadata.escalated());
+    assert_eq!(metadata.base.proof_size, proof.len());
+}
+
+#[tokio::test]
+async fn test_prove_timeout_and_cancellation() {
+    let backend = Sp1Backend::new();
+    
+    let message = b"Hello, World!";
+    let mut hasher = Sha256::new();
+    hasher.update(message);
+    let expected_hash: [u8; 32] = hasher.finalize().as_slice().try_into().unwrap();
+    
+    let mut program = Vec::with_capacity(33);
+    program.push(0x01); // Circuit type 1
+    program.extend_from_slice(&expected_hash);
+    
+    // A proof cannot finish within a millisecond
+    let options = crate::options::ProveOptions::default().with_timeout(Duration::from_millis(1));
+    let result = backend.prove_with_options(&program, message, &options).await;
+    assert!(matches!(result, Err(crate::error::ZkError::Timeout(_))));
+    
+    // A cancelled request fails without proving
+    let cancel = crate::options::CancellationToken::new();
+    cancel.cancel();
+    let options = crate::options::ProveOptions::default();
+    let result = backend.prove_cancellable(&program, message, &options, &cancel).await;
+    assert!(matches!(result, Err(crate::error::ZkError::Cancelled)));
+    
+    // Counters are released in both cases
+    let usage = backend.resource_usage();
+    assert_eq!(usage.active_tasks, 0);
+    assert_eq!(usage.queue_depth, 0);
+}
+
+#[tokio::test]
+async fn test_prove_with_progress() {
+    let backend = Sp1Backend::new();
+    
+    let message = b"Hello, World!";
+    let mut hasher = Sha256::new();
+    hasher.update(message);
+    let expected_hash: [u8; 32] = hasher.finalize().as_slice().try_into().unwrap();
+    
+    let mut program = Vec::with_capacity(33);
+    program.push(0x01); // Circuit type 1
+    program.extend_from_slice(&expected_hash);
+    
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let options = crate::options::ProveOptions::default();
+    let (proof, _) = backend.prove_with_progress(&program, message, &options, sender).await.unwrap();
+    
+    let mut events = Vec::new();
+    while let Ok(event) = receiver.try_recv() {
+        events.push(event);
+    }
+    assert_eq!(events.first(), Some(&crate::progress::ProveProgress::SetupDone { cached: false }));
+    assert!(events.iter().any(|event| matches!(event, crate::progress::ProveProgress::Executed { cycles: Some(_), .. })));
+    assert_eq!(events.last(), Some(&crate::progress::ProveProgress::Serialized { proof_size: proof.len() }));
+}
+
+#[tokio::test]
+async fn test_batch_prove_stream() {
+    use futures::StreamExt;
+    let backend = Sp1Backend::new();
+    
+    let messages: [&[u8]; 2] = [b"first", b"second"];
+    let programs: Vec<Vec<u8>> = messages.iter().map(|message| {
+        let mut program = vec![0x01];
+        program.extend_from_slice(&Sha256::digest(message));
+        program
+    }).collect();
+    let batch: Vec<(&[u8], &[u8])> = programs.iter()
+        .zip(messages)
+        .map(|(program, message)| (program.as_slice(), message))
+        .collect();
+    
+    let mut results: Vec<_> = backend.batch_prove_stream(&batch).collect().await;
+    results.sort_by_key(|(index, _)| *index);
+    assert_eq!(results.len(), 2);
+    for (index, result) in results {
+        let (proof, _) = result.unwrap();
+        assert!(backend.verify(batch[index].0, &proof, None).await.unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_onchain_proof_requires_groth16() {
+    let backend = Sp1Backend::new();
+
+    // Create test message
+    let message = b"Hello, World!";
+    let mut hasher = Sha256::new();
+    hasher.update(message);
+    let expected_hash: [u8; 32] = hasher.finalize().as_slice().try_into().unwrap();
+
+    // Create program bytes
+    let mut program = Vec::with_capacity(33);
+    program.push(0x01); // Circuit type 1
+    program.extend_from_slice(&expected_hash);
+
+    // Core proofs have no on-chain encoding
+    let (proof, metadata) = backend.prove(&program, message, None).await.unwrap();
+    assert!(matches!(backend.onchain_proof(&proof), Err(crate::error::ZkError::InvalidInput(_))));
+}
+
+#[tokio::test]
+async fn test_domain_binding() {
+    use crate::domain::{ChainId, DomainSeparator};
+
+    let backend = Sp1Backend::new();
+
+    // Create test message
+    let message = b"Hello, World!";
+    let mut hasher = Sha256::new();
+    hasher.update(message);
+    let expected_hash: [u8; 32] = hasher.finalize().as_slice().try_into().unwrap();
+
+    // Create program bytes
+    let mut program = Vec::with_capacity(33);
+    program.push(0x01); // Circuit type 1
+    program.extend_from_slice(&expected_hash);
+
+    // Prove a message from chain 1 to chain 2
+    let domain = DomainSeparator::new(ChainId(1), ChainId(2));
+    let options = crate::options::ProveOptions::default().with_domain(domain);
+    let (proof, metadata) = backend.prove_with_options(&program, message, &options).await.unwrap();
+    assert!(backend.verify_with_domain(&program, &proof, domain).await.unwrap());
+
+    // The committed outputs are reported in the metadata
+    assert_eq!(metadata.committed_hash(), Some(expected_hash));
+    assert_eq!(metadata.domain(), Some(domain));
+    assert_eq!(metadata.block_number(), None);
+
+    // The same proof is rejected for another destination
+    let other = DomainSeparator::new(ChainId(1), ChainId(3));
+    let result = backend.verify_with_domain(&program, &proof, other).await;
+    assert!(matches!(result, Err(crate::error::ZkError::DomainMismatch { .. })));
+}
+
+#[test]
+fn test_proof_cache_distinct_inputs() {
+    let cache = CircuitCache::new(CacheConfig::default());
+    let program = vec![0x01; 33];
+    
+    // Two inputs to the same program get separate entries
+    cache.store_proof(&program, b"input 1", vec![1], Duration::from_millis(10));
+    cache.store_proof(&program, b"input 2", vec![2], Duration::from_millis(10));
+    assert_eq!(cache.get_proof(&program, b"input 1").unwrap().proof, vec![1]);
+    assert_eq!(cache.get_proof(&program, b"input 2").unwrap().proof, vec![2]);
+    
+    // An input that was never proven misses
+    assert!(cache.get_proof(&program, b"input 3").is_none());
+    assert_eq!(cache.stats().proof_entries, 2);
+}
+
+#[test]
+fn test_proof_cache_distinct_programs() {
+    let cache = CircuitCache::new(CacheConfig::default());
+    let input = b"shared input";
+    
+    cache.store_proof(&[0x01; 33], input, vec![1], Duration::from_millis(10));
+    assert!(cache.get_proof(&[0x02; 33], input).is_none());
+    
+    // Re-storing the same program and input replaces the entry
+    cache.store_proof(&[0x01; 33], input, vec![3], Duration::from_millis(10));
+    assert_eq!(cache.get_proof(&[0x01; 33], input).unwrap().proof, vec![3]);
+    assert_eq!(cache.stats().proof_entries, 1);
+}
+
+#[test]
+fn test_proof_cache_survives_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = CacheConfig {
+        persist_path: Some(dir.path().to_path_buf()),
+        ..CacheConfig::default()
+    };
+    let program = vec![0x01; 33];
+    
+    let cache = CircuitCache::new(config.clone());
+    cache.store_proof(&program, b"input", vec![1, 2, 3], Duration::from_millis(10));
+    drop(cache);
+    
+    // A new cache over the same directory reloads the proof
+    let cache = CircuitCache::new(config.clone());
+    assert_eq!(cache.get_proof(&program, b"input").unwrap().proof, vec![1, 2, 3]);
+    
+    // Clearing the cache also clears what is on disk
+    cache.clear_all();
+    drop(cache);
+    assert!(CircuitCache::new(config).get_proof(&program, b"input").is_none());
+}
+
+#[test]
+fn test_proof_cache_compression() {
+    let program = vec![0x01; 33];
+    let proof: Vec<u8> = (0..64 * 1024).map(|i| (i % 13) as u8).collect();
+
+    let cache = CircuitCache::new(CacheConfig::default());
+    cache.store_proof(&program, b"input", proof.clone(), Duration::from_millis(10));
+    assert_eq!(cache.get_proof(&program, b"input").unwrap().proof, proof);
+    let stats = cache.stats();
+    assert_eq!(stats.proof_bytes, proof.len());
+    assert!(stats.stored_proof_bytes < proof.len());
+    assert!(stats.compression_ratio() < 1.0);
+
+    let cache = CircuitCache::new(CacheConfig { compress_proofs: false, ..CacheConfig::default() });
+    cache.store_proof(&program, b"input", proof.clone(), Duration::from_millis(10));
+    assert_eq!(cache.get_proof(&program, b"input").unwrap().proof, proof);
+    assert_eq!(cache.stats().stored_proof_bytes, proof.len());
+}
+
+#[tokio::test]
+async fn test_key_cache_reused() {
+    let backend = Sp1Backend::with_config(
+        Sp1Options::default(),
+        CacheConfig {
+            max_circuits: 10,
+            max_proofs: 10,
+            max_keys: 10,
+            max_age: Duration::from_secs(60),
+            enable_proof_cache: false,
+            persist_path: None,
+            compress_proofs: true,
+            hash_function: crate::hasher::HashFunction::Sha256,
+        },
+    );
+    
+    // Create test message
+    let message = b"Hello, World!";
+    let mut hasher = Sha256::new();
+    hasher.update(message);
+    let expected_hash: [u8; 32] = hasher.finalize().as_slice().try_into().unwrap();
+    
+    // Create program bytes
+    let mut program = Vec::with_capacity(33);
+    program.push(0x01); // Circuit type 1
+    program.extend_from_slice(&expected_hash);
+    
+    // Prove twice and verify; setup runs only once
+    let (proof, metadata) = backend.prove_with_options(&program, message, &crate::options::ProveOptions::default()).await.unwrap();
+    backend.prove(&program, message, None).await.unwrap();
+    assert!(backend.verify(&program, &proof, None).await.unwrap());
+    
+    // Only the second proof and the verification read the cached keys
+    let stats = backend.cache.stats();
+    assert_eq!(stats.key_entries, 1);
+    assert_eq!(stats.key_hits, 2);
+    let verifying_key = backend.cache.peek_verifying_key(&program).unwrap();
+    assert_eq!(metadata.vkey_hash, Some(verifying_key.bytes32_raw()));
+}
+
+#[tokio::test]
+async fn test_aggregate_proofs_requires_compressed_proofs() {
+    let backend = Sp1Backend::new();
+    let program = vec![0x01; 33];
+    assert!(backend.aggregate_proofs(&[(&program, b"proof")]).await.is_err());
+
+    let backend = backend.with_aggregation_program(vec![0x7F; 64]);
+    assert!(matches!(
+        backend.aggregate_proofs(&[]).await,
+        Err(crate::error::ZkError::InvalidInput(_))
+    ));
+    assert!(backend.aggregate_proofs(&[(&program, b"proof")]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_bridge_rejects_invalid_receipts() {
+    let backend = Sp1Backend::new();
+    assert!(backend.bridge_risc0_receipt(b"receipt", [0u8; 32]).await.is_err());
+
+    let backend = backend.with_bridge_program(vec![0x7F; 64]);
+    assert!(matches!(
+        backend.bridge_risc0_receipt(b"receipt", [0u8; 32]).await,
+        Err(crate::error::ZkError::InvalidInput(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_register_program() {
+    let backend = Sp1Backend::new();
+    assert!(backend.register_program("", b"\x7fELF".to_vec(), |_: &[u8]| true).is_err());
+    assert!(backend.register_program("custom", vec![0xAB; 64], |_: &[u8]| true).is_err());
+
+    let program = backend.register_program("custom", b"\x7fELF custom".to_vec(), |_: &[u8]| true).unwrap();
+    assert_eq!(backend.programs.resolve(&program).unwrap().unwrap().name, "custom");
+
+    // Unregistered programs are rejected before proving
+    backend.programs.unregister("custom");
+    assert!(backend.prove(&program, b"input", None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_descriptor_programs_need_a_guest() {
+    let backend = Sp1Backend::new();
+    let program = crate::descriptor::ProgramDescriptor::new(crate::profiles::MESSAGE_VERIFY, [0u8; 32]).encode();
+    assert!(matches!(
+        backend.prove_with_options(&program, b"input", &crate::options::ProveOptions::default()).await,
+        Err(crate::error::ZkError::InvalidProgram(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_health_probe_reports_missing_guest() {
+    // Without a message verification guest in the manifest the built-in probe fails
+    let policy = crate::probe::ProbePolicy { unhealthy_after: 2, ..Default::default() };
+    let backend = Sp1Backend::new().with_health_probe(policy);
+    let result = backend.run_health_probe().await.unwrap();
+    assert!(result.error.unwrap().contains("No SP1 guest"));
+    assert!(matches!(backend.health_check().await, HealthStatus::Degraded(_)));
+    backend.run_health_probe().await;
+    assert!(matches!(backend.health_check().await, HealthStatus::Unhealthy(reason) if reason.contains("No SP1 guest")));
+}
@@ -3,9 +3,11 @@
 
 //! Type definitions for SP1 backend
 
+use std::time::Duration;
 use serde::{Serialize, Deserialize};
 use sp1_sdk::{CpuProver, SP1Stdin, SP1ProofWithPublicValues};
 use crate::error::ZkError;
+use crate::metadata::ProofMode;
 // use sp1_core::SP1Verifier;
 
 /// SP1 circuit trait
@@ -29,6 +31,21 @@ pub struct Sp1Options {
     pub memory_limit: Option<usize>,
     /// Custom parameters
     pub custom_params: Option<Vec<u8>>,
+    /// Default proof mode: core, compressed, PLONK or Groth16
+    #[serde(default)]
+    pub proof_mode: ProofMode,
+    /// Default maximum acceptable proof size in bytes
+    #[serde(default)]
+    pub max_proof_size: Option<usize>,
+    /// Default maximum time a proof may take
+    #[serde(default)]
+    pub prove_timeout: Option<Duration>,
+    /// Maximum number of proofs of a batch verified at once, `num_threads` if unset
+    #[serde(default)]
+    pub max_batch_parallelism: Option<usize>,
+    /// Time proving and verification latencies are retained for, an hour if unset
+    #[serde(default)]
+    pub latency_window: Option<Duration>,
 }
 
 impl Default for Sp1Options {
@@ -37,6 +54,11 @@ impl Default for Sp1Options {
             num_threads: Some(4),
             memory_limit: Some(1024 * 1024 * 1024), // 1GB
             custom_params: None,
+            proof_mode: ProofMode::Core,
+            max_proof_size: None,
+            prove_timeout: None,
+            max_batch_parallelism: None,
+            latency_window: None,
         }
     }
 }
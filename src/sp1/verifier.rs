@@ -0,0 +1,44 @@
+//! Verification of SP1 proofs in every proof mode
+//!
+//! Core and compressed proofs are verified with the SP1 prover client. Groth16 and
+//! PLONK proofs wrap a compressed proof in a BN254 SNARK. Besides the client path,
+//! their on-chain encoding can be checked with [`verify_onchain`] using only the
+//! program's verifying key hash, exactly as the `SP1Verifier` contracts do.
+
+use sp1_sdk::{CpuProver, Prover, SP1Proof, SP1ProofWithPublicValues, SP1VerifyingKey};
+
+use crate::error::ZkError;
+use crate::metadata::ProofMode;
+
+pub use crate::verify::verify_onchain;
+
+/// Mode a proof was produced in
+pub fn proof_mode(proof: &SP1ProofWithPublicValues) -> ProofMode {
+    match proof.proof {
+        SP1Proof::Core(_) => ProofMode::Core,
+        SP1Proof::Compressed(_) => ProofMode::Compressed,
+        SP1Proof::Plonk(_) => ProofMode::Plonk,
+        SP1Proof::Groth16(_) => ProofMode::Groth16,
+    }
+}
+
+/// Decode a bincode-serialized proof
+pub fn decode_proof(proof: &[u8]) -> Result<SP1ProofWithPublicValues, ZkError> {
+    bincode::deserialize(proof)
+        .map_err(|e| ZkError::ProofVerification(format!("Failed to parse proof: {}", e)))
+}
+
+/// Verify a proof of any mode against a verifying key
+pub fn verify_proof(
+    client: &CpuProver,
+    proof: &SP1ProofWithPublicValues,
+    verifying_key: &SP1VerifyingKey,
+) -> bool {
+    match client.verify(proof, verifying_key) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!(mode = ?proof_mode(proof), "proof rejected: {}", e);
+            false
+        }
+    }
+}
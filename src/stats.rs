@@ -0,0 +1,658 @@
+//! Backend statistics beyond [`ZkStats`](frostgate_zkip::ZkStats)
+//!
+//! A [`StatsSnapshot`] copies every statistic of a backend at once. Long-running
+//! provers reset their statistics after taking a snapshot to report per-interval
+//! numbers, and save snapshots to disk to carry their history across restarts.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+use parking_lot::RwLock;
+use serde::{Serialize, Deserialize};
+use frostgate_zkip::ZkStats;
+
+use crate::error::ZkError;
+
+/// Category of a failed operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FailureCategory {
+    /// Guest program assertion or panic
+    GuestAssertion,
+    /// Operation exceeded its time limit
+    Timeout,
+    /// Operation was cancelled by the caller
+    Cancelled,
+    /// Proof exceeded the size budget
+    ProofTooLarge,
+    /// Invalid program or input
+    InvalidInput,
+    /// Proof failed to verify or parse
+    Verification,
+    /// Any other prover or backend error
+    Backend,
+}
+
+impl FailureCategory {
+    /// Categorize an error
+    pub fn of(error: &ZkError) -> Self {
+        match error {
+            ZkError::GuestAssertion { .. } => FailureCategory::GuestAssertion,
+            ZkError::Timeout(_) | ZkError::DeadlineUnreachable { .. } => FailureCategory::Timeout,
+            ZkError::Cancelled => FailureCategory::Cancelled,
+            ZkError::ProofTooLarge { .. } => FailureCategory::ProofTooLarge,
+            ZkError::InvalidInput(_)
+            | ZkError::InvalidProgram(_)
+            | ZkError::UnsupportedProgramVersion { .. } => FailureCategory::InvalidInput,
+            ZkError::ProofVerification(_)
+            | ZkError::CrossCheckMismatch { .. }
+            | ZkError::IncompatibleSdk { .. }
+            | ZkError::IncompatibleCircuit { .. }
+            | ZkError::UnsupportedEnvelopeVersion { .. }
+            | ZkError::ImageMismatch { .. } => FailureCategory::Verification,
+            _ => FailureCategory::Backend,
+        }
+    }
+}
+
+/// Number of recent failures retained by default
+pub const DEFAULT_RECENT_FAILURES: usize = 50;
+
+/// Record of a single failed operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureRecord {
+    /// Time of the failure
+    pub timestamp: SystemTime,
+    /// Hex-encoded program hash
+    pub program_hash: String,
+    /// Failure category
+    pub category: FailureCategory,
+    /// Error message
+    pub message: String,
+}
+
+/// Failure counters per category plus a window of recent failures
+#[derive(Debug)]
+pub struct FailureStats {
+    counts: RwLock<HashMap<FailureCategory, u64>>,
+    recent: RwLock<VecDeque<FailureRecord>>,
+    capacity: usize,
+}
+
+impl FailureStats {
+    /// Create empty failure statistics
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_RECENT_FAILURES)
+    }
+
+    /// Create empty failure statistics retaining `capacity` recent failures
+    ///
+    /// At least one recent failure is always retained.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            counts: RwLock::new(HashMap::new()),
+            recent: RwLock::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// Record a failure for a program
+    pub fn record(&self, program_hash: &str, error: &ZkError) {
+        let category = FailureCategory::of(error);
+        *self.counts.write().entry(category).or_insert(0) += 1;
+
+        let mut recent = self.recent.write();
+        if recent.len() == self.capacity {
+            recent.pop_front();
+        }
+        recent.push_back(FailureRecord {
+            timestamp: SystemTime::now(),
+            program_hash: program_hash.to_string(),
+            category,
+            message: error.to_string(),
+        });
+    }
+
+    /// Number of failures recorded for a category
+    pub fn count(&self, category: FailureCategory) -> u64 {
+        self.counts.read().get(&category).copied().unwrap_or(0)
+    }
+
+    /// Snapshot of all failure counters
+    pub fn snapshot(&self) -> HashMap<FailureCategory, u64> {
+        self.counts.read().clone()
+    }
+
+    /// Most recent failures, oldest first
+    pub fn recent(&self) -> Vec<FailureRecord> {
+        self.recent.read().iter().cloned().collect()
+    }
+
+    /// Clear all counters and recent failures
+    pub fn reset(&self) {
+        self.counts.write().clear();
+        self.recent.write().clear();
+    }
+
+    /// Replace the counters and recent failures, keeping the most recent that fit
+    pub fn restore(&self, counts: HashMap<FailureCategory, u64>, recent: Vec<FailureRecord>) {
+        *self.counts.write() = counts;
+        let skip = recent.len().saturating_sub(self.capacity);
+        *self.recent.write() = recent.into_iter().skip(skip).collect();
+    }
+}
+
+impl Default for FailureStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Proving statistics for a single program
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProgramStats {
+    /// Number of proofs generated
+    pub proofs: u64,
+    /// Number of failed proving attempts
+    pub failures: u64,
+    /// Total proving time
+    pub total_proving_time: Duration,
+    /// Time of the last proving attempt
+    pub last_proved: Option<SystemTime>,
+}
+
+impl ProgramStats {
+    /// Average proving time over successful proofs
+    pub fn avg_proving_time(&self) -> Duration {
+        if self.proofs == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(self.total_proving_time.as_secs_f64() / self.proofs as f64)
+        }
+    }
+}
+
+/// Per-program proving statistics keyed by program hash
+#[derive(Debug, Default)]
+pub struct ProgramStatsTracker {
+    programs: RwLock<HashMap<String, ProgramStats>>,
+}
+
+impl ProgramStatsTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a proving attempt
+    pub fn record(&self, program_hash: &str, duration: Duration, success: bool) {
+        let mut programs = self.programs.write();
+        let stats = programs.entry(program_hash.to_string()).or_default();
+        if success {
+            stats.proofs += 1;
+            stats.total_proving_time += duration;
+        } else {
+            stats.failures += 1;
+        }
+        stats.last_proved = Some(SystemTime::now());
+    }
+
+    /// Snapshot of all per-program statistics
+    pub fn snapshot(&self) -> HashMap<String, ProgramStats> {
+        self.programs.read().clone()
+    }
+
+    /// Forget all per-program statistics
+    pub fn reset(&self) {
+        self.programs.write().clear();
+    }
+
+    /// Replace all per-program statistics
+    pub fn restore(&self, programs: HashMap<String, ProgramStats>) {
+        *self.programs.write() = programs;
+    }
+}
+
+/// Default time latencies are retained for
+pub const DEFAULT_LATENCY_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Number of slices a retention window is divided into
+///
+/// Latencies expire a slice at a time, so a window retains up to a slice more than
+/// its length.
+const LATENCY_SLICES: u32 = 12;
+
+/// Bits of each power of two resolved into sub-buckets, bounding the relative error
+/// of a recorded latency to 1/32
+const SUB_BUCKET_BITS: u32 = 5;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+
+/// HDR-style histogram of latencies in microseconds
+///
+/// Buckets are linear within each power of two, so percentiles are accurate to about
+/// 3% of the latency whether it is a millisecond verification or a ten-minute proof.
+/// Percentiles report the upper bound of the bucket they fall into.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyHistogram {
+    buckets: BTreeMap<u32, u64>,
+    count: u64,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    /// Create an empty histogram
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a latency
+    pub fn record(&mut self, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        *self.buckets.entry(bucket(micros)).or_insert(0) += 1;
+        self.count += 1;
+        self.max = self.max.max(latency);
+    }
+
+    /// Add the latencies recorded by `other`
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        for (index, count) in &other.buckets {
+            *self.buckets.entry(*index).or_insert(0) += count;
+        }
+        self.count += other.count;
+        self.max = self.max.max(other.max);
+    }
+
+    /// Number of latencies recorded
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Largest latency recorded
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Latency below which `percentile` percent of the recorded latencies fall,
+    /// `None` if none were recorded
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0 * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        let index = self.buckets.iter()
+            .find(|(_, count)| {
+                seen += **count;
+                seen >= rank
+            })
+            .map(|(index, _)| *index)?;
+        Some(Duration::from_micros(bucket_upper_bound(index)).min(self.max))
+    }
+
+    /// Median latency
+    pub fn p50(&self) -> Option<Duration> {
+        self.percentile(50.0)
+    }
+
+    /// 95th percentile latency
+    pub fn p95(&self) -> Option<Duration> {
+        self.percentile(95.0)
+    }
+
+    /// 99th percentile latency
+    pub fn p99(&self) -> Option<Duration> {
+        self.percentile(99.0)
+    }
+}
+
+/// Bucket of a latency in microseconds
+fn bucket(micros: u64) -> u32 {
+    if micros < SUB_BUCKETS {
+        return micros as u32;
+    }
+    let shift = 63 - micros.leading_zeros() - SUB_BUCKET_BITS;
+    let sub_bucket = (micros >> shift) & (SUB_BUCKETS - 1);
+    (shift + 1) * SUB_BUCKETS as u32 + sub_bucket as u32
+}
+
+/// Largest latency in microseconds falling into a bucket
+fn bucket_upper_bound(index: u32) -> u64 {
+    let index = u64::from(index);
+    if index < SUB_BUCKETS {
+        return index;
+    }
+    let shift = index / SUB_BUCKETS - 1;
+    let sub_bucket = index % SUB_BUCKETS;
+    let bound = (u128::from(SUB_BUCKETS + sub_bucket + 1) << shift) - 1;
+    u64::try_from(bound).unwrap_or(u64::MAX)
+}
+
+/// Histogram of the latencies recorded within a retention window
+#[derive(Debug)]
+struct WindowedHistogram {
+    /// Histograms of consecutive slices of the window, oldest first
+    slices: VecDeque<(Instant, LatencyHistogram)>,
+    window: Duration,
+}
+
+impl WindowedHistogram {
+    fn new(window: Duration) -> Self {
+        Self { slices: VecDeque::new(), window }
+    }
+
+    fn record(&mut self, latency: Duration, now: Instant) {
+        self.expire(now);
+        let slice = self.window / LATENCY_SLICES;
+        match self.slices.back_mut() {
+            Some((start, histogram)) if now.duration_since(*start) < slice => histogram.record(latency),
+            _ => {
+                let mut histogram = LatencyHistogram::new();
+                histogram.record(latency);
+                self.slices.push_back((now, histogram));
+            }
+        }
+    }
+
+    fn snapshot(&mut self, now: Instant) -> LatencyHistogram {
+        self.expire(now);
+        let mut merged = LatencyHistogram::new();
+        for (_, histogram) in &self.slices {
+            merged.merge(histogram);
+        }
+        merged
+    }
+
+    /// Add latencies recorded elsewhere, retaining them for a window from `now`
+    fn restore(&mut self, histogram: &LatencyHistogram, now: Instant) {
+        if histogram.count() > 0 {
+            self.slices.push_back((now, histogram.clone()));
+        }
+    }
+
+    /// Drop the slices that started a whole window ago
+    fn expire(&mut self, now: Instant) {
+        while self.slices.front().is_some_and(|(start, _)| now.duration_since(*start) >= self.window) {
+            self.slices.pop_front();
+        }
+    }
+}
+
+/// Proving and verification latency histograms over a retention window
+#[derive(Debug)]
+pub struct LatencyStats {
+    proving: RwLock<WindowedHistogram>,
+    verification: RwLock<WindowedHistogram>,
+    window: Duration,
+}
+
+impl LatencyStats {
+    /// Create empty latency statistics retaining [`DEFAULT_LATENCY_WINDOW`]
+    pub fn new() -> Self {
+        Self::with_window(DEFAULT_LATENCY_WINDOW)
+    }
+
+    /// Create empty latency statistics retaining latencies recorded within `window`
+    pub fn with_window(window: Duration) -> Self {
+        let window = window.max(Duration::from_secs(1));
+        Self {
+            proving: RwLock::new(WindowedHistogram::new(window)),
+            verification: RwLock::new(WindowedHistogram::new(window)),
+            window,
+        }
+    }
+
+    /// Time latencies are retained for
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Record the latency of a proving attempt
+    pub fn record_proving(&self, latency: Duration) {
+        self.proving.write().record(latency, Instant::now());
+    }
+
+    /// Record the latency of a verification
+    pub fn record_verification(&self, latency: Duration) {
+        self.verification.write().record(latency, Instant::now());
+    }
+
+    /// Proving latencies recorded within the window
+    pub fn proving(&self) -> LatencyHistogram {
+        self.proving.write().snapshot(Instant::now())
+    }
+
+    /// Verification latencies recorded within the window
+    pub fn verification(&self) -> LatencyHistogram {
+        self.verification.write().snapshot(Instant::now())
+    }
+
+    /// Forget all recorded latencies
+    pub fn reset(&self) {
+        *self.proving.write() = WindowedHistogram::new(self.window);
+        *self.verification.write() = WindowedHistogram::new(self.window);
+    }
+
+    /// Add proving and verification latencies of a snapshot, retained for a window
+    /// from now
+    pub fn restore(&self, proving: &LatencyHistogram, verification: &LatencyHistogram) {
+        let now = Instant::now();
+        self.proving.write().restore(proving, now);
+        self.verification.write().restore(verification, now);
+    }
+}
+
+impl Default for LatencyStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Copy of every statistic of a backend at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    /// Time the snapshot was taken
+    pub taken_at: SystemTime,
+    /// Proving attempts
+    pub total_proofs: u64,
+    /// Verifications
+    pub total_verifications: u64,
+    /// Failed proving attempts and verifications
+    pub total_failures: u64,
+    /// Average proving time
+    pub avg_proving_time: Duration,
+    /// Average verification time
+    pub avg_verification_time: Duration,
+    /// Per-program proving statistics keyed by program hash
+    pub programs: HashMap<String, ProgramStats>,
+    /// Failure counts per category
+    pub failure_counts: HashMap<FailureCategory, u64>,
+    /// Most recent failures, oldest first
+    pub recent_failures: Vec<FailureRecord>,
+    /// Proving latencies within the retention window
+    pub proving_latency: LatencyHistogram,
+    /// Verification latencies within the retention window
+    pub verification_latency: LatencyHistogram,
+}
+
+impl StatsSnapshot {
+    /// Copy the statistics a backend keeps
+    pub fn new(
+        stats: &ZkStats,
+        programs: &ProgramStatsTracker,
+        failures: &FailureStats,
+        latency: &LatencyStats,
+    ) -> Self {
+        Self {
+            taken_at: SystemTime::now(),
+            total_proofs: stats.total_proofs,
+            total_verifications: stats.total_verifications,
+            total_failures: stats.total_failures,
+            avg_proving_time: stats.avg_proving_time,
+            avg_verification_time: stats.avg_verification_time,
+            programs: programs.snapshot(),
+            failure_counts: failures.snapshot(),
+            recent_failures: failures.recent(),
+            proving_latency: latency.proving(),
+            verification_latency: latency.verification(),
+        }
+    }
+
+    /// Aggregate statistics of the snapshot
+    pub fn zk_stats(&self) -> ZkStats {
+        ZkStats {
+            total_proofs: self.total_proofs,
+            total_verifications: self.total_verifications,
+            total_failures: self.total_failures,
+            avg_proving_time: self.avg_proving_time,
+            avg_verification_time: self.avg_verification_time,
+            ..ZkStats::default()
+        }
+    }
+
+    /// Write the snapshot to `path` as JSON, replacing any previous snapshot
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ZkError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Read a snapshot written by [`Self::save`], `None` if there is none
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>, ZkError> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guest_panic_is_categorized() {
+        let error = ZkError::from_prover_error(0x03, "Guest panicked: Gas used exceeds limit");
+        match &error {
+            ZkError::GuestAssertion { message, circuit_type } => {
+                assert_eq!(message, "Gas used exceeds limit");
+                assert_eq!(*circuit_type, 0x03);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let stats = FailureStats::with_capacity(1);
+        stats.record("01", &error);
+        stats.record("01", &ZkError::ProofGeneration("out of memory".into()));
+        assert_eq!(stats.count(FailureCategory::GuestAssertion), 1);
+        assert_eq!(stats.count(FailureCategory::Backend), 1);
+        
+        // Only the most recent failure is retained
+        let recent = stats.recent();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].category, FailureCategory::Backend);
+    }
+
+    #[test]
+    fn test_recent_failures_bounded() {
+        // A zero capacity still retains the latest failure rather than all of them
+        let stats = FailureStats::with_capacity(0);
+        for _ in 0..3 {
+            stats.record("01", &ZkError::Cancelled);
+        }
+        assert_eq!(stats.recent().len(), 1);
+    }
+
+    #[test]
+    fn test_avg_proving_time() {
+        let stats = ProgramStats {
+            proofs: 3,
+            total_proving_time: Duration::from_millis(1000),
+            ..ProgramStats::default()
+        };
+        assert_eq!(stats.avg_proving_time().as_micros(), 333_333);
+
+        // More proofs than fit in a u32 must not wrap the divisor
+        let stats = ProgramStats {
+            proofs: u32::MAX as u64 + 2,
+            total_proving_time: Duration::from_secs(u32::MAX as u64 + 2),
+            ..ProgramStats::default()
+        };
+        assert_eq!(stats.avg_proving_time().as_secs(), 1);
+    }
+
+    #[test]
+    fn test_latency_percentiles() {
+        let mut histogram = LatencyHistogram::new();
+        assert_eq!(histogram.p50(), None);
+        for millis in 1..=100 {
+            histogram.record(Duration::from_millis(millis));
+        }
+        assert_eq!(histogram.count(), 100);
+
+        // Percentiles are rounded up to their bucket, within 1/32 of the latency
+        for (percentile, millis) in [(histogram.p50(), 50), (histogram.p95(), 95), (histogram.p99(), 99)] {
+            let percentile = percentile.unwrap();
+            assert!(percentile >= Duration::from_millis(millis));
+            assert!(percentile <= Duration::from_millis(millis) * 33 / 32);
+        }
+        assert_eq!(histogram.percentile(100.0), Some(Duration::from_millis(100)));
+
+        // The largest latencies land in the last bucket
+        histogram.record(Duration::MAX);
+        assert_eq!(bucket_upper_bound(bucket(u64::MAX)), u64::MAX);
+    }
+
+    #[test]
+    fn test_latency_window_expires() {
+        let start = Instant::now();
+        let mut window = WindowedHistogram::new(Duration::from_secs(60));
+        window.record(Duration::from_secs(10), start);
+        window.record(Duration::from_secs(1), start + Duration::from_secs(30));
+        assert_eq!(window.snapshot(start + Duration::from_secs(30)).count(), 2);
+
+        // The first slice expires a window after it started
+        let histogram = window.snapshot(start + Duration::from_secs(60));
+        assert_eq!(histogram.count(), 1);
+        assert_eq!(histogram.max(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_snapshot_survives_restart() {
+        let programs = ProgramStatsTracker::new();
+        let failures = FailureStats::with_capacity(2);
+        let latency = LatencyStats::new();
+        programs.record("01", Duration::from_secs(2), true);
+        failures.record("01", &ZkError::Cancelled);
+        latency.record_proving(Duration::from_secs(2));
+        let stats = ZkStats { total_proofs: 2, total_failures: 1, ..ZkStats::default() };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stats").join("sp1.json");
+        assert!(StatsSnapshot::load(&path).unwrap().is_none());
+        StatsSnapshot::new(&stats, &programs, &failures, &latency).save(&path).unwrap();
+
+        // A fresh backend picks up where the previous one stopped
+        programs.reset();
+        failures.reset();
+        latency.reset();
+        assert!(programs.snapshot().is_empty());
+        assert_eq!(latency.proving().count(), 0);
+
+        let snapshot = StatsSnapshot::load(&path).unwrap().unwrap();
+        assert_eq!(snapshot.zk_stats().total_proofs, 2);
+        programs.restore(snapshot.programs);
+        failures.restore(snapshot.failure_counts, snapshot.recent_failures);
+        latency.restore(&snapshot.proving_latency, &snapshot.verification_latency);
+        assert_eq!(programs.snapshot()["01"].proofs, 1);
+        assert_eq!(failures.count(FailureCategory::Cancelled), 1);
+        assert_eq!(latency.proving().p50(), snapshot.proving_latency.p50());
+    }
+}
@@ -0,0 +1,206 @@
+//! Content-addressed artifact store
+//!
+//! Guest ELFs, proving keys and proofs can be hundreds of megabytes. The
+//! [`ArtifactStore`] keeps every blob exactly once on disk, addressed by its
+//! SHA-256 hash, and shares it between the program registry and the caches.
+//! Users take a reference when they start depending on a blob and release it when
+//! they are done; [`ArtifactStore::gc`] deletes blobs nobody references.
+//!
+//! Layout under the store root:
+//! - `blobs/<first byte>/<hash>`: blob contents
+//! - `refs.json`: reference counts keyed by hex-encoded hash
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+
+use crate::error::ZkError;
+
+/// Content hash of a stored blob
+pub type ArtifactHash = [u8; 32];
+
+const REFS_FILE: &str = "refs.json";
+
+/// On-disk content-addressed blob store with reference counting
+#[derive(Debug)]
+pub struct ArtifactStore {
+    root: PathBuf,
+    refs: Mutex<HashMap<String, u64>>,
+}
+
+impl ArtifactStore {
+    /// Open a store rooted at `root`, creating it if needed
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, ZkError> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join("blobs"))?;
+
+        // A corrupt reference file is treated as empty; unreferenced blobs are
+        // collected on the next gc
+        let refs = fs::read(root.join(REFS_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            root,
+            refs: Mutex::new(refs),
+        })
+    }
+
+    /// Root directory of the store
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Hash of a blob as used for addressing
+    pub fn hash(contents: &[u8]) -> ArtifactHash {
+        Sha256::digest(contents).into()
+    }
+
+    fn blob_path(&self, hash: &ArtifactHash) -> PathBuf {
+        let hex = hex::encode(hash);
+        self.root.join("blobs").join(&hex[..2]).join(hex)
+    }
+
+    /// Store a blob without taking a reference, returning its hash
+    ///
+    /// Storing a blob that is already present is a no-op.
+    pub fn put(&self, contents: &[u8]) -> Result<ArtifactHash, ZkError> {
+        let hash = Self::hash(contents);
+        let path = self.blob_path(&hash);
+        if !path.exists() {
+            fs::create_dir_all(path.parent().expect("blob path has a parent"))?;
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, contents)?;
+            fs::rename(&tmp, &path)?;
+        }
+        Ok(hash)
+    }
+
+    /// Read a blob, verifying its contents against the hash
+    ///
+    /// Corrupt blobs are deleted and reported as missing.
+    pub fn get(&self, hash: &ArtifactHash) -> Result<Option<Vec<u8>>, ZkError> {
+        let path = self.blob_path(hash);
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if Self::hash(&contents) != *hash {
+            tracing::warn!(hash = %hex::encode(hash), "removing corrupt artifact");
+            let _ = fs::remove_file(&path);
+            return Ok(None);
+        }
+        Ok(Some(contents))
+    }
+
+    /// Whether a blob is present
+    pub fn contains(&self, hash: &ArtifactHash) -> bool {
+        self.blob_path(hash).exists()
+    }
+
+    /// Store a blob and take a reference to it
+    pub fn put_retained(&self, contents: &[u8]) -> Result<ArtifactHash, ZkError> {
+        let hash = self.put(contents)?;
+        self.retain(&hash)?;
+        Ok(hash)
+    }
+
+    /// Take a reference to a blob
+    pub fn retain(&self, hash: &ArtifactHash) -> Result<(), ZkError> {
+        let mut refs = self.refs.lock();
+        *refs.entry(hex::encode(hash)).or_insert(0) += 1;
+        self.write_refs(&refs)
+    }
+
+    /// Release a reference to a blob
+    pub fn release(&self, hash: &ArtifactHash) -> Result<(), ZkError> {
+        let mut refs = self.refs.lock();
+        let key = hex::encode(hash);
+        if let Some(count) = refs.get_mut(&key) {
+            *count -= 1;
+            if *count == 0 {
+                refs.remove(&key);
+            }
+        }
+        self.write_refs(&refs)
+    }
+
+    /// Number of references to a blob
+    pub fn ref_count(&self, hash: &ArtifactHash) -> u64 {
+        self.refs.lock().get(&hex::encode(hash)).copied().unwrap_or(0)
+    }
+
+    /// Delete every blob without references, returning the number deleted
+    pub fn gc(&self) -> Result<usize, ZkError> {
+        let refs = self.refs.lock();
+        let mut removed = 0;
+        for shard in fs::read_dir(self.root.join("blobs"))? {
+            for blob in fs::read_dir(shard?.path())? {
+                let blob = blob?;
+                let name = blob.file_name().to_string_lossy().into_owned();
+                if !refs.contains_key(&name) {
+                    fs::remove_file(blob.path())?;
+                    removed += 1;
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    fn write_refs(&self, refs: &HashMap<String, u64>) -> Result<(), ZkError> {
+        let tmp = self.root.join(format!("{}.tmp", REFS_FILE));
+        fs::write(&tmp, serde_json::to_vec(refs)?)?;
+        fs::rename(&tmp, self.root.join(REFS_FILE))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deduplication_and_gc() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::open(dir.path()).unwrap();
+
+        let elf = vec![0x7F, b'E', b'L', b'F'];
+        let hash = store.put_retained(&elf).unwrap();
+        assert_eq!(store.put_retained(&elf).unwrap(), hash);
+        assert_eq!(store.ref_count(&hash), 2);
+
+        let orphan = store.put(b"orphan").unwrap();
+        assert_eq!(store.gc().unwrap(), 1);
+        assert!(!store.contains(&orphan));
+
+        store.release(&hash).unwrap();
+        store.release(&hash).unwrap();
+        assert_eq!(store.gc().unwrap(), 1);
+        assert!(store.get(&hash).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_refs_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let hash = ArtifactStore::open(dir.path()).unwrap().put_retained(b"key").unwrap();
+
+        let store = ArtifactStore::open(dir.path()).unwrap();
+        assert_eq!(store.ref_count(&hash), 1);
+        assert_eq!(store.get(&hash).unwrap().unwrap(), b"key");
+    }
+
+    #[test]
+    fn test_corrupt_blob_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::open(dir.path()).unwrap();
+        let hash = store.put(b"proof").unwrap();
+
+        fs::write(store.blob_path(&hash), b"tampered").unwrap();
+        assert!(store.get(&hash).unwrap().is_none());
+        assert!(!store.contains(&hash));
+    }
+}
@@ -0,0 +1,238 @@
+//! Support bundle export
+//!
+//! A [`SupportBundle`] collects the diagnostics needed to reproduce a problem into
+//! a single tarball: version information, a dashboard snapshot, recent events,
+//! recent failures, the redacted backend configuration and a manifest of the
+//! guest program artifacts. Services running a [`JobManager`] add the last failed
+//! jobs with [`SupportBundle::add_failed_jobs`].
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::error::ZkError;
+use crate::jobs::JobManager;
+use crate::stats::FailureStats;
+
+/// Number of most recent failures and failed jobs included in a bundle
+pub const BUNDLE_FAILURES: usize = 20;
+
+/// Configuration keys whose values are never exported
+const REDACTED_KEYS: &[&str] = &["custom_params", "api_key", "token", "secret", "password"];
+
+/// Artifact listed in the bundle manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactEntry {
+    /// Path relative to the artifact directory, or the artifact's name
+    pub path: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Hex-encoded SHA-256 digest
+    pub sha256: String,
+}
+
+/// Tarball of diagnostic files
+#[derive(Debug, Default)]
+pub struct SupportBundle {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl SupportBundle {
+    /// Create an empty bundle
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a raw file
+    pub fn add_file(&mut self, name: &str, contents: Vec<u8>) {
+        self.files.push((name.to_string(), contents));
+    }
+
+    /// Add a value as pretty-printed JSON
+    pub fn add_json<T: Serialize>(&mut self, name: &str, value: &T) -> Result<(), ZkError> {
+        let contents = serde_json::to_vec_pretty(value)?;
+        self.add_file(name, contents);
+        Ok(())
+    }
+
+    /// Add a configuration with secrets redacted
+    pub fn add_config<T: Serialize>(&mut self, name: &str, config: &T) -> Result<(), ZkError> {
+        let config = redact(serde_json::to_value(config)?);
+        self.add_json(name, &config)
+    }
+
+    /// Add the [`BUNDLE_FAILURES`] most recent failures, newest first
+    pub fn add_failures(&mut self, name: &str, failures: &FailureStats) -> Result<(), ZkError> {
+        let recent: Vec<_> = failures.recent().into_iter().rev().take(BUNDLE_FAILURES).collect();
+        self.add_json(name, &recent)
+    }
+
+    /// Add the notifications of the [`BUNDLE_FAILURES`] most recently failed jobs
+    pub fn add_failed_jobs(&mut self, name: &str, jobs: &JobManager) -> Result<(), ZkError> {
+        self.add_json(name, &jobs.failed_jobs(BUNDLE_FAILURES))
+    }
+
+    /// Add a manifest of the files in an artifact directory
+    ///
+    /// A missing directory produces an empty manifest. The files are read with
+    /// blocking I/O, see [`Self::add_artifact_manifest_async`].
+    pub fn add_artifact_manifest(&mut self, name: &str, dir: impl AsRef<Path>) -> Result<(), ZkError> {
+        let mut manifest = Vec::new();
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries {
+                let entry = entry?;
+                if !entry.file_type()?.is_file() {
+                    continue;
+                }
+                let contents = fs::read(entry.path())?;
+                manifest.push(artifact(entry.file_name().to_string_lossy().into_owned(), &contents));
+            }
+        }
+        self.add_manifest(name, manifest)
+    }
+
+    /// Add a manifest of the files in an artifact directory without blocking the runtime
+    pub async fn add_artifact_manifest_async(&mut self, name: &str, dir: PathBuf) -> Result<(), ZkError> {
+        let mut bundle = Self::new();
+        let name = name.to_string();
+        let manifest = tokio::task::spawn_blocking(move || {
+            bundle.add_artifact_manifest(&name, dir)?;
+            Ok::<_, ZkError>(bundle.files)
+        })
+        .await
+        .map_err(|e| ZkError::Backend(format!("Artifact manifest task failed: {}", e)))??;
+        self.files.extend(manifest);
+        Ok(())
+    }
+
+    /// Add a manifest of artifacts held in memory, given by name and contents
+    pub fn add_artifacts<'a>(&mut self, name: &str, artifacts: impl IntoIterator<Item = (String, &'a [u8])>) -> Result<(), ZkError> {
+        let manifest = artifacts.into_iter().map(|(path, contents)| artifact(path, contents)).collect();
+        self.add_manifest(name, manifest)
+    }
+
+    fn add_manifest(&mut self, name: &str, mut manifest: Vec<ArtifactEntry>) -> Result<(), ZkError> {
+        manifest.sort_by(|a, b| a.path.cmp(&b.path));
+        self.add_json(name, &manifest)
+    }
+
+    /// Names of the files in the bundle
+    pub fn file_names(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Write the bundle as a tarball on the blocking thread pool
+    pub async fn write_async(self, path: PathBuf) -> Result<(), ZkError> {
+        tokio::task::spawn_blocking(move || self.write(path))
+            .await
+            .map_err(|e| ZkError::Backend(format!("Support bundle task failed: {}", e)))?
+    }
+
+    /// Write the bundle as a tarball
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), ZkError> {
+        let mtime = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut builder = tar::Builder::new(File::create(path)?);
+        for (name, contents) in &self.files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(mtime);
+            header.set_cksum();
+            builder.append_data(&mut header, name, contents.as_slice())?;
+        }
+        builder.finish()?;
+        Ok(())
+    }
+}
+
+fn artifact(path: String, contents: &[u8]) -> ArtifactEntry {
+    ArtifactEntry {
+        path,
+        size: contents.len() as u64,
+        sha256: hex::encode(Sha256::digest(contents)),
+    }
+}
+
+/// Replace the values of secret configuration keys
+fn redact(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(map.into_iter()
+            .map(|(key, value)| {
+                if REDACTED_KEYS.contains(&key.as_str()) && !value.is_null() {
+                    (key, Value::String("<redacted>".into()))
+                } else {
+                    (key, redact(value))
+                }
+            })
+            .collect()),
+        Value::Array(values) => Value::Array(values.into_iter().map(redact).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::stats::FailureRecord;
+
+    #[test]
+    fn test_redact() {
+        let config = json!({
+            "num_threads": 4,
+            "custom_params": [1, 2, 3],
+            "remote": { "api_key": "secret-key" },
+        });
+        let redacted = redact(config);
+        assert_eq!(redacted["num_threads"], 4);
+        assert_eq!(redacted["custom_params"], "<redacted>");
+        assert_eq!(redacted["remote"]["api_key"], "<redacted>");
+    }
+
+    #[test]
+    fn test_write_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.tar");
+
+        let mut bundle = SupportBundle::new();
+        bundle.add_json("version.json", &json!({ "sdk": "5.0.0" })).unwrap();
+        bundle.add_artifact_manifest("artifacts.json", dir.path().join("missing")).unwrap();
+        bundle.write(&path).unwrap();
+
+        let mut archive = tar::Archive::new(File::open(&path).unwrap());
+        let names: Vec<String> = archive.entries().unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, vec!["version.json", "artifacts.json"]);
+    }
+
+    #[tokio::test]
+    async fn test_bundle_failures_and_artifacts() {
+        let failures = FailureStats::new();
+        for i in 0..BUNDLE_FAILURES + 5 {
+            failures.record(&format!("{:02x}", i), &ZkError::Cancelled);
+        }
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("guest.elf"), b"elf").unwrap();
+
+        let mut bundle = SupportBundle::new();
+        bundle.add_failures("failures.json", &failures).unwrap();
+        bundle.add_artifact_manifest_async("artifacts.json", dir.path().to_path_buf()).await.unwrap();
+        bundle.add_artifacts("elfs.json", [("circuit-1.elf".to_string(), &b"elf"[..])]).unwrap();
+
+        let failures: Vec<FailureRecord> = serde_json::from_slice(&bundle.files[0].1).unwrap();
+        assert_eq!(failures.len(), BUNDLE_FAILURES);
+        assert_eq!(failures[0].program_hash, format!("{:02x}", BUNDLE_FAILURES + 4));
+
+        let on_disk: Vec<ArtifactEntry> = serde_json::from_slice(&bundle.files[1].1).unwrap();
+        let in_memory: Vec<ArtifactEntry> = serde_json::from_slice(&bundle.files[2].1).unwrap();
+        assert_eq!(on_disk[0].path, "guest.elf");
+        assert_eq!(on_disk[0].sha256, in_memory[0].sha256);
+    }
+}
@@ -0,0 +1,178 @@
+//! Sampling of the process CPU and memory usage
+//!
+//! Backends report CPU and memory usage through `resource_usage()` and degrade
+//! their health when either runs high. Both are process-wide, so a single
+//! [`ResourceMonitor`] samples them with `sysinfo` on a background thread every
+//! [`SAMPLE_INTERVAL`] and every backend reads the latest sample. A proving run
+//! holds a [`PeakMemory`] to learn the highest resident memory sampled while it ran.
+//!
+//! The monitor starts on first use of [`monitor`]. Until the first sample is taken
+//! usage reads as zero.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Once, OnceLock, Weak};
+use std::time::Duration;
+use parking_lot::{Mutex, RwLock};
+use sysinfo::{Pid, System};
+use frostgate_zkip::ResourceUsage;
+
+/// Time between two samples
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// CPU usage in percent above which backends report themselves degraded
+pub const HIGH_CPU_USAGE: f64 = 90.0;
+
+static MONITOR: OnceLock<ResourceMonitor> = OnceLock::new();
+static SAMPLING: Once = Once::new();
+
+/// Process-wide resource monitor, starting its sampling thread on first use
+pub fn monitor() -> &'static ResourceMonitor {
+    let monitor = MONITOR.get_or_init(ResourceMonitor::default);
+    SAMPLING.call_once(|| {
+        let spawned = std::thread::Builder::new()
+            .name("frostgate-sysmon".into())
+            .spawn(move || monitor.run());
+        if let Err(e) = spawned {
+            tracing::warn!("resource sampling disabled: {}", e);
+        }
+    });
+    monitor
+}
+
+/// Usage of the process at one point in time
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceSample {
+    /// CPU usage in percent of all available cores
+    pub cpu_usage: f64,
+    /// Resident memory in bytes
+    pub memory: usize,
+}
+
+/// Latest process usage and the peaks tracked by running proofs
+#[derive(Debug, Default)]
+pub struct ResourceMonitor {
+    latest: RwLock<ResourceSample>,
+    peaks: Mutex<Vec<Weak<AtomicUsize>>>,
+}
+
+impl ResourceMonitor {
+    /// Latest sample
+    pub fn sample(&self) -> ResourceSample {
+        *self.latest.read()
+    }
+
+    /// `tracked` with the CPU and memory usage of the latest sample
+    pub fn usage(&self, tracked: ResourceUsage) -> ResourceUsage {
+        let sample = self.sample();
+        ResourceUsage {
+            cpu_usage: sample.cpu_usage,
+            memory_usage: sample.memory,
+            ..tracked
+        }
+    }
+
+    /// Track the peak resident memory from now until the returned guard is dropped
+    pub fn track_peak(&self) -> PeakMemory {
+        let peak = Arc::new(AtomicUsize::new(self.sample().memory));
+        self.peaks.lock().push(Arc::downgrade(&peak));
+        PeakMemory { peak }
+    }
+
+    fn record(&self, sample: ResourceSample) {
+        *self.latest.write() = sample;
+        self.peaks.lock().retain(|peak| match peak.upgrade() {
+            Some(peak) => {
+                peak.fetch_max(sample.memory, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        });
+    }
+
+    fn run(&self) {
+        let mut sampler = match Sampler::new() {
+            Some(sampler) => sampler,
+            None => {
+                tracing::warn!("resource sampling is not supported on this platform");
+                return;
+            }
+        };
+        loop {
+            if let Some(sample) = sampler.sample() {
+                self.record(sample);
+            }
+            std::thread::sleep(SAMPLE_INTERVAL);
+        }
+    }
+}
+
+/// Peak resident memory sampled while the guard is held
+#[derive(Debug)]
+pub struct PeakMemory {
+    peak: Arc<AtomicUsize>,
+}
+
+impl PeakMemory {
+    /// Highest resident memory sampled so far, `None` before the first sample
+    pub fn peak(&self) -> Option<usize> {
+        match self.peak.load(Ordering::Relaxed) {
+            0 => None,
+            peak => Some(peak),
+        }
+    }
+}
+
+/// Reads the usage of the current process
+struct Sampler {
+    system: System,
+    pid: Pid,
+    cores: f64,
+}
+
+impl Sampler {
+    fn new() -> Option<Self> {
+        let pid = sysinfo::get_current_pid().ok()?;
+        let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+        Some(Self { system: System::new(), pid, cores: cores as f64 })
+    }
+
+    /// Usage since the previous sample; the first sample reports no CPU usage
+    fn sample(&mut self) -> Option<ResourceSample> {
+        if !self.system.refresh_process(self.pid) {
+            return None;
+        }
+        let process = self.system.process(self.pid)?;
+        Some(ResourceSample {
+            cpu_usage: f64::from(process.cpu_usage()) / self.cores,
+            memory: process.memory() as usize,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peak_memory() {
+        let monitor = ResourceMonitor::default();
+        let peak = monitor.track_peak();
+        assert_eq!(peak.peak(), None);
+
+        monitor.record(ResourceSample { cpu_usage: 10.0, memory: 300 });
+        monitor.record(ResourceSample { cpu_usage: 20.0, memory: 200 });
+        assert_eq!(peak.peak(), Some(300));
+        assert_eq!(monitor.sample().memory, 200);
+
+        // Dropped guards stop being tracked
+        drop(peak);
+        monitor.record(ResourceSample { cpu_usage: 0.0, memory: 100 });
+        assert!(monitor.peaks.lock().is_empty());
+    }
+
+    #[test]
+    fn test_sampler_reads_process() {
+        let mut sampler = Sampler::new().unwrap();
+        assert!(sampler.sample().unwrap().memory > 0);
+    }
+}
@@ -0,0 +1,112 @@
+//! Process-wide CPU budget shared by the backends
+//!
+//! The SP1 and RISC0 provers parallelise internally on the global rayon pool, and
+//! every backend runs several proofs at once. Without coordination a process with
+//! both backends runs far more threads than it has cores. [`CpuBudget`] caps the
+//! total: the global rayon pool is sized to the budget, and every proving task
+//! holds as many budget threads as its backend's `num_threads` while it runs, so
+//! tasks from all backends together stay within the budget.
+//!
+//! The budget defaults to the number of available cores. Call [`configure`] once at
+//! startup, before any backend proves, to choose a different one.
+
+use std::sync::{Arc, OnceLock};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::error::ZkError;
+use crate::options::CancellationToken;
+
+static BUDGET: OnceLock<CpuBudget> = OnceLock::new();
+
+/// Threads held by a running task, returned to the budget on drop
+pub type CpuPermit = OwnedSemaphorePermit;
+
+/// Number of threads proving may use across all backends
+#[derive(Debug)]
+pub struct CpuBudget {
+    threads: usize,
+    permits: Arc<Semaphore>,
+}
+
+impl CpuBudget {
+    fn new(threads: usize) -> Self {
+        let threads = threads.max(1);
+        // Fails if the pool was already used; it then keeps its own size
+        if let Err(e) = rayon::ThreadPoolBuilder::new().num_threads(threads).build_global() {
+            tracing::warn!("global thread pool already initialized, CPU budget covers proving tasks only: {}", e);
+        }
+        Self {
+            threads,
+            permits: Arc::new(Semaphore::new(threads)),
+        }
+    }
+
+    /// Total number of threads
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Number of threads not held by any task
+    pub fn available(&self) -> usize {
+        self.permits.available_permits()
+    }
+
+    /// Wait until `threads` threads are free and hold them, giving up with
+    /// [`ZkError::Cancelled`] once `cancel` fires
+    ///
+    /// Requests beyond the budget hold the whole budget.
+    pub async fn acquire(&self, threads: usize, cancel: &CancellationToken) -> Result<CpuPermit, ZkError> {
+        let threads = threads.clamp(1, self.threads) as u32;
+        tokio::select! {
+            permit = self.permits.clone().acquire_many_owned(threads) => {
+                permit.map_err(|e| ZkError::Backend(format!("CPU budget closed: {}", e)))
+            }
+            _ = cancel.cancelled() => Err(ZkError::Cancelled),
+        }
+    }
+}
+
+/// Set the process-wide CPU budget to `threads`
+///
+/// Fails if the budget is already in use, since tasks may be holding threads of it.
+pub fn configure(threads: usize) -> Result<(), ZkError> {
+    BUDGET.set(CpuBudget::new(threads))
+        .map_err(|_| ZkError::Backend("CPU budget is already configured".into()))
+}
+
+/// Process-wide CPU budget
+pub fn budget() -> &'static CpuBudget {
+    BUDGET.get_or_init(|| {
+        let threads = std::thread::available_parallelism().map(|threads| threads.get()).unwrap_or(4);
+        CpuBudget::new(threads)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_budget_is_shared() {
+        let budget = CpuBudget {
+            threads: 4,
+            permits: Arc::new(Semaphore::new(4)),
+        };
+        let cancel = CancellationToken::new();
+
+        let first = budget.acquire(3, &cancel).await.unwrap();
+        assert_eq!(budget.available(), 1);
+
+        // A request beyond the budget waits for the whole budget
+        let waiting = tokio::time::timeout(Duration::from_millis(20), budget.acquire(8, &cancel)).await;
+        assert!(waiting.is_err());
+        drop(first);
+        let all = budget.acquire(8, &cancel).await.unwrap();
+        assert_eq!(budget.available(), 0);
+
+        cancel.cancel();
+        assert!(matches!(budget.acquire(1, &cancel).await, Err(ZkError::Cancelled)));
+        drop(all);
+    }
+}
@@ -0,0 +1,81 @@
+//! Verification without prover dependencies
+//!
+//! Light clients and relayer nodes that never generate proofs depend on this crate
+//! with `default-features = false, features = ["verify-only"]`, which leaves out both
+//! provers. Proofs are then checked against keys published by a proving node as
+//! [`ExportedKey`]s: RISC0 receipts against the exported image ID and SP1 Groth16 or
+//! PLONK proofs against the exported verifying key hash.
+
+use risc0_zkvm::{sha::Digest as ImageId, Receipt};
+use sp1_verifier::{Groth16Verifier, PlonkVerifier, GROTH16_VK_BYTES, PLONK_VK_BYTES};
+
+use crate::error::ZkError;
+use crate::metadata::ProofMode;
+use crate::vk::{ExportedKey, KeyKind};
+
+/// Decode a bincode-serialized RISC0 receipt
+pub fn decode_receipt(proof: &[u8]) -> Result<Receipt, ZkError> {
+    bincode::deserialize(proof)
+        .map_err(|e| ZkError::ProofVerification(format!("Failed to parse receipt: {}", e)))
+}
+
+/// Verify a serialized RISC0 receipt of any kind against an exported image ID
+pub fn verify_risc0(key: &ExportedKey, proof: &[u8]) -> Result<bool, ZkError> {
+    expect_kind(key, KeyKind::Risc0ImageId)?;
+    let receipt = decode_receipt(proof)?;
+    Ok(match receipt.verify(ImageId::from(key.key_hash)) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!("receipt rejected: {}", e);
+            false
+        }
+    })
+}
+
+/// Verify the on-chain encoding of an SP1 proof against an exported verifying key
+pub fn verify_sp1(
+    key: &ExportedKey,
+    mode: ProofMode,
+    proof: &[u8],
+    public_values: &[u8],
+) -> Result<bool, ZkError> {
+    expect_kind(key, KeyKind::Sp1VerifyingKey)?;
+    verify_onchain(mode, proof, public_values, &format!("0x{}", hex::encode(key.key_hash)))
+}
+
+/// Verify the on-chain encoding of a Groth16 or PLONK proof
+///
+/// `vkey_hash` is the `bytes32` hash of the program's verifying key.
+pub fn verify_onchain(
+    mode: ProofMode,
+    proof: &[u8],
+    public_values: &[u8],
+    vkey_hash: &str,
+) -> Result<bool, ZkError> {
+    let result = match mode {
+        ProofMode::Groth16 => Groth16Verifier::verify(proof, public_values, vkey_hash, &GROTH16_VK_BYTES)
+            .map_err(|e| e.to_string()),
+        ProofMode::Plonk => PlonkVerifier::verify(proof, public_values, vkey_hash, &PLONK_VK_BYTES)
+            .map_err(|e| e.to_string()),
+        mode => {
+            return Err(ZkError::UnsupportedVerifier {
+                chain: "evm".to_string(),
+                mode,
+            })
+        }
+    };
+    Ok(match result {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!(?mode, "on-chain proof rejected: {}", e);
+            false
+        }
+    })
+}
+
+fn expect_kind(key: &ExportedKey, kind: KeyKind) -> Result<(), ZkError> {
+    if key.kind != kind {
+        return Err(ZkError::InvalidInput(format!("Expected a {:?} key, got {:?}", kind, key.kind)));
+    }
+    Ok(())
+}
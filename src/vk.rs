@@ -0,0 +1,199 @@
+//! Verifying-key export for external verifiers
+//!
+//! Bridge operators register verification keys on-chain or in other services before
+//! any proof exists. [`ExportedKey`] is the stable format both backends export
+//! keys in. Every export is bound to the hash of the program it belongs to, so a
+//! key cannot be registered for the wrong program by mistake. The program is the
+//! one passed to `prove` and `verify`, hashed with the hash function of its circuit
+//! profile, so the binding matches the program hash in proof metadata.
+//!
+//! Layout, all integers little-endian:
+//! - `FGVK` magic (4 bytes)
+//! - format version (1 byte)
+//! - key kind (1 byte)
+//! - hash function of the program hash (1 byte)
+//! - program hash (32 bytes)
+//! - key hash as used by on-chain verifiers (32 bytes)
+//! - key length (4 bytes) followed by the key
+//!
+//! Version 1 exports have no hash function byte and bind SHA-256 program hashes.
+
+use crate::error::ZkError;
+use crate::hasher::HashFunction;
+
+/// Current export format version
+pub const VK_FORMAT_VERSION: u8 = 2;
+
+const MAGIC: &[u8; 4] = b"FGVK";
+const HEADER_LEN: usize = 4 + 1 + 1 + 1 + 32 + 32 + 4;
+
+/// Kind of an exported key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    /// Bincode-encoded SP1 verifying key; the key hash is its `bytes32` digest
+    Sp1VerifyingKey,
+    /// RISC0 image ID; the key hash is the image ID itself
+    Risc0ImageId,
+}
+
+impl KeyKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            KeyKind::Sp1VerifyingKey => 1,
+            KeyKind::Risc0ImageId => 2,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(KeyKind::Sp1VerifyingKey),
+            2 => Some(KeyKind::Risc0ImageId),
+            _ => None,
+        }
+    }
+}
+
+fn hash_function_to_byte(hash_function: HashFunction) -> u8 {
+    match hash_function {
+        HashFunction::Sha256 => 0,
+        HashFunction::Keccak256 => 1,
+        HashFunction::Blake2b => 2,
+        HashFunction::Blake3 => 3,
+        HashFunction::Poseidon => 4,
+    }
+}
+
+fn hash_function_from_byte(byte: u8) -> Option<HashFunction> {
+    match byte {
+        0 => Some(HashFunction::Sha256),
+        1 => Some(HashFunction::Keccak256),
+        2 => Some(HashFunction::Blake2b),
+        3 => Some(HashFunction::Blake3),
+        4 => Some(HashFunction::Poseidon),
+        _ => None,
+    }
+}
+
+/// Verification key exported for an external verifier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedKey {
+    /// Kind of key
+    pub kind: KeyKind,
+    /// Hash function `program_hash` was computed with
+    pub hash_function: HashFunction,
+    /// Hash of the program the key belongs to
+    pub program_hash: [u8; 32],
+    /// Key hash as registered with on-chain verifiers
+    pub key_hash: [u8; 32],
+    /// Serialized key
+    pub key: Vec<u8>,
+}
+
+impl ExportedKey {
+    /// Create an export bound to `program` under `hash_function`
+    pub fn new(kind: KeyKind, program: &[u8], hash_function: HashFunction, key_hash: [u8; 32], key: Vec<u8>) -> Self {
+        Self {
+            kind,
+            hash_function,
+            program_hash: hash_function.hash(program),
+            key_hash,
+            key,
+        }
+    }
+
+    /// Whether the key was exported for `program`
+    pub fn is_bound_to(&self, program: &[u8]) -> bool {
+        self.program_hash == self.hash_function.hash(program)
+    }
+
+    /// Encode in the stable export format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.key.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VK_FORMAT_VERSION);
+        bytes.push(self.kind.to_byte());
+        bytes.push(hash_function_to_byte(self.hash_function));
+        bytes.extend_from_slice(&self.program_hash);
+        bytes.extend_from_slice(&self.key_hash);
+        bytes.extend_from_slice(&(self.key.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.key);
+        bytes
+    }
+
+    /// Decode from the stable export format
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        if bytes.len() < HEADER_LEN - 1 || &bytes[..4] != MAGIC {
+            return Err(ZkError::InvalidInput("Not an exported verifying key".into()));
+        }
+        let (hash_function, header_len) = match bytes[4] {
+            1 => (HashFunction::Sha256, HEADER_LEN - 1),
+            VK_FORMAT_VERSION if bytes.len() >= HEADER_LEN => {
+                let hash_function = hash_function_from_byte(bytes[6])
+                    .ok_or_else(|| ZkError::InvalidInput(format!("Unknown hash function {}", bytes[6])))?;
+                (hash_function, HEADER_LEN)
+            }
+            VK_FORMAT_VERSION => return Err(ZkError::InvalidInput("Not an exported verifying key".into())),
+            version => return Err(ZkError::InvalidInput(format!("Unsupported key format version {}", version))),
+        };
+        let kind = KeyKind::from_byte(bytes[5])
+            .ok_or_else(|| ZkError::InvalidInput(format!("Unknown key kind {}", bytes[5])))?;
+        let hashes = &bytes[header_len - 68..header_len - 4];
+        let program_hash = hashes[..32].try_into().expect("slice is 32 bytes");
+        let key_hash = hashes[32..].try_into().expect("slice is 32 bytes");
+        let key_len = u32::from_le_bytes(bytes[header_len - 4..header_len].try_into().expect("slice is 4 bytes")) as usize;
+        if bytes.len() != header_len + key_len {
+            return Err(ZkError::InvalidInput("Exported key length mismatch".into()));
+        }
+        Ok(Self {
+            kind,
+            hash_function,
+            program_hash,
+            key_hash,
+            key: bytes[header_len..].to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_binding() {
+        let program = vec![0x01; 33];
+        let exported = ExportedKey::new(KeyKind::Risc0ImageId, &program, HashFunction::Keccak256, [7u8; 32], vec![7u8; 32]);
+        assert_eq!(exported.program_hash, HashFunction::Keccak256.hash(&program));
+
+        let decoded = ExportedKey::from_bytes(&exported.to_bytes()).unwrap();
+        assert_eq!(decoded, exported);
+        assert!(decoded.is_bound_to(&program));
+        assert!(!decoded.is_bound_to(&[0x02; 33]));
+    }
+
+    #[test]
+    fn test_decodes_version_1() {
+        let program = b"program";
+        let exported = ExportedKey::new(KeyKind::Sp1VerifyingKey, program, HashFunction::Sha256, [1u8; 32], vec![1, 2, 3]);
+        let mut bytes = exported.to_bytes();
+        bytes[4] = 1;
+        bytes.remove(6);
+
+        let decoded = ExportedKey::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, exported);
+        assert!(decoded.is_bound_to(program));
+    }
+
+    #[test]
+    fn test_rejects_malformed_exports() {
+        let exported = ExportedKey::new(KeyKind::Sp1VerifyingKey, b"program", HashFunction::Sha256, [1u8; 32], vec![1, 2, 3]);
+        let mut bytes = exported.to_bytes();
+
+        assert!(ExportedKey::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        bytes[6] = 0xFF;
+        assert!(ExportedKey::from_bytes(&bytes).is_err());
+        bytes[6] = 0;
+        bytes[4] = VK_FORMAT_VERSION + 1;
+        assert!(ExportedKey::from_bytes(&bytes).is_err());
+        assert!(ExportedKey::from_bytes(b"FGVK").is_err());
+    }
+}
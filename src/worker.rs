@@ -0,0 +1,325 @@
+//! Prover worker for cluster mode
+//!
+//! A [`Worker`] registers with the cluster coordinator, advertises its
+//! [`WorkerCapabilities`], pulls jobs, streams progress while proving and uploads
+//! finished proofs. Scaling out is a matter of starting more `frostgate-worker`
+//! processes pointed at the same coordinator.
+//!
+//! A worker can be drained for maintenance. It stops pulling jobs, and RISC0 jobs
+//! in progress are suspended after their current segment and handed back to the
+//! coordinator as a [`ProvingCheckpoint`] so another worker can finish them.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+use frostgate_zkip::ZkBackend;
+
+use crate::error::ZkError;
+use crate::risc0::{ProvingCheckpoint, ProvingOutcome, Risc0Backend};
+use crate::sp1::Sp1Backend;
+
+/// Default interval between job polls when the queue is empty
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default interval between progress reports while proving
+pub const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Resources a worker offers to the coordinator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerCapabilities {
+    /// Worker name, for operators
+    pub name: String,
+    /// Backends the worker can prove with
+    pub backends: Vec<String>,
+    /// Proving devices available to the worker
+    pub devices: Vec<String>,
+    /// Number of CPU cores
+    pub cpus: usize,
+    /// Memory available for proving in bytes
+    pub memory: Option<u64>,
+}
+
+/// Job handed to a worker by the coordinator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterJob {
+    /// Job identifier
+    pub id: String,
+    /// Backend to prove with
+    pub backend: String,
+    /// Program bytes
+    pub program: Vec<u8>,
+    /// Program input
+    pub input: Vec<u8>,
+    /// Checkpoint to resume from, for jobs handed off by a draining worker
+    #[serde(default)]
+    pub checkpoint: Option<ProvingCheckpoint>,
+}
+
+/// Progress update for a running job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum JobProgress {
+    /// The worker started proving
+    Started,
+    /// The worker is still proving
+    Proving {
+        /// Time spent proving so far
+        elapsed: Duration,
+    },
+}
+
+/// Finished job uploaded to the coordinator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobResult {
+    /// Job identifier
+    pub job_id: String,
+    /// Serialized proof, empty on failure
+    pub proof: Vec<u8>,
+    /// Hex-encoded program hash
+    pub program_hash: String,
+    /// Proving time
+    pub generation_time: Duration,
+    /// Error message if proving failed
+    pub error: Option<String>,
+}
+
+/// Coordinator protocol spoken by workers
+#[async_trait]
+pub trait Coordinator: Send + Sync {
+    /// Register a worker, returning its assigned identifier
+    async fn register(&self, capabilities: &WorkerCapabilities) -> Result<String, ZkError>;
+
+    /// Pull the next job for a worker, if any
+    async fn next_job(&self, worker_id: &str) -> Result<Option<ClusterJob>, ZkError>;
+
+    /// Report progress of a running job
+    async fn report_progress(&self, worker_id: &str, job_id: &str, progress: JobProgress) -> Result<(), ZkError>;
+
+    /// Upload a finished job
+    async fn upload(&self, worker_id: &str, result: &JobResult) -> Result<(), ZkError>;
+
+    /// Hand an unfinished job back so another worker can resume it
+    async fn hand_off(&self, worker_id: &str, job_id: &str, checkpoint: &ProvingCheckpoint) -> Result<(), ZkError>;
+}
+
+/// Coordinator reached over its HTTP API
+#[derive(Debug, Clone)]
+pub struct HttpCoordinator {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl HttpCoordinator {
+    /// Create a client for the coordinator at `base_url`
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn post<T: Serialize + ?Sized>(&self, path: &str, body: &T) -> Result<reqwest::Response, ZkError> {
+        self.client.post(format!("{}{}", self.base_url, path))
+            .json(body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| ZkError::Backend(format!("Coordinator request to {} failed: {}", path, e)))
+    }
+}
+
+#[async_trait]
+impl Coordinator for HttpCoordinator {
+    async fn register(&self, capabilities: &WorkerCapabilities) -> Result<String, ZkError> {
+        self.post("/workers", capabilities)
+            .await?
+            .json()
+            .await
+            .map_err(|e| ZkError::Backend(format!("Invalid registration response: {}", e)))
+    }
+
+    async fn next_job(&self, worker_id: &str) -> Result<Option<ClusterJob>, ZkError> {
+        self.post(&format!("/workers/{}/jobs/next", worker_id), &())
+            .await?
+            .json()
+            .await
+            .map_err(|e| ZkError::Backend(format!("Invalid job response: {}", e)))
+    }
+
+    async fn report_progress(&self, worker_id: &str, job_id: &str, progress: JobProgress) -> Result<(), ZkError> {
+        self.post(&format!("/workers/{}/jobs/{}/progress", worker_id, job_id), &progress).await?;
+        Ok(())
+    }
+
+    async fn upload(&self, worker_id: &str, result: &JobResult) -> Result<(), ZkError> {
+        self.post(&format!("/workers/{}/jobs/{}/result", worker_id, result.job_id), result).await?;
+        Ok(())
+    }
+
+    async fn hand_off(&self, worker_id: &str, job_id: &str, checkpoint: &ProvingCheckpoint) -> Result<(), ZkError> {
+        self.post(&format!("/workers/{}/jobs/{}/handoff", worker_id, job_id), checkpoint).await?;
+        Ok(())
+    }
+}
+
+/// Prover worker pulling jobs from a coordinator
+#[derive(Debug)]
+pub struct Worker<C> {
+    coordinator: C,
+    capabilities: WorkerCapabilities,
+    sp1: Option<Arc<Sp1Backend>>,
+    risc0: Option<Arc<Risc0Backend>>,
+    poll_interval: Duration,
+    progress_interval: Duration,
+    draining: Arc<AtomicBool>,
+}
+
+impl<C: Coordinator> Worker<C> {
+    /// Create a worker proving with the given backends
+    pub fn new(
+        coordinator: C,
+        name: &str,
+        sp1: Option<Arc<Sp1Backend>>,
+        risc0: Option<Arc<Risc0Backend>>,
+    ) -> Self {
+        let mut backends = Vec::new();
+        if sp1.is_some() {
+            backends.push("sp1".to_string());
+        }
+        if risc0.is_some() {
+            backends.push("risc0".to_string());
+        }
+        let capabilities = WorkerCapabilities {
+            name: name.to_string(),
+            backends,
+            devices: vec!["cpu".to_string()],
+            cpus: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            memory: None,
+        };
+        Self {
+            coordinator,
+            capabilities,
+            sp1,
+            risc0,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+            draining: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Set the memory advertised to the coordinator
+    pub fn with_memory(mut self, memory: u64) -> Self {
+        self.capabilities.memory = Some(memory);
+        self
+    }
+
+    /// Set the interval between job polls when the queue is empty
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Handle that drains the worker when set
+    ///
+    /// A draining worker stops pulling jobs, hands off RISC0 jobs in progress and
+    /// returns from [`Worker::run`].
+    pub fn drain_handle(&self) -> Arc<AtomicBool> {
+        self.draining.clone()
+    }
+
+    /// Capabilities advertised to the coordinator
+    pub fn capabilities(&self) -> &WorkerCapabilities {
+        &self.capabilities
+    }
+
+    /// Register and process jobs until drained or the coordinator becomes unreachable
+    pub async fn run(&self) -> Result<(), ZkError> {
+        let worker_id = self.coordinator.register(&self.capabilities).await?;
+        tracing::info!(worker_id = %worker_id, "registered with coordinator");
+
+        while !self.draining.load(Ordering::Acquire) {
+            match self.coordinator.next_job(&worker_id).await? {
+                Some(job) => match self.process(&worker_id, &job).await {
+                    Ok(result) => self.coordinator.upload(&worker_id, &result).await?,
+                    Err(checkpoint) => {
+                        tracing::info!(
+                            job_id = %job.id,
+                            proven = checkpoint.proven_segments(),
+                            total = checkpoint.total_segments(),
+                            "handing off job"
+                        );
+                        self.coordinator.hand_off(&worker_id, &job.id, &checkpoint).await?;
+                    }
+                },
+                None => tokio::time::sleep(self.poll_interval).await,
+            }
+        }
+        tracing::info!(worker_id = %worker_id, "worker drained");
+        Ok(())
+    }
+
+    /// Prove a single job, reporting progress while it runs
+    ///
+    /// Returns the checkpoint instead of a result if the job was suspended.
+    #[tracing::instrument(name = "job", skip_all, fields(job_id = %job.id, backend = %job.backend))]
+    async fn process(&self, worker_id: &str, job: &ClusterJob) -> Result<JobResult, ProvingCheckpoint> {
+        let start = SystemTime::now();
+        if let Err(e) = self.coordinator.report_progress(worker_id, &job.id, JobProgress::Started).await {
+            tracing::warn!(job_id = %job.id, "failed to report progress: {}", e);
+        }
+
+        let proving = self.prove(job);
+        tokio::pin!(proving);
+        let mut progress = tokio::time::interval(self.progress_interval);
+        progress.tick().await;
+        let result = loop {
+            tokio::select! {
+                result = &mut proving => break result,
+                _ = progress.tick() => {
+                    let elapsed = start.elapsed().unwrap_or_default();
+                    if let Err(e) = self.coordinator.report_progress(worker_id, &job.id, JobProgress::Proving { elapsed }).await {
+                        tracing::warn!(job_id = %job.id, "failed to report progress: {}", e);
+                    }
+                }
+            }
+        };
+
+        let generation_time = start.elapsed().unwrap_or_default();
+        Ok(match result {
+            Ok(ProvingOutcome::Suspended(checkpoint)) => return Err(checkpoint),
+            Ok(ProvingOutcome::Finished(proof)) => JobResult {
+                job_id: job.id.clone(),
+                proof,
+                program_hash: hex::encode(&job.program),
+                generation_time,
+                error: None,
+            },
+            Err(e) => JobResult {
+                job_id: job.id.clone(),
+                proof: Vec::new(),
+                program_hash: hex::encode(&job.program),
+                generation_time,
+                error: Some(e.to_string()),
+            },
+        })
+    }
+
+    async fn prove(&self, job: &ClusterJob) -> Result<ProvingOutcome, ZkError> {
+        match (job.backend.as_str(), &self.sp1, &self.risc0) {
+            ("sp1", Some(sp1), _) => ZkBackend::prove(sp1.as_ref(), &job.program, &job.input, None)
+                .await
+                .map(|(proof, _)| ProvingOutcome::Finished(proof))
+                .map_err(ZkError::from),
+            ("risc0", _, Some(risc0)) => {
+                // RISC0 jobs are proven segment by segment so they can be handed off
+                let checkpoint = match &job.checkpoint {
+                    Some(checkpoint) => checkpoint.clone(),
+                    None => risc0.checkpoint(&job.program, &job.input)?,
+                };
+                risc0.resume(checkpoint, self.draining.clone()).await
+            }
+            (backend, _, _) => Err(ZkError::Backend(format!("Backend {} not available on this worker", backend))),
+        }
+    }
+}
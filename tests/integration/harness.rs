@@ -0,0 +1,70 @@
+//! Harness for end-to-end tests against the real guest programs
+//!
+//! `build.rs` builds the RISC0 guests with `cargo risczero` and the SP1 guests with
+//! `sp1-build`, unless `SKIP_GUEST_BUILD` is set and placeholder files are left
+//! behind instead. Tests load their guest through [`require_guest!`], which skips the test with a
+//! message naming the missing toolchain instead of proving a placeholder. Set
+//! `FROSTGATE_REQUIRE_GUESTS=1` to turn skips into failures, e.g. in CI.
+
+use std::path::PathBuf;
+
+/// Leading bytes of every ELF file
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+
+/// Toolchain a guest program is built with
+#[derive(Debug, Clone, Copy)]
+pub enum Toolchain {
+    Risc0,
+    Sp1,
+}
+
+impl Toolchain {
+    /// Command that builds guests for this toolchain
+    fn command(self) -> &'static str {
+        match self {
+            Toolchain::Risc0 => "cargo risczero",
+            Toolchain::Sp1 => "cargo prove",
+        }
+    }
+
+    /// Location of a built guest
+    fn path(self, name: &str) -> PathBuf {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        match self {
+            Toolchain::Risc0 => root.join("target/riscv").join(format!("{}.elf", name)),
+            Toolchain::Sp1 => root.join("target/sp1").join(format!("{}.elf", name)),
+        }
+    }
+}
+
+/// Load a guest ELF, or `None` if it has not been built
+pub fn guest(toolchain: Toolchain, name: &str) -> Option<Vec<u8>> {
+    let path = toolchain.path(name);
+    match std::fs::read(&path) {
+        Ok(elf) if elf.starts_with(ELF_MAGIC) => Some(elf),
+        _ => {
+            let reason = format!(
+                "{} is missing or a placeholder; install `{}` and rebuild",
+                path.display(),
+                toolchain.command()
+            );
+            if std::env::var_os("FROSTGATE_REQUIRE_GUESTS").is_some() {
+                panic!("{}", reason);
+            }
+            eprintln!("skipping: {}", reason);
+            None
+        }
+    }
+}
+
+/// Load a guest ELF or return from the test if it has not been built
+macro_rules! require_guest {
+    ($toolchain:expr, $name:expr) => {
+        match $crate::harness::guest($toolchain, $name) {
+            Some(elf) => elf,
+            None => return,
+        }
+    };
+}
+
+pub(crate) use require_guest;
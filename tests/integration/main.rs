@@ -0,0 +1,168 @@
+//! End-to-end prove/verify tests against the real guest programs
+//!
+//! Run with `cargo test --features integration-tests --test integration`. Tests whose
+//! guest has not been built are skipped, see [`harness`].
+
+mod harness;
+
+use std::sync::Arc;
+use frostgate_circuits::chains::eth::keccak256;
+use frostgate_circuits::codec::rlp::{self, RlpItem};
+use frostgate_circuits::crosscheck::golden_vectors;
+use frostgate_circuits::risc0::{BlockVerifyCircuit, EcdsaVerifyCircuit, EthHeaderCircuit, TxVerifyCircuit};
+use frostgate_circuits::{
+    ChainId, CrossCheckPolicy, DomainSeparator, DualProver, EcdsaWitness, HashFunction, ProgramDescriptor, ProveOptions,
+    Risc0Backend, Risc0Config, Sp1Backend, ZkBackend,
+};
+use harness::{require_guest, Toolchain};
+use sha2::{Digest, Sha256};
+
+/// Program proving knowledge of a message with the given hash
+fn message_program(message: &[u8]) -> Vec<u8> {
+    let mut program = vec![0x01];
+    program.extend_from_slice(&Sha256::digest(message));
+    program
+}
+
+/// SHA-256 of the canonical JSON encoding the guests hash
+fn canonical_hash(json: &[u8]) -> [u8; 32] {
+    let canonical = frostgate_circuits::codec::json::canonicalize(json).unwrap();
+    Sha256::digest(&canonical).into()
+}
+
+#[tokio::test]
+async fn risc0_message_verify() {
+    require_guest!(Toolchain::Risc0, "message_verify");
+    let backend = Risc0Backend::new(Risc0Config::default());
+
+    let message = b"Hello, World!";
+    let program = message_program(message);
+    let (proof, _) = ZkBackend::prove(&backend, &program, message, None).await.unwrap();
+    assert!(ZkBackend::verify(&backend, &program, &proof, None).await.unwrap());
+
+    // A proof does not verify for a different message
+    let other = message_program(b"Goodbye, World!");
+    assert!(!ZkBackend::verify(&backend, &other, &proof, None).await.unwrap_or(false));
+}
+
+#[tokio::test]
+async fn risc0_message_verify_keccak() {
+    require_guest!(Toolchain::Risc0, "message_verify");
+    let backend = Risc0Backend::new(Risc0Config::default());
+
+    let message = b"Hello, World!";
+    let hash = keccak256(message);
+    let program = ProgramDescriptor::message_verify(hash, HashFunction::Keccak256).unwrap().encode();
+    let (proof, _) = ZkBackend::prove(&backend, &program, message, None).await.unwrap();
+    assert!(ZkBackend::verify(&backend, &program, &proof, None).await.unwrap());
+
+    // The SHA-256 program for the same hash does not accept the message
+    let sha256 = ProgramDescriptor::message_verify(hash, HashFunction::Sha256).unwrap().encode();
+    assert!(ZkBackend::prove(&backend, &sha256, message, None).await.is_err());
+}
+
+#[tokio::test]
+async fn risc0_message_verify_domain_binding() {
+    require_guest!(Toolchain::Risc0, "message_verify");
+    let backend = Risc0Backend::new(Risc0Config::default());
+
+    let message = b"Hello, World!";
+    let program = message_program(message);
+    let domain = DomainSeparator::new(ChainId(1), ChainId(137));
+    let options = ProveOptions::default().with_domain(domain);
+    let (proof, _) = backend.prove_with_options(&program, message, &options).await.unwrap();
+
+    assert!(backend.verify_with_domain(&program, &proof, domain).await.unwrap());
+    let reversed = DomainSeparator::new(ChainId(137), ChainId(1));
+    assert!(!backend.verify_with_domain(&program, &proof, reversed).await.unwrap_or(false));
+}
+
+#[tokio::test]
+async fn risc0_tx_verify() {
+    let elf = require_guest!(Toolchain::Risc0, "tx_verify");
+    let backend = Risc0Backend::new(Risc0Config::default());
+
+    let tx = br#"{"from":"0x123","to":"0x456","value":"100"}"#.to_vec();
+    let circuit = TxVerifyCircuit::new(tx.clone(), canonical_hash(&tx), elf);
+    let proof = backend.prove(&circuit).await.unwrap();
+    assert!(backend.verify(&circuit, &proof).await.unwrap());
+}
+
+#[tokio::test]
+async fn risc0_block_verify() {
+    let elf = require_guest!(Toolchain::Risc0, "block_verify");
+    let backend = Risc0Backend::new(Risc0Config::default());
+
+    let header = serde_json::to_vec(&serde_json::json!({
+        "parent_hash": "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef",
+        "state_root": "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890",
+        "transactions_root": "0x9876543210fedcba9876543210fedcba9876543210fedcba9876543210fedcba",
+        "receipts_root": "0xfedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210",
+        "number": "0x1234",
+        "timestamp": "0x61c8d240",
+        "gas_used": "0x1234567",
+        "gas_limit": "0x2345678",
+        "extra_data": []
+    }))
+    .unwrap();
+    let circuit = BlockVerifyCircuit::new(header.clone(), canonical_hash(&header), 0x1234, elf);
+    let proof = backend.prove(&circuit).await.unwrap();
+    assert!(backend.verify(&circuit, &proof).await.unwrap());
+}
+
+#[tokio::test]
+async fn risc0_eth_header_verify() {
+    let elf = require_guest!(Toolchain::Risc0, "eth_header_verify");
+    let backend = Risc0Backend::new(Risc0Config::default());
+
+    let mut fields = vec![RlpItem::Bytes(vec![0x11; 32]); 16];
+    fields[8] = RlpItem::uint(19_000_000);
+    fields[9] = RlpItem::uint(30_000_000);
+    fields[10] = RlpItem::uint(12_000_000);
+    fields[11] = RlpItem::uint(1_700_000_000);
+    let header = rlp::encode(&RlpItem::List(fields));
+    let circuit = EthHeaderCircuit::new(header.clone(), keccak256(&header), 19_000_000, elf).unwrap();
+    let proof = backend.prove(&circuit).await.unwrap();
+    assert!(backend.verify(&circuit, &proof).await.unwrap());
+}
+
+#[tokio::test]
+async fn risc0_ecdsa_verify() {
+    let elf = require_guest!(Toolchain::Risc0, "ecdsa_verify");
+    let backend = Risc0Backend::new(Risc0Config::default());
+
+    let key = k256::ecdsa::SigningKey::from_slice(&[0x22; 32]).unwrap();
+    let message_hash: [u8; 32] = Sha256::digest(b"Hello, World!").into();
+    let (signature, _) = key.sign_prehash_recoverable(&message_hash).unwrap();
+    let witness = EcdsaWitness {
+        message_hash,
+        public_key: key.verifying_key().to_encoded_point(true).as_bytes().to_vec(),
+        signature: signature.to_vec(),
+    };
+    let circuit = EcdsaVerifyCircuit::new(witness, message_hash, elf).unwrap();
+    let proof = backend.prove(&circuit).await.unwrap();
+    assert!(backend.verify(&circuit, &proof).await.unwrap());
+}
+
+#[tokio::test]
+async fn differential_golden_vectors() {
+    require_guest!(Toolchain::Risc0, "message_verify");
+    require_guest!(Toolchain::Sp1, "message_verifier");
+    let prover = DualProver::new(
+        Arc::new(Sp1Backend::new()),
+        Arc::new(Risc0Backend::new(Risc0Config::default())),
+        CrossCheckPolicy::default(),
+    );
+    let report = prover.differential(&golden_vectors()).await;
+    assert_eq!(report.results.len(), golden_vectors().len());
+    assert!(report.is_consistent(), "backends diverged: {:?}", report.divergences());
+}
+
+#[test]
+fn sp1_guest_programs() {
+    let backend = Sp1Backend::new();
+    for name in ["proof_aggregate", "risc0_bridge", "eth_verifier", "eth_log_verifier", "eth_header_chain", "beacon_verifier", "dot_verifier", "sol_verifier", "tm_verifier", "state_proof", "eip712_verifier", "ecdsa_verifier", "message_verifier", "poseidon_commit", "message_event", "merkle_inclusion"] {
+        let elf = require_guest!(Toolchain::Sp1, name);
+        assert!(backend.export_verifying_key(&elf).is_ok(), "{} has no verifying key", name);
+    }
+}